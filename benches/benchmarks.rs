@@ -8,7 +8,8 @@
 //! - SDI extraction (find_sdi_pages + extract_sdi_from_pages)
 
 use byteorder::{BigEndian, ByteOrder};
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::hint::black_box;
 
 use idb::innodb::checksum::validate_checksum;
 use idb::innodb::constants::*;
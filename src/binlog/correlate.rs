@@ -377,7 +377,12 @@ mod tests {
             is_unsigned: true,
             fixed_len: 4,
             is_variable: false,
+            max_len: 4,
+            version_added: 0,
+            version_dropped: 0,
+            instant_default: None,
             charset_max_bytes: 0,
+            collation_id: 0,
             datetime_precision: 0,
             is_system_column: false,
             elements: vec![],
@@ -426,7 +431,12 @@ mod tests {
             is_unsigned: true,
             fixed_len: 4,
             is_variable: false,
+            max_len: 4,
+            version_added: 0,
+            version_dropped: 0,
+            instant_default: None,
             charset_max_bytes: 0,
+            collation_id: 0,
             datetime_precision: 0,
             is_system_column: false,
             elements: vec![],
@@ -247,12 +247,22 @@ pub enum Commands {
     /// hex-only output.
     ///
     /// Supported types: integers (TINYINT–BIGINT), FLOAT, DOUBLE,
-    /// DATE, DATETIME, TIMESTAMP, YEAR, VARCHAR, CHAR. Unsupported
-    /// types (DECIMAL, BLOB, JSON, etc.) are exported as hex strings.
+    /// DECIMAL, DATE, DATETIME, TIMESTAMP, YEAR, VARCHAR, CHAR, and JSON
+    /// (decoded from MySQL's binary format). Unsupported types (BLOB,
+    /// GEOMETRY, etc.) are exported as hex strings.
+    ///
+    /// Columns stored off-page (long BLOB/TEXT/VARCHAR values) are
+    /// reassembled from their overflow page chain, up to `--max-lob-size`
+    /// bytes per value.
     ///
     /// Use `--where-delete-mark` to include only delete-marked records
     /// (useful for forensic recovery). Use `--system-columns` to include
     /// DB_TRX_ID and DB_ROLL_PTR in the output.
+    ///
+    /// Use `--index <name>` to export a secondary index instead of the
+    /// clustered index: its leaf records yield the key columns followed by
+    /// the primary key, and are often the last intact copy of that data
+    /// when the clustered index is damaged.
     Export {
         /// Path to InnoDB data file (.ibd)
         #[arg(short, long)]
@@ -285,6 +295,14 @@ pub enum Commands {
         /// Path to MySQL keyring file for decrypting encrypted tablespaces
         #[arg(long)]
         keyring: Option<String>,
+
+        /// Maximum size in bytes of a reassembled off-page (BLOB/TEXT) value
+        #[arg(long = "max-lob-size", default_value = "16777216")]
+        max_lob_size: usize,
+
+        /// Export the named index (e.g. a secondary index) instead of PRIMARY
+        #[arg(long)]
+        index: Option<String>,
     },
 
     /// Search for pages across data directory
@@ -441,9 +459,13 @@ pub enum Commands {
     /// checksum status. With `--verbose`, MLOG record types within each
     /// data block are decoded and summarized. Use `--blocks N` to limit
     /// output to the first N data blocks, or `--no-empty` to skip blocks
-    /// that contain no redo data.
+    /// that contain no redo data. Given a directory (a data directory or
+    /// `#innodb_redo`), all redo files are read as one LSN stream: the
+    /// files, LSN gaps, and overlaps are listed and blocks are shown in
+    /// LSN order.
     Log {
-        /// Path to redo log file (ib_logfile0, ib_logfile1, or #ib_redo*)
+        /// Path to redo log file (ib_logfile0, ib_logfile1, or #ib_redo*), or a
+        /// directory holding the whole redo log
         #[arg(short, long)]
         file: String,
 
@@ -462,12 +484,19 @@ pub enum Commands {
         /// Output in JSON format
         #[arg(long)]
         json: bool,
+
+        /// Path to MySQL keyring file for decrypting encrypted redo logs
+        #[arg(long)]
+        keyring: Option<String>,
     },
 
     /// Show InnoDB file and system information
     ///
     /// Operates in three modes. **`--ibdata`** reads the `ibdata1` page 0
-    /// FIL header and redo log checkpoint LSNs. **`--lsn-check`** compares
+    /// FIL header, redo log checkpoint LSNs, and pending change buffer
+    /// entries; a system tablespace split over several files is opened
+    /// from `innodb_data_file_path` in `--defaults-file`, or as `ibdata1`,
+    /// `ibdata2`, ... in the data directory. **`--lsn-check`** compares
     /// the `ibdata1` header LSN with the latest redo log checkpoint LSN to
     /// detect whether the system tablespace and redo log are in sync (useful
     /// for diagnosing crash-recovery state). **`-D`/`-t`** queries a live
@@ -511,7 +540,8 @@ pub enum Commands {
         #[arg(long)]
         password: Option<String>,
 
-        /// Path to MySQL defaults file (.my.cnf)
+        /// Path to MySQL defaults file (.my.cnf); with --ibdata, read for
+        /// innodb_data_file_path and innodb_data_home_dir
         #[arg(long = "defaults-file")]
         defaults_file: Option<String>,
 
@@ -805,6 +835,53 @@ pub enum Commands {
         keyring: Option<String>,
     },
 
+    /// List doublewrite buffer copies and restore torn pages from them
+    ///
+    /// Reads page copies from the legacy doublewrite area of a system
+    /// tablespace (ibdata1 pages 64-191, the checked file itself or
+    /// `--ibdata`) and from MySQL 8.0.20+
+    /// `#ib_<page_size>_<n>.dblwr` files, showing each copy's space ID, page
+    /// number, LSN, and checksum status. Pages of the tablespace that are
+    /// zero-filled, torn, or fail their checksum are matched against the
+    /// newest intact copy that is not older than the page.
+    ///
+    /// Without `--restore` the restorable pages are only reported. With
+    /// `--restore` the copies are written into the tablespace, after creating
+    /// a backup unless `--no-backup` is given.
+    Doublewrite {
+        /// Path to the tablespace to check (an .ibd file or ibdata1)
+        #[arg(short, long)]
+        file: String,
+
+        /// Doublewrite files to read (#ib_<page_size>_<n>.dblwr, comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        dblwr: Vec<String>,
+
+        /// System tablespace (ibdata1) whose legacy doublewrite area holds copies
+        #[arg(long)]
+        ibdata: Option<String>,
+
+        /// Write intact doublewrite copies over damaged pages
+        #[arg(long)]
+        restore: bool,
+
+        /// Skip creating a backup before restoring
+        #[arg(long)]
+        no_backup: bool,
+
+        /// List copies of pages from all tablespaces, not only this one
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+
+        /// Override page size (default: auto-detect)
+        #[arg(long = "page-size")]
+        page_size: Option<u32>,
+    },
+
     /// Per-index B+Tree health metrics
     ///
     /// Scans all INDEX pages in a tablespace and computes per-index health
@@ -1014,6 +1091,89 @@ pub enum Commands {
         keyring: Option<String>,
     },
 
+    /// Rebuild a secondary index offline from the clustered index
+    ///
+    /// Decodes every live clustered index row, builds the named secondary
+    /// index's entries, sorts them by the index key, and writes new leaf and
+    /// non-leaf pages into the pages the index's file segments already own,
+    /// keeping the root page. Unneeded segment pages are emptied, checksums
+    /// are recalculated, and the result is written to a new output file that
+    /// can be imported with `ALTER TABLE ... IMPORT TABLESPACE`. The source
+    /// file is never modified.
+    RebuildIndex {
+        /// Path to source InnoDB data file (.ibd)
+        #[arg(short, long)]
+        file: String,
+
+        /// Name of the secondary index to rebuild
+        #[arg(short, long)]
+        index: String,
+
+        /// Path to output file (required — always writes a new file)
+        #[arg(short, long)]
+        output: String,
+
+        /// Show per-page details
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+
+        /// Override page size (default: auto-detect)
+        #[arg(long = "page-size")]
+        page_size: Option<u32>,
+    },
+
+    /// Roll tablespaces forward by applying the redo log offline
+    ///
+    /// Decodes the redo log of a data directory (or the files given with
+    /// `--redo`) and replays every page-level record logged after the last
+    /// checkpoint, skipping records a page already contains (page LSN at or
+    /// past the record's mini-transaction). Stops at the end of the log or at
+    /// `--to-lsn`. Each tablespace that changes is copied into `--output-dir`
+    /// with the rolled-forward pages written and checksummed; source files
+    /// are never modified. ROW_FORMAT=COMPRESSED and encrypted tablespaces
+    /// are skipped.
+    RedoApply {
+        /// Path to MySQL data directory (tablespaces and redo log)
+        #[arg(short, long, required_unless_present = "file")]
+        datadir: Option<String>,
+
+        /// Tablespace file to roll forward (repeatable; instead of the datadir's)
+        #[arg(short, long)]
+        file: Vec<String>,
+
+        /// Redo log file (repeatable; default: the datadir's redo log)
+        #[arg(long)]
+        redo: Vec<String>,
+
+        /// Directory to write the rolled-forward tablespace copies to
+        #[arg(long = "output-dir")]
+        output_dir: String,
+
+        /// Replay only mini-transactions that end at or before this LSN
+        #[arg(long = "to-lsn")]
+        to_lsn: Option<u64>,
+
+        /// Show per-page details
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+
+        /// Override page size (default: auto-detect)
+        #[arg(long = "page-size")]
+        page_size: Option<u32>,
+
+        /// Maximum directory recursion depth (default: 2, 0 = unlimited)
+        #[arg(long)]
+        depth: Option<u32>,
+    },
+
     /// Cross-validate tablespace files against live MySQL metadata
     ///
     /// Scans a data directory for .ibd files and compares their space IDs
@@ -1077,6 +1237,12 @@ pub enum Commands {
     /// valid checksums. Checks page number sequence, space ID consistency,
     /// LSN monotonicity, B+Tree level validity, page chain bounds, and
     /// trailer LSN matching. Exits with code 1 if any check fails.
+    ///
+    /// With `--spatial`, also decodes every row's GEOMETRY columns and checks
+    /// that each geometry lies within the MBR recorded for it in the spatial
+    /// (R-tree) index leaf pages. With `--indexes`, decodes every secondary
+    /// index leaf entry, looks up its row in the clustered index, and reports
+    /// orphan entries and rows with a missing or duplicate entry.
     Verify {
         /// Path to InnoDB data file (.ibd)
         #[arg(short, long)]
@@ -1094,11 +1260,13 @@ pub enum Commands {
         #[arg(long = "page-size")]
         page_size: Option<u32>,
 
-        /// Path to MySQL keyring file for decrypting encrypted tablespaces
+        /// Path to MySQL keyring file for decrypting encrypted tablespaces and
+        /// redo logs
         #[arg(long)]
         keyring: Option<String>,
 
-        /// Path to redo log file to verify LSN continuity against the tablespace
+        /// Path to redo log file, or a directory holding the whole redo log, to
+        /// verify LSN continuity against the tablespace
         #[arg(long)]
         redo: Option<String>,
 
@@ -1109,6 +1277,14 @@ pub enum Commands {
         /// Path to XtraBackup checkpoint file to cross-reference LSNs
         #[arg(long = "backup-meta")]
         backup_meta: Option<String>,
+
+        /// Cross-check row geometries against spatial index MBRs
+        #[arg(long)]
+        spatial: bool,
+
+        /// Cross-check secondary index entries against the clustered index
+        #[arg(long)]
+        indexes: bool,
     },
 
     /// Parse and analyze MySQL binary log files
@@ -1232,6 +1408,10 @@ pub enum Commands {
         /// Path to MySQL keyring file for decrypting encrypted tablespaces
         #[arg(long)]
         keyring: Option<String>,
+
+        /// Maximum size in bytes of a reassembled off-page (BLOB/TEXT) value
+        #[arg(long = "max-lob-size", default_value = "16777216")]
+        max_lob_size: usize,
     },
 
     /// Verify data deletion and scan for residue (GDPR / forensic)
@@ -1352,7 +1532,8 @@ pub enum Commands {
     /// binary log row events into a single chronological view of what happened
     /// to each page and table.  Requires at least one log source.
     Timeline {
-        /// Path to InnoDB redo log file (ib_logfile0 or #ib_redo*)
+        /// Path to InnoDB redo log file (ib_logfile0 or #ib_redo*), or a directory
+        /// holding the whole redo log (data directory or #innodb_redo)
         #[arg(long)]
         redo_log: Option<String>,
 
@@ -1400,7 +1581,8 @@ pub enum Commands {
         #[arg(long = "page-size")]
         page_size: Option<u32>,
 
-        /// Path to MySQL keyring file for decrypting encrypted tablespaces
+        /// Path to MySQL keyring file for decrypting encrypted tablespaces and
+        /// redo logs
         #[arg(long)]
         keyring: Option<String>,
     },
@@ -0,0 +1,365 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::cli::wprintln;
+use crate::innodb::checksum::{validate_checksum, validate_lsn};
+use crate::innodb::doublewrite::{self, DblwrPage, PageDamage};
+use crate::innodb::page::FilHeader;
+use crate::innodb::write;
+use crate::util::audit::AuditLogger;
+use crate::IdbError;
+
+/// Options for the `inno doublewrite` subcommand.
+pub struct DoublewriteOptions {
+    /// Path to the tablespace whose pages are checked (or ibdata1).
+    pub file: String,
+    /// Paths to `#ib_<page_size>_<n>.dblwr` files.
+    pub dblwr: Vec<String>,
+    /// System tablespace whose legacy doublewrite area holds copies of
+    /// `file`'s pages (pre-8.0.20 servers).
+    pub ibdata: Option<String>,
+    /// Write the doublewrite copies over the damaged pages.
+    pub restore: bool,
+    /// Skip creating a backup of the tablespace before restoring.
+    pub no_backup: bool,
+    /// List every buffered copy, not only those for this tablespace.
+    pub verbose: bool,
+    /// Emit output as JSON.
+    pub json: bool,
+    /// Override the auto-detected page size.
+    pub page_size: Option<u32>,
+    /// Use memory-mapped I/O for file access.
+    pub mmap: bool,
+    /// Audit logger for recording write operations.
+    pub audit_logger: Option<Arc<AuditLogger>>,
+}
+
+#[derive(Serialize)]
+struct DoublewriteReport {
+    file: String,
+    space_id: u32,
+    page_size: u32,
+    sources: Vec<SourceReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backup_path: Option<String>,
+    restored: u64,
+    pages: Vec<PageRestoreInfo>,
+}
+
+#[derive(Serialize)]
+struct SourceReport {
+    source: String,
+    page_size: u32,
+    copies: Vec<DblwrPage>,
+}
+
+#[derive(Serialize)]
+struct PageRestoreInfo {
+    page_number: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    damage: Option<PageDamage>,
+    action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    copy_source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    copy_slot: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    copy_lsn: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_checksum_valid: Option<bool>,
+}
+
+/// List doublewrite buffer copies and restore torn pages from them.
+///
+/// Copies come from the legacy doublewrite area when `file` is a system
+/// tablespace, from the legacy area of the `--ibdata` system tablespace, and
+/// from every `--dblwr` file. Only copies carrying `file`'s space ID are
+/// considered. For each page of the tablespace
+/// that has a copy, the page is checked for damage (zero-filled, torn, or a
+/// checksum mismatch). A damaged page is restorable from the newest intact
+/// copy whose LSN is not older than the page. With `--restore` those copies
+/// are written into the tablespace, after a backup unless `--no-backup`.
+pub fn execute(opts: &DoublewriteOptions, writer: &mut dyn Write) -> Result<(), IdbError> {
+    let mut ts = crate::cli::open_tablespace(&opts.file, opts.page_size, opts.mmap)?;
+    let page_size = ts.page_size();
+    let page_count = ts.page_count();
+    let vendor = ts.vendor_info().clone();
+    let space_id = ts.fsp_header().map(|f| f.space_id).unwrap_or(0);
+
+    let mut sources: Vec<SourceReport> = Vec::new();
+    if let Some((_, copies)) = doublewrite::read_legacy_doublewrite(&mut ts)? {
+        sources.push(SourceReport {
+            source: format!("{} (legacy doublewrite area)", opts.file),
+            page_size,
+            copies,
+        });
+    }
+    if let Some(ref ibdata) = opts.ibdata {
+        let mut sys = crate::cli::open_tablespace(ibdata, None, opts.mmap)?;
+        let sys_page_size = sys.page_size();
+        match doublewrite::read_legacy_doublewrite(&mut sys)? {
+            Some((_, copies)) => sources.push(SourceReport {
+                source: format!("{} (legacy doublewrite area)", ibdata),
+                page_size: sys_page_size,
+                copies,
+            }),
+            None => {
+                return Err(IdbError::Argument(format!(
+                    "{} has no legacy doublewrite area",
+                    ibdata
+                )))
+            }
+        }
+    }
+    for path in &opts.dblwr {
+        let data = std::fs::read(path)
+            .map_err(|e| IdbError::Io(format!("Cannot read {}: {}", path, e)))?;
+        let dblwr_page_size = doublewrite::parse_dblwr_file_name(path)
+            .map(|(ps, _)| ps)
+            .unwrap_or(page_size);
+        sources.push(SourceReport {
+            source: path.clone(),
+            page_size: dblwr_page_size,
+            copies: doublewrite::parse_doublewrite_pages(&data, dblwr_page_size, 0, Some(&vendor)),
+        });
+    }
+    if sources.is_empty() {
+        return Err(IdbError::Argument(format!(
+            "{} has no legacy doublewrite area. Use --ibdata to read the system \
+             tablespace or --dblwr to specify #ib_*.dblwr files.",
+            opts.file
+        )));
+    }
+
+    // Copies belonging to this tablespace, trimmed to its physical page size
+    // (compressed pages occupy the start of a full-size slot)
+    let mut own: Vec<(usize, DblwrPage)> = Vec::new();
+    for (src_idx, src) in sources.iter().enumerate() {
+        for c in src.copies.iter().filter(|c| c.space_id == space_id) {
+            if c.data.len() < page_size as usize {
+                continue;
+            }
+            let mut copy = c.clone();
+            copy.data.truncate(page_size as usize);
+            copy.checksum_valid = validate_checksum(&copy.data, page_size, Some(&vendor)).valid;
+            copy.lsn_valid = validate_lsn(&copy.data, page_size);
+            own.push((src_idx, copy));
+        }
+    }
+    let mut page_numbers: Vec<u32> = own.iter().map(|(_, c)| c.page_no).collect();
+    page_numbers.sort_unstable();
+    page_numbers.dedup();
+
+    if !opts.json {
+        print_sources(&sources, space_id, opts.verbose, writer)?;
+    }
+
+    let backup_path = if opts.restore && !opts.no_backup {
+        let path = write::create_backup(&opts.file)?;
+        if !opts.json {
+            wprintln!(writer, "Backup created: {}", path.display())?;
+        }
+        if let Some(ref logger) = opts.audit_logger {
+            let _ = logger.log_backup(&opts.file, &path.display().to_string());
+        }
+        Some(path)
+    } else {
+        None
+    };
+
+    let mut restored = 0u64;
+    let mut page_details: Vec<PageRestoreInfo> = Vec::new();
+    for page_no in page_numbers {
+        let page_num = u64::from(page_no);
+        let mut info = PageRestoreInfo {
+            page_number: page_num,
+            damage: None,
+            action: "skipped".to_string(),
+            reason: None,
+            copy_source: None,
+            copy_slot: None,
+            copy_lsn: None,
+            post_checksum_valid: None,
+        };
+
+        if page_num >= page_count {
+            info.reason = Some(format!("Out of range ({} pages)", page_count));
+            page_details.push(info);
+            continue;
+        }
+        let current = write::read_page_raw(&opts.file, page_num, page_size)?;
+        info.damage = doublewrite::page_damage(&current, page_size, Some(&vendor));
+        let page_lsn = match info.damage {
+            None => {
+                info.action = "intact".to_string();
+                page_details.push(info);
+                continue;
+            }
+            Some(PageDamage::Empty) => 0,
+            Some(_) => FilHeader::parse(&current).map(|h| h.lsn).unwrap_or(0),
+        };
+
+        let copy =
+            doublewrite::find_restore_copy(own.iter().map(|(_, c)| c), space_id, page_no, page_lsn);
+        let copy = match copy {
+            Some(c) => c,
+            None => {
+                info.reason = Some("No intact copy at or after the page LSN".to_string());
+                page_details.push(info);
+                continue;
+            }
+        };
+        let src_idx = own
+            .iter()
+            .find(|(_, c)| std::ptr::eq(c, copy))
+            .map(|(i, _)| *i)
+            .unwrap_or(0);
+        info.copy_source = Some(sources[src_idx].source.clone());
+        info.copy_slot = Some(copy.slot);
+        info.copy_lsn = Some(copy.lsn);
+
+        if opts.restore {
+            write::write_page(&opts.file, page_num, page_size, &copy.data)?;
+            if let Some(ref logger) = opts.audit_logger {
+                let _ =
+                    logger.log_page_write(&opts.file, page_num, "doublewrite_restore", None, None);
+            }
+            let written = write::read_page_raw(&opts.file, page_num, page_size)?;
+            info.post_checksum_valid =
+                Some(validate_checksum(&written, page_size, Some(&vendor)).valid);
+            info.action = "restored".to_string();
+        } else {
+            info.action = "would_restore".to_string();
+        }
+        restored += 1;
+        page_details.push(info);
+    }
+
+    if opts.json {
+        let report = DoublewriteReport {
+            file: opts.file.clone(),
+            space_id,
+            page_size,
+            sources,
+            backup_path: backup_path.map(|p| p.display().to_string()),
+            restored,
+            pages: page_details,
+        };
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| IdbError::Parse(format!("JSON serialization error: {}", e)))?;
+        wprintln!(writer, "{}", json)?;
+        return Ok(());
+    }
+
+    wprintln!(writer)?;
+    wprintln!(
+        writer,
+        "Pages of space {} with doublewrite copies:",
+        space_id
+    )?;
+    for info in &page_details {
+        let status = match info.action.as_str() {
+            "intact" => "intact".green().to_string(),
+            "restored" => "restored".green().to_string(),
+            "would_restore" => "restorable".yellow().to_string(),
+            _ => "not restorable".red().to_string(),
+        };
+        let damage = info
+            .damage
+            .map(|d| format!(" [{}]", d.name()))
+            .unwrap_or_default();
+        let detail = match (&info.copy_source, info.copy_slot, info.copy_lsn) {
+            (Some(src), Some(slot), Some(lsn)) => {
+                format!(" from {} slot {} (LSN {})", src, slot, lsn)
+            }
+            _ => info
+                .reason
+                .as_ref()
+                .map(|r| format!(" ({})", r))
+                .unwrap_or_default(),
+        };
+        let post = match info.post_checksum_valid {
+            Some(true) => format!(" (post-validate: {})", "OK".green()),
+            Some(false) => format!(" (post-validate: {})", "CHECKSUM INVALID".red()),
+            None => String::new(),
+        };
+        wprintln!(
+            writer,
+            "  Page {:>6}: {}{}{}{}",
+            info.page_number,
+            status,
+            damage,
+            detail,
+            post
+        )?;
+    }
+
+    wprintln!(writer)?;
+    wprintln!(writer, "Doublewrite Summary:")?;
+    let label = if opts.restore {
+        "Restored"
+    } else {
+        "Restorable"
+    };
+    if restored > 0 {
+        wprintln!(writer, "  {}: {}", label, format!("{}", restored).green())?;
+        if !opts.restore {
+            wprintln!(writer, "  Run with --restore to write these pages.")?;
+        }
+    } else {
+        wprintln!(writer, "  {}: 0", label)?;
+    }
+
+    Ok(())
+}
+
+/// Print the copies held by each doublewrite source.
+///
+/// Without `verbose`, only copies of pages in `space_id` are listed.
+fn print_sources(
+    sources: &[SourceReport],
+    space_id: u32,
+    verbose: bool,
+    writer: &mut dyn Write,
+) -> Result<(), IdbError> {
+    for src in sources {
+        let shown: Vec<&DblwrPage> = src
+            .copies
+            .iter()
+            .filter(|c| verbose || c.space_id == space_id)
+            .collect();
+        wprintln!(
+            writer,
+            "=== {} ({} copies, {} for space {})",
+            src.source,
+            src.copies.len(),
+            src.copies.iter().filter(|c| c.space_id == space_id).count(),
+            space_id
+        )?;
+        for c in shown {
+            let status = if c.is_valid() {
+                "OK".green().to_string()
+            } else if !c.checksum_valid {
+                "CHECKSUM INVALID".red().to_string()
+            } else {
+                "TORN".red().to_string()
+            };
+            wprintln!(
+                writer,
+                "  Slot {:>6}  Space {:>6}  Page {:>8}  LSN {:>14}  {:<12} {}",
+                c.slot,
+                c.space_id,
+                c.page_no,
+                c.lsn,
+                c.page_type.name(),
+                status
+            )?;
+        }
+    }
+    Ok(())
+}
@@ -58,6 +58,8 @@ pub fn execute(opts: &DumpOptions, writer: &mut dyn Write) -> Result<(), IdbErro
 
     // Page mode: dump a specific page (or page 0 by default)
     let mut ts = crate::cli::open_tablespace(&opts.file, opts.page_size, opts.mmap)?;
+    // Dump the page bytes as stored at their file offsets
+    ts.set_page_decompression(false);
 
     if opts.decrypt {
         if let Some(ref keyring_path) = opts.keyring {
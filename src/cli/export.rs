@@ -1,18 +1,21 @@
 //! CLI implementation for the `inno export` subcommand.
 //!
-//! Extracts user records from clustered index leaf pages and outputs them
-//! as CSV, JSON, or raw hex. Uses SDI metadata for typed field decoding
-//! when available.
+//! Extracts user records from clustered index leaf pages (or, with
+//! `--index`, from a secondary index) and outputs them as CSV, JSON, or raw
+//! hex. Uses SDI metadata for typed field decoding when available.
 
 use std::io::Write;
 
 use crate::cli::wprintln;
-use crate::innodb::export::{csv_escape, decode_page_records, extract_column_layout};
+use crate::innodb::export::{
+    csv_escape, decode_page_records_with_lobs, extract_column_layout, extract_index_layouts,
+};
 use crate::innodb::field_decode::{ColumnStorageInfo, FieldValue};
 use crate::innodb::index::IndexHeader;
 use crate::innodb::page::FilHeader;
 use crate::innodb::page_types::PageType;
-use crate::innodb::record::walk_compact_records;
+use crate::innodb::record::walk_records;
+use crate::innodb::tablespace::Tablespace;
 use crate::IdbError;
 
 /// Output format for exported records.
@@ -57,6 +60,10 @@ pub struct ExportOptions {
     pub keyring: Option<String>,
     /// Use memory-mapped I/O for file access.
     pub mmap: bool,
+    /// Maximum size in bytes of a reassembled off-page column value.
+    pub max_lob_size: usize,
+    /// Export this index (by name) instead of the clustered index.
+    pub index: Option<String>,
 }
 
 /// Export records from a tablespace.
@@ -69,10 +76,20 @@ pub fn execute(opts: &ExportOptions, writer: &mut dyn Write) -> Result<(), IdbEr
         crate::cli::setup_decryption(&mut ts, keyring_path)?;
     }
 
-    let page_size = ts.page_size();
-
     // Try SDI extraction for typed decoding
-    let column_layout = extract_column_layout(&mut ts);
+    let column_layout = match opts.index {
+        Some(ref name) => {
+            let layouts = extract_index_layouts(&mut ts).ok_or_else(|| {
+                IdbError::Argument("--index requires SDI metadata (MySQL 8.0+)".to_string())
+            })?;
+            let layout = layouts
+                .into_iter()
+                .find(|l| l.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| IdbError::Argument(format!("Index '{}' not found in SDI", name)))?;
+            Some((layout.columns, layout.index_id))
+        }
+        None => extract_column_layout(&mut ts),
+    };
     let (columns, clustered_index_id) = match column_layout {
         Some((cols, idx_id)) => (Some(cols), Some(idx_id)),
         None => {
@@ -85,18 +102,25 @@ pub fn execute(opts: &ExportOptions, writer: &mut dyn Write) -> Result<(), IdbEr
 
     let use_hex = columns.is_none() || format == ExportFormat::Hex;
 
-    // Determine which index_id to export (clustered/PRIMARY)
+    // Determine which index_id to export (clustered/PRIMARY, or --index)
     // If we don't have SDI, we'll export all leaf INDEX pages
     let target_index_id = clustered_index_id;
 
     // Collect pages to process
     let mut pages_data: Vec<(u64, Vec<u8>)> = Vec::new();
-    ts.for_each_page(|page_num, data| {
+    ts.for_each_page_uncompressed(|page_num, data| {
         if let Some(specific_page) = opts.page {
             if page_num != specific_page {
                 return Ok(());
             }
         }
+        let data = match data {
+            Ok(d) => d,
+            Err(e) => {
+                eprintln!("Warning: Skipping page {}: {}", page_num, e);
+                return Ok(());
+            }
+        };
         let fil = match FilHeader::parse(data) {
             Some(h) => h,
             None => return Ok(()),
@@ -127,8 +151,8 @@ pub fn execute(opts: &ExportOptions, writer: &mut dyn Write) -> Result<(), IdbEr
     } else {
         let cols = columns.as_ref().unwrap();
         match format {
-            ExportFormat::Csv => output_csv(writer, &pages_data, cols, opts, page_size)?,
-            ExportFormat::Json => output_json(writer, &pages_data, cols, opts, page_size)?,
+            ExportFormat::Csv => output_csv(writer, &mut ts, &pages_data, cols, opts)?,
+            ExportFormat::Json => output_json(writer, &mut ts, &pages_data, cols, opts)?,
             ExportFormat::Hex => unreachable!(),
         }
     }
@@ -139,10 +163,10 @@ pub fn execute(opts: &ExportOptions, writer: &mut dyn Write) -> Result<(), IdbEr
 /// Output records as CSV.
 fn output_csv(
     writer: &mut dyn Write,
+    ts: &mut Tablespace,
     pages: &[(u64, Vec<u8>)],
     columns: &[ColumnStorageInfo],
     opts: &ExportOptions,
-    page_size: u32,
 ) -> Result<(), IdbError> {
     // Header row
     let headers: Vec<&str> = columns
@@ -153,12 +177,13 @@ fn output_csv(
     wprintln!(writer, "{}", headers.join(","))?;
 
     for (_, page_data) in pages {
-        let rows = decode_page_records(
+        let rows = decode_page_records_with_lobs(
+            ts,
             page_data,
             columns,
             opts.where_delete_mark,
             opts.system_columns,
-            page_size,
+            opts.max_lob_size,
         );
         for row in &rows {
             let values: Vec<String> = row.iter().map(|(_, v)| csv_escape(v)).collect();
@@ -172,20 +197,21 @@ fn output_csv(
 /// Output records as JSON (array of objects).
 fn output_json(
     writer: &mut dyn Write,
+    ts: &mut Tablespace,
     pages: &[(u64, Vec<u8>)],
     columns: &[ColumnStorageInfo],
     opts: &ExportOptions,
-    page_size: u32,
 ) -> Result<(), IdbError> {
     let mut all_rows: Vec<serde_json::Map<String, serde_json::Value>> = Vec::new();
 
     for (_, page_data) in pages {
-        let rows = decode_page_records(
+        let rows = decode_page_records_with_lobs(
+            ts,
             page_data,
             columns,
             opts.where_delete_mark,
             opts.system_columns,
-            page_size,
+            opts.max_lob_size,
         );
         for row in rows {
             let mut obj = serde_json::Map::new();
@@ -202,6 +228,8 @@ fn output_json(
                         .unwrap_or(serde_json::Value::Null),
                     FieldValue::Str(s) => serde_json::Value::String(s),
                     FieldValue::Hex(h) => serde_json::Value::String(h),
+                    FieldValue::Json(v) => v,
+                    FieldValue::Geometry(g) => g.geometry.to_geojson(),
                 };
                 obj.insert(name, json_val);
             }
@@ -233,7 +261,7 @@ fn output_hex(
     )?;
 
     for (page_num, page_data) in pages {
-        let records = walk_compact_records(page_data);
+        let records = walk_records(page_data);
         for rec in &records {
             let delete_mark = rec.header.delete_mark();
             if opts.where_delete_mark && !delete_mark {
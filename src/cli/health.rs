@@ -4,7 +4,7 @@
 //! garbage ratio, tree depth) by scanning all INDEX pages in a tablespace.
 //! Optionally computes bloat scores (A-F grades) and cardinality estimates.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::time::Instant;
 
@@ -63,6 +63,7 @@ pub fn execute(opts: &HealthOptions, writer: &mut dyn Write) -> Result<(), IdbEr
     // Single-pass collection
     let mut snapshots = Vec::new();
     let mut empty_pages = 0u64;
+    let mut empty_page_numbers: HashSet<u64> = HashSet::new();
     let mut rtree_pages = 0u64;
     let mut lob_pages = 0u64;
     let mut undo_pages = 0u64;
@@ -75,6 +76,7 @@ pub fn execute(opts: &HealthOptions, writer: &mut dyn Write) -> Result<(), IdbEr
     ts.for_each_page(|page_num, data| {
         if data.iter().all(|&b| b == 0) {
             empty_pages += 1;
+            empty_page_numbers.insert(page_num);
         } else if let Some(snap) = health::extract_index_page_snapshot(data, page_num) {
             // Track leaf pages for cardinality sampling
             if snap.level == 0 && opts.cardinality {
@@ -130,6 +132,9 @@ pub fn execute(opts: &HealthOptions, writer: &mut dyn Write) -> Result<(), IdbEr
     report.summary.rtree_pages = rtree_pages;
     report.summary.lob_pages = lob_pages;
     report.summary.undo_pages = undo_pages;
+    report.allocation = crate::innodb::xdes::read_allocation_map(&mut ts)
+        .ok()
+        .map(|map| map.summary(total_file_pages, &empty_page_numbers));
 
     // Best-effort SDI index name resolution
     resolve_index_names(
@@ -174,7 +179,6 @@ pub fn execute(opts: &HealthOptions, writer: &mut dyn Write) -> Result<(), IdbEr
                         leaf_pages,
                         &columns,
                         &col_name,
-                        page_size,
                         opts.sample_size,
                     );
                 }
@@ -364,6 +368,40 @@ fn print_text(
         report.summary.avg_fragmentation * 100.0
     )?;
 
+    if let Some(ref alloc) = report.allocation {
+        wprintln!(writer)?;
+        wprintln!(writer, "Allocation:")?;
+        wprintln!(
+            writer,
+            "  Extents:          {} initialized ({} free, {} free-frag, {} full-frag, {} segment), {} pages each",
+            alloc.initialized_extents,
+            alloc.free_extents,
+            alloc.free_frag_extents,
+            alloc.full_frag_extents,
+            alloc.segment_extents,
+            alloc.extent_size
+        )?;
+        wprintln!(
+            writer,
+            "  Pages:            {} used, {} free, {} uninitialized (free limit {})",
+            alloc.used_pages,
+            alloc.free_pages,
+            alloc.uninitialized_pages,
+            alloc.free_limit
+        )?;
+        wprintln!(
+            writer,
+            "  Allocated empty:  {}",
+            alloc.allocated_empty_pages
+        )?;
+        if verbose {
+            wprintln!(writer, "  Free with data:   {}", alloc.free_pages_with_data)?;
+        }
+        for err in &alloc.list_errors {
+            wprintln!(writer, "  List error:       {}", err)?;
+        }
+    }
+
     Ok(())
 }
 
@@ -8,7 +8,10 @@ use serde::Serialize;
 
 use crate::cli::wprintln;
 use crate::innodb::constants::*;
+use crate::innodb::datafile::{self, SystemTablespaceConfig};
+use crate::innodb::ibuf::{self, IbufSpaceChanges};
 use crate::innodb::page::FilHeader;
+use crate::innodb::tablespace::Tablespace;
 use crate::util::fs::find_tablespace_files;
 use crate::IdbError;
 
@@ -32,7 +35,9 @@ pub struct InfoOptions {
     pub user: Option<String>,
     /// MySQL password for live queries.
     pub password: Option<String>,
-    /// Path to a MySQL defaults file (`.my.cnf`).
+    /// Path to a MySQL defaults file (`.my.cnf`). With `ibdata`, its
+    /// `innodb_data_file_path` and `innodb_data_home_dir` locate the system
+    /// tablespace files.
     pub defaults_file: Option<String>,
     /// Scan data directory and produce a tablespace ID mapping.
     pub tablespace_map: bool,
@@ -53,10 +58,39 @@ struct IbdataInfoJson {
     lsn: u64,
     flush_lsn: u64,
     space_id: u32,
+    data_files: Vec<DataFileJson>,
     #[serde(skip_serializing_if = "Option::is_none")]
     redo_checkpoint_1_lsn: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     redo_checkpoint_2_lsn: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    change_buffer: Option<ChangeBufferJson>,
+}
+
+#[derive(Serialize)]
+struct DataFileJson {
+    file: String,
+    size: u64,
+    first_page: u64,
+    page_count: u64,
+}
+
+#[derive(Serialize)]
+struct ChangeBufferJson {
+    levels: u16,
+    leaf_pages: usize,
+    pending_changes: usize,
+    tables: Vec<ChangeBufferTableJson>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ChangeBufferTableJson {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(flatten)]
+    changes: IbufSpaceChanges,
 }
 
 #[derive(Serialize)]
@@ -195,27 +229,9 @@ fn execute_tablespace_map(opts: &InfoOptions, writer: &mut dyn Write) -> Result<
     let mut results: BTreeMap<String, u32> = BTreeMap::new();
 
     for ibd_path in &ibd_files {
-        let path_str = ibd_path.to_string_lossy();
-        let mut ts = match crate::cli::open_tablespace(&path_str, opts.page_size, opts.mmap) {
-            Ok(t) => t,
-            Err(_) => continue,
-        };
-
-        let space_id = match ts.fsp_header() {
-            Some(fsp) => fsp.space_id,
-            None => {
-                // Try reading space_id directly from FSP header position
-                match ts.read_page(0) {
-                    Ok(page0) => {
-                        if page0.len() >= FIL_PAGE_DATA + 4 {
-                            BigEndian::read_u32(&page0[FIL_PAGE_DATA..])
-                        } else {
-                            continue;
-                        }
-                    }
-                    Err(_) => continue,
-                }
-            }
+        let space_id = match read_space_id(&ibd_path.to_string_lossy(), opts) {
+            Some(id) => id,
+            None => continue,
         };
 
         let display_path = ibd_path
@@ -277,27 +293,65 @@ fn execute_tablespace_map(opts: &InfoOptions, writer: &mut dyn Write) -> Result<
     Ok(())
 }
 
+/// Read the space ID of a tablespace file from its FSP header.
+fn read_space_id(path: &str, opts: &InfoOptions) -> Option<u32> {
+    let mut ts = crate::cli::open_tablespace(path, opts.page_size, opts.mmap).ok()?;
+    match ts.fsp_header() {
+        Some(fsp) => Some(fsp.space_id),
+        None => {
+            // Try reading space_id directly from FSP header position
+            let page0 = ts.read_page(0).ok()?;
+            if page0.len() >= FIL_PAGE_DATA + 4 {
+                Some(BigEndian::read_u32(&page0[FIL_PAGE_DATA..]))
+            } else {
+                None
+            }
+        }
+    }
+}
+
 fn execute_ibdata(
     opts: &InfoOptions,
     datadir: &std::path::Path,
     writer: &mut dyn Write,
 ) -> Result<(), IdbError> {
-    let ibdata_path = datadir.join("ibdata1");
-    if !ibdata_path.exists() {
-        return Err(IdbError::Io(format!(
-            "ibdata1 not found in {}",
-            datadir.display()
-        )));
-    }
+    // Data files of the system tablespace, from innodb_data_file_path in the
+    // defaults file when given, otherwise ibdata1, ibdata2, ... in datadir
+    let config = match opts.defaults_file {
+        Some(ref df) => SystemTablespaceConfig::from_cnf_file(df)?,
+        None => SystemTablespaceConfig::default(),
+    };
+    let files = datafile::system_data_files(datadir, &config)?;
+    let ibdata_path = files[0].path.clone();
+    let mut ts = Tablespace::open_data_files(&files, opts.page_size)?;
 
     // Read page 0 of ibdata1
-    let page0 = read_file_bytes(&ibdata_path, 0, SIZE_PAGE_DEFAULT as usize)?;
+    let page0 = ts.read_page(0)?;
     let header = FilHeader::parse(&page0)
         .ok_or_else(|| IdbError::Parse("Cannot parse ibdata1 page 0 FIL header".to_string()))?;
 
+    let page_size = u64::from(ts.page_size());
+    let mut first_page = 0u64;
+    let mut data_files = Vec::with_capacity(files.len());
+    for df in &files {
+        let size = match df.size {
+            Some(size) => size,
+            None => std::fs::metadata(&df.path).map(|m| m.len()).unwrap_or(0),
+        };
+        data_files.push(DataFileJson {
+            file: df.path.display().to_string(),
+            size,
+            first_page,
+            page_count: size / page_size,
+        });
+        first_page += size / page_size;
+    }
+
     // Try to read redo log checkpoint LSNs
     let (cp1_lsn, cp2_lsn) = read_redo_checkpoint_lsns(datadir);
 
+    let change_buffer = read_change_buffer(opts, datadir, &mut ts);
+
     if opts.json {
         let info = IbdataInfoJson {
             ibdata_file: ibdata_path.display().to_string(),
@@ -307,8 +361,10 @@ fn execute_ibdata(
             lsn: header.lsn,
             flush_lsn: header.flush_lsn,
             space_id: header.space_id,
+            data_files,
             redo_checkpoint_1_lsn: cp1_lsn,
             redo_checkpoint_2_lsn: cp2_lsn,
+            change_buffer,
         };
         let json = serde_json::to_string_pretty(&info)
             .map_err(|e| IdbError::Parse(format!("JSON serialization error: {}", e)))?;
@@ -331,6 +387,21 @@ fn execute_ibdata(
     wprintln!(writer, "  Space ID:   {}", header.space_id)?;
     wprintln!(writer)?;
 
+    if data_files.len() > 1 {
+        wprintln!(writer, "{}", "System Tablespace Files".bold())?;
+        for df in &data_files {
+            wprintln!(
+                writer,
+                "  {}  pages {}-{} ({} bytes)",
+                df.file,
+                df.first_page,
+                (df.first_page + df.page_count).saturating_sub(1),
+                df.size
+            )?;
+        }
+        wprintln!(writer)?;
+    }
+
     if let Some(lsn) = cp1_lsn {
         wprintln!(writer, "Redo Log Checkpoint 1 LSN: {}", lsn)?;
     }
@@ -338,6 +409,87 @@ fn execute_ibdata(
         wprintln!(writer, "Redo Log Checkpoint 2 LSN: {}", lsn)?;
     }
 
+    if let Some(cb) = change_buffer {
+        print_change_buffer(&cb, writer)?;
+    }
+
+    Ok(())
+}
+
+/// Walk the change buffer tree in ibdata1 and summarize pending changes
+/// per tablespace, naming the `.ibd` file of each space where found.
+fn read_change_buffer(
+    opts: &InfoOptions,
+    datadir: &Path,
+    ts: &mut Tablespace,
+) -> Option<ChangeBufferJson> {
+    let tree = ibuf::read_ibuf_tree(ts).ok()?;
+    let by_space = tree.changes_by_space();
+
+    let mut files: BTreeMap<u32, String> = BTreeMap::new();
+    if !by_space.is_empty() {
+        for path in find_tablespace_files(datadir, &["ibd"], None).unwrap_or_default() {
+            if let Some(space_id) = read_space_id(&path.to_string_lossy(), opts) {
+                let display = path.strip_prefix(datadir).unwrap_or(&path);
+                files.insert(space_id, display.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    Some(ChangeBufferJson {
+        levels: tree.levels,
+        leaf_pages: tree.leaf_pages.len(),
+        pending_changes: tree.records.len(),
+        tables: by_space
+            .into_iter()
+            .map(|changes| ChangeBufferTableJson {
+                file: files.get(&changes.space_id).cloned(),
+                changes,
+            })
+            .collect(),
+        errors: tree.errors,
+    })
+}
+
+fn print_change_buffer(cb: &ChangeBufferJson, writer: &mut dyn Write) -> Result<(), IdbError> {
+    wprintln!(writer)?;
+    wprintln!(writer, "{}", "Change Buffer".bold())?;
+    wprintln!(writer, "  Tree levels:     {}", cb.levels)?;
+    wprintln!(writer, "  Leaf pages:      {}", cb.leaf_pages)?;
+    if cb.pending_changes == 0 {
+        wprintln!(writer, "  Pending changes: {}", "none".green())?;
+    } else {
+        wprintln!(
+            writer,
+            "  Pending changes: {}",
+            cb.pending_changes.to_string().yellow()
+        )?;
+        wprintln!(writer)?;
+        wprintln!(
+            writer,
+            "  {:>8}  {:>6}  {:>8}  {:>11}  {:>7}  FILE",
+            "SPACE_ID",
+            "PAGES",
+            "INSERTS",
+            "DEL-MARKS",
+            "PURGES"
+        )?;
+        for t in &cb.tables {
+            wprintln!(
+                writer,
+                "  {:>8}  {:>6}  {:>8}  {:>11}  {:>7}  {}",
+                t.changes.space_id,
+                t.changes.pages,
+                t.changes.counts.inserts,
+                t.changes.counts.delete_marks,
+                t.changes.counts.purges,
+                t.file.as_deref().unwrap_or("-")
+            )?;
+        }
+    }
+    for err in &cb.errors {
+        wprintln!(writer, "  {} {}", "Warning:".yellow(), err)?;
+    }
     Ok(())
 }
 
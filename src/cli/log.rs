@@ -1,27 +1,40 @@
+use std::collections::BTreeMap;
 use std::io::Write;
 
 use colored::Colorize;
 use serde::Serialize;
 
 use crate::cli::wprintln;
+use crate::innodb::encryption::EncryptionInfo;
+use crate::innodb::keyring::Keyring;
 use crate::innodb::log::{
-    validate_log_block_checksum, LogBlockHeader, LogFile, LogFileHeader, MlogRecordType,
-    LOG_BLOCK_HDR_SIZE, LOG_BLOCK_SIZE, LOG_FILE_HDR_BLOCKS,
+    validate_log_block_checksum, LogBlockHeader, LogFile, LogFileHeader,
+    LOG_BLOCK_ENCRYPT_BIT_MASK, LOG_BLOCK_SIZE, LOG_FILE_HDR_BLOCKS,
+};
+use crate::innodb::mariadb_redo::{
+    is_mariadb_10_8, read_mariadb_log, MariaDbCheckpoint, MariaDbRedoLog,
+};
+use crate::innodb::mlog::{decode_mlog_stream, read_log_streams, MlogDecodeError, MlogRecord};
+use crate::innodb::redo_set::{
+    LsnGap, LsnOverlap, RedoBlockLocation, RedoLogLayout, RedoLogMember, RedoLogSet,
 };
 use crate::IdbError;
 
 /// Options for the `inno log` subcommand.
 pub struct LogOptions {
-    /// Path to the redo log file (`ib_logfile0`, `ib_logfile1`, or `#ib_redo*`).
+    /// Path to the redo log file (`ib_logfile0`, `ib_logfile1`, or `#ib_redo*`),
+    /// or a directory holding the whole redo log.
     pub file: String,
     /// Limit output to the first N data blocks.
     pub blocks: Option<u64>,
     /// Skip blocks that contain no redo log data.
     pub no_empty: bool,
-    /// Show MLOG record types within each data block.
+    /// Show the MLOG records that start within each data block.
     pub verbose: bool,
     /// Emit output as JSON.
     pub json: bool,
+    /// Path to MySQL keyring file for decrypting encrypted redo logs.
+    pub keyring: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -31,13 +44,27 @@ struct LogSummaryJson {
     total_blocks: u64,
     data_blocks: u64,
     header: LogFileHeader,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encryption: Option<LogEncryptionJson>,
     checkpoint_1: Option<crate::innodb::log::LogCheckpoint>,
     checkpoint_2: Option<crate::innodb::log::LogCheckpoint>,
     blocks: Vec<BlockJson>,
 }
 
+#[derive(Serialize)]
+struct LogEncryptionJson {
+    #[serde(flatten)]
+    info: EncryptionInfo,
+    /// Data blocks are decrypted with the key from `--keyring`.
+    decrypted: bool,
+}
+
 #[derive(Serialize)]
 struct BlockJson {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lsn: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
     block_index: u64,
     block_no: u32,
     flush_flag: bool,
@@ -46,6 +73,62 @@ struct BlockJson {
     epoch_no: u32,
     checksum_valid: bool,
     record_types: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    records: Vec<MlogRecord>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    decode_errors: Vec<MlogDecodeError>,
+}
+
+/// Decoded records and decode errors, keyed by the file block index (or,
+/// for a redo log set, the block start LSN) where each record (or failed
+/// record) starts.
+#[derive(Default)]
+struct BlockRecords {
+    records: BTreeMap<u64, Vec<MlogRecord>>,
+    errors: BTreeMap<u64, Vec<MlogDecodeError>>,
+}
+
+impl BlockRecords {
+    /// Decode the whole record stream of `log` and attribute each record
+    /// to the block it starts in.
+    fn decode(log: &mut LogFile) -> Result<Self, IdbError> {
+        let mut by_block = BlockRecords::default();
+        for stream in read_log_streams(log)? {
+            let decoded = decode_mlog_stream(&stream);
+            for rec in decoded.records {
+                let block = stream.block_index_of_lsn(rec.start_lsn);
+                by_block.records.entry(block).or_default().push(rec);
+            }
+            for err in decoded.errors {
+                let block = stream.block_index_of_lsn(err.lsn);
+                by_block.errors.entry(block).or_default().push(err);
+            }
+        }
+        Ok(by_block)
+    }
+
+    /// Decode the record stream of a whole redo log set and attribute each
+    /// record to the start LSN of the block it starts in.
+    fn decode_set(set: &mut RedoLogSet) -> Result<Self, IdbError> {
+        let decoded = set.read_mlog_records()?;
+        let mut by_block = BlockRecords::default();
+        for rec in decoded.records {
+            let block = rec.start_lsn - rec.start_lsn % LOG_BLOCK_SIZE as u64;
+            by_block.records.entry(block).or_default().push(rec);
+        }
+        for err in decoded.errors {
+            let block = err.lsn - err.lsn % LOG_BLOCK_SIZE as u64;
+            by_block.errors.entry(block).or_default().push(err);
+        }
+        Ok(by_block)
+    }
+
+    fn take(&mut self, block_idx: u64) -> (Vec<MlogRecord>, Vec<MlogDecodeError>) {
+        (
+            self.records.remove(&block_idx).unwrap_or_default(),
+            self.errors.remove(&block_idx).unwrap_or_default(),
+        )
+    }
 }
 
 /// Analyze the structure of an InnoDB redo log file.
@@ -53,28 +136,63 @@ struct BlockJson {
 /// InnoDB redo logs are organized as a sequence of 512-byte blocks. The first
 /// four blocks are reserved: block 0 is the **log file header** (format version,
 /// log UUID, start LSN, creator string), blocks 1 and 3 are **checkpoint
-/// records** (checkpoint LSN), and block 2 is reserved/unused. All remaining
-/// blocks are **data blocks** containing the actual redo log records.
+/// records** (checkpoint LSN), and block 2 holds the **encryption info** of
+/// logs written with `innodb_redo_log_encrypt`. All remaining blocks are
+/// **data blocks** containing the actual redo log records.
 ///
 /// This command reads and displays all three sections. For data blocks, each
 /// block's header is decoded to show the block number, data length,
 /// first-record-group offset, epoch number, flush flag, and CRC-32C
 /// checksum validation status.
 ///
-/// With `--verbose`, the payload bytes of each non-empty data block are
-/// scanned for MLOG record type bytes (e.g., `MLOG_REC_INSERT`,
-/// `MLOG_UNDO_INSERT`, `MLOG_WRITE_STRING`) and a frequency summary is
-/// printed. Use `--blocks N` to limit output to the first N data blocks,
+/// With `--verbose`, the redo record stream is decoded across block
+/// boundaries and each block lists a frequency summary of the MLOG record
+/// types (e.g., `MLOG_REC_INSERT`, `MLOG_UNDO_INSERT`, `MLOG_WRITE_STRING`)
+/// that start in it, along with any records that failed to decode. Use `--blocks N` to limit output to the first N data blocks,
 /// or `--no-empty` to skip blocks with zero data length.
+///
+/// If the path is a directory (a data directory or `#innodb_redo`), all of
+/// its redo files are opened as a [`RedoLogSet`]. The files, the combined
+/// LSN range, and any gaps or overlaps between files are listed, and data
+/// blocks are shown in LSN order across files; `--verbose` decodes records
+/// that span file boundaries.
+///
+/// With `--keyring`, the data blocks of an encrypted redo log are decrypted
+/// with the redo log key, which is derived from the encryption info in
+/// block 2 and the master key in the keyring.
 pub fn execute(opts: &LogOptions, writer: &mut dyn Write) -> Result<(), IdbError> {
+    let keyring = opts.keyring.as_deref().map(Keyring::load).transpose()?;
+
+    if std::path::Path::new(&opts.file).is_dir() {
+        let mut set = RedoLogSet::open_dir_with_keyring(&opts.file, keyring.as_ref())?;
+        if let Some(i) = set.mariadb_10_8_member() {
+            let path = set.members()[i].path.clone();
+            return execute_mariadb(opts, &path, &mut LogFile::open(&path)?, writer);
+        }
+        return if opts.json {
+            execute_set_json(opts, &mut set, writer)
+        } else {
+            execute_set(opts, &mut set, writer)
+        };
+    }
+
     let mut log = LogFile::open(&opts.file)?;
 
     let header = log.read_header()?;
+    if is_mariadb_10_8(&header) {
+        return execute_mariadb(opts, &opts.file, &mut log, writer);
+    }
     let cp1 = log.read_checkpoint(0).ok();
     let cp2 = log.read_checkpoint(1).ok();
+    let encryption = log.read_encryption_info()?;
+    let decrypted = match keyring {
+        Some(ref keyring) => log.enable_decryption(keyring)?,
+        None => false,
+    };
 
     if opts.json {
-        return execute_json(opts, &mut log, header, cp1, cp2, writer);
+        let encryption = encryption.map(|info| LogEncryptionJson { info, decrypted });
+        return execute_json(opts, &mut log, header, encryption, cp1, cp2, writer);
     }
 
     // Print file info
@@ -108,15 +226,38 @@ pub fn execute(opts: &LogOptions, writer: &mut dyn Write) -> Result<(), IdbError
         wprintln!(
             writer,
             "  {}",
-            "Note: MLOG record types are not decoded for MariaDB redo logs".yellow()
+            "Note: MLOG record types are not decoded for MariaDB redo logs before 10.8".yellow()
         )?;
     }
     wprintln!(writer)?;
 
+    if let Some(ref info) = encryption {
+        wprintln!(writer, "{}", "Encryption (block 2)".bold())?;
+        wprintln!(writer, "  Master Key ID: {}", info.master_key_id)?;
+        wprintln!(writer, "  Server UUID:   {}", info.server_uuid)?;
+        if decrypted {
+            wprintln!(writer, "  Data blocks:   {}", "decrypted".green())?;
+        } else {
+            wprintln!(
+                writer,
+                "  Data blocks:   {}",
+                "encrypted (use --keyring to decrypt)".yellow()
+            )?;
+        }
+        wprintln!(writer)?;
+    }
+
     // Print checkpoints
     print_checkpoint(writer, "Checkpoint 1 (block 1)", &cp1)?;
     print_checkpoint(writer, "Checkpoint 2 (block 3)", &cp2)?;
 
+    // Verbose: decode MLOG records (skip for MariaDB before 10.8 — incompatible format)
+    let mut block_records = if opts.verbose && !is_mariadb {
+        BlockRecords::decode(&mut log)?
+    } else {
+        BlockRecords::default()
+    };
+
     // Iterate data blocks
     let data_blocks = log.data_block_count();
     let limit = opts.blocks.unwrap_or(data_blocks).min(data_blocks);
@@ -151,10 +292,15 @@ pub fn execute(opts: &LogOptions, writer: &mut dyn Write) -> Result<(), IdbError
         };
 
         let flush_str = if hdr.flush_flag { " FLUSH" } else { "" };
+        let encrypted_str = if hdr.data_len & LOG_BLOCK_ENCRYPT_BIT_MASK != 0 {
+            " ENCRYPTED"
+        } else {
+            ""
+        };
 
         wprintln!(
             writer,
-            "  Block {:>6}  no={:<10} len={:<5} first_rec={:<5} epoch={:<10} csum={}{}",
+            "  Block {:>6}  no={:<10} len={:<5} first_rec={:<5} epoch={:<10} csum={}{}{}",
             block_idx,
             hdr.block_no,
             hdr.data_len,
@@ -162,12 +308,11 @@ pub fn execute(opts: &LogOptions, writer: &mut dyn Write) -> Result<(), IdbError
             hdr.epoch_no,
             checksum_str,
             flush_str,
+            encrypted_str,
         )?;
 
-        // Verbose: show MLOG record types (skip for MariaDB — incompatible format)
-        if opts.verbose && hdr.has_data() && !is_mariadb {
-            print_record_types(writer, &block_data, &hdr)?;
-        }
+        let (records, errors) = block_records.take(block_idx);
+        print_record_types(writer, &records, &errors)?;
 
         displayed += 1;
     }
@@ -226,33 +371,13 @@ fn print_checkpoint(
 
 fn print_record_types(
     writer: &mut dyn Write,
-    block_data: &[u8],
-    hdr: &LogBlockHeader,
+    records: &[MlogRecord],
+    errors: &[MlogDecodeError],
 ) -> Result<(), IdbError> {
-    let data_end = (hdr.data_len as usize).min(LOG_BLOCK_SIZE - 4);
-    if data_end <= LOG_BLOCK_HDR_SIZE {
-        return Ok(());
-    }
-
-    let mut types: Vec<MlogRecordType> = Vec::new();
-    let mut pos = LOG_BLOCK_HDR_SIZE;
-
-    while pos < data_end {
-        let type_byte = block_data[pos];
-        // The single-record flag is bit 7 of the type byte
-        let rec_type = MlogRecordType::from_u8(type_byte & 0x7F);
-        types.push(rec_type);
-        // We can't fully decode record lengths without schema info,
-        // so just scan byte-by-byte for type bytes
-        pos += 1;
-    }
-
-    if !types.is_empty() {
-        // Count occurrences
-        let mut counts: std::collections::BTreeMap<String, usize> =
-            std::collections::BTreeMap::new();
-        for t in &types {
-            *counts.entry(t.to_string()).or_insert(0) += 1;
+    if !records.is_empty() {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for rec in records {
+            *counts.entry(rec.record_type.to_string()).or_insert(0) += 1;
         }
         let summary: Vec<String> = counts
             .iter()
@@ -260,6 +385,15 @@ fn print_record_types(
             .collect();
         wprintln!(writer, "    record types: {}", summary.join(", "))?;
     }
+    for err in errors {
+        wprintln!(
+            writer,
+            "    {} at LSN {}: {}",
+            "decode error".red(),
+            err.lsn,
+            err.message
+        )?;
+    }
 
     Ok(())
 }
@@ -268,10 +402,19 @@ fn execute_json(
     opts: &LogOptions,
     log: &mut LogFile,
     header: LogFileHeader,
+    encryption: Option<LogEncryptionJson>,
     cp1: Option<crate::innodb::log::LogCheckpoint>,
     cp2: Option<crate::innodb::log::LogCheckpoint>,
     writer: &mut dyn Write,
 ) -> Result<(), IdbError> {
+    let is_mariadb = crate::innodb::vendor::detect_vendor_from_created_by(&header.created_by)
+        == crate::innodb::vendor::InnoDbVendor::MariaDB;
+    let mut block_records = if opts.verbose && !is_mariadb {
+        BlockRecords::decode(log)?
+    } else {
+        BlockRecords::default()
+    };
+
     let data_blocks = log.data_block_count();
     let limit = opts.blocks.unwrap_or(data_blocks).min(data_blocks);
 
@@ -292,15 +435,15 @@ fn execute_json(
 
         let checksum_ok = validate_log_block_checksum(&block_data);
 
-        let is_mariadb = crate::innodb::vendor::detect_vendor_from_created_by(&header.created_by)
-            == crate::innodb::vendor::InnoDbVendor::MariaDB;
-        let record_types = if opts.verbose && hdr.has_data() && !is_mariadb {
-            collect_record_type_names(&block_data, &hdr)
-        } else {
-            Vec::new()
-        };
+        let (records, decode_errors) = block_records.take(block_idx);
+        let record_types = records
+            .iter()
+            .map(|rec| rec.record_type.to_string())
+            .collect();
 
         blocks_json.push(BlockJson {
+            lsn: None,
+            file: None,
             block_index: block_idx,
             block_no: hdr.block_no,
             flush_flag: hdr.flush_flag,
@@ -309,6 +452,8 @@ fn execute_json(
             epoch_no: hdr.epoch_no,
             checksum_valid: checksum_ok,
             record_types,
+            records,
+            decode_errors,
         });
     }
 
@@ -318,6 +463,7 @@ fn execute_json(
         total_blocks: log.block_count(),
         data_blocks: log.data_block_count(),
         header,
+        encryption,
         checkpoint_1: cp1,
         checkpoint_2: cp2,
         blocks: blocks_json,
@@ -330,21 +476,463 @@ fn execute_json(
     Ok(())
 }
 
-fn collect_record_type_names(block_data: &[u8], hdr: &LogBlockHeader) -> Vec<String> {
-    let data_end = (hdr.data_len as usize).min(LOG_BLOCK_SIZE - 4);
-    if data_end <= LOG_BLOCK_HDR_SIZE {
-        return Vec::new();
+#[derive(Serialize)]
+struct MariaDbLogJson<'a> {
+    file: &'a str,
+    file_size: u64,
+    capacity: u64,
+    header: &'a LogFileHeader,
+    header_checksum_valid: bool,
+    encrypted: bool,
+    checkpoint_1: &'a Option<MariaDbCheckpoint>,
+    checkpoint_2: &'a Option<MariaDbCheckpoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checkpoint_lsn: Option<u64>,
+    start_lsn: u64,
+    end_lsn: u64,
+    total_mtrs: usize,
+    mini_transactions: Vec<MtrJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct MtrJson<'a> {
+    start_lsn: u64,
+    end_lsn: u64,
+    record_types: Vec<String>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    records: &'a [MlogRecord],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    decode_errors: &'a [MlogDecodeError],
+}
+
+/// Show a MariaDB 10.8+ log: its header, both checkpoints, and the
+/// mini-transactions from the start of the log to its end (`--blocks`
+/// limits how many are listed).
+fn execute_mariadb(
+    opts: &LogOptions,
+    path: &str,
+    log: &mut LogFile,
+    writer: &mut dyn Write,
+) -> Result<(), IdbError> {
+    let redo = read_mariadb_log(log)?;
+    let total = redo.mtrs.len();
+    let limit = opts.blocks.map_or(total, |n| total.min(n as usize));
+
+    if opts.json {
+        return execute_mariadb_json(opts, path, log.file_size(), &redo, limit, writer);
+    }
+
+    wprintln!(writer, "{}", "InnoDB Redo Log File (MariaDB 10.8+)".bold())?;
+    wprintln!(writer, "  File:       {}", path)?;
+    wprintln!(writer, "  Size:       {} bytes", log.file_size())?;
+    wprintln!(writer, "  Capacity:   {} bytes", redo.capacity)?;
+    wprintln!(writer)?;
+
+    let header = &redo.header;
+    wprintln!(writer, "{}", "Log File Header".bold())?;
+    wprintln!(writer, "  Format:     0x{:08X}", header.format_version)?;
+    wprintln!(writer, "  First LSN:  {}", header.start_lsn)?;
+    wprintln!(writer, "  Created by: {}", header.created_by)?;
+    wprintln!(
+        writer,
+        "  Vendor:     {}",
+        crate::innodb::vendor::InnoDbVendor::MariaDB
+    )?;
+    wprintln!(
+        writer,
+        "  Checksum:   {}",
+        checksum_label(redo.header_checksum_valid)
+    )?;
+    if redo.encrypted {
+        wprintln!(writer, "  Encrypted:  yes")?;
+        wprintln!(
+            writer,
+            "  {}",
+            "Note: records of encrypted MariaDB redo logs are not decoded".yellow()
+        )?;
+    }
+    wprintln!(writer)?;
+
+    for (i, cp) in redo.checkpoints.iter().enumerate() {
+        let offset = if i == 0 { 0x1000 } else { 0x2000 };
+        wprintln!(
+            writer,
+            "{}",
+            format!("Checkpoint {} (offset {})", i + 1, offset).bold()
+        )?;
+        match cp {
+            Some(cp) => {
+                wprintln!(writer, "  LSN:          {}", cp.lsn)?;
+                wprintln!(writer, "  End LSN:      {}", cp.end_lsn)?;
+                wprintln!(
+                    writer,
+                    "  Checksum:     {}",
+                    checksum_label(cp.checksum_valid)
+                )?;
+            }
+            None => {
+                wprintln!(writer, "  {}", "(not present or unreadable)".yellow())?;
+            }
+        }
+        wprintln!(writer)?;
+    }
+
+    if redo.encrypted {
+        return Ok(());
+    }
+
+    wprintln!(
+        writer,
+        "{}",
+        format!(
+            "Mini-transactions (LSN {}..{})",
+            redo.start_lsn, redo.end_lsn
+        )
+        .bold()
+    )?;
+    for mtr in &redo.mtrs[..limit] {
+        wprintln!(
+            writer,
+            "  LSN {:>12}  len={:<6} records={}",
+            mtr.start_lsn,
+            mtr.end_lsn - mtr.start_lsn,
+            mtr.records.len() + mtr.errors.len()
+        )?;
+        if opts.verbose {
+            print_record_types(writer, &mtr.records, &mtr.errors)?;
+        }
+    }
+    if limit > 0 {
+        wprintln!(writer)?;
+    }
+
+    wprintln!(
+        writer,
+        "Displayed {} mini-transactions{}",
+        limit,
+        if limit < total {
+            format!(" (of {})", total)
+        } else {
+            String::new()
+        }
+    )?;
+
+    Ok(())
+}
+
+fn execute_mariadb_json(
+    opts: &LogOptions,
+    path: &str,
+    file_size: u64,
+    redo: &MariaDbRedoLog,
+    limit: usize,
+    writer: &mut dyn Write,
+) -> Result<(), IdbError> {
+    let mini_transactions = redo.mtrs[..limit]
+        .iter()
+        .map(|mtr| MtrJson {
+            start_lsn: mtr.start_lsn,
+            end_lsn: mtr.end_lsn,
+            record_types: mtr
+                .records
+                .iter()
+                .map(|rec| rec.record_type.to_string())
+                .collect(),
+            records: if opts.verbose { &mtr.records } else { &[] },
+            decode_errors: &mtr.errors,
+        })
+        .collect();
+
+    let summary = MariaDbLogJson {
+        file: path,
+        file_size,
+        capacity: redo.capacity,
+        header: &redo.header,
+        header_checksum_valid: redo.header_checksum_valid,
+        encrypted: redo.encrypted,
+        checkpoint_1: &redo.checkpoints[0],
+        checkpoint_2: &redo.checkpoints[1],
+        checkpoint_lsn: redo.checkpoint_lsn,
+        start_lsn: redo.start_lsn,
+        end_lsn: redo.end_lsn,
+        total_mtrs: redo.mtrs.len(),
+        mini_transactions,
+    };
+
+    let json = serde_json::to_string_pretty(&summary)
+        .map_err(|e| IdbError::Parse(format!("JSON serialization error: {}", e)))?;
+    wprintln!(writer, "{}", json)?;
+
+    Ok(())
+}
+
+fn checksum_label(valid: bool) -> String {
+    if valid {
+        "OK".green().to_string()
+    } else {
+        "INVALID".red().to_string()
     }
+}
 
-    let mut names = Vec::new();
-    let mut pos = LOG_BLOCK_HDR_SIZE;
+#[derive(Serialize)]
+struct LogSetJson<'a> {
+    directory: &'a str,
+    layout: RedoLogLayout,
+    files: &'a [RedoLogMember],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_lsn: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_lsn: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checkpoint_lsn: Option<u64>,
+    total_blocks: u64,
+    gaps: &'a [LsnGap],
+    overlaps: &'a [LsnOverlap],
+    blocks: Vec<BlockJson>,
+}
+
+/// Whether the set's records can be decoded (not MariaDB).
+fn set_is_mariadb(set: &RedoLogSet) -> bool {
+    set.members().iter().any(|m| {
+        crate::innodb::vendor::detect_vendor_from_created_by(&m.header.created_by)
+            == crate::innodb::vendor::InnoDbVendor::MariaDB
+    })
+}
 
-    while pos < data_end {
-        let type_byte = block_data[pos];
-        let rec_type = MlogRecordType::from_u8(type_byte & 0x7F);
-        names.push(rec_type.to_string());
-        pos += 1;
+/// A data block of a redo log set with its parsed header and checksum status.
+type SetBlock = (RedoBlockLocation, LogBlockHeader, bool);
+
+/// The blocks of a redo log set to display, in LSN order, and the number of
+/// empty blocks skipped.
+fn set_blocks(opts: &LogOptions, set: &mut RedoLogSet) -> Result<(Vec<SetBlock>, u64), IdbError> {
+    let limit = opts.blocks.unwrap_or(u64::MAX);
+    let locations: Vec<_> = set.blocks().copied().take(limit as usize).collect();
+    let mut blocks = Vec::with_capacity(locations.len());
+    let mut empty_skipped = 0u64;
+    for loc in locations {
+        let data = set.read_block(&loc)?;
+        let hdr = match LogBlockHeader::parse(&data) {
+            Some(h) => h,
+            None => continue,
+        };
+        if opts.no_empty && !hdr.has_data() {
+            empty_skipped += 1;
+            continue;
+        }
+        blocks.push((loc, hdr, validate_log_block_checksum(&data)));
     }
+    Ok((blocks, empty_skipped))
+}
 
-    names
+fn execute_set(
+    opts: &LogOptions,
+    set: &mut RedoLogSet,
+    writer: &mut dyn Write,
+) -> Result<(), IdbError> {
+    wprintln!(writer, "{}", "InnoDB Redo Log Set".bold())?;
+    wprintln!(writer, "  Directory:  {}", opts.file)?;
+    wprintln!(writer, "  Layout:     {}", set.layout())?;
+    let spare = set.members().iter().filter(|m| m.spare).count();
+    wprintln!(
+        writer,
+        "  Files:      {}{}",
+        set.members().len() - spare,
+        if spare > 0 {
+            format!(" (+{} spare)", spare)
+        } else {
+            String::new()
+        }
+    )?;
+    match (set.start_lsn(), set.end_lsn()) {
+        (Some(start), Some(end)) => wprintln!(writer, "  LSN range:  {} - {}", start, end)?,
+        _ => wprintln!(writer, "  LSN range:  {}", "(no log data)".yellow())?,
+    }
+    if let Some(cp) = set.checkpoint_lsn() {
+        wprintln!(writer, "  Checkpoint: {}", cp)?;
+    }
+    let is_mariadb = set_is_mariadb(set);
+    if is_mariadb {
+        wprintln!(
+            writer,
+            "  {}",
+            "Note: MLOG record types are not decoded for MariaDB redo logs before 10.8".yellow()
+        )?;
+    }
+    if opts.keyring.is_none() && set.members().iter().any(|m| m.encrypted) {
+        wprintln!(
+            writer,
+            "  {}",
+            "Note: redo log is encrypted; use --keyring to decrypt its data blocks".yellow()
+        )?;
+    }
+    wprintln!(writer)?;
+
+    wprintln!(writer, "{}", "Files (in LSN order)".bold())?;
+    for m in set.members() {
+        let name = std::path::Path::new(&m.path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| m.path.clone());
+        let mut notes = Vec::new();
+        if m.spare {
+            notes.push("spare".to_string());
+        }
+        if m.encrypted {
+            notes.push("encrypted".to_string());
+        }
+        if m.stale_blocks > 0 {
+            notes.push(format!("{} stale blocks", m.stale_blocks));
+        }
+        wprintln!(
+            writer,
+            "  {:<16} size={:<10} start_lsn={:<12} blocks={:<8}{}",
+            name,
+            m.file_size,
+            m.header.start_lsn,
+            m.blocks,
+            if notes.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", notes.join(", "))
+            }
+        )?;
+    }
+    wprintln!(writer)?;
+
+    if set.gaps().is_empty() && set.overlaps().is_empty() {
+        wprintln!(writer, "LSN stream: {}", "contiguous".green())?;
+    }
+    for gap in set.gaps() {
+        wprintln!(
+            writer,
+            "{} LSN {}..{} ({} bytes) is missing",
+            "Gap:".red(),
+            gap.start_lsn,
+            gap.end_lsn,
+            gap.end_lsn - gap.start_lsn
+        )?;
+    }
+    for o in set.overlaps() {
+        wprintln!(
+            writer,
+            "{} LSN {}..{} is in both {} and {}",
+            "Overlap:".yellow(),
+            o.start_lsn,
+            o.end_lsn,
+            o.file,
+            o.duplicate
+        )?;
+    }
+    wprintln!(writer)?;
+
+    let mut block_records = if opts.verbose && !is_mariadb {
+        BlockRecords::decode_set(set)?
+    } else {
+        BlockRecords::default()
+    };
+    let (blocks, empty_skipped) = set_blocks(opts, set)?;
+
+    if !blocks.is_empty() {
+        wprintln!(writer, "{}", "Data Blocks (LSN order)".bold())?;
+    }
+    for (loc, hdr, checksum_ok) in &blocks {
+        let checksum_str = if *checksum_ok {
+            "OK".green().to_string()
+        } else {
+            "INVALID".red().to_string()
+        };
+        let name = std::path::Path::new(&set.members()[loc.member].path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        wprintln!(
+            writer,
+            "  LSN {:>12}  {}:{:<6} no={:<10} len={:<5} first_rec={:<5} epoch={:<10} csum={}{}",
+            loc.lsn,
+            name,
+            loc.block,
+            hdr.block_no,
+            hdr.data_len,
+            hdr.first_rec_group,
+            hdr.epoch_no,
+            checksum_str,
+            if hdr.flush_flag { " FLUSH" } else { "" },
+        )?;
+        let (records, errors) = block_records.take(loc.lsn);
+        print_record_types(writer, &records, &errors)?;
+    }
+    if opts.no_empty && empty_skipped > 0 {
+        wprintln!(writer, "  ({} empty blocks skipped)", empty_skipped)?;
+    }
+    if !blocks.is_empty() || empty_skipped > 0 {
+        wprintln!(writer)?;
+    }
+
+    let total = set.block_count();
+    let shown = opts.blocks.unwrap_or(total).min(total);
+    wprintln!(
+        writer,
+        "Displayed {} data blocks{}",
+        blocks.len(),
+        if shown < total {
+            format!(" (of {})", total)
+        } else {
+            String::new()
+        }
+    )?;
+
+    Ok(())
+}
+
+fn execute_set_json(
+    opts: &LogOptions,
+    set: &mut RedoLogSet,
+    writer: &mut dyn Write,
+) -> Result<(), IdbError> {
+    let mut block_records = if opts.verbose && !set_is_mariadb(set) {
+        BlockRecords::decode_set(set)?
+    } else {
+        BlockRecords::default()
+    };
+    let (blocks, _) = set_blocks(opts, set)?;
+
+    let blocks_json = blocks
+        .into_iter()
+        .map(|(loc, hdr, checksum_valid)| {
+            let (records, decode_errors) = block_records.take(loc.lsn);
+            BlockJson {
+                lsn: Some(loc.lsn),
+                file: Some(set.members()[loc.member].path.clone()),
+                block_index: loc.block,
+                block_no: hdr.block_no,
+                flush_flag: hdr.flush_flag,
+                data_len: hdr.data_len,
+                first_rec_group: hdr.first_rec_group,
+                epoch_no: hdr.epoch_no,
+                checksum_valid,
+                record_types: records.iter().map(|r| r.record_type.to_string()).collect(),
+                records,
+                decode_errors,
+            }
+        })
+        .collect();
+
+    let summary = LogSetJson {
+        directory: &opts.file,
+        layout: set.layout(),
+        files: set.members(),
+        start_lsn: set.start_lsn(),
+        end_lsn: set.end_lsn(),
+        checkpoint_lsn: set.checkpoint_lsn(),
+        total_blocks: set.block_count(),
+        gaps: set.gaps(),
+        overlaps: set.overlaps(),
+        blocks: blocks_json,
+    };
+
+    let json = serde_json::to_string_pretty(&summary)
+        .map_err(|e| IdbError::Parse(format!("JSON serialization error: {}", e)))?;
+    wprintln!(writer, "{}", json)?;
+
+    Ok(())
 }
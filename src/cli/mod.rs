@@ -33,7 +33,10 @@
 //! | `inno log` | [`log`] | Analyze redo log file headers, checkpoints, and data blocks |
 //! | `inno info` | [`info`] | Inspect `ibdata1`, compare LSNs, or query a live MySQL instance |
 //! | `inno defrag` | [`defrag`] | Defragment a tablespace by reclaiming free space and reordering pages |
+//! | `inno rebuild-index` | [`rebuild_index`] | Rebuild a secondary index offline from the clustered index |
+//! | `inno redo-apply` | [`redo_apply`] | Roll tablespaces forward by applying redo log records offline |
 //! | `inno transplant` | [`transplant`] | Copy specific pages from a donor tablespace into a target |
+//! | `inno doublewrite` | [`doublewrite`] | List doublewrite buffer copies and restore torn pages |
 //! | `inno audit` | [`audit`] | Audit a data directory for integrity, health, or corrupt pages |
 //! | `inno completions` | — | Generate shell completion scripts for bash, zsh, fish, or powershell |
 //!
@@ -63,6 +66,7 @@ pub mod comply;
 pub mod corrupt;
 pub mod defrag;
 pub mod diff;
+pub mod doublewrite;
 pub mod dump;
 pub mod export;
 pub mod find;
@@ -71,7 +75,9 @@ pub mod info;
 pub mod log;
 pub mod pages;
 pub mod parse;
+pub mod rebuild_index;
 pub mod recover;
+pub mod redo_apply;
 pub mod repair;
 pub mod schema;
 pub mod sdi;
@@ -118,6 +124,7 @@ pub(crate) fn csv_escape(val: &str) -> String {
     }
 }
 
+use crate::innodb::datafile::DataFile;
 use crate::innodb::decryption::DecryptionContext;
 use crate::innodb::keyring::Keyring;
 use crate::innodb::tablespace::Tablespace;
@@ -129,17 +136,38 @@ use indicatif::{ProgressBar, ProgressStyle};
 /// When `use_mmap` is true, the file is memory-mapped via `mmap(2)` for
 /// potentially better performance on large files (especially with parallel
 /// processing). When `page_size` is `Some`, auto-detection is bypassed.
+///
+/// A `;`-separated list of paths (e.g. `ibdata1;ibdata2`) opens a system
+/// tablespace spread over several files as one page space, using the whole
+/// of each file. Such tablespaces are always read with buffered I/O.
+///
+/// Transparent page decompression is enabled, so MySQL punch-hole and
+/// MariaDB page_compressed pages are returned as logical page images.
 pub(crate) fn open_tablespace(
     path: &str,
     page_size: Option<u32>,
     use_mmap: bool,
 ) -> Result<Tablespace, IdbError> {
-    match (use_mmap, page_size) {
-        (true, Some(ps)) => Tablespace::open_mmap_with_page_size(path, ps),
-        (true, None) => Tablespace::open_mmap(path),
-        (false, Some(ps)) => Tablespace::open_with_page_size(path, ps),
-        (false, None) => Tablespace::open(path),
-    }
+    let mut ts = if path.contains(';') {
+        let files: Vec<DataFile> = path
+            .split(';')
+            .filter(|p| !p.is_empty())
+            .map(|p| DataFile {
+                path: p.into(),
+                size: None,
+            })
+            .collect();
+        Tablespace::open_data_files(&files, page_size)?
+    } else {
+        match (use_mmap, page_size) {
+            (true, Some(ps)) => Tablespace::open_mmap_with_page_size(path, ps)?,
+            (true, None) => Tablespace::open_mmap(path)?,
+            (false, Some(ps)) => Tablespace::open_with_page_size(path, ps)?,
+            (false, None) => Tablespace::open(path)?,
+        }
+    };
+    ts.set_page_decompression(true);
+    Ok(ts)
 }
 
 /// Set up decryption on a tablespace if a keyring path is provided.
@@ -4,17 +4,21 @@ use colored::Colorize;
 use serde::Serialize;
 
 use crate::cli::{wprint, wprintln};
+use crate::innodb::btree::{decode_node_pointers, NodePointer};
 use crate::innodb::checksum;
 use crate::innodb::compression;
 use crate::innodb::encryption;
 use crate::innodb::health::compute_fill_factor;
+use crate::innodb::ibuf;
 use crate::innodb::index::{FsegHeader, IndexHeader, SystemRecords};
+use crate::innodb::inode::{self, FsegInode};
 use crate::innodb::lob::{BlobPageHeader, LobChainInfo, LobFirstPageHeader};
 use crate::innodb::page::{FilHeader, FspHeader};
 use crate::innodb::page_types::PageType;
 use crate::innodb::record::walk_compact_records;
 use crate::innodb::tablespace::Tablespace;
 use crate::innodb::undo::{UndoPageHeader, UndoSegmentHeader};
+use crate::innodb::xdes::{self, XdesEntry, XdesState};
 use crate::util::hex::format_offset;
 use crate::IdbError;
 
@@ -71,6 +75,14 @@ struct PageDetailJson {
     delete_marked_pct: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     lob_chain: Option<LobChainInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    node_pointers: Option<Vec<NodePointer>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    xdes_entries: Option<Vec<XdesEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inode_entries: Option<Vec<FsegInode>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ibuf_buffered_pages: Option<Vec<u32>>,
 }
 
 /// Perform deep structural analysis of pages in an InnoDB tablespace.
@@ -104,6 +116,7 @@ pub fn execute(opts: &PagesOptions, writer: &mut dyn Write) -> Result<(), IdbErr
     }
 
     let page_size = ts.page_size();
+    let extent_size = xdes::extent_size(ts.logical_page_size());
 
     if opts.json {
         return execute_json(opts, &mut ts, page_size, writer);
@@ -126,6 +139,10 @@ pub fn execute(opts: &PagesOptions, writer: &mut dyn Write) -> Result<(), IdbErr
         if opts.lob_chain {
             print_lob_chain_if_applicable(&page_data, page_num, &mut ts, writer)?;
         }
+        print_node_pointers_if_applicable(&page_data, &mut ts, writer)?;
+        print_xdes_entries_if_applicable(&page_data, page_size, extent_size, opts.verbose, writer)?;
+        print_inode_entries_if_applicable(&page_data, page_size, extent_size, writer)?;
+        print_ibuf_bitmap_if_applicable(&page_data, page_size, ts.page_count(), writer)?;
         return Ok(());
     }
 
@@ -135,6 +152,9 @@ pub fn execute(opts: &PagesOptions, writer: &mut dyn Write) -> Result<(), IdbErr
         if let Some(fsp) = FspHeader::parse(&page0) {
             print_fsp_header_detail(&fsp, &page0, opts.verbose, ts.vendor_info(), writer)?;
         }
+        if let Ok(map) = xdes::read_allocation_map(&mut ts) {
+            print_allocation_map(&map, ts.page_count(), writer)?;
+        }
     }
 
     for page_num in 0..ts.page_count() {
@@ -170,6 +190,15 @@ pub fn execute(opts: &PagesOptions, writer: &mut dyn Write) -> Result<(), IdbErr
             if opts.lob_chain {
                 print_lob_chain_if_applicable(&page_data, page_num, &mut ts, writer)?;
             }
+            print_xdes_entries_if_applicable(
+                &page_data,
+                page_size,
+                extent_size,
+                opts.verbose,
+                writer,
+            )?;
+            print_inode_entries_if_applicable(&page_data, page_size, extent_size, writer)?;
+            print_ibuf_bitmap_if_applicable(&page_data, page_size, ts.page_count(), writer)?;
         }
     }
 
@@ -363,6 +392,48 @@ fn execute_json(
             None
         };
 
+        let node_pointers = if opts.page.is_some() {
+            node_pointers_for_page(&page_data, ts).map(|(_, ptrs)| ptrs)
+        } else {
+            None
+        };
+
+        let xdes_entries = if matches!(pt, PageType::FspHdr | PageType::Xdes) {
+            let extent_size = xdes::extent_size(ts.logical_page_size());
+            Some(
+                xdes::parse_xdes_page(&page_data, page_num as u32, page_size, extent_size)
+                    .into_iter()
+                    .filter(|e| e.state != XdesState::NotInited)
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        let inode_entries = if pt == PageType::Inode {
+            let extent_size = xdes::extent_size(ts.logical_page_size());
+            Some(inode::parse_inode_page(
+                &page_data,
+                page_num as u32,
+                page_size,
+                extent_size,
+            ))
+        } else {
+            None
+        };
+
+        let ibuf_buffered_pages = if pt == PageType::IbufBitmap {
+            Some(
+                ibuf::parse_ibuf_bitmap(&page_data, page_size, page_num as u32)
+                    .into_iter()
+                    .filter(|e| e.buffered && u64::from(e.page_no) < ts.page_count())
+                    .map(|e| e.page_no)
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
         pages.push(PageDetailJson {
             page_number: page_num,
             page_type_name: pt.name().to_string(),
@@ -377,6 +448,10 @@ fn execute_json(
             total_record_count,
             delete_marked_pct,
             lob_chain,
+            node_pointers,
+            xdes_entries,
+            inode_entries,
+            ibuf_buffered_pages,
         });
     }
 
@@ -868,6 +943,220 @@ fn print_fsp_header_detail(
     Ok(())
 }
 
+/// Print the extent allocation summary and FSP list walks.
+fn print_allocation_map(
+    map: &xdes::AllocationMap,
+    page_count: u64,
+    writer: &mut dyn Write,
+) -> Result<(), IdbError> {
+    let summary = map.summary(page_count, &Default::default());
+
+    wprintln!(writer)?;
+    wprintln!(writer, "=== Extent Allocation")?;
+    wprintln!(writer, "Extent Size: {} pages", map.extent_size)?;
+    wprintln!(
+        writer,
+        "Extents: {} initialized ({} FSEG/FSEG_FRAG)",
+        summary.initialized_extents,
+        summary.segment_extents
+    )?;
+    wprintln!(
+        writer,
+        "Pages: {} used, {} free, {} uninitialized",
+        summary.used_pages,
+        summary.free_pages,
+        summary.uninitialized_pages
+    )?;
+    for walk in map.list_walks() {
+        let members = walk
+            .members
+            .iter()
+            .map(|m| m.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        wprintln!(
+            writer,
+            "{}: {} entries{}",
+            walk.name,
+            walk.base_len,
+            if members.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", members)
+            }
+        )?;
+        for err in &walk.errors {
+            wprintln!(writer, "  {}", format!("List error: {}", err).red())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the extent descriptors of page 0 or an XDES page.
+///
+/// Descriptors that are not initialized are skipped. With `verbose`, each
+/// extent's page bitmap is shown (`#` used, `.` free).
+fn print_xdes_entries_if_applicable(
+    page_data: &[u8],
+    page_size: u32,
+    extent_size: u32,
+    verbose: bool,
+    writer: &mut dyn Write,
+) -> Result<(), IdbError> {
+    let header = match FilHeader::parse(page_data) {
+        Some(h) => h,
+        None => return Ok(()),
+    };
+    if !matches!(header.page_type, PageType::FspHdr | PageType::Xdes) {
+        return Ok(());
+    }
+
+    let entries: Vec<XdesEntry> =
+        xdes::parse_xdes_page(page_data, header.page_number, page_size, extent_size)
+            .into_iter()
+            .filter(|e| e.state != XdesState::NotInited)
+            .collect();
+
+    wprintln!(writer)?;
+    wprintln!(
+        writer,
+        "=== XDES Entries: Page {} ({} initialized)",
+        header.page_number,
+        entries.len()
+    )?;
+    for e in &entries {
+        wprintln!(
+            writer,
+            "  Extent {:<6} Pages {:>8}-{:<8} {:<10} Used {:>3}/{:<3} Segment {}",
+            e.extent_no,
+            e.first_page,
+            e.first_page + extent_size - 1,
+            e.state.name(),
+            e.n_used_pages,
+            extent_size,
+            e.segment_id
+        )?;
+        if verbose {
+            wprintln!(writer, "    {}", e.page_map())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the used file segment inodes of an INODE page.
+fn print_inode_entries_if_applicable(
+    page_data: &[u8],
+    page_size: u32,
+    extent_size: u32,
+    writer: &mut dyn Write,
+) -> Result<(), IdbError> {
+    let header = match FilHeader::parse(page_data) {
+        Some(h) => h,
+        None => return Ok(()),
+    };
+    if header.page_type != PageType::Inode {
+        return Ok(());
+    }
+
+    let inodes = inode::parse_inode_page(page_data, header.page_number, page_size, extent_size);
+    wprintln!(writer)?;
+    wprintln!(
+        writer,
+        "=== FSEG Inodes: Page {} ({} used of {})",
+        header.page_number,
+        inodes.len(),
+        inode::inodes_per_page(page_size, extent_size)
+    )?;
+    for i in &inodes {
+        let frags = i
+            .frag_pages
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        wprintln!(
+            writer,
+            "  Segment {:<8} Offset {:<6} FREE {} NOT_FULL {} ({} used) FULL {} Frag [{}]",
+            i.seg_id,
+            i.offset,
+            i.free.len,
+            i.not_full.len,
+            i.not_full_n_used,
+            i.full.len,
+            frags
+        )?;
+        if !i.magic_valid {
+            wprintln!(writer, "    {}", "Invalid FSEG_MAGIC_N".red())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Print a summary of the change buffer bits of an IBUF_BITMAP page.
+fn print_ibuf_bitmap_if_applicable(
+    page_data: &[u8],
+    page_size: u32,
+    page_count: u64,
+    writer: &mut dyn Write,
+) -> Result<(), IdbError> {
+    let header = match FilHeader::parse(page_data) {
+        Some(h) => h,
+        None => return Ok(()),
+    };
+    if header.page_type != PageType::IbufBitmap {
+        return Ok(());
+    }
+
+    let entries: Vec<_> = ibuf::parse_ibuf_bitmap(page_data, page_size, header.page_number)
+        .into_iter()
+        .filter(|e| u64::from(e.page_no) < page_count)
+        .collect();
+    let mut free = [0usize; 4];
+    for e in &entries {
+        free[e.free_space as usize] += 1;
+    }
+    let buffered: Vec<String> = entries
+        .iter()
+        .filter(|e| e.buffered)
+        .map(|e| e.page_no.to_string())
+        .collect();
+    wprintln!(writer)?;
+    wprintln!(
+        writer,
+        "=== IBUF Bitmap: Page {} ({} pages tracked)",
+        header.page_number,
+        entries.len()
+    )?;
+    wprintln!(
+        writer,
+        "  Free space categories: 0={} 1={} 2={} 3={}",
+        free[0],
+        free[1],
+        free[2],
+        free[3]
+    )?;
+    wprintln!(
+        writer,
+        "  Change buffer tree pages: {}",
+        entries.iter().filter(|e| e.ibuf).count()
+    )?;
+    if buffered.is_empty() {
+        wprintln!(writer, "  Buffered changes: none")?;
+    } else {
+        wprintln!(
+            writer,
+            "  Buffered changes: {} [{}]",
+            buffered.len().to_string().yellow(),
+            buffered.join(", ")
+        )?;
+    }
+
+    Ok(())
+}
+
 /// Print LOB chain information if the given page is a BLOB/LOB first page.
 fn print_lob_chain_if_applicable(
     page_data: &[u8],
@@ -927,6 +1216,74 @@ fn print_lob_chain_if_applicable(
     Ok(())
 }
 
+/// Decode the node pointers of a non-leaf INDEX page.
+///
+/// The key layout is resolved from SDI by the page's index ID. Returns the
+/// index name and its node pointers, or `None` for leaf and non-INDEX pages
+/// and when the index has no SDI definition.
+fn node_pointers_for_page(
+    page_data: &[u8],
+    ts: &mut Tablespace,
+) -> Option<(String, Vec<NodePointer>)> {
+    let header = FilHeader::parse(page_data)?;
+    if header.page_type != PageType::Index {
+        return None;
+    }
+    let idx = IndexHeader::parse(page_data)?;
+    if idx.is_leaf() {
+        return None;
+    }
+    let layouts = crate::innodb::export::extract_index_layouts(ts)?;
+    let layout = layouts.iter().find(|l| l.index_id == idx.index_id)?;
+    Some((
+        layout.name.clone(),
+        decode_node_pointers(page_data, layout.node_pointer_columns()),
+    ))
+}
+
+/// Print node-pointer keys and child pages if the page is a non-leaf INDEX page.
+fn print_node_pointers_if_applicable(
+    page_data: &[u8],
+    ts: &mut Tablespace,
+    writer: &mut dyn Write,
+) -> Result<(), IdbError> {
+    use crate::innodb::undelete::field_value_to_sql;
+
+    let (index_name, pointers) = match node_pointers_for_page(page_data, ts) {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+    let page_no = FilHeader::parse(page_data).map_or(0, |h| h.page_number);
+
+    wprintln!(writer)?;
+    wprintln!(
+        writer,
+        "=== Node Pointers: Page {} (index {}, {} records)",
+        page_no,
+        index_name,
+        pointers.len()
+    )?;
+    for (i, ptr) in pointers.iter().enumerate() {
+        let key = ptr
+            .key
+            .iter()
+            .map(|(name, val)| format!("{}={}", name, field_value_to_sql(val)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        wprintln!(
+            writer,
+            "  [{:>3}] Offset {:<6} Child Page {:<8} ({}){}",
+            i,
+            ptr.offset,
+            ptr.child_page_no,
+            key,
+            if ptr.min_rec { " [min_rec]" } else { "" }
+        )?;
+    }
+
+    Ok(())
+}
+
 /// Check if a page type matches the user-provided filter string.
 ///
 /// Matches against the page type name (case-insensitive). Supports
@@ -0,0 +1,157 @@
+//! CLI implementation for the `inno rebuild-index` subcommand.
+//!
+//! Regenerates the leaf and non-leaf pages of a secondary index from the
+//! clustered index records, reusing the pages of the index's own file
+//! segments, and writes the result to a new file that can be imported with
+//! `ALTER TABLE ... IMPORT TABLESPACE`.
+
+use std::io::Write;
+use std::sync::Arc;
+
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::cli::wprintln;
+use crate::innodb::checksum::{validate_checksum, ChecksumAlgorithm};
+use crate::innodb::index_rebuild::{
+    index_segment_pages, read_rebuild_spec, rebuild_secondary_index, IndexRebuildResult,
+};
+use crate::innodb::write;
+use crate::util::audit::AuditLogger;
+use crate::IdbError;
+
+/// Options for the `inno rebuild-index` subcommand.
+pub struct RebuildIndexOptions {
+    /// Path to the source InnoDB tablespace file (.ibd).
+    pub file: String,
+    /// Name of the secondary index to rebuild.
+    pub index: String,
+    /// Path to output file (always creates a new file).
+    pub output: String,
+    /// Show per-page details.
+    pub verbose: bool,
+    /// Emit output as JSON.
+    pub json: bool,
+    /// Override the auto-detected page size.
+    pub page_size: Option<u32>,
+    /// Use memory-mapped I/O for file access.
+    pub mmap: bool,
+    /// Audit logger for recording write operations.
+    pub audit_logger: Option<Arc<AuditLogger>>,
+}
+
+#[derive(Serialize)]
+struct RebuildIndexReport<'a> {
+    source: String,
+    output: String,
+    #[serde(flatten)]
+    rebuild: &'a IndexRebuildResult,
+    post_validation: PostValidation,
+}
+
+#[derive(Serialize)]
+struct PostValidation {
+    total: u64,
+    valid: u64,
+}
+
+/// Rebuild a secondary index from the clustered index into a new file.
+pub fn execute(opts: &RebuildIndexOptions, writer: &mut dyn Write) -> Result<(), IdbError> {
+    let mut ts = crate::cli::open_tablespace(&opts.file, opts.page_size, opts.mmap)?;
+    if ts.is_encrypted() {
+        return Err(IdbError::Argument(
+            "Encrypted tablespaces cannot be rebuilt".to_string(),
+        ));
+    }
+    let page_size = ts.page_size();
+    let vendor_info = ts.vendor_info().clone();
+
+    let spec = read_rebuild_spec(&mut ts, &opts.index)?;
+    let segments = index_segment_pages(&mut ts, spec.index_root)?;
+
+    if !opts.json {
+        wprintln!(
+            writer,
+            "Rebuilding index {} (id {}) of {} from the clustered index...",
+            spec.index.name,
+            spec.index.index_id,
+            opts.file
+        )?;
+    }
+
+    let result = rebuild_secondary_index(&mut ts, &spec, &segments)?;
+
+    let page0 = write::read_page_raw(&opts.file, 0, page_size)?;
+    let algorithm = match write::detect_algorithm(&page0, page_size, Some(&vendor_info)) {
+        ChecksumAlgorithm::None => ChecksumAlgorithm::Crc32c,
+        algorithm => algorithm,
+    };
+
+    std::fs::copy(&opts.file, &opts.output)
+        .map_err(|e| IdbError::Io(format!("Cannot create {}: {}", opts.output, e)))?;
+    for (page_no, page) in &result.pages {
+        let mut page = page.clone();
+        write::fix_page_checksum(&mut page, page_size, algorithm);
+        write::write_page(&opts.output, u64::from(*page_no), page_size, &page)?;
+        if opts.verbose && !opts.json {
+            wprintln!(writer, "Page {:>4}: {}", page_no, "rewritten".green())?;
+        }
+    }
+    if let Some(ref logger) = opts.audit_logger {
+        let _ = logger.log_file_write(&opts.output, "rebuild-index", result.pages.len() as u64);
+    }
+
+    // Post-validate the rewritten pages
+    let mut valid_count = 0u64;
+    for (page_no, _) in &result.pages {
+        let page = write::read_page_raw(&opts.output, u64::from(*page_no), page_size)?;
+        if validate_checksum(&page, page_size, Some(&vendor_info)).valid {
+            valid_count += 1;
+        }
+    }
+    let total = result.pages.len() as u64;
+
+    if opts.json {
+        let report = RebuildIndexReport {
+            source: opts.file.clone(),
+            output: opts.output.clone(),
+            rebuild: &result,
+            post_validation: PostValidation {
+                total,
+                valid: valid_count,
+            },
+        };
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| IdbError::Parse(format!("JSON serialization error: {}", e)))?;
+        wprintln!(writer, "{}", json)?;
+    } else {
+        wprintln!(writer)?;
+        wprintln!(writer, "Rebuild Summary:")?;
+        wprintln!(writer, "  Source:          {}", opts.file)?;
+        wprintln!(writer, "  Output:          {}", opts.output)?;
+        wprintln!(
+            writer,
+            "  Index:           {} (id {}, root page {})",
+            result.index_name,
+            result.index_id,
+            result.root_page
+        )?;
+        wprintln!(writer, "  Entries:         {}", result.entries)?;
+        wprintln!(writer, "  Levels:          {}", result.levels)?;
+        wprintln!(writer, "  Leaf pages:      {}", result.leaf_pages)?;
+        wprintln!(writer, "  Non-leaf pages:  {}", result.non_leaf_pages)?;
+        if result.freed_pages > 0 {
+            wprintln!(writer, "  Freed pages:     {}", result.freed_pages)?;
+        }
+        let status = if valid_count == total {
+            format!("{}/{} valid checksums", valid_count, total)
+        } else {
+            format!("{}/{} valid checksums", valid_count, total)
+                .red()
+                .to_string()
+        };
+        wprintln!(writer, "  Post-validation: {}", status)?;
+    }
+
+    Ok(())
+}
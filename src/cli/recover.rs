@@ -480,7 +480,7 @@ pub fn execute(opts: &RecoverOptions, writer: &mut dyn Write) -> Result<(), IdbE
     }
 
     let mut corruption_patterns: Vec<(String, u64)> = pattern_counts.into_iter().collect();
-    corruption_patterns.sort_by(|a, b| b.1.cmp(&a.1));
+    corruption_patterns.sort_by_key(|b| std::cmp::Reverse(b.1));
 
     let stats = RecoverStats {
         file_size,
@@ -669,7 +669,7 @@ fn execute_streaming(
 
     // Output summary
     let mut corruption_patterns: Vec<(String, u64)> = pattern_counts.into_iter().collect();
-    corruption_patterns.sort_by(|a, b| b.1.cmp(&a.1));
+    corruption_patterns.sort_by_key(|b| std::cmp::Reverse(b.1));
 
     let stats = RecoverStats {
         file_size,
@@ -0,0 +1,621 @@
+//! CLI implementation for the `inno redo-apply` subcommand.
+//!
+//! Rolls tablespaces forward offline by replaying the page-level redo
+//! records logged after the last checkpoint, the way InnoDB crash recovery
+//! would on startup. Source files are never modified: every tablespace that
+//! receives at least one record is copied into the output directory and the
+//! rolled-forward pages are written there with fresh checksums. If any page
+//! cannot be rolled forward, nothing is written.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::cli::wprintln;
+use crate::innodb::checksum::{validate_checksum, ChecksumAlgorithm};
+use crate::innodb::datafile::{self, SystemTablespaceConfig};
+use crate::innodb::redo_apply::{
+    apply_to_tablespace, plan_redo_apply, reject_tablespace, RedoApplyPlan, TablespaceApplyResult,
+};
+use crate::innodb::redo_set::RedoLogSet;
+use crate::innodb::vendor::VendorInfo;
+use crate::innodb::write;
+use crate::util::audit::AuditLogger;
+use crate::IdbError;
+
+/// Options for the `inno redo-apply` subcommand.
+pub struct RedoApplyOptions {
+    /// MySQL data directory holding the tablespaces and redo log.
+    pub datadir: Option<String>,
+    /// Individual tablespace files to roll forward (instead of a datadir).
+    pub files: Vec<String>,
+    /// Redo log files (default: the redo log of `datadir`).
+    pub redo: Vec<String>,
+    /// Directory the rolled-forward copies are written to.
+    pub output_dir: String,
+    /// Stop at this LSN instead of the end of the log.
+    pub to_lsn: Option<u64>,
+    /// Show per-page details.
+    pub verbose: bool,
+    /// Emit output as JSON.
+    pub json: bool,
+    /// Override the auto-detected page size.
+    pub page_size: Option<u32>,
+    /// Maximum directory recursion depth.
+    pub depth: Option<u32>,
+    /// Use memory-mapped I/O for file access.
+    pub mmap: bool,
+    /// Audit logger for recording write operations.
+    pub audit_logger: Option<Arc<AuditLogger>>,
+}
+
+#[derive(Serialize)]
+struct RedoApplyReport<'a> {
+    redo_files: &'a [String],
+    checkpoint_lsn: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to_lsn: Option<u64>,
+    decode_errors: usize,
+    plan: &'a RedoApplyPlan,
+    tablespaces: &'a [TablespaceReport],
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    missing_spaces: Vec<u32>,
+}
+
+#[derive(Serialize)]
+struct TablespaceReport {
+    source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unsupported: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(flatten)]
+    result: Option<TablespaceApplyResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_validation: Option<PostValidation>,
+}
+
+#[derive(Serialize)]
+struct PostValidation {
+    total: u64,
+    valid: u64,
+}
+
+/// The files of one tablespace, in page order, with the paths of their
+/// copies below the output directory. Only a system tablespace spans more
+/// than one file.
+struct Target {
+    files: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Target {
+    /// The source files joined with `;`, as accepted by `open_tablespace`.
+    fn source(&self) -> String {
+        self.files
+            .iter()
+            .map(|(source, _)| source.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+}
+
+/// Replay the redo log against a set of tablespaces into an output directory.
+pub fn execute(opts: &RedoApplyOptions, writer: &mut dyn Write) -> Result<(), IdbError> {
+    let datadir = opts.datadir.as_deref().map(Path::new);
+    if let Some(dir) = datadir {
+        if !dir.is_dir() {
+            return Err(IdbError::Argument(format!(
+                "Data directory does not exist: {}",
+                dir.display()
+            )));
+        }
+    }
+
+    let mut redo_set = match (opts.redo.is_empty(), datadir) {
+        (false, _) => RedoLogSet::open_files(&opts.redo)?,
+        (true, Some(dir)) => RedoLogSet::open_dir(&dir.to_string_lossy())?,
+        (true, None) => {
+            return Err(IdbError::Argument(
+                "--redo is required when --datadir is not given".to_string(),
+            ))
+        }
+    };
+    if redo_set.mariadb_10_8_member().is_some() {
+        return Err(IdbError::Argument(
+            "MariaDB 10.8+ redo logs are not supported by redo-apply".to_string(),
+        ));
+    }
+    let redo_files: Vec<String> = redo_set
+        .members()
+        .iter()
+        .filter(|m| !m.spare)
+        .map(|m| m.path.clone())
+        .collect();
+
+    // Decode the redo files as one LSN-ordered record stream
+    let checkpoint_lsn = redo_set.checkpoint_lsn().unwrap_or(0);
+    let decoded = redo_set.read_mlog_records()?;
+    let decode_errors = decoded.errors.len();
+    let records = decoded.records;
+
+    let plan = plan_redo_apply(&records, checkpoint_lsn, opts.to_lsn);
+
+    if !opts.json {
+        wprintln!(
+            writer,
+            "Replaying {} redo file(s) from checkpoint LSN {}...",
+            redo_files.len(),
+            checkpoint_lsn
+        )?;
+    }
+
+    let targets = find_targets(opts, datadir)?;
+    let output_dir = Path::new(&opts.output_dir);
+    let mut reports = Vec::new();
+    let mut found_spaces = Vec::new();
+    // (report index, target, page size, vendor) of tablespaces to write
+    let mut pending = Vec::new();
+
+    for target in &targets {
+        let source = target.source();
+        let mut report = TablespaceReport {
+            source: source.clone(),
+            output: None,
+            unsupported: None,
+            error: None,
+            result: None,
+            post_validation: None,
+        };
+        let mut ts = match crate::cli::open_tablespace(&source, opts.page_size, opts.mmap) {
+            Ok(ts) => ts,
+            Err(e) => {
+                report.error = Some(e.to_string());
+                reports.push(report);
+                continue;
+            }
+        };
+        let Some(space_id) = ts.fsp_header().map(|h| h.space_id) else {
+            continue;
+        };
+        if !plan.pages.contains_key(&space_id) {
+            continue;
+        }
+        found_spaces.push(space_id);
+
+        let unsupported = if ts.is_encrypted() {
+            Some("encrypted tablespace")
+        } else if ts.zip_size().is_some() {
+            Some("ROW_FORMAT=COMPRESSED tablespace")
+        } else {
+            None
+        };
+        let result = match unsupported {
+            Some(reason) => {
+                report.unsupported = Some(reason.to_string());
+                reject_tablespace(&mut ts, &plan, space_id, reason)
+            }
+            None => apply_to_tablespace(&mut ts, &plan, space_id),
+        };
+        match result {
+            Ok(result) => {
+                if !result.changed.is_empty() {
+                    pending.push((
+                        reports.len(),
+                        target,
+                        ts.page_size(),
+                        ts.vendor_info().clone(),
+                    ));
+                }
+                report.result = Some(result);
+            }
+            Err(e) => report.error = Some(e.to_string()),
+        }
+        reports.push(report);
+    }
+
+    // A page that could not be rolled forward leaves the other pages of its
+    // mini-transactions inconsistent, and an unreadable tablespace may hold
+    // such pages, so nothing is written
+    let pages_failed = reports
+        .iter()
+        .filter_map(|r| r.result.as_ref())
+        .map(|r| r.pages_failed)
+        .sum::<u64>();
+    let targets_failed = reports.iter().filter(|r| r.error.is_some()).count();
+    if pages_failed == 0 && targets_failed == 0 {
+        for (index, target, page_size, vendor_info) in pending {
+            let report = &mut reports[index];
+            let Some(ref result) = report.result else {
+                continue;
+            };
+            let (output, post_validation) = write_target(
+                target,
+                output_dir,
+                result,
+                page_size,
+                &vendor_info,
+                opts.audit_logger.as_deref(),
+            )?;
+            report.output = Some(output);
+            report.post_validation = Some(post_validation);
+        }
+    }
+
+    let missing_spaces: Vec<u32> = plan
+        .space_ids()
+        .into_iter()
+        .filter(|id| !found_spaces.contains(id))
+        .collect();
+
+    if opts.json {
+        let report = RedoApplyReport {
+            redo_files: &redo_files,
+            checkpoint_lsn,
+            to_lsn: opts.to_lsn,
+            decode_errors,
+            plan: &plan,
+            tablespaces: &reports,
+            missing_spaces,
+        };
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| IdbError::Parse(format!("JSON serialization error: {}", e)))?;
+        wprintln!(writer, "{}", json)?;
+        return check_failed(pages_failed, targets_failed);
+    }
+
+    for report in &reports {
+        print_tablespace(writer, report, opts.verbose)?;
+    }
+
+    wprintln!(writer)?;
+    wprintln!(writer, "Redo Apply Summary:")?;
+    wprintln!(writer, "  Checkpoint LSN:     {}", checkpoint_lsn)?;
+    wprintln!(writer, "  Replayed to LSN:    {}", plan.end_lsn)?;
+    wprintln!(
+        writer,
+        "  Mini-transactions:  {} replayed, {} before checkpoint, {} after target",
+        plan.mtr_count,
+        plan.mtrs_before,
+        plan.mtrs_after
+    )?;
+    wprintln!(
+        writer,
+        "  Page records:       {} on {} page(s)",
+        plan.record_count(),
+        plan.page_count()
+    )?;
+    let sum = |f: fn(&TablespaceApplyResult) -> u64| -> u64 {
+        reports
+            .iter()
+            .filter_map(|r| r.result.as_ref())
+            .map(f)
+            .sum()
+    };
+    wprintln!(
+        writer,
+        "  Pages:              {} rolled forward, {} already current, {} failed",
+        sum(|r| r.pages_changed),
+        sum(|r| r.pages_current),
+        sum(|r| r.pages_failed)
+    )?;
+    let written = reports.iter().filter(|r| r.output.is_some()).count();
+    wprintln!(
+        writer,
+        "  Files written:      {} (in {})",
+        written,
+        opts.output_dir
+    )?;
+    let (valid, total) = reports
+        .iter()
+        .filter_map(|r| r.post_validation.as_ref())
+        .fold((0, 0), |(v, t), p| (v + p.valid, t + p.total));
+    if total > 0 {
+        let status = format!("{}/{} valid checksums", valid, total);
+        let status = if valid == total {
+            status
+        } else {
+            status.red().to_string()
+        };
+        wprintln!(writer, "  Post-validation:    {}", status)?;
+    }
+
+    if decode_errors > 0 {
+        wprintln!(
+            writer,
+            "  {} {} corrupt redo record(s) were skipped",
+            "Warning:".yellow(),
+            decode_errors
+        )?;
+    }
+    if plan.mtrs_broken > 0 {
+        wprintln!(
+            writer,
+            "  {} {} incomplete mini-transaction(s) were discarded",
+            "Warning:".yellow(),
+            plan.mtrs_broken
+        )?;
+    }
+    if let Some(lsn) = plan.incomplete_at {
+        wprintln!(
+            writer,
+            "  {} log ends inside a mini-transaction at LSN {}; it was not replayed",
+            "Note:".cyan(),
+            lsn
+        )?;
+    }
+    if !plan.index_loads.is_empty() {
+        let ids: Vec<String> = plan.index_loads.iter().map(u64::to_string).collect();
+        wprintln!(
+            writer,
+            "  {} index(es) {} were bulk-loaded without redo logging and may be incomplete",
+            "Warning:".yellow(),
+            ids.join(", ")
+        )?;
+    }
+    if !missing_spaces.is_empty() {
+        let ids: Vec<String> = missing_spaces.iter().map(u32::to_string).collect();
+        wprintln!(
+            writer,
+            "  {} no tablespace file found for space ID(s) {}",
+            "Warning:".yellow(),
+            ids.join(", ")
+        )?;
+    }
+
+    check_failed(pages_failed, targets_failed)
+}
+
+/// Refuse a replay in which any page could not be rolled forward or any
+/// tablespace could not be read.
+fn check_failed(pages_failed: u64, targets_failed: usize) -> Result<(), IdbError> {
+    let mut failures = Vec::new();
+    if pages_failed > 0 {
+        failures.push(format!(
+            "{} page(s) could not be rolled forward",
+            pages_failed
+        ));
+    }
+    if targets_failed > 0 {
+        failures.push(format!(
+            "{} tablespace(s) could not be read",
+            targets_failed
+        ));
+    }
+    if failures.is_empty() {
+        return Ok(());
+    }
+    Err(IdbError::Parse(format!(
+        "{}; no files were written",
+        failures.join(" and ")
+    )))
+}
+
+/// Copy a target's files into the output directory and write its
+/// rolled-forward pages there, each to the file that holds it. Returns the
+/// output path and the checksum validation of the written pages.
+fn write_target(
+    target: &Target,
+    output_dir: &Path,
+    result: &TablespaceApplyResult,
+    page_size: u32,
+    vendor_info: &VendorInfo,
+    audit_logger: Option<&AuditLogger>,
+) -> Result<(String, PostValidation), IdbError> {
+    // (output path, first page, page count) of each file
+    let mut segments: Vec<(String, u64, u64)> = Vec::with_capacity(target.files.len());
+    let mut first_page = 0u64;
+    for (source, relative) in &target.files {
+        let output = output_dir.join(relative);
+        if output.exists() && same_file(&output, source) {
+            return Err(IdbError::Argument(format!(
+                "Output {} would overwrite its source",
+                output.display()
+            )));
+        }
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| IdbError::Io(format!("Cannot create {}: {}", parent.display(), e)))?;
+        }
+        let size = std::fs::copy(source, &output)
+            .map_err(|e| IdbError::Io(format!("Cannot create {}: {}", output.display(), e)))?;
+        let pages = size / u64::from(page_size);
+        segments.push((output.to_string_lossy().to_string(), first_page, pages));
+        first_page += pages;
+    }
+    // Pages past the end of the last file extend it
+    let owner = |page_no: u32| -> (&str, u64) {
+        let page_no = u64::from(page_no);
+        let (path, start, _) = segments
+            .iter()
+            .find(|(_, start, pages)| page_no < start + pages)
+            .unwrap_or(&segments[segments.len() - 1]);
+        (path.as_str(), page_no - start)
+    };
+
+    let page0 = write::read_page_raw(&segments[0].0, 0, page_size)?;
+    let algorithm = match write::detect_algorithm(&page0, page_size, Some(vendor_info)) {
+        ChecksumAlgorithm::None => ChecksumAlgorithm::Crc32c,
+        algorithm => algorithm,
+    };
+
+    for (page_no, page) in &result.changed {
+        let mut page = page.clone();
+        write::fix_page_checksum(&mut page, page_size, algorithm);
+        let (path, local) = owner(*page_no);
+        write::write_page(path, local, page_size, &page)?;
+    }
+    let output = segments
+        .iter()
+        .map(|(path, _, _)| path.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    if let Some(logger) = audit_logger {
+        let _ = logger.log_file_write(&output, "redo-apply", result.changed.len() as u64);
+    }
+
+    // Post-validate the rewritten pages
+    let mut valid = 0u64;
+    for (page_no, _) in &result.changed {
+        let (path, local) = owner(*page_no);
+        let page = write::read_page_raw(path, local, page_size)?;
+        if validate_checksum(&page, page_size, Some(vendor_info)).valid {
+            valid += 1;
+        }
+    }
+    let total = result.changed.len() as u64;
+    Ok((output, PostValidation { total, valid }))
+}
+
+fn print_tablespace(
+    writer: &mut dyn Write,
+    report: &TablespaceReport,
+    verbose: bool,
+) -> Result<(), IdbError> {
+    let result = match (&report.error, &report.result) {
+        (Some(error), _) => {
+            wprintln!(writer, "{}: {}", report.source, error.red())?;
+            return Ok(());
+        }
+        (None, Some(result)) => result,
+        (None, None) => return Ok(()),
+    };
+    if let Some(ref reason) = report.unsupported {
+        wprintln!(
+            writer,
+            "{} (space {}): {} ({}), {}",
+            report.source,
+            result.space_id,
+            "unsupported".red(),
+            reason,
+            format!("{} page(s) failed", result.pages_failed).red()
+        )?;
+        return Ok(());
+    }
+
+    let mut status = format!(
+        "{} page(s) rolled forward, {} current",
+        result.pages_changed, result.pages_current
+    );
+    if result.pages_failed > 0 {
+        status.push_str(&format!(
+            ", {}",
+            format!("{} failed", result.pages_failed).red()
+        ));
+    }
+    wprintln!(
+        writer,
+        "{} (space {}): {}",
+        report.source,
+        result.space_id,
+        status
+    )?;
+    if let Some(ref output) = report.output {
+        wprintln!(writer, "  -> {}", output)?;
+    }
+
+    for page in &result.pages {
+        if let Some(ref error) = page.error {
+            wprintln!(writer, "  Page {:>6}: {}", page.page_no, error.red())?;
+        } else if verbose && page.applied > 0 {
+            wprintln!(
+                writer,
+                "  Page {:>6}: LSN {} -> {} ({} record(s){})",
+                page.page_no,
+                page.old_lsn,
+                page.new_lsn,
+                page.applied,
+                if page.extended { ", new page" } else { "" }
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Tablespaces to consider, with their paths relative to the output
+/// directory.
+///
+/// A `--file` may list the data files of a multi-file system tablespace
+/// separated by `;`. In a data directory, the system tablespace is
+/// `ibdata1`, `ibdata2`, ... opened as one page space.
+fn find_targets(opts: &RedoApplyOptions, datadir: Option<&Path>) -> Result<Vec<Target>, IdbError> {
+    let Some(dir) = datadir.filter(|_| opts.files.is_empty()) else {
+        // Each file is copied to the top of the output directory
+        let mut outputs: HashMap<PathBuf, &str> = HashMap::new();
+        let mut targets = Vec::with_capacity(opts.files.len());
+        for f in &opts.files {
+            let mut files = Vec::new();
+            for p in f.split(';').filter(|p| !p.is_empty()) {
+                let source = PathBuf::from(p);
+                let relative = file_name(&source);
+                if let Some(other) = outputs.insert(relative.clone(), p) {
+                    return Err(IdbError::Argument(format!(
+                        "{} and {} would both be written to {}",
+                        other,
+                        p,
+                        relative.display()
+                    )));
+                }
+                files.push((source, relative));
+            }
+            targets.push(Target { files });
+        }
+        return Ok(targets);
+    };
+    let relative = |source: &Path| {
+        source
+            .strip_prefix(dir)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|_| file_name(source))
+    };
+
+    let mut files = crate::util::fs::find_tablespace_files(dir, &["ibd", "ibu"], opts.depth)?;
+    // Undo tablespaces without a file extension
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("undo_") && !name.contains('.') && entry.path().is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+    files.sort();
+    files.dedup();
+
+    let mut targets = Vec::with_capacity(files.len() + 1);
+    if let Ok(data_files) = datafile::system_data_files(dir, &SystemTablespaceConfig::default()) {
+        targets.push(Target {
+            files: data_files
+                .into_iter()
+                .map(|df| {
+                    let rel = relative(&df.path);
+                    (df.path, rel)
+                })
+                .collect(),
+        });
+    }
+    targets.extend(files.into_iter().map(|source| {
+        let rel = relative(&source);
+        Target {
+            files: vec![(source, rel)],
+        }
+    }));
+    Ok(targets)
+}
+
+fn file_name(path: &Path) -> PathBuf {
+    PathBuf::from(path.file_name().unwrap_or(path.as_os_str()))
+}
+
+/// Whether two paths name the same file.
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
@@ -3,9 +3,11 @@
 use std::io::Write;
 
 use crate::cli::{csv_escape, wprintln};
+use crate::innodb::keyring::Keyring;
+use crate::innodb::redo_set::RedoLogSet;
 use crate::innodb::timeline::{
-    extract_binlog_timeline, extract_redo_timeline, extract_undo_timeline, merge_timeline,
-    TimelineAction, TimelineReport,
+    extract_binlog_timeline, extract_redo_set_timeline, extract_redo_timeline,
+    extract_undo_timeline, merge_timeline, TimelineAction, TimelineReport,
 };
 use crate::IdbError;
 
@@ -35,8 +37,17 @@ pub fn execute(opts: &TimelineOptions, writer: &mut dyn Write) -> Result<(), Idb
 
     // Extract from each source
     let redo_entries = if let Some(ref path) = opts.redo_log {
-        let mut log = crate::innodb::log::LogFile::open(path)?;
-        extract_redo_timeline(&mut log)?
+        let keyring = opts.keyring.as_deref().map(Keyring::load).transpose()?;
+        if std::path::Path::new(path).is_dir() {
+            let mut set = RedoLogSet::open_dir_with_keyring(path, keyring.as_ref())?;
+            extract_redo_set_timeline(&mut set)?
+        } else {
+            let mut log = crate::innodb::log::LogFile::open(path)?;
+            if let Some(ref keyring) = keyring {
+                log.enable_decryption(keyring)?;
+            }
+            extract_redo_timeline(&mut log)?
+        }
     } else {
         Vec::new()
     };
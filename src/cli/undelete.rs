@@ -63,6 +63,8 @@ pub struct UndeleteOptions {
     pub keyring: Option<String>,
     /// Use memory-mapped I/O.
     pub mmap: bool,
+    /// Maximum size in bytes of a reassembled off-page column value.
+    pub max_lob_size: usize,
 }
 
 /// Execute the undelete subcommand.
@@ -90,6 +92,7 @@ pub fn execute(opts: &UndeleteOptions, writer: &mut dyn Write) -> Result<(), Idb
         opts.confidence,
         opts.min_trx_id,
         opts.page,
+        opts.max_lob_size,
     )?;
 
     // Filter by table name if requested
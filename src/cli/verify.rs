@@ -3,7 +3,9 @@
 //! Runs pure structural checks on a tablespace file without requiring
 //! valid checksums. Checks page number sequence, space ID consistency,
 //! LSN monotonicity, B+Tree level validity, page chain bounds, and
-//! trailer LSN matching.
+//! trailer LSN matching. Optionally cross-checks row geometries against
+//! spatial index MBRs and secondary index entries against the clustered
+//! index.
 
 use std::io::Write;
 
@@ -37,6 +39,10 @@ pub struct VerifyOptions {
     pub chain: Vec<String>,
     /// Path to XtraBackup checkpoint file for LSN cross-reference.
     pub backup_meta: Option<String>,
+    /// Cross-check row geometries against spatial index MBRs.
+    pub spatial: bool,
+    /// Cross-check secondary index entries against the clustered index.
+    pub indexes: bool,
 }
 
 /// Combined JSON output for verify with redo and/or chain.
@@ -50,6 +56,10 @@ struct FullVerifyReport {
     chain: Option<ChainReport>,
     #[serde(skip_serializing_if = "Option::is_none")]
     backup_meta: Option<crate::innodb::verify::BackupMetaVerifyResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spatial: Option<crate::innodb::verify::SpatialVerifyResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    indexes: Option<crate::innodb::verify::IndexConsistencyResult>,
 }
 
 /// Run structural verification on a tablespace file.
@@ -84,13 +94,25 @@ pub fn execute(opts: &VerifyOptions, writer: &mut dyn Write) -> Result<(), IdbEr
         0
     };
 
-    let config = VerifyConfig::default();
+    // Index layouts from SDI enable the record order check
+    let config = VerifyConfig {
+        index_layouts: crate::innodb::export::extract_index_layouts(&mut ts).unwrap_or_default(),
+        ..VerifyConfig::default()
+    };
     let report = verify_tablespace(&all_pages, page_size, space_id, &opts.file, &config);
 
     // Redo log continuity check
     let redo_result = if let Some(ref redo_path) = opts.redo {
-        Some(crate::innodb::verify::verify_redo_continuity(
-            redo_path, &all_pages, page_size,
+        let keyring = opts
+            .keyring
+            .as_deref()
+            .map(crate::innodb::keyring::Keyring::load)
+            .transpose()?;
+        Some(crate::innodb::verify::verify_redo_continuity_with_keyring(
+            redo_path,
+            keyring.as_ref(),
+            &all_pages,
+            page_size,
         )?)
     } else {
         None
@@ -105,9 +127,23 @@ pub fn execute(opts: &VerifyOptions, writer: &mut dyn Write) -> Result<(), IdbEr
         None
     };
 
+    // Spatial index MBR cross-check
+    let spatial_result = if opts.spatial {
+        Some(crate::innodb::verify::verify_spatial_mbrs(&mut ts)?)
+    } else {
+        None
+    };
+
+    // Secondary index consistency
+    let indexes_result = if opts.indexes {
+        Some(crate::innodb::verify::verify_secondary_indexes(&mut ts)?)
+    } else {
+        None
+    };
+
     let mut overall_passed = report.passed;
     if let Some(ref redo) = redo_result {
-        if !redo.covers_tablespace {
+        if !redo.covers_tablespace || !redo.gaps_after_checkpoint.is_empty() {
             overall_passed = false;
         }
     }
@@ -116,6 +152,16 @@ pub fn execute(opts: &VerifyOptions, writer: &mut dyn Write) -> Result<(), IdbEr
             overall_passed = false;
         }
     }
+    if let Some(ref spatial) = spatial_result {
+        if !spatial.passed {
+            overall_passed = false;
+        }
+    }
+    if let Some(ref indexes) = indexes_result {
+        if !indexes.passed {
+            overall_passed = false;
+        }
+    }
 
     if opts.json {
         let full = FullVerifyReport {
@@ -123,6 +169,8 @@ pub fn execute(opts: &VerifyOptions, writer: &mut dyn Write) -> Result<(), IdbEr
             redo: redo_result,
             chain: None,
             backup_meta: backup_meta_result,
+            spatial: spatial_result,
+            indexes: indexes_result,
         };
         let json = serde_json::to_string_pretty(&full)
             .map_err(|e| IdbError::Parse(format!("JSON serialization error: {}", e)))?;
@@ -169,6 +217,13 @@ pub fn execute(opts: &VerifyOptions, writer: &mut dyn Write) -> Result<(), IdbEr
             wprintln!(writer)?;
         }
 
+        for w in &report.warnings {
+            wprintln!(writer, "  {} {}", "Warning:".yellow(), w)?;
+        }
+        if !report.warnings.is_empty() {
+            wprintln!(writer)?;
+        }
+
         // Redo log continuity
         if let Some(ref redo) = redo_result {
             wprintln!(writer, "  Redo Log Continuity:")?;
@@ -181,6 +236,33 @@ pub fn execute(opts: &VerifyOptions, writer: &mut dyn Write) -> Result<(), IdbEr
                 format!("{} (gap: {} bytes)", "FAIL".red(), redo.lsn_gap)
             };
             wprintln!(writer, "    Covers tablespace: {}", redo_status)?;
+            if !redo.redo_files.is_empty() {
+                let stream_status = if redo.gaps_after_checkpoint.is_empty() {
+                    "PASS".green().to_string()
+                } else {
+                    "FAIL".red().to_string()
+                };
+                wprintln!(writer, "    Redo files:       {}", redo.redo_files.len())?;
+                wprintln!(writer, "    Contiguous log:   {}", stream_status)?;
+                for gap in &redo.gaps_after_checkpoint {
+                    wprintln!(
+                        writer,
+                        "      missing LSN {}..{}",
+                        gap.start_lsn,
+                        gap.end_lsn
+                    )?;
+                }
+                for o in &redo.overlaps {
+                    wprintln!(
+                        writer,
+                        "      {} LSN {}..{} is also in {}",
+                        "Warning:".yellow(),
+                        o.start_lsn,
+                        o.end_lsn,
+                        o.duplicate
+                    )?;
+                }
+            }
             wprintln!(writer)?;
         }
 
@@ -234,6 +316,86 @@ pub fn execute(opts: &VerifyOptions, writer: &mut dyn Write) -> Result<(), IdbEr
             wprintln!(writer)?;
         }
 
+        // Spatial index MBR cross-check
+        if let Some(ref spatial) = spatial_result {
+            wprintln!(writer, "  Spatial Index MBRs:")?;
+            if spatial.indexes.is_empty() {
+                wprintln!(writer, "    No spatial indexes found")?;
+            }
+            for idx in &spatial.indexes {
+                wprintln!(
+                    writer,
+                    "    Index:           {} ({}, id {})",
+                    idx.name,
+                    idx.column,
+                    idx.index_id
+                )?;
+            }
+            wprintln!(writer, "    Rows checked:    {}", spatial.rows_checked)?;
+            let spatial_status = if spatial.passed {
+                "PASS".green().to_string()
+            } else {
+                format!("{} ({} rows)", "FAIL".red(), spatial.issues.len())
+            };
+            wprintln!(writer, "    Status:          {}", spatial_status)?;
+
+            if opts.verbose {
+                for issue in &spatial.issues {
+                    wprintln!(
+                        writer,
+                        "      Page {:>4} [{}] PK ({}): {}",
+                        issue.page_number,
+                        issue.index_name,
+                        issue.primary_key,
+                        issue.message
+                    )?;
+                }
+            }
+            wprintln!(writer)?;
+        }
+
+        // Secondary index consistency
+        if let Some(ref indexes) = indexes_result {
+            wprintln!(writer, "  Secondary Indexes:")?;
+            if indexes.indexes.is_empty() {
+                wprintln!(writer, "    No secondary indexes found")?;
+            }
+            for idx in &indexes.indexes {
+                wprintln!(
+                    writer,
+                    "    Index:           {} (id {}): {} entries, {} orphan, {} missing, {} duplicate",
+                    idx.name,
+                    idx.index_id,
+                    idx.entries_checked,
+                    idx.orphan_entries,
+                    idx.missing_entries,
+                    idx.duplicate_entries
+                )?;
+            }
+            wprintln!(writer, "    Rows checked:    {}", indexes.rows_checked)?;
+            let indexes_status = if indexes.passed {
+                "PASS".green().to_string()
+            } else {
+                format!("{} ({} issues)", "FAIL".red(), indexes.issues.len())
+            };
+            wprintln!(writer, "    Status:          {}", indexes_status)?;
+
+            if opts.verbose {
+                for issue in &indexes.issues {
+                    wprintln!(
+                        writer,
+                        "      Page {:>4} [{}] {} PK ({}): {}",
+                        issue.page_number,
+                        issue.index_name,
+                        issue.kind,
+                        issue.primary_key,
+                        issue.message
+                    )?;
+                }
+            }
+            wprintln!(writer)?;
+        }
+
         let overall = if overall_passed {
             "PASS".green().to_string()
         } else {
@@ -654,7 +654,7 @@ mod tests {
         BigEndian::write_u32(&mut page[fsp + FSP_FREE_LIMIT..], total_pages);
         BigEndian::write_u32(&mut page[fsp + FSP_SPACE_FLAGS..], 0);
         let trailer = PS - SIZE_FIL_TRAILER;
-        BigEndian::write_u32(&mut page[trailer + 4..], 1000 & 0xFFFFFFFF);
+        BigEndian::write_u32(&mut page[trailer + 4..], 1000);
         let end = PS - SIZE_FIL_TRAILER;
         let crc1 = crc32c::crc32c(&page[FIL_PAGE_OFFSET..FIL_PAGE_FILE_FLUSH_LSN]);
         let crc2 = crc32c::crc32c(&page[FIL_PAGE_DATA..end]);
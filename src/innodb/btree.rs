@@ -11,17 +11,28 @@
 //! descends through non-leaf (node pointer) pages by comparing the search
 //! key against record keys on each level. At each non-leaf page, the child
 //! pointer of the last record whose key is <= the search key is followed.
-//! The traversal terminates when a leaf page (level 0) is reached.
+//! The traversal terminates when a leaf page (level 0) is reached. Within a
+//! page, the page directory is binary-searched first, so only the records
+//! owned by one directory slot are compared linearly.
+//!
+//! [`decode_node_pointers`] exposes the node-pointer records of a single
+//! non-leaf page (key prefix and child page number) so a descent can be
+//! inspected by hand.
 
 use byteorder::{BigEndian, ByteOrder};
 use serde::Serialize;
 
-use crate::innodb::export::extract_column_layout;
+use crate::innodb::constants::{PAGE_NEW_INFIMUM, PAGE_NEW_SUPREMUM};
+use crate::innodb::export::{extract_column_layout, read_record_fields, RecordField};
 use crate::innodb::field_decode::{decode_field, ColumnStorageInfo, FieldValue};
 use crate::innodb::index::IndexHeader;
 use crate::innodb::page::FilHeader;
+use crate::innodb::page_dir::{read_dir_slots, search_directory};
 use crate::innodb::page_types::PageType;
-use crate::innodb::record::{read_variable_field_lengths, walk_compact_records, RecordType};
+use crate::innodb::record::{
+    next_record_origin, read_record_header, read_variable_field_lengths, walk_compact_records,
+    walk_records, RecordInfo, RecordType,
+};
 use crate::innodb::sdi;
 use crate::innodb::tablespace::Tablespace;
 use crate::IdbError;
@@ -54,6 +65,22 @@ pub struct BtreeSearchResult {
     pub levels_traversed: u16,
 }
 
+/// A decoded node-pointer record from a non-leaf INDEX page.
+#[derive(Debug, Clone, Serialize)]
+pub struct NodePointer {
+    /// Absolute byte offset of the record origin within the page.
+    pub offset: usize,
+    /// Whether this is the leftmost record on its level (`REC_INFO_MIN_REC_FLAG`).
+    /// Its key is ignored during search; it covers everything below the next key.
+    pub min_rec: bool,
+    /// Delete mark of the record.
+    pub delete_mark: bool,
+    /// Decoded key prefix as `(column_name, value)` pairs.
+    pub key: Vec<(String, FieldValue)>,
+    /// Page number of the child page this record points to.
+    pub child_page_no: u32,
+}
+
 /// Extract clustered index info from a tablespace's SDI metadata.
 ///
 /// Returns `(root_page_no, index_id, pk_columns)` where `pk_columns` contains
@@ -206,7 +233,7 @@ pub fn search_btree(
         }
 
         // Non-leaf page: find the child pointer to follow
-        let records = walk_compact_records(&page_data);
+        let records = candidate_records(&page_data, pk_columns, search_key);
 
         // Default to the leftmost child (from the min_rec record)
         let mut child_page: Option<u32> = None;
@@ -258,6 +285,143 @@ pub fn search_btree(
     }
 }
 
+/// Node-pointer records of a non-leaf page that can hold the search key's
+/// child pointer.
+///
+/// Binary-searches the page directory for the last slot whose owner record
+/// is <= the search key and returns the records from that owner up to the
+/// supremum, so the caller's linear scan starts at most one slot (8 records)
+/// before the key. Falls back to the whole record list when the directory
+/// does not point to the infimum and supremum.
+fn candidate_records(
+    page_data: &[u8],
+    pk_columns: &[ColumnStorageInfo],
+    search_key: &[PkValue],
+) -> Vec<RecordInfo> {
+    let slots = match read_dir_slots(page_data) {
+        Some(s)
+            if s.len() >= 2 && s[0] == PAGE_NEW_INFIMUM && s[s.len() - 1] == PAGE_NEW_SUPREMUM =>
+        {
+            s
+        }
+        _ => return walk_compact_records(page_data),
+    };
+
+    let slot = search_directory(&slots, |offset| {
+        match read_record_header(page_data, offset, true) {
+            // The min_rec record is smaller than any key
+            Some(h) if h.min_rec() => std::cmp::Ordering::Less,
+            Some(_) => compare_record_key(page_data, offset, pk_columns, search_key),
+            None => std::cmp::Ordering::Greater,
+        }
+    });
+
+    let mut records = Vec::new();
+    let mut offset = slots[slot];
+    let mut header = match read_record_header(page_data, offset, true) {
+        Some(h) => h,
+        None => return walk_compact_records(page_data),
+    };
+    if slot > 0 {
+        records.push(RecordInfo {
+            offset,
+            header: header.clone(),
+        });
+    }
+    while records.len() < page_data.len() {
+        offset = match next_record_origin(page_data, offset, &header) {
+            Some(o) => o,
+            None => break,
+        };
+        header = match read_record_header(page_data, offset, true) {
+            Some(h) if h.rec_type() != RecordType::Supremum => h,
+            _ => break,
+        };
+        records.push(RecordInfo {
+            offset,
+            header: header.clone(),
+        });
+    }
+    records
+}
+
+/// Decode the node-pointer records of a non-leaf INDEX page.
+///
+/// Each node pointer stores the key prefix of the first record on its child
+/// page followed by a 4-byte child page number. `layout` lists the key
+/// fields in physical order, e.g. from
+/// [`IndexLayout::node_pointer_columns`](crate::innodb::export::IndexLayout::node_pointer_columns).
+/// Compact and redundant row formats are both supported.
+///
+/// Records of other types (leaf records, infimum/supremum) and records whose
+/// fields cannot be located are skipped, so a leaf page yields an empty list.
+pub fn decode_node_pointers(page_data: &[u8], layout: &[ColumnStorageInfo]) -> Vec<NodePointer> {
+    let compact = IndexHeader::parse(page_data).is_none_or(|h| h.is_compact());
+
+    // The child page number is read as one more fixed 4-byte field
+    let mut fields_layout = layout.to_vec();
+    fields_layout.push(ColumnStorageInfo {
+        name: "child_page_no".to_string(),
+        dd_type: 0,
+        column_type: "system".to_string(),
+        is_nullable: false,
+        is_unsigned: true,
+        fixed_len: 4,
+        is_variable: false,
+        max_len: 4,
+        version_added: 0,
+        version_dropped: 0,
+        instant_default: None,
+        charset_max_bytes: 0,
+        collation_id: 0,
+        datetime_precision: 0,
+        is_system_column: true,
+        elements: Vec::new(),
+        numeric_precision: 0,
+        numeric_scale: 0,
+    });
+
+    let mut pointers = Vec::new();
+    for rec in walk_records(page_data) {
+        if rec.header.rec_type() != RecordType::NodePtr {
+            continue;
+        }
+        let fields = match read_record_fields(page_data, rec.offset, &fields_layout, compact) {
+            Some(f) => f,
+            None => continue,
+        };
+        let (child_start, _) = fields[layout.len()];
+        let child_page_no = match page_data.get(child_start..child_start + 4) {
+            Some(b) => BigEndian::read_u32(b),
+            None => continue,
+        };
+
+        let key = layout
+            .iter()
+            .zip(&fields)
+            .map(|(col, (start, field))| {
+                let value = match field {
+                    RecordField::Stored { len, .. } => page_data
+                        .get(*start..*start + *len)
+                        .map(|d| decode_field(d, col))
+                        .unwrap_or(FieldValue::Null),
+                    RecordField::Null | RecordField::Absent => FieldValue::Null,
+                };
+                (col.name.clone(), value)
+            })
+            .collect();
+
+        pointers.push(NodePointer {
+            offset: rec.offset,
+            min_rec: rec.header.min_rec(),
+            delete_mark: rec.header.delete_mark(),
+            key,
+            child_page_no,
+        });
+    }
+    pointers
+}
+
 /// Compare the primary key stored in a record against a search key.
 ///
 /// Decodes PK column values from the record at the given offset and
@@ -434,7 +598,12 @@ mod tests {
             is_unsigned: unsigned,
             fixed_len: 4,
             is_variable: false,
+            max_len: 4,
+            version_added: 0,
+            version_dropped: 0,
+            instant_default: None,
             charset_max_bytes: 0,
+            collation_id: 0,
             datetime_precision: 0,
             is_system_column: false,
             elements: Vec::new(),
@@ -565,6 +734,80 @@ mod tests {
         assert_eq!(child, Some(7));
     }
 
+    #[test]
+    fn test_decode_node_pointers() {
+        let pk_cols = vec![int_column("id", false)];
+        let page_size = SIZE_PAGE_DEFAULT as usize;
+
+        let records: Vec<Vec<u8>> = [(1i32, 4u32), (100, 5), (250, 9)]
+            .iter()
+            .map(|&(key, child)| {
+                let mut rec = vec![0u8; 8];
+                BigEndian::write_u32(&mut rec[0..4], (key as u32) ^ 0x80000000);
+                BigEndian::write_u32(&mut rec[4..8], child);
+                rec
+            })
+            .collect();
+        let page = build_index_page(page_size, 3, 100, 1, &records, true);
+
+        let ptrs = decode_node_pointers(&page, &pk_cols);
+        assert_eq!(ptrs.len(), 3);
+        assert!(ptrs[0].min_rec);
+        assert!(!ptrs[1].min_rec);
+        let children: Vec<u32> = ptrs.iter().map(|p| p.child_page_no).collect();
+        assert_eq!(children, vec![4, 5, 9]);
+        assert_eq!(ptrs[2].key[0].0, "id");
+        assert!(matches!(ptrs[2].key[0].1, FieldValue::Int(250)));
+
+        // Leaf records are not node pointers
+        let leaf = build_index_page(page_size, 4, 100, 0, &records, false);
+        assert!(decode_node_pointers(&leaf, &pk_cols).is_empty());
+    }
+
+    #[test]
+    fn test_candidate_records_uses_directory() {
+        let pk_cols = vec![int_column("id", false)];
+        let page_size = SIZE_PAGE_DEFAULT as usize;
+
+        let records: Vec<Vec<u8>> = (0..12u32)
+            .map(|i| {
+                let mut rec = vec![0u8; 8];
+                BigEndian::write_u32(&mut rec[0..4], (i * 10) ^ 0x80000000);
+                BigEndian::write_u32(&mut rec[4..8], 100 + i);
+                rec
+            })
+            .collect();
+        let mut page = build_index_page(page_size, 3, 100, 1, &records, true);
+
+        // Without a directory every record is a candidate
+        assert_eq!(
+            candidate_records(&page, &pk_cols, &[PkValue::Int(75)]).len(),
+            12
+        );
+
+        // Slots: infimum, 4th record (key 30), 8th record (key 70), supremum
+        let offsets: Vec<usize> = walk_compact_records(&page)
+            .iter()
+            .map(|r| r.offset)
+            .collect();
+        let slots = [PAGE_NEW_INFIMUM, offsets[3], offsets[7], PAGE_NEW_SUPREMUM];
+        BigEndian::write_u16(&mut page[FIL_PAGE_DATA + PAGE_N_DIR_SLOTS..], 4);
+        for (i, slot) in slots.iter().enumerate() {
+            let at = page_size - SIZE_FIL_TRAILER - (i + 1) * PAGE_DIR_SLOT_SIZE;
+            BigEndian::write_u16(&mut page[at..], *slot as u16);
+        }
+
+        let candidates = candidate_records(&page, &pk_cols, &[PkValue::Int(75)]);
+        assert_eq!(candidates.len(), 5);
+        assert_eq!(candidates[0].offset, offsets[7]);
+        let child = extract_child_page_no(&page, candidates[0].offset, &pk_cols, page_size);
+        assert_eq!(child, Some(107));
+
+        // A key below the first slot owner scans from the infimum
+        let candidates = candidate_records(&page, &pk_cols, &[PkValue::Int(5)]);
+        assert_eq!(candidates.len(), 12);
+    }
+
     #[test]
     fn test_compare_int_keys() {
         // Signed int comparison
@@ -0,0 +1,192 @@
+//! Character set transcoding for string columns.
+//!
+//! InnoDB stores CHAR, VARCHAR, and TEXT values in the column's character
+//! set, identified by the collation ID in the SDI column definition (see
+//! [`schema::charset_from_collation`](crate::innodb::schema::charset_from_collation)).
+//! This module converts those bytes to Unicode and reports each character
+//! set's minimum and maximum bytes per character, which determine whether a
+//! CHAR column is stored as fixed- or variable-length.
+//!
+//! Single- and multi-byte legacy character sets are decoded with
+//! `encoding_rs`; UCS-2, UTF-16, and UTF-32 (stored big-endian, except
+//! `utf16le`) are decoded directly.
+
+use encoding_rs::Encoding;
+
+/// Minimum and maximum bytes per character for a MySQL character set.
+///
+/// Unknown character sets are assumed to be `utf8mb4` (1 to 4 bytes).
+///
+/// # Examples
+///
+/// ```
+/// use idb::innodb::charset::charset_width;
+///
+/// assert_eq!(charset_width("latin1"), (1, 1));
+/// assert_eq!(charset_width("ucs2"), (2, 2));
+/// assert_eq!(charset_width("gbk"), (1, 2));
+/// assert_eq!(charset_width("utf8mb4"), (1, 4));
+/// ```
+pub fn charset_width(charset: &str) -> (usize, usize) {
+    match charset {
+        "utf8mb3" => (1, 3),
+        "utf8mb4" => (1, 4),
+        "ucs2" => (2, 2),
+        "utf16" | "utf16le" => (2, 4),
+        "utf32" => (4, 4),
+        "big5" | "cp932" | "euckr" | "gb2312" | "gbk" | "sjis" => (1, 2),
+        "eucjpms" | "ujis" => (1, 3),
+        "gb18030" => (1, 4),
+        c if single_byte_encoding(c).is_some() || SINGLE_BYTE_UNMAPPED.contains(&c) => (1, 1),
+        "ascii" | "binary" => (1, 1),
+        _ => (1, 4),
+    }
+}
+
+/// Decode column bytes in the given MySQL character set to a `String`.
+///
+/// Returns `None` if the bytes are not valid in that character set, so the
+/// caller can fall back to a lossless representation. Character sets without
+/// a Unicode mapping (and `binary`) are accepted only if the bytes happen to
+/// be valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use idb::innodb::charset::decode_charset;
+///
+/// assert_eq!(decode_charset(&[0xCF, 0xF0, 0xE8], "cp1251").unwrap(), "При");
+/// assert_eq!(decode_charset(&[0x00, 0x41, 0x04, 0x10], "ucs2").unwrap(), "AА");
+/// assert_eq!(decode_charset(&[0xE9], "latin1").unwrap(), "é");
+/// assert!(decode_charset(&[0xE9], "utf8mb4").is_none());
+/// ```
+pub fn decode_charset(data: &[u8], charset: &str) -> Option<String> {
+    match charset {
+        "ucs2" | "utf16" => decode_utf16(data, false),
+        "utf16le" => decode_utf16(data, true),
+        "utf32" => decode_utf32(data),
+        _ => match multi_byte_encoding(charset).or_else(|| single_byte_encoding(charset)) {
+            Some(enc) => enc
+                .decode_without_bom_handling_and_without_replacement(data)
+                .map(|s| s.into_owned()),
+            None => std::str::from_utf8(data).ok().map(str::to_string),
+        },
+    }
+}
+
+/// Single-byte character sets that have no `encoding_rs` mapping.
+const SINGLE_BYTE_UNMAPPED: &[&str] = &[
+    "armscii8", "cp850", "cp852", "dec8", "geostd8", "hp8", "keybcs2", "macce", "swe7",
+];
+
+/// `encoding_rs` decoder for a single-byte MySQL character set.
+fn single_byte_encoding(charset: &str) -> Option<&'static Encoding> {
+    Some(match charset {
+        // MySQL latin1 is cp1252 with the five undefined bytes mapped to
+        // C1 controls, which matches the WHATWG windows-1252 decoder
+        "latin1" => encoding_rs::WINDOWS_1252,
+        "latin2" => encoding_rs::ISO_8859_2,
+        "latin5" => encoding_rs::WINDOWS_1254,
+        "latin7" => encoding_rs::ISO_8859_13,
+        "cp1250" => encoding_rs::WINDOWS_1250,
+        "cp1251" => encoding_rs::WINDOWS_1251,
+        "cp1256" => encoding_rs::WINDOWS_1256,
+        "cp1257" => encoding_rs::WINDOWS_1257,
+        "cp866" => encoding_rs::IBM866,
+        "koi8r" => encoding_rs::KOI8_R,
+        "koi8u" => encoding_rs::KOI8_U,
+        "greek" => encoding_rs::ISO_8859_7,
+        "hebrew" => encoding_rs::ISO_8859_8,
+        "tis620" => encoding_rs::WINDOWS_874,
+        "macroman" => encoding_rs::MACINTOSH,
+        _ => return None,
+    })
+}
+
+/// `encoding_rs` decoder for a multi-byte (CJK) MySQL character set.
+fn multi_byte_encoding(charset: &str) -> Option<&'static Encoding> {
+    Some(match charset {
+        "utf8mb3" | "utf8mb4" | "ascii" => encoding_rs::UTF_8,
+        "gbk" | "gb2312" => encoding_rs::GBK,
+        "gb18030" => encoding_rs::GB18030,
+        "big5" => encoding_rs::BIG5,
+        "sjis" | "cp932" => encoding_rs::SHIFT_JIS,
+        "ujis" | "eucjpms" => encoding_rs::EUC_JP,
+        "euckr" => encoding_rs::EUC_KR,
+        _ => return None,
+    })
+}
+
+/// Decode UTF-16 (or UCS-2) code units; unpaired surrogates are invalid.
+fn decode_utf16(data: &[u8], little_endian: bool) -> Option<String> {
+    if !data.len().is_multiple_of(2) {
+        return None;
+    }
+    let units = data.chunks_exact(2).map(|c| {
+        if little_endian {
+            u16::from_le_bytes([c[0], c[1]])
+        } else {
+            u16::from_be_bytes([c[0], c[1]])
+        }
+    });
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .ok()
+}
+
+/// Decode big-endian UTF-32 code points.
+fn decode_utf32(data: &[u8]) -> Option<String> {
+    if !data.len().is_multiple_of(4) {
+        return None;
+    }
+    data.chunks_exact(4)
+        .map(|c| char::from_u32(u32::from_be_bytes([c[0], c[1], c[2], c[3]])))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_cjk() {
+        // "中文" in GBK and Shift_JIS "日本"
+        assert_eq!(
+            decode_charset(&[0xD6, 0xD0, 0xCE, 0xC4], "gbk").unwrap(),
+            "中文"
+        );
+        assert_eq!(
+            decode_charset(&[0x93, 0xFA, 0x96, 0x7B], "sjis").unwrap(),
+            "日本"
+        );
+        assert!(decode_charset(&[0xD6], "gbk").is_none());
+    }
+
+    #[test]
+    fn test_decode_utf16_and_utf32() {
+        // U+1F600 as a surrogate pair
+        assert_eq!(
+            decode_charset(&[0xD8, 0x3D, 0xDE, 0x00], "utf16").unwrap(),
+            "\u{1F600}"
+        );
+        assert_eq!(
+            decode_charset(&[0x3D, 0xD8, 0x00, 0xDE], "utf16le").unwrap(),
+            "\u{1F600}"
+        );
+        assert!(decode_charset(&[0xD8, 0x3D], "utf16").is_none());
+        assert!(decode_charset(&[0x00, 0x41, 0x00], "ucs2").is_none());
+        assert_eq!(
+            decode_charset(&[0, 0, 0, 0x41, 0, 0x01, 0xF6, 0x00], "utf32").unwrap(),
+            "A\u{1F600}"
+        );
+        assert!(decode_charset(&[0, 0x11, 0, 0], "utf32").is_none());
+    }
+
+    #[test]
+    fn test_decode_unmapped_and_binary() {
+        assert_eq!(decode_charset(b"abc", "cp852").unwrap(), "abc");
+        assert!(decode_charset(&[0xFF], "binary").is_none());
+        assert_eq!(charset_width("cp852"), (1, 1));
+        assert_eq!(charset_width("unknown"), (1, 4));
+    }
+}
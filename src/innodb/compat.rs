@@ -221,6 +221,8 @@ pub struct TablespaceInfo {
     pub has_compressed_pages: bool,
     /// Whether the tablespace uses instant ADD COLUMN (detected from SDI).
     pub has_instant_columns: bool,
+    /// Number of pages whose IBUF bitmap bits show unmerged change buffer entries.
+    pub ibuf_buffered_pages: u64,
 }
 
 /// Compatibility report for a tablespace.
@@ -308,7 +310,10 @@ pub fn extract_tablespace_info(
     let has_compressed_pages = {
         let page_count = ts.page_count();
         let mut found = false;
-        // Check first 10 pages (or all if fewer) for compression indicator
+        // Check first 10 pages (or all if fewer) for compression indicator,
+        // looking at the stored pages rather than their decompressed images
+        let decompress = ts.page_decompression();
+        ts.set_page_decompression(false);
         let check_count = page_count.min(10);
         for i in 0..check_count {
             if let Ok(page) = ts.read_page(i) {
@@ -320,9 +325,12 @@ pub fn extract_tablespace_info(
                 }
             }
         }
+        ts.set_page_decompression(decompress);
         found
     };
 
+    let ibuf_buffered_pages = crate::innodb::ibuf::read_buffered_pages(ts).len() as u64;
+
     Ok(TablespaceInfo {
         page_size,
         fsp_flags,
@@ -334,6 +342,7 @@ pub fn extract_tablespace_info(
         mysql_version_id,
         has_compressed_pages,
         has_instant_columns,
+        ibuf_buffered_pages,
     })
 }
 
@@ -350,6 +359,7 @@ pub fn check_compatibility(info: &TablespaceInfo, target: &MysqlVersion) -> Vec<
     check_encryption(info, target, &mut checks);
     check_vendor_compatibility(info, target, &mut checks);
     check_compression(info, target, &mut checks);
+    check_change_buffer(info, target, &mut checks);
 
     checks
 }
@@ -561,6 +571,27 @@ fn check_compression(info: &TablespaceInfo, _target: &MysqlVersion, checks: &mut
     }
 }
 
+/// Check for change buffer entries that have not been merged into the file.
+fn check_change_buffer(
+    info: &TablespaceInfo,
+    _target: &MysqlVersion,
+    checks: &mut Vec<CompatCheck>,
+) {
+    if info.ibuf_buffered_pages > 0 {
+        checks.push(CompatCheck {
+            check: "change_buffer".to_string(),
+            message: format!(
+                "{} page(s) have unmerged change buffer entries in ibdata1; \
+                 a copy of this file alone would lose them",
+                info.ibuf_buffered_pages
+            ),
+            severity: Severity::Warning,
+            current_value: Some(format!("{} buffered pages", info.ibuf_buffered_pages)),
+            expected: Some("0 buffered pages (run a slow shutdown first)".to_string()),
+        });
+    }
+}
+
 /// Per-file result for directory scan mode.
 #[derive(Debug, Clone, Serialize)]
 pub struct ScanFileResult {
@@ -676,6 +707,7 @@ mod tests {
             mysql_version_id: None,
             has_compressed_pages: false,
             has_instant_columns: false,
+            ibuf_buffered_pages: 0,
         };
         let target = MysqlVersion::parse("8.0.0").unwrap();
         let mut checks = Vec::new();
@@ -697,6 +729,7 @@ mod tests {
             mysql_version_id: None,
             has_compressed_pages: false,
             has_instant_columns: false,
+            ibuf_buffered_pages: 0,
         };
         let target = MysqlVersion::parse("5.6.0").unwrap();
         let mut checks = Vec::new();
@@ -718,6 +751,7 @@ mod tests {
             mysql_version_id: None,
             has_compressed_pages: false,
             has_instant_columns: false,
+            ibuf_buffered_pages: 0,
         };
         let target = MysqlVersion::parse("8.0.0").unwrap();
         let mut checks = Vec::new();
@@ -740,6 +774,7 @@ mod tests {
             mysql_version_id: None,
             has_compressed_pages: false,
             has_instant_columns: false,
+            ibuf_buffered_pages: 0,
         };
         let target = MysqlVersion::parse("5.7.44").unwrap();
         let mut checks = Vec::new();
@@ -761,6 +796,7 @@ mod tests {
             mysql_version_id: None,
             has_compressed_pages: false,
             has_instant_columns: false,
+            ibuf_buffered_pages: 0,
         };
         let target = MysqlVersion::parse("8.4.0").unwrap();
         let mut checks = Vec::new();
@@ -783,6 +819,7 @@ mod tests {
             mysql_version_id: None,
             has_compressed_pages: false,
             has_instant_columns: false,
+            ibuf_buffered_pages: 0,
         };
         let target = MysqlVersion::parse("8.4.0").unwrap();
         let mut checks = Vec::new();
@@ -804,6 +841,7 @@ mod tests {
             mysql_version_id: None,
             has_compressed_pages: false,
             has_instant_columns: false,
+            ibuf_buffered_pages: 0,
         };
         let target = MysqlVersion::parse("8.4.0").unwrap();
         let mut checks = Vec::new();
@@ -826,6 +864,7 @@ mod tests {
             mysql_version_id: None,
             has_compressed_pages: false,
             has_instant_columns: false,
+            ibuf_buffered_pages: 0,
         };
         let target = MysqlVersion::parse("9.0.0").unwrap();
         let mut checks = Vec::new();
@@ -848,6 +887,7 @@ mod tests {
             mysql_version_id: None,
             has_compressed_pages: false,
             has_instant_columns: false,
+            ibuf_buffered_pages: 0,
         };
         let target = MysqlVersion::parse("5.6.0").unwrap();
         let mut checks = Vec::new();
@@ -869,6 +909,7 @@ mod tests {
             mysql_version_id: Some(80032),
             has_compressed_pages: false,
             has_instant_columns: false,
+            ibuf_buffered_pages: 0,
         };
         let target = MysqlVersion::parse("8.4.0").unwrap();
         let report = build_compat_report(&info, &target, "test.ibd");
@@ -890,10 +931,37 @@ mod tests {
             mysql_version_id: None,
             has_compressed_pages: false,
             has_instant_columns: false,
+            ibuf_buffered_pages: 0,
         };
         let target = MysqlVersion::parse("8.4.0").unwrap();
         let report = build_compat_report(&info, &target, "test.ibd");
         assert!(!report.compatible);
         assert!(report.summary.errors > 0);
     }
+
+    #[test]
+    fn test_change_buffer_warning() {
+        let info = TablespaceInfo {
+            page_size: 16384,
+            fsp_flags: 0,
+            space_id: 1,
+            row_format: None,
+            has_sdi: true,
+            is_encrypted: false,
+            vendor: VendorInfo::mysql(),
+            mysql_version_id: Some(80032),
+            has_compressed_pages: false,
+            has_instant_columns: false,
+            ibuf_buffered_pages: 3,
+        };
+        let target = MysqlVersion::parse("8.4.0").unwrap();
+        let report = build_compat_report(&info, &target, "test.ibd");
+        let check = report
+            .checks
+            .iter()
+            .find(|c| c.check == "change_buffer")
+            .unwrap();
+        assert_eq!(check.severity, Severity::Warning);
+        assert!(report.compatible);
+    }
 }
@@ -290,6 +290,8 @@ fn field_display(val: &FieldValue) -> String {
         FieldValue::Double(d) => d.to_string(),
         FieldValue::Str(s) => s.clone(),
         FieldValue::Hex(h) => h.clone(),
+        FieldValue::Json(v) => v.to_string(),
+        FieldValue::Geometry(g) => g.geometry.to_wkt(),
     }
 }
 
@@ -327,7 +329,7 @@ fn encode_value_needles(col: &ColumnStorageInfo, target: &str) -> Vec<Vec<u8>> {
     }
 
     let width = col.fixed_len;
-    let is_int = matches!(col.dd_type, 2 | 3 | 4 | 5 | 9); // TINY/SHORT/LONG/INT24/LONGLONG
+    let is_int = matches!(col.dd_type, 2 | 3 | 4 | 9 | 10); // TINY/SHORT/LONG/LONGLONG/INT24
     if is_int && (1..=8).contains(&width) {
         if col.is_unsigned {
             if let Ok(v) = target.parse::<u64>() {
@@ -389,7 +391,7 @@ pub fn verify_deleted(
             ))
         })?;
 
-    let page_size = ts.page_size();
+    let page_size = ts.logical_page_size();
     let pk_cols = pk_columns(&columns);
     let target_is_pk = pk_cols.iter().any(|c| c.name.eq_ignore_ascii_case(column));
 
@@ -398,7 +400,11 @@ pub fn verify_deleted(
 
     // Collect clustered leaf pages first (the callback borrows ts immutably).
     let mut leaf_pages: Vec<(u64, Vec<u8>)> = Vec::new();
-    ts.for_each_page(|pn, pdata| {
+    ts.for_each_page_uncompressed(|pn, pdata| {
+        let pdata = match pdata {
+            Ok(d) => d,
+            Err(_) => return Ok(()),
+        };
         let hdr = match FilHeader::parse(pdata) {
             Some(h) => h,
             None => return Ok(()),
@@ -419,7 +425,7 @@ pub fn verify_deleted(
 
     for (pn, pdata) in &leaf_pages {
         // Live records.
-        for row in decode_page_records(pdata, &columns, false, false, page_size) {
+        for row in decode_page_records(pdata, &columns, false, false) {
             records_examined += 1;
             if let Some((_, val)) = row.iter().find(|(n, _)| n.eq_ignore_ascii_case(column)) {
                 if value_matches(val, target_value) {
@@ -435,7 +441,7 @@ pub fn verify_deleted(
         }
 
         // Delete-marked records (include system cols to recover the trx id).
-        for row in decode_page_records(pdata, &columns, true, true, page_size) {
+        for row in decode_page_records(pdata, &columns, true, true) {
             records_examined += 1;
             let matched = row
                 .iter()
@@ -684,7 +690,12 @@ mod tests {
             is_unsigned: false,
             fixed_len: 4,
             is_variable: false,
+            max_len: 4,
+            version_added: 0,
+            version_dropped: 0,
+            instant_default: None,
             charset_max_bytes: 0,
+            collation_id: 0,
             datetime_precision: 0,
             is_system_column: false,
             elements: vec![],
@@ -706,7 +717,12 @@ mod tests {
             is_unsigned: true,
             fixed_len: 8,
             is_variable: false,
+            max_len: 8,
+            version_added: 0,
+            version_dropped: 0,
+            instant_default: None,
             charset_max_bytes: 0,
+            collation_id: 0,
             datetime_precision: 0,
             is_system_column: false,
             elements: vec![],
@@ -727,7 +743,12 @@ mod tests {
             is_unsigned: false,
             fixed_len: 4,
             is_variable: false,
+            max_len: 4,
+            version_added: 0,
+            version_dropped: 0,
+            instant_default: None,
             charset_max_bytes: 0,
+            collation_id: 0,
             datetime_precision: 0,
             is_system_column: sys,
             elements: vec![],
@@ -1,17 +1,27 @@
 //! Tablespace compression detection and decompression.
 //!
 //! Detects the compression algorithm from FSP flags and provides zlib and LZ4
-//! decompression helpers for compressed page data.
+//! decompression helpers for compressed page data. Transparently compressed
+//! pages (MySQL punch-hole and MariaDB page_compressed) are inflated by
+//! [`decompress_page`], and ROW_FORMAT=COMPRESSED (page_zip) INDEX pages are
+//! decoded back into regular page images via [`decompress_zip_page`].
 //!
 //! Supports both MySQL (bits 11-12) and MariaDB flag layouts:
 //! - MariaDB full_crc32: compression algo in bits 5-7
 //! - MariaDB original: PAGE_COMPRESSION flag at bit 16
 //! - MariaDB page-level: algorithm ID embedded per-page at offset 26
 
-use flate2::read::ZlibDecoder;
+use std::collections::HashSet;
 use std::io::Read;
 
+use byteorder::{BigEndian, ByteOrder};
+use flate2::read::ZlibDecoder;
+use flate2::{Decompress, FlushDecompress, Status};
+
+use crate::innodb::constants::*;
+use crate::innodb::page_types::PageType;
 use crate::innodb::vendor::VendorInfo;
+use crate::IdbError;
 
 /// Compression algorithm detected or used for a page.
 ///
@@ -267,6 +277,124 @@ pub fn is_hole_punched(page_data: &[u8], page_size: u32) -> bool {
         .all(|&b| b == 0)
 }
 
+/// Inflate a transparently compressed page into its logical page image.
+///
+/// Handles MySQL punch-hole compression (`FIL_PAGE_COMPRESSED`, type 14,
+/// written for `COMPRESSION='zlib'` or `'lz4'`) and MariaDB
+/// `PAGE_COMPRESSED` pages (type 34354) in the original, non-full_crc32
+/// format. Pages of any other type return `Ok(None)`, so every page of a
+/// tablespace can be passed through this function.
+///
+/// The returned buffer is `page_size` bytes long and holds the page as
+/// InnoDB wrote it before compression, with its original page type and
+/// checksum. Compressed and encrypted pages must be decrypted first.
+///
+/// # Errors
+///
+/// Returns [`IdbError::Parse`] if the compression header is inconsistent,
+/// the algorithm is not supported, or the payload does not inflate to the
+/// expected size.
+///
+/// # Examples
+///
+/// ```
+/// use byteorder::{BigEndian, ByteOrder};
+/// use idb::innodb::compression::decompress_page;
+///
+/// // An INDEX page is not transparently compressed
+/// let mut page = vec![0u8; 16384];
+/// BigEndian::write_u16(&mut page[24..], 17855);
+/// assert!(decompress_page(&page, 16384).unwrap().is_none());
+///
+/// // A FIL_PAGE_COMPRESSED page with an empty payload is rejected
+/// BigEndian::write_u16(&mut page[24..], 14);
+/// assert!(decompress_page(&page, 16384).is_err());
+/// ```
+pub fn decompress_page(page_data: &[u8], page_size: usize) -> Result<Option<Vec<u8>>, IdbError> {
+    if page_data.len() < FIL_PAGE_DATA + FIL_PAGE_COMP_METADATA_LEN || page_size < page_data.len() {
+        return Ok(None);
+    }
+    let page_type = BigEndian::read_u16(&page_data[FIL_PAGE_TYPE..]);
+    if page_type == PageType::Compressed.as_u16() {
+        decompress_mysql_page(page_data, page_size).map(Some)
+    } else if page_type == FIL_PAGE_PAGE_COMPRESSED {
+        decompress_mariadb_page(page_data, page_size).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Inflate a MySQL `FIL_PAGE_COMPRESSED` page.
+///
+/// The FIL header is kept as written; bytes 26-33 hold the compression
+/// header (version, algorithm, original type, original and compressed
+/// sizes) and the compressed page body follows at `FIL_PAGE_DATA`.
+fn decompress_mysql_page(page_data: &[u8], page_size: usize) -> Result<Vec<u8>, IdbError> {
+    let corrupt = |what: String| IdbError::Parse(format!("FIL_PAGE_COMPRESSED: {}", what));
+
+    let algorithm = page_data[FIL_PAGE_COMPRESSION_ALGORITHM];
+    let original_type = BigEndian::read_u16(&page_data[FIL_PAGE_COMPRESSION_ORIGINAL_TYPE..]);
+    let original_size =
+        BigEndian::read_u16(&page_data[FIL_PAGE_COMPRESSION_ORIGINAL_SIZE..]) as usize;
+    let compressed_size =
+        BigEndian::read_u16(&page_data[FIL_PAGE_COMPRESSION_COMPRESSED_SIZE..]) as usize;
+
+    if original_size == 0 || FIL_PAGE_DATA + original_size > page_size {
+        return Err(corrupt(format!("invalid original size {}", original_size)));
+    }
+    if compressed_size == 0 || FIL_PAGE_DATA + compressed_size > page_data.len() {
+        return Err(corrupt(format!(
+            "invalid compressed size {}",
+            compressed_size
+        )));
+    }
+    let payload = &page_data[FIL_PAGE_DATA..FIL_PAGE_DATA + compressed_size];
+
+    let body = match algorithm {
+        1 => decompress_zlib(payload),
+        2 => decompress_lz4(payload, original_size),
+        other => return Err(corrupt(format!("unsupported algorithm {}", other))),
+    }
+    .filter(|b| b.len() == original_size)
+    .ok_or_else(|| corrupt("payload does not inflate to the original size".to_string()))?;
+
+    let mut page = vec![0u8; page_size];
+    page[..FIL_PAGE_DATA].copy_from_slice(&page_data[..FIL_PAGE_DATA]);
+    page[FIL_PAGE_COMPRESSION_VERSION..FIL_PAGE_COMPRESSION_COMPRESSED_SIZE + 2].fill(0);
+    BigEndian::write_u16(&mut page[FIL_PAGE_TYPE..], original_type);
+    page[FIL_PAGE_DATA..FIL_PAGE_DATA + original_size].copy_from_slice(&body);
+    Ok(page)
+}
+
+/// Inflate a MariaDB `PAGE_COMPRESSED` page.
+///
+/// The algorithm is the 8-byte big-endian value at `FIL_PAGE_COMP_ALGO`,
+/// the payload length is stored at `FIL_PAGE_DATA`, and the payload is
+/// the whole original page, header and trailer included.
+fn decompress_mariadb_page(page_data: &[u8], page_size: usize) -> Result<Vec<u8>, IdbError> {
+    let corrupt = |what: String| IdbError::Parse(format!("PAGE_COMPRESSED: {}", what));
+
+    let algo_id = BigEndian::read_u64(&page_data[FIL_PAGE_COMP_ALGO..]);
+    let header_len = FIL_PAGE_DATA + FIL_PAGE_COMP_METADATA_LEN;
+    let actual_size = BigEndian::read_u16(&page_data[FIL_PAGE_COMP_SIZE..]) as usize;
+    if actual_size == 0 || header_len + actual_size > page_data.len() {
+        return Err(corrupt(format!("invalid payload size {}", actual_size)));
+    }
+    let payload = &page_data[header_len..header_len + actual_size];
+
+    let algorithm = u8::try_from(algo_id)
+        .map(mariadb_algo_from_id)
+        .unwrap_or(CompressionAlgorithm::None);
+    let page = match algorithm {
+        CompressionAlgorithm::Zlib => decompress_zlib(payload),
+        CompressionAlgorithm::Lz4 => decompress_lz4(payload, page_size),
+        _ => return Err(corrupt(format!("unsupported algorithm {}", algo_id))),
+    }
+    .filter(|p| p.len() == page_size)
+    .ok_or_else(|| corrupt("payload does not inflate to the page size".to_string()))?;
+    Ok(page)
+}
+
 impl std::fmt::Display for CompressionAlgorithm {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -282,6 +410,715 @@ impl std::fmt::Display for CompressionAlgorithm {
     }
 }
 
+// ── ROW_FORMAT=COMPRESSED (page_zip) ────────────────────────────────
+
+/// Extra bytes of the infimum record (n_owned=1, heap_no=0, status=2).
+const ZIP_INFIMUM_EXTRA: [u8; 3] = [0x01, 0x00, 0x02];
+/// Data bytes of the infimum record.
+const ZIP_INFIMUM_DATA: &[u8; 8] = b"infimum\0";
+/// Extra bytes (after n_owned) and data bytes of the supremum record.
+const ZIP_SUPREMUM_EXTRA_DATA: [u8; 12] = [
+    0x00, 0x0b, 0x00, 0x00, b's', b'u', b'p', b'r', b'e', b'm', b'u', b'm',
+];
+
+/// One field of the dummy index recovered from a compressed page.
+#[derive(Debug, Clone, Copy)]
+struct ZipField {
+    /// Fixed storage length, or 0 for a variable-length field.
+    fixed_len: usize,
+    /// Variable-length field with a maximum length above 255 bytes; its
+    /// length may take two bytes and it may be stored externally.
+    big: bool,
+    /// Field is declared NULL-able and has a bit in the null bitmap.
+    nullable: bool,
+}
+
+/// Dummy index rebuilt from the page_zip fields encoding.
+///
+/// Compressed pages embed a compact description of the record format at the
+/// start of the zlib stream (`page_zip_fields_encode` in MySQL). Consecutive
+/// fixed-length NOT NULL columns are merged into a single field, so field
+/// positions do not map 1:1 to table columns.
+#[derive(Debug)]
+struct ZipIndex {
+    fields: Vec<ZipField>,
+    n_nullable: usize,
+    /// Field holding DB_TRX_ID and DB_ROLL_PTR (clustered index leaf pages).
+    trx_id_col: Option<usize>,
+}
+
+/// Field boundaries of one record, as computed by `rec_get_offsets`.
+#[derive(Debug)]
+struct ZipRecOffsets {
+    /// End offset of each field, relative to the record origin.
+    ends: Vec<usize>,
+    /// Whether each field is stored externally (BLOB reference).
+    externs: Vec<bool>,
+    /// Number of header bytes before the origin (including the 5 fixed bytes).
+    extra_size: usize,
+}
+
+impl ZipRecOffsets {
+    fn field_start(&self, i: usize) -> usize {
+        if i == 0 {
+            0
+        } else {
+            self.ends[i - 1]
+        }
+    }
+
+    fn data_size(&self) -> usize {
+        self.ends.last().copied().unwrap_or(0)
+    }
+
+    /// Byte ranges of the record data that are kept outside the zlib stream
+    /// and the modification log: DB_TRX_ID/DB_ROLL_PTR, BLOB references, and
+    /// the child page number of node pointers. Sorted by offset.
+    ///
+    /// Returns `None` if a field is too short to hold its uncompressed part.
+    fn uncompressed_ranges(&self, index: &ZipIndex, node_ptr: bool) -> Option<Vec<(usize, usize)>> {
+        let mut ranges = Vec::new();
+        if node_ptr {
+            let size = self.data_size();
+            ranges.push((size.checked_sub(REC_NODE_PTR_SIZE)?, REC_NODE_PTR_SIZE));
+            return Some(ranges);
+        }
+        for i in 0..self.ends.len() {
+            if Some(i) == index.trx_id_col {
+                ranges.push((self.field_start(i), DATA_TRX_ID_LEN + DATA_ROLL_PTR_LEN));
+            } else if self.externs[i] {
+                ranges.push((
+                    self.ends[i].checked_sub(BTR_EXTERN_FIELD_REF_SIZE)?,
+                    BTR_EXTERN_FIELD_REF_SIZE,
+                ));
+            }
+        }
+        Some(ranges)
+    }
+}
+
+/// Decode the page_zip fields encoding (`page_zip_fields_decode`).
+fn decode_zip_fields(buf: &[u8], is_leaf: bool) -> Result<ZipIndex, IdbError> {
+    let err = || IdbError::Parse("page_zip: corrupt fields encoding".to_string());
+
+    let mut values = Vec::new();
+    let mut pos = 0;
+    while pos < buf.len() {
+        let b = buf[pos];
+        pos += 1;
+        if b & 0x80 != 0 {
+            let lo = *buf.get(pos).ok_or_else(err)?;
+            pos += 1;
+            values.push((((b & 0x7F) as usize) << 8 | lo as usize, true));
+        } else {
+            values.push((b as usize, false));
+        }
+    }
+    let (last, _) = values.pop().ok_or_else(err)?;
+
+    let mut fields = Vec::with_capacity(values.len());
+    for &(val, two_byte) in &values {
+        let nullable = val & 1 == 0;
+        let field = if two_byte {
+            ZipField {
+                fixed_len: val >> 1,
+                big: false,
+                nullable,
+            }
+        } else if val >= 126 {
+            ZipField {
+                fixed_len: 0,
+                big: true,
+                nullable,
+            }
+        } else if val <= 1 {
+            ZipField {
+                fixed_len: 0,
+                big: false,
+                nullable,
+            }
+        } else {
+            ZipField {
+                fixed_len: val >> 1,
+                big: false,
+                nullable,
+            }
+        };
+        fields.push(field);
+    }
+
+    let counted_nullable = fields.iter().filter(|f| f.nullable).count();
+    if is_leaf {
+        let trx_id_col = if last == 0 {
+            None
+        } else if last >= fields.len() {
+            return Err(err());
+        } else {
+            Some(last)
+        };
+        Ok(ZipIndex {
+            fields,
+            n_nullable: counted_nullable,
+            trx_id_col,
+        })
+    } else {
+        if last < counted_nullable {
+            return Err(err());
+        }
+        Ok(ZipIndex {
+            fields,
+            n_nullable: last,
+            trx_id_col: None,
+        })
+    }
+}
+
+/// Compute record field offsets from the header bytes preceding the origin.
+///
+/// `extra(k)` returns the k-th header byte walking away from the record
+/// origin: the null bitmap first, then the variable-length field lengths.
+/// On a page this is `page[origin - 6 - k]`; in the modification log the
+/// bytes are stored in exactly this order.
+fn zip_rec_offsets(
+    index: &ZipIndex,
+    node_ptr: bool,
+    extra: impl Fn(usize) -> Option<u8>,
+) -> Option<ZipRecOffsets> {
+    let n_null_bytes = index.n_nullable.div_ceil(8);
+    let mut lens_pos = n_null_bytes;
+    let mut null_idx = 0;
+    let mut offs = 0;
+    let n_fields = index.fields.len() + usize::from(node_ptr);
+    let mut ends = Vec::with_capacity(n_fields);
+    let mut externs = Vec::with_capacity(n_fields);
+
+    for field in &index.fields {
+        if field.nullable {
+            if null_idx >= index.n_nullable {
+                return None;
+            }
+            let byte = extra(null_idx / 8)?;
+            let is_null = byte & (1 << (null_idx % 8)) != 0;
+            null_idx += 1;
+            if is_null {
+                ends.push(offs);
+                externs.push(false);
+                continue;
+            }
+        }
+
+        let mut is_extern = false;
+        if field.fixed_len == 0 {
+            let b0 = extra(lens_pos)? as usize;
+            lens_pos += 1;
+            if field.big && b0 & 0x80 != 0 {
+                let b1 = extra(lens_pos)? as usize;
+                lens_pos += 1;
+                let len = (b0 << 8) | b1;
+                offs += len & 0x3FFF;
+                is_extern = len & 0x4000 != 0;
+            } else {
+                offs += b0;
+            }
+        } else {
+            offs += field.fixed_len;
+        }
+        ends.push(offs);
+        externs.push(is_extern);
+    }
+
+    if node_ptr {
+        offs += REC_NODE_PTR_SIZE;
+        ends.push(offs);
+        externs.push(false);
+    }
+
+    Some(ZipRecOffsets {
+        ends,
+        externs,
+        extra_size: REC_N_NEW_EXTRA_BYTES + lens_pos,
+    })
+}
+
+/// Compute record offsets from the header bytes already present on `page`.
+fn zip_rec_offsets_on_page(
+    page: &[u8],
+    rec: usize,
+    index: &ZipIndex,
+    node_ptr: bool,
+) -> Option<ZipRecOffsets> {
+    zip_rec_offsets(index, node_ptr, |k| {
+        let pos = rec.checked_sub(REC_N_NEW_EXTRA_BYTES + 1 + k)?;
+        page.get(pos).copied()
+    })
+}
+
+/// Write the relative next-record pointer of a compact record.
+fn zip_set_next_offs(page: &mut [u8], rec: usize, next: usize) {
+    let val = if next == 0 {
+        0
+    } else {
+        (next as u16).wrapping_sub(rec as u16)
+    };
+    BigEndian::write_u16(&mut page[rec - 2..], val);
+}
+
+/// Sequential reader over the inflated record stream.
+struct ZipStream<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl ZipStream<'_> {
+    /// Copy stream bytes to `page[*next_out..target]`. Returns false if the
+    /// stream ended before `target` was reached.
+    fn copy_to(&mut self, page: &mut [u8], next_out: &mut usize, target: usize) -> bool {
+        if target < *next_out {
+            return false;
+        }
+        let want = target - *next_out;
+        let avail = self.data.len() - self.pos;
+        let n = want.min(avail).min(page.len().saturating_sub(*next_out));
+        page[*next_out..*next_out + n].copy_from_slice(&self.data[self.pos..self.pos + n]);
+        self.pos += n;
+        *next_out += n;
+        n == want
+    }
+}
+
+/// Inflate the zlib stream of a compressed page.
+///
+/// `page_zip_compress` writes the fields encoding, issues a full flush and
+/// then compresses the record data up to `Z_FINISH`. The stream is inflated
+/// incrementally up to each candidate flush marker (the empty stored block
+/// `00 00 FF FF`); the same bytes can occur inside compressed or stored data,
+/// so a candidate is only accepted if a fresh raw inflater decodes the rest of
+/// the stream from it, which holds at a full flush because it resets the
+/// dictionary.
+///
+/// Returns the fields encoding, the record data and the number of stream
+/// bytes consumed (the modification log starts right after them).
+fn inflate_zip_stream(
+    compressed: &[u8],
+    page_size: usize,
+) -> Result<(Vec<u8>, Vec<u8>, usize), IdbError> {
+    let corrupt = |what: &str| IdbError::Parse(format!("page_zip: {}", what));
+
+    let mut inflater = Decompress::new(true);
+    let mut fields_buf = Vec::with_capacity(page_size);
+    let mut search = 0;
+    loop {
+        let flush_end = compressed[search..]
+            .windows(4)
+            .position(|w| w == [0x00, 0x00, 0xFF, 0xFF])
+            .map(|p| search + p + 4)
+            .ok_or_else(|| corrupt("missing fields encoding flush marker"))?;
+        let consumed = inflater.total_in() as usize;
+        inflater
+            .decompress_vec(
+                &compressed[consumed..flush_end],
+                &mut fields_buf,
+                FlushDecompress::Sync,
+            )
+            .map_err(|e| corrupt(&format!("inflate fields: {}", e)))?;
+        if inflater.total_in() as usize != flush_end {
+            return Err(corrupt("fields encoding too long"));
+        }
+        let mut probe = Decompress::new(false);
+        let mut probe_buf = Vec::with_capacity(page_size);
+        if let Ok(Status::StreamEnd) = probe.decompress_vec(
+            &compressed[flush_end..],
+            &mut probe_buf,
+            FlushDecompress::Finish,
+        ) {
+            break;
+        }
+        search = flush_end - 3;
+    }
+
+    let mut stream_buf = Vec::with_capacity(page_size);
+    let consumed = inflater.total_in() as usize;
+    let status = inflater
+        .decompress_vec(
+            &compressed[consumed..],
+            &mut stream_buf,
+            FlushDecompress::Finish,
+        )
+        .map_err(|e| corrupt(&format!("inflate records: {}", e)))?;
+    if status != Status::StreamEnd {
+        return Err(corrupt("truncated zlib stream"));
+    }
+    Ok((fields_buf, stream_buf, inflater.total_in() as usize))
+}
+
+/// Decompress a ROW_FORMAT=COMPRESSED (page_zip) INDEX page.
+///
+/// Reconstructs the regular uncompressed page image that InnoDB keeps in the
+/// buffer pool, following `page_zip_decompress` in MySQL:
+///
+/// 1. The FIL and INDEX page headers are copied verbatim.
+/// 2. The dense page directory at the end of the compressed page is decoded
+///    into the sparse page directory and the record heap order.
+/// 3. The zlib stream is inflated: first the record format (fields encoding),
+///    then the record headers and data in heap order.
+/// 4. Record header bits (delete mark, n_owned, next pointers) are restored
+///    from the dense directory.
+/// 5. The modification log is replayed on top of the inflated records.
+/// 6. Columns stored uncompressed (DB_TRX_ID/DB_ROLL_PTR, child page
+///    pointers, BLOB references) are copied back into the records.
+///
+/// `zip_page` is the physical page as stored on disk (its length is the
+/// compressed page size, e.g. 8192 for KEY_BLOCK_SIZE=8). `page_size` is the
+/// logical (uncompressed) page size of the tablespace. The returned buffer is
+/// `page_size` bytes long and can be walked with
+/// [`walk_compact_records`](crate::innodb::record::walk_compact_records).
+///
+/// The FIL trailer of the returned image is zeroed, as InnoDB does; page
+/// checksums must be validated against the original compressed page.
+///
+/// # Errors
+///
+/// Returns [`IdbError::Parse`] if the page is not a valid compressed INDEX
+/// page (bad zlib stream, inconsistent directory, or corrupt log).
+pub fn decompress_zip_page(zip_page: &[u8], page_size: usize) -> Result<Vec<u8>, IdbError> {
+    let zip_size = zip_page.len();
+    if zip_size < PAGE_DATA_OFFSET + SIZE_FIL_TRAILER || page_size < zip_size {
+        return Err(IdbError::Parse(format!(
+            "page_zip: invalid compressed page size {} for page size {}",
+            zip_size, page_size
+        )));
+    }
+    let corrupt = |what: &str| IdbError::Parse(format!("page_zip: {}", what));
+
+    let hdr = FIL_PAGE_DATA;
+    let n_slots = BigEndian::read_u16(&zip_page[hdr + PAGE_N_DIR_SLOTS..]) as usize;
+    let heap_top = BigEndian::read_u16(&zip_page[hdr + PAGE_HEAP_TOP..]) as usize;
+    let n_heap = (BigEndian::read_u16(&zip_page[hdr + PAGE_N_HEAP..]) & 0x7FFF) as usize;
+    let n_recs = BigEndian::read_u16(&zip_page[hdr + PAGE_N_RECS..]) as usize;
+    let is_leaf = BigEndian::read_u16(&zip_page[hdr + PAGE_LEVEL..]) == 0;
+    let prev_page = BigEndian::read_u32(&zip_page[FIL_PAGE_PREV..]);
+
+    if n_heap < PAGE_HEAP_NO_USER_LOW || n_recs > n_heap - PAGE_HEAP_NO_USER_LOW {
+        return Err(corrupt("invalid record counts"));
+    }
+    let n_dense = n_heap - PAGE_HEAP_NO_USER_LOW;
+    if n_dense * PAGE_ZIP_DIR_SLOT_SIZE >= zip_size - PAGE_DATA_OFFSET
+        || heap_top > page_size - SIZE_FIL_TRAILER
+    {
+        return Err(corrupt("page header out of range"));
+    }
+
+    let dense_start = zip_size - n_dense * PAGE_ZIP_DIR_SLOT_SIZE;
+    let dense =
+        |i: usize| BigEndian::read_u16(&zip_page[zip_size - PAGE_ZIP_DIR_SLOT_SIZE * (i + 1)..]);
+
+    let mut page = vec![0u8; page_size];
+    page[..PAGE_DATA_OFFSET].copy_from_slice(&zip_page[..PAGE_DATA_OFFSET]);
+
+    // Rebuild the sparse page directory and collect record offsets.
+    let rec_min = PAGE_NEW_SUPREMUM_END + REC_N_NEW_EXTRA_BYTES;
+    let mut recs = Vec::with_capacity(n_dense);
+    let mut slot = page_size - SIZE_FIL_TRAILER - PAGE_DIR_SLOT_SIZE;
+    BigEndian::write_u16(&mut page[slot..], PAGE_NEW_INFIMUM as u16);
+    for i in 0..n_dense {
+        let offs = dense(i);
+        let rec = (offs & PAGE_ZIP_DIR_SLOT_MASK) as usize;
+        if i < n_recs {
+            if offs & PAGE_ZIP_DIR_SLOT_OWNED != 0 {
+                slot = slot
+                    .checked_sub(PAGE_DIR_SLOT_SIZE)
+                    .filter(|&s| s > heap_top)
+                    .ok_or_else(|| corrupt("page directory overflow"))?;
+                BigEndian::write_u16(&mut page[slot..], rec as u16);
+            }
+        } else if offs & !PAGE_ZIP_DIR_SLOT_MASK != 0 {
+            return Err(corrupt("flags set on free-list directory slot"));
+        }
+        if rec < rec_min || rec >= heap_top {
+            return Err(corrupt("record offset out of range"));
+        }
+        recs.push(rec);
+    }
+    slot = slot
+        .checked_sub(PAGE_DIR_SLOT_SIZE)
+        .filter(|&s| s > heap_top)
+        .ok_or_else(|| corrupt("page directory overflow"))?;
+    BigEndian::write_u16(&mut page[slot..], PAGE_NEW_SUPREMUM as u16);
+    if (page_size - SIZE_FIL_TRAILER - slot) / PAGE_DIR_SLOT_SIZE != n_slots {
+        return Err(corrupt("directory slot count mismatch"));
+    }
+    recs.sort_unstable();
+
+    // Infimum and supremum records.
+    page[PAGE_NEW_INFIMUM - REC_N_NEW_EXTRA_BYTES..PAGE_NEW_INFIMUM - 2]
+        .copy_from_slice(&ZIP_INFIMUM_EXTRA);
+    let first = if n_recs == 0 {
+        PAGE_NEW_SUPREMUM
+    } else {
+        (dense(0) & PAGE_ZIP_DIR_SLOT_MASK) as usize
+    };
+    zip_set_next_offs(&mut page, PAGE_NEW_INFIMUM, first);
+    page[PAGE_NEW_INFIMUM..PAGE_NEW_INFIMUM + 8].copy_from_slice(ZIP_INFIMUM_DATA);
+    page[PAGE_NEW_SUPREMUM - REC_N_NEW_EXTRA_BYTES + 1..PAGE_NEW_SUPREMUM_END]
+        .copy_from_slice(&ZIP_SUPREMUM_EXTRA_DATA);
+
+    let (fields_buf, stream_buf, consumed) =
+        inflate_zip_stream(&zip_page[PAGE_DATA_OFFSET..dense_start], page_size)?;
+    let index = decode_zip_fields(&fields_buf, is_leaf)?;
+    let mlog_start = PAGE_DATA_OFFSET + consumed;
+
+    // Restore info bits, n_owned and next pointers from the dense directory.
+    let mut info_bits = if !is_leaf && prev_page == FIL_NULL {
+        REC_INFO_MIN_REC_FLAG
+    } else {
+        0
+    };
+    let mut n_owned: u8 = 1;
+    let mut rec = PAGE_NEW_INFIMUM;
+    for i in 0..n_recs {
+        let offs = dense(i);
+        if offs & PAGE_ZIP_DIR_SLOT_DEL != 0 {
+            info_bits |= REC_INFO_DELETED_FLAG;
+        }
+        if offs & PAGE_ZIP_DIR_SLOT_OWNED != 0 {
+            info_bits |= n_owned;
+            n_owned = 1;
+        } else {
+            n_owned += 1;
+        }
+        let next = (offs & PAGE_ZIP_DIR_SLOT_MASK) as usize;
+        zip_set_next_offs(&mut page, rec, next);
+        rec = next;
+        page[rec - REC_N_NEW_EXTRA_BYTES] = info_bits;
+        info_bits = 0;
+    }
+    zip_set_next_offs(&mut page, rec, PAGE_NEW_SUPREMUM);
+    page[PAGE_NEW_SUPREMUM - REC_N_NEW_EXTRA_BYTES] = n_owned;
+    for i in n_recs..n_dense {
+        let free = dense(i) as usize;
+        page[free - REC_N_NEW_EXTRA_BYTES] = 0;
+        let next = if i + 1 < n_dense {
+            dense(i + 1) as usize
+        } else {
+            0
+        };
+        zip_set_next_offs(&mut page, free, next);
+    }
+
+    // Inflate the records in heap order, skipping the fixed header bytes
+    // and the columns that are stored uncompressed.
+    let node_ptr = !is_leaf;
+    let status_bits = u16::from(if node_ptr {
+        REC_STATUS_NODE_PTR
+    } else {
+        REC_STATUS_ORDINARY
+    });
+    let mut heap_status = ((PAGE_HEAP_NO_USER_LOW as u16) << 3) | status_bits;
+    let mut stream = ZipStream {
+        data: &stream_buf,
+        pos: 0,
+    };
+    let mut next_out = PAGE_ZIP_START;
+    let mut stream_complete = true;
+    for &rec in &recs {
+        if !stream.copy_to(&mut page, &mut next_out, rec - REC_N_NEW_EXTRA_BYTES) {
+            stream_complete = false;
+            break;
+        }
+        BigEndian::write_u16(&mut page[rec - 4..], heap_status);
+        heap_status += 1 << 3;
+        next_out = rec;
+
+        if index.trx_id_col.is_none() && !node_ptr {
+            continue;
+        }
+        let offsets = zip_rec_offsets_on_page(&page, rec, &index, node_ptr)
+            .ok_or_else(|| corrupt("invalid record header"))?;
+        let ranges = offsets
+            .uncompressed_ranges(&index, node_ptr)
+            .ok_or_else(|| corrupt("invalid record header"))?;
+        let mut complete = true;
+        for (start, len) in ranges {
+            if !stream.copy_to(&mut page, &mut next_out, rec + start) {
+                complete = false;
+                break;
+            }
+            next_out += len;
+        }
+        if !complete || !stream.copy_to(&mut page, &mut next_out, rec + offsets.data_size()) {
+            stream_complete = false;
+            break;
+        }
+    }
+    if stream_complete {
+        // Trailing bytes up to PAGE_HEAP_TOP (garbage of reused free space).
+        stream.copy_to(&mut page, &mut next_out, heap_top);
+    }
+
+    // Replay the modification log.
+    let trx_storage = if index.trx_id_col.is_some() {
+        DATA_TRX_ID_LEN + DATA_ROLL_PTR_LEN
+    } else if node_ptr {
+        REC_NODE_PTR_SIZE
+    } else {
+        0
+    };
+    let mlog_end = dense_start
+        .checked_sub(n_dense * trx_storage)
+        .filter(|&e| e >= mlog_start)
+        .ok_or_else(|| corrupt("modification log out of range"))?;
+    apply_zip_log(
+        &mut page,
+        &zip_page[mlog_start..mlog_end],
+        &recs,
+        &index,
+        heap_status,
+    )?;
+
+    // Copy the columns that are stored uncompressed.
+    let free_recs: HashSet<usize> = (n_recs..n_dense)
+        .map(|i| (dense(i) & PAGE_ZIP_DIR_SLOT_MASK) as usize)
+        .collect();
+    let mut storage = dense_start;
+    let mut externs = dense_start - n_dense * trx_storage;
+    for &rec in recs.iter().filter(|_| trx_storage > 0) {
+        let offsets = zip_rec_offsets_on_page(&page, rec, &index, node_ptr)
+            .ok_or_else(|| corrupt("invalid record header"))?;
+        if rec + offsets.data_size() > page_size {
+            return Err(corrupt("record extends past page end"));
+        }
+        storage = storage
+            .checked_sub(trx_storage)
+            .filter(|&s| s >= mlog_end)
+            .ok_or_else(|| corrupt("uncompressed column storage out of range"))?;
+        let dst = if node_ptr {
+            (rec + offsets.data_size())
+                .checked_sub(REC_NODE_PTR_SIZE)
+                .ok_or_else(|| corrupt("invalid node pointer record"))?
+        } else {
+            rec + offsets.field_start(index.trx_id_col.unwrap_or(0))
+        };
+        page.get_mut(dst..dst + trx_storage)
+            .ok_or_else(|| corrupt("record extends past page end"))?
+            .copy_from_slice(&zip_page[storage..storage + trx_storage]);
+
+        if node_ptr {
+            continue;
+        }
+        let exists = !free_recs.contains(&rec);
+        for i in 0..offsets.ends.len() {
+            if !offsets.externs[i] {
+                continue;
+            }
+            let dst = (rec + offsets.ends[i])
+                .checked_sub(BTR_EXTERN_FIELD_REF_SIZE)
+                .ok_or_else(|| corrupt("invalid BLOB reference"))?;
+            let field_ref = page
+                .get_mut(dst..dst + BTR_EXTERN_FIELD_REF_SIZE)
+                .ok_or_else(|| corrupt("record extends past page end"))?;
+            if exists {
+                externs = externs
+                    .checked_sub(BTR_EXTERN_FIELD_REF_SIZE)
+                    .filter(|&e| e >= mlog_start)
+                    .ok_or_else(|| corrupt("BLOB reference storage out of range"))?;
+                field_ref.copy_from_slice(&zip_page[externs..externs + BTR_EXTERN_FIELD_REF_SIZE]);
+            } else {
+                field_ref.fill(0);
+            }
+        }
+    }
+
+    Ok(page)
+}
+
+/// Replay the page_zip modification log (`page_zip_apply_log`).
+///
+/// Each entry starts with `(heap_no - 1) << 1 | clear` (1 or 2 bytes),
+/// followed by the record header bytes in reverse order and the record data
+/// minus the uncompressed columns. A zero byte terminates the log.
+fn apply_zip_log(
+    page: &mut [u8],
+    log: &[u8],
+    recs: &[usize],
+    index: &ZipIndex,
+    mut heap_status: u16,
+) -> Result<(), IdbError> {
+    let corrupt = |what: &str| IdbError::Parse(format!("page_zip: modification log {}", what));
+    let mut pos = 0;
+
+    loop {
+        let mut val = *log.get(pos).ok_or_else(|| corrupt("is not terminated"))? as usize;
+        pos += 1;
+        if val == 0 {
+            return Ok(());
+        }
+        if val & 0x80 != 0 {
+            let lo = *log.get(pos).ok_or_else(|| corrupt("is truncated"))? as usize;
+            pos += 1;
+            val = (val & 0x7F) << 8 | lo;
+        }
+        let heap_idx = val >> 1;
+        if heap_idx == 0 || heap_idx > recs.len() {
+            return Err(corrupt("references an invalid heap number"));
+        }
+        let rec = recs[heap_idx - 1];
+        let hs = (((heap_idx + 1) as u16) << 3) | (heap_status & 0x07);
+        if hs > heap_status {
+            return Err(corrupt("skips a heap number"));
+        } else if hs == heap_status {
+            heap_status += 1 << 3;
+        }
+        BigEndian::write_u16(&mut page[rec - 4..], hs);
+        let node_ptr = hs & 0x07 == u16::from(REC_STATUS_NODE_PTR);
+
+        if val & 1 != 0 {
+            // Record was freed: clear its data bytes.
+            let offsets = zip_rec_offsets_on_page(page, rec, index, node_ptr)
+                .ok_or_else(|| corrupt("clears an invalid record"))?;
+            let end = (rec + offsets.data_size()).min(page.len());
+            page[rec..end].fill(0);
+            continue;
+        }
+
+        let offsets = zip_rec_offsets(index, node_ptr, |k| log.get(pos + k).copied())
+            .ok_or_else(|| corrupt("has an invalid record header"))?;
+        let n_extra = offsets.extra_size - REC_N_NEW_EXTRA_BYTES;
+        if rec < offsets.extra_size || pos + n_extra > log.len() {
+            return Err(corrupt("has an invalid record header"));
+        }
+        for k in 0..n_extra {
+            page[rec - REC_N_NEW_EXTRA_BYTES - 1 - k] = log[pos + k];
+        }
+        pos += n_extra;
+
+        let data_end = rec + offsets.data_size();
+        if data_end > page.len() {
+            return Err(corrupt("writes past the page end"));
+        }
+        let mut next_out = rec;
+        let mut copy = |page: &mut [u8], next_out: &mut usize, to: usize| {
+            let len = to
+                .checked_sub(*next_out)
+                .ok_or_else(|| corrupt("has overlapping columns"))?;
+            let src = log
+                .get(pos..pos + len)
+                .ok_or_else(|| corrupt("is truncated"))?;
+            page[*next_out..to].copy_from_slice(src);
+            pos += len;
+            *next_out = to;
+            Ok::<(), IdbError>(())
+        };
+        let ranges = offsets
+            .uncompressed_ranges(index, node_ptr)
+            .ok_or_else(|| corrupt("has an invalid record header"))?;
+        for (start, len) in ranges {
+            copy(page, &mut next_out, rec + start)?;
+            next_out += len;
+        }
+        copy(page, &mut next_out, data_end)?;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -417,4 +1254,305 @@ mod tests {
         page[page_size as usize - 10] = 0x01;
         assert!(!is_hole_punched(&page, page_size));
     }
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        use flate2::write::ZlibEncoder;
+        use std::io::Write;
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// A 16K INDEX page with a recognizable body and trailer.
+    fn logical_page() -> Vec<u8> {
+        let mut page = vec![0u8; 16384];
+        BigEndian::write_u32(&mut page[FIL_PAGE_SPACE_OR_CHKSUM..], 0x1234_5678);
+        BigEndian::write_u32(&mut page[FIL_PAGE_OFFSET..], 3);
+        BigEndian::write_u16(&mut page[FIL_PAGE_TYPE..], 17855);
+        for (i, b) in page[FIL_PAGE_DATA..200].iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        BigEndian::write_u32(&mut page[16380..], 0xCAFE);
+        page
+    }
+
+    /// Compress `page` the way MySQL does for COMPRESSION='zlib'/'lz4'.
+    fn mysql_compressed(page: &[u8], algorithm: u8) -> Vec<u8> {
+        let body = &page[FIL_PAGE_DATA..];
+        let payload = match algorithm {
+            1 => zlib(body),
+            _ => lz4_flex::compress(body),
+        };
+        let mut out = vec![0u8; page.len()];
+        out[..FIL_PAGE_DATA].copy_from_slice(&page[..FIL_PAGE_DATA]);
+        BigEndian::write_u16(&mut out[FIL_PAGE_TYPE..], 14);
+        out[FIL_PAGE_COMPRESSION_VERSION] = 1;
+        out[FIL_PAGE_COMPRESSION_ALGORITHM] = algorithm;
+        BigEndian::write_u16(&mut out[FIL_PAGE_COMPRESSION_ORIGINAL_TYPE..], 17855);
+        BigEndian::write_u16(
+            &mut out[FIL_PAGE_COMPRESSION_ORIGINAL_SIZE..],
+            body.len() as u16,
+        );
+        BigEndian::write_u16(
+            &mut out[FIL_PAGE_COMPRESSION_COMPRESSED_SIZE..],
+            payload.len() as u16,
+        );
+        out[FIL_PAGE_DATA..FIL_PAGE_DATA + payload.len()].copy_from_slice(&payload);
+        out
+    }
+
+    #[test]
+    fn test_decompress_page_mysql() {
+        let page = logical_page();
+        for algorithm in [1, 2] {
+            let compressed = mysql_compressed(&page, algorithm);
+            let inflated = decompress_page(&compressed, 16384).unwrap().unwrap();
+            assert_eq!(inflated, page, "algorithm {}", algorithm);
+        }
+
+        // Unsupported algorithm and truncated payloads are errors
+        let mut compressed = mysql_compressed(&page, 1);
+        compressed[FIL_PAGE_COMPRESSION_ALGORITHM] = 9;
+        assert!(decompress_page(&compressed, 16384).is_err());
+        let mut compressed = mysql_compressed(&page, 2);
+        BigEndian::write_u16(&mut compressed[FIL_PAGE_COMPRESSION_COMPRESSED_SIZE..], 10);
+        assert!(decompress_page(&compressed, 16384).is_err());
+    }
+
+    #[test]
+    fn test_decompress_page_mariadb() {
+        let page = logical_page();
+        for (algorithm, payload) in [(1u64, zlib(&page)), (2, lz4_flex::compress(&page))] {
+            let mut compressed = vec![0u8; 16384];
+            compressed[..FIL_PAGE_DATA].copy_from_slice(&page[..FIL_PAGE_DATA]);
+            BigEndian::write_u32(&mut compressed[FIL_PAGE_SPACE_OR_CHKSUM..], 0xDEADBEEF);
+            BigEndian::write_u16(&mut compressed[FIL_PAGE_TYPE..], FIL_PAGE_PAGE_COMPRESSED);
+            BigEndian::write_u64(&mut compressed[FIL_PAGE_COMP_ALGO..], algorithm);
+            BigEndian::write_u16(&mut compressed[FIL_PAGE_COMP_SIZE..], payload.len() as u16);
+            let start = FIL_PAGE_DATA + FIL_PAGE_COMP_METADATA_LEN;
+            compressed[start..start + payload.len()].copy_from_slice(&payload);
+
+            let inflated = decompress_page(&compressed, 16384).unwrap().unwrap();
+            assert_eq!(inflated, page, "algorithm {}", algorithm);
+
+            // LZO and other algorithms are not supported
+            BigEndian::write_u64(&mut compressed[FIL_PAGE_COMP_ALGO..], 3);
+            assert!(decompress_page(&compressed, 16384).is_err());
+        }
+
+        // Other page types pass through
+        assert!(decompress_page(&page, 16384).unwrap().is_none());
+    }
+
+    /// Build a KEY_BLOCK_SIZE=8 compressed clustered leaf page for
+    /// `(id INT NOT NULL PRIMARY KEY, name VARCHAR(10) NULL)` the way
+    /// `page_zip_compress` lays it out. Returns the compressed page and the
+    /// record origins in heap order.
+    fn build_zip_leaf_page(
+        rows: &[(u32, Option<&[u8]>, bool)],
+        mlog: &[u8],
+    ) -> (Vec<u8>, Vec<usize>) {
+        use flate2::{Compress, Compression, FlushCompress};
+
+        let zip_size = 8192;
+        let mut logical = vec![0u8; 16384];
+        let mut recs = Vec::new();
+        let mut ends = Vec::new();
+        let mut pos = PAGE_ZIP_START;
+        for (i, (id, name, _)) in rows.iter().enumerate() {
+            match name {
+                Some(n) => {
+                    logical[pos] = n.len() as u8; // variable-length byte
+                    logical[pos + 1] = 0; // null bitmap
+                    pos += 2;
+                }
+                None => {
+                    logical[pos] = 0x01;
+                    pos += 1;
+                }
+            }
+            let rec = pos + REC_N_NEW_EXTRA_BYTES;
+            BigEndian::write_u32(&mut logical[rec..], *id);
+            BigEndian::write_u48(&mut logical[rec + 4..], 0x100 + i as u64);
+            BigEndian::write_uint(
+                &mut logical[rec + 10..],
+                0x0001_0000_0000_0000 + i as u64,
+                7,
+            );
+            let mut end = rec + 17;
+            if let Some(n) = name {
+                logical[end..end + n.len()].copy_from_slice(n);
+                end += n.len();
+            }
+            recs.push(rec);
+            ends.push(end);
+            pos = end;
+        }
+        let heap_top = pos;
+
+        // Fields encoding: id (4, NOT NULL), DB_TRX_ID+DB_ROLL_PTR (13, NOT NULL),
+        // name (variable, nullable), then trx_id_col = 1.
+        let fields = [9u8, 27, 0, 1];
+        let mut c = Compress::new(Compression::default(), true);
+        let mut stream = Vec::with_capacity(32768);
+        c.compress_vec(&fields, &mut stream, FlushCompress::Full)
+            .unwrap();
+        let mut next_in = PAGE_ZIP_START;
+        for (&rec, &end) in recs.iter().zip(&ends) {
+            c.compress_vec(&logical[next_in..rec - 5], &mut stream, FlushCompress::None)
+                .unwrap();
+            c.compress_vec(&logical[rec..rec + 4], &mut stream, FlushCompress::None)
+                .unwrap();
+            c.compress_vec(&logical[rec + 17..end], &mut stream, FlushCompress::None)
+                .unwrap();
+            next_in = end;
+        }
+        c.compress_vec(
+            &logical[next_in..heap_top],
+            &mut stream,
+            FlushCompress::Finish,
+        )
+        .unwrap();
+
+        let mut zip = vec![0u8; zip_size];
+        BigEndian::write_u32(&mut zip[FIL_PAGE_OFFSET..], 3);
+        BigEndian::write_u32(&mut zip[FIL_PAGE_PREV..], FIL_NULL);
+        BigEndian::write_u32(&mut zip[FIL_PAGE_NEXT..], FIL_NULL);
+        BigEndian::write_u16(&mut zip[FIL_PAGE_TYPE..], 17855);
+        let hdr = FIL_PAGE_DATA;
+        BigEndian::write_u16(&mut zip[hdr + PAGE_N_DIR_SLOTS..], 2);
+        BigEndian::write_u16(&mut zip[hdr + PAGE_HEAP_TOP..], heap_top as u16);
+        BigEndian::write_u16(
+            &mut zip[hdr + PAGE_N_HEAP..],
+            0x8000 | (2 + rows.len() as u16),
+        );
+        BigEndian::write_u16(&mut zip[hdr + PAGE_N_RECS..], rows.len() as u16);
+        let mlog_start = PAGE_DATA_OFFSET + stream.len();
+        zip[PAGE_DATA_OFFSET..mlog_start].copy_from_slice(&stream);
+        zip[mlog_start..mlog_start + mlog.len()].copy_from_slice(mlog);
+
+        let n = rows.len();
+        for (i, &rec) in recs.iter().enumerate() {
+            let mut slot = rec as u16;
+            if rows[i].2 {
+                slot |= PAGE_ZIP_DIR_SLOT_DEL;
+            }
+            BigEndian::write_u16(&mut zip[zip_size - 2 * (i + 1)..], slot);
+            let storage = zip_size - 2 * n - 13 * (i + 1);
+            zip[storage..storage + 13].copy_from_slice(&logical[rec + 4..rec + 17]);
+        }
+        (zip, recs)
+    }
+
+    #[test]
+    fn test_decompress_zip_page_clustered_leaf() {
+        let rows: [(u32, Option<&[u8]>, bool); 3] = [
+            (1, Some(b"alpha"), false),
+            (2, None, true),
+            (3, Some(b"gamma"), false),
+        ];
+        let (zip, recs) = build_zip_leaf_page(&rows, &[0]);
+        let page = decompress_zip_page(&zip, 16384).unwrap();
+        assert_eq!(page.len(), 16384);
+        assert_eq!(&page[..PAGE_DATA_OFFSET], &zip[..PAGE_DATA_OFFSET]);
+
+        let walked = crate::innodb::record::walk_compact_records(&page);
+        assert_eq!(walked.len(), 3);
+        for (i, rec) in walked.iter().enumerate() {
+            assert_eq!(rec.offset, recs[i]);
+            assert_eq!(rec.header.delete_mark(), rows[i].2);
+            assert_eq!(BigEndian::read_u32(&page[rec.offset..]), rows[i].0);
+            assert_eq!(
+                BigEndian::read_u48(&page[rec.offset + 4..]),
+                0x100 + i as u64
+            );
+        }
+        assert_eq!(&page[recs[0] + 17..recs[0] + 22], b"alpha");
+        assert_eq!(&page[recs[2] + 17..recs[2] + 22], b"gamma");
+        assert_eq!(page[recs[1] - 6], 0x01);
+        assert_eq!(&page[PAGE_NEW_INFIMUM..PAGE_NEW_INFIMUM + 7], b"infimum");
+        assert_eq!(&page[PAGE_NEW_SUPREMUM..PAGE_NEW_SUPREMUM + 8], b"supremum");
+    }
+
+    #[test]
+    fn test_decompress_zip_page_applies_modification_log() {
+        // Rewrite heap_no 3 (second record): header byte 0x01 (name IS NULL),
+        // then the 4 bytes of `id` preceding DB_TRX_ID.
+        let mlog = [4u8, 0x01, 0x00, 0x00, 0x00, 0x2A, 0x00];
+        let rows: [(u32, Option<&[u8]>, bool); 2] = [(1, Some(b"x"), false), (2, None, false)];
+        let (zip, recs) = build_zip_leaf_page(&rows, &mlog);
+        let page = decompress_zip_page(&zip, 16384).unwrap();
+        assert_eq!(BigEndian::read_u32(&page[recs[0]..]), 1);
+        assert_eq!(BigEndian::read_u32(&page[recs[1]..]), 42);
+        // DB_TRX_ID still restored from the uncompressed storage area
+        assert_eq!(BigEndian::read_u48(&page[recs[1] + 4..]), 0x101);
+    }
+
+    #[test]
+    fn test_decompress_zip_page_empty_and_invalid() {
+        let (zip, _) = build_zip_leaf_page(&[], &[0]);
+        let page = decompress_zip_page(&zip, 16384).unwrap();
+        assert!(crate::innodb::record::walk_compact_records(&page).is_empty());
+
+        assert!(decompress_zip_page(&vec![0u8; 8192], 16384).is_err());
+        assert!(decompress_zip_page(&zip[..64], 16384).is_err());
+    }
+
+    #[test]
+    fn test_inflate_zip_stream_skips_marker_bytes_in_payload() {
+        use flate2::{Compress, Compression, FlushCompress};
+
+        // Stored blocks keep the payload verbatim, so the flush marker bytes
+        // appear in the stream before the real full flush.
+        let fields = [0x09u8, 0x00, 0x00, 0xFF, 0xFF, 0x1B, 0x01];
+        let records = [0x41u8, 0x00, 0x00, 0xFF, 0xFF, 0x42];
+        let mut c = Compress::new(Compression::none(), true);
+        let mut stream = Vec::with_capacity(256);
+        c.compress_vec(&fields, &mut stream, FlushCompress::Full)
+            .unwrap();
+        c.compress_vec(&records, &mut stream, FlushCompress::Finish)
+            .unwrap();
+        let marker = stream
+            .windows(4)
+            .position(|w| w == [0x00, 0x00, 0xFF, 0xFF])
+            .unwrap();
+        assert!(marker < 2 + 5 + fields.len());
+        stream.extend_from_slice(&[0x00, 0x00]); // empty modification log
+
+        let (f, r, consumed) = inflate_zip_stream(&stream, 16384).unwrap();
+        assert_eq!(f, fields);
+        assert_eq!(r, records);
+        assert_eq!(consumed, stream.len() - 2);
+    }
+
+    #[test]
+    fn test_zip_uncompressed_ranges_reject_short_fields() {
+        let index = ZipIndex {
+            fields: vec![ZipField {
+                fixed_len: 0,
+                big: true,
+                nullable: false,
+            }],
+            n_nullable: 0,
+            trx_id_col: None,
+        };
+        // A BLOB reference needs 20 bytes; a 5-byte extern field is corrupt
+        let offsets = ZipRecOffsets {
+            ends: vec![5],
+            externs: vec![true],
+            extra_size: REC_N_NEW_EXTRA_BYTES + 2,
+        };
+        assert!(offsets.uncompressed_ranges(&index, false).is_none());
+
+        let offsets = ZipRecOffsets {
+            ends: vec![25],
+            externs: vec![true],
+            extra_size: REC_N_NEW_EXTRA_BYTES + 2,
+        };
+        assert_eq!(
+            offsets.uncompressed_ranges(&index, false),
+            Some(vec![(5, BTR_EXTERN_FIELD_REF_SIZE)])
+        );
+    }
 }
@@ -41,6 +41,9 @@ pub const FIL_PAGE_FILE_FLUSH_LSN: usize = 26;
 /// Offset of the space ID. 4 bytes.
 pub const FIL_PAGE_SPACE_ID: usize = 34;
 
+/// Page type value of B+Tree index pages (`FIL_PAGE_INDEX`).
+pub const FIL_PAGE_INDEX: u16 = 17855;
+
 // ── FIL Trailer (8 bytes total) ─────────────────────────────────────
 
 /// Size of the FIL trailer in bytes.
@@ -68,6 +71,125 @@ pub const FSP_FREE_LIMIT: usize = 12;
 pub const FSP_SPACE_FLAGS: usize = 16;
 /// Offset of the used-page count in the FSP_FREE_FRAG list. 4 bytes.
 pub const FSP_FRAG_N_USED: usize = 20;
+/// Offset of the FSP_FREE list base node (wholly free extents). 16 bytes.
+pub const FSP_FREE: usize = 24;
+/// Offset of the FSP_FREE_FRAG list base node (fragment extents with free pages). 16 bytes.
+pub const FSP_FREE_FRAG: usize = 40;
+/// Offset of the FSP_FULL_FRAG list base node (full fragment extents). 16 bytes.
+pub const FSP_FULL_FRAG: usize = 56;
+/// Offset of the next unused segment ID. 8 bytes.
+pub const FSP_SEG_ID: usize = 72;
+/// Offset of the list base node of INODE pages with no free slots. 16 bytes.
+pub const FSP_SEG_INODES_FULL: usize = 80;
+/// Offset of the list base node of INODE pages with free slots. 16 bytes.
+pub const FSP_SEG_INODES_FREE: usize = 96;
+
+// ── File lists (fut0lst.h) and extent descriptors (fsp0fsp.h) ──────
+
+/// Size of a file address (page number 4 + byte offset 2). 6 bytes.
+pub const FIL_ADDR_SIZE: usize = 6;
+/// Size of a list base node (length 4 + first 6 + last 6). 16 bytes.
+pub const FLST_BASE_NODE_SIZE: usize = 16;
+/// Size of a list node (prev 6 + next 6). 12 bytes.
+pub const FLST_NODE_SIZE: usize = 12;
+/// Offset of the XDES array on page 0 and on XDES pages.
+pub const XDES_ARR_OFFSET: usize = FIL_PAGE_DATA + FSP_HEADER_SIZE;
+/// Offset of the owning segment ID within an XDES entry. 8 bytes.
+pub const XDES_ID: usize = 0;
+/// Offset of the list node within an XDES entry. 12 bytes.
+pub const XDES_FLST_NODE: usize = 8;
+/// Offset of the extent state within an XDES entry. 4 bytes.
+pub const XDES_STATE: usize = 20;
+/// Offset of the page bitmap within an XDES entry (2 bits per page).
+pub const XDES_BITMAP: usize = 24;
+/// Bitmap bit set when the page is free.
+pub const XDES_FREE_BIT: usize = 0;
+/// Bitmap bit reserved for the "clean" flag (always set, unused by InnoDB).
+pub const XDES_CLEAN_BIT: usize = 1;
+/// Bitmap bits per page.
+pub const XDES_BITS_PER_PAGE: usize = 2;
+
+// ── File segment INODE pages (fsp0fsp.h) ────────────────────────────
+
+/// Offset of the list node linking INODE pages into FSP_SEG_INODES_*. 12 bytes.
+pub const FSEG_INODE_PAGE_NODE: usize = FIL_PAGE_DATA;
+/// Offset of the inode array on an INODE page.
+pub const FSEG_ARR_OFFSET: usize = FIL_PAGE_DATA + FLST_NODE_SIZE;
+/// Offset of the segment ID within an inode (0 if the slot is unused). 8 bytes.
+pub const FSEG_ID: usize = 0;
+/// Offset of the used-page count of the NOT_FULL extent list. 4 bytes.
+pub const FSEG_NOT_FULL_N_USED: usize = 8;
+/// Offset of the list base node of wholly free extents owned by the segment. 16 bytes.
+pub const FSEG_FREE: usize = 12;
+/// Offset of the list base node of partially used extents. 16 bytes.
+pub const FSEG_NOT_FULL: usize = 28;
+/// Offset of the list base node of full extents. 16 bytes.
+pub const FSEG_FULL: usize = 44;
+/// Offset of the inode magic number. 4 bytes.
+pub const FSEG_MAGIC_N: usize = 60;
+/// Offset of the fragment page array (one 4-byte page number per slot).
+pub const FSEG_FRAG_ARR: usize = 64;
+/// Size of one fragment array slot.
+pub const FSEG_FRAG_SLOT_SIZE: usize = 4;
+/// Expected value of `FSEG_MAGIC_N` in a used inode.
+pub const FSEG_MAGIC_N_VALUE: u32 = 97937874;
+
+// ── Legacy doublewrite buffer header (trx0sys.h) ───────────────────
+
+/// Page number of the transaction system header in the system tablespace.
+pub const TRX_SYS_PAGE_NO: u64 = 5;
+/// Distance of the doublewrite header from the end of the TRX_SYS page.
+pub const TRX_SYS_DOUBLEWRITE_FROM_END: usize = 200;
+/// Offset of the doublewrite magic number within the header. 4 bytes.
+pub const TRX_SYS_DOUBLEWRITE_MAGIC: usize = FSEG_HEADER_SIZE;
+/// Offset of the first page of block 1. 4 bytes.
+pub const TRX_SYS_DOUBLEWRITE_BLOCK1: usize = FSEG_HEADER_SIZE + 4;
+/// Offset of the first page of block 2. 4 bytes.
+pub const TRX_SYS_DOUBLEWRITE_BLOCK2: usize = FSEG_HEADER_SIZE + 8;
+/// The magic and block fields are repeated at this distance.
+pub const TRX_SYS_DOUBLEWRITE_REPEAT: usize = 12;
+/// Offset of the "space IDs stored" marker. 4 bytes.
+pub const TRX_SYS_DOUBLEWRITE_SPACE_ID_STORED: usize =
+    2 * TRX_SYS_DOUBLEWRITE_REPEAT + FSEG_HEADER_SIZE;
+/// Expected value of `TRX_SYS_DOUBLEWRITE_MAGIC` once the buffer is created.
+pub const TRX_SYS_DOUBLEWRITE_MAGIC_N: u32 = 536853855;
+/// Expected value of `TRX_SYS_DOUBLEWRITE_SPACE_ID_STORED`.
+pub const TRX_SYS_DOUBLEWRITE_SPACE_ID_STORED_N: u32 = 1783657386;
+
+// ── Change buffer (ibuf0ibuf.h) ─────────────────────────────────────
+
+/// Space ID of the change buffer tree (the system tablespace).
+pub const IBUF_SPACE_ID: u32 = 0;
+/// Change buffer header page in the system tablespace.
+pub const FSP_IBUF_HEADER_PAGE_NO: u32 = 3;
+/// Change buffer tree root page in the system tablespace.
+pub const FSP_IBUF_TREE_ROOT_PAGE_NO: u32 = 4;
+/// Index ID of the change buffer tree (`DICT_IBUF_ID_MIN + IBUF_SPACE_ID`).
+pub const DICT_IBUF_ID_MIN: u64 = 0xFFFF_FFFF_0000_0000;
+/// Offset of the bitmap on an IBUF_BITMAP page.
+pub const IBUF_BITMAP: usize = PAGE_DATA;
+/// Bitmap bits per tracked page.
+pub const IBUF_BITS_PER_PAGE: usize = 4;
+/// Bit offset of the 2-bit free space category.
+pub const IBUF_BITMAP_FREE: usize = 0;
+/// Bit offset of the "changes buffered for this page" flag.
+pub const IBUF_BITMAP_BUFFERED: usize = 2;
+/// Bit offset of the "page belongs to the change buffer tree" flag.
+pub const IBUF_BITMAP_IBUF: usize = 3;
+/// Size of the operation info at the start of the metadata field.
+pub const IBUF_REC_INFO_SIZE: usize = 4;
+/// Offset of the 2-byte operation counter in the metadata field.
+pub const IBUF_REC_OFFSET_COUNTER: usize = 0;
+/// Offset of the operation type in the metadata field.
+pub const IBUF_REC_OFFSET_TYPE: usize = 2;
+/// Offset of the flags byte in the metadata field.
+pub const IBUF_REC_OFFSET_FLAGS: usize = 3;
+/// Flag: the buffered record is in compact format.
+pub const IBUF_REC_COMPACT: u8 = 0x1;
+/// Size of the per-field type info in the metadata field.
+pub const DATA_NEW_ORDER_NULL_TYPE_BUF_SIZE: usize = 6;
+/// Number of system fields before the buffered record's fields.
+pub const IBUF_REC_FIELD_USER: usize = 4;
 
 // ── FSP flags bit positions for page size detection ─────────────────
 
@@ -75,6 +197,11 @@ pub const FSP_FRAG_N_USED: usize = 20;
 pub const FSP_FLAGS_POS_PAGE_SSIZE: u32 = 6;
 /// Bitmask for the 4-bit page size field within FSP flags.
 pub const FSP_FLAGS_MASK_PAGE_SSIZE: u32 = 0xF << FSP_FLAGS_POS_PAGE_SSIZE;
+/// Bit position of the compressed page size (ZIP_SSIZE) field within FSP flags.
+pub const FSP_FLAGS_POS_ZIP_SSIZE: u32 = 1;
+/// Bitmask for the 4-bit compressed page size field within FSP flags.
+/// Non-zero only for ROW_FORMAT=COMPRESSED tablespaces.
+pub const FSP_FLAGS_MASK_ZIP_SSIZE: u32 = 0xF << FSP_FLAGS_POS_ZIP_SSIZE;
 
 // ── Page Header (INDEX page specific, starts at FIL_PAGE_DATA) ──────
 
@@ -121,6 +248,22 @@ pub const REC_N_OLD_EXTRA_BYTES: usize = 6;
 /// Extra bytes preceding each record in new-style (compact) format.
 pub const REC_N_NEW_EXTRA_BYTES: usize = 5;
 
+/// Record status (low 3 bits of heap_no) of leaf (ordinary) records.
+pub const REC_STATUS_ORDINARY: u8 = 0;
+/// Record status of node pointer (non-leaf) records.
+pub const REC_STATUS_NODE_PTR: u8 = 1;
+/// First heap number used by user records (0 = infimum, 1 = supremum).
+pub const PAGE_HEAP_NO_USER_LOW: usize = 2;
+
+/// Info bit marking the leftmost node pointer on a level.
+pub const REC_INFO_MIN_REC_FLAG: u8 = 0x10;
+/// Info bit marking a delete-marked record.
+pub const REC_INFO_DELETED_FLAG: u8 = 0x20;
+/// Info bit marking a record that carries a row version (8.0.29+).
+pub const REC_INFO_VERSION_FLAG: u8 = 0x40;
+/// Info bit marking a record that stores its field count (8.0.12-8.0.28).
+pub const REC_INFO_INSTANT_FLAG: u8 = 0x80;
+
 // ── System record offsets (compact pages) ───────────────────────────
 
 /// Computed data offset (FIL_PAGE_DATA + PAGE_HEADER + 2 * FSEG_HEADER).
@@ -180,6 +323,32 @@ pub const FIL_PAGE_PAGE_COMPRESSED_ENCRYPTED: u16 = 37401;
 /// MariaDB instant ALTER TABLE metadata page.
 pub const FIL_PAGE_TYPE_INSTANT: u16 = 18;
 
+// ── Transparent page compression ───────────────────────────────────
+
+/// MySQL compression header: format version (1 byte at offset 26).
+pub const FIL_PAGE_COMPRESSION_VERSION: usize = 26;
+
+/// MySQL compression header: algorithm (1 byte at offset 27; 1 = zlib, 2 = LZ4).
+pub const FIL_PAGE_COMPRESSION_ALGORITHM: usize = 27;
+
+/// MySQL compression header: page type before compression (2 bytes at offset 28).
+pub const FIL_PAGE_COMPRESSION_ORIGINAL_TYPE: usize = 28;
+
+/// MySQL compression header: uncompressed payload size (2 bytes at offset 30).
+pub const FIL_PAGE_COMPRESSION_ORIGINAL_SIZE: usize = 30;
+
+/// MySQL compression header: compressed payload size (2 bytes at offset 32).
+pub const FIL_PAGE_COMPRESSION_COMPRESSED_SIZE: usize = 32;
+
+/// MariaDB page_compressed: algorithm, an 8-byte field at offset 26.
+pub const FIL_PAGE_COMP_ALGO: usize = 26;
+
+/// MariaDB page_compressed: compressed payload size (2 bytes at FIL_PAGE_DATA).
+pub const FIL_PAGE_COMP_SIZE: usize = FIL_PAGE_DATA;
+
+/// MariaDB page_compressed: size of the metadata before the payload.
+pub const FIL_PAGE_COMP_METADATA_LEN: usize = 2;
+
 // ── Encryption constants ──────────────────────────────────────────
 
 /// Size of the encryption magic marker. 3 bytes.
@@ -214,6 +383,36 @@ pub const FIL_PAGE_ORIGINAL_TYPE_V1: usize = 26;
 /// Size of a single XDES (extent descriptor) entry. 40 bytes.
 pub const XDES_SIZE: usize = 40;
 
+// ── Compressed page (page_zip) layout ──────────────────────────────
+
+/// End of the supremum record on a compact page (offset 120).
+pub const PAGE_NEW_SUPREMUM_END: usize = PAGE_NEW_SUPREMUM + 8;
+/// Start of the compressed record heap; same as the end of the supremum record.
+pub const PAGE_ZIP_START: usize = PAGE_NEW_SUPREMUM_END;
+/// Size of a sparse page directory slot (2 bytes).
+pub const PAGE_DIR_SLOT_SIZE: usize = 2;
+/// Minimum number of records owned by a directory slot other than the
+/// infimum and supremum slots.
+pub const PAGE_DIR_SLOT_MIN_N_OWNED: u8 = 4;
+/// Maximum number of records owned by a directory slot.
+pub const PAGE_DIR_SLOT_MAX_N_OWNED: u8 = 8;
+/// Size of a dense page directory slot on a compressed page (2 bytes).
+pub const PAGE_ZIP_DIR_SLOT_SIZE: usize = 2;
+/// Mask for the record offset stored in a dense directory slot.
+pub const PAGE_ZIP_DIR_SLOT_MASK: u16 = 0x3FFF;
+/// Dense directory flag: the record owns a sparse directory slot.
+pub const PAGE_ZIP_DIR_SLOT_OWNED: u16 = 0x4000;
+/// Dense directory flag: the record is delete-marked.
+pub const PAGE_ZIP_DIR_SLOT_DEL: u16 = 0x8000;
+/// Length of the DB_TRX_ID system column (6 bytes).
+pub const DATA_TRX_ID_LEN: usize = 6;
+/// Length of the DB_ROLL_PTR system column (7 bytes).
+pub const DATA_ROLL_PTR_LEN: usize = 7;
+/// Size of the child page pointer in a node pointer record (4 bytes).
+pub const REC_NODE_PTR_SIZE: usize = 4;
+/// Size of an externally stored (BLOB) field reference (20 bytes).
+pub const BTR_EXTERN_FIELD_REF_SIZE: usize = 20;
+
 // ── Insert direction values ─────────────────────────────────────────
 
 /// Insert direction: left.
@@ -226,3 +425,56 @@ pub const PAGE_SAME_REC: u16 = 3;
 pub const PAGE_SAME_PAGE: u16 = 4;
 /// Insert direction: no direction.
 pub const PAGE_NO_DIRECTION: u16 = 5;
+
+// ── Undo log pages (trx0undo.h) ─────────────────────────────────────
+
+/// Offset of the undo page header.
+pub const TRX_UNDO_PAGE_HDR: usize = FIL_PAGE_DATA;
+/// Offset of the undo page type within the page header. 2 bytes.
+pub const TRX_UNDO_PAGE_TYPE: usize = 0;
+/// Offset of the first record of the latest undo log. 2 bytes.
+pub const TRX_UNDO_PAGE_START: usize = 2;
+/// Offset of the first free byte on the page. 2 bytes.
+pub const TRX_UNDO_PAGE_FREE: usize = 4;
+/// Offset of the undo page list node. 12 bytes (FLST_NODE).
+pub const TRX_UNDO_PAGE_NODE: usize = 6;
+/// Size of the undo page header.
+pub const TRX_UNDO_PAGE_HDR_SIZE: usize = 18;
+
+/// Offset of the undo segment header (first page of a segment only).
+pub const TRX_UNDO_SEG_HDR: usize = TRX_UNDO_PAGE_HDR + TRX_UNDO_PAGE_HDR_SIZE;
+/// Offset of the segment state within the segment header. 2 bytes.
+pub const TRX_UNDO_STATE: usize = 0;
+/// Offset of the latest undo log header. 2 bytes.
+pub const TRX_UNDO_LAST_LOG: usize = 2;
+/// Offset of the segment's FSEG header. 10 bytes.
+pub const TRX_UNDO_FSEG_HEADER: usize = 4;
+/// Offset of the segment page list base node. 16 bytes (FLST_BASE_NODE).
+pub const TRX_UNDO_PAGE_LIST: usize = 14;
+/// Size of the undo segment header.
+pub const TRX_UNDO_SEG_HDR_SIZE: usize = 30;
+/// Segment state: contains an undo log of an active transaction.
+pub const TRX_UNDO_ACTIVE: u16 = 1;
+
+/// Offset of the transaction ID within an undo log header. 8 bytes.
+pub const TRX_UNDO_TRX_ID: usize = 0;
+/// Offset of the transaction commit number. 8 bytes.
+pub const TRX_UNDO_TRX_NO: usize = 8;
+/// Offset of the delete-marks flag. 2 bytes.
+pub const TRX_UNDO_DEL_MARKS: usize = 16;
+/// Offset of the first undo record of this log. 2 bytes.
+pub const TRX_UNDO_LOG_START: usize = 18;
+/// Offset of the log flags (`TRX_UNDO_XID_EXISTS` before 8.0). 1 byte.
+pub const TRX_UNDO_FLAGS: usize = 20;
+/// Offset of the dictionary-operation flag. 1 byte.
+pub const TRX_UNDO_DICT_TRANS: usize = 21;
+/// Offset of the table ID of a dictionary operation. 8 bytes.
+pub const TRX_UNDO_TABLE_ID: usize = 22;
+/// Offset of the next undo log header on the page. 2 bytes.
+pub const TRX_UNDO_NEXT_LOG: usize = 30;
+/// Offset of the previous undo log header on the page. 2 bytes.
+pub const TRX_UNDO_PREV_LOG: usize = 32;
+/// Size of an undo log header without XID information.
+pub const TRX_UNDO_LOG_OLD_HDR_SIZE: usize = 34 + FLST_NODE_SIZE;
+/// Size of an undo log header with XID information.
+pub const TRX_UNDO_LOG_XA_HDR_SIZE: usize = TRX_UNDO_LOG_OLD_HDR_SIZE + 140;
@@ -198,8 +198,13 @@ mod tests {
         // Fill the ENTIRE data area with non-zero, varied data so ZeroFill
         // never triggers on test pages that should match other patterns
         let data_end = ps - SIZE_FIL_TRAILER;
-        for i in FIL_PAGE_DATA..data_end {
-            page[i] = ((i.wrapping_mul(7).wrapping_add(13)) & 0xFF) as u8;
+        for (i, b) in page
+            .iter_mut()
+            .enumerate()
+            .take(data_end)
+            .skip(FIL_PAGE_DATA)
+        {
+            *b = ((i.wrapping_mul(7).wrapping_add(13)) & 0xFF) as u8;
         }
 
         // Trailer LSN (low 32 bits of header LSN)
@@ -0,0 +1,313 @@
+//! System tablespace data files (`innodb_data_file_path`).
+//!
+//! The system tablespace can be split across several files, configured as
+//! `innodb_data_file_path=ibdata1:1G;ibdata2:1G:autoextend`. InnoDB numbers
+//! the pages continuously across the files in the listed order: page 0 is
+//! the first page of the first file, and the first page of each later file
+//! follows the last page of the one before it.
+//!
+//! This module parses the setting (from its value or a `my.cnf` file) and
+//! resolves the ordered list of [`DataFile`]s, which
+//! [`Tablespace::open_data_files`](crate::innodb::tablespace::Tablespace::open_data_files)
+//! opens as one logical page space.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::IdbError;
+
+/// One entry of `innodb_data_file_path`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DataFileSpec {
+    /// File name, relative to `innodb_data_home_dir` (or absolute).
+    pub name: String,
+    /// Configured size in bytes.
+    pub size: u64,
+    /// The file grows past its configured size (only the last file).
+    pub autoextend: bool,
+    /// Maximum size in bytes of an autoextending file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<u64>,
+    /// The file is a raw disk partition (`newraw` or `raw`).
+    pub raw: bool,
+}
+
+/// A file of a (possibly multi-file) tablespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataFile {
+    /// Path to the file.
+    pub path: PathBuf,
+    /// Number of bytes of the file that belong to the tablespace, or `None`
+    /// to use the whole file (autoextending or inferred files).
+    pub size: Option<u64>,
+}
+
+/// System tablespace settings read from a `my.cnf` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemTablespaceConfig {
+    /// `innodb_data_home_dir`, if set.
+    pub data_home_dir: Option<String>,
+    /// `innodb_data_file_path`, if set.
+    pub data_file_path: Option<String>,
+}
+
+impl SystemTablespaceConfig {
+    /// Read the settings from the server sections of a `my.cnf` file.
+    ///
+    /// Options are taken from `[mysqld]`, `[server]`, `[mariadb]`,
+    /// `[mariadbd]`, and versioned `[mysqld-X.Y]` sections; dashes and
+    /// underscores in option names are interchangeable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use idb::innodb::datafile::SystemTablespaceConfig;
+    ///
+    /// let cnf = "[client]\nuser=root\n\n[mysqld]\ninnodb-data-file-path = ibdata1:12M;ibdata2:12M:autoextend\n";
+    /// let cfg = SystemTablespaceConfig::from_cnf(cnf);
+    /// assert_eq!(
+    ///     cfg.data_file_path.as_deref(),
+    ///     Some("ibdata1:12M;ibdata2:12M:autoextend")
+    /// );
+    /// assert!(cfg.data_home_dir.is_none());
+    /// ```
+    pub fn from_cnf(content: &str) -> Self {
+        let mut cfg = SystemTablespaceConfig::default();
+        let mut in_server = false;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                let section = line.trim_matches(|c| c == '[' || c == ']').to_lowercase();
+                in_server = matches!(
+                    section.as_str(),
+                    "mysqld" | "server" | "mariadb" | "mariadbd"
+                ) || section.starts_with("mysqld-")
+                    || section.starts_with("mariadb-");
+                continue;
+            }
+            if !in_server || line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                let key = key.trim().to_lowercase().replace('-', "_");
+                let value = value
+                    .split('#')
+                    .next()
+                    .unwrap_or("")
+                    .trim()
+                    .trim_matches('"')
+                    .trim_matches('\'')
+                    .to_string();
+                match key.as_str() {
+                    "innodb_data_file_path" => cfg.data_file_path = Some(value),
+                    "innodb_data_home_dir" => cfg.data_home_dir = Some(value),
+                    _ => {}
+                }
+            }
+        }
+
+        cfg
+    }
+
+    /// Read the settings from a `my.cnf` file on disk.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_cnf_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, IdbError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| IdbError::Io(format!("Cannot read {}: {}", path.display(), e)))?;
+        Ok(Self::from_cnf(&content))
+    }
+}
+
+/// Parse a size such as `12M`, `1G`, or `65536` (bytes) into bytes.
+fn parse_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let (digits, multiplier) = match s.chars().last()?.to_ascii_uppercase() {
+        'K' => (&s[..s.len() - 1], 1u64 << 10),
+        'M' => (&s[..s.len() - 1], 1u64 << 20),
+        'G' => (&s[..s.len() - 1], 1u64 << 30),
+        'T' => (&s[..s.len() - 1], 1u64 << 40),
+        _ => (s, 1),
+    };
+    digits.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+/// Parse an `innodb_data_file_path` value.
+///
+/// Each `;`-separated entry has the form
+/// `name:size[newraw|raw][:autoextend[:max:size]]`; sizes take a `K`, `M`,
+/// `G`, or `T` suffix (no suffix means bytes). Only the last entry may
+/// autoextend.
+///
+/// # Examples
+///
+/// ```
+/// use idb::innodb::datafile::parse_data_file_path;
+///
+/// let specs = parse_data_file_path("ibdata1:1G;ibdata2:512M:autoextend:max:2G").unwrap();
+/// assert_eq!(specs.len(), 2);
+/// assert_eq!(specs[0].name, "ibdata1");
+/// assert_eq!(specs[0].size, 1 << 30);
+/// assert!(!specs[0].autoextend);
+/// assert!(specs[1].autoextend);
+/// assert_eq!(specs[1].max_size, Some(2 << 30));
+/// ```
+pub fn parse_data_file_path(value: &str) -> Result<Vec<DataFileSpec>, IdbError> {
+    let mut specs = Vec::new();
+    let entries: Vec<&str> = value
+        .split(';')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .collect();
+
+    for (i, entry) in entries.iter().enumerate() {
+        let err = || IdbError::Argument(format!("Invalid innodb_data_file_path entry: {}", entry));
+
+        // Keep a Windows drive letter ("C:\...") with the name
+        let name_start = match entry.as_bytes() {
+            [d, b':', b'\\' | b'/', ..] if d.is_ascii_alphabetic() => 2,
+            _ => 0,
+        };
+        let colon = entry[name_start..].find(':').ok_or_else(err)? + name_start;
+        let name = entry[..colon].to_string();
+        let mut parts = entry[colon + 1..].split(':');
+
+        let size_str = parts.next().ok_or_else(err)?.to_lowercase();
+        let (size_str, raw) = match size_str
+            .strip_suffix("newraw")
+            .or_else(|| size_str.strip_suffix("raw"))
+        {
+            Some(s) => (s.to_string(), true),
+            None => (size_str.clone(), false),
+        };
+        let size = parse_size(&size_str).ok_or_else(err)?;
+
+        let mut autoextend = false;
+        let mut max_size = None;
+        match parts.next() {
+            None => {}
+            Some(a) if a.eq_ignore_ascii_case("autoextend") => {
+                if i + 1 != entries.len() {
+                    return Err(IdbError::Argument(format!(
+                        "Only the last innodb_data_file_path entry can autoextend: {}",
+                        entry
+                    )));
+                }
+                autoextend = true;
+                match parts.next() {
+                    None => {}
+                    Some(m) if m.eq_ignore_ascii_case("max") => {
+                        max_size = Some(parts.next().and_then(parse_size).ok_or_else(err)?);
+                    }
+                    Some(_) => return Err(err()),
+                }
+            }
+            Some(_) => return Err(err()),
+        }
+        if parts.next().is_some() || name.is_empty() {
+            return Err(err());
+        }
+
+        specs.push(DataFileSpec {
+            name,
+            size,
+            autoextend,
+            max_size,
+            raw,
+        });
+    }
+
+    if specs.is_empty() {
+        return Err(IdbError::Argument(
+            "innodb_data_file_path lists no data files".to_string(),
+        ));
+    }
+    Ok(specs)
+}
+
+/// Resolve the ordered data files of the system tablespace in `datadir`.
+///
+/// With an `innodb_data_file_path` in `config`, its files are located in
+/// `innodb_data_home_dir` (when set and present) or `datadir`, and each
+/// file except an autoextending last one is limited to its configured size.
+/// Without one, `ibdata1`, `ibdata2`, ... are taken in order for as long
+/// as they exist, each contributing its whole file.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn system_data_files(
+    datadir: &std::path::Path,
+    config: &SystemTablespaceConfig,
+) -> Result<Vec<DataFile>, IdbError> {
+    let spec = match config.data_file_path {
+        Some(ref path) => path,
+        None => {
+            let files: Vec<DataFile> = (1..)
+                .map(|n| datadir.join(format!("ibdata{}", n)))
+                .take_while(|p| p.is_file())
+                .map(|path| DataFile { path, size: None })
+                .collect();
+            if files.is_empty() {
+                return Err(IdbError::Io(format!(
+                    "ibdata1 not found in {}",
+                    datadir.display()
+                )));
+            }
+            return Ok(files);
+        }
+    };
+
+    let home = config
+        .data_home_dir
+        .as_deref()
+        .filter(|d| !d.is_empty())
+        .map(std::path::Path::new)
+        .filter(|d| d.is_dir())
+        .unwrap_or(datadir);
+    Ok(parse_data_file_path(spec)?
+        .into_iter()
+        .map(|s| DataFile {
+            path: home.join(&s.name),
+            size: if s.autoextend { None } else { Some(s.size) },
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_data_file_path_variants() {
+        let specs = parse_data_file_path("ibdata1:12M:autoextend").unwrap();
+        assert_eq!(specs[0].size, 12 << 20);
+        assert!(specs[0].autoextend);
+
+        let specs = parse_data_file_path("/dev/sdb1:3Gnewraw;ibdata2:65536").unwrap();
+        assert_eq!(specs[0].name, "/dev/sdb1");
+        assert!(specs[0].raw);
+        assert_eq!(specs[1].size, 65536);
+
+        let specs = parse_data_file_path(r"C:\data\ibdata1:10M").unwrap();
+        assert_eq!(specs[0].name, r"C:\data\ibdata1");
+
+        assert!(parse_data_file_path("ibdata1:1G:autoextend;ibdata2:1G").is_err());
+        assert!(parse_data_file_path("ibdata1").is_err());
+        assert!(parse_data_file_path("ibdata1:abc").is_err());
+        assert!(parse_data_file_path("").is_err());
+    }
+
+    #[test]
+    fn test_from_cnf_ignores_other_sections() {
+        let cnf = "[client]\ninnodb_data_file_path=wrong:1M\n[mysqld-8.0]\n\
+                   innodb_data_home_dir = /var/lib/mysql-sys # comment\n\
+                   innodb_data_file_path=\"ibdata1:1G;ibdata2:1G:autoextend\"\n";
+        let cfg = SystemTablespaceConfig::from_cnf(cnf);
+        assert_eq!(cfg.data_home_dir.as_deref(), Some("/var/lib/mysql-sys"));
+        assert_eq!(
+            cfg.data_file_path.as_deref(),
+            Some("ibdata1:1G;ibdata2:1G:autoextend")
+        );
+    }
+}
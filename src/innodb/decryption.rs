@@ -3,7 +3,9 @@
 //! Provides [`DecryptionContext`] which holds the per-tablespace key and IV
 //! derived from the keyring master key and the encryption info on page 0.
 //! Pages with encrypted page types (15, 16, 17) are decrypted in-place
-//! by [`DecryptionContext::decrypt_page`].
+//! by [`DecryptionContext::decrypt_page`]; redo log blocks written with
+//! `innodb_redo_log_encrypt` are decrypted by
+//! [`DecryptionContext::decrypt_log_block`].
 
 use aes::cipher::block_padding::NoPadding;
 use aes::cipher::{BlockDecryptMut, KeyInit, KeyIvInit};
@@ -13,14 +15,22 @@ use byteorder::{BigEndian, ByteOrder};
 use crate::innodb::constants::*;
 use crate::innodb::encryption::EncryptionInfo;
 use crate::innodb::keyring::Keyring;
+use crate::innodb::log::{LOG_BLOCK_ENCRYPT_BIT_MASK, LOG_BLOCK_SIZE};
 use crate::innodb::page_types::PageType;
 use crate::IdbError;
 
 type Aes256CbcDec = cbc::Decryptor<Aes256>;
 type Aes256EcbDec = ecb::Decryptor<Aes256>;
 
+/// Start of the encrypted part of a redo log block (after the 12-byte
+/// block header).
+const LOG_BLOCK_ENCRYPT_START: usize = 12;
+
 /// Holds the decrypted per-tablespace key and IV for page decryption.
-#[derive(Debug)]
+///
+/// A redo log's key and IV are stored the same way, so one context also
+/// decrypts redo log blocks.
+#[derive(Debug, Clone)]
 pub struct DecryptionContext {
     /// Decrypted 32-byte tablespace key for AES-256-CBC.
     tablespace_key: [u8; 32],
@@ -110,8 +120,14 @@ impl DecryptionContext {
         }
 
         // Read the original page type stored at offset 26 (FIL_PAGE_FILE_FLUSH_LSN)
-        // MySQL saves the original type here before encrypting
-        let original_type = BigEndian::read_u16(&page_data[FIL_PAGE_ORIGINAL_TYPE_V1..]);
+        // MySQL saves the original type here before encrypting. Compressed
+        // pages keep their compression header there instead and decrypt
+        // back to FIL_PAGE_COMPRESSED.
+        let original_type = if page_type == PageType::CompressedEncrypted {
+            PageType::Compressed.as_u16()
+        } else {
+            BigEndian::read_u16(&page_data[FIL_PAGE_ORIGINAL_TYPE_V1..])
+        };
 
         // Encrypted range: [38..page_size-8)
         let encrypt_start = SIZE_FIL_HEAD;
@@ -151,6 +167,50 @@ impl DecryptionContext {
         Ok(true)
     }
 
+    /// Decrypt an encrypted redo log block in-place.
+    ///
+    /// MySQL encrypts bytes [12..512) of each data block (everything after
+    /// the block header, including the checksum) with AES-256-CBC, then
+    /// encrypts the last 32 bytes again because 500 is not a multiple of
+    /// the AES block size. Both steps are undone here and the encrypt bit
+    /// (bit 15 of the data length) is cleared, so the block checksum can
+    /// then be validated as usual.
+    ///
+    /// Returns `Ok(false)` if the block's encrypt bit is not set.
+    pub fn decrypt_log_block(&self, block: &mut [u8]) -> Result<bool, IdbError> {
+        if block.len() < LOG_BLOCK_SIZE {
+            return Err(IdbError::Parse(
+                "Redo log block too short for decryption".to_string(),
+            ));
+        }
+
+        let data_len = BigEndian::read_u16(&block[4..]);
+        if data_len & LOG_BLOCK_ENCRYPT_BIT_MASK == 0 {
+            return Ok(false);
+        }
+
+        let aes_block_size = 16;
+        let body = &mut block[LOG_BLOCK_ENCRYPT_START..LOG_BLOCK_SIZE];
+        let main_len = body.len() / aes_block_size * aes_block_size;
+        let iv: [u8; 16] = self.tablespace_iv[..16].try_into().unwrap();
+        let decrypt = |data: &mut [u8]| {
+            Aes256CbcDec::new_from_slices(&self.tablespace_key, &iv)
+                .map_err(|e| IdbError::Parse(format!("AES-256-CBC init failed: {}", e)))?
+                .decrypt_padded_mut::<NoPadding>(data)
+                .map(|_| ())
+                .map_err(|e| IdbError::Parse(format!("AES-256-CBC decrypt failed: {}", e)))
+        };
+
+        if main_len < body.len() {
+            let tail = body.len() - 2 * aes_block_size;
+            decrypt(&mut body[tail..])?;
+        }
+        decrypt(&mut body[..main_len])?;
+
+        BigEndian::write_u16(&mut block[4..], data_len & !LOG_BLOCK_ENCRYPT_BIT_MASK);
+        Ok(true)
+    }
+
     /// Check if a page has an encrypted page type.
     pub fn is_encrypted_page(page_data: &[u8]) -> bool {
         if page_data.len() < SIZE_FIL_HEAD {
@@ -193,8 +253,13 @@ mod tests {
         BigEndian::write_u32(&mut page[FIL_PAGE_SPACE_ID..], space_id);
 
         // Write some recognizable data in the page body
-        for i in SIZE_FIL_HEAD..page_size - SIZE_FIL_TRAILER {
-            page[i] = ((i * 7 + 13) & 0xFF) as u8;
+        for (i, b) in page
+            .iter_mut()
+            .enumerate()
+            .take(page_size - SIZE_FIL_TRAILER)
+            .skip(SIZE_FIL_HEAD)
+        {
+            *b = ((i * 7 + 13) & 0xFF) as u8;
         }
 
         // Encrypt body: [38..page_size-8)
@@ -231,8 +296,13 @@ mod tests {
 
         // Build a reference page with original content
         let mut reference = vec![0u8; page_size];
-        for i in SIZE_FIL_HEAD..page_size - SIZE_FIL_TRAILER {
-            reference[i] = ((i * 7 + 13) & 0xFF) as u8;
+        for (i, b) in reference
+            .iter_mut()
+            .enumerate()
+            .take(page_size - SIZE_FIL_TRAILER)
+            .skip(SIZE_FIL_HEAD)
+        {
+            *b = ((i * 7 + 13) & 0xFF) as u8;
         }
 
         // Build encrypted version
@@ -277,6 +347,46 @@ mod tests {
         assert!(!result);
     }
 
+    #[test]
+    fn test_decrypt_log_block_roundtrip() {
+        let key: [u8; 32] = [0x42; 32];
+        let iv: [u8; 32] = [0x13; 32];
+        let cbc_iv: [u8; 16] = iv[..16].try_into().unwrap();
+
+        let mut reference = vec![0u8; LOG_BLOCK_SIZE];
+        BigEndian::write_u32(&mut reference[0..], 17);
+        BigEndian::write_u16(&mut reference[4..], 300);
+        BigEndian::write_u16(&mut reference[6..], 12);
+        for (i, b) in reference.iter_mut().enumerate().take(300).skip(12) {
+            *b = (i * 7 + 13) as u8;
+        }
+        let crc = crc32c::crc32c(&reference[..508]);
+        BigEndian::write_u32(&mut reference[508..], crc);
+
+        // As MySQL writes it: the body, then its last 32 bytes again
+        let mut block = reference.clone();
+        Aes256CbcEnc::new_from_slices(&key, &cbc_iv)
+            .unwrap()
+            .encrypt_padded_mut::<NoPadding>(&mut block[12..508], 496)
+            .unwrap();
+        Aes256CbcEnc::new_from_slices(&key, &cbc_iv)
+            .unwrap()
+            .encrypt_padded_mut::<NoPadding>(&mut block[480..512], 32)
+            .unwrap();
+        BigEndian::write_u16(&mut block[4..], 300 | LOG_BLOCK_ENCRYPT_BIT_MASK);
+
+        let ctx = DecryptionContext {
+            tablespace_key: key,
+            tablespace_iv: iv,
+        };
+        assert!(ctx.decrypt_log_block(&mut block).unwrap());
+        assert_eq!(block, reference);
+
+        // Already plaintext: left alone
+        assert!(!ctx.decrypt_log_block(&mut block).unwrap());
+        assert_eq!(block, reference);
+    }
+
     #[test]
     fn test_is_encrypted_page() {
         let mut page = vec![0u8; 38];
@@ -334,7 +444,7 @@ mod tests {
         }
 
         let mut entry = Vec::new();
-        let pod_size = 40 + key_id.len() + 3 + 0 + 32;
+        let pod_size = 40 + key_id.len() + 3 + 32;
         entry.extend_from_slice(&(pod_size as u64).to_le_bytes());
         entry.extend_from_slice(&(key_id.len() as u64).to_le_bytes());
         entry.extend_from_slice(&(3u64).to_le_bytes()); // "AES"
@@ -400,7 +510,7 @@ mod tests {
         }
 
         let mut entry = Vec::new();
-        let pod_size = 40 + key_id.len() + 3 + 0 + 32;
+        let pod_size = 40 + key_id.len() + 3 + 32;
         entry.extend_from_slice(&(pod_size as u64).to_le_bytes());
         entry.extend_from_slice(&(key_id.len() as u64).to_le_bytes());
         entry.extend_from_slice(&(3u64).to_le_bytes());
@@ -0,0 +1,322 @@
+//! Doublewrite buffer parsing.
+//!
+//! Before InnoDB writes a page to its tablespace it first writes a copy to
+//! the doublewrite buffer. If the server crashes mid-write and leaves a
+//! *torn* page (part old, part new), crash recovery restores the page from
+//! that copy. The buffer lives in one of two places:
+//!
+//! - **MySQL 8.0.20+**: separate `#ib_<page_size>_<n>.dblwr` files in the
+//!   data directory. Each file is a sequence of full page copies; unused
+//!   slots are zero-filled.
+//! - **Earlier versions**: two blocks of one extent each inside the system
+//!   tablespace (normally pages 64–191 of `ibdata1`), located through the
+//!   doublewrite header on the TRX_SYS page (page 5).
+//!
+//! [`parse_doublewrite_pages`] lists the copies in either form, and
+//! [`find_restore_copy`] picks the copy that can replace a damaged page,
+//! following the same rules InnoDB applies during recovery.
+
+use byteorder::{BigEndian, ByteOrder};
+use serde::Serialize;
+
+use crate::innodb::checksum::{validate_checksum, validate_lsn};
+use crate::innodb::constants::*;
+use crate::innodb::page::FilHeader;
+use crate::innodb::page_types::PageType;
+use crate::innodb::tablespace::Tablespace;
+use crate::innodb::vendor::VendorInfo;
+use crate::innodb::xdes;
+use crate::IdbError;
+
+/// A page copy held in the doublewrite buffer.
+#[derive(Debug, Clone, Serialize)]
+pub struct DblwrPage {
+    /// Position in the buffer: the slot index in a `.dblwr` file, or the
+    /// system tablespace page number for the legacy buffer.
+    pub slot: u32,
+    /// Space ID from the copy's FIL header.
+    pub space_id: u32,
+    /// Page number from the copy's FIL header.
+    pub page_no: u32,
+    /// LSN from the copy's FIL header.
+    pub lsn: u64,
+    /// Page type from the copy's FIL header.
+    pub page_type: PageType,
+    /// Whether the copy's checksum is valid.
+    pub checksum_valid: bool,
+    /// Whether the header and trailer LSNs agree.
+    pub lsn_valid: bool,
+    /// The page copy.
+    #[serde(skip)]
+    pub data: Vec<u8>,
+}
+
+impl DblwrPage {
+    /// Whether the copy is intact and can be used to restore a page.
+    pub fn is_valid(&self) -> bool {
+        self.checksum_valid && self.lsn_valid
+    }
+}
+
+/// Parse the page size and file number from a `#ib_<page_size>_<n>.dblwr`
+/// file name.
+///
+/// # Examples
+///
+/// ```
+/// use idb::innodb::doublewrite::parse_dblwr_file_name;
+///
+/// assert_eq!(parse_dblwr_file_name("#ib_16384_0.dblwr"), Some((16384, 0)));
+/// assert_eq!(parse_dblwr_file_name("/data/#ib_8192_1.dblwr"), Some((8192, 1)));
+/// assert_eq!(parse_dblwr_file_name("ibdata1"), None);
+/// ```
+pub fn parse_dblwr_file_name(path: &str) -> Option<(u32, u32)> {
+    let name = std::path::Path::new(path).file_name()?.to_str()?;
+    let stem = name.strip_prefix("#ib_")?.strip_suffix(".dblwr")?;
+    let (page_size, n) = stem.split_once('_')?;
+    Some((page_size.parse().ok()?, n.parse().ok()?))
+}
+
+/// List the page copies in a doublewrite buffer.
+///
+/// `data` holds consecutive page slots of `page_size` bytes, such as the
+/// contents of a `.dblwr` file. Zero-filled slots are skipped. `first_slot`
+/// is the slot number of the first page (0 for `.dblwr` files, the first
+/// block page for the legacy buffer).
+pub fn parse_doublewrite_pages(
+    data: &[u8],
+    page_size: u32,
+    first_slot: u32,
+    vendor_info: Option<&VendorInfo>,
+) -> Vec<DblwrPage> {
+    let ps = page_size as usize;
+    if ps == 0 {
+        return Vec::new();
+    }
+    data.chunks_exact(ps)
+        .enumerate()
+        .filter(|(_, page)| page.iter().any(|&b| b != 0))
+        .filter_map(|(i, page)| {
+            let header = FilHeader::parse(page)?;
+            Some(DblwrPage {
+                slot: first_slot + i as u32,
+                space_id: header.space_id,
+                page_no: header.page_number,
+                lsn: header.lsn,
+                page_type: header.page_type,
+                checksum_valid: validate_checksum(page, page_size, vendor_info).valid,
+                lsn_valid: validate_lsn(page, page_size),
+                data: page.to_vec(),
+            })
+        })
+        .collect()
+}
+
+/// Doublewrite header on the TRX_SYS page of the system tablespace.
+#[derive(Debug, Clone, Serialize)]
+pub struct LegacyDoublewriteHeader {
+    /// First page of block 1.
+    pub block1: u32,
+    /// First page of block 2.
+    pub block2: u32,
+    /// Whether copies carry space IDs (set by MySQL 4.1.1+).
+    pub space_id_stored: bool,
+}
+
+impl LegacyDoublewriteHeader {
+    /// Parse the header from the TRX_SYS page.
+    ///
+    /// Returns `None` if the doublewrite buffer was never created.
+    pub fn parse(trx_sys_page: &[u8]) -> Option<Self> {
+        let base = trx_sys_page
+            .len()
+            .checked_sub(TRX_SYS_DOUBLEWRITE_FROM_END)?;
+        let d = &trx_sys_page[base..];
+        if BigEndian::read_u32(&d[TRX_SYS_DOUBLEWRITE_MAGIC..]) != TRX_SYS_DOUBLEWRITE_MAGIC_N {
+            return None;
+        }
+        Some(LegacyDoublewriteHeader {
+            block1: BigEndian::read_u32(&d[TRX_SYS_DOUBLEWRITE_BLOCK1..]),
+            block2: BigEndian::read_u32(&d[TRX_SYS_DOUBLEWRITE_BLOCK2..]),
+            space_id_stored: BigEndian::read_u32(&d[TRX_SYS_DOUBLEWRITE_SPACE_ID_STORED..])
+                == TRX_SYS_DOUBLEWRITE_SPACE_ID_STORED_N,
+        })
+    }
+}
+
+/// Read the legacy doublewrite buffer of a system tablespace.
+///
+/// Returns `Ok(None)` if the tablespace has no TRX_SYS page with a
+/// doublewrite header (for example a file-per-table `.ibd`). Each block is
+/// one extent long.
+pub fn read_legacy_doublewrite(
+    ts: &mut Tablespace,
+) -> Result<Option<(LegacyDoublewriteHeader, Vec<DblwrPage>)>, IdbError> {
+    if ts.page_count() <= TRX_SYS_PAGE_NO {
+        return Ok(None);
+    }
+    let trx_sys = ts.read_page(TRX_SYS_PAGE_NO)?;
+    if FilHeader::parse(&trx_sys).map(|h| h.page_type) != Some(PageType::TrxSys) {
+        return Ok(None);
+    }
+    let header = match LegacyDoublewriteHeader::parse(&trx_sys) {
+        Some(h) => h,
+        None => return Ok(None),
+    };
+
+    let page_size = ts.page_size();
+    let block_size = xdes::extent_size(ts.logical_page_size());
+    let vendor = ts.vendor_info().clone();
+    let mut pages = Vec::new();
+    for first in [header.block1, header.block2] {
+        let mut block = Vec::with_capacity(block_size as usize * page_size as usize);
+        for p in first..first + block_size {
+            if u64::from(p) >= ts.page_count() {
+                break;
+            }
+            block.extend_from_slice(&ts.read_page(u64::from(p))?);
+        }
+        pages.extend(parse_doublewrite_pages(
+            &block,
+            page_size,
+            first,
+            Some(&vendor),
+        ));
+    }
+    Ok(Some((header, pages)))
+}
+
+/// How a tablespace page is damaged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageDamage {
+    /// The page is zero-filled; its write never reached the file.
+    Empty,
+    /// The header and trailer LSNs differ — a partially written page.
+    Torn,
+    /// The stored checksum does not match the page contents.
+    ChecksumMismatch,
+}
+
+impl PageDamage {
+    /// Human-readable description.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PageDamage::Empty => "all-zero",
+            PageDamage::Torn => "torn (LSN mismatch)",
+            PageDamage::ChecksumMismatch => "checksum mismatch",
+        }
+    }
+}
+
+/// Classify the damage of a tablespace page, or `None` if it is intact.
+pub fn page_damage(
+    page: &[u8],
+    page_size: u32,
+    vendor_info: Option<&VendorInfo>,
+) -> Option<PageDamage> {
+    if page.iter().all(|&b| b == 0) {
+        Some(PageDamage::Empty)
+    } else if !validate_lsn(page, page_size) {
+        Some(PageDamage::Torn)
+    } else if !validate_checksum(page, page_size, vendor_info).valid {
+        Some(PageDamage::ChecksumMismatch)
+    } else {
+        None
+    }
+}
+
+/// Pick the doublewrite copy that can restore `page_no` of `space_id`.
+///
+/// Only intact copies of the same page are considered, and a copy is only
+/// used if it is at least as new as the damaged page (`page_lsn`; pass 0
+/// for zero-filled pages). The newest such copy is returned.
+pub fn find_restore_copy<'a>(
+    copies: impl IntoIterator<Item = &'a DblwrPage>,
+    space_id: u32,
+    page_no: u32,
+    page_lsn: u64,
+) -> Option<&'a DblwrPage> {
+    copies
+        .into_iter()
+        .filter(|c| c.space_id == space_id && c.page_no == page_no && c.is_valid())
+        .filter(|c| c.lsn >= page_lsn)
+        .max_by_key(|c| c.lsn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::innodb::checksum::{recalculate_checksum, ChecksumAlgorithm};
+
+    const PS: u32 = SIZE_PAGE_16K;
+
+    fn page(space_id: u32, page_no: u32, lsn: u64) -> Vec<u8> {
+        let mut p = vec![0u8; PS as usize];
+        BigEndian::write_u32(&mut p[FIL_PAGE_OFFSET..], page_no);
+        BigEndian::write_u64(&mut p[FIL_PAGE_LSN..], lsn);
+        BigEndian::write_u16(&mut p[FIL_PAGE_TYPE..], 17855);
+        BigEndian::write_u32(&mut p[FIL_PAGE_SPACE_ID..], space_id);
+        BigEndian::write_u32(&mut p[PS as usize - 4..], lsn as u32);
+        recalculate_checksum(&mut p, PS, ChecksumAlgorithm::Crc32c);
+        p
+    }
+
+    #[test]
+    fn test_parse_doublewrite_pages_and_restore_copy() {
+        let mut torn = page(7, 3, 900);
+        BigEndian::write_u32(&mut torn[PS as usize - 4..], 1);
+        let buf = [
+            page(7, 3, 500),
+            vec![0u8; PS as usize],
+            page(7, 3, 1000),
+            torn,
+            page(8, 3, 2000),
+        ]
+        .concat();
+        let copies = parse_doublewrite_pages(&buf, PS, 0, None);
+        assert_eq!(copies.len(), 4);
+        assert_eq!(copies[1].slot, 2);
+        assert!(!copies[2].is_valid());
+
+        let best = find_restore_copy(&copies, 7, 3, 800).unwrap();
+        assert_eq!(best.lsn, 1000);
+        assert!(find_restore_copy(&copies, 7, 3, 1500).is_none());
+        assert!(find_restore_copy(&copies, 7, 4, 0).is_none());
+    }
+
+    #[test]
+    fn test_page_damage() {
+        let good = page(7, 3, 1000);
+        assert_eq!(page_damage(&good, PS, None), None);
+        assert_eq!(
+            page_damage(&vec![0u8; PS as usize], PS, None),
+            Some(PageDamage::Empty)
+        );
+        let mut torn = good.clone();
+        BigEndian::write_u32(&mut torn[PS as usize - 4..], 1);
+        assert_eq!(page_damage(&torn, PS, None), Some(PageDamage::Torn));
+        let mut bad = good;
+        bad[200] ^= 0xFF;
+        assert_eq!(
+            page_damage(&bad, PS, None),
+            Some(PageDamage::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn test_legacy_header() {
+        let mut trx_sys = vec![0u8; PS as usize];
+        let base = PS as usize - TRX_SYS_DOUBLEWRITE_FROM_END;
+        assert!(LegacyDoublewriteHeader::parse(&trx_sys).is_none());
+        BigEndian::write_u32(
+            &mut trx_sys[base + TRX_SYS_DOUBLEWRITE_MAGIC..],
+            TRX_SYS_DOUBLEWRITE_MAGIC_N,
+        );
+        BigEndian::write_u32(&mut trx_sys[base + TRX_SYS_DOUBLEWRITE_BLOCK1..], 64);
+        BigEndian::write_u32(&mut trx_sys[base + TRX_SYS_DOUBLEWRITE_BLOCK2..], 128);
+        let h = LegacyDoublewriteHeader::parse(&trx_sys).unwrap();
+        assert_eq!((h.block1, h.block2), (64, 128));
+        assert!(!h.space_id_stored);
+    }
+}
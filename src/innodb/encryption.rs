@@ -128,7 +128,8 @@ pub fn mariadb_encryption_key_version(page_data: &[u8]) -> Option<u32> {
     Some(BigEndian::read_u32(&page_data[26..]))
 }
 
-/// Parsed encryption info from page 0 of an encrypted tablespace.
+/// Parsed encryption info from page 0 of an encrypted tablespace, or from
+/// block 2 of an encrypted redo log file.
 ///
 /// Located after the XDES array on page 0, this structure contains the
 /// master key ID, server UUID, and the encrypted tablespace key+IV needed
@@ -216,13 +217,20 @@ pub fn encryption_info_offset(page_size: u32) -> usize {
 /// assert!(parse_encryption_info(&empty_page, page_size).is_none());
 /// ```
 pub fn parse_encryption_info(page0: &[u8], page_size: u32) -> Option<EncryptionInfo> {
-    let offset = encryption_info_offset(page_size);
+    parse_encryption_info_at(page0, encryption_info_offset(page_size))
+}
 
-    if page0.len() < offset + ENCRYPTION_INFO_SIZE {
+/// Parse an encryption info structure stored at `offset` in `buf`.
+///
+/// Page 0 of a tablespace and block 2 of a redo log file
+/// (`innodb_redo_log_encrypt`) use the same layout. Returns `None` if no
+/// magic marker is found there.
+pub fn parse_encryption_info_at(buf: &[u8], offset: usize) -> Option<EncryptionInfo> {
+    if buf.len() < offset + ENCRYPTION_INFO_SIZE {
         return None;
     }
 
-    let magic = &page0[offset..offset + ENCRYPTION_MAGIC_SIZE];
+    let magic = &buf[offset..offset + ENCRYPTION_MAGIC_SIZE];
     let magic_version = if magic == ENCRYPTION_MAGIC_V1 {
         1
     } else if magic == ENCRYPTION_MAGIC_V2 {
@@ -233,14 +241,14 @@ pub fn parse_encryption_info(page0: &[u8], page_size: u32) -> Option<EncryptionI
         return None;
     };
 
-    let master_key_id = BigEndian::read_u32(&page0[offset + 3..]);
-    let uuid_bytes = &page0[offset + 7..offset + 7 + ENCRYPTION_SERVER_UUID_LEN];
+    let master_key_id = BigEndian::read_u32(&buf[offset + 3..]);
+    let uuid_bytes = &buf[offset + 7..offset + 7 + ENCRYPTION_SERVER_UUID_LEN];
     let server_uuid = String::from_utf8_lossy(uuid_bytes).to_string();
 
     let mut encrypted_key_iv = [0u8; 64];
-    encrypted_key_iv.copy_from_slice(&page0[offset + 43..offset + 43 + 64]);
+    encrypted_key_iv.copy_from_slice(&buf[offset + 43..offset + 43 + 64]);
 
-    let checksum = BigEndian::read_u32(&page0[offset + 107..]);
+    let checksum = BigEndian::read_u32(&buf[offset + 107..]);
 
     Some(EncryptionInfo {
         magic_version,
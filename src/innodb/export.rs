@@ -9,15 +9,28 @@
 //! | Function | Purpose |
 //! |----------|---------|
 //! | [`extract_column_layout`] | Parse SDI metadata to build column layout and find clustered index ID |
-//! | [`decode_page_records`] | Walk compact records on a page and decode fields using column metadata |
+//! | [`extract_index_layouts`] | Parse SDI metadata to build the column layout of every B-tree index |
+//! | [`decode_page_records`] | Walk compact or redundant records on a page and decode fields using column metadata |
+//! | [`decode_page_records_with_lobs`] | Same, reassembling externally stored (off-page) columns |
+//! | [`decode_index_records`] | Decode a page of any index (clustered or secondary) by index ID |
 //! | [`csv_escape`] | RFC 4180 CSV escaping for [`FieldValue`] |
 
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::innodb::constants::{
+    BTR_EXTERN_FIELD_REF_SIZE, REC_N_NEW_EXTRA_BYTES, REC_N_OLD_EXTRA_BYTES,
+};
 use crate::innodb::field_decode::{self, ColumnStorageInfo, FieldValue};
-use crate::innodb::record::walk_compact_records;
-use crate::innodb::schema::SdiEnvelope;
+use crate::innodb::index::IndexHeader;
+use crate::innodb::lob::{self, ExternFieldRef};
+use crate::innodb::record::{walk_records, CompactRecordHeader, RedundantRecordHeader};
+use crate::innodb::schema::{parse_se_private_data, DdIndex, DdTable, SdiEnvelope};
 use crate::innodb::sdi;
 use crate::innodb::tablespace::Tablespace;
 
+/// Default limit on the size of a reassembled externally stored value (16 MiB).
+pub const DEFAULT_MAX_LOB_SIZE: usize = 16 * 1024 * 1024;
+
 /// Extract column layout from SDI metadata.
 ///
 /// Reads SDI pages from the tablespace, deserializes the table definition,
@@ -77,6 +90,126 @@ pub fn extract_column_layout(ts: &mut Tablespace) -> Option<(Vec<ColumnStorageIn
     None
 }
 
+/// Column layout of one B-tree index of a table.
+#[derive(Debug, Clone)]
+pub struct IndexLayout {
+    /// Index name (`PRIMARY` for the clustered index).
+    pub name: String,
+    /// InnoDB index ID (matches `PAGE_INDEX_ID` on the index's pages).
+    pub index_id: u64,
+    /// Whether this is the clustered (PRIMARY) index.
+    pub is_clustered: bool,
+    /// Fields stored in the index's leaf records, in physical order.
+    pub columns: Vec<ColumnStorageInfo>,
+    /// Per entry of `columns`, whether it is a descending (`DESC`) key part.
+    pub descending: Vec<bool>,
+    /// Per entry of `columns`, the length in bytes of a column prefix key
+    /// part, or `None` if the field stores the whole column.
+    pub prefix_lengths: Vec<Option<usize>>,
+}
+
+impl IndexLayout {
+    /// Key fields stored in this index's node-pointer (non-leaf) records.
+    ///
+    /// For the clustered index these are the primary key columns (or
+    /// DB_ROW_ID), i.e. the fields ahead of DB_TRX_ID. Secondary index node
+    /// pointers carry every leaf field: the key columns and the primary key.
+    pub fn node_pointer_columns(&self) -> &[ColumnStorageInfo] {
+        if !self.is_clustered {
+            return &self.columns;
+        }
+        let n_key = self
+            .columns
+            .iter()
+            .position(|c| c.name == "DB_TRX_ID")
+            .unwrap_or(self.columns.len());
+        &self.columns[..n_key]
+    }
+}
+
+/// Extract the column layouts of every B-tree index from SDI metadata.
+///
+/// The clustered index uses [`field_decode::build_column_layout`] and
+/// secondary indexes use [`field_decode::build_index_layout`]. FULLTEXT
+/// and SPATIAL indexes, and indexes without an `id` in `se_private_data`,
+/// are skipped.
+///
+/// Returns `None` if SDI metadata is unavailable or cannot be parsed.
+pub fn extract_index_layouts(ts: &mut Tablespace) -> Option<Vec<IndexLayout>> {
+    let sdi_pages = sdi::find_sdi_pages(ts).ok()?;
+    if sdi_pages.is_empty() {
+        return None;
+    }
+    let records = sdi::extract_sdi_from_pages(ts, &sdi_pages).ok()?;
+
+    let rec = records.iter().find(|r| r.sdi_type == 1)?;
+    let envelope: SdiEnvelope = serde_json::from_str(&rec.data).ok()?;
+    let table = &envelope.dd_object;
+
+    let mut layouts = Vec::new();
+    for idx in &table.indexes {
+        // 4 = FULLTEXT, 5 = SPATIAL
+        if matches!(idx.index_type, 4 | 5) {
+            continue;
+        }
+        let index_id = match idx
+            .se_private_data
+            .as_deref()
+            .and_then(|spd| parse_se_private_data(spd).get("id")?.parse::<u64>().ok())
+        {
+            Some(id) => id,
+            None => continue,
+        };
+        let is_clustered = idx.index_type == 1;
+        let columns = if is_clustered {
+            field_decode::build_column_layout(table)
+        } else {
+            field_decode::build_index_layout(table, idx)
+        };
+        let descending = descending_key_parts(table, idx, &columns);
+        let prefix_lengths = if is_clustered {
+            vec![None; columns.len()]
+        } else {
+            field_decode::index_prefix_lengths(table, idx)
+        };
+        layouts.push(IndexLayout {
+            name: idx.name.clone(),
+            index_id,
+            is_clustered,
+            columns,
+            descending,
+            prefix_lengths,
+        });
+    }
+    Some(layouts)
+}
+
+/// Flag the fields of an index layout that are `DESC` key parts of the
+/// index itself or of the primary key appended to it.
+fn descending_key_parts(
+    table: &DdTable,
+    index: &DdIndex,
+    columns: &[ColumnStorageInfo],
+) -> Vec<bool> {
+    let pk = table.indexes.iter().find(|i| i.index_type == 1);
+    columns
+        .iter()
+        .map(|col| {
+            [Some(index), pk]
+                .into_iter()
+                .flatten()
+                .flat_map(|i| &i.elements)
+                .find(|e| {
+                    table
+                        .columns
+                        .get(e.column_opx as usize)
+                        .is_some_and(|c| c.name == col.name)
+                })
+                .is_some_and(|e| e.order == 1)
+        })
+        .collect()
+}
+
 /// Extract the table name from SDI metadata.
 ///
 /// Returns the `dd_object.name` field from the first Table SDI record,
@@ -102,9 +235,15 @@ pub fn extract_table_name(ts: &mut Tablespace) -> Option<String> {
 
 /// Decode records from a single page using the column layout.
 ///
-/// Walks compact-format records on the page, applies delete-mark and
-/// system-column filters, and returns a list of rows. Each row is a list
-/// of `(column_name, decoded_value)` pairs.
+/// Walks the records on the page, applies delete-mark and system-column
+/// filters, and returns a list of rows. Each row is a list of
+/// `(column_name, decoded_value)` pairs. Compact and redundant row formats
+/// are both supported; the format is taken from the page's INDEX header
+/// (see [`IndexHeader::is_compact`]).
+///
+/// Externally stored (off-page) columns cannot be followed without the
+/// tablespace and are returned as hex of the local prefix and the 20-byte
+/// reference; use [`decode_page_records_with_lobs`] to reassemble them.
 ///
 /// # Arguments
 ///
@@ -120,9 +259,88 @@ pub fn decode_page_records(
     columns: &[ColumnStorageInfo],
     where_delete_mark: bool,
     system_columns: bool,
-    _page_size: u32,
 ) -> Vec<Vec<(String, FieldValue)>> {
-    let records = walk_compact_records(page_data);
+    decode_records(
+        page_data,
+        columns,
+        where_delete_mark,
+        system_columns,
+        &mut |_| None,
+    )
+}
+
+/// Decode records from a single page, reassembling off-page columns.
+///
+/// Behaves like [`decode_page_records`], but when a BLOB/TEXT/JSON or long
+/// VARCHAR value is stored externally, follows its reference through the
+/// overflow page chain (see [`lob::read_external_field`]) and decodes the
+/// full value. Values longer than `max_lob_size` bytes are truncated.
+/// If the chain cannot be read, the field falls back to hex.
+pub fn decode_page_records_with_lobs(
+    ts: &mut Tablespace,
+    page_data: &[u8],
+    columns: &[ColumnStorageInfo],
+    where_delete_mark: bool,
+    system_columns: bool,
+    max_lob_size: usize,
+) -> Vec<Vec<(String, FieldValue)>> {
+    decode_records(
+        page_data,
+        columns,
+        where_delete_mark,
+        system_columns,
+        &mut |ext| {
+            let remaining = max_lob_size.saturating_sub(ext.prefix_len);
+            lob::read_external_field(ts, &ext.field_ref, remaining).ok()
+        },
+    )
+}
+
+/// Decode records from a page of the index with ID `index_id`.
+///
+/// Looks up the index in `layouts` (from [`extract_index_layouts`]) and
+/// decodes the page like [`decode_page_records`]. Secondary index leaf
+/// pages yield the key columns followed by the primary key columns, which
+/// makes them a fallback source of data when the clustered index is
+/// damaged. Returns no rows if `index_id` has no layout.
+pub fn decode_index_records(
+    page_data: &[u8],
+    index_id: u64,
+    layouts: &[IndexLayout],
+    where_delete_mark: bool,
+    system_columns: bool,
+) -> Vec<Vec<(String, FieldValue)>> {
+    match layouts.iter().find(|l| l.index_id == index_id) {
+        Some(layout) => decode_records(
+            page_data,
+            &layout.columns,
+            where_delete_mark,
+            system_columns,
+            &mut |_| None,
+        ),
+        None => Vec::new(),
+    }
+}
+
+/// An externally stored field encountered while decoding a record.
+struct ExternField {
+    /// Length of the locally stored prefix (excluding the reference).
+    prefix_len: usize,
+    /// Parsed reference to the overflow pages.
+    field_ref: ExternFieldRef,
+}
+
+/// Shared record walker; `resolve_extern` returns the external part of an
+/// off-page column, or `None` to fall back to hex.
+fn decode_records(
+    page_data: &[u8],
+    columns: &[ColumnStorageInfo],
+    where_delete_mark: bool,
+    system_columns: bool,
+    resolve_extern: &mut dyn FnMut(&ExternField) -> Option<Vec<u8>>,
+) -> Vec<Vec<(String, FieldValue)>> {
+    let compact = IndexHeader::parse(page_data).is_none_or(|h| h.is_compact());
+    let records = walk_records(page_data);
     let mut rows = Vec::new();
 
     for rec in &records {
@@ -135,70 +353,48 @@ pub fn decode_page_records(
             continue;
         }
 
-        // Decode fields
-        let mut row = Vec::new();
-        let mut pos = rec.offset;
-
-        // Read nullable bitmap and variable-length headers
-        let n_nullable = columns.iter().filter(|c| c.is_nullable).count();
-        let n_variable = columns.iter().filter(|c| c.is_variable).count();
-
-        let (nulls, var_lengths) = match crate::innodb::record::read_variable_field_lengths(
-            page_data, rec.offset, n_nullable, n_variable,
-        ) {
-            Some(r) => r,
+        // Read the null bitmap and length array (compact) or the field end
+        // offset array (redundant)
+        let fields = match read_record_fields(page_data, rec.offset, columns, compact) {
+            Some(f) => f,
             None => continue,
         };
 
-        let mut null_idx = 0;
-        let mut var_idx = 0;
+        let mut row = Vec::new();
+
+        for (col, (pos, field)) in columns.iter().zip(fields) {
+            let (len, external) = match field {
+                RecordField::Stored { len, external } => (len, external),
+                RecordField::Null | RecordField::Absent => {
+                    if system_columns || !col.is_system_column {
+                        let val = match field {
+                            RecordField::Absent => col.instant_default.clone(),
+                            _ => None,
+                        };
+                        row.push((col.name.clone(), val.unwrap_or(FieldValue::Null)));
+                    }
+                    continue;
+                }
+            };
+            let end = pos + len;
 
-        for col in columns {
             // Skip system columns unless requested
             if !system_columns && col.is_system_column {
-                if col.fixed_len > 0 {
-                    pos += col.fixed_len;
-                }
                 continue;
             }
 
-            // Check null
-            if col.is_nullable {
-                if null_idx < nulls.len() && nulls[null_idx] {
-                    row.push((col.name.clone(), FieldValue::Null));
-                    null_idx += 1;
-                    continue;
-                }
-                null_idx += 1;
+            if (!col.is_variable && len == 0) || end > page_data.len() {
+                row.push((col.name.clone(), FieldValue::Null));
+                continue;
             }
 
-            if col.is_variable {
-                // Variable-length field
-                let len = if var_idx < var_lengths.len() {
-                    var_lengths[var_idx]
-                } else {
-                    0
-                };
-                var_idx += 1;
-
-                if pos + len <= page_data.len() {
-                    let val = field_decode::decode_field(&page_data[pos..pos + len], col);
-                    row.push((col.name.clone(), val));
-                    pos += len;
-                } else {
-                    row.push((col.name.clone(), FieldValue::Null));
-                }
+            let local = &page_data[pos..end];
+            let val = if external {
+                decode_external_field(local, col, resolve_extern)
             } else {
-                // Fixed-length field
-                let len = col.fixed_len;
-                if len > 0 && pos + len <= page_data.len() {
-                    let val = field_decode::decode_field(&page_data[pos..pos + len], col);
-                    row.push((col.name.clone(), val));
-                    pos += len;
-                } else {
-                    row.push((col.name.clone(), FieldValue::Null));
-                }
-            }
+                field_decode::decode_field(local, col)
+            };
+            row.push((col.name.clone(), val));
         }
 
         rows.push(row);
@@ -207,12 +403,244 @@ pub fn decode_page_records(
     rows
 }
 
+/// Decode an externally stored field from its local part.
+fn decode_external_field(
+    local: &[u8],
+    col: &ColumnStorageInfo,
+    resolve_extern: &mut dyn FnMut(&ExternField) -> Option<Vec<u8>>,
+) -> FieldValue {
+    if local.len() < BTR_EXTERN_FIELD_REF_SIZE {
+        return field_decode::decode_field(local, col);
+    }
+    let prefix_len = local.len() - BTR_EXTERN_FIELD_REF_SIZE;
+    let field_ref = match ExternFieldRef::parse(&local[prefix_len..]) {
+        Some(r) => r,
+        None => return field_decode::decode_field(local, col),
+    };
+
+    match resolve_extern(&ExternField {
+        prefix_len,
+        field_ref,
+    }) {
+        Some(external) => {
+            let mut full = Vec::with_capacity(prefix_len + external.len());
+            full.extend_from_slice(&local[..prefix_len]);
+            full.extend_from_slice(&external);
+            field_decode::decode_field(&full, col)
+        }
+        None => FieldValue::Hex(
+            local
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>(),
+        ),
+    }
+}
+
+/// Storage of one column within a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RecordField {
+    /// SQL NULL; no bytes are stored.
+    Null,
+    /// Stored in the record: `len` bytes, the last 20 of which are an
+    /// overflow page reference when `external` is set.
+    Stored { len: usize, external: bool },
+    /// Not stored because the record predates an instant ADD COLUMN or
+    /// postdates an instant DROP COLUMN.
+    Absent,
+}
+
+/// Read the per-column field lengths of a compact-format record.
+///
+/// Returns one [`RecordField`] per column in `columns`.
+///
+/// Follows `rec_init_offsets_comp_ordinary()`: the null bitmap and the
+/// variable-length array grow backwards from the 5-byte extra header, NULL
+/// fields have no length entry, and only "big" columns (see
+/// [`ColumnStorageInfo::is_big`]) may use a 2-byte length.
+///
+/// Records written after an instant ADD/DROP COLUMN carry either a row
+/// version byte (version flag, 8.0.29+) or their field count (instant flag,
+/// 8.0.12-8.0.28) ahead of the null bitmap; these select which columns
+/// are physically present. Records without either flag store only the
+/// columns that predate instant DDL.
+pub(crate) fn read_compact_field_lengths(
+    page_data: &[u8],
+    record_origin: usize,
+    columns: &[ColumnStorageInfo],
+) -> Option<Vec<RecordField>> {
+    let extra_start = record_origin.checked_sub(REC_N_NEW_EXTRA_BYTES)?;
+    let hdr = CompactRecordHeader::parse(page_data.get(extra_start..)?)?;
+    let mut nulls = extra_start.checked_sub(1)?;
+
+    let present: Vec<bool> = if hdr.versioned {
+        let version = *page_data.get(nulls)? as u32;
+        nulls = nulls.checked_sub(1)?;
+        columns.iter().map(|c| c.in_row_version(version)).collect()
+    } else if hdr.instant {
+        // 1 or 2 byte field count (high bit of the first byte set for 2)
+        let mut n_fields = *page_data.get(nulls)? as usize;
+        if n_fields & 0x80 != 0 {
+            nulls = nulls.checked_sub(1)?;
+            n_fields = ((n_fields & 0x7F) << 8) | *page_data.get(nulls)? as usize;
+        }
+        nulls = nulls.checked_sub(1)?;
+        (0..columns.len()).map(|i| i < n_fields).collect()
+    } else {
+        columns
+            .iter()
+            .map(|c| c.version_added == 0 && c.instant_default.is_none())
+            .collect()
+    };
+
+    // Null bitmap bits are consumed upwards from bit 0, moving to the
+    // preceding byte after every 8 nullable fields
+    let n_nullable = columns
+        .iter()
+        .zip(&present)
+        .filter(|(c, &p)| p && c.is_nullable)
+        .count();
+    let mut lens = (nulls + 1).checked_sub(n_nullable.div_ceil(8))?;
+
+    let mut fields = Vec::with_capacity(columns.len());
+    let mut null_idx = 0;
+
+    for (col, &is_present) in columns.iter().zip(&present) {
+        if !is_present {
+            fields.push(RecordField::Absent);
+            continue;
+        }
+
+        if col.is_nullable {
+            let byte = *page_data.get(nulls - null_idx / 8)?;
+            let is_null = byte & (1 << (null_idx % 8)) != 0;
+            null_idx += 1;
+            if is_null {
+                fields.push(RecordField::Null);
+                continue;
+            }
+        }
+
+        if !col.is_variable {
+            fields.push(RecordField::Stored {
+                len: col.fixed_len,
+                external: false,
+            });
+            continue;
+        }
+
+        lens = lens.checked_sub(1)?;
+        let mut len = *page_data.get(lens)? as usize;
+        let mut external = false;
+        if col.is_big() && len & 0x80 != 0 {
+            lens = lens.checked_sub(1)?;
+            len = (len << 8) | *page_data.get(lens)? as usize;
+            external = len & 0x4000 != 0;
+            len &= 0x3FFF;
+        }
+        fields.push(RecordField::Stored { len, external });
+    }
+
+    Some(fields)
+}
+
+/// Read the fields of a record in either row format.
+///
+/// Returns one `(data_offset, field)` pair per column, where `data_offset`
+/// is the absolute page offset at which the field's data starts.
+pub(crate) fn read_record_fields(
+    page_data: &[u8],
+    record_origin: usize,
+    columns: &[ColumnStorageInfo],
+    compact: bool,
+) -> Option<Vec<(usize, RecordField)>> {
+    if !compact {
+        return read_redundant_field_offsets(page_data, record_origin, columns);
+    }
+    let mut pos = record_origin;
+    let fields = read_compact_field_lengths(page_data, record_origin, columns)?;
+    Some(
+        fields
+            .into_iter()
+            .map(|field| {
+                let start = pos;
+                if let RecordField::Stored { len, .. } = field {
+                    pos += len;
+                }
+                (start, field)
+            })
+            .collect(),
+    )
+}
+
+/// Read the per-column fields of a redundant-format record.
+///
+/// Returns one `(data_offset, field)` pair per column in `columns`.
+///
+/// Follows `rec_get_nth_field_offs_old()`: the field end offsets (relative
+/// to the record origin) grow backwards from the 6-byte extra header, one
+/// per field, using 1 byte each if the header's short flag is set and 2
+/// bytes otherwise. The top bit of each entry marks SQL NULL, and in the
+/// 2-byte form the next bit marks an externally stored field. A NULL
+/// fixed-length field still occupies its full width (zero-filled), so data
+/// offsets are taken from the previous field's end rather than accumulated.
+///
+/// Columns beyond the record's field count (added by instant ADD COLUMN)
+/// are reported as [`RecordField::Absent`].
+pub(crate) fn read_redundant_field_offsets(
+    page_data: &[u8],
+    record_origin: usize,
+    columns: &[ColumnStorageInfo],
+) -> Option<Vec<(usize, RecordField)>> {
+    let extra_start = record_origin.checked_sub(REC_N_OLD_EXTRA_BYTES)?;
+    let hdr = RedundantRecordHeader::parse(page_data.get(extra_start..)?)?;
+    let n_fields = hdr.n_fields as usize;
+    let entry_size = if hdr.one_byte_offs { 1 } else { 2 };
+
+    let mut fields = Vec::with_capacity(columns.len());
+    let mut start = 0usize;
+
+    for i in 0..columns.len() {
+        if i >= n_fields {
+            fields.push((record_origin + start, RecordField::Absent));
+            continue;
+        }
+
+        let at = extra_start.checked_sub((i + 1) * entry_size)?;
+        let (end, is_null, external) = if hdr.one_byte_offs {
+            let b = *page_data.get(at)?;
+            ((b & 0x7F) as usize, b & 0x80 != 0, false)
+        } else {
+            let w = BigEndian::read_u16(page_data.get(at..at + 2)?);
+            ((w & 0x3FFF) as usize, w & 0x8000 != 0, w & 0x4000 != 0)
+        };
+        if end < start {
+            return None;
+        }
+
+        let field = if is_null {
+            RecordField::Null
+        } else {
+            RecordField::Stored {
+                len: end - start,
+                external,
+            }
+        };
+        fields.push((record_origin + start, field));
+        start = end;
+    }
+
+    Some(fields)
+}
+
 /// CSV-escape a field value per RFC 4180.
 ///
 /// - `Null` produces an empty string.
 /// - Numeric types produce their string representation.
 /// - Strings containing commas, double quotes, or newlines are quoted and
 ///   internal double quotes are doubled.
+/// - JSON documents are written as JSON text, quoted like strings.
+/// - Geometries are written as WKT, quoted like strings.
 /// - Hex values are passed through unquoted.
 pub fn csv_escape(val: &FieldValue) -> String {
     match val {
@@ -221,13 +649,421 @@ pub fn csv_escape(val: &FieldValue) -> String {
         FieldValue::Uint(n) => n.to_string(),
         FieldValue::Float(f) => f.to_string(),
         FieldValue::Double(d) => d.to_string(),
-        FieldValue::Str(s) => {
-            if s.contains(',') || s.contains('"') || s.contains('\n') || s.contains('\r') {
-                format!("\"{}\"", s.replace('"', "\"\""))
-            } else {
-                s.clone()
+        FieldValue::Str(s) => csv_quote(s),
+        FieldValue::Hex(h) => h.clone(),
+        FieldValue::Json(v) => csv_quote(&v.to_string()),
+        FieldValue::Geometry(g) => csv_quote(&g.geometry.to_wkt()),
+    }
+}
+
+/// Quote a CSV string field if it contains a delimiter, quote, or newline.
+fn csv_quote(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') || s.contains('\r') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::{BigEndian, ByteOrder};
+
+    use crate::innodb::constants::{
+        FIL_NULL, FIL_PAGE_DATA, PAGE_NEW_INFIMUM, PAGE_NEW_SUPREMUM, PAGE_N_HEAP,
+        PAGE_OLD_INFIMUM, PAGE_OLD_SUPREMUM,
+    };
+
+    fn column(name: &str, dd_type: u64, fixed_len: usize, nullable: bool) -> ColumnStorageInfo {
+        ColumnStorageInfo {
+            name: name.to_string(),
+            dd_type,
+            column_type: String::new(),
+            is_nullable: nullable,
+            is_unsigned: false,
+            fixed_len,
+            is_variable: fixed_len == 0,
+            max_len: if fixed_len == 0 { 65535 } else { fixed_len },
+            version_added: 0,
+            version_dropped: 0,
+            instant_default: None,
+            charset_max_bytes: 1,
+            collation_id: 0,
+            datetime_precision: 0,
+            is_system_column: name.starts_with("DB_"),
+            elements: Vec::new(),
+            numeric_precision: 0,
+            numeric_scale: 0,
+        }
+    }
+
+    /// Columns: id INT, DB_TRX_ID, DB_ROLL_PTR, body TEXT NULL, note TEXT NULL.
+    fn columns() -> Vec<ColumnStorageInfo> {
+        vec![
+            column("id", 4, 4, false),
+            column("DB_TRX_ID", 0, 6, false),
+            column("DB_ROLL_PTR", 0, 7, false),
+            column("body", 27, 0, true),
+            column("note", 27, 0, true),
+        ]
+    }
+
+    /// Build an INDEX page with one record whose `body` is stored off-page
+    /// at `blob_page` and whose `note` is NULL.
+    fn build_page(blob_page: u32, blob_len: u32) -> Vec<u8> {
+        let mut page = vec![0u8; 16384];
+        BigEndian::write_u16(&mut page[24..], 17855);
+        BigEndian::write_u16(&mut page[FIL_PAGE_DATA + PAGE_N_HEAP..], 0x8000); // compact
+
+        // Extra bytes: 2-byte length (extern), 1-byte null bitmap, 5-byte header
+        let origin = 140;
+        page[origin - 6] = 0b10; // note is NULL
+        page[origin - 7] = 0x80 | 0x40; // 2-byte length, extern flag
+        page[origin - 8] = 20; // 20-byte reference only (no local prefix)
+
+        // Infimum -> record -> supremum
+        let inf = PAGE_NEW_INFIMUM - REC_N_NEW_EXTRA_BYTES;
+        page[inf + 2] = 0x02;
+        BigEndian::write_i16(&mut page[inf + 3..], (origin - PAGE_NEW_INFIMUM) as i16);
+        BigEndian::write_u16(&mut page[origin - 4..], 2 << 3);
+        BigEndian::write_i16(
+            &mut page[origin - 2..],
+            PAGE_NEW_SUPREMUM as i16 - origin as i16,
+        );
+        let sup = PAGE_NEW_SUPREMUM - REC_N_NEW_EXTRA_BYTES;
+        page[sup + 2] = 0x0B;
+
+        // Fields: id=1, trx_id, roll_ptr, 20-byte extern reference
+        BigEndian::write_u32(&mut page[origin..], 0x8000_0001);
+        BigEndian::write_u32(&mut page[origin + 17 + 4..], blob_page);
+        BigEndian::write_u32(&mut page[origin + 17 + 8..], FIL_PAGE_DATA as u32);
+        BigEndian::write_u32(&mut page[origin + 17 + 16..], blob_len);
+        page
+    }
+
+    fn build_blob_page(page_no: u32, data: &[u8]) -> Vec<u8> {
+        let mut page = vec![0u8; 16384];
+        BigEndian::write_u32(&mut page[4..], page_no);
+        BigEndian::write_u16(&mut page[24..], 10); // BLOB
+        BigEndian::write_u32(&mut page[FIL_PAGE_DATA..], data.len() as u32);
+        BigEndian::write_u32(&mut page[FIL_PAGE_DATA + 4..], FIL_NULL);
+        page[FIL_PAGE_DATA + 8..FIL_PAGE_DATA + 8 + data.len()].copy_from_slice(data);
+        page
+    }
+
+    #[test]
+    fn test_csv_escape_json() {
+        let v = FieldValue::Json(serde_json::json!({"a": [1, "x,y"]}));
+        assert_eq!(csv_escape(&v), "\"{\"\"a\"\":[1,\"\"x,y\"\"]}\"");
+        let v = FieldValue::Json(serde_json::json!(42));
+        assert_eq!(csv_escape(&v), "42");
+    }
+
+    #[test]
+    fn test_csv_escape_geometry() {
+        use crate::innodb::geometry::{Geometry, Point, SpatialValue};
+        let v = FieldValue::Geometry(SpatialValue {
+            srid: 0,
+            geometry: Geometry::LineString(vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 1.0, y: 1.0 },
+            ]),
+        });
+        assert_eq!(csv_escape(&v), "\"LINESTRING(0 0,1 1)\"");
+    }
+
+    #[test]
+    fn test_read_compact_field_lengths_extern_and_null() {
+        let page = build_page(1, 11);
+        let fields = read_compact_field_lengths(&page, 140, &columns()).unwrap();
+        assert_eq!(
+            fields,
+            vec![
+                RecordField::Stored {
+                    len: 4,
+                    external: false
+                },
+                RecordField::Stored {
+                    len: 6,
+                    external: false
+                },
+                RecordField::Stored {
+                    len: 7,
+                    external: false
+                },
+                RecordField::Stored {
+                    len: 20,
+                    external: true
+                },
+                RecordField::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_compact_field_lengths_small_column_single_byte() {
+        // A VARCHAR(10) length byte with the high bit set is still one byte
+        let mut col = column("v", 16, 0, false);
+        col.max_len = 10;
+        let mut page = vec![0u8; 64];
+        page[40 - 6] = 0x85;
+        let fields = read_compact_field_lengths(&page, 40, &[col]).unwrap();
+        assert_eq!(
+            fields,
+            vec![RecordField::Stored {
+                len: 0x85,
+                external: false
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decode_page_records_with_lobs() {
+        let mut data = vec![0u8; 16384]; // page 0
+        data.extend_from_slice(&build_blob_page(1, b"hello world"));
+        let mut ts = Tablespace::from_bytes(data).unwrap();
+        let page = build_page(1, 11);
+
+        let rows = decode_page_records_with_lobs(&mut ts, &page, &columns(), false, false, 1024);
+        assert_eq!(rows.len(), 1);
+        assert!(matches!(rows[0][0].1, FieldValue::Int(1)));
+        assert_eq!(rows[0][1].0, "body");
+        assert!(matches!(&rows[0][1].1, FieldValue::Str(s) if s == "hello world"));
+        assert!(matches!(rows[0][2].1, FieldValue::Null));
+
+        // Size limit truncates the reassembled value
+        let rows = decode_page_records_with_lobs(&mut ts, &page, &columns(), false, false, 5);
+        assert!(matches!(&rows[0][1].1, FieldValue::Str(s) if s == "hello"));
+
+        // Without the tablespace the reference is returned as hex
+        let rows = decode_page_records(&page, &columns(), false, false);
+        assert!(matches!(rows[0][1].1, FieldValue::Hex(_)));
+    }
+
+    /// Build an INDEX page from `(extra, info_bits, data)` records, where
+    /// `extra` holds the bytes preceding the 5-byte header in page order.
+    fn build_records_page(records: &[(Vec<u8>, u8, Vec<u8>)]) -> Vec<u8> {
+        let mut page = vec![0u8; 16384];
+        BigEndian::write_u16(&mut page[24..], 17855);
+        BigEndian::write_u16(&mut page[FIL_PAGE_DATA + PAGE_N_HEAP..], 0x8000); // compact
+        let inf = PAGE_NEW_INFIMUM - REC_N_NEW_EXTRA_BYTES;
+        page[inf + 2] = 0x02;
+        let sup = PAGE_NEW_SUPREMUM - REC_N_NEW_EXTRA_BYTES;
+        page[sup + 2] = 0x0B;
+
+        let mut prev = PAGE_NEW_INFIMUM;
+        let mut heap = PAGE_NEW_SUPREMUM + 8;
+        for (i, (extra, info, data)) in records.iter().enumerate() {
+            let origin = heap + extra.len() + REC_N_NEW_EXTRA_BYTES;
+            page[heap..heap + extra.len()].copy_from_slice(extra);
+            page[origin - 5] = *info;
+            BigEndian::write_u16(&mut page[origin - 4..], ((i as u16) + 2) << 3);
+            page[origin..origin + data.len()].copy_from_slice(data);
+            BigEndian::write_i16(&mut page[prev - 2..], origin as i16 - prev as i16);
+            prev = origin;
+            heap = origin + data.len();
+        }
+        BigEndian::write_i16(
+            &mut page[prev - 2..],
+            PAGE_NEW_SUPREMUM as i16 - prev as i16,
+        );
+        page
+    }
+
+    fn record_data(ints: &[u32]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&(ints[0] | 0x8000_0000).to_be_bytes());
+        data.extend_from_slice(&[0u8; 13]); // DB_TRX_ID + DB_ROLL_PTR
+        for v in &ints[1..] {
+            data.extend_from_slice(&(v | 0x8000_0000).to_be_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_decode_page_records_instant_row_versions() {
+        // id, a, b (nullable, dropped in v2), c (added in v1, default 7)
+        let mut cols = vec![
+            column("id", 4, 4, false),
+            column("DB_TRX_ID", 0, 6, false),
+            column("DB_ROLL_PTR", 0, 7, false),
+            column("a", 4, 4, false),
+            column("!hidden!_dropped_v2_p4_b", 4, 4, true),
+            column("c", 4, 4, false),
+        ];
+        cols[4].is_system_column = true;
+        cols[4].version_dropped = 2;
+        cols[5].version_added = 1;
+        cols[5].instant_default = Some(FieldValue::Int(7));
+
+        let page = build_records_page(&[
+            // Written before any instant DDL: id, a, b (null bitmap = 0)
+            (vec![0x00], 0x00, record_data(&[1, 10, 20])),
+            // Row version 2: id, a, c (b dropped, no nullable fields)
+            (vec![2], 0x40, record_data(&[2, 11, 12])),
+            // Pre-8.0.29 instant flag with 4 fields: id, trx, roll, a
+            (vec![4], 0x80, record_data(&[3, 13])),
+        ]);
+
+        let rows = decode_page_records(&page, &cols, false, false);
+        let values: Vec<Vec<i64>> = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|(_, v)| match v {
+                        FieldValue::Int(n) => *n,
+                        _ => -1,
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            values,
+            vec![vec![1, 10, 7], vec![2, 11, 12], vec![3, 13, 7]]
+        );
+
+        // The dropped column is still visible with system columns
+        let rows = decode_page_records(&page, &cols, false, true);
+        assert!(matches!(rows[0][4].1, FieldValue::Int(20)));
+        assert!(matches!(rows[1][4].1, FieldValue::Null));
+    }
+
+    /// Build a redundant-format INDEX page from `(end_offsets, info_bits,
+    /// data)` records, where `end_offsets` are the 2-byte field end offsets
+    /// in field order.
+    fn build_redundant_page(records: &[(Vec<u16>, u8, Vec<u8>)]) -> Vec<u8> {
+        let mut page = vec![0u8; 16384];
+        BigEndian::write_u16(&mut page[24..], 17855);
+        BigEndian::write_u16(&mut page[PAGE_OLD_SUPREMUM - 5..], 1 << 3);
+
+        let mut prev = PAGE_OLD_INFIMUM;
+        let mut heap = PAGE_OLD_SUPREMUM + 9;
+        for (i, (ends, info, data)) in records.iter().enumerate() {
+            let origin = heap + ends.len() * 2 + REC_N_OLD_EXTRA_BYTES;
+            for (j, end) in ends.iter().enumerate() {
+                let at = origin - REC_N_OLD_EXTRA_BYTES - (j + 1) * 2;
+                BigEndian::write_u16(&mut page[at..], *end);
             }
+            page[origin - 6] = *info;
+            BigEndian::write_u16(&mut page[origin - 5..], ((i as u16) + 2) << 3);
+            page[origin - 3] = (ends.len() as u8) << 1;
+            page[origin..origin + data.len()].copy_from_slice(data);
+            BigEndian::write_u16(&mut page[prev - 2..], origin as u16);
+            prev = origin;
+            heap = origin + data.len();
         }
-        FieldValue::Hex(h) => h.clone(),
+        BigEndian::write_u16(&mut page[prev - 2..], PAGE_OLD_SUPREMUM as u16);
+        page
+    }
+
+    #[test]
+    fn test_decode_page_records_redundant() {
+        let cols = vec![
+            column("id", 4, 4, false),
+            column("DB_TRX_ID", 0, 6, false),
+            column("DB_ROLL_PTR", 0, 7, false),
+            column("n", 4, 4, true),
+            column("body", 16, 0, true),
+        ];
+
+        let mut live = record_data(&[1, 42]);
+        live.extend_from_slice(b"abc");
+        // NULL INT still occupies its 4 bytes; NULL VARCHAR occupies none
+        let nulls = record_data(&[2, 0]);
+        let page = build_redundant_page(&[
+            (vec![4, 10, 17, 21, 24], 0x00, live),
+            (vec![4, 10, 17, 0x8000 | 21, 0x8000 | 21], 0x00, nulls),
+            (vec![4, 10, 17, 21, 21], 0x20, record_data(&[3, 7])),
+        ]);
+
+        let rows = decode_page_records(&page, &cols, false, false);
+        assert_eq!(rows.len(), 2);
+        assert!(matches!(rows[0][0].1, FieldValue::Int(1)));
+        assert!(matches!(rows[0][1].1, FieldValue::Int(42)));
+        assert!(matches!(&rows[0][2].1, FieldValue::Str(s) if s == "abc"));
+        assert!(matches!(rows[1][0].1, FieldValue::Int(2)));
+        assert!(matches!(rows[1][1].1, FieldValue::Null));
+        assert!(matches!(rows[1][2].1, FieldValue::Null));
+
+        let rows = decode_page_records(&page, &cols, true, false);
+        assert_eq!(rows.len(), 1);
+        assert!(matches!(rows[0][0].1, FieldValue::Int(3)));
+        assert!(matches!(rows[0][1].1, FieldValue::Int(7)));
+    }
+
+    #[test]
+    fn test_read_redundant_field_offsets_extern_and_instant() {
+        let mut page = vec![0u8; 256];
+        let origin = 100;
+        // Two 2-byte end offsets: a 4-byte field, then an external 30-byte
+        // field; the third column was added after the record was written
+        BigEndian::write_u16(&mut page[origin - 8..], 4);
+        BigEndian::write_u16(&mut page[origin - 10..], 0x4000 | 34);
+        page[origin - 3] = 2 << 1;
+        let cols = vec![
+            column("id", 4, 4, false),
+            column("body", 27, 0, true),
+            column("added", 4, 4, true),
+        ];
+
+        let fields = read_redundant_field_offsets(&page, origin, &cols).unwrap();
+        assert_eq!(
+            fields,
+            vec![
+                (
+                    origin,
+                    RecordField::Stored {
+                        len: 4,
+                        external: false
+                    }
+                ),
+                (
+                    origin + 4,
+                    RecordField::Stored {
+                        len: 30,
+                        external: true
+                    }
+                ),
+                (origin + 34, RecordField::Absent),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_index_records_secondary() {
+        // KEY (n) on a table with PRIMARY KEY (id): leaf records hold n, id
+        let layouts = vec![
+            IndexLayout {
+                name: "PRIMARY".to_string(),
+                index_id: 10,
+                is_clustered: true,
+                columns: columns(),
+                descending: vec![false; 5],
+                prefix_lengths: vec![None; 5],
+            },
+            IndexLayout {
+                name: "k_n".to_string(),
+                index_id: 11,
+                is_clustered: false,
+                columns: vec![column("n", 4, 4, true), column("id", 4, 4, false)],
+                descending: vec![false; 2],
+                prefix_lengths: vec![None; 2],
+            },
+        ];
+        let mut data = Vec::new();
+        data.extend_from_slice(&(42u32 | 0x8000_0000).to_be_bytes());
+        data.extend_from_slice(&(7u32 | 0x8000_0000).to_be_bytes());
+        // One null bitmap byte (n is not NULL)
+        let page = build_records_page(&[(vec![0x00], 0x00, data)]);
+
+        let rows = decode_index_records(&page, 11, &layouts, false, false);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0].0, "n");
+        assert!(matches!(rows[0][0].1, FieldValue::Int(42)));
+        assert_eq!(rows[0][1].0, "id");
+        assert!(matches!(rows[0][1].1, FieldValue::Int(7)));
+
+        assert!(decode_index_records(&page, 99, &layouts, false, false).is_empty());
     }
 }
@@ -18,18 +18,27 @@
 //! | DATETIME | 5+fsp bytes packed bit-field | `decode_datetime` |
 //! | TIMESTAMP | 4+fsp bytes UTC seconds | `decode_timestamp` |
 //! | TIME | 3+fsp bytes packed bit-field | `decode_time` |
+//! | DATETIME (pre-5.6.4) | 8-byte integer `YYYYMMDDhhmmss` | `decode_old_datetime` |
+//! | TIMESTAMP (pre-5.6.4) | 4 bytes UTC seconds | `decode_timestamp` |
+//! | TIME (pre-5.6.4) | 3-byte integer `hhhmmss` | `decode_old_time` |
+//! | DECIMAL (pre-5.0) | Fixed-width ASCII digits | `decode_old_decimal` |
 //! | YEAR | 1 byte + 1900 | `decode_year` |
-//! | VARCHAR/CHAR | UTF-8 with lossy fallback | `decode_string` |
+//! | VARCHAR/CHAR (incl. pre-5.0.3 VARCHAR) | Transcoded from the column's character set, hex if invalid | [`charset::decode_charset`] |
 //! | ENUM | 1-2 byte index into element list | `decode_enum` |
 //! | SET | Bitmask into element list | `decode_set` |
-//! | BLOB/TEXT | Inline bytes or hex | `decode_string` |
-//! | JSON | Inline bytes as hex | `decode_hex` |
-//! | GEOMETRY | Inline bytes as hex (WKB) | `decode_hex` |
+//! | BLOB/TEXT | Transcoded like VARCHAR, hex if invalid | [`charset::decode_charset`] |
+//! | JSON | MySQL binary JSON | [`json_binary::decode_json_binary`] |
+//! | GEOMETRY | SRID + WKB | [`geometry::decode_geometry`] |
 //! | Others | Raw hex | `decode_hex` |
 
 use serde::Serialize;
 
-use crate::innodb::schema::DdTable;
+use crate::innodb::charset;
+use crate::innodb::geometry::{self, SpatialValue};
+use crate::innodb::json_binary;
+use crate::innodb::schema::{
+    charset_from_collation, parse_se_private_data, DdColumn, DdIndex, DdTable,
+};
 
 /// Decoded field value from an InnoDB record.
 #[derive(Debug, Clone, Serialize)]
@@ -49,6 +58,10 @@ pub enum FieldValue {
     Str(String),
     /// Hex-encoded bytes for unsupported types.
     Hex(String),
+    /// JSON document decoded from MySQL's binary JSON format.
+    Json(serde_json::Value),
+    /// Spatial value decoded from a GEOMETRY column.
+    Geometry(SpatialValue),
 }
 
 /// Physical storage information for a single column.
@@ -68,8 +81,22 @@ pub struct ColumnStorageInfo {
     pub fixed_len: usize,
     /// Whether this is a variable-length field.
     pub is_variable: bool,
+    /// Maximum stored length in bytes (SDI `char_length` for string types).
+    pub max_len: usize,
+    /// Row version in which the column was added by instant ADD COLUMN
+    /// (0 if the column predates any instant DDL).
+    pub version_added: u32,
+    /// Row version in which the column was dropped by instant DROP COLUMN
+    /// (0 if the column has not been dropped).
+    pub version_dropped: u32,
+    /// Value for records written before the column was instantly added,
+    /// or `None` if the column was not added by instant ADD COLUMN.
+    pub instant_default: Option<FieldValue>,
     /// Maximum bytes per character for string types.
     pub charset_max_bytes: usize,
+    /// Collation ID (selects the character set used to decode strings;
+    /// 0 if unknown, which decodes as UTF-8).
+    pub collation_id: u64,
     /// Fractional seconds precision for DATETIME/TIMESTAMP/TIME.
     pub datetime_precision: u64,
     /// Whether this is a system column (DB_TRX_ID, DB_ROLL_PTR, DB_ROW_ID).
@@ -82,28 +109,57 @@ pub struct ColumnStorageInfo {
     pub numeric_scale: u64,
 }
 
-// MySQL dd_type codes (from sql/dd/types/column.h)
+impl ColumnStorageInfo {
+    /// Whether this is a "big" variable-length column.
+    ///
+    /// Big columns (BLOB/TEXT/JSON/GEOMETRY, or any column whose maximum
+    /// length exceeds 255 bytes) use a 2-byte length in the compact record
+    /// header when the value is longer than 127 bytes, and are the only
+    /// columns that can be stored externally on overflow pages.
+    pub fn is_big(&self) -> bool {
+        self.is_variable
+            && (matches!(
+                self.dd_type,
+                DD_TYPE_TINY_BLOB..=DD_TYPE_BLOB | DD_TYPE_JSON | DD_TYPE_GEOMETRY
+            ) || self.max_len > 255)
+    }
+
+    /// Whether the column is physically stored in records of row version
+    /// `version` (MySQL 8.0.29+ instant ADD/DROP COLUMN).
+    pub fn in_row_version(&self, version: u32) -> bool {
+        self.version_added <= version
+            && (self.version_dropped == 0 || self.version_dropped > version)
+    }
+}
+
+// MySQL dd_type codes (dd::enum_column_types in sql/dd/types/column.h)
+const DD_TYPE_DECIMAL: u64 = 1; // DECIMAL (pre-5.0 ASCII format)
 const DD_TYPE_TINY: u64 = 2; // TINYINT
 const DD_TYPE_SHORT: u64 = 3; // SMALLINT
-const DD_TYPE_INT24: u64 = 5; // MEDIUMINT
 const DD_TYPE_LONG: u64 = 4; // INT
+const DD_TYPE_FLOAT: u64 = 5; // FLOAT
+const DD_TYPE_DOUBLE: u64 = 6; // DOUBLE
+const DD_TYPE_TIMESTAMP: u64 = 8; // TIMESTAMP (pre-5.6.4)
 const DD_TYPE_LONGLONG: u64 = 9; // BIGINT
-const DD_TYPE_FLOAT: u64 = 6; // FLOAT
-const DD_TYPE_DOUBLE: u64 = 7; // DOUBLE
-const DD_TYPE_NEWDECIMAL: u64 = 20; // DECIMAL
-const DD_TYPE_DATE: u64 = 13; // DATE (newdate)
-const DD_TYPE_DATETIME: u64 = 18; // DATETIME2
-const DD_TYPE_TIMESTAMP: u64 = 17; // TIMESTAMP2
+const DD_TYPE_INT24: u64 = 10; // MEDIUMINT
+const DD_TYPE_TIME: u64 = 12; // TIME (pre-5.6.4)
+const DD_TYPE_DATETIME: u64 = 13; // DATETIME (pre-5.6.4)
 const DD_TYPE_YEAR: u64 = 14; // YEAR
+const DD_TYPE_DATE: u64 = 15; // DATE (newdate)
 const DD_TYPE_VARCHAR: u64 = 16; // VARCHAR
-const DD_TYPE_STRING: u64 = 15; // CHAR
-const DD_TYPE_BLOB: u64 = 19; // BLOB/TEXT
-const DD_TYPE_JSON: u64 = 21; // JSON
+const DD_TYPE_BIT: u64 = 17; // BIT
+const DD_TYPE_TIMESTAMP2: u64 = 18; // TIMESTAMP
+const DD_TYPE_DATETIME2: u64 = 19; // DATETIME
+const DD_TYPE_TIME2: u64 = 20; // TIME
+const DD_TYPE_NEWDECIMAL: u64 = 21; // DECIMAL
 const DD_TYPE_ENUM: u64 = 22; // ENUM
 const DD_TYPE_SET: u64 = 23; // SET
-const DD_TYPE_BIT: u64 = 24; // BIT
-const DD_TYPE_GEOMETRY: u64 = 25; // GEOMETRY
-const DD_TYPE_TIME2: u64 = 12; // TIME2
+const DD_TYPE_TINY_BLOB: u64 = 24; // TINYBLOB/TINYTEXT
+const DD_TYPE_BLOB: u64 = 27; // BLOB/TEXT (all sizes use this code in SDI)
+const DD_TYPE_VAR_STRING: u64 = 28; // VARCHAR (pre-5.0.3)
+const DD_TYPE_STRING: u64 = 29; // CHAR/BINARY
+const DD_TYPE_GEOMETRY: u64 = 30; // GEOMETRY
+const DD_TYPE_JSON: u64 = 31; // JSON
 
 /// Build a column layout from SDI table metadata.
 ///
@@ -115,6 +171,14 @@ const DD_TYPE_TIME2: u64 = 12; // TIME2
 ///
 /// Hidden columns (hidden == 2, i.e., SE-hidden) are included as system columns.
 /// Virtual/generated columns (is_virtual) are excluded.
+///
+/// Tables altered with `ALGORITHM=INSTANT` carry extra metadata in
+/// `se_private_data`. Columns dropped instantly (MySQL 8.0.29+) remain in
+/// the layout as system columns because older records still store them,
+/// and with row versions the non-key columns follow their `physical_pos`.
+/// Columns added instantly get their `version_added` and the default value
+/// that applies to records written before the ADD COLUMN (table
+/// `instant_col` marks the pre-8.0.29 instant columns).
 pub fn build_column_layout(dd_table: &DdTable) -> Vec<ColumnStorageInfo> {
     let mut layout = Vec::new();
 
@@ -131,14 +195,31 @@ pub fn build_column_layout(dd_table: &DdTable) -> Vec<ColumnStorageInfo> {
         }
     }
 
-    // Build visible user columns sorted by ordinal_position
+    // Build visible user columns sorted by ordinal_position, plus columns
+    // removed by instant DROP COLUMN (SE-hidden but still in older records)
     let mut user_columns: Vec<&crate::innodb::schema::DdColumn> = dd_table
         .columns
         .iter()
-        .filter(|c| !c.is_virtual && (c.hidden == 1 || c.hidden == 4)) // HT_VISIBLE + HT_HIDDEN_USER only
+        .filter(|c| {
+            // HT_VISIBLE, HT_HIDDEN_USER, or dropped in some row version
+            !c.is_virtual && (c.hidden == 1 || c.hidden == 4 || instant_version(c).1 > 0)
+        })
         .collect();
     user_columns.sort_by_key(|c| c.ordinal_position);
 
+    // With row versions, records store columns in physical order
+    let has_row_versions = dd_table.columns.iter().any(|c| {
+        let (added, dropped) = instant_version(c);
+        added > 0 || dropped > 0
+    });
+    if has_row_versions {
+        user_columns.sort_by_key(|c| {
+            se_private_value(c, "physical_pos")
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(u64::MAX)
+        });
+    }
+
     // PK columns first
     for &pk_opx in &pk_col_positions {
         if let Some(col) = dd_table.columns.get(pk_opx as usize) {
@@ -157,7 +238,12 @@ pub fn build_column_layout(dd_table: &DdTable) -> Vec<ColumnStorageInfo> {
         is_unsigned: true,
         fixed_len: 6,
         is_variable: false,
+        max_len: 6,
+        version_added: 0,
+        version_dropped: 0,
+        instant_default: None,
         charset_max_bytes: 0,
+        collation_id: 0,
         datetime_precision: 0,
         is_system_column: true,
         elements: Vec::new(),
@@ -172,7 +258,12 @@ pub fn build_column_layout(dd_table: &DdTable) -> Vec<ColumnStorageInfo> {
         is_unsigned: true,
         fixed_len: 7,
         is_variable: false,
+        max_len: 7,
+        version_added: 0,
+        version_dropped: 0,
+        instant_default: None,
         charset_max_bytes: 0,
+        collation_id: 0,
         datetime_precision: 0,
         is_system_column: true,
         elements: Vec::new(),
@@ -185,14 +276,150 @@ pub fn build_column_layout(dd_table: &DdTable) -> Vec<ColumnStorageInfo> {
         let col_opx = dd_table.columns.iter().position(|c| std::ptr::eq(c, *col));
         if let Some(opx) = col_opx {
             if !pk_col_positions.contains(&(opx as u64)) {
-                layout.push(column_to_storage_info(col, false));
+                let is_dropped = instant_version(col).1 > 0;
+                layout.push(column_to_storage_info(col, is_dropped));
+            }
+        }
+    }
+
+    // Pre-8.0.29 instant ADD COLUMN: user columns past `instant_col` are
+    // absent from records without the instant flag
+    let instant_cols = dd_table.se_private_data.as_deref().and_then(|spd| {
+        parse_se_private_data(spd)
+            .get("instant_col")?
+            .parse::<usize>()
+            .ok()
+    });
+    if let Some(n_instant) = instant_cols {
+        for col in layout
+            .iter_mut()
+            .filter(|c| !c.is_system_column)
+            .skip(n_instant)
+        {
+            if col.instant_default.is_none() {
+                col.instant_default = Some(FieldValue::Null);
+            }
+        }
+    }
+
+    layout
+}
+
+/// Prefix length value in `DdIndexElement::length` meaning the full column.
+const FULL_COLUMN_LENGTH: u64 = 4_294_967_295;
+
+/// Build a secondary index column layout from SDI table metadata.
+///
+/// Secondary index leaf records store the key columns in element order,
+/// followed by the primary key columns not already in the key (SDI lists
+/// these as hidden elements; any missing are appended from the PRIMARY
+/// index). A column prefix stores at most `length` bytes, so a prefix of a
+/// fixed-length column becomes fixed at the prefix length.
+///
+/// Unlike [`build_column_layout`], the layout has no DB_TRX_ID/DB_ROLL_PTR
+/// and no instant ADD/DROP COLUMN metadata, because secondary index
+/// records are always rebuilt with every field present. A hidden
+/// DB_ROW_ID (tables without a primary key) is included as a 6-byte
+/// system column.
+pub fn build_index_layout(dd_table: &DdTable, index: &DdIndex) -> Vec<ColumnStorageInfo> {
+    let mut layout = Vec::new();
+    for (col, prefix) in index_fields(dd_table, index) {
+        if col.hidden == 2 {
+            // Only DB_ROW_ID can be part of an index key
+            layout.push(ColumnStorageInfo {
+                name: col.name.clone(),
+                dd_type: 0,
+                column_type: "system".to_string(),
+                is_nullable: false,
+                is_unsigned: true,
+                fixed_len: 6,
+                is_variable: false,
+                max_len: 6,
+                version_added: 0,
+                version_dropped: 0,
+                instant_default: None,
+                charset_max_bytes: 0,
+                collation_id: 0,
+                datetime_precision: 0,
+                is_system_column: true,
+                elements: Vec::new(),
+                numeric_precision: 0,
+                numeric_scale: 0,
+            });
+            continue;
+        }
+
+        let mut info = column_to_storage_info(col, false);
+        info.version_added = 0;
+        info.version_dropped = 0;
+        info.instant_default = None;
+
+        if let Some(length) = prefix {
+            if !info.is_variable {
+                info.fixed_len = length;
             }
         }
+        layout.push(info);
     }
 
     layout
 }
 
+/// Column prefix lengths of a secondary index, aligned with
+/// [`build_index_layout`].
+///
+/// Each entry is the prefix length in bytes for a column prefix key part,
+/// or `None` if the field stores the whole column.
+pub fn index_prefix_lengths(dd_table: &DdTable, index: &DdIndex) -> Vec<Option<usize>> {
+    index_fields(dd_table, index)
+        .into_iter()
+        .map(|(_, prefix)| prefix)
+        .collect()
+}
+
+/// Fields of a secondary index record: the key columns in element order,
+/// then the primary key columns not already in the key, each with its
+/// prefix length if it is a column prefix.
+fn index_fields<'a>(dd_table: &'a DdTable, index: &DdIndex) -> Vec<(&'a DdColumn, Option<usize>)> {
+    let mut elements: Vec<(u64, u64)> = index
+        .elements
+        .iter()
+        .map(|e| (e.column_opx, e.length))
+        .collect();
+    if let Some(pk) = dd_table.indexes.iter().find(|i| i.index_type == 1) {
+        for elem in pk.elements.iter().filter(|e| !e.hidden) {
+            if !elements.iter().any(|&(opx, _)| opx == elem.column_opx) {
+                elements.push((elem.column_opx, FULL_COLUMN_LENGTH));
+            }
+        }
+    }
+
+    elements
+        .into_iter()
+        .filter_map(|(opx, length)| {
+            let col = dd_table.columns.get(opx as usize)?;
+            let is_prefix = length != FULL_COLUMN_LENGTH && length < col.char_length;
+            Some((col, is_prefix.then_some(length as usize)))
+        })
+        .collect()
+}
+
+/// Look up a key in a column's `se_private_data`.
+fn se_private_value(col: &crate::innodb::schema::DdColumn, key: &str) -> Option<String> {
+    let spd = col.se_private_data.as_deref()?;
+    parse_se_private_data(spd).remove(key)
+}
+
+/// Instant DDL row versions `(version_added, version_dropped)` of a column.
+fn instant_version(col: &crate::innodb::schema::DdColumn) -> (u32, u32) {
+    let version = |key| {
+        se_private_value(col, key)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0)
+    };
+    (version("version_added"), version("version_dropped"))
+}
+
 /// Convert a DdColumn to a ColumnStorageInfo.
 fn column_to_storage_info(
     col: &crate::innodb::schema::DdColumn,
@@ -201,8 +428,9 @@ fn column_to_storage_info(
     let (fixed_len, is_variable) = compute_storage_size(col);
     let charset_max_bytes = charset_max_bytes_from_collation(col.collation_id);
     let elements: Vec<String> = col.elements.iter().map(|e| e.name.clone()).collect();
+    let (version_added, version_dropped) = instant_version(col);
 
-    ColumnStorageInfo {
+    let mut info = ColumnStorageInfo {
         name: col.name.clone(),
         dd_type: col.dd_type,
         column_type: col.column_type_utf8.clone(),
@@ -210,13 +438,34 @@ fn column_to_storage_info(
         is_unsigned: col.is_unsigned,
         fixed_len,
         is_variable,
+        max_len: col.char_length as usize,
+        version_added,
+        version_dropped,
+        instant_default: None,
         charset_max_bytes,
+        collation_id: col.collation_id,
         datetime_precision: col.datetime_precision,
         is_system_column: is_system,
         elements,
         numeric_precision: col.numeric_precision,
         numeric_scale: col.numeric_scale,
-    }
+    };
+
+    // Instantly added columns record their default in InnoDB format
+    // ("default=<hex>") or as "default_null=1"
+    if se_private_value(col, "default_null").is_some() {
+        info.instant_default = Some(FieldValue::Null);
+    } else if let Some(hex) = se_private_value(col, "default") {
+        let bytes: Option<Vec<u8>> = (0..hex.len() / 2)
+            .map(|i| u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok())
+            .collect();
+        info.instant_default = Some(match bytes {
+            Some(b) => decode_field(&b, &info),
+            None => FieldValue::Null,
+        });
+    }
+
+    info
 }
 
 /// Compute the fixed storage size and variable-length flag for a column.
@@ -231,25 +480,35 @@ fn compute_storage_size(col: &crate::innodb::schema::DdColumn) -> (usize, bool)
         DD_TYPE_DOUBLE => (8, false),
         DD_TYPE_YEAR => (1, false),
         DD_TYPE_DATE => (3, false),
-        DD_TYPE_DATETIME | DD_TYPE_TIMESTAMP | DD_TYPE_TIME2 => {
+        // Pre-5.6.4 temporal types have no fractional seconds
+        DD_TYPE_DATETIME => (8, false),
+        DD_TYPE_TIMESTAMP => (4, false),
+        DD_TYPE_TIME => (3, false),
+        DD_TYPE_DATETIME2 | DD_TYPE_TIMESTAMP2 | DD_TYPE_TIME2 => {
             // Base size + fractional seconds storage
             let base = match col.dd_type {
-                DD_TYPE_DATETIME => 5,
-                DD_TYPE_TIMESTAMP => 4,
+                DD_TYPE_DATETIME2 => 5,
+                DD_TYPE_TIMESTAMP2 => 4,
                 DD_TYPE_TIME2 => 3,
                 _ => unreachable!(),
             };
             let fsp_bytes = fsp_storage_bytes(col.datetime_precision);
             (base + fsp_bytes, false)
         }
-        DD_TYPE_VARCHAR | DD_TYPE_BLOB | DD_TYPE_JSON | DD_TYPE_GEOMETRY => {
+        DD_TYPE_VARCHAR
+        | DD_TYPE_VAR_STRING
+        | DD_TYPE_TINY_BLOB..=DD_TYPE_BLOB
+        | DD_TYPE_JSON
+        | DD_TYPE_GEOMETRY => {
             (0, true) // variable-length
         }
         DD_TYPE_STRING => {
-            // CHAR: fixed-length = char_length * charset_max_bytes
-            let max_bytes = charset_max_bytes_from_collation(col.collation_id);
-            if max_bytes > 1 {
-                // Multi-byte CHAR is stored as variable-length in compact format
+            // CHAR: char_length is already in bytes (characters * mbmaxlen)
+            let (min_bytes, max_bytes) = charset::charset_width(
+                charset_from_collation(col.collation_id).unwrap_or("utf8mb4"),
+            );
+            if min_bytes != max_bytes {
+                // Variable-width CHAR is stored as variable-length in compact format
                 (0, true)
             } else {
                 (col.char_length as usize, false)
@@ -276,6 +535,15 @@ fn compute_storage_size(col: &crate::innodb::schema::DdColumn) -> (usize, bool)
             let bytes = bits.div_ceil(8).max(1);
             (bytes, false)
         }
+        DD_TYPE_DECIMAL => {
+            // Pre-5.0 DECIMAL: one ASCII byte per digit, sign, and point
+            let len = if col.char_length > 0 {
+                col.char_length as usize
+            } else {
+                col.numeric_precision as usize + 2
+            };
+            (len, false)
+        }
         DD_TYPE_NEWDECIMAL => {
             // DECIMAL: complex packed BCD, approximate
             let precision = col.numeric_precision as usize;
@@ -313,27 +581,12 @@ fn fsp_storage_bytes(fsp: u64) -> usize {
 }
 
 /// Determine max bytes per character from collation ID.
-/// Common collation IDs from MySQL:
-///   - 33 (utf8_general_ci): 3 bytes
-///   - 45 (utf8mb4_general_ci): 4 bytes
-///   - 255 (utf8mb4_0900_ai_ci): 4 bytes
-///   - 8 (latin1_swedish_ci): 1 byte
-///   - 63 (binary): 1 byte
+///
+/// Unknown collations are assumed to be utf8mb4 (4 bytes, the safe upper bound).
 fn charset_max_bytes_from_collation(collation_id: u64) -> usize {
-    match collation_id {
-        // latin1 collations
-        5 | 8 | 15 | 31 | 47 | 48 | 49 | 94 => 1,
-        // binary
-        63 => 1,
-        // ascii
-        11 | 65 => 1,
-        // utf8 (3-byte)
-        33 | 83 | 192..=215 => 3,
-        // utf8mb4 (4-byte) — most common in MySQL 8.0+
-        45 | 46 | 224..=247 | 255 | 256..=310 => 4,
-        // Default: assume 4-byte (safe upper bound)
-        _ => 4,
-    }
+    charset_from_collation(collation_id)
+        .map(|cs| charset::charset_width(cs).1)
+        .unwrap_or(4)
 }
 
 /// Decode a field value from raw bytes based on column storage info.
@@ -351,16 +604,29 @@ pub fn decode_field(data: &[u8], col: &ColumnStorageInfo) -> FieldValue {
         DD_TYPE_FLOAT => decode_float(data),
         DD_TYPE_DOUBLE => decode_double(data),
         DD_TYPE_NEWDECIMAL => decode_decimal(data, col.numeric_precision, col.numeric_scale),
+        DD_TYPE_DECIMAL => decode_old_decimal(data),
         DD_TYPE_DATE => decode_date(data),
-        DD_TYPE_DATETIME => decode_datetime(data, col.datetime_precision),
-        DD_TYPE_TIMESTAMP => decode_timestamp(data, col.datetime_precision),
+        DD_TYPE_DATETIME => decode_old_datetime(data),
+        DD_TYPE_TIMESTAMP => decode_timestamp(data, 0),
+        DD_TYPE_TIME => decode_old_time(data),
+        DD_TYPE_DATETIME2 => decode_datetime(data, col.datetime_precision),
+        DD_TYPE_TIMESTAMP2 => decode_timestamp(data, col.datetime_precision),
         DD_TYPE_TIME2 => decode_time(data, col.datetime_precision),
         DD_TYPE_YEAR => decode_year(data),
-        DD_TYPE_VARCHAR | DD_TYPE_STRING => decode_string(data),
-        DD_TYPE_BLOB => decode_string(data),
+        DD_TYPE_VARCHAR
+        | DD_TYPE_VAR_STRING
+        | DD_TYPE_STRING
+        | DD_TYPE_TINY_BLOB..=DD_TYPE_BLOB => decode_string(data, col),
         DD_TYPE_ENUM => decode_enum(data, &col.elements),
         DD_TYPE_SET => decode_set(data, &col.elements),
-        DD_TYPE_JSON | DD_TYPE_GEOMETRY => decode_hex(data),
+        DD_TYPE_JSON => match json_binary::decode_json_binary(data) {
+            Some(v) => FieldValue::Json(v),
+            None => decode_hex(data),
+        },
+        DD_TYPE_GEOMETRY => match geometry::decode_geometry(data) {
+            Some(g) => FieldValue::Geometry(g),
+            None => decode_hex(data),
+        },
         // System columns: decode as unsigned int
         0 if col.is_system_column => decode_int(data, data.len(), true),
         // Everything else: hex fallback
@@ -545,7 +811,61 @@ fn decode_datetime(data: &[u8], fsp: u64) -> FieldValue {
     }
 }
 
-/// Decode a TIMESTAMP2 (4 + fsp bytes).
+/// Decode a pre-5.6.4 DATETIME (8 bytes).
+///
+/// Stored as a signed 64-bit integer `YYYYMMDDhhmmss`, big-endian with
+/// the sign bit XOR'd like other InnoDB signed integers.
+fn decode_old_datetime(data: &[u8]) -> FieldValue {
+    if data.len() < 8 {
+        return decode_hex(data);
+    }
+
+    let mut raw = [0u8; 8];
+    raw.copy_from_slice(&data[..8]);
+    let val = (u64::from_be_bytes(raw) ^ (1 << 63)) as i64;
+    if val < 0 {
+        return decode_hex(data);
+    }
+    let val = val as u64;
+
+    let date = val / 1_000_000;
+    let time = val % 1_000_000;
+    FieldValue::Str(format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        date / 10000,
+        (date / 100) % 100,
+        date % 100,
+        time / 10000,
+        (time / 100) % 100,
+        time % 100
+    ))
+}
+
+/// Decode a pre-5.6.4 TIME (3 bytes).
+///
+/// Stored as a signed 24-bit integer `±hhhmmss`, big-endian with the sign
+/// bit XOR'd.
+fn decode_old_time(data: &[u8]) -> FieldValue {
+    if data.len() < 3 {
+        return decode_hex(data);
+    }
+
+    let stored = ((data[0] as u32) << 16) | ((data[1] as u32) << 8) | data[2] as u32;
+    let signed_val = stored as i32 - 0x800000;
+    let sign = if signed_val < 0 { "-" } else { "" };
+    let abs_val = signed_val.unsigned_abs();
+
+    FieldValue::Str(format!(
+        "{}{:02}:{:02}:{:02}",
+        sign,
+        abs_val / 10000,
+        (abs_val / 100) % 100,
+        abs_val % 100
+    ))
+}
+
+/// Decode a TIMESTAMP2 (4 + fsp bytes), or a pre-5.6.4 TIMESTAMP with
+/// `fsp` 0.
 ///
 /// 4-byte big-endian UTC seconds since epoch.
 fn decode_timestamp(data: &[u8], fsp: u64) -> FieldValue {
@@ -628,20 +948,52 @@ fn decode_year(data: &[u8]) -> FieldValue {
     }
 }
 
-/// Decode a string field (VARCHAR or CHAR).
-fn decode_string(data: &[u8]) -> FieldValue {
-    // CHAR fields have trailing padding (0x20 for ASCII/UTF-8)
-    let trimmed = trim_trailing_spaces(data);
-    FieldValue::Str(String::from_utf8_lossy(trimmed).to_string())
+/// Decode a string field (CHAR, VARCHAR, or TEXT) in the column's character set.
+///
+/// CHAR values are padded with spaces to their full width; the padding is
+/// trimmed after transcoding so multi-byte spaces (e.g. `00 20` in ucs2) are
+/// removed whole. BINARY columns keep their padding. Bytes that are invalid
+/// in the character set are returned as hex rather than lossily replaced.
+fn decode_string(data: &[u8], col: &ColumnStorageInfo) -> FieldValue {
+    let cs = charset_from_collation(col.collation_id).unwrap_or("utf8mb4");
+    match charset::decode_charset(data, cs) {
+        Some(s) if col.dd_type == DD_TYPE_STRING && cs != "binary" => {
+            FieldValue::Str(s.trim_end_matches(' ').to_string())
+        }
+        Some(s) => FieldValue::Str(s),
+        None => decode_hex(data),
+    }
 }
 
-/// Trim trailing 0x20 (space) bytes.
-fn trim_trailing_spaces(data: &[u8]) -> &[u8] {
-    let mut end = data.len();
-    while end > 0 && data[end - 1] == 0x20 {
-        end -= 1;
+/// Decode a pre-5.0 DECIMAL.
+///
+/// The value is stored as a fixed-width ASCII string (`field_length`
+/// bytes), right-aligned and padded on the left with spaces or zeros,
+/// with an optional leading `-`. Leading zeros are dropped from the
+/// integer part; non-numeric content falls back to hex.
+fn decode_old_decimal(data: &[u8]) -> FieldValue {
+    let text = match std::str::from_utf8(data) {
+        Ok(t) => t.trim_matches(' '),
+        Err(_) => return decode_hex(data),
+    };
+    let (sign, digits) = match text.strip_prefix('-') {
+        Some(rest) => ("-", rest.trim_start_matches(' ')),
+        None => ("", text.strip_prefix('+').unwrap_or(text)),
+    };
+    let valid = !digits.is_empty()
+        && digits.bytes().all(|b| b.is_ascii_digit() || b == b'.')
+        && digits.bytes().filter(|&b| b == b'.').count() <= 1;
+    if !valid {
+        return decode_hex(data);
     }
-    &data[..end]
+
+    let trimmed = digits.trim_start_matches('0');
+    let digits = if trimmed.is_empty() || trimmed.starts_with('.') {
+        format!("0{}", trimmed)
+    } else {
+        trimmed.to_string()
+    };
+    FieldValue::Str(format!("{}{}", sign, digits))
 }
 
 /// Decode InnoDB packed BCD DECIMAL.
@@ -652,7 +1004,7 @@ fn trim_trailing_spaces(data: &[u8]) -> &[u8] {
 /// - The first byte has the sign bit XOR'd for memcmp ordering
 /// - Positive values: high bit set in stored form
 /// - Negative values: all bytes XOR'd with 0xFF
-fn decode_decimal(data: &[u8], precision: u64, scale: u64) -> FieldValue {
+pub(crate) fn decode_decimal(data: &[u8], precision: u64, scale: u64) -> FieldValue {
     if precision == 0 {
         return decode_hex(data);
     }
@@ -868,7 +1220,12 @@ mod tests {
             is_unsigned: unsigned,
             fixed_len: 4,
             is_variable: false,
+            max_len: 4,
+            version_added: 0,
+            version_dropped: 0,
+            instant_default: None,
             charset_max_bytes: 1,
+            collation_id: 0,
             datetime_precision: 0,
             is_system_column: false,
             elements: Vec::new(),
@@ -1002,7 +1359,12 @@ mod tests {
             is_unsigned: false,
             fixed_len: 0,
             is_variable: true,
+            max_len: 1020,
+            version_added: 0,
+            version_dropped: 0,
+            instant_default: None,
             charset_max_bytes: 4,
+            collation_id: 0,
             datetime_precision: 0,
             is_system_column: false,
             elements: vec![],
@@ -1027,7 +1389,12 @@ mod tests {
             is_unsigned: false,
             fixed_len: 10,
             is_variable: false,
+            max_len: 10,
+            version_added: 0,
+            version_dropped: 0,
+            instant_default: None,
             charset_max_bytes: 1,
+            collation_id: 0,
             datetime_precision: 0,
             is_system_column: false,
             elements: vec![],
@@ -1040,6 +1407,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_decode_string_collation() {
+        // CHAR(3) in ucs2: "Aб" + one 2-byte space of padding
+        let mut col = make_col(DD_TYPE_STRING, false);
+        col.collation_id = 35;
+        let data = [0x00, 0x41, 0x04, 0x31, 0x00, 0x20];
+        match decode_field(&data, &col) {
+            FieldValue::Str(s) => assert_eq!(s, "Aб"),
+            other => panic!("Expected Str, got {:?}", other),
+        }
+
+        // VARCHAR in cp1251
+        col.dd_type = DD_TYPE_VARCHAR;
+        col.collation_id = 51;
+        match decode_field(&[0xCF, 0xF0, 0xE8], &col) {
+            FieldValue::Str(s) => assert_eq!(s, "При"),
+            other => panic!("Expected Str, got {:?}", other),
+        }
+
+        // Invalid UTF-8 falls back to hex instead of replacement characters
+        col.collation_id = 255;
+        match decode_field(&[0x61, 0xFF], &col) {
+            FieldValue::Hex(h) => assert_eq!(h, "0x61ff"),
+            other => panic!("Expected Hex, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_decode_hex_fallback() {
         let data = [0xDE, 0xAD, 0xBE, 0xEF];
@@ -1117,6 +1511,209 @@ mod tests {
         assert_eq!(layout[4].name, "age");
     }
 
+    #[test]
+    fn test_build_column_layout_instant_row_versions() {
+        use crate::innodb::schema::DdColumn;
+        // id (PK), a, b dropped in v2, c added in v1 with AFTER id
+        let dd_table = DdTable {
+            name: "t".to_string(),
+            columns: vec![
+                DdColumn {
+                    name: "id".to_string(),
+                    dd_type: DD_TYPE_LONG,
+                    ordinal_position: 1,
+                    hidden: 1,
+                    se_private_data: Some("physical_pos=0;table_id=1065;".to_string()),
+                    ..Default::default()
+                },
+                DdColumn {
+                    name: "c".to_string(),
+                    dd_type: DD_TYPE_LONG,
+                    ordinal_position: 2,
+                    hidden: 1,
+                    se_private_data: Some(
+                        "default=80000007;physical_pos=5;table_id=1065;version_added=1;"
+                            .to_string(),
+                    ),
+                    ..Default::default()
+                },
+                DdColumn {
+                    name: "a".to_string(),
+                    dd_type: DD_TYPE_LONG,
+                    ordinal_position: 3,
+                    hidden: 1,
+                    se_private_data: Some("physical_pos=3;table_id=1065;".to_string()),
+                    ..Default::default()
+                },
+                DdColumn {
+                    name: "!hidden!_dropped_v2_p4_b".to_string(),
+                    dd_type: DD_TYPE_LONG,
+                    ordinal_position: 6,
+                    hidden: 2,
+                    is_nullable: true,
+                    se_private_data: Some(
+                        "physical_pos=4;table_id=1065;version_dropped=2;".to_string(),
+                    ),
+                    ..Default::default()
+                },
+                DdColumn {
+                    name: "DB_TRX_ID".to_string(),
+                    dd_type: 10,
+                    ordinal_position: 4,
+                    hidden: 2,
+                    se_private_data: Some("physical_pos=1;table_id=1065;".to_string()),
+                    ..Default::default()
+                },
+            ],
+            indexes: vec![crate::innodb::schema::DdIndex {
+                name: "PRIMARY".to_string(),
+                index_type: 1,
+                elements: vec![crate::innodb::schema::DdIndexElement {
+                    column_opx: 0,
+                    length: 4,
+                    order: 2,
+                    hidden: false,
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let layout = build_column_layout(&dd_table);
+        let names: Vec<&str> = layout.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "id",
+                "DB_TRX_ID",
+                "DB_ROLL_PTR",
+                "a",
+                "!hidden!_dropped_v2_p4_b",
+                "c"
+            ]
+        );
+        assert!(layout[4].is_system_column);
+        assert_eq!(layout[4].version_dropped, 2);
+        assert!(layout[4].in_row_version(1));
+        assert!(!layout[4].in_row_version(2));
+        assert_eq!(layout[5].version_added, 1);
+        assert!(!layout[5].in_row_version(0));
+        assert!(matches!(
+            layout[5].instant_default,
+            Some(FieldValue::Int(7))
+        ));
+        assert!(layout[3].instant_default.is_none());
+    }
+
+    #[test]
+    fn test_build_index_layout_prefix_and_pk() {
+        use crate::innodb::schema::{DdColumn, DdIndex, DdIndexElement};
+        let elem = |column_opx, length, hidden| DdIndexElement {
+            column_opx,
+            length,
+            order: 2,
+            hidden,
+        };
+        // id INT PK, code CHAR(8) latin1, name VARCHAR(64) latin1 with an
+        // instant default; KEY k (code(3), name) without the hidden PK element
+        let dd_table = DdTable {
+            name: "t".to_string(),
+            columns: vec![
+                DdColumn {
+                    name: "id".to_string(),
+                    dd_type: DD_TYPE_LONG,
+                    hidden: 1,
+                    ..Default::default()
+                },
+                DdColumn {
+                    name: "code".to_string(),
+                    dd_type: DD_TYPE_STRING,
+                    char_length: 8,
+                    collation_id: 8,
+                    hidden: 1,
+                    ..Default::default()
+                },
+                DdColumn {
+                    name: "name".to_string(),
+                    dd_type: DD_TYPE_VARCHAR,
+                    char_length: 64,
+                    collation_id: 8,
+                    is_nullable: true,
+                    hidden: 1,
+                    se_private_data: Some("default_null=1;version_added=1;".to_string()),
+                    ..Default::default()
+                },
+            ],
+            indexes: vec![
+                DdIndex {
+                    name: "PRIMARY".to_string(),
+                    index_type: 1,
+                    elements: vec![elem(0, 4, false)],
+                    ..Default::default()
+                },
+                DdIndex {
+                    name: "k".to_string(),
+                    index_type: 3,
+                    elements: vec![elem(1, 3, false), elem(2, FULL_COLUMN_LENGTH, false)],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let layout = build_index_layout(&dd_table, &dd_table.indexes[1]);
+        let names: Vec<&str> = layout.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["code", "name", "id"]);
+        assert_eq!(layout[0].fixed_len, 3);
+        assert!(!layout[0].is_variable);
+        assert!(layout[1].is_variable);
+        assert!(layout[1].instant_default.is_none());
+        assert_eq!(layout[1].version_added, 0);
+        assert_eq!(layout[2].fixed_len, 4);
+        assert!(layout.iter().all(|c| !c.is_system_column));
+    }
+
+    #[test]
+    fn test_build_column_layout_instant_col_v1() {
+        use crate::innodb::schema::DdColumn;
+        let col = |name: &str, pos: u64, spd: Option<&str>| DdColumn {
+            name: name.to_string(),
+            dd_type: DD_TYPE_LONG,
+            ordinal_position: pos,
+            hidden: 1,
+            is_nullable: pos > 1,
+            se_private_data: spd.map(|s| s.to_string()),
+            ..Default::default()
+        };
+        let dd_table = DdTable {
+            name: "t".to_string(),
+            columns: vec![
+                col("id", 1, None),
+                col("a", 2, None),
+                col("b", 3, Some("default_null=1;table_id=1066;")),
+                col("c", 4, None),
+            ],
+            indexes: vec![crate::innodb::schema::DdIndex {
+                name: "PRIMARY".to_string(),
+                index_type: 1,
+                elements: vec![crate::innodb::schema::DdIndexElement {
+                    column_opx: 0,
+                    length: 4,
+                    order: 2,
+                    hidden: false,
+                }],
+                ..Default::default()
+            }],
+            se_private_data: Some("instant_col=2;".to_string()),
+            ..Default::default()
+        };
+
+        let layout = build_column_layout(&dd_table);
+        assert!(layout[3].instant_default.is_none()); // a
+        assert!(matches!(layout[4].instant_default, Some(FieldValue::Null))); // b
+        assert!(matches!(layout[5].instant_default, Some(FieldValue::Null))); // c
+    }
+
     #[test]
     fn test_fsp_storage_bytes() {
         assert_eq!(fsp_storage_bytes(0), 0);
@@ -1136,6 +1733,81 @@ mod tests {
         assert_eq!(charset_max_bytes_from_collation(255), 4); // utf8mb4
     }
 
+    // -----------------------------------------------------------------------
+    // Legacy (pre-5.6.4 / pre-5.0) type decoder tests
+    // -----------------------------------------------------------------------
+
+    fn decode_str(data: &[u8], dd_type: u64) -> String {
+        match decode_field(data, &make_col(dd_type, false)) {
+            FieldValue::Str(s) => s,
+            other => panic!("Expected Str, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_old_datetime() {
+        // 2009-03-15 12:34:56 as 20090315123456 with the sign bit flipped
+        let data = [0x80, 0x00, 0x12, 0x45, 0xA4, 0x18, 0xAB, 0x00];
+        assert_eq!(decode_str(&data, DD_TYPE_DATETIME), "2009-03-15 12:34:56");
+        assert_eq!(
+            decode_str(&[0x80, 0, 0, 0, 0, 0, 0, 0], DD_TYPE_DATETIME),
+            "0000-00-00 00:00:00"
+        );
+    }
+
+    #[test]
+    fn test_decode_old_time() {
+        // 12:34:56 and -838:59:59 as signed 24-bit hhhmmss
+        assert_eq!(decode_str(&[0x81, 0xE2, 0x40], DD_TYPE_TIME), "12:34:56");
+        assert_eq!(decode_str(&[0x00, 0x0A, 0x59], DD_TYPE_TIME), "-838:59:59");
+    }
+
+    #[test]
+    fn test_decode_old_timestamp() {
+        assert_eq!(
+            decode_str(&[0x49, 0x96, 0x02, 0xD2], DD_TYPE_TIMESTAMP),
+            "2009-02-13 23:31:30"
+        );
+    }
+
+    #[test]
+    fn test_decode_old_decimal() {
+        assert_eq!(decode_str(b"  -012.50", DD_TYPE_DECIMAL), "-12.50");
+        assert_eq!(decode_str(b"    0.25", DD_TYPE_DECIMAL), "0.25");
+        assert_eq!(decode_str(b"000.25", DD_TYPE_DECIMAL), "0.25");
+        assert_eq!(decode_str(b"  +1234", DD_TYPE_DECIMAL), "1234");
+        assert_eq!(decode_str(b"00000", DD_TYPE_DECIMAL), "0");
+        assert!(matches!(
+            decode_field(b"12a", &make_col(DD_TYPE_DECIMAL, false)),
+            FieldValue::Hex(_)
+        ));
+    }
+
+    #[test]
+    fn test_decode_var_string() {
+        assert_eq!(decode_str(b"abc", DD_TYPE_VAR_STRING), "abc");
+    }
+
+    #[test]
+    fn test_legacy_storage_sizes() {
+        let col = |dd_type, char_length| crate::innodb::schema::DdColumn {
+            dd_type,
+            char_length,
+            numeric_precision: 5,
+            numeric_scale: 2,
+            ..Default::default()
+        };
+        assert_eq!(compute_storage_size(&col(DD_TYPE_DATETIME, 0)), (8, false));
+        assert_eq!(compute_storage_size(&col(DD_TYPE_TIMESTAMP, 0)), (4, false));
+        assert_eq!(compute_storage_size(&col(DD_TYPE_TIME, 0)), (3, false));
+        assert_eq!(compute_storage_size(&col(DD_TYPE_DECIMAL, 7)), (7, false));
+        assert_eq!(compute_storage_size(&col(DD_TYPE_DECIMAL, 0)), (7, false));
+        assert_eq!(
+            compute_storage_size(&col(DD_TYPE_VAR_STRING, 30)),
+            (0, true)
+        );
+    }
+
     // -----------------------------------------------------------------------
     // DECIMAL decoder tests
     // -----------------------------------------------------------------------
@@ -1370,7 +2042,12 @@ mod tests {
             is_unsigned: false,
             fixed_len: 0,
             is_variable: true,
+            max_len: 65535,
+            version_added: 0,
+            version_dropped: 0,
+            instant_default: None,
             charset_max_bytes: 4,
+            collation_id: 0,
             datetime_precision: 0,
             is_system_column: false,
             elements: Vec::new(),
@@ -1399,6 +2076,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_decode_geometry_wkb() {
+        let col = make_col(DD_TYPE_GEOMETRY, false);
+        let mut data = vec![0, 0, 0, 0, 1];
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&2.0f64.to_le_bytes());
+        data.extend_from_slice(&3.0f64.to_le_bytes());
+        match decode_field(&data, &col) {
+            FieldValue::Geometry(g) => assert_eq!(g.geometry.to_wkt(), "POINT(2 3)"),
+            other => panic!("Expected Geometry, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_decode_geometry_hex() {
         let col = make_col(DD_TYPE_GEOMETRY, false);
@@ -329,7 +329,7 @@ mod tests {
 
     #[test]
     fn test_summarize_fts_files() {
-        let files = vec![
+        let files = [
             "FTS_0000000000000437_CONFIG.ibd",
             "FTS_0000000000000437_00000000000004a2_INDEX_0.ibd",
             "FTS_0000000000000437_00000000000004a2_INDEX_1.ibd",
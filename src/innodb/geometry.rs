@@ -0,0 +1,490 @@
+//! Spatial (GEOMETRY) column decoding.
+//!
+//! MySQL stores GEOMETRY, POINT, LINESTRING, POLYGON, and the MULTI* and
+//! GEOMETRYCOLLECTION types in the same internal format: a 4-byte
+//! little-endian SRID followed by the OGC Well-Known Binary (WKB)
+//! representation of the value. This module parses that format into a
+//! [`Geometry`] tree and renders it as WKT (for CSV and SQL output) or
+//! GeoJSON (for JSON output).
+//!
+//! Coordinates are reported in storage order (`x`, `y`); for geographic
+//! spatial reference systems MySQL stores longitude as `x` and latitude as
+//! `y`.
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use serde::{Serialize, Serializer};
+
+use crate::innodb::rtree::MinimumBoundingRectangle;
+
+/// WKB geometry type codes.
+const WKB_POINT: u32 = 1;
+const WKB_LINESTRING: u32 = 2;
+const WKB_POLYGON: u32 = 3;
+const WKB_MULTIPOINT: u32 = 4;
+const WKB_MULTILINESTRING: u32 = 5;
+const WKB_MULTIPOLYGON: u32 = 6;
+const WKB_GEOMETRYCOLLECTION: u32 = 7;
+
+/// Maximum nesting depth accepted for GEOMETRYCOLLECTION values.
+const MAX_COLLECTION_DEPTH: usize = 32;
+
+/// A single coordinate pair.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A parsed WKB geometry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    /// A single point.
+    Point(Point),
+    /// An ordered sequence of points.
+    LineString(Vec<Point>),
+    /// An exterior ring followed by zero or more interior rings.
+    Polygon(Vec<Vec<Point>>),
+    /// A set of points.
+    MultiPoint(Vec<Point>),
+    /// A set of line strings.
+    MultiLineString(Vec<Vec<Point>>),
+    /// A set of polygons.
+    MultiPolygon(Vec<Vec<Vec<Point>>>),
+    /// A heterogeneous collection of geometries.
+    GeometryCollection(Vec<Geometry>),
+}
+
+/// A decoded GEOMETRY column value: the SRID plus the geometry.
+///
+/// Serializes as a GeoJSON geometry object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpatialValue {
+    /// Spatial reference system identifier (0 = Cartesian plane).
+    pub srid: u32,
+    /// The geometry itself.
+    pub geometry: Geometry,
+}
+
+impl Serialize for SpatialValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.geometry.to_geojson().serialize(serializer)
+    }
+}
+
+/// Decode a GEOMETRY column value (SRID + WKB).
+///
+/// Returns `None` if the data is truncated, uses an unknown WKB type, or has
+/// trailing bytes after the geometry.
+///
+/// # Examples
+///
+/// ```
+/// use idb::innodb::geometry::decode_geometry;
+///
+/// let mut data = vec![0xE6, 0x10, 0, 0]; // SRID 4326
+/// data.push(1); // little-endian WKB
+/// data.extend_from_slice(&1u32.to_le_bytes()); // POINT
+/// data.extend_from_slice(&1.5f64.to_le_bytes());
+/// data.extend_from_slice(&(-2.0f64).to_le_bytes());
+///
+/// let value = decode_geometry(&data).unwrap();
+/// assert_eq!(value.srid, 4326);
+/// assert_eq!(value.geometry.to_wkt(), "POINT(1.5 -2)");
+/// ```
+pub fn decode_geometry(data: &[u8]) -> Option<SpatialValue> {
+    if data.len() < 4 {
+        return None;
+    }
+    let srid = LittleEndian::read_u32(data);
+    let mut reader = WkbReader {
+        data: &data[4..],
+        pos: 0,
+    };
+    let geometry = reader.read_geometry(0)?;
+    if reader.pos != reader.data.len() {
+        return None;
+    }
+    Some(SpatialValue { srid, geometry })
+}
+
+impl Geometry {
+    /// Render the geometry as Well-Known Text, in the format produced by
+    /// MySQL's `ST_AsText()` (e.g. `POLYGON((0 0,1 0,1 1,0 0))`).
+    pub fn to_wkt(&self) -> String {
+        match self {
+            Geometry::Point(p) => format!("POINT({})", wkt_point(p)),
+            Geometry::LineString(pts) => format!("LINESTRING({})", wkt_points(pts)),
+            Geometry::Polygon(rings) => format!("POLYGON({})", wkt_rings(rings)),
+            Geometry::MultiPoint(pts) => {
+                let parts: Vec<String> =
+                    pts.iter().map(|p| format!("({})", wkt_point(p))).collect();
+                format!("MULTIPOINT({})", parts.join(","))
+            }
+            Geometry::MultiLineString(lines) => {
+                format!("MULTILINESTRING({})", wkt_rings(lines))
+            }
+            Geometry::MultiPolygon(polys) => {
+                let parts: Vec<String> = polys
+                    .iter()
+                    .map(|rings| format!("({})", wkt_rings(rings)))
+                    .collect();
+                format!("MULTIPOLYGON({})", parts.join(","))
+            }
+            Geometry::GeometryCollection(geoms) => {
+                if geoms.is_empty() {
+                    return "GEOMETRYCOLLECTION EMPTY".to_string();
+                }
+                let parts: Vec<String> = geoms.iter().map(|g| g.to_wkt()).collect();
+                format!("GEOMETRYCOLLECTION({})", parts.join(","))
+            }
+        }
+    }
+
+    /// Render the geometry as a GeoJSON geometry object (RFC 7946).
+    pub fn to_geojson(&self) -> serde_json::Value {
+        match self {
+            Geometry::Point(p) => geojson("Point", json_point(p)),
+            Geometry::LineString(pts) => geojson("LineString", json_points(pts)),
+            Geometry::Polygon(rings) => geojson("Polygon", json_rings(rings)),
+            Geometry::MultiPoint(pts) => geojson("MultiPoint", json_points(pts)),
+            Geometry::MultiLineString(lines) => geojson("MultiLineString", json_rings(lines)),
+            Geometry::MultiPolygon(polys) => geojson(
+                "MultiPolygon",
+                serde_json::Value::Array(polys.iter().map(|r| json_rings(r)).collect()),
+            ),
+            Geometry::GeometryCollection(geoms) => serde_json::json!({
+                "type": "GeometryCollection",
+                "geometries": geoms.iter().map(|g| g.to_geojson()).collect::<Vec<_>>(),
+            }),
+        }
+    }
+
+    /// Compute the minimum bounding rectangle of all coordinates.
+    ///
+    /// Returns `None` for an empty geometry collection.
+    pub fn mbr(&self) -> Option<MinimumBoundingRectangle> {
+        let mut points = Vec::new();
+        self.collect_points(&mut points);
+        let first = points.first()?;
+        let mut mbr = MinimumBoundingRectangle {
+            min_x: first.x,
+            min_y: first.y,
+            max_x: first.x,
+            max_y: first.y,
+        };
+        for p in &points[1..] {
+            mbr.min_x = mbr.min_x.min(p.x);
+            mbr.min_y = mbr.min_y.min(p.y);
+            mbr.max_x = mbr.max_x.max(p.x);
+            mbr.max_y = mbr.max_y.max(p.y);
+        }
+        Some(mbr)
+    }
+
+    fn collect_points(&self, out: &mut Vec<Point>) {
+        match self {
+            Geometry::Point(p) => out.push(*p),
+            Geometry::LineString(pts) | Geometry::MultiPoint(pts) => out.extend_from_slice(pts),
+            Geometry::Polygon(rings) | Geometry::MultiLineString(rings) => {
+                rings.iter().for_each(|r| out.extend_from_slice(r))
+            }
+            Geometry::MultiPolygon(polys) => polys
+                .iter()
+                .flatten()
+                .for_each(|r| out.extend_from_slice(r)),
+            Geometry::GeometryCollection(geoms) => geoms.iter().for_each(|g| g.collect_points(out)),
+        }
+    }
+}
+
+/// Cursor over a WKB byte stream. Each nested geometry carries its own
+/// byte-order marker.
+struct WkbReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl WkbReader<'_> {
+    fn take(&mut self, n: usize) -> Option<&[u8]> {
+        let end = self.pos.checked_add(n)?;
+        let slice = self.data.get(self.pos..end)?;
+        self.pos = end;
+        Some(slice)
+    }
+
+    fn read_u32(&mut self, little_endian: bool) -> Option<u32> {
+        let b = self.take(4)?;
+        Some(if little_endian {
+            LittleEndian::read_u32(b)
+        } else {
+            BigEndian::read_u32(b)
+        })
+    }
+
+    fn read_f64(&mut self, little_endian: bool) -> Option<f64> {
+        let b = self.take(8)?;
+        Some(if little_endian {
+            LittleEndian::read_f64(b)
+        } else {
+            BigEndian::read_f64(b)
+        })
+    }
+
+    /// Read an element count, rejecting counts that cannot fit in the
+    /// remaining bytes (each element needs at least `min_size` bytes).
+    fn read_count(&mut self, little_endian: bool, min_size: usize) -> Option<usize> {
+        let n = self.read_u32(little_endian)? as usize;
+        let remaining = self.data.len() - self.pos;
+        if n.checked_mul(min_size)? > remaining {
+            return None;
+        }
+        Some(n)
+    }
+
+    fn read_point(&mut self, little_endian: bool) -> Option<Point> {
+        let x = self.read_f64(little_endian)?;
+        let y = self.read_f64(little_endian)?;
+        Some(Point { x, y })
+    }
+
+    fn read_points(&mut self, little_endian: bool) -> Option<Vec<Point>> {
+        let n = self.read_count(little_endian, 16)?;
+        (0..n).map(|_| self.read_point(little_endian)).collect()
+    }
+
+    fn read_rings(&mut self, little_endian: bool) -> Option<Vec<Vec<Point>>> {
+        let n = self.read_count(little_endian, 4)?;
+        (0..n).map(|_| self.read_points(little_endian)).collect()
+    }
+
+    /// Read a byte-order marker and geometry type.
+    fn read_header(&mut self) -> Option<(bool, u32)> {
+        let little_endian = match self.take(1)?[0] {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        let wkb_type = self.read_u32(little_endian)?;
+        Some((little_endian, wkb_type))
+    }
+
+    /// Read a nested geometry of the given WKB type (MULTI* members).
+    fn read_member<T>(
+        &mut self,
+        expected: u32,
+        read: impl Fn(&mut Self, bool) -> Option<T>,
+    ) -> Option<T> {
+        let (le, wkb_type) = self.read_header()?;
+        if wkb_type != expected {
+            return None;
+        }
+        read(self, le)
+    }
+
+    fn read_geometry(&mut self, depth: usize) -> Option<Geometry> {
+        if depth > MAX_COLLECTION_DEPTH {
+            return None;
+        }
+        let (le, wkb_type) = self.read_header()?;
+        match wkb_type {
+            WKB_POINT => self.read_point(le).map(Geometry::Point),
+            WKB_LINESTRING => self.read_points(le).map(Geometry::LineString),
+            WKB_POLYGON => self.read_rings(le).map(Geometry::Polygon),
+            WKB_MULTIPOINT => {
+                let n = self.read_count(le, 21)?;
+                (0..n)
+                    .map(|_| self.read_member(WKB_POINT, Self::read_point))
+                    .collect::<Option<_>>()
+                    .map(Geometry::MultiPoint)
+            }
+            WKB_MULTILINESTRING => {
+                let n = self.read_count(le, 9)?;
+                (0..n)
+                    .map(|_| self.read_member(WKB_LINESTRING, Self::read_points))
+                    .collect::<Option<_>>()
+                    .map(Geometry::MultiLineString)
+            }
+            WKB_MULTIPOLYGON => {
+                let n = self.read_count(le, 9)?;
+                (0..n)
+                    .map(|_| self.read_member(WKB_POLYGON, Self::read_rings))
+                    .collect::<Option<_>>()
+                    .map(Geometry::MultiPolygon)
+            }
+            WKB_GEOMETRYCOLLECTION => {
+                let n = self.read_count(le, 5)?;
+                (0..n)
+                    .map(|_| self.read_geometry(depth + 1))
+                    .collect::<Option<_>>()
+                    .map(Geometry::GeometryCollection)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn wkt_point(p: &Point) -> String {
+    format!("{} {}", p.x, p.y)
+}
+
+fn wkt_points(pts: &[Point]) -> String {
+    pts.iter().map(wkt_point).collect::<Vec<_>>().join(",")
+}
+
+fn wkt_rings(rings: &[Vec<Point>]) -> String {
+    rings
+        .iter()
+        .map(|r| format!("({})", wkt_points(r)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn geojson(kind: &str, coordinates: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({ "type": kind, "coordinates": coordinates })
+}
+
+fn json_point(p: &Point) -> serde_json::Value {
+    serde_json::json!([p.x, p.y])
+}
+
+fn json_points(pts: &[Point]) -> serde_json::Value {
+    serde_json::Value::Array(pts.iter().map(json_point).collect())
+}
+
+fn json_rings(rings: &[Vec<Point>]) -> serde_json::Value {
+    serde_json::Value::Array(rings.iter().map(|r| json_points(r)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wkb_header(buf: &mut Vec<u8>, wkb_type: u32) {
+        buf.push(1);
+        buf.extend_from_slice(&wkb_type.to_le_bytes());
+    }
+
+    fn wkb_points(buf: &mut Vec<u8>, pts: &[(f64, f64)]) {
+        buf.extend_from_slice(&(pts.len() as u32).to_le_bytes());
+        for &(x, y) in pts {
+            buf.extend_from_slice(&x.to_le_bytes());
+            buf.extend_from_slice(&y.to_le_bytes());
+        }
+    }
+
+    fn with_srid(srid: u32, wkb: &[u8]) -> Vec<u8> {
+        let mut data = srid.to_le_bytes().to_vec();
+        data.extend_from_slice(wkb);
+        data
+    }
+
+    #[test]
+    fn test_decode_big_endian_point() {
+        let mut wkb = vec![0u8];
+        wkb.extend_from_slice(&1u32.to_be_bytes());
+        wkb.extend_from_slice(&3.0f64.to_be_bytes());
+        wkb.extend_from_slice(&4.25f64.to_be_bytes());
+        let value = decode_geometry(&with_srid(0, &wkb)).unwrap();
+        assert_eq!(value.geometry, Geometry::Point(Point { x: 3.0, y: 4.25 }));
+        assert_eq!(
+            serde_json::to_value(&value).unwrap(),
+            serde_json::json!({"type": "Point", "coordinates": [3.0, 4.25]})
+        );
+    }
+
+    #[test]
+    fn test_decode_polygon() {
+        let mut wkb = Vec::new();
+        wkb_header(&mut wkb, WKB_POLYGON);
+        wkb.extend_from_slice(&1u32.to_le_bytes());
+        wkb_points(
+            &mut wkb,
+            &[(0.0, 0.0), (10.0, 0.0), (10.0, 5.0), (0.0, 0.0)],
+        );
+        let value = decode_geometry(&with_srid(0, &wkb)).unwrap();
+        assert_eq!(value.geometry.to_wkt(), "POLYGON((0 0,10 0,10 5,0 0))");
+        assert_eq!(
+            value.geometry.to_geojson(),
+            serde_json::json!({
+                "type": "Polygon",
+                "coordinates": [[[0.0, 0.0], [10.0, 0.0], [10.0, 5.0], [0.0, 0.0]]]
+            })
+        );
+        let mbr = value.geometry.mbr().unwrap();
+        assert_eq!(
+            (mbr.min_x, mbr.min_y, mbr.max_x, mbr.max_y),
+            (0.0, 0.0, 10.0, 5.0)
+        );
+    }
+
+    #[test]
+    fn test_decode_multi_and_collection() {
+        let mut wkb = Vec::new();
+        wkb_header(&mut wkb, WKB_GEOMETRYCOLLECTION);
+        wkb.extend_from_slice(&2u32.to_le_bytes());
+        // MULTIPOINT((1 2),(3 4))
+        wkb_header(&mut wkb, WKB_MULTIPOINT);
+        wkb.extend_from_slice(&2u32.to_le_bytes());
+        for (x, y) in [(1.0f64, 2.0f64), (3.0, 4.0)] {
+            wkb_header(&mut wkb, WKB_POINT);
+            wkb.extend_from_slice(&x.to_le_bytes());
+            wkb.extend_from_slice(&y.to_le_bytes());
+        }
+        // MULTILINESTRING((0 0,-1 -1))
+        wkb_header(&mut wkb, WKB_MULTILINESTRING);
+        wkb.extend_from_slice(&1u32.to_le_bytes());
+        wkb_header(&mut wkb, WKB_LINESTRING);
+        wkb_points(&mut wkb, &[(0.0, 0.0), (-1.0, -1.0)]);
+
+        let value = decode_geometry(&with_srid(0, &wkb)).unwrap();
+        assert_eq!(
+            value.geometry.to_wkt(),
+            "GEOMETRYCOLLECTION(MULTIPOINT((1 2),(3 4)),MULTILINESTRING((0 0,-1 -1)))"
+        );
+        let json = value.geometry.to_geojson();
+        assert_eq!(json["type"], "GeometryCollection");
+        assert_eq!(json["geometries"][0]["type"], "MultiPoint");
+        assert_eq!(json["geometries"][1]["coordinates"][0][1][0], -1.0);
+        let mbr = value.geometry.mbr().unwrap();
+        assert_eq!(
+            (mbr.min_x, mbr.min_y, mbr.max_x, mbr.max_y),
+            (-1.0, -1.0, 3.0, 4.0)
+        );
+    }
+
+    #[test]
+    fn test_empty_collection() {
+        let mut wkb = Vec::new();
+        wkb_header(&mut wkb, WKB_GEOMETRYCOLLECTION);
+        wkb.extend_from_slice(&0u32.to_le_bytes());
+        let value = decode_geometry(&with_srid(0, &wkb)).unwrap();
+        assert_eq!(value.geometry.to_wkt(), "GEOMETRYCOLLECTION EMPTY");
+        assert!(value.geometry.mbr().is_none());
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed() {
+        assert!(decode_geometry(&[0, 0]).is_none());
+        // Unknown type
+        let mut wkb = Vec::new();
+        wkb_header(&mut wkb, 99);
+        assert!(decode_geometry(&with_srid(0, &wkb)).is_none());
+        // Truncated point
+        let mut wkb = Vec::new();
+        wkb_header(&mut wkb, WKB_POINT);
+        wkb.extend_from_slice(&1.0f64.to_le_bytes());
+        assert!(decode_geometry(&with_srid(0, &wkb)).is_none());
+        // Implausible point count
+        let mut wkb = Vec::new();
+        wkb_header(&mut wkb, WKB_LINESTRING);
+        wkb.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(decode_geometry(&with_srid(0, &wkb)).is_none());
+        // MULTIPOINT member of the wrong type
+        let mut wkb = Vec::new();
+        wkb_header(&mut wkb, WKB_MULTIPOINT);
+        wkb.extend_from_slice(&1u32.to_le_bytes());
+        wkb_header(&mut wkb, WKB_LINESTRING);
+        wkb_points(&mut wkb, &[(0.0, 0.0)]);
+        assert!(decode_geometry(&with_srid(0, &wkb)).is_none());
+    }
+}
@@ -0,0 +1,571 @@
+//! Change buffer (insert buffer) bitmap and tree parsing.
+//!
+//! When a secondary index leaf page is not in the buffer pool, InnoDB can
+//! record the change in the *change buffer* instead of reading the page.
+//! The buffered operations live in a B+Tree in the system tablespace (root
+//! at page 4 of `ibdata1`) and are merged into the target page the next
+//! time it is read. Until then the `.ibd` file alone is out of date.
+//!
+//! Every tablespace tracks this with IBUF_BITMAP pages (page 1 of each
+//! XDES interval). The bitmap holds 4 bits per page: a 2-bit free space
+//! category, a flag that changes are buffered for the page, and a flag that
+//! the page belongs to the change buffer tree itself.
+//!
+//! Change buffer records use the redundant row format. Their first four
+//! fields are the target space ID, a marker byte, the target page number,
+//! and a metadata field holding an operation counter and type (insert,
+//! delete-mark, or purge); the buffered record's own fields follow.
+
+use std::collections::{BTreeMap, HashSet};
+
+use byteorder::{BigEndian, ByteOrder};
+use serde::Serialize;
+
+use crate::innodb::constants::*;
+use crate::innodb::index::IndexHeader;
+use crate::innodb::page::FilHeader;
+use crate::innodb::page_types::PageType;
+use crate::innodb::record::{walk_records, RedundantRecordHeader};
+use crate::innodb::tablespace::Tablespace;
+use crate::IdbError;
+
+/// Change buffer bitmap bits for one page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct IbufBitmapEntry {
+    /// Page the bits describe.
+    pub page_no: u32,
+    /// Free space category (0-3): at least 0, 1/32, 2/32, or 4/32 of the
+    /// page is free.
+    pub free_space: u8,
+    /// Changes for this page are waiting in the change buffer.
+    pub buffered: bool,
+    /// The page belongs to the change buffer tree.
+    pub ibuf: bool,
+}
+
+impl IbufBitmapEntry {
+    /// Minimum free bytes on the page implied by the free space category.
+    pub fn min_free_bytes(&self, page_size: u32) -> u32 {
+        let unit = page_size / 32;
+        match self.free_space {
+            3 => 4 * unit,
+            n => u32::from(n) * unit,
+        }
+    }
+}
+
+/// Read the bitmap bits of `page_no` from the IBUF_BITMAP page covering it.
+///
+/// The bitmap page at `p` (where `p % page_size == 1`) describes pages
+/// `p - 1` through `p - 2 + page_size`. Returns `None` if the page is
+/// outside the bitmap.
+pub fn ibuf_bitmap_entry(
+    bitmap_page: &[u8],
+    page_size: u32,
+    page_no: u32,
+) -> Option<IbufBitmapEntry> {
+    let bit = (page_no % page_size) as usize * IBUF_BITS_PER_PAGE;
+    let byte = *bitmap_page.get(IBUF_BITMAP + bit / 8)?;
+    let bits = (byte >> (bit % 8)) & 0x0F;
+    Some(IbufBitmapEntry {
+        page_no,
+        free_space: (bits >> IBUF_BITMAP_FREE) & 0x3,
+        buffered: bits & (1 << IBUF_BITMAP_BUFFERED) != 0,
+        ibuf: bits & (1 << IBUF_BITMAP_IBUF) != 0,
+    })
+}
+
+/// Decode every entry of an IBUF_BITMAP page.
+///
+/// `bitmap_page_no` is the page's own number; entries are returned for the
+/// `page_size` pages it covers, starting at the preceding XDES page.
+///
+/// # Examples
+///
+/// ```
+/// use idb::innodb::constants::IBUF_BITMAP;
+/// use idb::innodb::ibuf::parse_ibuf_bitmap;
+///
+/// let mut page = vec![0u8; 16384];
+/// // Page 3: free space category 1, changes buffered (bits 12-15)
+/// page[IBUF_BITMAP + 1] = 0b0101 << 4;
+/// let entries = parse_ibuf_bitmap(&page, 16384, 1);
+/// assert_eq!(entries.len(), 16384);
+/// assert_eq!(entries[3].free_space, 1);
+/// assert!(entries[3].buffered);
+/// assert!(!entries[2].buffered);
+/// ```
+pub fn parse_ibuf_bitmap(
+    bitmap_page: &[u8],
+    page_size: u32,
+    bitmap_page_no: u32,
+) -> Vec<IbufBitmapEntry> {
+    let first = bitmap_page_no.saturating_sub(1);
+    (first..first.saturating_add(page_size))
+        .map_while(|p| ibuf_bitmap_entry(bitmap_page, page_size, p))
+        .collect()
+}
+
+/// Find the pages of a tablespace whose bitmap bits say changes are buffered.
+///
+/// Reads the IBUF_BITMAP page of each XDES interval through `read_page`;
+/// intervals whose bitmap page is missing or of another type are skipped.
+pub fn scan_buffered_pages(
+    page_size: u32,
+    page_count: u64,
+    mut read_page: impl FnMut(u32) -> Option<Vec<u8>>,
+) -> Vec<u32> {
+    let mut buffered = Vec::new();
+    let mut bitmap_page_no = 1u64;
+    while bitmap_page_no < page_count {
+        let page = read_page(bitmap_page_no as u32);
+        let is_bitmap = page
+            .as_deref()
+            .and_then(FilHeader::parse)
+            .is_some_and(|h| h.page_type == PageType::IbufBitmap);
+        if let (Some(page), true) = (page, is_bitmap) {
+            buffered.extend(
+                parse_ibuf_bitmap(&page, page_size, bitmap_page_no as u32)
+                    .into_iter()
+                    .filter(|e| e.buffered && u64::from(e.page_no) < page_count)
+                    .map(|e| e.page_no),
+            );
+        }
+        bitmap_page_no += u64::from(page_size);
+    }
+    buffered
+}
+
+/// Find the pages of a tablespace with changes waiting in the change buffer.
+///
+/// See [`scan_buffered_pages`]; unreadable pages are skipped.
+pub fn read_buffered_pages(ts: &mut Tablespace) -> Vec<u32> {
+    let page_size = ts.page_size();
+    let page_count = ts.page_count();
+    scan_buffered_pages(page_size, page_count, |p| ts.read_page(p as u64).ok())
+}
+
+/// A buffered operation type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IbufOp {
+    /// Insert a record (`IBUF_OP_INSERT`).
+    Insert,
+    /// Delete-mark a record (`IBUF_OP_DELETE_MARK`).
+    DeleteMark,
+    /// Remove a delete-marked record (`IBUF_OP_DELETE`, issued by purge).
+    Purge,
+}
+
+impl IbufOp {
+    /// Convert the stored operation code.
+    pub fn from_u8(op: u8) -> Option<Self> {
+        match op {
+            0 => Some(IbufOp::Insert),
+            1 => Some(IbufOp::DeleteMark),
+            2 => Some(IbufOp::Purge),
+            _ => None,
+        }
+    }
+
+    /// Display name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            IbufOp::Insert => "insert",
+            IbufOp::DeleteMark => "delete-mark",
+            IbufOp::Purge => "purge",
+        }
+    }
+}
+
+/// A decoded change buffer record.
+#[derive(Debug, Clone, Serialize)]
+pub struct IbufRecord {
+    /// Change buffer leaf page holding the record.
+    pub ibuf_page: u32,
+    /// Tablespace the change applies to.
+    pub space_id: u32,
+    /// Page the change applies to.
+    pub page_no: u32,
+    /// Buffered operation.
+    pub op: IbufOp,
+    /// Operation counter, ordering changes to the same page (absent in the
+    /// pre-5.5 format).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counter: Option<u16>,
+    /// Whether the buffered record is in compact format.
+    pub compact: bool,
+    /// Number of fields in the buffered record.
+    pub n_fields: usize,
+}
+
+/// Start offsets and lengths of the fields of a redundant-format record.
+fn old_record_fields(page: &[u8], origin: usize) -> Option<Vec<(usize, usize)>> {
+    let extra_start = origin.checked_sub(REC_N_OLD_EXTRA_BYTES)?;
+    let hdr = RedundantRecordHeader::parse(page.get(extra_start..)?)?;
+    let entry_size = if hdr.one_byte_offs { 1 } else { 2 };
+    let mut fields = Vec::with_capacity(hdr.n_fields as usize);
+    let mut start = 0usize;
+    for i in 0..hdr.n_fields as usize {
+        let at = extra_start.checked_sub((i + 1) * entry_size)?;
+        let end = if hdr.one_byte_offs {
+            (*page.get(at)? & 0x7F) as usize
+        } else {
+            (BigEndian::read_u16(page.get(at..at + 2)?) & 0x3FFF) as usize
+        };
+        fields.push((origin + start, end.checked_sub(start)?));
+        start = end;
+    }
+    Some(fields)
+}
+
+/// Decode a change buffer record at `origin`.
+///
+/// Returns `None` if the fields are malformed or the operation is unknown.
+pub fn decode_ibuf_record(page: &[u8], ibuf_page: u32, origin: usize) -> Option<IbufRecord> {
+    let fields = old_record_fields(page, origin)?;
+    let read_u32 = |(start, len): (usize, usize)| {
+        (len == 4).then(|| page.get(start..start + 4).map(BigEndian::read_u32))?
+    };
+
+    // Pre-4.1 records have no space ID or marker: page number, then fields
+    if fields.len() < IBUF_REC_FIELD_USER || fields[1].1 != 1 {
+        return Some(IbufRecord {
+            ibuf_page,
+            space_id: IBUF_SPACE_ID,
+            page_no: read_u32(*fields.first()?)?,
+            op: IbufOp::Insert,
+            counter: None,
+            compact: false,
+            n_fields: fields.len().checked_sub(2)?,
+        });
+    }
+
+    let (meta_start, meta_len) = fields[3];
+    let meta = page.get(meta_start..meta_start + meta_len)?;
+    let (op, counter, compact) = match meta_len % DATA_NEW_ORDER_NULL_TYPE_BUF_SIZE {
+        0 => (IbufOp::Insert, None, false),
+        IBUF_REC_INFO_SIZE => (
+            IbufOp::from_u8(meta[IBUF_REC_OFFSET_TYPE])?,
+            Some(BigEndian::read_u16(&meta[IBUF_REC_OFFSET_COUNTER..])),
+            meta[IBUF_REC_OFFSET_FLAGS] & IBUF_REC_COMPACT != 0,
+        ),
+        _ => return None,
+    };
+    Some(IbufRecord {
+        ibuf_page,
+        space_id: read_u32(fields[0])?,
+        page_no: read_u32(fields[2])?,
+        op,
+        counter,
+        compact,
+        n_fields: fields.len() - IBUF_REC_FIELD_USER,
+    })
+}
+
+/// Counts of buffered operations.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IbufChangeCounts {
+    /// Buffered inserts.
+    pub inserts: u64,
+    /// Buffered delete-marks.
+    pub delete_marks: u64,
+    /// Buffered purges.
+    pub purges: u64,
+}
+
+impl IbufChangeCounts {
+    fn add(&mut self, op: IbufOp) {
+        match op {
+            IbufOp::Insert => self.inserts += 1,
+            IbufOp::DeleteMark => self.delete_marks += 1,
+            IbufOp::Purge => self.purges += 1,
+        }
+    }
+
+    /// Total buffered operations.
+    pub fn total(&self) -> u64 {
+        self.inserts + self.delete_marks + self.purges
+    }
+}
+
+/// Buffered changes for one page.
+#[derive(Debug, Clone, Serialize)]
+pub struct IbufPageChanges {
+    /// Tablespace of the page.
+    pub space_id: u32,
+    /// Page number.
+    pub page_no: u32,
+    /// Operation counts.
+    #[serde(flatten)]
+    pub counts: IbufChangeCounts,
+}
+
+/// Buffered changes for one tablespace.
+#[derive(Debug, Clone, Serialize)]
+pub struct IbufSpaceChanges {
+    /// Tablespace ID.
+    pub space_id: u32,
+    /// Number of distinct pages with buffered changes.
+    pub pages: u64,
+    /// Operation counts.
+    #[serde(flatten)]
+    pub counts: IbufChangeCounts,
+}
+
+/// The change buffer tree of a system tablespace.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IbufTree {
+    /// Root page number.
+    pub root_page: u32,
+    /// Number of B+Tree levels (1 if the root is a leaf).
+    pub levels: u16,
+    /// Leaf pages visited, in order.
+    pub leaf_pages: Vec<u32>,
+    /// Buffered records that are not delete-marked.
+    pub records: Vec<IbufRecord>,
+    /// Problems found while walking the tree.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+impl IbufTree {
+    /// Buffered changes grouped by target page.
+    pub fn changes_by_page(&self) -> Vec<IbufPageChanges> {
+        let mut map: BTreeMap<(u32, u32), IbufChangeCounts> = BTreeMap::new();
+        for r in &self.records {
+            map.entry((r.space_id, r.page_no)).or_default().add(r.op);
+        }
+        map.into_iter()
+            .map(|((space_id, page_no), counts)| IbufPageChanges {
+                space_id,
+                page_no,
+                counts,
+            })
+            .collect()
+    }
+
+    /// Buffered changes grouped by target tablespace.
+    pub fn changes_by_space(&self) -> Vec<IbufSpaceChanges> {
+        let mut map: BTreeMap<u32, IbufSpaceChanges> = BTreeMap::new();
+        for p in self.changes_by_page() {
+            let entry = map.entry(p.space_id).or_insert_with(|| IbufSpaceChanges {
+                space_id: p.space_id,
+                pages: 0,
+                counts: IbufChangeCounts::default(),
+            });
+            entry.pages += 1;
+            entry.counts.inserts += p.counts.inserts;
+            entry.counts.delete_marks += p.counts.delete_marks;
+            entry.counts.purges += p.counts.purges;
+        }
+        map.into_values().collect()
+    }
+}
+
+/// Walk the change buffer tree of a system tablespace.
+///
+/// Descends from the root (page 4) along the leftmost node pointers and
+/// then follows the leaf page chain, decoding every record that is not
+/// delete-marked. `read_page` returns the contents of a page by number.
+pub fn walk_ibuf_tree(mut read_page: impl FnMut(u32) -> Option<Vec<u8>>) -> IbufTree {
+    let mut tree = IbufTree {
+        root_page: FSP_IBUF_TREE_ROOT_PAGE_NO,
+        ..Default::default()
+    };
+
+    let mut page_no = FSP_IBUF_TREE_ROOT_PAGE_NO;
+    let mut visited = HashSet::new();
+    let mut page = loop {
+        let data = match read_page(page_no) {
+            Some(d) => d,
+            None => {
+                tree.errors.push(format!("cannot read page {}", page_no));
+                return tree;
+            }
+        };
+        let idx = match IndexHeader::parse(&data) {
+            Some(idx) if idx.index_id == DICT_IBUF_ID_MIN => idx,
+            _ => {
+                tree.errors.push(format!(
+                    "page {} is not a change buffer index page",
+                    page_no
+                ));
+                return tree;
+            }
+        };
+        if page_no == FSP_IBUF_TREE_ROOT_PAGE_NO {
+            tree.levels = idx.level + 1;
+        }
+        if idx.level == 0 {
+            break data;
+        }
+        if !visited.insert(page_no) {
+            tree.errors.push(format!("cycle at page {}", page_no));
+            return tree;
+        }
+        let child = walk_records(&data)
+            .first()
+            .and_then(|r| old_record_fields(&data, r.offset))
+            .and_then(|f| f.last().copied())
+            .filter(|&(_, len)| len == REC_NODE_PTR_SIZE)
+            .map(|(start, _)| BigEndian::read_u32(&data[start..]));
+        page_no = match child {
+            Some(c) => c,
+            None => {
+                tree.errors
+                    .push(format!("no node pointer on non-leaf page {}", page_no));
+                return tree;
+            }
+        };
+    };
+
+    let mut visited = HashSet::new();
+    loop {
+        if !visited.insert(page_no) {
+            tree.errors
+                .push(format!("leaf chain cycle at page {}", page_no));
+            break;
+        }
+        tree.leaf_pages.push(page_no);
+        for rec in walk_records(&page) {
+            if rec.header.delete_mark() {
+                continue;
+            }
+            match decode_ibuf_record(&page, page_no, rec.offset) {
+                Some(r) => tree.records.push(r),
+                None => tree.errors.push(format!(
+                    "undecodable record at page {} offset {}",
+                    page_no, rec.offset
+                )),
+            }
+        }
+        let next = match FilHeader::parse(&page) {
+            Some(h) if h.next_page != FIL_NULL => h.next_page,
+            _ => break,
+        };
+        page = match read_page(next) {
+            Some(d) => d,
+            None => {
+                tree.errors.push(format!("cannot read page {}", next));
+                break;
+            }
+        };
+        page_no = next;
+    }
+    tree
+}
+
+/// Walk the change buffer tree of a system tablespace (`ibdata1`).
+///
+/// See [`walk_ibuf_tree`].
+pub fn read_ibuf_tree(ts: &mut Tablespace) -> Result<IbufTree, IdbError> {
+    if ts.page_count() <= u64::from(FSP_IBUF_TREE_ROOT_PAGE_NO) {
+        return Err(IdbError::Argument(
+            "Tablespace is too small to hold a change buffer tree".to_string(),
+        ));
+    }
+    Ok(walk_ibuf_tree(|p| ts.read_page(p as u64).ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a redundant-format change buffer leaf page holding
+    /// `(space_id, page_no, op, counter)` records.
+    fn build_ibuf_leaf(records: &[(u32, u32, u8, u16)], next: u32) -> Vec<u8> {
+        let mut page = vec![0u8; 16384];
+        BigEndian::write_u16(&mut page[FIL_PAGE_TYPE..], 17855);
+        BigEndian::write_u32(&mut page[FIL_PAGE_NEXT..], next);
+        BigEndian::write_u64(&mut page[FIL_PAGE_DATA + PAGE_INDEX_ID..], DICT_IBUF_ID_MIN);
+        BigEndian::write_u16(&mut page[FIL_PAGE_DATA + PAGE_N_HEAP..], 2);
+        BigEndian::write_u16(&mut page[PAGE_OLD_SUPREMUM - 5..], 1 << 3);
+
+        let mut prev = PAGE_OLD_INFIMUM;
+        let mut heap = PAGE_OLD_SUPREMUM + 9;
+        for (i, &(space, page_no, op, counter)) in records.iter().enumerate() {
+            // space(4) marker(1) page(4) meta(4 + 1 * 6) user field(4)
+            let mut data = Vec::new();
+            data.extend_from_slice(&space.to_be_bytes());
+            data.push(0);
+            data.extend_from_slice(&page_no.to_be_bytes());
+            data.extend_from_slice(&counter.to_be_bytes());
+            data.push(op);
+            data.push(IBUF_REC_COMPACT);
+            data.extend_from_slice(&[0u8; 6]);
+            data.extend_from_slice(&[0x80, 0, 0, 7]);
+            let ends = [4u8, 5, 9, 19, 23];
+
+            let origin = heap + ends.len() + REC_N_OLD_EXTRA_BYTES;
+            for (j, end) in ends.iter().enumerate() {
+                page[origin - REC_N_OLD_EXTRA_BYTES - (j + 1)] = *end;
+            }
+            BigEndian::write_u16(&mut page[origin - 5..], ((i as u16) + 2) << 3);
+            page[origin - 3] = ((ends.len() as u8) << 1) | 1;
+            page[origin..origin + data.len()].copy_from_slice(&data);
+            BigEndian::write_u16(&mut page[prev - 2..], origin as u16);
+            prev = origin;
+            heap = origin + data.len();
+        }
+        BigEndian::write_u16(&mut page[prev - 2..], PAGE_OLD_SUPREMUM as u16);
+        page
+    }
+
+    #[test]
+    fn test_walk_ibuf_tree() {
+        let root = build_ibuf_leaf(&[(12, 3, 0, 0), (12, 3, 1, 1), (12, 9, 2, 0)], 5);
+        let leaf2 = build_ibuf_leaf(&[(40, 4, 0, 0)], FIL_NULL);
+        let tree = walk_ibuf_tree(|p| match p {
+            4 => Some(root.clone()),
+            5 => Some(leaf2.clone()),
+            _ => None,
+        });
+        assert!(tree.errors.is_empty(), "{:?}", tree.errors);
+        assert_eq!(tree.levels, 1);
+        assert_eq!(tree.leaf_pages, vec![4, 5]);
+        assert_eq!(tree.records.len(), 4);
+        assert_eq!(tree.records[1].op, IbufOp::DeleteMark);
+        assert_eq!(tree.records[1].counter, Some(1));
+        assert!(tree.records[0].compact);
+        assert_eq!(tree.records[0].n_fields, 1);
+
+        let by_space = tree.changes_by_space();
+        assert_eq!(by_space.len(), 2);
+        assert_eq!(by_space[0].space_id, 12);
+        assert_eq!(by_space[0].pages, 2);
+        assert_eq!(by_space[0].counts.inserts, 1);
+        assert_eq!(by_space[0].counts.delete_marks, 1);
+        assert_eq!(by_space[0].counts.purges, 1);
+        assert_eq!(by_space[1].counts.total(), 1);
+    }
+
+    #[test]
+    fn test_walk_ibuf_tree_rejects_other_index() {
+        let mut root = build_ibuf_leaf(&[], FIL_NULL);
+        BigEndian::write_u64(&mut root[FIL_PAGE_DATA + PAGE_INDEX_ID..], 42);
+        let tree = walk_ibuf_tree(|_| Some(root.clone()));
+        assert_eq!(tree.errors.len(), 1);
+        assert!(tree.records.is_empty());
+    }
+
+    #[test]
+    fn test_scan_buffered_pages() {
+        let mut bitmap = vec![0u8; 16384];
+        BigEndian::write_u16(&mut bitmap[FIL_PAGE_TYPE..], 5);
+        // Page 5 buffered (bit 22), page 6 in the ibuf tree (bit 27)
+        bitmap[IBUF_BITMAP + 2] = 1 << 6;
+        bitmap[IBUF_BITMAP + 3] = 1 << 3;
+        let found = scan_buffered_pages(16384, 8, |p| (p == 1).then(|| bitmap.clone()));
+        assert_eq!(found, vec![5]);
+        let e = ibuf_bitmap_entry(&bitmap, 16384, 6).unwrap();
+        assert!(e.ibuf && !e.buffered);
+        let free = IbufBitmapEntry {
+            page_no: 0,
+            free_space: 3,
+            buffered: false,
+            ibuf: false,
+        };
+        assert_eq!(free.min_free_bytes(16384), 2048);
+    }
+}
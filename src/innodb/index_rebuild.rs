@@ -0,0 +1,1044 @@
+//! Offline secondary index rebuild.
+//!
+//! Regenerates the B+Tree of a secondary index from the clustered index,
+//! much like `ALTER TABLE ... FORCE` does on a running server. Every live
+//! clustered row yields one index entry: the key columns (cut to their
+//! prefix length for column prefix key parts) followed by the primary key
+//! columns not already in the key, copied byte for byte from the clustered
+//! record. The entries are sorted by the index key and packed into
+//! compact-format leaf pages, and node-pointer levels are built on top
+//! until a single page remains, which becomes the index's existing root
+//! page.
+//!
+//! The new pages reuse the index's own file segments: leaf pages come from
+//! the leaf segment and non-leaf pages from the non-leaf segment (see
+//! [`inode`](crate::innodb::inode)). The extent descriptors, inodes, and
+//! the root page number recorded in the data dictionary therefore stay
+//! valid, and the tablespace can be imported with `ALTER TABLE ... IMPORT
+//! TABLESPACE`. Segment pages the new tree does not need are reset to
+//! empty FIL_PAGE_TYPE_ALLOCATED pages but stay owned by their segment.
+//!
+//! [`read_rebuild_spec`] collects the index definition from SDI,
+//! [`index_segment_pages`] finds the pages of its segments, and
+//! [`rebuild_secondary_index`] builds the pages. Checksums are left to the
+//! caller (see `write::fix_page_checksum`).
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use byteorder::{BigEndian, ByteOrder};
+use serde::Serialize;
+
+use crate::innodb::btree::decode_node_pointers;
+use crate::innodb::charset::charset_width;
+use crate::innodb::constants::*;
+use crate::innodb::export::{extract_index_layouts, read_record_fields, IndexLayout, RecordField};
+use crate::innodb::field_decode::{self, ColumnStorageInfo, FieldValue};
+use crate::innodb::index::{FsegHeader, IndexHeader};
+use crate::innodb::inode;
+use crate::innodb::lob::{self, ExternFieldRef};
+use crate::innodb::page::FilHeader;
+use crate::innodb::page_dir::{cmp_pad_space, compare_field_bytes, is_byte_ordered};
+use crate::innodb::record::{walk_records, RecordType};
+use crate::innodb::schema::{
+    charset_from_collation, collation_name, parse_se_private_data, SdiEnvelope,
+};
+use crate::innodb::sdi;
+use crate::innodb::tablespace::Tablespace;
+use crate::innodb::xdes;
+use crate::IdbError;
+
+/// Maximum B+Tree depth followed when walking the clustered index.
+const MAX_BTREE_DEPTH: u16 = 10;
+
+/// Records owned by each page directory slot other than the supremum's.
+const RECORDS_PER_SLOT: usize = 4;
+
+/// Extra bytes of the infimum record (n_owned=1, heap_no=0, status=2),
+/// excluding the next-record offset.
+const INFIMUM_EXTRA: [u8; 3] = [0x01, 0x00, 0x02];
+
+/// Extra bytes of the supremum record (heap_no=1, status=3) after its
+/// n_owned byte, excluding the next-record offset.
+const SUPREMUM_EXTRA: [u8; 2] = [0x00, 0x0b];
+
+/// Definition of a secondary index to rebuild.
+#[derive(Debug, Clone)]
+pub struct IndexRebuildSpec {
+    /// Layout of the clustered index records that are the rebuild's source.
+    pub clustered: IndexLayout,
+    /// Root page of the clustered index.
+    pub clustered_root: u32,
+    /// Layout of the secondary index to rebuild.
+    pub index: IndexLayout,
+    /// Root page of the secondary index.
+    pub index_root: u32,
+    /// Per field of `index`, the prefix length in bytes for a column prefix
+    /// key part, or `None` if the field stores the whole column.
+    pub prefix_lengths: Vec<Option<usize>>,
+    /// Per field of `index`, the stored value for rows written before the
+    /// column was added by instant ADD COLUMN (`None` for NULL).
+    pub instant_defaults: Vec<Option<Vec<u8>>>,
+}
+
+/// Pages of the file segments of a secondary index.
+#[derive(Debug, Clone, Default)]
+pub struct SegmentPages {
+    /// Root page (always in the non-leaf segment).
+    pub root: u32,
+    /// Pages of the leaf segment, in ascending order.
+    pub leaf: Vec<u32>,
+    /// Pages of the non-leaf segment other than the root, in ascending order.
+    pub non_leaf: Vec<u32>,
+}
+
+/// Result of rebuilding a secondary index.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexRebuildResult {
+    /// Index name from the data dictionary.
+    pub index_name: String,
+    /// InnoDB index ID.
+    pub index_id: u64,
+    /// Root page number.
+    pub root_page: u32,
+    /// Live clustered rows read, one index entry each.
+    pub entries: u64,
+    /// Number of B+Tree levels (1 when the root is a leaf).
+    pub levels: u16,
+    /// Leaf pages written (including the root if it is a leaf).
+    pub leaf_pages: u64,
+    /// Non-leaf pages written (including the root if it is not a leaf).
+    pub non_leaf_pages: u64,
+    /// Segment pages reset to FIL_PAGE_TYPE_ALLOCATED.
+    pub freed_pages: u64,
+    /// Rebuilt and freed pages as `(page_number, contents)`, without
+    /// checksums.
+    #[serde(skip)]
+    pub pages: Vec<(u32, Vec<u8>)>,
+}
+
+/// Stored values of one index entry: one per field, `None` for NULL.
+type Entry = Vec<Option<Vec<u8>>>;
+
+/// An encoded record ready to be placed on a page.
+struct EncodedRecord {
+    /// Null bitmap and variable-length array, in page order.
+    extra: Vec<u8>,
+    /// Field data (and the child page number of a node pointer).
+    data: Vec<u8>,
+    /// Whether the min_rec info bit is set.
+    min_rec: bool,
+}
+
+impl EncodedRecord {
+    fn size(&self) -> usize {
+        self.extra.len() + REC_N_NEW_EXTRA_BYTES + self.data.len()
+    }
+}
+
+/// Read the definition of the secondary index `index_name` from SDI.
+///
+/// # Errors
+///
+/// Returns `IdbError::Argument` if the table has no such B-tree secondary
+/// index, and `IdbError::Parse` if SDI metadata, the clustered index, or a
+/// root page number is unavailable.
+pub fn read_rebuild_spec(
+    ts: &mut Tablespace,
+    index_name: &str,
+) -> Result<IndexRebuildSpec, IdbError> {
+    let no_sdi = || IdbError::Parse("Cannot read table definition from SDI".to_string());
+    let sdi_pages = sdi::find_sdi_pages(ts)?;
+    let records = sdi::extract_sdi_from_pages(ts, &sdi_pages)?;
+    let rec = records
+        .iter()
+        .find(|r| r.sdi_type == 1)
+        .ok_or_else(no_sdi)?;
+    let envelope: SdiEnvelope = serde_json::from_str(&rec.data).map_err(|_| no_sdi())?;
+    let table = &envelope.dd_object;
+    let layouts = extract_index_layouts(ts).ok_or_else(no_sdi)?;
+
+    let root_of = |name: &str| {
+        table
+            .indexes
+            .iter()
+            .find(|i| i.name == name)
+            .and_then(|i| i.se_private_data.as_deref())
+            .and_then(|spd| parse_se_private_data(spd).get("root")?.parse::<u32>().ok())
+            .ok_or_else(|| IdbError::Parse(format!("No root page for index {} in SDI", name)))
+    };
+
+    let clustered = layouts
+        .iter()
+        .find(|l| l.is_clustered)
+        .cloned()
+        .ok_or_else(|| IdbError::Parse("No clustered index in SDI".to_string()))?;
+    let index = match layouts.iter().find(|l| l.name == index_name) {
+        Some(l) if l.is_clustered => {
+            return Err(IdbError::Argument(format!(
+                "{} is the clustered index; only secondary indexes can be rebuilt",
+                index_name
+            )))
+        }
+        Some(l) => l.clone(),
+        None => {
+            return Err(IdbError::Argument(format!(
+                "No B-tree secondary index named {}",
+                index_name
+            )))
+        }
+    };
+    let dd_index = table
+        .indexes
+        .iter()
+        .find(|i| i.name == index_name)
+        .ok_or_else(no_sdi)?;
+
+    let instant_defaults = index
+        .columns
+        .iter()
+        .map(|c| {
+            table
+                .columns
+                .iter()
+                .find(|dc| dc.name == c.name)
+                .and_then(|dc| dc.se_private_data.as_deref())
+                .and_then(|spd| {
+                    let hex = parse_se_private_data(spd).remove("default")?;
+                    (0..hex.len() / 2)
+                        .map(|i| u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok())
+                        .collect()
+                })
+        })
+        .collect();
+
+    Ok(IndexRebuildSpec {
+        clustered_root: root_of(&clustered.name)?,
+        index_root: root_of(index_name)?,
+        prefix_lengths: field_decode::index_prefix_lengths(table, dd_index),
+        instant_defaults,
+        clustered,
+        index,
+    })
+}
+
+/// Find the pages owned by the leaf and non-leaf segments of the index
+/// whose root page is `root_page`.
+///
+/// The segments are located through the FSEG headers on the root page and
+/// their pages are read from the INODE and extent descriptor pages.
+///
+/// # Errors
+///
+/// Returns `IdbError::Parse` if the root page's FSEG headers do not point
+/// at segment inodes, or if the root page is not in its non-leaf segment.
+pub fn index_segment_pages(ts: &mut Tablespace, root_page: u32) -> Result<SegmentPages, IdbError> {
+    let root = ts.read_page(u64::from(root_page))?;
+    let alloc = xdes::read_allocation_map(ts)?;
+    let owners = inode::read_ownership_map(ts, &alloc);
+
+    let segment_of = |fseg: Option<FsegHeader>, name: &str| {
+        fseg.and_then(|f| {
+            owners
+                .segments
+                .iter()
+                .find(|s| s.inode_page == f.page_no && s.inode_offset == f.offset)
+        })
+        .map(|s| owners.segment_pages(s.seg_id))
+        .ok_or_else(|| {
+            IdbError::Parse(format!(
+                "Root page {} has no valid {} segment header",
+                root_page, name
+            ))
+        })
+    };
+    let leaf = segment_of(FsegHeader::parse_leaf(&root), "leaf")?;
+    let mut non_leaf = segment_of(FsegHeader::parse_internal(&root), "non-leaf")?;
+
+    let before = non_leaf.len();
+    non_leaf.retain(|&p| p != root_page);
+    if non_leaf.len() == before {
+        return Err(IdbError::Parse(format!(
+            "Root page {} is not owned by its non-leaf segment",
+            root_page
+        )));
+    }
+    Ok(SegmentPages {
+        root: root_page,
+        leaf,
+        non_leaf,
+    })
+}
+
+/// Rebuild a secondary index from the clustered index.
+///
+/// Reads every live (not delete-marked) record on the clustered index leaf
+/// level, found by descending from `spec.clustered_root` to the leftmost
+/// leaf and following the next-page links. Each record yields one entry
+/// of `spec.index`; externally stored values are read from their overflow
+/// pages as far as the index needs them, and multi-byte character set
+/// prefixes are cut at a character boundary (UTF-8 only; other character
+/// sets are cut at the byte length).
+///
+/// Entries are sorted by the index key, honouring `DESC` key parts. Fields
+/// whose stored bytes sort in collation order are compared as bytes (see
+/// [`page_dir`](crate::innodb::page_dir)); other strings are compared
+/// lowercased with trailing spaces ignored, which matches
+/// case-insensitive collations for unaccented text, and FLOAT/DOUBLE
+/// values numerically.
+///
+/// Pages are filled completely, leaf pages are taken from `pages.leaf` and
+/// non-leaf pages from `pages.non_leaf` in ascending order, and the top
+/// level is written to `pages.root`, keeping its FSEG headers. Every page
+/// carries the highest LSN found on the clustered index, and leaf pages
+/// the highest DB_TRX_ID as `PAGE_MAX_TRX_ID`.
+///
+/// # Errors
+///
+/// Returns `IdbError::Parse` for ROW_FORMAT=COMPRESSED or REDUNDANT tables,
+/// index fields that the clustered index does not store (virtual columns
+/// and functional key parts), string keys whose collation order cannot be
+/// reproduced (non-ASCII values in a case-insensitive collation, or an
+/// unsupported collation), an unreadable clustered index, or when the new
+/// tree needs more pages than a segment owns.
+pub fn rebuild_secondary_index(
+    ts: &mut Tablespace,
+    spec: &IndexRebuildSpec,
+    pages: &SegmentPages,
+) -> Result<IndexRebuildResult, IdbError> {
+    if ts.zip_size().is_some() {
+        return Err(IdbError::Parse(
+            "ROW_FORMAT=COMPRESSED tables cannot be rebuilt".to_string(),
+        ));
+    }
+    let page_size = ts.page_size() as usize;
+    let columns = &spec.index.columns;
+
+    // Position of each index field in the clustered record
+    let sources: Vec<usize> = columns
+        .iter()
+        .map(|c| {
+            spec.clustered
+                .columns
+                .iter()
+                .position(|cc| cc.name == c.name)
+                .ok_or_else(|| {
+                    IdbError::Parse(format!(
+                        "Column {} of index {} is not stored in the clustered index",
+                        c.name, spec.index.name
+                    ))
+                })
+        })
+        .collect::<Result<_, _>>()?;
+    let trx_id_pos = spec
+        .clustered
+        .columns
+        .iter()
+        .position(|c| c.name == "DB_TRX_ID");
+
+    let old_root = ts.read_page(u64::from(pages.root))?;
+    let space_id = BigEndian::read_u32(&old_root[FIL_PAGE_SPACE_ID..]);
+    let mut lsn = FilHeader::parse(&old_root).map_or(0, |h| h.lsn);
+    let mut max_trx_id = 0u64;
+
+    let mut entries: Vec<Entry> = Vec::new();
+    for leaf in clustered_leaf_pages(ts, &spec.clustered, spec.clustered_root)? {
+        if let Some(h) = FilHeader::parse(&leaf) {
+            lsn = lsn.max(h.lsn);
+        }
+        for rec in walk_records(&leaf) {
+            if rec.header.delete_mark() || rec.header.rec_type() != RecordType::Ordinary {
+                continue;
+            }
+            let fields = read_record_fields(&leaf, rec.offset, &spec.clustered.columns, true)
+                .ok_or_else(|| {
+                    IdbError::Parse(format!(
+                        "Cannot read clustered record at offset {}",
+                        rec.offset
+                    ))
+                })?;
+            if let Some(pos) = trx_id_pos {
+                if let (start, RecordField::Stored { len: 6, .. }) = fields[pos] {
+                    let mut id = [0u8; 8];
+                    id[2..].copy_from_slice(&leaf[start..start + 6]);
+                    max_trx_id = max_trx_id.max(u64::from_be_bytes(id));
+                }
+            }
+
+            let mut entry = Entry::with_capacity(columns.len());
+            for (i, col) in columns.iter().enumerate() {
+                let value = match fields[sources[i]] {
+                    (_, RecordField::Null) => None,
+                    (_, RecordField::Absent) => spec.instant_defaults[i].clone(),
+                    (start, RecordField::Stored { len, external }) => {
+                        let local = leaf.get(start..start + len).ok_or_else(|| {
+                            IdbError::Parse(format!("Field {} overruns the page", col.name))
+                        })?;
+                        let prefix = spec.prefix_lengths.get(i).copied().flatten();
+                        let value = if external {
+                            read_external(ts, local, prefix.unwrap_or(col.max_len))?
+                        } else {
+                            local.to_vec()
+                        };
+                        Some(match prefix {
+                            Some(n) => column_prefix(value, n, col),
+                            None => value,
+                        })
+                    }
+                };
+                entry.push(value);
+            }
+            entries.push(entry);
+        }
+    }
+
+    check_key_order(&entries, &spec.index)?;
+    entries.sort_by(|a, b| compare_entries(a, b, &spec.index));
+
+    let mut records: Vec<(Entry, EncodedRecord)> = entries
+        .into_iter()
+        .map(|e| {
+            let rec = encode_record(&e, columns, None, false);
+            (e, rec)
+        })
+        .collect();
+    let n_entries = records.len() as u64;
+
+    let mut built: Vec<(u32, Vec<u8>)> = Vec::new();
+    let mut leaf_pool = pages.leaf.iter().copied();
+    let mut non_leaf_pool = pages.non_leaf.iter().copied();
+    let mut level = 0u16;
+    let (mut leaf_pages, mut non_leaf_pages) = (0u64, 0u64);
+
+    loop {
+        let groups = split_pages(&records, page_size);
+        let header = PageParams {
+            space_id,
+            index_id: spec.index.index_id,
+            level,
+            lsn,
+            max_trx_id: if level == 0 { max_trx_id } else { 0 },
+            page_size,
+        };
+        if groups.len() <= 1 {
+            let recs: Vec<&EncodedRecord> = records.iter().map(|(_, r)| r).collect();
+            let mut page = build_index_page(pages.root, FIL_NULL, FIL_NULL, &recs, &header);
+            // Keep the FSEG headers of the index's segments
+            let fseg = FIL_PAGE_DATA + PAGE_BTR_SEG_LEAF;
+            page[fseg..fseg + 2 * FSEG_HEADER_SIZE]
+                .copy_from_slice(&old_root[fseg..fseg + 2 * FSEG_HEADER_SIZE]);
+            built.push((pages.root, page));
+            if level == 0 {
+                leaf_pages += 1;
+            } else {
+                non_leaf_pages += 1;
+            }
+            break;
+        }
+
+        let pool: &mut dyn Iterator<Item = u32> = if level == 0 {
+            &mut leaf_pool
+        } else {
+            &mut non_leaf_pool
+        };
+        let page_nos: Vec<u32> = pool.take(groups.len()).collect();
+        if page_nos.len() < groups.len() {
+            let (segment, owned) = if level == 0 {
+                ("leaf", pages.leaf.len())
+            } else {
+                ("non-leaf", pages.non_leaf.len() + 1)
+            };
+            return Err(IdbError::Parse(format!(
+                "Index {} needs more pages than its {} segment owns ({}) at level {}",
+                spec.index.name, segment, owned, level
+            )));
+        }
+
+        let mut node_ptrs = Vec::with_capacity(groups.len());
+        for (g, range) in groups.iter().enumerate() {
+            let prev = if g == 0 { FIL_NULL } else { page_nos[g - 1] };
+            let next = page_nos.get(g + 1).copied().unwrap_or(FIL_NULL);
+            let recs: Vec<&EncodedRecord> = records[range.clone()].iter().map(|(_, r)| r).collect();
+            built.push((
+                page_nos[g],
+                build_index_page(page_nos[g], prev, next, &recs, &header),
+            ));
+
+            let first = records[range.start].0.clone();
+            let rec = encode_record(&first, columns, Some(page_nos[g]), g == 0);
+            node_ptrs.push((first, rec));
+        }
+        if level == 0 {
+            leaf_pages += groups.len() as u64;
+        } else {
+            non_leaf_pages += groups.len() as u64;
+        }
+        records = node_ptrs;
+        level += 1;
+    }
+
+    // Segment pages left over become empty allocated pages
+    let mut freed_pages = 0u64;
+    for page_no in leaf_pool.chain(non_leaf_pool) {
+        let mut page = vec![0u8; page_size];
+        BigEndian::write_u32(&mut page[FIL_PAGE_OFFSET..], page_no);
+        BigEndian::write_u64(&mut page[FIL_PAGE_LSN..], lsn);
+        BigEndian::write_u32(&mut page[FIL_PAGE_SPACE_ID..], space_id);
+        built.push((page_no, page));
+        freed_pages += 1;
+    }
+    built.sort_by_key(|(p, _)| *p);
+
+    Ok(IndexRebuildResult {
+        index_name: spec.index.name.clone(),
+        index_id: spec.index.index_id,
+        root_page: pages.root,
+        entries: n_entries,
+        levels: level + 1,
+        leaf_pages,
+        non_leaf_pages,
+        freed_pages,
+        pages: built,
+    })
+}
+
+/// Read the leaf pages of the clustered index in key order.
+fn clustered_leaf_pages(
+    ts: &mut Tablespace,
+    clustered: &IndexLayout,
+    root: u32,
+) -> Result<Vec<Vec<u8>>, IdbError> {
+    let read_index_page = |ts: &mut Tablespace, page_no: u32| {
+        let page = ts.read_page(u64::from(page_no))?;
+        match IndexHeader::parse(&page) {
+            Some(h) if h.index_id == clustered.index_id => {
+                if !h.is_compact() {
+                    return Err(IdbError::Parse(
+                        "ROW_FORMAT=REDUNDANT tables cannot be rebuilt".to_string(),
+                    ));
+                }
+                Ok((h, page))
+            }
+            _ => Err(IdbError::Parse(format!(
+                "Page {} is not a page of the clustered index",
+                page_no
+            ))),
+        }
+    };
+
+    // Descend along the leftmost node pointers
+    let mut page_no = root;
+    let mut depth = 0u16;
+    let mut page = loop {
+        let (hdr, page) = read_index_page(ts, page_no)?;
+        if hdr.is_leaf() {
+            break page;
+        }
+        depth += 1;
+        if depth > MAX_BTREE_DEPTH {
+            return Err(IdbError::Parse(
+                "Clustered index exceeds maximum B+Tree depth".to_string(),
+            ));
+        }
+        page_no = decode_node_pointers(&page, clustered.node_pointer_columns())
+            .first()
+            .map(|np| np.child_page_no)
+            .ok_or_else(|| {
+                IdbError::Parse(format!("Non-leaf page {} has no node pointers", page_no))
+            })?;
+    };
+
+    let mut leaves = Vec::new();
+    let mut visited = HashSet::new();
+    loop {
+        visited.insert(page_no);
+        let next = BigEndian::read_u32(&page[FIL_PAGE_NEXT..]);
+        leaves.push(page);
+        if next == FIL_NULL {
+            break;
+        }
+        if !visited.insert(next) {
+            return Err(IdbError::Parse(format!(
+                "Clustered index leaf chain loops back to page {}",
+                next
+            )));
+        }
+        page_no = next;
+        page = read_index_page(ts, page_no)?.1;
+    }
+    Ok(leaves)
+}
+
+/// Reassemble up to `max_len` bytes of an externally stored field from its
+/// local part (prefix plus 20-byte reference).
+fn read_external(ts: &mut Tablespace, local: &[u8], max_len: usize) -> Result<Vec<u8>, IdbError> {
+    let prefix_len = local
+        .len()
+        .checked_sub(BTR_EXTERN_FIELD_REF_SIZE)
+        .ok_or_else(|| IdbError::Parse("Truncated external field reference".to_string()))?;
+    let field_ref = ExternFieldRef::parse(&local[prefix_len..])
+        .ok_or_else(|| IdbError::Parse("Invalid external field reference".to_string()))?;
+    let mut value = local[..prefix_len].to_vec();
+    if value.len() < max_len {
+        value.extend(lob::read_external_field(
+            ts,
+            &field_ref,
+            max_len - value.len(),
+        )?);
+    }
+    Ok(value)
+}
+
+/// Cut a column value to a prefix key part of `prefix_len` bytes.
+///
+/// Like `dtype_get_at_most_n_mbchars()`, a prefix in a variable-width
+/// character set keeps `prefix_len / mbmaxlen` characters.
+fn column_prefix(mut value: Vec<u8>, prefix_len: usize, col: &ColumnStorageInfo) -> Vec<u8> {
+    let charset = charset_from_collation(col.collation_id).unwrap_or("binary");
+    let (min, max) = charset_width(charset);
+    let is_string = matches!(col.dd_type, 16 | 24..=29);
+    let len = if is_string && min != max && charset.starts_with("utf8") {
+        let n_chars = prefix_len / max;
+        value
+            .iter()
+            .enumerate()
+            .filter(|(_, &b)| b & 0xC0 != 0x80)
+            .nth(n_chars)
+            .map_or(value.len(), |(pos, _)| pos)
+    } else {
+        prefix_len
+    };
+    value.truncate(len);
+    value
+}
+
+/// Compare two index entries by key, honouring `DESC` key parts.
+fn compare_entries(a: &Entry, b: &Entry, index: &IndexLayout) -> Ordering {
+    for (i, col) in index.columns.iter().enumerate() {
+        let ord = match (&a[i], &b[i]) {
+            // NULL sorts before any value
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(x), Some(y)) => {
+                compare_field_bytes(col, x, y).unwrap_or_else(|| compare_decoded(col, x, y))
+            }
+        };
+        let ord = if index.descending.get(i).copied().unwrap_or(false) {
+            ord.reverse()
+        } else {
+            ord
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    Ordering::Equal
+}
+
+/// How [`compare_decoded`] orders a string key field whose stored bytes do
+/// not sort in collation order. Only ASCII values can be ordered.
+#[derive(Clone, Copy)]
+enum AsciiOrder {
+    /// Legacy case-insensitive collations (`_general_ci`, `latin1_swedish_ci`):
+    /// letters compare as uppercase and trailing spaces are ignored.
+    Folded,
+    /// UCA case-insensitive collations, which agree with the folded byte
+    /// order only for letters, digits, and spaces; `_0900_` collations are
+    /// `NO PAD`.
+    Uca { pad_space: bool },
+}
+
+impl AsciiOrder {
+    fn of(col: &ColumnStorageInfo) -> Option<Self> {
+        let name = collation_name(col.collation_id)?;
+        let ascii_based = ["ascii_", "latin1_", "utf8_", "utf8mb3_", "utf8mb4_"]
+            .iter()
+            .any(|cs| name.starts_with(cs));
+        if !ascii_based {
+            None
+        } else if name.ends_with("_general_ci") || name == "latin1_swedish_ci" {
+            Some(AsciiOrder::Folded)
+        } else if name.ends_with("_0900_ai_ci") || name.ends_with("_0900_as_ci") {
+            Some(AsciiOrder::Uca { pad_space: false })
+        } else if name.ends_with("_unicode_ci") || name.ends_with("_unicode_520_ci") {
+            Some(AsciiOrder::Uca { pad_space: true })
+        } else {
+            None
+        }
+    }
+
+    /// Whether a stored value can be ordered.
+    fn supports(self, value: &[u8]) -> bool {
+        match self {
+            AsciiOrder::Folded => value.is_ascii(),
+            AsciiOrder::Uca { .. } => value
+                .iter()
+                .all(|&c| c.is_ascii_alphanumeric() || c == b' '),
+        }
+    }
+}
+
+/// Whether a key field holds CHAR, VARCHAR, or TEXT data.
+fn is_string_field(col: &ColumnStorageInfo) -> bool {
+    matches!(col.dd_type, 16 | 24..=29)
+}
+
+/// Check that every string key value whose collation order cannot be
+/// decided from its bytes can be ordered by [`compare_decoded`].
+///
+/// # Errors
+///
+/// Returns `IdbError::Parse` naming the column and collation otherwise.
+fn check_key_order(entries: &[Entry], index: &IndexLayout) -> Result<(), IdbError> {
+    for (i, col) in index.columns.iter().enumerate() {
+        if !is_string_field(col) || is_byte_ordered(col) {
+            continue;
+        }
+        let order = AsciiOrder::of(col);
+        let supported = entries
+            .iter()
+            .filter_map(|e| e[i].as_deref())
+            .all(|v| order.is_some_and(|o| o.supports(v)));
+        if !supported {
+            return Err(IdbError::Parse(format!(
+                "Cannot sort index {}: the {} order of column {} cannot be reproduced for its values",
+                index.name,
+                collation_name(col.collation_id).unwrap_or("unknown collation"),
+                col.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Compare two stored values whose bytes do not sort in collation order.
+///
+/// String values must have passed [`check_key_order`].
+fn compare_decoded(col: &ColumnStorageInfo, a: &[u8], b: &[u8]) -> Ordering {
+    if is_string_field(col) {
+        let (a, b) = (a.to_ascii_uppercase(), b.to_ascii_uppercase());
+        return match AsciiOrder::of(col) {
+            Some(AsciiOrder::Uca { pad_space: false }) => a.cmp(&b),
+            _ => cmp_pad_space(&a, &b),
+        };
+    }
+    match (
+        field_decode::decode_field(a, col),
+        field_decode::decode_field(b, col),
+    ) {
+        (FieldValue::Float(x), FieldValue::Float(y)) => x.total_cmp(&y),
+        (FieldValue::Double(x), FieldValue::Double(y)) => x.total_cmp(&y),
+        _ => a.cmp(b),
+    }
+}
+
+/// Encode an index entry as a compact-format record.
+///
+/// Follows `rec_convert_dtuple_to_rec_comp()`: the null bitmap and the
+/// lengths of the non-NULL variable-length fields are stored backwards
+/// from the 5-byte extra header, using 2 bytes for values of "big"
+/// columns longer than 127 bytes. A node pointer appends the 4-byte child
+/// page number to the data.
+fn encode_record(
+    entry: &Entry,
+    columns: &[ColumnStorageInfo],
+    child: Option<u32>,
+    min_rec: bool,
+) -> EncodedRecord {
+    let n_nullable = columns.iter().filter(|c| c.is_nullable).count();
+    let mut nulls = vec![0u8; n_nullable.div_ceil(8)];
+    let mut lens = Vec::new();
+    let mut data = Vec::new();
+    let mut null_idx = 0;
+
+    for (col, value) in columns.iter().zip(entry) {
+        if col.is_nullable {
+            if value.is_none() {
+                nulls[null_idx / 8] |= 1 << (null_idx % 8);
+            }
+            null_idx += 1;
+        }
+        let value = match value {
+            Some(v) => v,
+            None => continue,
+        };
+        if col.is_variable {
+            let len = value.len();
+            if col.is_big() && len > 127 {
+                lens.push(0x80 | (len >> 8) as u8);
+            }
+            lens.push(len as u8);
+        }
+        data.extend_from_slice(value);
+    }
+    if let Some(page_no) = child {
+        data.extend_from_slice(&page_no.to_be_bytes());
+    }
+
+    // Read backwards: null bitmap bytes first, then lengths
+    let mut extra: Vec<u8> = nulls;
+    extra.extend(lens);
+    extra.reverse();
+    EncodedRecord {
+        extra,
+        data,
+        min_rec,
+    }
+}
+
+/// Space a page of `n` records of `bytes` total size needs, including the
+/// system records, page directory, and FIL trailer.
+fn page_space(n: usize, bytes: usize) -> usize {
+    PAGE_NEW_SUPREMUM_END
+        + bytes
+        + (2 + n / RECORDS_PER_SLOT) * PAGE_DIR_SLOT_SIZE
+        + SIZE_FIL_TRAILER
+}
+
+/// Split a level's records into consecutive full pages.
+fn split_pages(
+    records: &[(Entry, EncodedRecord)],
+    page_size: usize,
+) -> Vec<std::ops::Range<usize>> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    let mut bytes = 0;
+    for (i, (_, rec)) in records.iter().enumerate() {
+        if i > start && page_space(i - start + 1, bytes + rec.size()) > page_size {
+            groups.push(start..i);
+            start = i;
+            bytes = 0;
+        }
+        bytes += rec.size();
+    }
+    if start < records.len() {
+        groups.push(start..records.len());
+    }
+    groups
+}
+
+/// Header fields shared by the pages of one level.
+struct PageParams {
+    space_id: u32,
+    index_id: u64,
+    level: u16,
+    lsn: u64,
+    max_trx_id: u64,
+    page_size: usize,
+}
+
+/// Lay out a compact-format INDEX page holding `records` in order.
+///
+/// Every fourth record owns a directory slot; the supremum owns the
+/// remaining one to three records (or only itself).
+fn build_index_page(
+    page_no: u32,
+    prev: u32,
+    next: u32,
+    records: &[&EncodedRecord],
+    params: &PageParams,
+) -> Vec<u8> {
+    let ps = params.page_size;
+    let mut page = vec![0u8; ps];
+    BigEndian::write_u32(&mut page[FIL_PAGE_OFFSET..], page_no);
+    BigEndian::write_u32(&mut page[FIL_PAGE_PREV..], prev);
+    BigEndian::write_u32(&mut page[FIL_PAGE_NEXT..], next);
+    BigEndian::write_u64(&mut page[FIL_PAGE_LSN..], params.lsn);
+    BigEndian::write_u16(&mut page[FIL_PAGE_TYPE..], FIL_PAGE_INDEX);
+    BigEndian::write_u32(&mut page[FIL_PAGE_SPACE_ID..], params.space_id);
+
+    // Infimum and supremum
+    page[PAGE_NEW_INFIMUM - REC_N_NEW_EXTRA_BYTES..PAGE_NEW_INFIMUM - 2]
+        .copy_from_slice(&INFIMUM_EXTRA);
+    page[PAGE_NEW_INFIMUM..PAGE_NEW_INFIMUM + 8].copy_from_slice(b"infimum\0");
+    page[PAGE_NEW_SUPREMUM - 4..PAGE_NEW_SUPREMUM - 2].copy_from_slice(&SUPREMUM_EXTRA);
+    page[PAGE_NEW_SUPREMUM..PAGE_NEW_SUPREMUM + 8].copy_from_slice(b"supremum");
+
+    let status = u16::from(if params.level == 0 {
+        REC_STATUS_ORDINARY
+    } else {
+        REC_STATUS_NODE_PTR
+    });
+    let mut origins = vec![PAGE_NEW_INFIMUM];
+    let mut pos = PAGE_NEW_SUPREMUM_END;
+    for (i, rec) in records.iter().enumerate() {
+        page[pos..pos + rec.extra.len()].copy_from_slice(&rec.extra);
+        let origin = pos + rec.extra.len() + REC_N_NEW_EXTRA_BYTES;
+        if rec.min_rec {
+            page[origin - REC_N_NEW_EXTRA_BYTES] = REC_INFO_MIN_REC_FLAG;
+        }
+        let heap_no = (i + 2) as u16;
+        BigEndian::write_u16(&mut page[origin - 4..], (heap_no << 3) | status);
+        page[origin..origin + rec.data.len()].copy_from_slice(&rec.data);
+        origins.push(origin);
+        pos = origin + rec.data.len();
+    }
+    origins.push(PAGE_NEW_SUPREMUM);
+    for w in origins.windows(2) {
+        let rel = (w[1] as i16).wrapping_sub(w[0] as i16);
+        BigEndian::write_i16(&mut page[w[0] - 2..], rel);
+    }
+
+    // Directory: infimum, every RECORDS_PER_SLOT-th record, supremum
+    let mut owners = vec![(0usize, 1usize)];
+    let mut last = 0;
+    for i in (RECORDS_PER_SLOT..=records.len()).step_by(RECORDS_PER_SLOT) {
+        owners.push((i, RECORDS_PER_SLOT));
+        last = i;
+    }
+    owners.push((origins.len() - 1, records.len() - last + 1));
+    for (slot, &(idx, n_owned)) in owners.iter().enumerate() {
+        let origin = origins[idx];
+        page[origin - REC_N_NEW_EXTRA_BYTES] |= n_owned as u8;
+        let at = ps - SIZE_FIL_TRAILER - (slot + 1) * PAGE_DIR_SLOT_SIZE;
+        BigEndian::write_u16(&mut page[at..], origin as u16);
+    }
+
+    let h = FIL_PAGE_DATA;
+    BigEndian::write_u16(&mut page[h + PAGE_N_DIR_SLOTS..], owners.len() as u16);
+    BigEndian::write_u16(&mut page[h + PAGE_HEAP_TOP..], pos as u16);
+    BigEndian::write_u16(
+        &mut page[h + PAGE_N_HEAP..],
+        0x8000 | (records.len() as u16 + 2),
+    );
+    BigEndian::write_u16(&mut page[h + PAGE_DIRECTION..], PAGE_NO_DIRECTION);
+    BigEndian::write_u16(&mut page[h + PAGE_N_RECS..], records.len() as u16);
+    BigEndian::write_u64(&mut page[h + PAGE_MAX_TRX_ID..], params.max_trx_id);
+    BigEndian::write_u16(&mut page[h + PAGE_LEVEL..], params.level);
+    BigEndian::write_u64(&mut page[h + PAGE_INDEX_ID..], params.index_id);
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::innodb::page_dir::{check_page_directory, check_record_order};
+
+    const PS: usize = 16384;
+
+    fn col(name: &str, dd_type: u64, fixed_len: usize, nullable: bool) -> ColumnStorageInfo {
+        ColumnStorageInfo {
+            name: name.to_string(),
+            dd_type,
+            column_type: String::new(),
+            is_nullable: nullable,
+            is_unsigned: false,
+            fixed_len,
+            is_variable: fixed_len == 0,
+            max_len: if fixed_len == 0 { 400 } else { fixed_len },
+            version_added: 0,
+            version_dropped: 0,
+            instant_default: None,
+            charset_max_bytes: 4,
+            collation_id: 255,
+            datetime_precision: 0,
+            is_system_column: false,
+            elements: Vec::new(),
+            numeric_precision: 0,
+            numeric_scale: 0,
+        }
+    }
+
+    fn layout() -> IndexLayout {
+        IndexLayout {
+            name: "k".to_string(),
+            index_id: 9,
+            is_clustered: false,
+            columns: vec![col("k", 4, 4, true), col("id", 4, 4, false)],
+            descending: vec![false, false],
+            prefix_lengths: vec![None, None],
+        }
+    }
+
+    fn int(v: i32) -> Option<Vec<u8>> {
+        Some(((v as u32) ^ 0x8000_0000).to_be_bytes().to_vec())
+    }
+
+    fn params(level: u16) -> PageParams {
+        PageParams {
+            space_id: 1,
+            index_id: 9,
+            level,
+            lsn: 100,
+            max_trx_id: 0,
+            page_size: PS,
+        }
+    }
+
+    #[test]
+    fn test_encode_record_nulls_and_lengths() {
+        let columns = vec![
+            col("a", 16, 0, true),
+            col("b", 4, 4, true),
+            col("id", 4, 4, false),
+        ];
+        let long = vec![b'x'; 200];
+        let rec = encode_record(&vec![Some(long), None, int(1)], &columns, Some(7), false);
+        // Page order: 2-byte length (low, high), null bitmap (b is NULL)
+        assert_eq!(rec.extra, vec![200, 0x80, 0b10]);
+        assert_eq!(rec.data.len(), 200 + 4 + 4);
+        assert_eq!(&rec.data[204..], &7u32.to_be_bytes());
+    }
+
+    #[test]
+    fn test_build_index_page_is_consistent() {
+        let index = layout();
+        let entries: Vec<Entry> = (0..10).map(|i| vec![int(i * 10), int(i)]).collect();
+        let recs: Vec<EncodedRecord> = entries
+            .iter()
+            .map(|e| encode_record(e, &index.columns, None, false))
+            .collect();
+        let refs: Vec<&EncodedRecord> = recs.iter().collect();
+        let page = build_index_page(5, FIL_NULL, FIL_NULL, &refs, &params(0));
+
+        let dir = check_page_directory(&page).unwrap();
+        assert!(dir.issues.is_empty());
+        assert_eq!(dir.slots.len(), 4);
+        assert!(check_record_order(&page, &index).is_empty());
+        assert_eq!(walk_records(&page).len(), 10);
+        let hdr = IndexHeader::parse(&page).unwrap();
+        assert_eq!(hdr.n_recs, 10);
+        assert_eq!(hdr.index_id, 9);
+    }
+
+    #[test]
+    fn test_compare_entries_nulls_and_desc() {
+        let mut index = layout();
+        assert_eq!(
+            compare_entries(&vec![None, int(2)], &vec![int(-5), int(1)], &index),
+            Ordering::Less
+        );
+        index.descending[0] = true;
+        assert_eq!(
+            compare_entries(&vec![int(1), int(1)], &vec![int(2), int(1)], &index),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn test_column_prefix_utf8() {
+        let c = col("s", 16, 0, true);
+        // 2 characters of utf8mb4 (prefix length 8 bytes)
+        let value = "héllo".as_bytes().to_vec();
+        assert_eq!(column_prefix(value, 8, &c), "hé".as_bytes());
+    }
+
+    #[test]
+    fn test_split_pages() {
+        let index = layout();
+        let records: Vec<(Entry, EncodedRecord)> = (0..2000)
+            .map(|i| {
+                let e = vec![int(i), int(i)];
+                let r = encode_record(&e, &index.columns, None, false);
+                (e, r)
+            })
+            .collect();
+        let groups = split_pages(&records, PS);
+        assert!(groups.len() > 1);
+        assert_eq!(groups.last().unwrap().end, 2000);
+        for g in &groups {
+            assert!(page_space(g.len(), g.len() * records[0].1.size()) <= PS);
+        }
+    }
+}
@@ -0,0 +1,496 @@
+//! File segment INODE page parsing and page ownership.
+//!
+//! Every B+Tree index owns two file segments (leaf and non-leaf), and other
+//! structures (rollback segments, the doublewrite buffer, the change buffer)
+//! own their own. A segment is described by an *inode* on a FIL_PAGE_INODE
+//! page: its ID, three lists of extents it owns (FREE, NOT_FULL, FULL), and
+//! a fragment array of individually allocated pages (32 slots for 16K pages,
+//! half the extent size in general). INODE pages are linked from the FSP
+//! header through the FSP_SEG_INODES_FULL and FSP_SEG_INODES_FREE lists.
+//!
+//! [`build_ownership_map`] combines the inodes with the extent descriptors
+//! from [`xdes`](crate::innodb::xdes) to assign every used page to its
+//! owning segment, and resolves each segment to its index through the leaf
+//! and non-leaf FSEG headers on index root pages. Pages that are marked used
+//! but owned by no segment are reported as leaked; pages claimed by more
+//! than one segment as double-owned.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use byteorder::{BigEndian, ByteOrder};
+use serde::Serialize;
+
+use crate::innodb::constants::*;
+use crate::innodb::index::{FsegHeader, IndexHeader};
+use crate::innodb::page::FilHeader;
+use crate::innodb::page_types::PageType;
+use crate::innodb::tablespace::Tablespace;
+use crate::innodb::xdes::{AllocationMap, FlstBaseNode, PageAllocState};
+
+/// Number of fragment array slots in an inode.
+///
+/// # Examples
+///
+/// ```
+/// use idb::innodb::inode::frag_slots;
+///
+/// assert_eq!(frag_slots(64), 32);   // 16K pages
+/// assert_eq!(frag_slots(256), 128); // 4K pages
+/// ```
+pub fn frag_slots(extent_size: u32) -> usize {
+    extent_size as usize / 2
+}
+
+/// Size in bytes of one inode (192 bytes for 16K pages).
+pub fn inode_size(extent_size: u32) -> usize {
+    FSEG_FRAG_ARR + frag_slots(extent_size) * FSEG_FRAG_SLOT_SIZE
+}
+
+/// Number of inodes on one INODE page (85 for 16K pages).
+pub fn inodes_per_page(page_size: u32, extent_size: u32) -> usize {
+    (page_size as usize).saturating_sub(FSEG_ARR_OFFSET + SIZE_FIL_TRAILER + 2)
+        / inode_size(extent_size)
+}
+
+/// A used file segment inode.
+#[derive(Debug, Clone, Serialize)]
+pub struct FsegInode {
+    /// INODE page holding this inode.
+    pub page_no: u32,
+    /// Byte offset of the inode within its page.
+    pub offset: u16,
+    /// Segment ID.
+    pub seg_id: u64,
+    /// Number of used pages in the NOT_FULL extents.
+    pub not_full_n_used: u32,
+    /// Wholly free extents owned by the segment.
+    pub free: FlstBaseNode,
+    /// Partially used extents owned by the segment.
+    pub not_full: FlstBaseNode,
+    /// Full extents owned by the segment.
+    pub full: FlstBaseNode,
+    /// Whether `FSEG_MAGIC_N` holds the expected value.
+    pub magic_valid: bool,
+    /// Pages in the fragment array (empty slots omitted).
+    pub frag_pages: Vec<u32>,
+}
+
+impl FsegInode {
+    /// Parse an inode; returns `None` for unused slots (segment ID 0).
+    pub fn parse(data: &[u8], page_no: u32, offset: u16, extent_size: u32) -> Option<Self> {
+        if data.len() < inode_size(extent_size) {
+            return None;
+        }
+        let seg_id = BigEndian::read_u64(&data[FSEG_ID..]);
+        if seg_id == 0 {
+            return None;
+        }
+        let frag_pages = (0..frag_slots(extent_size))
+            .map(|i| BigEndian::read_u32(&data[FSEG_FRAG_ARR + i * FSEG_FRAG_SLOT_SIZE..]))
+            .filter(|&p| p != FIL_NULL)
+            .collect();
+        Some(FsegInode {
+            page_no,
+            offset,
+            seg_id,
+            not_full_n_used: BigEndian::read_u32(&data[FSEG_NOT_FULL_N_USED..]),
+            free: FlstBaseNode::parse(&data[FSEG_FREE..])?,
+            not_full: FlstBaseNode::parse(&data[FSEG_NOT_FULL..])?,
+            full: FlstBaseNode::parse(&data[FSEG_FULL..])?,
+            magic_valid: BigEndian::read_u32(&data[FSEG_MAGIC_N..]) == FSEG_MAGIC_N_VALUE,
+            frag_pages,
+        })
+    }
+}
+
+/// Decode the used inodes on an INODE page.
+///
+/// `page_size` is the physical page size and `extent_size` the pages per
+/// extent (see [`xdes::extent_size`](crate::innodb::xdes::extent_size)).
+///
+/// # Examples
+///
+/// ```
+/// use idb::innodb::inode::parse_inode_page;
+///
+/// // A page with no used inodes
+/// let page = vec![0u8; 16384];
+/// assert!(parse_inode_page(&page, 2, 16384, 64).is_empty());
+/// ```
+pub fn parse_inode_page(
+    page_data: &[u8],
+    page_no: u32,
+    page_size: u32,
+    extent_size: u32,
+) -> Vec<FsegInode> {
+    let size = inode_size(extent_size);
+    (0..inodes_per_page(page_size, extent_size))
+        .filter_map(|i| {
+            let off = FSEG_ARR_OFFSET + i * size;
+            FsegInode::parse(page_data.get(off..)?, page_no, off as u16, extent_size)
+        })
+        .collect()
+}
+
+/// Which B+Tree segment of an index a file segment is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SegmentRole {
+    /// Leaf pages (`PAGE_BTR_SEG_LEAF`).
+    Leaf,
+    /// Root and other non-leaf pages (`PAGE_BTR_SEG_TOP`).
+    NonLeaf,
+}
+
+/// A file segment and the pages it owns.
+#[derive(Debug, Clone, Serialize)]
+pub struct SegmentInfo {
+    /// Segment ID.
+    pub seg_id: u64,
+    /// INODE page holding the segment's inode.
+    pub inode_page: u32,
+    /// Byte offset of the inode within its page.
+    pub inode_offset: u16,
+    /// Index the segment belongs to, if referenced from an index root page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_id: Option<u64>,
+    /// Leaf or non-leaf segment of that index.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<SegmentRole>,
+    /// Pages in the fragment array.
+    pub frag_pages: Vec<u32>,
+    /// Extent numbers on the segment's FREE, NOT_FULL, and FULL lists.
+    pub extents: Vec<u32>,
+    /// Number of used pages owned (fragment pages plus used extent pages).
+    pub used_pages: u64,
+    /// Inconsistencies found in the inode or its extent lists.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Page ownership for a whole tablespace.
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnershipMap {
+    /// Segments in INODE list order.
+    pub segments: Vec<SegmentInfo>,
+    /// INODE pages that were read.
+    pub inode_pages: Vec<u32>,
+    /// Owning segment IDs per page.
+    #[serde(skip)]
+    owners: BTreeMap<u32, Vec<u64>>,
+}
+
+impl OwnershipMap {
+    /// Segments owning a page (empty if none).
+    pub fn owners(&self, page_no: u32) -> &[u64] {
+        self.owners.get(&page_no).map_or(&[], |v| v.as_slice())
+    }
+
+    /// Pages owned by a segment, in ascending order.
+    pub fn segment_pages(&self, seg_id: u64) -> Vec<u32> {
+        self.owners
+            .iter()
+            .filter(|(_, segs)| segs.contains(&seg_id))
+            .map(|(&p, _)| p)
+            .collect()
+    }
+
+    /// Pages claimed by more than one segment, with their owners.
+    pub fn double_owned_pages(&self) -> Vec<(u32, Vec<u64>)> {
+        self.owners
+            .iter()
+            .filter(|(_, segs)| segs.len() > 1)
+            .map(|(&p, segs)| (p, segs.clone()))
+            .collect()
+    }
+
+    /// Pages marked used in the extent descriptors but owned by no segment.
+    ///
+    /// Pages the space manages itself are excluded: page 0 and XDES pages,
+    /// change buffer bitmap pages (the page after each XDES page), and INODE
+    /// pages.
+    pub fn leaked_pages(&self, alloc: &AllocationMap, page_count: u64) -> Vec<u32> {
+        let inode_pages: HashSet<u32> = self.inode_pages.iter().copied().collect();
+        (0..page_count.min(u64::from(u32::MAX)) as u32)
+            .filter(|&p| {
+                alloc.page_state(p) == PageAllocState::Used
+                    && p % alloc.page_size > 1
+                    && !inode_pages.contains(&p)
+                    && !self.owners.contains_key(&p)
+            })
+            .collect()
+    }
+}
+
+/// Build the page ownership map of a tablespace.
+///
+/// Reads the INODE pages on the FSP_SEG_INODES lists of `alloc`, walks each
+/// segment's extent lists, and records the used pages of those extents and
+/// the segment's fragment pages as owned by it. Fragment pages that are
+/// index pages are then checked for FSEG headers pointing back at an inode
+/// to attribute segments to indexes.
+///
+/// `read_page` returns the contents of a page by number, or `None` if it
+/// cannot be read.
+pub fn build_ownership_map(
+    alloc: &AllocationMap,
+    mut read_page: impl FnMut(u32) -> Option<Vec<u8>>,
+) -> OwnershipMap {
+    let inode_pages: Vec<u32> = alloc
+        .seg_inodes_full
+        .members
+        .iter()
+        .chain(&alloc.seg_inodes_free.members)
+        .copied()
+        .collect();
+
+    let mut segments = Vec::new();
+    let mut owners: BTreeMap<u32, Vec<u64>> = BTreeMap::new();
+    for &inode_page in &inode_pages {
+        let data = match read_page(inode_page) {
+            Some(d) => d,
+            None => continue,
+        };
+        for inode in parse_inode_page(&data, inode_page, alloc.page_size, alloc.extent_size) {
+            let seg = build_segment(alloc, &inode);
+            let mut owned: Vec<u32> = seg.frag_pages.clone();
+            for &extent_no in &seg.extents {
+                if let Some(e) = alloc.extents.get(extent_no as usize) {
+                    owned.extend(
+                        (0..alloc.extent_size)
+                            .filter(|&i| !e.is_page_free(i as usize))
+                            .map(|i| e.first_page + i),
+                    );
+                }
+            }
+            owned.sort_unstable();
+            owned.dedup();
+            for p in owned {
+                owners.entry(p).or_default().push(seg.seg_id);
+            }
+            segments.push(seg);
+        }
+    }
+
+    // Index root pages carry the FSEG headers of both segments of their index
+    let by_inode: HashMap<(u32, u16), usize> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| ((s.inode_page, s.inode_offset), i))
+        .collect();
+    let candidates: Vec<u32> = segments
+        .iter()
+        .flat_map(|s| s.frag_pages.iter().copied())
+        .collect();
+    for page_no in candidates {
+        let data = match read_page(page_no) {
+            Some(d) => d,
+            None => continue,
+        };
+        let is_index = FilHeader::parse(&data).is_some_and(|h| {
+            matches!(
+                h.page_type,
+                PageType::Index | PageType::Rtree | PageType::Sdi
+            )
+        });
+        let idx = match IndexHeader::parse(&data) {
+            Some(idx) if is_index => idx,
+            _ => continue,
+        };
+        for (role, fseg) in [
+            (SegmentRole::Leaf, FsegHeader::parse_leaf(&data)),
+            (SegmentRole::NonLeaf, FsegHeader::parse_internal(&data)),
+        ] {
+            let target = fseg.and_then(|f| by_inode.get(&(f.page_no, f.offset)).copied());
+            if let Some(i) = target {
+                segments[i].index_id = Some(idx.index_id);
+                segments[i].role = Some(role);
+            }
+        }
+    }
+
+    OwnershipMap {
+        segments,
+        inode_pages,
+        owners,
+    }
+}
+
+/// Walk an inode's extent lists and check them against the descriptors.
+fn build_segment(alloc: &AllocationMap, inode: &FsegInode) -> SegmentInfo {
+    let mut seg = SegmentInfo {
+        seg_id: inode.seg_id,
+        inode_page: inode.page_no,
+        inode_offset: inode.offset,
+        index_id: None,
+        role: None,
+        frag_pages: inode.frag_pages.clone(),
+        extents: Vec::new(),
+        used_pages: 0,
+        errors: Vec::new(),
+    };
+    if !inode.magic_valid {
+        seg.errors.push("invalid FSEG_MAGIC_N".to_string());
+    }
+
+    for (name, base) in [
+        ("FSEG_FREE", &inode.free),
+        ("FSEG_NOT_FULL", &inode.not_full),
+        ("FSEG_FULL", &inode.full),
+    ] {
+        let walk = alloc.walk_extent_list(name, base);
+        seg.errors
+            .extend(walk.errors.iter().map(|e| format!("{}: {}", name, e)));
+        for &extent_no in &walk.members {
+            if let Some(e) = alloc.extents.get(extent_no as usize) {
+                if e.segment_id != inode.seg_id {
+                    seg.errors.push(format!(
+                        "{}: extent {} belongs to segment {}",
+                        name, extent_no, e.segment_id
+                    ));
+                }
+                seg.used_pages += u64::from(e.n_used_pages);
+            }
+        }
+        seg.extents.extend(walk.members);
+    }
+    seg.used_pages += seg
+        .frag_pages
+        .iter()
+        .filter(|&&p| alloc.page_state(p) == PageAllocState::Used)
+        .count() as u64;
+    seg
+}
+
+/// Read the page ownership map of a tablespace.
+///
+/// See [`build_ownership_map`]; unreadable pages are skipped.
+pub fn read_ownership_map(ts: &mut Tablespace, alloc: &AllocationMap) -> OwnershipMap {
+    build_ownership_map(alloc, |p| ts.read_page(p as u64).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::innodb::xdes::build_allocation_map;
+
+    const PS: u32 = SIZE_PAGE_16K;
+
+    fn write_addr(buf: &mut [u8], page_no: u32, boffset: u16) {
+        BigEndian::write_u32(&mut buf[0..], page_no);
+        BigEndian::write_u16(&mut buf[4..], boffset);
+    }
+
+    fn null_list(buf: &mut [u8]) {
+        BigEndian::write_u32(&mut buf[0..], 0);
+        write_addr(&mut buf[4..], FIL_NULL, 0);
+        write_addr(&mut buf[10..], FIL_NULL, 0);
+    }
+
+    /// Tablespace of 5 pages in one FREE_FRAG extent: 0 FSP_HDR, 1 IBUF
+    /// bitmap, 2 INODE, 3 index root (segments 1 and 2), 4 leaked.
+    fn build_pages(frag_b: u32) -> Vec<Vec<u8>> {
+        let mut pages = vec![vec![0u8; PS as usize]; 5];
+
+        let p0 = &mut pages[0];
+        let fsp = FIL_PAGE_DATA;
+        BigEndian::write_u32(&mut p0[fsp + FSP_SIZE..], 5);
+        BigEndian::write_u32(&mut p0[fsp + FSP_FREE_LIMIT..], 64);
+        for list in [
+            FSP_FREE,
+            FSP_FREE_FRAG,
+            FSP_FULL_FRAG,
+            FSP_SEG_INODES_FULL,
+            FSP_SEG_INODES_FREE,
+        ] {
+            null_list(&mut p0[fsp + list..]);
+        }
+        BigEndian::write_u32(&mut p0[fsp + FSP_SEG_INODES_FREE..], 1);
+        write_addr(
+            &mut p0[fsp + FSP_SEG_INODES_FREE + 4..],
+            2,
+            FIL_PAGE_DATA as u16,
+        );
+        let x = XDES_ARR_OFFSET;
+        BigEndian::write_u32(&mut p0[x + XDES_STATE..], 2);
+        for b in &mut p0[x + XDES_BITMAP..x + XDES_SIZE] {
+            *b = 0xFF;
+        }
+        p0[x + XDES_BITMAP] = 0xAA; // pages 0-3 used
+        p0[x + XDES_BITMAP + 1] = 0xFE; // page 4 used
+
+        let p2 = &mut pages[2];
+        BigEndian::write_u16(&mut p2[FIL_PAGE_TYPE..], 3); // INODE
+        write_addr(&mut p2[FSEG_INODE_PAGE_NODE..], FIL_NULL, 0);
+        write_addr(&mut p2[FSEG_INODE_PAGE_NODE + 6..], FIL_NULL, 0);
+        for (slot, seg_id, frag) in [(0usize, 1u64, 3u32), (1, 2, frag_b)] {
+            let i = FSEG_ARR_OFFSET + slot * inode_size(64);
+            BigEndian::write_u64(&mut p2[i + FSEG_ID..], seg_id);
+            for list in [FSEG_FREE, FSEG_NOT_FULL, FSEG_FULL] {
+                null_list(&mut p2[i + list..]);
+            }
+            BigEndian::write_u32(&mut p2[i + FSEG_MAGIC_N..], FSEG_MAGIC_N_VALUE);
+            for s in 0..32 {
+                BigEndian::write_u32(&mut p2[i + FSEG_FRAG_ARR + s * 4..], FIL_NULL);
+            }
+            BigEndian::write_u32(&mut p2[i + FSEG_FRAG_ARR..], frag);
+        }
+
+        // Root page: non-leaf segment 1, leaf segment 2
+        let p3 = &mut pages[3];
+        BigEndian::write_u16(&mut p3[FIL_PAGE_TYPE..], 17855);
+        BigEndian::write_u64(&mut p3[FIL_PAGE_DATA + PAGE_INDEX_ID..], 77);
+        let leaf = FIL_PAGE_DATA + PAGE_BTR_SEG_LEAF;
+        BigEndian::write_u32(&mut p3[leaf + 4..], 2);
+        BigEndian::write_u16(
+            &mut p3[leaf + 8..],
+            (FSEG_ARR_OFFSET + inode_size(64)) as u16,
+        );
+        let top = FIL_PAGE_DATA + PAGE_BTR_SEG_TOP;
+        BigEndian::write_u32(&mut p3[top + 4..], 2);
+        BigEndian::write_u16(&mut p3[top + 8..], FSEG_ARR_OFFSET as u16);
+        pages
+    }
+
+    fn maps(pages: &[Vec<u8>]) -> (AllocationMap, OwnershipMap) {
+        let read = |p: u32| pages.get(p as usize).cloned();
+        let alloc = build_allocation_map(&pages[0], PS, 64, pages.len() as u64, read).unwrap();
+        let owners = build_ownership_map(&alloc, read);
+        (alloc, owners)
+    }
+
+    #[test]
+    fn test_parse_inode_page() {
+        let pages = build_pages(FIL_NULL);
+        let inodes = parse_inode_page(&pages[2], 2, PS, 64);
+        assert_eq!(inodes.len(), 2);
+        assert_eq!(inodes[0].seg_id, 1);
+        assert_eq!(inodes[0].offset as usize, FSEG_ARR_OFFSET);
+        assert!(inodes[0].magic_valid);
+        assert_eq!(inodes[0].frag_pages, vec![3]);
+        assert!(inodes[1].frag_pages.is_empty());
+        assert_eq!(inodes_per_page(PS, 64), 85);
+    }
+
+    #[test]
+    fn test_ownership_and_index_roles() {
+        let pages = build_pages(FIL_NULL);
+        let (alloc, map) = maps(&pages);
+        assert_eq!(map.inode_pages, vec![2]);
+        assert_eq!(map.owners(3), &[1]);
+        assert_eq!(map.segment_pages(1), vec![3]);
+        assert!(map.segment_pages(2).is_empty());
+        assert_eq!(map.segments[0].index_id, Some(77));
+        assert_eq!(map.segments[0].role, Some(SegmentRole::NonLeaf));
+        assert_eq!(map.segments[1].role, Some(SegmentRole::Leaf));
+        assert_eq!(map.segments[0].used_pages, 1);
+        assert_eq!(map.leaked_pages(&alloc, 5), vec![4]);
+        assert!(map.double_owned_pages().is_empty());
+    }
+
+    #[test]
+    fn test_double_owned_page() {
+        let pages = build_pages(3);
+        let (_, map) = maps(&pages);
+        assert_eq!(map.double_owned_pages(), vec![(3, vec![1, 2])]);
+    }
+}
@@ -0,0 +1,414 @@
+//! MySQL binary JSON decoding.
+//!
+//! MySQL stores `JSON` columns in a compact binary format (see
+//! `sql-common/json_binary.cc`). Each value starts with a one-byte type,
+//! followed by a type-specific payload. Objects and arrays come in a
+//! "small" variant (2-byte counts and offsets) and a "large" variant
+//! (4-byte counts and offsets); all integers are little-endian.
+//!
+//! | Type | Code | Payload |
+//! |------|------|---------|
+//! | Small/large object | 0x00 / 0x01 | count, size, key entries, value entries, keys, values |
+//! | Small/large array | 0x02 / 0x03 | count, size, value entries, values |
+//! | Literal | 0x04 | 0x00 null, 0x01 true, 0x02 false |
+//! | INT16/UINT16 | 0x05 / 0x06 | 2 bytes |
+//! | INT32/UINT32 | 0x07 / 0x08 | 4 bytes |
+//! | INT64/UINT64 | 0x09 / 0x0A | 8 bytes |
+//! | DOUBLE | 0x0B | 8-byte IEEE 754 |
+//! | STRING | 0x0C | variable-length size + UTF-8 bytes |
+//! | OPAQUE | 0x0F | MySQL field type, variable-length size, data |
+//!
+//! Opaque DATETIME/TIMESTAMP, DATE, TIME, and DECIMAL values are rendered
+//! the way MySQL prints them (DECIMAL as a string, so no digits are lost);
+//! other opaque values use MySQL's `base64:type<N>:<data>` notation.
+
+use byteorder::{ByteOrder, LittleEndian};
+use serde_json::{Map, Number, Value};
+
+use crate::innodb::field_decode::{self, FieldValue};
+
+const JSONB_TYPE_SMALL_OBJECT: u8 = 0x00;
+const JSONB_TYPE_LARGE_OBJECT: u8 = 0x01;
+const JSONB_TYPE_SMALL_ARRAY: u8 = 0x02;
+const JSONB_TYPE_LARGE_ARRAY: u8 = 0x03;
+const JSONB_TYPE_LITERAL: u8 = 0x04;
+const JSONB_TYPE_INT16: u8 = 0x05;
+const JSONB_TYPE_UINT16: u8 = 0x06;
+const JSONB_TYPE_INT32: u8 = 0x07;
+const JSONB_TYPE_UINT32: u8 = 0x08;
+const JSONB_TYPE_INT64: u8 = 0x09;
+const JSONB_TYPE_UINT64: u8 = 0x0A;
+const JSONB_TYPE_DOUBLE: u8 = 0x0B;
+const JSONB_TYPE_STRING: u8 = 0x0C;
+const JSONB_TYPE_OPAQUE: u8 = 0x0F;
+
+const JSONB_NULL_LITERAL: u8 = 0x00;
+const JSONB_TRUE_LITERAL: u8 = 0x01;
+const JSONB_FALSE_LITERAL: u8 = 0x02;
+
+/// Key entry: key offset + 2-byte key length.
+const KEY_LEN_SIZE: usize = 2;
+
+/// Maximum nesting depth accepted by MySQL (JSON_DOCUMENT_MAX_DEPTH).
+const MAX_DEPTH: usize = 100;
+
+/// MySQL field types that appear in opaque JSON values (enum_field_types).
+const MYSQL_TYPE_TIMESTAMP: u8 = 7;
+const MYSQL_TYPE_DATE: u8 = 10;
+const MYSQL_TYPE_TIME: u8 = 11;
+const MYSQL_TYPE_DATETIME: u8 = 12;
+const MYSQL_TYPE_NEWDECIMAL: u8 = 246;
+
+/// Decode a MySQL binary JSON document into a [`serde_json::Value`].
+///
+/// A zero-length value is JSON `null`, matching MySQL. Returns `None` if
+/// the data is not well-formed binary JSON.
+///
+/// # Examples
+///
+/// ```
+/// use idb::innodb::json_binary::decode_json_binary;
+///
+/// // Small array [1, "a"]: type 0x02, count=2, size=12,
+/// // entries (INT16 inline 1) and (STRING at offset 10), then "a"
+/// let data = [
+///     0x02, 0x02, 0x00, 0x0C, 0x00, 0x05, 0x01, 0x00, 0x0C, 0x0A, 0x00, 0x01, b'a',
+/// ];
+/// let value = decode_json_binary(&data).unwrap();
+/// assert_eq!(value, serde_json::json!([1, "a"]));
+/// ```
+pub fn decode_json_binary(data: &[u8]) -> Option<Value> {
+    if data.is_empty() {
+        return Some(Value::Null);
+    }
+    parse_value(data[0], &data[1..], 0)
+}
+
+/// Parse a value of type `jtype` whose payload starts at `data[0]`.
+fn parse_value(jtype: u8, data: &[u8], depth: usize) -> Option<Value> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    match jtype {
+        JSONB_TYPE_SMALL_OBJECT => parse_container(data, true, false, depth),
+        JSONB_TYPE_LARGE_OBJECT => parse_container(data, true, true, depth),
+        JSONB_TYPE_SMALL_ARRAY => parse_container(data, false, false, depth),
+        JSONB_TYPE_LARGE_ARRAY => parse_container(data, false, true, depth),
+        JSONB_TYPE_LITERAL => match *data.first()? {
+            JSONB_NULL_LITERAL => Some(Value::Null),
+            JSONB_TRUE_LITERAL => Some(Value::Bool(true)),
+            JSONB_FALSE_LITERAL => Some(Value::Bool(false)),
+            _ => None,
+        },
+        JSONB_TYPE_INT16 => Some(Value::from(LittleEndian::read_i16(data.get(..2)?))),
+        JSONB_TYPE_UINT16 => Some(Value::from(LittleEndian::read_u16(data.get(..2)?))),
+        JSONB_TYPE_INT32 => Some(Value::from(LittleEndian::read_i32(data.get(..4)?))),
+        JSONB_TYPE_UINT32 => Some(Value::from(LittleEndian::read_u32(data.get(..4)?))),
+        JSONB_TYPE_INT64 => Some(Value::from(LittleEndian::read_i64(data.get(..8)?))),
+        JSONB_TYPE_UINT64 => Some(Value::from(LittleEndian::read_u64(data.get(..8)?))),
+        JSONB_TYPE_DOUBLE => {
+            let d = LittleEndian::read_f64(data.get(..8)?);
+            Number::from_f64(d).map(Value::Number)
+        }
+        JSONB_TYPE_STRING => {
+            let (len, n) = read_variable_length(data)?;
+            let bytes = data.get(n..n.checked_add(len)?)?;
+            Some(Value::String(String::from_utf8_lossy(bytes).into_owned()))
+        }
+        JSONB_TYPE_OPAQUE => {
+            let field_type = *data.first()?;
+            let (len, n) = read_variable_length(&data[1..])?;
+            let start = 1 + n;
+            let bytes = data.get(start..start.checked_add(len)?)?;
+            Some(decode_opaque(field_type, bytes))
+        }
+        _ => None,
+    }
+}
+
+/// Parse an object or array. Offsets are relative to `data[0]`.
+fn parse_container(data: &[u8], is_object: bool, large: bool, depth: usize) -> Option<Value> {
+    let offset_size = if large { 4 } else { 2 };
+    let read_offset = |pos: usize| -> Option<usize> {
+        let bytes = data.get(pos..pos + offset_size)?;
+        Some(if large {
+            LittleEndian::read_u32(bytes) as usize
+        } else {
+            LittleEndian::read_u16(bytes) as usize
+        })
+    };
+
+    let count = read_offset(0)?;
+    let size = read_offset(offset_size)?;
+    if size > data.len() {
+        return None;
+    }
+    let data = &data[..size];
+
+    let key_entry_size = offset_size + KEY_LEN_SIZE;
+    let value_entry_size = 1 + offset_size;
+    let header_size = 2 * offset_size;
+    let value_entries_start = if is_object {
+        header_size.checked_add(count.checked_mul(key_entry_size)?)?
+    } else {
+        header_size
+    };
+    if value_entries_start.checked_add(count.checked_mul(value_entry_size)?)? > size {
+        return None;
+    }
+
+    let mut values = Vec::with_capacity(count);
+    for i in 0..count {
+        let entry = value_entries_start + i * value_entry_size;
+        let vtype = data[entry];
+        let inlined = match vtype {
+            JSONB_TYPE_LITERAL | JSONB_TYPE_INT16 | JSONB_TYPE_UINT16 => true,
+            JSONB_TYPE_INT32 | JSONB_TYPE_UINT32 => large,
+            _ => false,
+        };
+        let value = if inlined {
+            parse_value(vtype, &data[entry + 1..entry + value_entry_size], depth + 1)?
+        } else {
+            let offset = read_offset(entry + 1)?;
+            parse_value(vtype, data.get(offset..)?, depth + 1)?
+        };
+        values.push(value);
+    }
+
+    if !is_object {
+        return Some(Value::Array(values));
+    }
+
+    let mut map = Map::new();
+    for (i, value) in values.into_iter().enumerate() {
+        let entry = header_size + i * key_entry_size;
+        let key_offset = read_offset(entry)?;
+        let key_len = LittleEndian::read_u16(&data[entry + offset_size..]) as usize;
+        let key = data.get(key_offset..key_offset.checked_add(key_len)?)?;
+        map.insert(String::from_utf8_lossy(key).into_owned(), value);
+    }
+    Some(Value::Object(map))
+}
+
+/// Read a variable-length size: 7 bits per byte, low-order group first,
+/// high bit set on all but the last byte. Returns `(size, bytes_read)`.
+fn read_variable_length(data: &[u8]) -> Option<(usize, usize)> {
+    let mut len: u64 = 0;
+    for (i, &b) in data.iter().take(5).enumerate() {
+        len |= ((b & 0x7F) as u64) << (7 * i);
+        if b & 0x80 == 0 {
+            return Some((usize::try_from(len).ok()?, i + 1));
+        }
+    }
+    None
+}
+
+/// Render an opaque value the way MySQL prints it in JSON text.
+fn decode_opaque(field_type: u8, data: &[u8]) -> Value {
+    match field_type {
+        MYSQL_TYPE_DATETIME | MYSQL_TYPE_TIMESTAMP | MYSQL_TYPE_DATE if data.len() >= 8 => {
+            let packed = LittleEndian::read_i64(data);
+            Value::String(format_packed_datetime(
+                packed,
+                field_type == MYSQL_TYPE_DATE,
+            ))
+        }
+        MYSQL_TYPE_TIME if data.len() >= 8 => {
+            Value::String(format_packed_time(LittleEndian::read_i64(data)))
+        }
+        MYSQL_TYPE_NEWDECIMAL if data.len() >= 2 => {
+            let (precision, scale) = (data[0] as u64, data[1] as u64);
+            // Kept as text: DECIMAL(65,30) does not fit in an f64
+            match field_decode::decode_decimal(&data[2..], precision, scale) {
+                FieldValue::Str(s) => Value::String(s),
+                _ => opaque_base64(field_type, data),
+            }
+        }
+        _ => opaque_base64(field_type, data),
+    }
+}
+
+/// Format a packed DATETIME/DATE (`TIME_to_longlong_datetime_packed`).
+fn format_packed_datetime(packed: i64, date_only: bool) -> String {
+    let tmp = packed.unsigned_abs();
+    let frac = tmp % (1 << 24);
+    let ymdhms = tmp >> 24;
+    let ymd = ymdhms >> 17;
+    let hms = ymdhms % (1 << 17);
+    let ym = ymd >> 5;
+    let (year, month, day) = (ym / 13, ym % 13, ymd % (1 << 5));
+    if date_only {
+        return format!("{:04}-{:02}-{:02}", year, month, day);
+    }
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}",
+        year,
+        month,
+        day,
+        hms >> 12,
+        (hms >> 6) % (1 << 6),
+        hms % (1 << 6),
+        frac
+    )
+}
+
+/// Format a packed TIME (`TIME_to_longlong_time_packed`).
+fn format_packed_time(packed: i64) -> String {
+    let tmp = packed.unsigned_abs();
+    let frac = tmp % (1 << 24);
+    let hms = tmp >> 24;
+    format!(
+        "{}{:02}:{:02}:{:02}.{:06}",
+        if packed < 0 { "-" } else { "" },
+        (hms >> 12) % (1 << 10),
+        (hms >> 6) % (1 << 6),
+        hms % (1 << 6),
+        frac
+    )
+}
+
+/// MySQL's text form for opaque values without a native JSON rendering.
+fn opaque_base64(field_type: u8, data: &[u8]) -> Value {
+    Value::String(format!("base64:type{}:{}", field_type, base64_encode(data)))
+}
+
+/// Standard base64 encoding with padding.
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decode_literals_and_scalars() {
+        assert_eq!(decode_json_binary(&[]), Some(Value::Null));
+        assert_eq!(decode_json_binary(&[0x04, 0x01]), Some(json!(true)));
+        assert_eq!(decode_json_binary(&[0x04, 0x02]), Some(json!(false)));
+        assert_eq!(decode_json_binary(&[0x04, 0x00]), Some(Value::Null));
+        assert_eq!(decode_json_binary(&[0x05, 0xFE, 0xFF]), Some(json!(-2)));
+        assert_eq!(
+            decode_json_binary(&[0x0A, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]),
+            Some(json!(u64::MAX))
+        );
+        let mut dbl = vec![0x0B];
+        dbl.extend_from_slice(&1.5f64.to_le_bytes());
+        assert_eq!(decode_json_binary(&dbl), Some(json!(1.5)));
+        assert_eq!(
+            decode_json_binary(&[0x0C, 0x03, b'a', b'b', b'c']),
+            Some(json!("abc"))
+        );
+    }
+
+    #[test]
+    fn test_decode_small_object_nested() {
+        // {"a": 1, "b": [true, "xy"]}
+        let array: Vec<u8> = vec![
+            0x02, 0x00, // count
+            0x0D, 0x00, // size
+            0x04, 0x01, 0x00, // literal true (inline)
+            0x0C, 0x0A, 0x00, // string at offset 10
+            0x02, b'x', b'y',
+        ];
+        let mut obj: Vec<u8> = vec![
+            0x02, 0x00, // count
+            0x00, 0x00, // size (patched below)
+            0x12, 0x00, 0x01, 0x00, // key "a" at 18
+            0x13, 0x00, 0x01, 0x00, // key "b" at 19
+            0x05, 0x01, 0x00, // INT16 1 (inline)
+            0x02, 0x14, 0x00, // small array at 20
+            b'a', b'b',
+        ];
+        obj.extend_from_slice(&array);
+        let size = obj.len() as u16;
+        obj[2..4].copy_from_slice(&size.to_le_bytes());
+
+        let mut data = vec![0x00];
+        data.extend_from_slice(&obj);
+        assert_eq!(
+            decode_json_binary(&data),
+            Some(json!({"a": 1, "b": [true, "xy"]}))
+        );
+    }
+
+    #[test]
+    fn test_decode_large_array_inline_int32() {
+        // Large array [100000, -1]: INT32 values are inlined in large format
+        let mut data = vec![0x03];
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&18u32.to_le_bytes());
+        data.push(0x07);
+        data.extend_from_slice(&100_000i32.to_le_bytes());
+        data.push(0x07);
+        data.extend_from_slice(&(-1i32).to_le_bytes());
+        assert_eq!(decode_json_binary(&data), Some(json!([100_000, -1])));
+    }
+
+    #[test]
+    fn test_decode_opaque_datetime_and_decimal() {
+        // 2015-01-15 23:24:25.000123
+        let ymd: i64 = ((2015 * 13 + 1) << 5) | 15;
+        let hms: i64 = (23 << 12) | (24 << 6) | 25;
+        let packed = (((ymd << 17) | hms) << 24) | 123;
+        let mut data = vec![0x0F, MYSQL_TYPE_DATETIME, 0x08];
+        data.extend_from_slice(&packed.to_le_bytes());
+        assert_eq!(
+            decode_json_binary(&data),
+            Some(json!("2015-01-15 23:24:25.000123"))
+        );
+
+        data[1] = MYSQL_TYPE_DATE;
+        assert_eq!(decode_json_binary(&data), Some(json!("2015-01-15")));
+
+        // DECIMAL(5,2) 123.45: intg=3 -> 2 bytes, frac=2 -> 1 byte
+        let data = [0x0F, MYSQL_TYPE_NEWDECIMAL, 0x05, 5, 2, 0x80, 0x7B, 0x2D];
+        assert_eq!(decode_json_binary(&data), Some(json!("123.45")));
+
+        // DECIMAL(30,10) 12345678901234567890.0123456789: intg=20 -> 9+9+2
+        // digits (4+4+1 bytes), frac=10 -> 9+1 digits (4+1 bytes)
+        let mut data = vec![0x0F, MYSQL_TYPE_NEWDECIMAL, 16, 30, 10];
+        data.push(0x80 | 12);
+        data.extend_from_slice(&345_678_901u32.to_be_bytes());
+        data.extend_from_slice(&234_567_890u32.to_be_bytes());
+        data.extend_from_slice(&12_345_678u32.to_be_bytes());
+        data.push(9);
+        assert_eq!(
+            decode_json_binary(&data),
+            Some(json!("12345678901234567890.0123456789"))
+        );
+    }
+
+    #[test]
+    fn test_decode_opaque_other_and_invalid() {
+        let data = [0x0F, 252, 0x03, b'a', b'b', b'c'];
+        assert_eq!(
+            decode_json_binary(&data),
+            Some(json!("base64:type252:YWJj"))
+        );
+        assert_eq!(base64_encode(b"ab"), "YWI=");
+        assert_eq!(base64_encode(b"a"), "YQ==");
+
+        // Truncated container and unknown type
+        assert!(decode_json_binary(&[0x00, 0x05, 0x00, 0xFF, 0x00]).is_none());
+        assert!(decode_json_binary(&[0x0E]).is_none());
+    }
+}
@@ -5,11 +5,17 @@
 //! length and next-page pointer. MySQL 8.0+ introduces structured LOB first pages
 //! (type 22) with a richer header ([`LobFirstPageHeader`]) containing version,
 //! flags, total data length, transaction ID, and index entry pointers.
+//!
+//! [`read_external_field`] follows an [`ExternFieldRef`] (the 20-byte reference
+//! stored in a record for an off-page column) and reassembles the full value.
 
 use byteorder::{BigEndian, ByteOrder};
+use flate2::{Decompress, FlushDecompress, Status};
 use serde::Serialize;
 
-use crate::innodb::constants::{FIL_NULL, FIL_PAGE_DATA};
+use crate::innodb::constants::{
+    BTR_EXTERN_FIELD_REF_SIZE, FIL_NULL, FIL_PAGE_DATA, FIL_PAGE_NEXT, SIZE_FIL_TRAILER,
+};
 
 /// Old-style BLOB page header offsets (relative to FIL_PAGE_DATA).
 ///
@@ -633,6 +639,10 @@ fn walk_new_lob_chain(
 }
 
 /// Walk compressed LOB chain (ZLOB_FIRST → index entries).
+///
+/// Each index entry describes one independently compressed chunk, stored
+/// either as a zlib stream on ZLOB_DATA pages or as a fragment on a
+/// ZLOB_FRAG page.
 fn walk_zlob_chain(
     ts: &mut crate::innodb::tablespace::Tablespace,
     start_page: u64,
@@ -650,12 +660,10 @@ fn walk_zlob_chain(
         data_len: zlob_hdr.data_len,
     }];
 
-    // Parse index entries from the first page
-    let index_start = FIL_PAGE_DATA + ZLOB_FIRST_HDR_SIZE;
-    let entries = LobIndexEntry::parse_all(first_page_data, index_start);
+    let entries = zlob_index_entries(ts, start_page, first_page_data, max_pages.saturating_sub(1))?;
 
-    for entry in entries.iter().take(max_pages.saturating_sub(1)) {
-        let data_page = match ts.read_page(entry.page_no as u64) {
+    for entry in &entries {
+        let data_page = match ts.read_page(entry.z_page_no as u64) {
             Ok(d) => d,
             Err(_) => continue,
         };
@@ -668,9 +676,9 @@ fn walk_zlob_chain(
         };
 
         pages.push(LobChainPage {
-            page_no: entry.page_no as u64,
+            page_no: entry.z_page_no as u64,
             page_type: page_type_name.to_string(),
-            data_len: entry.data_len,
+            data_len: entry.zdata_len,
         });
     }
 
@@ -685,6 +693,496 @@ fn walk_zlob_chain(
     }))
 }
 
+/// One entry of a compressed LOB index list (`z_index_entry_t`).
+#[derive(Debug, Clone, Copy)]
+struct ZlobIndexEntry {
+    /// First page of the chunk's zlib stream, or the fragment page.
+    z_page_no: u32,
+    /// Fragment ID on `z_page_no`, or `ZLOB_FRAG_ID_NULL` for a stream.
+    frag_id: u16,
+    /// Uncompressed length of the chunk.
+    data_len: u32,
+    /// Compressed length of the chunk.
+    zdata_len: u32,
+}
+
+/// Follow the index list of a ZLOB_FIRST page and return its entries in
+/// LOB order. Entries may live on the first page or on ZLOB_INDEX pages.
+fn zlob_index_entries(
+    ts: &mut crate::innodb::tablespace::Tablespace,
+    first_page_no: u64,
+    first_page: &[u8],
+    max_entries: usize,
+) -> Result<Vec<ZlobIndexEntry>, crate::IdbError> {
+    let base = FIL_PAGE_DATA + ZLOB_FIRST_INDEX_LIST;
+    if first_page.len() < base + 16 {
+        return Err(crate::IdbError::Parse(format!(
+            "ZLOB first page {} is truncated",
+            first_page_no
+        )));
+    }
+    // FLST base node: length (4), first (page 4 + boffset 2), last (6)
+    let mut node_page = BigEndian::read_u32(&first_page[base + 4..]);
+    let mut node_off = BigEndian::read_u16(&first_page[base + 8..]) as usize;
+
+    let mut entries = Vec::new();
+    let mut cached: Option<(u32, Vec<u8>)> = None;
+    let limit = max_entries.min(ts.page_count() as usize * 256);
+
+    while node_page != FIL_NULL && entries.len() < limit {
+        let entry_page: &[u8] = if node_page as u64 == first_page_no {
+            first_page
+        } else {
+            if cached.as_ref().map(|(p, _)| *p) != Some(node_page) {
+                cached = Some((node_page, ts.read_page(node_page as u64)?));
+            }
+            &cached.as_ref().unwrap().1
+        };
+        if node_off + ZLOB_INDEX_ENTRY_SIZE > entry_page.len() {
+            break;
+        }
+        let entry = &entry_page[node_off..node_off + ZLOB_INDEX_ENTRY_SIZE];
+        entries.push(ZlobIndexEntry {
+            z_page_no: BigEndian::read_u32(&entry[ZLOB_ENTRY_Z_PAGE_NO..]),
+            frag_id: BigEndian::read_u16(&entry[ZLOB_ENTRY_Z_FRAG_ID..]),
+            data_len: BigEndian::read_u32(&entry[ZLOB_ENTRY_DATA_LEN..]),
+            zdata_len: BigEndian::read_u32(&entry[ZLOB_ENTRY_ZDATA_LEN..]),
+        });
+        node_page = BigEndian::read_u32(&entry[LOB_ENTRY_NEXT..]);
+        node_off = BigEndian::read_u16(&entry[LOB_ENTRY_NEXT + 4..]) as usize;
+    }
+    Ok(entries)
+}
+
+// ---------------------------------------------------------------------------
+// External field reassembly
+// ---------------------------------------------------------------------------
+
+/// External field reference offsets (btr0types.h: BTR_EXTERN_*).
+const BTR_EXTERN_SPACE_ID: usize = 0; // 4 bytes
+const BTR_EXTERN_PAGE_NO: usize = 4; // 4 bytes
+const BTR_EXTERN_OFFSET: usize = 8; // 4 bytes (LOB version in 8.0)
+const BTR_EXTERN_LEN: usize = 12; // 8 bytes, flags in the first byte
+const BTR_EXTERN_OWNER_FLAG: u8 = 0x80;
+const BTR_EXTERN_INHERITED_FLAG: u8 = 0x40;
+
+/// MySQL 8.0 LOB first page layout (lob0first.h, relative to FIL_PAGE_DATA).
+const LOB_FIRST_INDEX_LIST: usize = 26; // FLST_BASE_NODE (16 bytes)
+const LOB_FIRST_PAGE_DATA: usize = 58; // start of the index entry array
+
+/// MySQL 8.0 LOB index entry layout (lob0index.h).
+const LOB_ENTRY_NEXT: usize = 6; // fil_addr_t (page 4 + boffset 2)
+const LOB_ENTRY_PAGE_NO: usize = 48; // 4 bytes
+const LOB_ENTRY_DATA_LEN: usize = 52; // 2 bytes used
+
+/// MySQL 8.0 ZLOB first page layout (zlob0first.h, relative to FIL_PAGE_DATA).
+const ZLOB_FIRST_INDEX_LIST: usize = 50; // FLST_BASE_NODE (16 bytes)
+
+/// MySQL 8.0 ZLOB index entry layout (zlob0index.h).
+const ZLOB_INDEX_ENTRY_SIZE: usize = 66;
+const ZLOB_ENTRY_Z_PAGE_NO: usize = 48; // 4 bytes
+const ZLOB_ENTRY_Z_FRAG_ID: usize = 52; // 2 bytes
+const ZLOB_ENTRY_DATA_LEN: usize = 54; // 4 bytes, uncompressed
+const ZLOB_ENTRY_ZDATA_LEN: usize = 58; // 4 bytes, compressed
+const ZLOB_FRAG_ID_NULL: u16 = 0xFFFF;
+
+/// MySQL 8.0 ZLOB fragment page layout (zlob0int.h). The page directory
+/// grows down from the trailer; each slot holds the offset of a fragment
+/// node: prev/next (2 + 2), total length (2), fragment ID (2), data.
+const ZLOB_FRAG_DIR_FIRST: usize = 12; // from the page end
+const ZLOB_FRAG_NODE_LEN: usize = 4; // 2 bytes
+const ZLOB_FRAG_NODE_DATA: usize = 8;
+
+/// Parsed 20-byte external field reference (BTR_EXTERN_FIELD_REF).
+///
+/// Stored at the end of the locally stored prefix of a column whose value
+/// was moved to overflow pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ExternFieldRef {
+    /// Tablespace ID holding the overflow pages.
+    pub space_id: u32,
+    /// First overflow page number.
+    pub page_no: u32,
+    /// Byte offset of the BLOB header on the first page (LOB version in 8.0).
+    pub offset: u32,
+    /// Length of the externally stored part, in bytes.
+    pub length: u64,
+    /// Whether this record owns the externally stored value.
+    pub is_owner: bool,
+    /// Whether the value was inherited from an earlier record version.
+    pub is_inherited: bool,
+}
+
+impl ExternFieldRef {
+    /// Parse an external field reference from the last 20 bytes of a field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use idb::innodb::lob::ExternFieldRef;
+    /// use byteorder::{BigEndian, ByteOrder};
+    ///
+    /// let mut r = [0u8; 20];
+    /// BigEndian::write_u32(&mut r[0..], 7); // space_id
+    /// BigEndian::write_u32(&mut r[4..], 42); // page_no
+    /// BigEndian::write_u32(&mut r[8..], 38); // offset
+    /// BigEndian::write_u32(&mut r[16..], 70_000); // length
+    ///
+    /// let ext = ExternFieldRef::parse(&r).unwrap();
+    /// assert_eq!(ext.page_no, 42);
+    /// assert_eq!(ext.length, 70_000);
+    /// assert!(ext.is_owner);
+    /// ```
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < BTR_EXTERN_FIELD_REF_SIZE {
+            return None;
+        }
+        let flags = data[BTR_EXTERN_LEN];
+        Some(ExternFieldRef {
+            space_id: BigEndian::read_u32(&data[BTR_EXTERN_SPACE_ID..]),
+            page_no: BigEndian::read_u32(&data[BTR_EXTERN_PAGE_NO..]),
+            offset: BigEndian::read_u32(&data[BTR_EXTERN_OFFSET..]),
+            // Only the low 32 bits carry the length
+            length: BigEndian::read_u32(&data[BTR_EXTERN_LEN + 4..]) as u64,
+            is_owner: flags & BTR_EXTERN_OWNER_FLAG == 0,
+            is_inherited: flags & BTR_EXTERN_INHERITED_FLAG != 0,
+        })
+    }
+}
+
+/// Read the externally stored part of a column value.
+///
+/// Dispatches on the type of the first overflow page:
+/// - Old-style BLOB (10): follows the chain via [`walk_blob_chain`]
+/// - ZBLOB/ZBLOB2 (11/12): inflates the zlib stream spanning the chain
+/// - LOB_FIRST (24): walks the MySQL 8.0 LOB index list
+/// - ZLOB_FIRST (25): inflates each chunk of the MySQL 8.0 compressed LOB
+///
+/// At most `max_len` bytes are returned; longer values are truncated.
+///
+/// # Examples
+///
+/// ```no_run
+/// use idb::innodb::tablespace::Tablespace;
+/// use idb::innodb::lob::{read_external_field, ExternFieldRef};
+///
+/// let mut ts = Tablespace::open("table.ibd").unwrap();
+/// let ref_bytes = [0u8; 20]; // last 20 bytes of an externally stored field
+/// let ext = ExternFieldRef::parse(&ref_bytes).unwrap();
+/// let value = read_external_field(&mut ts, &ext, 16 * 1024 * 1024).unwrap();
+/// println!("{} bytes", value.len());
+/// ```
+pub fn read_external_field(
+    ts: &mut crate::innodb::tablespace::Tablespace,
+    ext: &ExternFieldRef,
+    max_len: usize,
+) -> Result<Vec<u8>, crate::IdbError> {
+    let want = (ext.length as usize).min(max_len);
+    if want == 0 || ext.page_no == FIL_NULL {
+        return Ok(Vec::new());
+    }
+
+    let first_page = ts.read_page(ext.page_no as u64)?;
+    let page_type = FilHeader::parse(&first_page)
+        .map(|h| h.page_type)
+        .ok_or_else(|| {
+            crate::IdbError::Parse(format!("Cannot parse FIL header of page {}", ext.page_no))
+        })?;
+
+    let mut data = match page_type {
+        PageType::Blob => read_old_blob(ts, ext.page_no as u64, want)?,
+        PageType::ZBlob | PageType::ZBlob2 => read_zblob(ts, ext, first_page, want)?,
+        PageType::LobFirst => read_lob(ts, ext.page_no as u64, &first_page, want)?,
+        PageType::ZlobFirst => read_zlob(ts, ext.page_no as u64, &first_page, want)?,
+        other => {
+            return Err(crate::IdbError::Parse(format!(
+                "Page {} is not a LOB page ({})",
+                ext.page_no,
+                other.name()
+            )))
+        }
+    };
+    data.truncate(want);
+    Ok(data)
+}
+
+/// Copy the data of an old-style BLOB chain (types 10-12, uncompressed).
+fn read_old_blob(
+    ts: &mut crate::innodb::tablespace::Tablespace,
+    start_page: u64,
+    want: usize,
+) -> Result<Vec<u8>, crate::IdbError> {
+    let per_page = (ts.page_size() as usize)
+        .saturating_sub(FIL_PAGE_DATA + LOB_HDR_SIZE + SIZE_FIL_TRAILER)
+        .max(1);
+    let chain = walk_blob_chain(ts, start_page, want.div_ceil(per_page) + 1)?;
+
+    let mut data = Vec::with_capacity(want);
+    for (page_no, part_len) in chain {
+        let page = ts.read_page(page_no)?;
+        let start = FIL_PAGE_DATA + LOB_HDR_SIZE;
+        let end = (start + part_len as usize).min(page.len());
+        if start >= end {
+            break;
+        }
+        data.extend_from_slice(&page[start..end]);
+        if data.len() >= want {
+            break;
+        }
+    }
+    Ok(data)
+}
+
+/// Inflate a ROW_FORMAT=COMPRESSED BLOB (ZBLOB/ZBLOB2).
+///
+/// The value is a single zlib stream spanning the chain. On the first page
+/// the next-page pointer sits at the reference offset (normally
+/// FIL_PAGE_NEXT); the stream runs from FIL_PAGE_DATA to the end of each
+/// compressed page.
+fn read_zblob(
+    ts: &mut crate::innodb::tablespace::Tablespace,
+    ext: &ExternFieldRef,
+    first_page: Vec<u8>,
+    want: usize,
+) -> Result<Vec<u8>, crate::IdbError> {
+    let mut inflater = Decompress::new(true);
+    let mut out = Vec::with_capacity(want);
+    let mut page = first_page;
+    let mut offset = ext.offset as usize;
+    let max_pages = ts.page_count();
+
+    for _ in 0..max_pages {
+        if offset + 4 > page.len() {
+            break;
+        }
+        let next = BigEndian::read_u32(&page[offset..]);
+        let data_start = if offset == FIL_PAGE_NEXT {
+            FIL_PAGE_DATA
+        } else {
+            offset + 4
+        };
+
+        let mut input = &page[data_start.min(page.len())..];
+        while !input.is_empty() && out.len() < want {
+            let before_in = inflater.total_in();
+            let before_out = out.len();
+            out.reserve((want - out.len()).min(input.len() * 4).max(1));
+            let status = inflater
+                .decompress_vec(input, &mut out, FlushDecompress::None)
+                .map_err(|e| crate::IdbError::Parse(format!("ZBLOB inflate failed: {}", e)))?;
+            input = &input[(inflater.total_in() - before_in) as usize..];
+            if status == Status::StreamEnd {
+                return Ok(out);
+            }
+            if inflater.total_in() == before_in && out.len() == before_out {
+                break;
+            }
+        }
+
+        if out.len() >= want || next == FIL_NULL || next == 0 {
+            break;
+        }
+        page = ts.read_page(next as u64)?;
+        offset = FIL_PAGE_NEXT;
+    }
+    Ok(out)
+}
+
+/// Number of index entries on a MySQL 8.0 LOB first page.
+fn lob_first_node_count(page_size: u32) -> usize {
+    match page_size {
+        4096 => 1,
+        8192 => 5,
+        32768 => 20,
+        65536 => 40,
+        _ => 10,
+    }
+}
+
+/// Reassemble a MySQL 8.0 uncompressed LOB by walking its index list.
+///
+/// Each index entry names the page holding one chunk of the value. The
+/// first chunk lives on the LOB_FIRST page after the index entry array;
+/// the rest live on LOB_DATA pages.
+fn read_lob(
+    ts: &mut crate::innodb::tablespace::Tablespace,
+    first_page_no: u64,
+    first_page: &[u8],
+    want: usize,
+) -> Result<Vec<u8>, crate::IdbError> {
+    let base = FIL_PAGE_DATA + LOB_FIRST_INDEX_LIST;
+    if first_page.len() < FIL_PAGE_DATA + LOB_FIRST_PAGE_DATA {
+        return Err(crate::IdbError::Parse(format!(
+            "LOB first page {} is truncated",
+            first_page_no
+        )));
+    }
+    let first_data_start = FIL_PAGE_DATA
+        + LOB_FIRST_PAGE_DATA
+        + lob_first_node_count(ts.page_size()) * LOB_INDEX_ENTRY_SIZE;
+
+    // FLST base node: length (4), first (page 4 + boffset 2), last (6)
+    let mut node_page = BigEndian::read_u32(&first_page[base + 4..]);
+    let mut node_off = BigEndian::read_u16(&first_page[base + 8..]) as usize;
+
+    let mut data = Vec::with_capacity(want);
+    let mut cached: Option<(u32, Vec<u8>)> = None;
+    let max_entries = ts.page_count();
+
+    for _ in 0..max_entries {
+        if node_page == FIL_NULL || data.len() >= want {
+            break;
+        }
+
+        let entry_page: &[u8] = if node_page as u64 == first_page_no {
+            first_page
+        } else {
+            if cached.as_ref().map(|(p, _)| *p) != Some(node_page) {
+                cached = Some((node_page, ts.read_page(node_page as u64)?));
+            }
+            &cached.as_ref().unwrap().1
+        };
+        if node_off + LOB_INDEX_ENTRY_SIZE > entry_page.len() {
+            break;
+        }
+        let entry = &entry_page[node_off..node_off + LOB_INDEX_ENTRY_SIZE];
+        let chunk_page = BigEndian::read_u32(&entry[LOB_ENTRY_PAGE_NO..]);
+        let chunk_len = BigEndian::read_u16(&entry[LOB_ENTRY_DATA_LEN..]) as usize;
+        let next_page = BigEndian::read_u32(&entry[LOB_ENTRY_NEXT..]);
+        let next_off = BigEndian::read_u16(&entry[LOB_ENTRY_NEXT + 4..]) as usize;
+
+        if chunk_page as u64 == first_page_no {
+            let end = (first_data_start + chunk_len).min(first_page.len());
+            data.extend_from_slice(&first_page[first_data_start.min(end)..end]);
+        } else if chunk_page != FIL_NULL {
+            let page = ts.read_page(chunk_page as u64)?;
+            let start = FIL_PAGE_DATA + LOB_DATA_HDR_SIZE;
+            let end = (start + chunk_len).min(page.len());
+            data.extend_from_slice(&page[start.min(end)..end]);
+        }
+
+        node_page = next_page;
+        node_off = next_off;
+    }
+    Ok(data)
+}
+
+/// Reassemble a MySQL 8.0 compressed LOB (ZLOB_FIRST).
+///
+/// The value is split into chunks, each compressed as its own zlib stream.
+/// Small chunks are stored as fragments on ZLOB_FRAG pages; larger ones
+/// span a chain of ZLOB_DATA pages linked through FIL_PAGE_NEXT.
+fn read_zlob(
+    ts: &mut crate::innodb::tablespace::Tablespace,
+    first_page_no: u64,
+    first_page: &[u8],
+    want: usize,
+) -> Result<Vec<u8>, crate::IdbError> {
+    let entries = zlob_index_entries(ts, first_page_no, first_page, usize::MAX)?;
+
+    let mut data = Vec::with_capacity(want);
+    for entry in entries {
+        if data.len() >= want {
+            break;
+        }
+        if entry.z_page_no == FIL_NULL {
+            continue;
+        }
+        let zdata = if entry.frag_id == ZLOB_FRAG_ID_NULL {
+            read_zlob_stream(ts, &entry)?
+        } else {
+            read_zlob_fragment(ts, &entry)?
+        };
+
+        let mut inflater = Decompress::new(true);
+        let start = data.len();
+        data.reserve(entry.data_len as usize);
+        let status = inflater
+            .decompress_vec(&zdata, &mut data, FlushDecompress::Finish)
+            .map_err(|e| crate::IdbError::Parse(format!("ZLOB inflate failed: {}", e)))?;
+        if status != Status::StreamEnd || data.len() - start != entry.data_len as usize {
+            return Err(crate::IdbError::Parse(format!(
+                "ZLOB chunk at page {} inflated to {} bytes, expected {}",
+                entry.z_page_no,
+                data.len() - start,
+                entry.data_len
+            )));
+        }
+    }
+    Ok(data)
+}
+
+/// Collect the compressed bytes of a chunk stored on ZLOB_DATA pages.
+fn read_zlob_stream(
+    ts: &mut crate::innodb::tablespace::Tablespace,
+    entry: &ZlobIndexEntry,
+) -> Result<Vec<u8>, crate::IdbError> {
+    let want = entry.zdata_len as usize;
+    let mut zdata = Vec::with_capacity(want);
+    let mut page_no = entry.z_page_no;
+
+    for _ in 0..ts.page_count() {
+        if zdata.len() >= want || page_no == FIL_NULL {
+            break;
+        }
+        let page = ts.read_page(page_no as u64)?;
+        let hdr = match FilHeader::parse(&page) {
+            Some(h) if h.page_type == PageType::ZlobData => h,
+            _ => {
+                return Err(crate::IdbError::Parse(format!(
+                    "Page {} in a ZLOB stream is not a ZLOB_DATA page",
+                    page_no
+                )))
+            }
+        };
+        let data_hdr = ZlobDataPageHeader::parse(&page).ok_or_else(|| {
+            crate::IdbError::Parse(format!("ZLOB data page {} is truncated", page_no))
+        })?;
+        let start = FIL_PAGE_DATA + ZLOB_DATA_HDR_SIZE;
+        let len = (data_hdr.data_len as usize).min(want - zdata.len());
+        let chunk = page.get(start..start + len).ok_or_else(|| {
+            crate::IdbError::Parse(format!("ZLOB data page {} overflows the page", page_no))
+        })?;
+        zdata.extend_from_slice(chunk);
+        page_no = hdr.next_page;
+    }
+    Ok(zdata)
+}
+
+/// Read the compressed bytes of a chunk stored as a ZLOB_FRAG fragment.
+fn read_zlob_fragment(
+    ts: &mut crate::innodb::tablespace::Tablespace,
+    entry: &ZlobIndexEntry,
+) -> Result<Vec<u8>, crate::IdbError> {
+    let page = ts.read_page(entry.z_page_no as u64)?;
+    let corrupt = || {
+        crate::IdbError::Parse(format!(
+            "ZLOB fragment {} on page {} is corrupt",
+            entry.frag_id, entry.z_page_no
+        ))
+    };
+    if FilHeader::parse(&page).map(|h| h.page_type) != Some(PageType::ZlobFrag) {
+        return Err(crate::IdbError::Parse(format!(
+            "Page {} is not a ZLOB_FRAG page",
+            entry.z_page_no
+        )));
+    }
+
+    let slot = page
+        .len()
+        .checked_sub(ZLOB_FRAG_DIR_FIRST + entry.frag_id as usize * 2)
+        .ok_or_else(corrupt)?;
+    let node = BigEndian::read_u16(page.get(slot..slot + 2).ok_or_else(corrupt)?) as usize;
+    let total_len =
+        BigEndian::read_u16(page.get(node + ZLOB_FRAG_NODE_LEN..).ok_or_else(corrupt)?) as usize;
+    let len = total_len
+        .checked_sub(ZLOB_FRAG_NODE_DATA)
+        .ok_or_else(corrupt)?
+        .min(entry.zdata_len as usize);
+    let start = node + ZLOB_FRAG_NODE_DATA;
+    Ok(page.get(start..start + len).ok_or_else(corrupt)?.to_vec())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -851,4 +1349,239 @@ mod tests {
         let page = vec![0u8; 40]; // base=38, need 38+11=49
         assert!(LobDataPageHeader::parse(&page).is_none());
     }
+
+    fn lob_tablespace(pages: Vec<Vec<u8>>) -> crate::innodb::tablespace::Tablespace {
+        let mut data = vec![0u8; 16384]; // page 0
+        for p in pages {
+            data.extend_from_slice(&p);
+        }
+        crate::innodb::tablespace::Tablespace::from_bytes(data).unwrap()
+    }
+
+    fn typed_page(page_no: u32, page_type: u16) -> Vec<u8> {
+        let mut page = vec![0u8; 16384];
+        BigEndian::write_u32(&mut page[4..], page_no);
+        BigEndian::write_u32(&mut page[FIL_PAGE_NEXT..], FIL_NULL);
+        BigEndian::write_u16(&mut page[24..], page_type);
+        page
+    }
+
+    fn extern_ref(page_no: u32, offset: u32, length: u32) -> ExternFieldRef {
+        let mut r = [0u8; 20];
+        BigEndian::write_u32(&mut r[BTR_EXTERN_PAGE_NO..], page_no);
+        BigEndian::write_u32(&mut r[BTR_EXTERN_OFFSET..], offset);
+        BigEndian::write_u32(&mut r[BTR_EXTERN_LEN + 4..], length);
+        ExternFieldRef::parse(&r).unwrap()
+    }
+
+    #[test]
+    fn test_extern_field_ref_flags() {
+        let mut r = [0u8; 20];
+        r[BTR_EXTERN_LEN] = BTR_EXTERN_OWNER_FLAG | BTR_EXTERN_INHERITED_FLAG;
+        BigEndian::write_u32(&mut r[BTR_EXTERN_LEN + 4..], 123);
+        let ext = ExternFieldRef::parse(&r).unwrap();
+        assert!(!ext.is_owner);
+        assert!(ext.is_inherited);
+        assert_eq!(ext.length, 123);
+        assert!(ExternFieldRef::parse(&r[..19]).is_none());
+    }
+
+    #[test]
+    fn test_read_external_field_old_blob() {
+        let value: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        let base = FIL_PAGE_DATA;
+        let mut p1 = typed_page(1, 10);
+        BigEndian::write_u32(&mut p1[base + LOB_HDR_PART_LEN..], 16000);
+        BigEndian::write_u32(&mut p1[base + LOB_HDR_NEXT_PAGE_NO..], 2);
+        p1[base + LOB_HDR_SIZE..base + LOB_HDR_SIZE + 16000].copy_from_slice(&value[..16000]);
+        let mut p2 = typed_page(2, 10);
+        BigEndian::write_u32(&mut p2[base + LOB_HDR_PART_LEN..], 4000);
+        BigEndian::write_u32(&mut p2[base + LOB_HDR_NEXT_PAGE_NO..], FIL_NULL);
+        p2[base + LOB_HDR_SIZE..base + LOB_HDR_SIZE + 4000].copy_from_slice(&value[16000..]);
+
+        let mut ts = lob_tablespace(vec![p1, p2]);
+        let ext = extern_ref(1, FIL_PAGE_DATA as u32, 20_000);
+        assert_eq!(
+            read_external_field(&mut ts, &ext, usize::MAX).unwrap(),
+            value
+        );
+
+        // Size limit truncates the value
+        let short = read_external_field(&mut ts, &ext, 100).unwrap();
+        assert_eq!(short, &value[..100]);
+    }
+
+    #[test]
+    fn test_read_external_field_lob_first() {
+        let base = FIL_PAGE_DATA;
+        let entries = base + LOB_FIRST_PAGE_DATA;
+        let first_data = entries + 10 * LOB_INDEX_ENTRY_SIZE;
+
+        let mut p1 = typed_page(1, 24);
+        // Index list: first node is entry 0 on page 1
+        BigEndian::write_u32(&mut p1[base + LOB_FIRST_INDEX_LIST..], 2);
+        BigEndian::write_u32(&mut p1[base + LOB_FIRST_INDEX_LIST + 4..], 1);
+        BigEndian::write_u16(&mut p1[base + LOB_FIRST_INDEX_LIST + 8..], entries as u16);
+        // Entry 0: data on the first page, next = entry 1
+        let e0 = entries;
+        BigEndian::write_u32(&mut p1[e0 + LOB_ENTRY_NEXT..], 1);
+        BigEndian::write_u16(
+            &mut p1[e0 + LOB_ENTRY_NEXT + 4..],
+            (entries + LOB_INDEX_ENTRY_SIZE) as u16,
+        );
+        BigEndian::write_u32(&mut p1[e0 + LOB_ENTRY_PAGE_NO..], 1);
+        BigEndian::write_u16(&mut p1[e0 + LOB_ENTRY_DATA_LEN..], 6);
+        p1[first_data..first_data + 6].copy_from_slice(b"hello ");
+        // Entry 1: data on LOB_DATA page 2, last in the list
+        let e1 = entries + LOB_INDEX_ENTRY_SIZE;
+        BigEndian::write_u32(&mut p1[e1 + LOB_ENTRY_NEXT..], FIL_NULL);
+        BigEndian::write_u32(&mut p1[e1 + LOB_ENTRY_PAGE_NO..], 2);
+        BigEndian::write_u16(&mut p1[e1 + LOB_ENTRY_DATA_LEN..], 5);
+
+        let mut p2 = typed_page(2, 23);
+        p2[base + LOB_DATA_HDR_SIZE..base + LOB_DATA_HDR_SIZE + 5].copy_from_slice(b"world");
+
+        let mut ts = lob_tablespace(vec![p1, p2]);
+        let ext = extern_ref(1, 1, 11);
+        assert_eq!(
+            read_external_field(&mut ts, &ext, usize::MAX).unwrap(),
+            b"hello world"
+        );
+    }
+
+    #[test]
+    fn test_read_external_field_zblob() {
+        use flate2::write::ZlibEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let value: Vec<u8> = (0..40_000u32).map(|i| (i * 7 % 256) as u8).collect();
+        let mut enc = ZlibEncoder::new(Vec::new(), Compression::none());
+        enc.write_all(&value).unwrap();
+        let stream = enc.finish().unwrap();
+        let per_page = 16384 - FIL_PAGE_DATA;
+        assert!(stream.len() > 2 * per_page);
+
+        let mut pages = Vec::new();
+        for (i, chunk) in stream.chunks(per_page).enumerate() {
+            let page_no = i as u32 + 1;
+            let mut p = typed_page(page_no, if i == 0 { 11 } else { 12 });
+            let n_chunks = stream.len().div_ceil(per_page);
+            if i + 1 < n_chunks {
+                BigEndian::write_u32(&mut p[FIL_PAGE_NEXT..], page_no + 1);
+            }
+            p[FIL_PAGE_DATA..FIL_PAGE_DATA + chunk.len()].copy_from_slice(chunk);
+            pages.push(p);
+        }
+
+        let mut ts = lob_tablespace(pages);
+        let ext = extern_ref(1, FIL_PAGE_NEXT as u32, value.len() as u32);
+        assert_eq!(
+            read_external_field(&mut ts, &ext, usize::MAX).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn test_read_external_field_zlob() {
+        use flate2::write::ZlibEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let zlib = |data: &[u8]| {
+            let mut enc = ZlibEncoder::new(Vec::new(), Compression::none());
+            enc.write_all(data).unwrap();
+            enc.finish().unwrap()
+        };
+        // Chunk 0 is a stream over two ZLOB_DATA pages, chunk 1 a fragment
+        let chunk0: Vec<u8> = (0..20_000u32).map(|i| (i * 13 % 251) as u8).collect();
+        let chunk1 = b"tail of the value".to_vec();
+        let z0 = zlib(&chunk0);
+        let z1 = zlib(&chunk1);
+
+        let base = FIL_PAGE_DATA;
+        let write_entry =
+            |page: &mut [u8], off: usize, z_page: u32, frag: u16, len: usize, zlen: usize| {
+                BigEndian::write_u32(&mut page[off + LOB_ENTRY_NEXT..], FIL_NULL);
+                BigEndian::write_u32(&mut page[off + ZLOB_ENTRY_Z_PAGE_NO..], z_page);
+                BigEndian::write_u16(&mut page[off + ZLOB_ENTRY_Z_FRAG_ID..], frag);
+                BigEndian::write_u32(&mut page[off + ZLOB_ENTRY_DATA_LEN..], len as u32);
+                BigEndian::write_u32(&mut page[off + ZLOB_ENTRY_ZDATA_LEN..], zlen as u32);
+            };
+
+        // Page 1: ZLOB_FIRST with the index list head and entry 0
+        let mut p1 = typed_page(1, 25);
+        let e0 = base + 98;
+        BigEndian::write_u32(&mut p1[base + ZLOB_FIRST_INDEX_LIST + 4..], 1);
+        BigEndian::write_u16(&mut p1[base + ZLOB_FIRST_INDEX_LIST + 8..], e0 as u16);
+        write_entry(&mut p1, e0, 2, ZLOB_FRAG_ID_NULL, chunk0.len(), z0.len());
+        // Entry 1 lives on the ZLOB_INDEX page 5
+        let e1 = base + 200;
+        BigEndian::write_u32(&mut p1[e0 + LOB_ENTRY_NEXT..], 5);
+        BigEndian::write_u16(&mut p1[e0 + LOB_ENTRY_NEXT + 4..], e1 as u16);
+
+        // Pages 2-3: the zlib stream of chunk 0
+        let split = 12_000;
+        let mut p2 = typed_page(2, 26);
+        BigEndian::write_u32(&mut p2[FIL_PAGE_NEXT..], 3);
+        BigEndian::write_u32(&mut p2[base + ZLOB_DATA_DATA_LEN..], split as u32);
+        p2[base + ZLOB_DATA_HDR_SIZE..base + ZLOB_DATA_HDR_SIZE + split]
+            .copy_from_slice(&z0[..split]);
+        let mut p3 = typed_page(3, 26);
+        let rest = z0.len() - split;
+        BigEndian::write_u32(&mut p3[base + ZLOB_DATA_DATA_LEN..], rest as u32);
+        p3[base + ZLOB_DATA_HDR_SIZE..base + ZLOB_DATA_HDR_SIZE + rest]
+            .copy_from_slice(&z0[split..]);
+
+        // Page 4: ZLOB_FRAG with fragment 1 at offset 300
+        let mut p4 = typed_page(4, 28);
+        let node = 300;
+        let slot = 16384 - ZLOB_FRAG_DIR_FIRST - 2;
+        BigEndian::write_u16(&mut p4[slot..], node as u16);
+        BigEndian::write_u16(
+            &mut p4[node + ZLOB_FRAG_NODE_LEN..],
+            (ZLOB_FRAG_NODE_DATA + z1.len()) as u16,
+        );
+        p4[node + ZLOB_FRAG_NODE_DATA..node + ZLOB_FRAG_NODE_DATA + z1.len()].copy_from_slice(&z1);
+
+        let mut p5 = typed_page(5, 27);
+        write_entry(&mut p5, e1, 4, 1, chunk1.len(), z1.len());
+
+        let mut ts = lob_tablespace(vec![p1, p2, p3, p4, p5]);
+        let mut expected = chunk0.clone();
+        expected.extend_from_slice(&chunk1);
+        let ext = extern_ref(1, 1, expected.len() as u32);
+        assert_eq!(
+            read_external_field(&mut ts, &ext, usize::MAX).unwrap(),
+            expected
+        );
+        assert_eq!(
+            read_external_field(&mut ts, &ext, 100).unwrap(),
+            &expected[..100]
+        );
+
+        let chain = walk_lob_chain(&mut ts, 1, 100).unwrap().unwrap();
+        assert_eq!(chain.chain_type, "zlob");
+        let pages: Vec<(u64, &str)> = chain
+            .pages
+            .iter()
+            .map(|p| (p.page_no, p.page_type.as_str()))
+            .collect();
+        assert_eq!(
+            pages,
+            vec![(1, "ZLOB_FIRST"), (2, "ZLOB_DATA"), (4, "ZLOB_FRAG")]
+        );
+    }
+
+    #[test]
+    fn test_read_external_field_rejects_non_lob_page() {
+        let mut ts = lob_tablespace(vec![typed_page(1, 17855)]);
+        let ext = extern_ref(1, FIL_PAGE_DATA as u32, 100);
+        assert!(read_external_field(&mut ts, &ext, usize::MAX).is_err());
+        // Zero-length reference does not touch the tablespace
+        let empty = extern_ref(1, FIL_PAGE_DATA as u32, 0);
+        assert!(read_external_field(&mut ts, &empty, usize::MAX)
+            .unwrap()
+            .is_empty());
+    }
 }
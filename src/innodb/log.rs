@@ -24,11 +24,18 @@
 //! then [`LogFile::read_block`] to read individual data blocks. Each block's
 //! [`LogBlockHeader`] provides the block number, data length, first record
 //! group offset, epoch number, and CRC-32C checksum validation status.
+//!
+//! Logs written with `innodb_redo_log_encrypt` keep their encryption info in
+//! block 2. [`LogFile::enable_decryption`] derives the key from a keyring,
+//! after which [`LogFile::read_block`] returns decrypted data blocks.
 
 use byteorder::{BigEndian, ByteOrder};
 use serde::Serialize;
 use std::io::{Cursor, Read, Seek, SeekFrom};
 
+use crate::innodb::decryption::DecryptionContext;
+use crate::innodb::encryption::{parse_encryption_info_at, EncryptionInfo};
+use crate::innodb::keyring::Keyring;
 use crate::IdbError;
 
 /// Supertrait combining `Read + Seek` for type-erased readers.
@@ -48,10 +55,16 @@ pub const LOG_BLOCK_HDR_SIZE: usize = 14;
 pub const LOG_BLOCK_TRL_SIZE: usize = 4;
 /// Bitmask for the flush flag in the block number field (bit 31).
 pub const LOG_BLOCK_FLUSH_BIT_MASK: u32 = 0x80000000;
+/// Bitmask for the encrypted flag in the data length field (bit 15), set
+/// on data blocks written with `innodb_redo_log_encrypt`.
+pub const LOG_BLOCK_ENCRYPT_BIT_MASK: u16 = 0x8000;
 /// Byte offset of the CRC-32C checksum within a block (bytes 508-511).
 pub const LOG_BLOCK_CHECKSUM_OFFSET: usize = 508;
 /// Number of reserved header/checkpoint blocks at the start of the file.
 pub const LOG_FILE_HDR_BLOCKS: u64 = 4;
+/// Block holding the redo log encryption info (between the two checkpoint
+/// blocks), when `innodb_redo_log_encrypt` is enabled.
+pub const LOG_ENCRYPTION_BLOCK: u64 = 2;
 
 /// Offset of the format version within the log file header (block 0).
 ///
@@ -569,6 +582,33 @@ pub enum MlogRecordType {
     /// Delete from start of page list with row versioning (type 76).
     MlogListStartDelete,
 
+    // ── MariaDB 10.5+ physical record types ─────────────────────────
+    /// MariaDB `FREE_PAGE`: the page is freed.
+    MariaFreePage,
+    /// MariaDB `INIT_PAGE`: the page is zero-initialized.
+    MariaInitPage,
+    /// MariaDB `EXTENDED`: a logical page operation with a subtype byte.
+    MariaExtended,
+    /// MariaDB `WRITE`: write a string of bytes to a page.
+    MariaWrite,
+    /// MariaDB `MEMSET`: fill a page range with a repeated pattern.
+    MariaMemset,
+    /// MariaDB `MEMMOVE`: copy a page range within the page.
+    MariaMemmove,
+    /// MariaDB `OPTION`: an optional record that recovery may ignore.
+    MariaOption,
+    /// MariaDB `FILE_CREATE`: a tablespace file was created.
+    MariaFileCreate,
+    /// MariaDB `FILE_DELETE`: a tablespace file was deleted.
+    MariaFileDelete,
+    /// MariaDB `FILE_RENAME`: a tablespace file was renamed.
+    MariaFileRename,
+    /// MariaDB `FILE_MODIFY`: a tablespace file was first modified since
+    /// the latest checkpoint.
+    MariaFileModify,
+    /// MariaDB `FILE_CHECKPOINT`: marks the end of a checkpoint.
+    MariaFileCheckpoint,
+
     /// Unknown or unrecognized record type.
     Unknown(u8),
 }
@@ -578,6 +618,8 @@ impl MlogRecordType {
     ///
     /// Maps all known type codes from MySQL `mtr0types.h` across versions
     /// 5.7 through 9.1. Unrecognized codes are wrapped in `Unknown(N)`.
+    /// The `Maria*` variants use a different encoding and are only produced
+    /// by [`mariadb_redo`](crate::innodb::mariadb_redo).
     ///
     /// # Examples
     ///
@@ -744,6 +786,18 @@ impl MlogRecordType {
             MlogRecordType::MlogZipPageCompressNoData => "MLOG_ZIP_PAGE_COMPRESS_NO_DATA",
             MlogRecordType::MlogListEndDelete => "MLOG_LIST_END_DELETE",
             MlogRecordType::MlogListStartDelete => "MLOG_LIST_START_DELETE",
+            MlogRecordType::MariaFreePage => "FREE_PAGE",
+            MlogRecordType::MariaInitPage => "INIT_PAGE",
+            MlogRecordType::MariaExtended => "EXTENDED",
+            MlogRecordType::MariaWrite => "WRITE",
+            MlogRecordType::MariaMemset => "MEMSET",
+            MlogRecordType::MariaMemmove => "MEMMOVE",
+            MlogRecordType::MariaOption => "OPTION",
+            MlogRecordType::MariaFileCreate => "FILE_CREATE",
+            MlogRecordType::MariaFileDelete => "FILE_DELETE",
+            MlogRecordType::MariaFileRename => "FILE_RENAME",
+            MlogRecordType::MariaFileModify => "FILE_MODIFY",
+            MlogRecordType::MariaFileCheckpoint => "FILE_CHECKPOINT",
             MlogRecordType::Unknown(_) => "UNKNOWN",
         }
     }
@@ -758,149 +812,6 @@ impl std::fmt::Display for MlogRecordType {
     }
 }
 
-/// A single parsed MLOG record from a redo log data block.
-///
-/// Each record carries the type of redo operation and (for page-referencing
-/// types) the `space_id` and `page_no` that the operation targets.  These
-/// two fields are encoded as InnoDB compressed integers immediately after
-/// the type byte.
-///
-/// ## Limitations
-///
-/// * Records that span block boundaries are not decoded — only intra-block
-///   records are returned.
-/// * The exact record payload length is not determined (would require schema
-///   context), so records are identified by scanning for valid type bytes.
-#[derive(Debug, Clone, Serialize)]
-pub struct MlogRecord {
-    /// Offset within the block payload (bytes from block start).
-    pub block_offset: usize,
-    /// MLOG record type (single-rec flag stripped).
-    pub record_type: MlogRecordType,
-    /// Whether the single-record-group flag (bit 7 of type byte) was set.
-    pub single_rec: bool,
-    /// Tablespace ID (InnoDB compressed integer), if this type carries a page reference.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub space_id: Option<u32>,
-    /// Page number within the tablespace.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub page_no: Option<u32>,
-}
-
-impl MlogRecordType {
-    /// Returns `true` for MLOG types that do NOT carry a `(space_id, page_no)` pair.
-    ///
-    /// These are: multi-rec-end, dummy, file-level ops, LSN marker, and test.
-    fn is_non_page_type(&self) -> bool {
-        matches!(
-            self,
-            MlogRecordType::MlogMultiRecEnd
-                | MlogRecordType::MlogDummyRecord
-                | MlogRecordType::MlogFileCreate
-                | MlogRecordType::MlogFileRename
-                | MlogRecordType::MlogFileDelete
-                | MlogRecordType::MlogFileExtend
-                | MlogRecordType::MlogLsn
-                | MlogRecordType::MlogTest
-                | MlogRecordType::MlogTableDynamicMeta
-        )
-    }
-
-    /// Returns `true` if the raw type code is in the known range (1..=76, excluding gaps).
-    fn is_known_type_code(code: u8) -> bool {
-        matches!(
-            code,
-            1..=2 | 4 | 8..=11 | 13..=22 | 24..=35 | 36..=46 | 48..=53 | 57..=59 | 61..=76
-        )
-    }
-}
-
-/// Parse MLOG records from a single redo log data block.
-///
-/// Walks the block payload from `LOG_BLOCK_HDR_SIZE` up to `hdr.data_len`,
-/// extracting the record type and, for page-referencing types, the compressed
-/// `space_id` and `page_no` fields.
-///
-/// Records are identified by scanning for valid type bytes.  Because the
-/// exact record payload length is not determined, some false positives may
-/// occur when data bytes happen to look like valid type codes.  To mitigate
-/// this, decoded `space_id` values above 2^24 are treated as implausible
-/// and the record is emitted without page references.
-pub fn parse_mlog_records(block: &[u8], hdr: &LogBlockHeader) -> Vec<MlogRecord> {
-    let data_end = std::cmp::min(hdr.data_len as usize, block.len());
-    if data_end <= LOG_BLOCK_HDR_SIZE {
-        return Vec::new();
-    }
-
-    let mut records = Vec::new();
-    let mut pos = LOG_BLOCK_HDR_SIZE;
-
-    while pos < data_end {
-        let raw_type = block[pos];
-        // Skip zero/padding bytes
-        if raw_type == 0 {
-            pos += 1;
-            continue;
-        }
-
-        let single_rec = (raw_type & 0x80) != 0;
-        let type_code = raw_type & 0x7F;
-
-        // Only process known type codes
-        if !MlogRecordType::is_known_type_code(type_code) {
-            pos += 1;
-            continue;
-        }
-
-        let record_type = MlogRecordType::from_u8(type_code);
-        let block_offset = pos;
-        pos += 1; // consume type byte
-
-        if record_type.is_non_page_type() {
-            records.push(MlogRecord {
-                block_offset,
-                record_type,
-                single_rec,
-                space_id: None,
-                page_no: None,
-            });
-            continue;
-        }
-
-        // Try to read compressed space_id and page_no
-        let (space_id, page_no) = if let Some((sid, sid_len)) =
-            crate::innodb::undo::read_compressed(block, pos)
-        {
-            let next = pos + sid_len;
-            if sid <= 0x00FF_FFFF {
-                // plausible space_id
-                if let Some((pno, pno_len)) = crate::innodb::undo::read_compressed(block, next) {
-                    pos = next + pno_len;
-                    (Some(sid as u32), Some(pno as u32))
-                } else {
-                    pos = next;
-                    (Some(sid as u32), None)
-                }
-            } else {
-                // implausible space_id — skip
-                (None, None)
-            }
-        } else {
-            (None, None)
-        };
-
-        records.push(MlogRecord {
-            block_offset,
-            record_type,
-            single_rec,
-            space_id,
-            page_no,
-        });
-    }
-
-    records
-}
-
 /// Compute the approximate LSN for a record within a redo log block.
 ///
 /// Each data block carries `LOG_BLOCK_SIZE - LOG_BLOCK_HDR_SIZE - LOG_BLOCK_TRL_SIZE`
@@ -923,6 +834,7 @@ pub fn compute_record_lsn(start_lsn: u64, block_idx: u64, payload_offset: usize)
 pub struct LogFile {
     reader: Box<dyn ReadSeek>,
     file_size: u64,
+    decryption: Option<DecryptionContext>,
 }
 
 impl LogFile {
@@ -969,7 +881,11 @@ impl LogFile {
             )));
         }
 
-        Ok(LogFile { reader, file_size })
+        Ok(LogFile {
+            reader,
+            file_size,
+            decryption: None,
+        })
     }
 
     /// Total number of 512-byte blocks in the file.
@@ -983,6 +899,9 @@ impl LogFile {
     }
 
     /// Read a single 512-byte block by block number.
+    ///
+    /// If a decryption context has been set, encrypted data blocks are
+    /// decrypted before being returned.
     pub fn read_block(&mut self, block_no: u64) -> Result<Vec<u8>, IdbError> {
         let offset = block_no * LOG_BLOCK_SIZE as u64;
         if offset + LOG_BLOCK_SIZE as u64 > self.file_size {
@@ -1001,6 +920,37 @@ impl LogFile {
             .read_exact(&mut buf)
             .map_err(|e| IdbError::Io(format!("Read error at block {}: {}", block_no, e)))?;
 
+        if block_no >= LOG_FILE_HDR_BLOCKS {
+            if let Some(ref ctx) = self.decryption {
+                ctx.decrypt_log_block(&mut buf)?;
+            }
+        }
+
+        Ok(buf)
+    }
+
+    /// Read `len` bytes starting at byte `offset`.
+    ///
+    /// For logs without 512-byte block framing (MariaDB 10.8+).
+    pub fn read_bytes(&mut self, offset: u64, len: usize) -> Result<Vec<u8>, IdbError> {
+        if offset + len as u64 > self.file_size {
+            return Err(IdbError::Io(format!(
+                "Range {}..{} is beyond end of file (file size {})",
+                offset,
+                offset + len as u64,
+                self.file_size
+            )));
+        }
+
+        self.reader
+            .seek(SeekFrom::Start(offset))
+            .map_err(|e| IdbError::Io(format!("Seek error: {}", e)))?;
+
+        let mut buf = vec![0u8; len];
+        self.reader
+            .read_exact(&mut buf)
+            .map_err(|e| IdbError::Io(format!("Read error at offset {}: {}", offset, e)))?;
+
         Ok(buf)
     }
 
@@ -1029,6 +979,42 @@ impl LogFile {
         })
     }
 
+    /// Read the redo log encryption info from block 2.
+    ///
+    /// Returns `None` if the log is not encrypted (no encryption magic).
+    pub fn read_encryption_info(&mut self) -> Result<Option<EncryptionInfo>, IdbError> {
+        let block = self.read_block(LOG_ENCRYPTION_BLOCK)?;
+        Ok(parse_encryption_info_at(&block, 0))
+    }
+
+    /// Set a decryption context for transparent data block decryption.
+    pub fn set_decryption_context(&mut self, ctx: DecryptionContext) {
+        self.decryption = Some(ctx);
+    }
+
+    /// The decryption context set on this file, if any.
+    pub fn decryption_context(&self) -> Option<&DecryptionContext> {
+        self.decryption.as_ref()
+    }
+
+    /// Derive the redo log key from the encryption info in block 2 and the
+    /// master key in `keyring`, and decrypt data blocks from now on.
+    ///
+    /// Returns `Ok(false)` if the file has no encryption info. Only the
+    /// first file of a pre-8.0.30 `ib_logfile` group carries it; set the
+    /// same context on the other files with
+    /// [`set_decryption_context`](Self::set_decryption_context).
+    pub fn enable_decryption(&mut self, keyring: &Keyring) -> Result<bool, IdbError> {
+        match self.read_encryption_info()? {
+            Some(info) => {
+                let ctx = DecryptionContext::from_encryption_info(&info, keyring)?;
+                self.set_decryption_context(ctx);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
     /// File size in bytes.
     pub fn file_size(&self) -> u64 {
         self.file_size
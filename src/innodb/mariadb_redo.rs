@@ -0,0 +1,1057 @@
+//! MariaDB 10.8+ redo log parsing.
+//!
+//! MariaDB 10.8 replaced the block-framed redo log with a single
+//! `ib_logfile0` in which one circular byte stream follows a fixed header
+//! area:
+//!
+//! | Offset | Contents |
+//! |--------|----------|
+//! | 0 | File header: format (`"Phys"`), first LSN at 8, creator at 16, CRC-32C at 508 |
+//! | 0x1000 | Checkpoint 1: checkpoint LSN, end LSN, CRC-32C at 60 |
+//! | 0x2000 | Checkpoint 2 |
+//! | 0x3000 | Log data: the first LSN is stored here, and the log wraps back here after the end of the file |
+//!
+//! There are no 512-byte blocks. Each mini-transaction is a run of records
+//! followed by a sequence bit (a byte that flips on every pass over the
+//! file) and the CRC-32C of the records, so the log ends at the first
+//! mini-transaction whose sequence bit or checksum does not match.
+//!
+//! Records use MariaDB's physical format (`mtr0types.h`): a type byte
+//! holding a same-page flag, the record type, and a 4-bit length (0 means a
+//! variable-length length follows), then the space ID and page number
+//! unless the record continues the previous record's page, then the body.
+//! Byte offsets in `WRITE`, `MEMSET`, and `MEMMOVE` records are relative to
+//! the end of the previous write to the same page; they are resolved to
+//! page offsets here. Records are returned in the same [`MlogRecord`] model
+//! that [`mlog`](crate::innodb::mlog) produces for MySQL, with the `Maria*`
+//! [`MlogRecordType`] variants.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use idb::innodb::log::LogFile;
+//! use idb::innodb::mariadb_redo::read_mariadb_log;
+//!
+//! let mut log = LogFile::open("ib_logfile0").unwrap();
+//! let redo = read_mariadb_log(&mut log).unwrap();
+//! for mtr in &redo.mtrs {
+//!     println!("{}..{}: {} records", mtr.start_lsn, mtr.end_lsn, mtr.records.len());
+//! }
+//! ```
+
+use byteorder::{BigEndian, ByteOrder};
+use serde::Serialize;
+
+use crate::innodb::constants::FIL_PAGE_TYPE;
+use crate::innodb::log::{
+    validate_log_block_checksum, LogFile, LogFileHeader, MlogRecordType, LOG_BLOCK_SIZE,
+};
+use crate::innodb::mlog::{MlogBody, MlogDecodeError, MlogRecord, MlogStreamDecode};
+use crate::innodb::vendor::{detect_vendor_from_created_by, InnoDbVendor};
+use crate::IdbError;
+
+/// Header format of a MariaDB 10.8+ redo log (`"Phys"`).
+pub const MARIADB_FORMAT_10_8: u32 = 0x5068_7973;
+/// Header format of an encrypted MariaDB 10.8+ redo log (`"PhxE"`).
+pub const MARIADB_FORMAT_ENC_10_8: u32 = 0x5068_7845;
+/// File offset of the first checkpoint.
+pub const MARIADB_CHECKPOINT_1: u64 = 0x1000;
+/// File offset of the second checkpoint.
+pub const MARIADB_CHECKPOINT_2: u64 = 0x2000;
+/// File offset of the log data (where the header's first LSN is stored).
+pub const MARIADB_LOG_START_OFFSET: u64 = 0x3000;
+
+/// Bytes of a checkpoint covered by its CRC-32C (stored right after them).
+const CHECKPOINT_CRC_OFFSET: usize = 60;
+/// Size of a checkpoint.
+const CHECKPOINT_SIZE: usize = 64;
+/// Sequence bit byte and CRC-32C closing each mini-transaction.
+const MTR_TRAILER_SIZE: u64 = 5;
+/// Bytes read from the file at a time.
+const READ_CHUNK: u64 = 1 << 20;
+/// Largest page size; write offsets must stay below it.
+const MAX_PAGE_SIZE: u32 = 65536;
+
+// Page record types (bits 6..4 of the type byte)
+const FREE_PAGE: u8 = 0x00;
+const INIT_PAGE: u8 = 0x10;
+const EXTENDED: u8 = 0x20;
+const WRITE: u8 = 0x30;
+const MEMSET: u8 = 0x40;
+const MEMMOVE: u8 = 0x50;
+const RESERVED: u8 = 0x60;
+const OPTION: u8 = 0x70;
+
+// File record types (bit 7 set, no same-page meaning)
+const FILE_CREATE: u8 = 0x80;
+const FILE_DELETE: u8 = 0x90;
+const FILE_RENAME: u8 = 0xA0;
+const FILE_MODIFY: u8 = 0xB0;
+const FILE_CHECKPOINT: u8 = 0xF0;
+
+/// Whether `header` belongs to a MariaDB 10.8+ redo log.
+///
+/// The vendor comes from the creator string; the format field tells the
+/// 10.8 layout apart from older MariaDB logs, which keep MySQL's block
+/// framing.
+pub fn is_mariadb_10_8(header: &LogFileHeader) -> bool {
+    detect_vendor_from_created_by(&header.created_by) == InnoDbVendor::MariaDB
+        && matches!(
+            header.format_version,
+            MARIADB_FORMAT_10_8 | MARIADB_FORMAT_ENC_10_8
+        )
+}
+
+/// A MariaDB 10.8+ checkpoint (at file offset 0x1000 or 0x2000).
+#[derive(Debug, Clone, Serialize)]
+pub struct MariaDbCheckpoint {
+    /// File offset of the checkpoint.
+    pub offset: u64,
+    /// Checkpoint LSN: recovery starts reading the log here.
+    pub lsn: u64,
+    /// LSN of the `FILE_CHECKPOINT` record written for this checkpoint.
+    pub end_lsn: u64,
+    /// Whether the stored CRC-32C matches.
+    pub checksum_valid: bool,
+}
+
+impl MariaDbCheckpoint {
+    /// Parse a checkpoint from the 64 bytes at file offset `offset`.
+    pub fn parse(offset: u64, buf: &[u8]) -> Option<Self> {
+        if buf.len() < CHECKPOINT_SIZE {
+            return None;
+        }
+        let crc = crc32c::crc32c(&buf[..CHECKPOINT_CRC_OFFSET]);
+        Some(MariaDbCheckpoint {
+            offset,
+            lsn: BigEndian::read_u64(&buf[0..]),
+            end_lsn: BigEndian::read_u64(&buf[8..]),
+            checksum_valid: crc == BigEndian::read_u32(&buf[CHECKPOINT_CRC_OFFSET..]),
+        })
+    }
+
+    /// Whether recovery would accept this checkpoint for a log whose first
+    /// LSN is `first_lsn`.
+    pub fn is_usable(&self, first_lsn: u64) -> bool {
+        self.checksum_valid && self.lsn >= first_lsn && self.end_lsn >= self.lsn
+    }
+}
+
+/// Read a checkpoint of a MariaDB 10.8+ log (slot 0 = offset 0x1000,
+/// slot 1 = offset 0x2000).
+pub fn read_mariadb_checkpoint(log: &mut LogFile, slot: u8) -> Result<MariaDbCheckpoint, IdbError> {
+    let offset = match slot {
+        0 => MARIADB_CHECKPOINT_1,
+        1 => MARIADB_CHECKPOINT_2,
+        _ => {
+            return Err(IdbError::Argument(format!(
+                "Invalid checkpoint slot {} (must be 0 or 1)",
+                slot
+            )))
+        }
+    };
+    let buf = log.read_bytes(offset, CHECKPOINT_SIZE)?;
+    MariaDbCheckpoint::parse(offset, &buf)
+        .ok_or_else(|| IdbError::Parse(format!("Failed to parse checkpoint at offset {}", offset)))
+}
+
+/// LSN of the newest usable checkpoint of a MariaDB 10.8+ log, if any.
+pub fn read_mariadb_checkpoint_lsn(log: &mut LogFile) -> Result<Option<u64>, IdbError> {
+    let first_lsn = log.read_header()?.start_lsn;
+    Ok((0..2)
+        .filter_map(|slot| read_mariadb_checkpoint(log, slot).ok())
+        .filter(|cp| cp.is_usable(first_lsn))
+        .map(|cp| cp.lsn)
+        .max())
+}
+
+/// A mini-transaction: records that recovery applies all or not at all.
+#[derive(Debug, Clone, Serialize)]
+pub struct MariaDbMtr {
+    /// LSN of the first record.
+    pub start_lsn: u64,
+    /// LSN just past the sequence bit and checksum.
+    pub end_lsn: u64,
+    /// Decoded records, in log order.
+    pub records: Vec<MlogRecord>,
+    /// Records that could not be decoded.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<MlogDecodeError>,
+}
+
+/// A decoded MariaDB 10.8+ redo log.
+#[derive(Debug, Clone, Serialize)]
+pub struct MariaDbRedoLog {
+    /// File header; `start_lsn` is the LSN stored at offset 0x3000.
+    pub header: LogFileHeader,
+    /// Whether the header's CRC-32C matches.
+    pub header_checksum_valid: bool,
+    /// Whether the log is encrypted; its records are then not decoded.
+    pub encrypted: bool,
+    /// Bytes of log data the file holds before it wraps.
+    pub capacity: u64,
+    /// The two checkpoints, if readable.
+    pub checkpoints: [Option<MariaDbCheckpoint>; 2],
+    /// LSN of the newest usable checkpoint.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkpoint_lsn: Option<u64>,
+    /// LSN where decoding started: the first LSN while the file still
+    /// holds the log from there to the checkpoint, else the checkpoint LSN.
+    pub start_lsn: u64,
+    /// LSN where the log ends.
+    pub end_lsn: u64,
+    /// Complete mini-transactions from `start_lsn` to `end_lsn`.
+    pub mtrs: Vec<MariaDbMtr>,
+}
+
+impl MariaDbRedoLog {
+    /// Flatten the mini-transactions into one record stream.
+    pub fn into_stream_decode(self) -> MlogStreamDecode {
+        let mut decoded = MlogStreamDecode::default();
+        for mtr in self.mtrs {
+            decoded.records.extend(mtr.records);
+            decoded.errors.extend(mtr.errors);
+        }
+        decoded
+    }
+}
+
+/// Read the header and checkpoints of a MariaDB 10.8+ redo log and decode
+/// all of its mini-transactions.
+pub fn read_mariadb_log(log: &mut LogFile) -> Result<MariaDbRedoLog, IdbError> {
+    let header = log.read_header()?;
+    if !is_mariadb_10_8(&header) {
+        return Err(IdbError::Parse(format!(
+            "Not a MariaDB 10.8+ redo log (format 0x{:08X}, created by \"{}\")",
+            header.format_version, header.created_by
+        )));
+    }
+    if log.file_size() <= MARIADB_LOG_START_OFFSET {
+        return Err(IdbError::Parse(format!(
+            "File is too small for a MariaDB redo log ({} bytes, minimum {})",
+            log.file_size(),
+            MARIADB_LOG_START_OFFSET + 1
+        )));
+    }
+
+    let header_checksum_valid = validate_log_block_checksum(&log.read_bytes(0, LOG_BLOCK_SIZE)?);
+    let checkpoints = [
+        read_mariadb_checkpoint(log, 0).ok(),
+        read_mariadb_checkpoint(log, 1).ok(),
+    ];
+    let first_lsn = header.start_lsn;
+    let checkpoint_lsn = checkpoints
+        .iter()
+        .flatten()
+        .filter(|cp| cp.is_usable(first_lsn))
+        .map(|cp| cp.lsn)
+        .max();
+    let encrypted = header.format_version == MARIADB_FORMAT_ENC_10_8;
+    let capacity = log.file_size() - MARIADB_LOG_START_OFFSET;
+
+    let mut redo = MariaDbRedoLog {
+        header,
+        header_checksum_valid,
+        encrypted,
+        capacity,
+        checkpoints,
+        checkpoint_lsn,
+        start_lsn: first_lsn,
+        end_lsn: first_lsn,
+        mtrs: Vec::new(),
+    };
+    if encrypted {
+        return Ok(redo);
+    }
+
+    // Once the log has wrapped, the start of the file's first pass is
+    // overwritten and only the checkpoint is a known record boundary
+    let (mut mtrs, mut end_lsn) = read_mtrs(log, first_lsn, capacity, first_lsn)?;
+    if let Some(cp) = checkpoint_lsn.filter(|&cp| cp > end_lsn) {
+        redo.start_lsn = cp;
+        (mtrs, end_lsn) = read_mtrs(log, first_lsn, capacity, cp)?;
+    }
+    redo.mtrs = mtrs;
+    redo.end_lsn = end_lsn;
+    Ok(redo)
+}
+
+/// Read and decode all records of a MariaDB 10.8+ redo log.
+///
+/// Each mini-transaction of one record has `single_rec` set, matching the
+/// meaning of MySQL's single-record flag.
+pub fn read_mariadb_records(log: &mut LogFile) -> Result<MlogStreamDecode, IdbError> {
+    let redo = read_mariadb_log(log)?;
+    if redo.encrypted {
+        return Err(IdbError::Parse(
+            "Encrypted MariaDB redo logs are not supported".to_string(),
+        ));
+    }
+    Ok(redo.into_stream_decode())
+}
+
+/// Decode mini-transactions from `start` until the log ends or one full
+/// pass over the file has been read. Returns them and the end LSN.
+fn read_mtrs(
+    log: &mut LogFile,
+    first_lsn: u64,
+    capacity: u64,
+    start: u64,
+) -> Result<(Vec<MariaDbMtr>, u64), IdbError> {
+    let mut stream = CircularStream {
+        log,
+        first_lsn,
+        capacity,
+        base: start,
+        buf: Vec::new(),
+        limit: start + capacity,
+    };
+    let mut mtrs = Vec::new();
+    let mut lsn = start;
+    while let Some(marker) = stream.frame_mtr(lsn)? {
+        let end = marker + MTR_TRAILER_SIZE;
+        mtrs.push(decode_mtr(stream.slice(lsn, marker), lsn, end));
+        lsn = end;
+        stream.discard_before(lsn);
+    }
+    Ok((mtrs, lsn))
+}
+
+/// The log data area read as an LSN-addressed stream.
+struct CircularStream<'a> {
+    log: &'a mut LogFile,
+    first_lsn: u64,
+    capacity: u64,
+    /// LSN of `buf[0]`.
+    base: u64,
+    buf: Vec<u8>,
+    /// One full pass over the file from where reading started.
+    limit: u64,
+}
+
+impl CircularStream<'_> {
+    /// Load the stream up to `end`; false if that is past one full pass.
+    fn fill(&mut self, end: u64) -> Result<bool, IdbError> {
+        if end > self.limit {
+            return Ok(false);
+        }
+        while self.base + (self.buf.len() as u64) < end {
+            let lsn = self.base + self.buf.len() as u64;
+            let pos = (lsn - self.first_lsn) % self.capacity;
+            let len = (self.capacity - pos).min(self.limit - lsn).min(READ_CHUNK);
+            let bytes = self
+                .log
+                .read_bytes(MARIADB_LOG_START_OFFSET + pos, len as usize)?;
+            self.buf.extend_from_slice(&bytes);
+        }
+        Ok(true)
+    }
+
+    fn byte(&self, lsn: u64) -> u8 {
+        self.buf[(lsn - self.base) as usize]
+    }
+
+    fn slice(&self, start: u64, end: u64) -> &[u8] {
+        &self.buf[(start - self.base) as usize..(end - self.base) as usize]
+    }
+
+    /// Free the buffer before `lsn` once enough of it has been consumed.
+    fn discard_before(&mut self, lsn: u64) {
+        if lsn - self.base >= READ_CHUNK {
+            self.buf.drain(..(lsn - self.base) as usize);
+            self.base = lsn;
+        }
+    }
+
+    /// The sequence bit of a mini-transaction ending at `lsn`: 1 on the
+    /// first pass over the file, 0 on the second, and so on.
+    fn sequence_bit(&self, lsn: u64) -> u8 {
+        u8::from(((lsn - self.first_lsn) / self.capacity).is_multiple_of(2))
+    }
+
+    /// Walk the records of the mini-transaction at `start` and check its
+    /// sequence bit and checksum. Returns the LSN of the sequence bit, or
+    /// `None` if the log ends at `start`.
+    fn frame_mtr(&mut self, start: u64) -> Result<Option<u64>, IdbError> {
+        let mut lsn = start;
+        loop {
+            if !self.fill(lsn + 1)? {
+                return Ok(None);
+            }
+            let b = self.byte(lsn);
+            if b <= 1 {
+                break;
+            }
+            if b & 0x0F != 0 {
+                lsn += 1 + u64::from(b & 0x0F);
+                continue;
+            }
+            if !self.fill(lsn + 2)? {
+                return Ok(None);
+            }
+            let len_len = varint_len(self.byte(lsn + 1));
+            if len_len > 3 || !self.fill(lsn + 1 + len_len as u64)? {
+                return Ok(None);
+            }
+            let extra = match decode_varint(self.slice(lsn + 1, lsn + 1 + len_len as u64)) {
+                Some((v, _)) => v,
+                None => return Ok(None),
+            };
+            lsn += 16 + u64::from(extra);
+        }
+
+        if lsn == start || !self.fill(lsn + MTR_TRAILER_SIZE)? {
+            return Ok(None);
+        }
+        if self.byte(lsn) != self.sequence_bit(lsn) {
+            return Ok(None);
+        }
+        let stored = BigEndian::read_u32(self.slice(lsn + 1, lsn + MTR_TRAILER_SIZE));
+        if crc32c::crc32c(self.slice(start, lsn)) != stored {
+            return Ok(None);
+        }
+        Ok(Some(lsn))
+    }
+}
+
+/// Length of a variable-length integer from its first byte (6 = invalid).
+fn varint_len(first: u8) -> usize {
+    (first.leading_ones() as usize + 1).min(6)
+}
+
+/// Decode a `mlog_encode_varint()` integer; returns it and its length.
+fn decode_varint(buf: &[u8]) -> Option<(u32, usize)> {
+    let first = *buf.first()?;
+    let len = varint_len(first);
+    let bytes = buf.get(..len)?;
+    let tail = |n: usize| {
+        bytes[1..n]
+            .iter()
+            .fold(0u32, |acc, &b| (acc << 8) | u32::from(b))
+    };
+    let v = match len {
+        1 => u32::from(first),
+        2 => 0x80 + ((u32::from(first & 0x3F) << 8) | tail(2)),
+        3 => 0x4080 + ((u32::from(first & 0x1F) << 16) | tail(3)),
+        4 => 0x20_4080 + ((u32::from(first & 0x0F) << 24) | tail(4)),
+        5 if first == 0xF0 => tail(5).checked_add(0x1020_4080)?,
+        _ => return None,
+    };
+    Some((v, len))
+}
+
+/// Split the record at the start of `data` into its type byte length
+/// prefix and body. The record was already framed, so it is complete.
+fn split_record(data: &[u8]) -> (usize, usize) {
+    let b = data[0];
+    if b & 0x0F != 0 {
+        return (1, usize::from(b & 0x0F));
+    }
+    let (extra, len_len) = decode_varint(&data[1..]).unwrap_or((0, 1));
+    (1 + len_len, extra as usize + 15 - len_len)
+}
+
+/// Decode the records of one framed mini-transaction.
+fn decode_mtr(data: &[u8], start_lsn: u64, end_lsn: u64) -> MariaDbMtr {
+    let mut mtr = MariaDbMtr {
+        start_lsn,
+        end_lsn,
+        records: Vec::new(),
+        errors: Vec::new(),
+    };
+    let mut state = PageState::default();
+    let mut count = 0;
+    let mut pos = 0;
+    while pos < data.len() {
+        let (hdr_len, body_len) = split_record(&data[pos..]);
+        let next = pos + hdr_len + body_len;
+        let lsn = start_lsn + pos as u64;
+        let body = &data[pos + hdr_len..next];
+        match state.decode(data[pos], body) {
+            Ok((record_type, space_id, page_no, body)) => mtr.records.push(MlogRecord {
+                start_lsn: lsn,
+                end_lsn: start_lsn + next as u64,
+                record_type,
+                single_rec: false,
+                space_id,
+                page_no,
+                body,
+            }),
+            Err(message) => mtr.errors.push(MlogDecodeError {
+                lsn,
+                message,
+                resumed_at: Some(if next < data.len() {
+                    start_lsn + next as u64
+                } else {
+                    end_lsn
+                }),
+            }),
+        }
+        count += 1;
+        pos = next;
+    }
+    if count == 1 {
+        if let Some(rec) = mtr.records.first_mut() {
+            rec.single_rec = true;
+        }
+    }
+    mtr
+}
+
+type Decoded = (MlogRecordType, Option<u32>, Option<u32>, MlogBody);
+
+/// Page context carried from record to record within a mini-transaction.
+#[derive(Default)]
+struct PageState {
+    /// Page of the previous page record, which same-page records reuse.
+    page: Option<(u32, u32)>,
+    /// End of the previous write to that page; relative offsets start
+    /// here. 1 after `FREE_PAGE`, where no write may follow.
+    last_offset: u32,
+}
+
+impl PageState {
+    fn decode(&mut self, b: u8, body: &[u8]) -> Result<Decoded, String> {
+        let mut r = BodyReader { buf: body, pos: 0 };
+        let page_type = b & 0x70;
+        let ids = match self.page.filter(|_| b & 0x80 != 0) {
+            Some(ids) => {
+                if page_type <= INIT_PAGE {
+                    return Err("FREE_PAGE or INIT_PAGE with the same-page flag".to_string());
+                }
+                ids
+            }
+            None => {
+                self.last_offset = 0;
+                let ids = (r.varint()?, r.varint()?);
+                self.page = (b & 0x80 == 0).then_some(ids);
+                ids
+            }
+        };
+
+        if self.page.is_none() {
+            return decode_file_record(b, ids, &mut r);
+        }
+
+        let (record_type, body) = match page_type {
+            FREE_PAGE => {
+                r.end()?;
+                self.last_offset = 1;
+                (MlogRecordType::MariaFreePage, MlogBody::Empty)
+            }
+            INIT_PAGE => {
+                r.end()?;
+                self.last_offset = FIL_PAGE_TYPE as u32;
+                (MlogRecordType::MariaInitPage, MlogBody::Empty)
+            }
+            EXTENDED | OPTION => {
+                let subtype = r.u8()?;
+                let data = r.rest().to_vec();
+                if page_type == OPTION {
+                    (
+                        MlogRecordType::MariaOption,
+                        MlogBody::Extended { subtype, data },
+                    )
+                } else {
+                    self.last_offset = FIL_PAGE_TYPE as u32;
+                    (
+                        MlogRecordType::MariaExtended,
+                        MlogBody::Extended { subtype, data },
+                    )
+                }
+            }
+            RESERVED => return Err("reserved record type 0x60".to_string()),
+            _ => self.decode_write(page_type, &mut r)?,
+        };
+        Ok((record_type, Some(ids.0), Some(ids.1), body))
+    }
+
+    /// `WRITE`, `MEMSET`, and `MEMMOVE`: an offset relative to the previous
+    /// write, then the type-specific fields.
+    fn decode_write(
+        &mut self,
+        page_type: u8,
+        r: &mut BodyReader<'_>,
+    ) -> Result<(MlogRecordType, MlogBody), String> {
+        if self.last_offset == 1 {
+            return Err("write to a freed page".to_string());
+        }
+        let target = self.last_offset + r.short_varint()?;
+        if !(8..MAX_PAGE_SIZE).contains(&target) {
+            return Err(format!("page offset {} out of range", target));
+        }
+        let offset = target as u16;
+
+        match page_type {
+            WRITE => {
+                let data = r.rest().to_vec();
+                if data.is_empty() {
+                    return Err("WRITE without data".to_string());
+                }
+                self.advance(target, data.len() as u32)?;
+                Ok((
+                    MlogRecordType::MariaWrite,
+                    MlogBody::WriteString { offset, data },
+                ))
+            }
+            MEMSET => {
+                let len = r.short_varint()? + 1;
+                let fill = r.rest().to_vec();
+                if fill.is_empty() || fill.len() as u32 > len {
+                    return Err(format!(
+                        "MEMSET pattern of {} bytes for {} bytes",
+                        fill.len(),
+                        len
+                    ));
+                }
+                self.advance(target, len)?;
+                Ok((
+                    MlogRecordType::MariaMemset,
+                    MlogBody::Memset {
+                        offset,
+                        len: len as u16,
+                        fill,
+                    },
+                ))
+            }
+            MEMMOVE => {
+                let len = r.short_varint()? + 1;
+                let s = r.short_varint()?;
+                r.end()?;
+                let distance = (s >> 1) + 1;
+                let source = if s & 1 == 1 {
+                    target.checked_sub(distance)
+                } else {
+                    Some(target + distance)
+                };
+                let source = source
+                    .filter(|&src| src >= 8 && src + len <= MAX_PAGE_SIZE)
+                    .ok_or_else(|| "MEMMOVE source out of range".to_string())?;
+                self.advance(target, len)?;
+                Ok((
+                    MlogRecordType::MariaMemmove,
+                    MlogBody::Memmove {
+                        offset,
+                        len: len as u16,
+                        source: source as u16,
+                    },
+                ))
+            }
+            _ => unreachable!("not a write record type"),
+        }
+    }
+
+    /// Move past `len` bytes written at `target`.
+    fn advance(&mut self, target: u32, len: u32) -> Result<(), String> {
+        if target + len > MAX_PAGE_SIZE {
+            return Err(format!(
+                "{} bytes at page offset {} run past the page",
+                len, target
+            ));
+        }
+        self.last_offset = target + len;
+        Ok(())
+    }
+}
+
+/// `FILE_*` records: a tablespace ID, a page number (0), and file names.
+fn decode_file_record(
+    b: u8,
+    (space_id, _): (u32, u32),
+    r: &mut BodyReader<'_>,
+) -> Result<Decoded, String> {
+    let path = |bytes: &[u8]| String::from_utf8_lossy(bytes).to_string();
+    let (record_type, body) = match b & 0xF0 {
+        FILE_CHECKPOINT => {
+            let lsn = r.u64()?;
+            r.end()?;
+            return Ok((
+                MlogRecordType::MariaFileCheckpoint,
+                None,
+                None,
+                MlogBody::FileCheckpoint { lsn },
+            ));
+        }
+        FILE_CREATE => (
+            MlogRecordType::MariaFileCreate,
+            MlogBody::FileCreate {
+                flags: 0,
+                path: path(r.rest()),
+            },
+        ),
+        FILE_DELETE => (
+            MlogRecordType::MariaFileDelete,
+            MlogBody::FileDelete {
+                path: path(r.rest()),
+            },
+        ),
+        FILE_MODIFY => (
+            MlogRecordType::MariaFileModify,
+            MlogBody::FileModify {
+                path: path(r.rest()),
+            },
+        ),
+        FILE_RENAME => {
+            let names = r.rest();
+            let nul = names
+                .iter()
+                .position(|&c| c == 0)
+                .ok_or_else(|| "FILE_RENAME without a new name".to_string())?;
+            (
+                MlogRecordType::MariaFileRename,
+                MlogBody::FileRename {
+                    from: path(&names[..nul]),
+                    to: path(&names[nul + 1..]),
+                },
+            )
+        }
+        t => return Err(format!("unknown file record type 0x{:02X}", t)),
+    };
+    Ok((record_type, Some(space_id), None, body))
+}
+
+/// Reader over one record body.
+struct BodyReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl BodyReader<'_> {
+    fn u8(&mut self) -> Result<u8, String> {
+        let b = *self
+            .buf
+            .get(self.pos)
+            .ok_or_else(|| "record body too short".to_string())?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos + 8)
+            .ok_or_else(|| "record body too short".to_string())?;
+        self.pos += 8;
+        Ok(BigEndian::read_u64(bytes))
+    }
+
+    /// A space ID or page number (1 to 5 bytes).
+    fn varint(&mut self) -> Result<u32, String> {
+        let (v, len) = decode_varint(&self.buf[self.pos..])
+            .ok_or_else(|| "invalid page identifier".to_string())?;
+        self.pos += len;
+        Ok(v)
+    }
+
+    /// An offset or length (1 to 3 bytes).
+    fn short_varint(&mut self) -> Result<u32, String> {
+        match decode_varint(&self.buf[self.pos..]) {
+            Some((v, len)) if len <= 3 => {
+                self.pos += len;
+                Ok(v)
+            }
+            _ => Err("invalid offset or length".to_string()),
+        }
+    }
+
+    fn rest(&mut self) -> &[u8] {
+        let rest = &self.buf[self.pos..];
+        self.pos = self.buf.len();
+        rest
+    }
+
+    fn end(&self) -> Result<(), String> {
+        if self.pos == self.buf.len() {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} unexpected bytes after the record",
+                self.buf.len() - self.pos
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST_LSN: u64 = 10_000;
+
+    fn varint(v: u32) -> Vec<u8> {
+        match v {
+            0..=0x7F => vec![v as u8],
+            0x80..=0x407F => {
+                let v = v - 0x80;
+                vec![0x80 | (v >> 8) as u8, v as u8]
+            }
+            _ => {
+                let v = v - 0x4080;
+                vec![0xC0 | (v >> 16) as u8, (v >> 8) as u8, v as u8]
+            }
+        }
+    }
+
+    /// A record: type byte (with `same_page`), length, page ID, payload.
+    fn record(kind: u8, ids: Option<(u32, u32)>, payload: &[u8]) -> Vec<u8> {
+        let mut body = Vec::new();
+        if let Some((space, page)) = ids {
+            body.extend(varint(space));
+            body.extend(varint(page));
+        }
+        body.extend_from_slice(payload);
+        let flag = if ids.is_none() && kind < FILE_CREATE {
+            0x80
+        } else {
+            0
+        };
+        if body.len() < 16 {
+            let mut rec = vec![kind | flag | body.len() as u8];
+            rec.extend(body);
+            return rec;
+        }
+        let len_len = if body.len() + 1 - 15 < 0x80 { 1 } else { 2 };
+        let mut rec = vec![kind | flag];
+        rec.extend(varint((body.len() + len_len - 15) as u32));
+        rec.extend(body);
+        rec
+    }
+
+    fn mtr(records: &[Vec<u8>], bit: u8) -> Vec<u8> {
+        let recs = records.concat();
+        let mut out = recs.clone();
+        out.push(bit);
+        out.extend_from_slice(&crc32c::crc32c(&recs).to_be_bytes());
+        out
+    }
+
+    /// A log file of `capacity` data bytes with `data` written from
+    /// `FIRST_LSN` on (wrapping around) and a checkpoint at `checkpoint`.
+    fn log_file(capacity: usize, data: &[(u64, Vec<u8>)], checkpoint: u64) -> LogFile {
+        let mut buf = vec![0u8; MARIADB_LOG_START_OFFSET as usize + capacity];
+        BigEndian::write_u32(&mut buf[0..], MARIADB_FORMAT_10_8);
+        BigEndian::write_u64(&mut buf[8..], FIRST_LSN);
+        buf[16..31].copy_from_slice(b"MariaDB 10.11.6");
+        let crc = crc32c::crc32c(&buf[..508]);
+        BigEndian::write_u32(&mut buf[508..], crc);
+
+        let cp = MARIADB_CHECKPOINT_1 as usize;
+        BigEndian::write_u64(&mut buf[cp..], checkpoint);
+        BigEndian::write_u64(&mut buf[cp + 8..], checkpoint);
+        let crc = crc32c::crc32c(&buf[cp..cp + 60]);
+        BigEndian::write_u32(&mut buf[cp + 60..], crc);
+
+        for (lsn, bytes) in data {
+            for (i, &b) in bytes.iter().enumerate() {
+                let pos = (lsn + i as u64 - FIRST_LSN) as usize % capacity;
+                buf[MARIADB_LOG_START_OFFSET as usize + pos] = b;
+            }
+        }
+        LogFile::from_bytes(buf).unwrap()
+    }
+
+    #[test]
+    fn test_decode_varint() {
+        for v in [0u32, 0x7F, 0x80, 0x1234, 0x407F, 0x4080, 0x12_3456] {
+            let enc = varint(v);
+            assert_eq!(decode_varint(&enc), Some((v, enc.len())), "{:#x}", v);
+        }
+        assert_eq!(decode_varint(&[0xF0, 0, 0, 0, 1]), Some((0x1020_4081, 5)));
+        assert_eq!(decode_varint(&[0xF8, 0, 0, 0, 0]), None);
+        assert_eq!(decode_varint(&[0x80]), None);
+    }
+
+    #[test]
+    fn test_page_records() {
+        let mut data = mtr(
+            &[
+                record(INIT_PAGE, Some((5, 3)), &[]),
+                // FIL_PAGE_TYPE + 14 = FIL_PAGE_DATA + PAGE_N_DIR_SLOTS
+                record(WRITE, None, &[[14].as_slice(), &[0, 2]].concat()),
+                // 4 bytes after the previous write, fill 10 bytes with 0xAB
+                record(MEMSET, None, &[4, 9, 0xAB]),
+                // copy 3 bytes from 5 bytes before the target
+                record(MEMMOVE, None, &[0, 2, 9]),
+                record(EXTENDED, None, &[1]),
+                record(WRITE, Some((5, 4)), &[[100].as_slice(), &[7; 20]].concat()),
+                record(FREE_PAGE, Some((5, 9)), &[]),
+            ],
+            1,
+        );
+        let second = FIRST_LSN + data.len() as u64;
+        data.extend(mtr(&[record(WRITE, Some((0, 0)), &[60, 1])], 1));
+        let mut log = log_file(4096, &[(FIRST_LSN, data.clone())], FIRST_LSN);
+
+        let redo = read_mariadb_log(&mut log).unwrap();
+        assert_eq!(redo.start_lsn, FIRST_LSN);
+        assert_eq!(redo.end_lsn, FIRST_LSN + data.len() as u64);
+        assert_eq!(redo.mtrs.len(), 2);
+        assert_eq!(redo.mtrs[1].start_lsn, second);
+        let recs = &redo.mtrs[0].records;
+        assert!(redo.mtrs[0].errors.is_empty(), "{:?}", redo.mtrs[0].errors);
+
+        let types: Vec<&str> = recs.iter().map(|r| r.record_type.name()).collect();
+        assert_eq!(
+            types,
+            [
+                "INIT_PAGE",
+                "WRITE",
+                "MEMSET",
+                "MEMMOVE",
+                "EXTENDED",
+                "WRITE",
+                "FREE_PAGE"
+            ]
+        );
+        assert!(recs.iter().take(5).all(|r| r.page_no == Some(3)));
+        assert_eq!(recs[5].page_no, Some(4));
+        assert!(!recs[0].single_rec);
+        assert!(redo.mtrs[1].records[0].single_rec);
+
+        match &recs[1].body {
+            MlogBody::WriteString { offset, data } => {
+                assert_eq!(*offset, 38);
+                assert_eq!(data, &[0, 2]);
+            }
+            other => panic!("{:?}", other),
+        }
+        match &recs[2].body {
+            MlogBody::Memset { offset, len, fill } => {
+                assert_eq!((*offset, *len), (44, 10));
+                assert_eq!(fill, &[0xAB]);
+            }
+            other => panic!("{:?}", other),
+        }
+        match &recs[3].body {
+            MlogBody::Memmove {
+                offset,
+                len,
+                source,
+            } => assert_eq!((*offset, *len, *source), (54, 3, 49)),
+            other => panic!("{:?}", other),
+        }
+        match &recs[5].body {
+            MlogBody::WriteString { offset, data } => {
+                assert_eq!(*offset, 100);
+                assert_eq!(data.len(), 20);
+            }
+            other => panic!("{:?}", other),
+        }
+        // Each record ends where the next starts
+        for pair in recs.windows(2) {
+            assert_eq!(pair[0].end_lsn, pair[1].start_lsn);
+        }
+    }
+
+    #[test]
+    fn test_file_records() {
+        let data = mtr(
+            &[
+                record(FILE_MODIFY, Some((7, 0)), b"./db/t1.ibd"),
+                record(FILE_RENAME, Some((7, 0)), b"./db/t1.ibd\0./db/t2.ibd"),
+                record(FILE_CHECKPOINT, Some((0, 0)), &FIRST_LSN.to_be_bytes()),
+            ],
+            1,
+        );
+        let mut log = log_file(4096, &[(FIRST_LSN, data)], FIRST_LSN);
+        let decoded = read_mariadb_records(&mut log).unwrap();
+        assert!(decoded.errors.is_empty());
+        let recs = decoded.records;
+        assert_eq!(recs[0].space_id, Some(7));
+        assert_eq!(recs[0].page_no, None);
+        match &recs[1].body {
+            MlogBody::FileRename { from, to } => {
+                assert_eq!(from, "./db/t1.ibd");
+                assert_eq!(to, "./db/t2.ibd");
+            }
+            other => panic!("{:?}", other),
+        }
+        assert!(matches!(
+            recs[2].body,
+            MlogBody::FileCheckpoint { lsn } if lsn == FIRST_LSN
+        ));
+        assert_eq!(recs[2].space_id, None);
+    }
+
+    #[test]
+    fn test_log_end_and_corrupt_record() {
+        let good = mtr(&[record(WRITE, Some((1, 2)), &[38, 1, 2])], 1);
+        // The checksum is valid, but the write offset is below 8
+        let bad = mtr(
+            &[
+                record(WRITE, Some((1, 2)), &[2, 1]),
+                record(INIT_PAGE, Some((1, 3)), &[]),
+            ],
+            1,
+        );
+        let stale = mtr(&[record(WRITE, Some((1, 2)), &[38, 9])], 0);
+        let mut broken = mtr(&[record(WRITE, Some((1, 2)), &[38, 9])], 1);
+        broken[3] ^= 0xFF;
+
+        let a = FIRST_LSN + good.len() as u64;
+        let b = a + bad.len() as u64;
+        let mut log = log_file(
+            4096,
+            &[(FIRST_LSN, good.clone()), (a, bad.clone()), (b, stale)],
+            FIRST_LSN,
+        );
+        let redo = read_mariadb_log(&mut log).unwrap();
+        // A wrong sequence bit ends the log
+        assert_eq!(redo.end_lsn, b);
+        assert_eq!(redo.mtrs[1].errors.len(), 1);
+        assert!(redo.mtrs[1].errors[0].message.contains("out of range"));
+        assert_eq!(redo.mtrs[1].records.len(), 1);
+
+        // So does a checksum mismatch
+        let mut log = log_file(4096, &[(FIRST_LSN, good), (a, broken)], FIRST_LSN);
+        assert_eq!(read_mariadb_log(&mut log).unwrap().end_lsn, a);
+    }
+
+    #[test]
+    fn test_wrapped_log_starts_at_checkpoint() {
+        // Second pass over a 512-byte log. The start of the file already
+        // holds the tail of the second mini-transaction, which wraps around
+        // and so carries the sequence bit of the third pass
+        let checkpoint = FIRST_LSN + 1005;
+        let first = mtr(
+            &[record(
+                WRITE,
+                Some((1, 2)),
+                &[38, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+            )],
+            0,
+        );
+        let second_lsn = checkpoint + first.len() as u64;
+        let second = mtr(&[record(INIT_PAGE, Some((1, 5)), &[])], 1);
+        let mut log = log_file(
+            512,
+            &[(checkpoint, first), (second_lsn, second)],
+            checkpoint,
+        );
+
+        let redo = read_mariadb_log(&mut log).unwrap();
+        assert_eq!(redo.checkpoint_lsn, Some(checkpoint));
+        assert_eq!(redo.start_lsn, checkpoint);
+        assert_eq!(redo.mtrs.len(), 2);
+        assert_eq!(redo.mtrs[1].records[0].page_no, Some(5));
+        assert_eq!(redo.end_lsn, second_lsn + 8);
+    }
+
+    #[test]
+    fn test_rejects_mysql_and_encrypted_logs() {
+        let mut buf = vec![0u8; 0x4000];
+        BigEndian::write_u32(&mut buf[0..], 6);
+        buf[16..28].copy_from_slice(b"MySQL 8.0.35");
+        let mut log = LogFile::from_bytes(buf.clone()).unwrap();
+        assert!(!is_mariadb_10_8(&log.read_header().unwrap()));
+        assert!(read_mariadb_log(&mut log).is_err());
+
+        BigEndian::write_u32(&mut buf[0..], MARIADB_FORMAT_ENC_10_8);
+        buf[16..28].copy_from_slice(b"MariaDB 11.4");
+        let mut log = LogFile::from_bytes(buf).unwrap();
+        let redo = read_mariadb_log(&mut log).unwrap();
+        assert!(redo.encrypted);
+        assert!(redo.mtrs.is_empty());
+        assert!(read_mariadb_records(&mut log).is_err());
+    }
+}
@@ -0,0 +1,1499 @@
+//! Redo log record (MLOG) stream decoding.
+//!
+//! Redo log records are written as one continuous byte stream that is cut
+//! into 512-byte blocks; a record can start in one block and end several
+//! blocks later. [`read_log_streams`] strips the block headers and trailers
+//! from each run of consecutive blocks in a [`LogFile`], and
+//! [`decode_mlog_stream`] walks a [`LogStream`] record by record, decoding
+//! each body with the exact layout MySQL's `recv_parse_log_rec()` expects,
+//! so every record is returned with its start and end LSN.
+//!
+//! Record layouts follow MySQL 8.0 (`mtr0types.h`, `mtr0log.cc`, and the
+//! per-type `*_parse_*()` functions). Types carrying a page reference start
+//! with the type byte, the compressed space ID, and the compressed page
+//! number; `MLOG_MULTI_REC_END`, `MLOG_DUMMY_RECORD`, and
+//! `MLOG_TABLE_DYNAMIC_META` do not.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use idb::innodb::log::LogFile;
+//! use idb::innodb::mlog::read_mlog_records;
+//!
+//! let mut log = LogFile::open("ib_logfile0").unwrap();
+//! let decoded = read_mlog_records(&mut log).unwrap();
+//! for rec in &decoded.records {
+//!     println!("{} {} {:?}:{:?}", rec.start_lsn, rec.record_type, rec.space_id, rec.page_no);
+//! }
+//! ```
+
+use byteorder::{BigEndian, ByteOrder};
+use serde::Serialize;
+
+use crate::innodb::log::{
+    validate_log_block_checksum, LogBlockHeader, LogFile, MlogRecordType,
+    LOG_BLOCK_CHECKSUM_OFFSET, LOG_BLOCK_SIZE, LOG_FILE_HDR_BLOCKS,
+};
+use crate::innodb::mariadb_redo::{is_mariadb_10_8, read_mariadb_records};
+use crate::IdbError;
+
+/// Offset of the first log data byte within a 512-byte block.
+///
+/// The block header is 12 bytes in every MySQL format (block number, data
+/// length, first record group, checkpoint/epoch number).
+/// [`LOG_BLOCK_HDR_SIZE`](crate::innodb::log::LOG_BLOCK_HDR_SIZE) is a
+/// larger threshold used only to decide whether a block holds data.
+pub const LOG_BLOCK_DATA_START: usize = 12;
+
+/// Log data bytes carried by one full block (512 - 12 header - 4 trailer).
+pub const LOG_BLOCK_DATA_SIZE: usize = LOG_BLOCK_CHECKSUM_OFFSET - LOG_BLOCK_DATA_START;
+
+/// Mask applied to `lsn / 512` to derive a block number (`LOG_BLOCK_MAX_NO`).
+const LOG_BLOCK_NO_MASK: u64 = 0x3FFF_FFFF;
+
+/// Field length in the index descriptor of a variable-length field.
+const INDEX_FIELD_VARIABLE: u16 = 0x7FFF;
+
+/// Length written for an SQL NULL field in an update vector (`UNIV_SQL_NULL`).
+const UNIV_SQL_NULL: u32 = 0xFFFF_FFFF;
+
+/// Flags byte of the MySQL 8.0.28+ index descriptor.
+const INDEX_FLAG_COMPACT: u8 = 0x01;
+const INDEX_FLAG_VERSIONED: u8 = 0x02;
+const INDEX_FLAG_INSTANT: u8 = 0x04;
+
+/// `persistent_type_t` codes in `MLOG_TABLE_DYNAMIC_META` records.
+const PM_INDEX_CORRUPTED: u8 = 1;
+const PM_TABLE_AUTO_INC: u8 = 2;
+
+/// Log data from a run of consecutive blocks, with block headers and
+/// trailers removed.
+///
+/// Every block of the run except the last one is full, so stream offsets map
+/// to LSNs linearly within each block: LSNs count the header and trailer
+/// bytes of every block, stream offsets do not.
+#[derive(Debug, Clone)]
+pub struct LogStream {
+    /// LSN of the start of the first block in the run (a multiple of 512).
+    pub start_lsn: u64,
+    /// Index of the first block of the run within its log file.
+    pub first_block: u64,
+    /// Number of blocks in the run.
+    pub block_count: u64,
+    /// De-blocked log data.
+    pub data: Vec<u8>,
+    /// Stream offsets where a record group is known to start, taken from
+    /// the `first_rec_group` field of each block. The first entry is where
+    /// decoding begins; the rest are used to resynchronize after corrupt
+    /// records.
+    pub group_starts: Vec<usize>,
+}
+
+impl LogStream {
+    /// LSN of the byte at stream offset `pos`.
+    ///
+    /// `pos == data.len()` yields the LSN just past the last byte.
+    pub fn lsn_at(&self, pos: usize) -> u64 {
+        let block = (pos / LOG_BLOCK_DATA_SIZE) as u64;
+        let in_block = (pos % LOG_BLOCK_DATA_SIZE + LOG_BLOCK_DATA_START) as u64;
+        self.start_lsn + block * LOG_BLOCK_SIZE as u64 + in_block
+    }
+
+    /// Index within the log file of the block holding `lsn`.
+    pub fn block_index_of_lsn(&self, lsn: u64) -> u64 {
+        self.first_block + lsn.saturating_sub(self.start_lsn) / LOG_BLOCK_SIZE as u64
+    }
+
+    /// LSN just past the last byte of the run.
+    pub fn end_lsn(&self) -> u64 {
+        self.lsn_at(self.data.len())
+    }
+}
+
+/// Block number MySQL stores for the block containing `lsn`.
+fn block_no_for_lsn(lsn: u64) -> u32 {
+    (((lsn / LOG_BLOCK_SIZE as u64) & LOG_BLOCK_NO_MASK) + 1) as u32
+}
+
+/// Block number that follows `block_no` (block numbers wrap after 2^30).
+fn next_block_no(block_no: u32) -> u32 {
+    ((u64::from(block_no) & LOG_BLOCK_NO_MASK) + 1) as u32
+}
+
+/// Start LSN of a block whose position in the file suggests `near`.
+///
+/// Block numbers are derived from the LSN, so the stored number pins the
+/// exact LSN. Blocks left over from an earlier pass over the file carry
+/// older numbers; the nearest LSN with the stored number is used.
+pub(crate) fn block_start_lsn(near: u64, block_no: u32) -> u64 {
+    let cycle = LOG_BLOCK_NO_MASK + 1;
+    let expected = u64::from(block_no_for_lsn(near));
+    let behind = expected.wrapping_sub(u64::from(block_no)) & LOG_BLOCK_NO_MASK;
+    if behind <= cycle / 2 {
+        near.saturating_sub(behind * LOG_BLOCK_SIZE as u64)
+    } else {
+        near + (cycle - behind) * LOG_BLOCK_SIZE as u64
+    }
+}
+
+/// Split the data blocks of a redo log file into runs of consecutive blocks.
+///
+/// A run continues while each block is full, its successor carries the next
+/// block number, and every checksum is valid. A run starts at the first
+/// record group of its first block, so blocks with no record group start are
+/// skipped until one is found. Block LSNs are derived from the file header's
+/// start LSN and each block's stored number.
+pub fn read_log_streams(log: &mut LogFile) -> Result<Vec<LogStream>, IdbError> {
+    let header = log.read_header()?;
+    let base_lsn = header.start_lsn - header.start_lsn % LOG_BLOCK_SIZE as u64;
+    let mut builder = LogStreamBuilder::default();
+    for i in 0..log.data_block_count() {
+        let block_idx = LOG_FILE_HDR_BLOCKS + i;
+        let block = log.read_block(block_idx)?;
+        builder.push(block_idx, base_lsn + i * LOG_BLOCK_SIZE as u64, &block);
+    }
+    Ok(builder.finish())
+}
+
+/// Accumulates blocks, in log order, into [`LogStream`] runs.
+///
+/// Shared by [`read_log_streams`] and
+/// [`RedoLogSet::read_log_streams`](crate::innodb::redo_set::RedoLogSet::read_log_streams),
+/// which feeds the blocks of several files in LSN order.
+#[derive(Default)]
+pub(crate) struct LogStreamBuilder {
+    streams: Vec<LogStream>,
+    current: Option<LogStream>,
+    last_block_no: u32,
+}
+
+impl LogStreamBuilder {
+    /// Add the block at index `block_idx` of its file. `near` is an LSN
+    /// close to the start of the block; the stored block number pins the
+    /// exact one.
+    pub(crate) fn push(&mut self, block_idx: u64, near: u64, block: &[u8]) {
+        let hdr = match LogBlockHeader::parse(block) {
+            Some(h) => h,
+            None => return,
+        };
+        let data_end = (hdr.data_len as usize).min(LOG_BLOCK_CHECKSUM_OFFSET);
+        if data_end <= LOG_BLOCK_DATA_START || !validate_log_block_checksum(block) {
+            self.streams.extend(self.current.take());
+            return;
+        }
+        let first_rec = hdr.first_rec_group as usize;
+        let has_group = first_rec >= LOG_BLOCK_DATA_START && first_rec < data_end;
+
+        let continues = self.current.is_some() && hdr.block_no == next_block_no(self.last_block_no);
+        if !continues {
+            self.streams.extend(self.current.take());
+            if !has_group {
+                return;
+            }
+            self.current = Some(LogStream {
+                start_lsn: block_start_lsn(near, hdr.block_no),
+                first_block: block_idx,
+                block_count: 0,
+                data: Vec::new(),
+                group_starts: Vec::new(),
+            });
+        }
+
+        if let Some(stream) = self.current.as_mut() {
+            if has_group {
+                stream
+                    .group_starts
+                    .push(stream.data.len() + first_rec - LOG_BLOCK_DATA_START);
+            }
+            stream
+                .data
+                .extend_from_slice(&block[LOG_BLOCK_DATA_START..data_end]);
+            stream.block_count += 1;
+        }
+        self.last_block_no = hdr.block_no;
+
+        if data_end < LOG_BLOCK_CHECKSUM_OFFSET {
+            self.streams.extend(self.current.take());
+        }
+    }
+
+    /// End the open run and return all runs.
+    pub(crate) fn finish(mut self) -> Vec<LogStream> {
+        self.streams.extend(self.current.take());
+        self.streams
+    }
+}
+
+// ── Decoded record types ────────────────────────────────────────────────
+
+/// A redo log record decoded from the log stream.
+#[derive(Debug, Clone, Serialize)]
+pub struct MlogRecord {
+    /// LSN of the record's type byte.
+    pub start_lsn: u64,
+    /// LSN just past the record's last byte.
+    pub end_lsn: u64,
+    /// MLOG record type (single-rec flag stripped).
+    pub record_type: MlogRecordType,
+    /// Whether the single-record-group flag (bit 7 of the type byte) was set.
+    pub single_rec: bool,
+    /// Tablespace ID, for records that reference a page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub space_id: Option<u32>,
+    /// Page number, for records that reference a page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_no: Option<u32>,
+    /// Decoded record body.
+    pub body: MlogBody,
+}
+
+/// Index descriptor logged ahead of record-level operations.
+///
+/// Pre-8.0.28 (`_8027`) records carry a descriptor only for compact pages;
+/// 8.0.28+ records always start with a flags byte.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MlogIndex {
+    /// Whether the index is on a compact-format page.
+    pub compact: bool,
+    /// Whether the index has row versions (8.0.29+ instant ADD/DROP COLUMN).
+    pub versioned: bool,
+    /// Whether the index has instantly added columns (8.0.12-8.0.28 style).
+    pub instant: bool,
+    /// Number of fields that uniquely identify a record.
+    pub n_uniq: u16,
+    /// Number of fields before the first instantly added column.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instant_fields: Option<u16>,
+    /// Fields added or dropped by instant DDL, for versioned indexes.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub versioned_fields: Vec<MlogVersionedField>,
+    /// Per-field storage; empty for redundant-format pages.
+    pub fields: Vec<MlogIndexField>,
+}
+
+/// One field of an [`MlogIndex`] descriptor.
+#[derive(Debug, Clone, Serialize)]
+pub struct MlogIndexField {
+    /// Fixed length in bytes, or `None` for variable-length fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixed_len: Option<u16>,
+    /// Whether the field is declared NOT NULL.
+    pub not_null: bool,
+    /// Whether a variable-length field can exceed 255 bytes.
+    pub long: bool,
+}
+
+/// An instantly added or dropped field of a versioned [`MlogIndex`].
+#[derive(Debug, Clone, Serialize)]
+pub struct MlogVersionedField {
+    /// Physical position of the field.
+    pub phy_pos: u16,
+    /// Row version that added the field (0 if not instantly added).
+    pub version_added: u8,
+    /// Row version that dropped the field (0 if not dropped).
+    pub version_dropped: u8,
+}
+
+/// A record insert, as logged by `page_cur_insert_rec_write_log()`.
+///
+/// Only the part of the record that differs from the record before the
+/// cursor (`data`, the "end segment") is logged.
+#[derive(Debug, Clone, Serialize)]
+pub struct MlogInsert {
+    /// Page offset of the record the new record is inserted after. Absent
+    /// for the short inserts inside `MLOG_LIST_END_COPY_CREATED`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor_offset: Option<u16>,
+    /// Info and status bits, when they differ from the cursor record.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info_and_status_bits: Option<u8>,
+    /// Offset of the record origin from the start of the record.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin_offset: Option<u32>,
+    /// Number of leading bytes shared with the cursor record.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mismatch_index: Option<u32>,
+    /// Logged end segment of the record.
+    pub data: Vec<u8>,
+}
+
+/// Transaction system fields logged with clustered index changes.
+#[derive(Debug, Clone, Serialize)]
+pub struct MlogSysFields {
+    /// Position of DB_TRX_ID within the record.
+    pub trx_id_pos: u32,
+    /// DB_ROLL_PTR value.
+    pub roll_ptr: u64,
+    /// DB_TRX_ID value.
+    pub trx_id: u64,
+}
+
+/// One field of an update-in-place vector.
+#[derive(Debug, Clone, Serialize)]
+pub struct MlogUpdateField {
+    /// Field number within the index record.
+    pub field_no: u32,
+    /// New value, or `None` for SQL NULL.
+    pub data: Option<Vec<u8>>,
+}
+
+/// A persistent table metadata change in `MLOG_TABLE_DYNAMIC_META`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MlogTableMetadata {
+    /// Indexes marked corrupted, as `(space_id, index_id)` pairs.
+    CorruptedIndexes { indexes: Vec<(u32, u64)> },
+    /// New AUTO_INCREMENT counter value.
+    AutoInc { value: u64 },
+}
+
+/// Decoded body of an [`MlogRecord`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MlogBody {
+    /// No body (page create, undo erase, file page init, group markers, ...).
+    Empty,
+    /// `MLOG_1BYTE` .. `MLOG_8BYTES`: write `value` at `offset`.
+    WriteBytes { offset: u16, value: u64 },
+    /// `MLOG_WRITE_STRING` (and MariaDB `WRITE`): write `data` at `offset`.
+    WriteString { offset: u16, data: Vec<u8> },
+    /// `MLOG_*REC_INSERT*`: insert a record.
+    RecInsert {
+        index: MlogIndex,
+        insert: MlogInsert,
+    },
+    /// `MLOG_*REC_CLUST_DELETE_MARK*`: set or clear a clustered delete mark.
+    ClustDeleteMark {
+        index: MlogIndex,
+        flags: u8,
+        value: bool,
+        sys: MlogSysFields,
+        offset: u16,
+    },
+    /// `MLOG_*REC_SEC_DELETE_MARK`: set or clear a secondary delete mark.
+    SecDeleteMark {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        index: Option<MlogIndex>,
+        value: bool,
+        offset: u16,
+    },
+    /// `MLOG_*REC_UPDATE_IN_PLACE*`: overwrite fields of a record.
+    UpdateInPlace {
+        index: MlogIndex,
+        flags: u8,
+        sys: MlogSysFields,
+        offset: u16,
+        info_bits: u8,
+        fields: Vec<MlogUpdateField>,
+    },
+    /// `MLOG_*REC_DELETE*`: delete the record at `offset`.
+    RecDelete { index: MlogIndex, offset: u16 },
+    /// `MLOG_*LIST_END_DELETE*` / `MLOG_*LIST_START_DELETE*`: delete the
+    /// records from `offset` to the end, or from the start to `offset`.
+    ListDelete { index: MlogIndex, offset: u16 },
+    /// `MLOG_*LIST_END_COPY_CREATED*`: records copied to a new page.
+    ListCopyCreated {
+        index: MlogIndex,
+        inserts: Vec<MlogInsert>,
+    },
+    /// `MLOG_*PAGE_REORGANIZE*`, `MLOG_ZIP_PAGE_COMPRESS_NO_DATA*`.
+    PageReorganize {
+        index: MlogIndex,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        compression_level: Option<u8>,
+    },
+    /// `MLOG_*REC_MIN_MARK`: set the min-rec flag of the record at `offset`.
+    RecMinMark { offset: u16 },
+    /// `MLOG_UNDO_INSERT`: append an undo record.
+    UndoInsert { data: Vec<u8> },
+    /// `MLOG_UNDO_INIT`: initialize an undo page of type `undo_type`.
+    UndoInit { undo_type: u32 },
+    /// `MLOG_UNDO_HDR_CREATE` / `MLOG_UNDO_HDR_REUSE`.
+    UndoHeader { trx_id: u64 },
+    /// `MLOG_ZIP_WRITE_NODE_PTR`.
+    ZipWriteNodePtr {
+        offset: u16,
+        z_offset: u16,
+        child_page_no: u32,
+    },
+    /// `MLOG_ZIP_WRITE_BLOB_PTR`.
+    ZipWriteBlobPtr {
+        offset: u16,
+        z_offset: u16,
+        blob_ref: Vec<u8>,
+    },
+    /// `MLOG_ZIP_WRITE_HEADER`.
+    ZipWriteHeader { offset: u8, data: Vec<u8> },
+    /// `MLOG_ZIP_PAGE_COMPRESS`: a full compressed page image.
+    ZipPageCompress {
+        prev_page: u32,
+        next_page: u32,
+        data: Vec<u8>,
+        trailer: Vec<u8>,
+    },
+    /// `MLOG_FILE_CREATE` (MariaDB `FILE_CREATE` carries no flags: 0).
+    FileCreate { flags: u32, path: String },
+    /// `MLOG_FILE_RENAME`.
+    FileRename { from: String, to: String },
+    /// `MLOG_FILE_DELETE`.
+    FileDelete { path: String },
+    /// `MLOG_FILE_EXTEND`: the file is extended by `size` bytes at `offset`.
+    FileExtend { offset: u64, size: u64 },
+    /// `MLOG_INDEX_LOAD`: an index was bulk-loaded without redo logging.
+    IndexLoad { index_id: u64 },
+    /// `MLOG_TABLE_DYNAMIC_META`.
+    TableDynamicMeta {
+        table_id: u64,
+        version: u64,
+        metadata: MlogTableMetadata,
+    },
+    /// `MLOG_TEST` (written only by MySQL's unit tests).
+    Test { key: u64, value: u64 },
+    /// MariaDB `MEMSET`: fill `len` bytes at `offset` by repeating `fill`.
+    Memset {
+        offset: u16,
+        len: u16,
+        fill: Vec<u8>,
+    },
+    /// MariaDB `MEMMOVE`: copy `len` bytes from `source` to `offset`.
+    Memmove { offset: u16, len: u16, source: u16 },
+    /// MariaDB `EXTENDED` / `OPTION`: a subtype byte and its payload.
+    Extended { subtype: u8, data: Vec<u8> },
+    /// MariaDB `FILE_MODIFY`: the first change to `path` since the latest
+    /// checkpoint.
+    FileModify { path: String },
+    /// MariaDB `FILE_CHECKPOINT`: the checkpoint at `lsn` is complete.
+    FileCheckpoint { lsn: u64 },
+}
+
+/// A stream position where decoding failed.
+#[derive(Debug, Clone, Serialize)]
+pub struct MlogDecodeError {
+    /// LSN of the record that could not be decoded.
+    pub lsn: u64,
+    /// What was wrong with it.
+    pub message: String,
+    /// LSN where decoding resumed, if a later record group was found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resumed_at: Option<u64>,
+}
+
+/// Records decoded from one or more [`LogStream`]s.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MlogStreamDecode {
+    /// Records in stream order.
+    pub records: Vec<MlogRecord>,
+    /// Corrupt records, each followed by a resynchronization.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<MlogDecodeError>,
+    /// LSN of a record cut off by the end of its run, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncated_at: Option<u64>,
+}
+
+// ── Decoding ────────────────────────────────────────────────────────────
+
+/// Why a record could not be decoded.
+enum Fail {
+    /// The stream ended inside the record.
+    Truncated,
+    /// The record bytes are not a valid record.
+    Corrupt(String),
+}
+
+type Parse<T> = Result<T, Fail>;
+
+/// Byte reader over the de-blocked stream.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Parse<&'a [u8]> {
+        if self.pos + n > self.data.len() {
+            return Err(Fail::Truncated);
+        }
+        let bytes = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Parse<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Parse<u16> {
+        Ok(BigEndian::read_u16(self.take(2)?))
+    }
+
+    fn u32(&mut self) -> Parse<u32> {
+        Ok(BigEndian::read_u32(self.take(4)?))
+    }
+
+    fn u64(&mut self) -> Parse<u64> {
+        Ok(BigEndian::read_u64(self.take(8)?))
+    }
+
+    fn bytes(&mut self, n: usize) -> Parse<Vec<u8>> {
+        Ok(self.take(n)?.to_vec())
+    }
+
+    /// `mach_parse_compressed()`: a 1-5 byte compressed u32.
+    fn compressed(&mut self) -> Parse<u32> {
+        let lead = *self.data.get(self.pos).ok_or(Fail::Truncated)?;
+        if lead > 0xF0 {
+            return Err(Fail::Corrupt(format!(
+                "invalid compressed integer lead byte 0x{:02X}",
+                lead
+            )));
+        }
+        match crate::innodb::undo::read_compressed(self.data, self.pos) {
+            Some((val, len)) => {
+                self.pos += len;
+                Ok(val as u32)
+            }
+            None => Err(Fail::Truncated),
+        }
+    }
+
+    /// `mach_u64_parse_compressed()`: compressed high word, 4-byte low word.
+    fn u64_compressed(&mut self) -> Parse<u64> {
+        let high = self.compressed()?;
+        let low = self.u32()?;
+        Ok((u64::from(high) << 32) | u64::from(low))
+    }
+
+    /// `mach_u64_parse_much_compressed()`: a compressed u32, or 0xFF
+    /// followed by compressed high and low words.
+    fn u64_much_compressed(&mut self) -> Parse<u64> {
+        if *self.data.get(self.pos).ok_or(Fail::Truncated)? != 0xFF {
+            return Ok(u64::from(self.compressed()?));
+        }
+        self.pos += 1;
+        let high = self.compressed()?;
+        let low = self.compressed()?;
+        Ok((u64::from(high) << 32) | u64::from(low))
+    }
+
+    /// A 2-byte length followed by a file path (NUL terminator stripped).
+    fn path(&mut self) -> Parse<String> {
+        let len = self.u16()? as usize;
+        let raw = self.take(len)?;
+        let end = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+        Ok(String::from_utf8_lossy(&raw[..end]).into_owned())
+    }
+}
+
+/// Decode every record in `stream`, starting at its first record group.
+///
+/// A corrupt record is reported in [`MlogStreamDecode::errors`] and decoding
+/// resumes at the next record group start. A record cut off by the end of
+/// the stream ends decoding and is reported in `truncated_at`.
+pub fn decode_mlog_stream(stream: &LogStream) -> MlogStreamDecode {
+    let mut result = MlogStreamDecode::default();
+    let mut pos = match stream.group_starts.first() {
+        Some(&p) => p,
+        None => return result,
+    };
+
+    while pos < stream.data.len() {
+        let mut reader = Reader {
+            data: &stream.data,
+            pos,
+        };
+        match decode_record(&mut reader) {
+            Ok((record_type, single_rec, page, body)) => {
+                result.records.push(MlogRecord {
+                    start_lsn: stream.lsn_at(pos),
+                    end_lsn: stream.lsn_at(reader.pos),
+                    record_type,
+                    single_rec,
+                    space_id: page.map(|(s, _)| s),
+                    page_no: page.map(|(_, p)| p),
+                    body,
+                });
+                pos = reader.pos;
+            }
+            Err(Fail::Truncated) => {
+                result.truncated_at = Some(stream.lsn_at(pos));
+                break;
+            }
+            Err(Fail::Corrupt(message)) => {
+                let next = stream.group_starts.iter().copied().find(|&g| g > pos);
+                result.errors.push(MlogDecodeError {
+                    lsn: stream.lsn_at(pos),
+                    message,
+                    resumed_at: next.map(|g| stream.lsn_at(g)),
+                });
+                match next {
+                    Some(g) => pos = g,
+                    None => break,
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Read and decode all records of a redo log file.
+///
+/// Each run from [`read_log_streams`] is decoded on its own; records are
+/// returned in file order. A MariaDB 10.8+ log, which has no block framing,
+/// is decoded with
+/// [`read_mariadb_records`](crate::innodb::mariadb_redo::read_mariadb_records)
+/// instead.
+pub fn read_mlog_records(log: &mut LogFile) -> Result<MlogStreamDecode, IdbError> {
+    if is_mariadb_10_8(&log.read_header()?) {
+        return read_mariadb_records(log);
+    }
+    Ok(decode_mlog_streams(&read_log_streams(log)?))
+}
+
+/// Decode each run on its own and concatenate the results.
+pub(crate) fn decode_mlog_streams(streams: &[LogStream]) -> MlogStreamDecode {
+    let mut all = MlogStreamDecode::default();
+    for stream in streams {
+        let decoded = decode_mlog_stream(stream);
+        all.records.extend(decoded.records);
+        all.errors.extend(decoded.errors);
+        if decoded.truncated_at.is_some() {
+            all.truncated_at = decoded.truncated_at;
+        }
+    }
+    all
+}
+
+type DecodedRecord = (MlogRecordType, bool, Option<(u32, u32)>, MlogBody);
+
+/// Decode one record starting at the reader's position.
+fn decode_record(r: &mut Reader<'_>) -> Parse<DecodedRecord> {
+    let raw = r.u8()?;
+    let single_rec = raw & 0x80 != 0;
+    let record_type = MlogRecordType::from_u8(raw & 0x7F);
+
+    match record_type {
+        MlogRecordType::MlogMultiRecEnd | MlogRecordType::MlogDummyRecord => {
+            return Ok((record_type, single_rec, None, MlogBody::Empty));
+        }
+        MlogRecordType::MlogTableDynamicMeta => {
+            let table_id = r.u64_much_compressed()?;
+            let version = r.u64_much_compressed()?;
+            let metadata = parse_table_metadata(r)?;
+            let body = MlogBody::TableDynamicMeta {
+                table_id,
+                version,
+                metadata,
+            };
+            return Ok((record_type, single_rec, None, body));
+        }
+        MlogRecordType::Unknown(code) => {
+            return Err(Fail::Corrupt(format!("unknown MLOG record type {}", code)));
+        }
+        _ => {}
+    }
+
+    let space_id = r.compressed()?;
+    let page_no = r.compressed()?;
+    let body = parse_body(record_type, r)?;
+    Ok((record_type, single_rec, Some((space_id, page_no)), body))
+}
+
+/// Decode the body of a page-referencing record.
+fn parse_body(record_type: MlogRecordType, r: &mut Reader<'_>) -> Parse<MlogBody> {
+    use MlogRecordType as T;
+
+    let body = match record_type {
+        T::Mlog1Byte | T::Mlog2Bytes | T::Mlog4Bytes => {
+            let offset = r.u16()?;
+            let value = r.compressed()?;
+            let max = match record_type {
+                T::Mlog1Byte => 0xFF,
+                T::Mlog2Bytes => 0xFFFF,
+                _ => u32::MAX,
+            };
+            if value > max {
+                return Err(Fail::Corrupt(format!(
+                    "{} value {} out of range",
+                    record_type, value
+                )));
+            }
+            MlogBody::WriteBytes {
+                offset,
+                value: u64::from(value),
+            }
+        }
+        T::Mlog8Bytes => {
+            let offset = r.u16()?;
+            let value = r.u64_compressed()?;
+            MlogBody::WriteBytes { offset, value }
+        }
+        T::MlogWriteString => {
+            let offset = r.u16()?;
+            let len = r.u16()? as usize;
+            if offset as usize + len > u16::MAX as usize + 1 {
+                return Err(Fail::Corrupt(format!(
+                    "MLOG_WRITE_STRING of {} bytes at offset {} overruns the page",
+                    len, offset
+                )));
+            }
+            MlogBody::WriteString {
+                offset,
+                data: r.bytes(len)?,
+            }
+        }
+
+        T::MlogRecInsert8027 | T::MlogCompRecInsert8027 | T::MlogRecInsert => {
+            let index = parse_record_index(record_type, r)?;
+            let insert = parse_insert(r, false)?;
+            MlogBody::RecInsert { index, insert }
+        }
+        T::MlogRecClustDeleteMark8027
+        | T::MlogCompRecClustDeleteMark8027
+        | T::MlogRecClustDeleteMark => {
+            let index = parse_record_index(record_type, r)?;
+            let flags = r.u8()?;
+            let value = r.u8()? != 0;
+            let sys = parse_sys_fields(r)?;
+            let offset = r.u16()?;
+            MlogBody::ClustDeleteMark {
+                index,
+                flags,
+                value,
+                sys,
+                offset,
+            }
+        }
+        T::MlogRecSecDeleteMark | T::MlogCompRecSecDeleteMark => {
+            let index = match record_type {
+                T::MlogCompRecSecDeleteMark => Some(parse_index_8027(r, true)?),
+                _ => None,
+            };
+            let value = r.u8()? != 0;
+            let offset = r.u16()?;
+            MlogBody::SecDeleteMark {
+                index,
+                value,
+                offset,
+            }
+        }
+        T::MlogRecUpdateInPlace8027 | T::MlogCompRecUpdateInPlace8027 | T::MlogRecUpdateInPlace => {
+            let index = parse_record_index(record_type, r)?;
+            let flags = r.u8()?;
+            let sys = parse_sys_fields(r)?;
+            let offset = r.u16()?;
+            let info_bits = r.u8()?;
+            let n_fields = r.compressed()?;
+            let mut fields = Vec::new();
+            for _ in 0..n_fields {
+                let field_no = r.compressed()?;
+                let len = r.compressed()?;
+                let data = if len == UNIV_SQL_NULL {
+                    None
+                } else {
+                    Some(r.bytes(len as usize)?)
+                };
+                fields.push(MlogUpdateField { field_no, data });
+            }
+            MlogBody::UpdateInPlace {
+                index,
+                flags,
+                sys,
+                offset,
+                info_bits,
+                fields,
+            }
+        }
+        T::MlogRecDelete8027 | T::MlogCompRecDelete8027 | T::MlogRecDelete => {
+            let index = parse_record_index(record_type, r)?;
+            let offset = r.u16()?;
+            MlogBody::RecDelete { index, offset }
+        }
+        T::MlogListEndDelete8027
+        | T::MlogListStartDelete8027
+        | T::MlogCompListEndDelete8027
+        | T::MlogCompListStartDelete8027
+        | T::MlogListEndDelete
+        | T::MlogListStartDelete => {
+            let index = parse_record_index(record_type, r)?;
+            let offset = r.u16()?;
+            MlogBody::ListDelete { index, offset }
+        }
+        T::MlogListEndCopyCreated8027
+        | T::MlogCompListEndCopyCreated8027
+        | T::MlogListEndCopyCreated => {
+            let index = parse_record_index(record_type, r)?;
+            let len = r.u32()? as usize;
+            let data = r.take(len)?;
+            let mut inner = Reader { data, pos: 0 };
+            let mut inserts = Vec::new();
+            while inner.pos < data.len() {
+                match parse_insert(&mut inner, true) {
+                    Ok(insert) => inserts.push(insert),
+                    Err(Fail::Truncated) => {
+                        return Err(Fail::Corrupt(format!(
+                            "{} record list overruns its {} bytes",
+                            record_type, len
+                        )))
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            MlogBody::ListCopyCreated { index, inserts }
+        }
+        T::MlogPageReorganize8027 | T::MlogCompPageReorganize8027 | T::MlogPageReorganize => {
+            let index = parse_record_index(record_type, r)?;
+            MlogBody::PageReorganize {
+                index,
+                compression_level: None,
+            }
+        }
+        T::MlogZipPageReorganize8027
+        | T::MlogZipPageReorganize
+        | T::MlogZipPageCompressNoData8027
+        | T::MlogZipPageCompressNoData => {
+            let index = parse_record_index(record_type, r)?;
+            let level = r.u8()?;
+            MlogBody::PageReorganize {
+                index,
+                compression_level: Some(level),
+            }
+        }
+
+        T::MlogPageCreate
+        | T::MlogCompPageCreate
+        | T::MlogPageCreateRTree
+        | T::MlogCompPageCreateRTree
+        | T::MlogPageCreateSdi
+        | T::MlogCompPageCreateSdi
+        | T::MlogUndoEraseEnd
+        | T::MlogIbufBitmapInit
+        | T::MlogInitFilePage
+        | T::MlogInitFilePage2
+        | T::MlogLsn => MlogBody::Empty,
+
+        T::MlogRecMinMark | T::MlogCompRecMinMark => MlogBody::RecMinMark { offset: r.u16()? },
+        T::MlogUndoInsert => {
+            let len = r.u16()? as usize;
+            MlogBody::UndoInsert {
+                data: r.bytes(len)?,
+            }
+        }
+        T::MlogUndoInit => MlogBody::UndoInit {
+            undo_type: r.compressed()?,
+        },
+        T::MlogUndoHdrCreate | T::MlogUndoHdrReuse => MlogBody::UndoHeader {
+            trx_id: r.u64_compressed()?,
+        },
+
+        T::MlogZipWriteNodePtr => MlogBody::ZipWriteNodePtr {
+            offset: r.u16()?,
+            z_offset: r.u16()?,
+            child_page_no: r.u32()?,
+        },
+        T::MlogZipWriteBlobPtr => MlogBody::ZipWriteBlobPtr {
+            offset: r.u16()?,
+            z_offset: r.u16()?,
+            blob_ref: r.bytes(20)?,
+        },
+        T::MlogZipWriteHeader => {
+            let offset = r.u8()?;
+            let len = r.u8()? as usize;
+            if len == 0 {
+                return Err(Fail::Corrupt(
+                    "MLOG_ZIP_WRITE_HEADER with zero length".to_string(),
+                ));
+            }
+            MlogBody::ZipWriteHeader {
+                offset,
+                data: r.bytes(len)?,
+            }
+        }
+        T::MlogZipPageCompress => {
+            let size = r.u16()? as usize;
+            let trailer_size = r.u16()? as usize;
+            let prev_page = r.u32()?;
+            let next_page = r.u32()?;
+            MlogBody::ZipPageCompress {
+                prev_page,
+                next_page,
+                data: r.bytes(size)?,
+                trailer: r.bytes(trailer_size)?,
+            }
+        }
+
+        T::MlogFileCreate => MlogBody::FileCreate {
+            flags: r.u32()?,
+            path: r.path()?,
+        },
+        T::MlogFileRename => MlogBody::FileRename {
+            from: r.path()?,
+            to: r.path()?,
+        },
+        T::MlogFileDelete => MlogBody::FileDelete { path: r.path()? },
+        T::MlogFileExtend => MlogBody::FileExtend {
+            offset: r.u64()?,
+            size: r.u64()?,
+        },
+        T::MlogIndexLoad => MlogBody::IndexLoad { index_id: r.u64()? },
+        T::MlogTest => MlogBody::Test {
+            key: r.u64()?,
+            value: r.u64()?,
+        },
+
+        T::MlogMultiRecEnd | T::MlogDummyRecord | T::MlogTableDynamicMeta | T::Unknown(_) => {
+            unreachable!("handled by decode_record")
+        }
+        T::MariaFreePage
+        | T::MariaInitPage
+        | T::MariaExtended
+        | T::MariaWrite
+        | T::MariaMemset
+        | T::MariaMemmove
+        | T::MariaOption
+        | T::MariaFileCreate
+        | T::MariaFileDelete
+        | T::MariaFileRename
+        | T::MariaFileModify
+        | T::MariaFileCheckpoint => unreachable!("not produced by MlogRecordType::from_u8"),
+    };
+
+    Ok(body)
+}
+
+/// Parse the index descriptor of a record-level operation.
+///
+/// `_8027` types use the pre-8.0.28 descriptor, present only for compact
+/// (`MLOG_COMP_*`) and compressed-page types; the 8.0.28+ types use the
+/// flagged descriptor.
+fn parse_record_index(record_type: MlogRecordType, r: &mut Reader<'_>) -> Parse<MlogIndex> {
+    use MlogRecordType as T;
+
+    match record_type {
+        T::MlogRecInsert8027
+        | T::MlogRecClustDeleteMark8027
+        | T::MlogRecUpdateInPlace8027
+        | T::MlogRecDelete8027
+        | T::MlogListEndDelete8027
+        | T::MlogListStartDelete8027
+        | T::MlogListEndCopyCreated8027
+        | T::MlogPageReorganize8027 => parse_index_8027(r, false),
+        T::MlogCompRecInsert8027
+        | T::MlogCompRecClustDeleteMark8027
+        | T::MlogCompRecUpdateInPlace8027
+        | T::MlogCompRecDelete8027
+        | T::MlogCompListEndDelete8027
+        | T::MlogCompListStartDelete8027
+        | T::MlogCompListEndCopyCreated8027
+        | T::MlogCompPageReorganize8027
+        | T::MlogZipPageReorganize8027
+        | T::MlogZipPageCompressNoData8027 => parse_index_8027(r, true),
+        _ => parse_index(r),
+    }
+}
+
+/// `mlog_parse_index_8027()`: field count, unique field count, and one
+/// 2-byte length per field, for compact pages only.
+fn parse_index_8027(r: &mut Reader<'_>, compact: bool) -> Parse<MlogIndex> {
+    if !compact {
+        return Ok(MlogIndex {
+            n_uniq: 1,
+            ..MlogIndex::default()
+        });
+    }
+    let n = r.u16()?;
+    let n_uniq = r.u16()?;
+    Ok(MlogIndex {
+        compact: true,
+        n_uniq,
+        fields: parse_index_fields(r, n)?,
+        ..MlogIndex::default()
+    })
+}
+
+/// `mlog_parse_index()` (8.0.28+): a flags byte, then for compact indexes
+/// the instant/versioned field information, the field counts, and one
+/// 2-byte length per field.
+fn parse_index(r: &mut Reader<'_>) -> Parse<MlogIndex> {
+    let flags = r.u8()?;
+    if flags & !(INDEX_FLAG_COMPACT | INDEX_FLAG_VERSIONED | INDEX_FLAG_INSTANT) != 0 {
+        return Err(Fail::Corrupt(format!(
+            "invalid index descriptor flags 0x{:02X}",
+            flags
+        )));
+    }
+    let mut index = MlogIndex {
+        compact: flags & INDEX_FLAG_COMPACT != 0,
+        versioned: flags & INDEX_FLAG_VERSIONED != 0,
+        instant: flags & INDEX_FLAG_INSTANT != 0,
+        n_uniq: 1,
+        ..MlogIndex::default()
+    };
+    if !index.compact {
+        return Ok(index);
+    }
+
+    if index.versioned {
+        let n_versioned = r.u16()?;
+        for _ in 0..n_versioned {
+            index.versioned_fields.push(MlogVersionedField {
+                phy_pos: r.u16()?,
+                version_added: r.u8()?,
+                version_dropped: r.u8()?,
+            });
+        }
+    }
+    let n = r.u16()?;
+    if index.instant {
+        index.instant_fields = Some(r.u16()?);
+    }
+    index.n_uniq = r.u16()?;
+    index.fields = parse_index_fields(r, n)?;
+    Ok(index)
+}
+
+/// Per-field lengths of an index descriptor.
+///
+/// The high bit flags NOT NULL; the low 15 bits are the fixed length, 0 for
+/// a short variable-length field, or 0x7FFF for a long one.
+fn parse_index_fields(r: &mut Reader<'_>, n: u16) -> Parse<Vec<MlogIndexField>> {
+    let mut fields = Vec::with_capacity(n as usize);
+    for _ in 0..n {
+        let len = r.u16()?;
+        let fixed = len & 0x7FFF;
+        fields.push(MlogIndexField {
+            fixed_len: (fixed != 0 && fixed != INDEX_FIELD_VARIABLE).then_some(fixed),
+            not_null: len & 0x8000 != 0,
+            long: fixed == INDEX_FIELD_VARIABLE,
+        });
+    }
+    Ok(fields)
+}
+
+/// `page_cur_parse_insert_rec()`.
+///
+/// The end segment length is stored doubled; its low bit flags that the
+/// info bits, origin offset, and mismatch index follow.
+fn parse_insert(r: &mut Reader<'_>, short: bool) -> Parse<MlogInsert> {
+    let cursor_offset = if short { None } else { Some(r.u16()?) };
+    let end_seg_len = r.compressed()?;
+    if end_seg_len >= 2 * 65536 {
+        return Err(Fail::Corrupt(format!(
+            "insert end segment length {} exceeds a page",
+            end_seg_len >> 1
+        )));
+    }
+    let (info_and_status_bits, origin_offset, mismatch_index) = if end_seg_len & 1 != 0 {
+        (Some(r.u8()?), Some(r.compressed()?), Some(r.compressed()?))
+    } else {
+        (None, None, None)
+    };
+    Ok(MlogInsert {
+        cursor_offset,
+        info_and_status_bits,
+        origin_offset,
+        mismatch_index,
+        data: r.bytes((end_seg_len >> 1) as usize)?,
+    })
+}
+
+/// `row_upd_parse_sys_vals()`: DB_TRX_ID position, DB_ROLL_PTR, DB_TRX_ID.
+fn parse_sys_fields(r: &mut Reader<'_>) -> Parse<MlogSysFields> {
+    let trx_id_pos = r.compressed()?;
+    let roll_ptr = r
+        .take(7)?
+        .iter()
+        .fold(0u64, |acc, &b| (acc << 8) | u64::from(b));
+    let trx_id = r.u64_compressed()?;
+    Ok(MlogSysFields {
+        trx_id_pos,
+        roll_ptr,
+        trx_id,
+    })
+}
+
+/// Body of `MLOG_TABLE_DYNAMIC_META` after the table ID and version.
+fn parse_table_metadata(r: &mut Reader<'_>) -> Parse<MlogTableMetadata> {
+    match r.u8()? {
+        PM_INDEX_CORRUPTED => {
+            let n = r.u8()?;
+            let mut indexes = Vec::with_capacity(n as usize);
+            for _ in 0..n {
+                let space_id = r.compressed()?;
+                let index_id = r.u64_much_compressed()?;
+                indexes.push((space_id, index_id));
+            }
+            Ok(MlogTableMetadata::CorruptedIndexes { indexes })
+        }
+        PM_TABLE_AUTO_INC => Ok(MlogTableMetadata::AutoInc {
+            value: r.u64_much_compressed()?,
+        }),
+        other => Err(Fail::Corrupt(format!(
+            "unknown table metadata type {}",
+            other
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode `val` as a `mach_write_compressed()` integer.
+    fn compressed(val: u32) -> Vec<u8> {
+        match val {
+            0..=0x7F => vec![val as u8],
+            0x80..=0x3FFF => vec![0x80 | (val >> 8) as u8, val as u8],
+            0x4000..=0x1F_FFFF => vec![0xC0 | (val >> 16) as u8, (val >> 8) as u8, val as u8],
+            0x20_0000..=0x0FFF_FFFF => vec![
+                0xE0 | (val >> 24) as u8,
+                (val >> 16) as u8,
+                (val >> 8) as u8,
+                val as u8,
+            ],
+            _ => {
+                let mut v = vec![0xF0];
+                v.extend_from_slice(&val.to_be_bytes());
+                v
+            }
+        }
+    }
+
+    fn header(type_code: u8, space_id: u32, page_no: u32) -> Vec<u8> {
+        let mut v = vec![type_code];
+        v.extend(compressed(space_id));
+        v.extend(compressed(page_no));
+        v
+    }
+
+    fn stream(data: Vec<u8>) -> LogStream {
+        LogStream {
+            start_lsn: 8192,
+            first_block: LOG_FILE_HDR_BLOCKS,
+            block_count: 1 + (data.len() / LOG_BLOCK_DATA_SIZE) as u64,
+            data,
+            group_starts: vec![0],
+        }
+    }
+
+    #[test]
+    fn test_lsn_mapping_skips_block_framing() {
+        let s = stream(vec![0; 2 * LOG_BLOCK_DATA_SIZE]);
+        assert_eq!(s.lsn_at(0), 8192 + 12);
+        assert_eq!(s.lsn_at(LOG_BLOCK_DATA_SIZE - 1), 8192 + 507);
+        assert_eq!(s.lsn_at(LOG_BLOCK_DATA_SIZE), 8192 + 512 + 12);
+        assert_eq!(s.block_index_of_lsn(8192 + 600), LOG_FILE_HDR_BLOCKS + 1);
+    }
+
+    #[test]
+    fn test_block_start_lsn_from_block_no() {
+        // Block number matches the position
+        assert_eq!(block_start_lsn(8192, block_no_for_lsn(8192)), 8192);
+        // Block left over from a pass two blocks earlier
+        assert_eq!(
+            block_start_lsn(8192, block_no_for_lsn(8192) - 2),
+            8192 - 1024
+        );
+        assert_eq!(next_block_no(0x4000_0000), 1);
+    }
+
+    #[test]
+    fn test_decode_write_records() {
+        let mut data = header(1, 5, 3);
+        data.extend_from_slice(&38u16.to_be_bytes());
+        data.extend(compressed(0xAB));
+        data.extend(header(8, 5, 3));
+        data.extend_from_slice(&16u16.to_be_bytes());
+        data.extend(compressed(1));
+        data.extend_from_slice(&2u32.to_be_bytes());
+        data.extend(header(0x80 | 30, 5, 3));
+        data.extend_from_slice(&100u16.to_be_bytes());
+        data.extend_from_slice(&3u16.to_be_bytes());
+        data.extend_from_slice(b"abc");
+
+        let decoded = decode_mlog_stream(&stream(data));
+        assert!(decoded.errors.is_empty());
+        assert_eq!(decoded.records.len(), 3);
+        let r0 = &decoded.records[0];
+        assert_eq!(r0.record_type, MlogRecordType::Mlog1Byte);
+        assert_eq!((r0.space_id, r0.page_no), (Some(5), Some(3)));
+        assert!(matches!(
+            r0.body,
+            MlogBody::WriteBytes {
+                offset: 38,
+                value: 0xAB
+            }
+        ));
+        assert_eq!(r0.start_lsn, 8192 + 12);
+        assert_eq!(r0.end_lsn, 8192 + 12 + 7);
+        assert_eq!(decoded.records[1].start_lsn, r0.end_lsn);
+        assert!(matches!(
+            decoded.records[1].body,
+            MlogBody::WriteBytes {
+                offset: 16,
+                value: 0x1_0000_0002
+            }
+        ));
+        let r2 = &decoded.records[2];
+        assert!(r2.single_rec);
+        match &r2.body {
+            MlogBody::WriteString { offset, data } => {
+                assert_eq!(*offset, 100);
+                assert_eq!(data, b"abc");
+            }
+            other => panic!("unexpected body {:?}", other),
+        }
+        assert_eq!(decoded.truncated_at, None);
+    }
+
+    #[test]
+    fn test_decode_comp_insert_with_index() {
+        // MLOG_COMP_REC_INSERT_8027 with a 2-field index descriptor
+        let mut data = header(38, 7, 4);
+        data.extend_from_slice(&2u16.to_be_bytes()); // n
+        data.extend_from_slice(&1u16.to_be_bytes()); // n_uniq
+        data.extend_from_slice(&0x8004u16.to_be_bytes()); // INT NOT NULL
+        data.extend_from_slice(&0x7FFFu16.to_be_bytes()); // long variable
+        data.extend_from_slice(&99u16.to_be_bytes()); // cursor offset
+        data.extend(compressed((5 << 1) | 1)); // end_seg_len with extra info
+        data.push(0x00); // info and status bits
+        data.extend(compressed(7)); // origin offset
+        data.extend(compressed(2)); // mismatch index
+        data.extend_from_slice(&[1, 2, 3, 4, 5]);
+        data.push(31); // MLOG_MULTI_REC_END
+
+        let decoded = decode_mlog_stream(&stream(data));
+        assert_eq!(decoded.records.len(), 2);
+        match &decoded.records[0].body {
+            MlogBody::RecInsert { index, insert } => {
+                assert!(index.compact);
+                assert_eq!(index.n_uniq, 1);
+                assert_eq!(index.fields.len(), 2);
+                assert_eq!(index.fields[0].fixed_len, Some(4));
+                assert!(index.fields[0].not_null);
+                assert!(index.fields[1].long);
+                assert_eq!(insert.cursor_offset, Some(99));
+                assert_eq!(insert.origin_offset, Some(7));
+                assert_eq!(insert.mismatch_index, Some(2));
+                assert_eq!(insert.data, vec![1, 2, 3, 4, 5]);
+            }
+            other => panic!("unexpected body {:?}", other),
+        }
+        assert_eq!(
+            decoded.records[1].record_type,
+            MlogRecordType::MlogMultiRecEnd
+        );
+        assert_eq!(decoded.records[1].space_id, None);
+    }
+
+    #[test]
+    fn test_decode_versioned_index_descriptor() {
+        // MLOG_REC_DELETE (8.0.28+) with a versioned compact index
+        let mut data = header(69, 7, 4);
+        data.push(INDEX_FLAG_COMPACT | INDEX_FLAG_VERSIONED);
+        data.extend_from_slice(&1u16.to_be_bytes()); // one versioned field
+        data.extend_from_slice(&3u16.to_be_bytes());
+        data.extend_from_slice(&[1, 0]);
+        data.extend_from_slice(&4u16.to_be_bytes()); // n
+        data.extend_from_slice(&1u16.to_be_bytes()); // n_uniq
+        for len in [0x8004u16, 0x8006, 0x8007, 0x0000] {
+            data.extend_from_slice(&len.to_be_bytes());
+        }
+        data.extend_from_slice(&0x1234u16.to_be_bytes());
+
+        let decoded = decode_mlog_stream(&stream(data));
+        assert!(decoded.errors.is_empty());
+        match &decoded.records[0].body {
+            MlogBody::RecDelete { index, offset } => {
+                assert!(index.versioned);
+                assert_eq!(index.versioned_fields.len(), 1);
+                assert_eq!(index.versioned_fields[0].phy_pos, 3);
+                assert_eq!(index.versioned_fields[0].version_added, 1);
+                assert_eq!(index.fields.len(), 4);
+                assert_eq!(index.fields[3].fixed_len, None);
+                assert!(!index.fields[3].not_null);
+                assert_eq!(*offset, 0x1234);
+            }
+            other => panic!("unexpected body {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_update_in_place_and_delete_mark() {
+        // MLOG_REC_UPDATE_IN_PLACE_8027 (redundant, no descriptor)
+        let mut data = header(13, 2, 9);
+        data.push(0); // flags
+        data.extend(compressed(1)); // trx id position
+        data.extend_from_slice(&[0x80, 0, 0, 0, 0x10, 0x01, 0x10]); // roll ptr
+        data.extend(compressed(0));
+        data.extend_from_slice(&0x505u32.to_be_bytes()); // trx id
+        data.extend_from_slice(&200u16.to_be_bytes()); // record offset
+        data.push(0x20); // info bits
+        data.extend(compressed(2)); // n_fields
+        data.extend(compressed(3));
+        data.extend(compressed(2));
+        data.extend_from_slice(b"hi");
+        data.extend(compressed(4));
+        data.extend(compressed(UNIV_SQL_NULL));
+        // MLOG_REC_SEC_DELETE_MARK
+        data.extend(header(11, 2, 10));
+        data.push(1);
+        data.extend_from_slice(&300u16.to_be_bytes());
+
+        let decoded = decode_mlog_stream(&stream(data));
+        assert!(decoded.errors.is_empty());
+        assert_eq!(decoded.records.len(), 2);
+        match &decoded.records[0].body {
+            MlogBody::UpdateInPlace {
+                index,
+                sys,
+                offset,
+                info_bits,
+                fields,
+                ..
+            } => {
+                assert!(!index.compact);
+                assert_eq!(sys.trx_id, 0x505);
+                assert_eq!(sys.roll_ptr, 0x80_0000_0010_0110);
+                assert_eq!(*offset, 200);
+                assert_eq!(*info_bits, 0x20);
+                assert_eq!(fields.len(), 2);
+                assert_eq!(fields[0].data.as_deref(), Some(&b"hi"[..]));
+                assert_eq!(fields[1].data, None);
+            }
+            other => panic!("unexpected body {:?}", other),
+        }
+        assert!(matches!(
+            decoded.records[1].body,
+            MlogBody::SecDeleteMark {
+                index: None,
+                value: true,
+                offset: 300
+            }
+        ));
+    }
+
+    #[test]
+    fn test_decode_file_records_and_dynamic_meta() {
+        let mut data = header(33, 12, 0);
+        data.extend_from_slice(&0x21u32.to_be_bytes());
+        data.extend_from_slice(&11u16.to_be_bytes());
+        data.extend_from_slice(b"./db/t.ibd\0");
+        data.extend(header(65, 12, 0));
+        data.extend_from_slice(&65536u64.to_be_bytes());
+        data.extend_from_slice(&4_194_304u64.to_be_bytes());
+        data.push(62); // MLOG_TABLE_DYNAMIC_META
+        data.extend(compressed(1066)); // table id
+        data.extend(compressed(3)); // version
+        data.push(PM_TABLE_AUTO_INC);
+        data.push(0xFF); // much-compressed with a high word
+        data.extend(compressed(1));
+        data.extend(compressed(5));
+
+        let decoded = decode_mlog_stream(&stream(data));
+        assert!(decoded.errors.is_empty());
+        assert_eq!(decoded.records.len(), 3);
+        match &decoded.records[0].body {
+            MlogBody::FileCreate { flags, path } => {
+                assert_eq!(*flags, 0x21);
+                assert_eq!(path, "./db/t.ibd");
+            }
+            other => panic!("unexpected body {:?}", other),
+        }
+        assert!(matches!(
+            decoded.records[1].body,
+            MlogBody::FileExtend {
+                offset: 65536,
+                size: 4_194_304
+            }
+        ));
+        match &decoded.records[2].body {
+            MlogBody::TableDynamicMeta {
+                table_id,
+                version,
+                metadata: MlogTableMetadata::AutoInc { value },
+            } => {
+                assert_eq!(*table_id, 1066);
+                assert_eq!(*version, 3);
+                assert_eq!(*value, (1u64 << 32) | 5);
+            }
+            other => panic!("unexpected body {:?}", other),
+        }
+        assert_eq!(decoded.records[2].space_id, None);
+    }
+
+    #[test]
+    fn test_decode_list_copy_created_short_inserts() {
+        let mut inner = compressed(3 << 1);
+        inner.extend_from_slice(&[9, 9, 9]);
+        inner.extend(compressed((2 << 1) | 1));
+        inner.extend_from_slice(&[0x01]);
+        inner.extend(compressed(5));
+        inner.extend(compressed(0));
+        inner.extend_from_slice(&[8, 8]);
+
+        let mut data = header(71, 3, 6);
+        data.push(INDEX_FLAG_COMPACT);
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data.extend_from_slice(&0x8004u16.to_be_bytes());
+        data.extend_from_slice(&(inner.len() as u32).to_be_bytes());
+        data.extend(inner);
+
+        let decoded = decode_mlog_stream(&stream(data));
+        match &decoded.records[0].body {
+            MlogBody::ListCopyCreated { inserts, .. } => {
+                assert_eq!(inserts.len(), 2);
+                assert_eq!(inserts[0].cursor_offset, None);
+                assert_eq!(inserts[0].data, vec![9, 9, 9]);
+                assert_eq!(inserts[1].info_and_status_bits, Some(1));
+                assert_eq!(inserts[1].data, vec![8, 8]);
+            }
+            other => panic!("unexpected body {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_truncated_and_corrupt() {
+        // A string write cut off by the end of the stream
+        let mut data = header(30, 1, 1);
+        data.extend_from_slice(&0u16.to_be_bytes());
+        data.extend_from_slice(&10u16.to_be_bytes());
+        data.extend_from_slice(b"abc");
+        let decoded = decode_mlog_stream(&stream(data));
+        assert!(decoded.records.is_empty());
+        assert_eq!(decoded.truncated_at, Some(8192 + 12));
+
+        // Unknown type, then resynchronize at the next record group
+        let mut data = vec![0x7E; LOG_BLOCK_DATA_SIZE + 4];
+        let resume = LOG_BLOCK_DATA_SIZE + 3;
+        data[resume] = 31;
+        let mut s = stream(data);
+        s.group_starts.push(resume);
+        let decoded = decode_mlog_stream(&s);
+        assert_eq!(decoded.errors.len(), 1);
+        assert_eq!(decoded.errors[0].resumed_at, Some(8192 + 512 + 12 + 3));
+        assert_eq!(decoded.records.len(), 1);
+        assert_eq!(decoded.records[0].start_lsn, 8192 + 512 + 15);
+    }
+
+    #[test]
+    fn test_record_spanning_blocks() {
+        // A string write straddling the first block boundary
+        let mut data = vec![31u8; LOG_BLOCK_DATA_SIZE - 10];
+        data.extend(header(30, 1, 1));
+        data.extend_from_slice(&0u16.to_be_bytes());
+        data.extend_from_slice(&20u16.to_be_bytes());
+        data.extend_from_slice(&[7u8; 20]);
+        let decoded = decode_mlog_stream(&stream(data));
+        let last = decoded.records.last().unwrap();
+        assert_eq!(last.record_type, MlogRecordType::MlogWriteString);
+        assert_eq!(
+            last.start_lsn,
+            8192 + 12 + (LOG_BLOCK_DATA_SIZE - 10) as u64
+        );
+        // 7 header/offset/length bytes and 20 data bytes: 17 land in block 2
+        assert_eq!(last.end_lsn, 8192 + 512 + 12 + 17);
+    }
+}
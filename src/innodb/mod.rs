@@ -14,51 +14,80 @@
 //! |--------|---------|
 //! | [`tablespace`] | File I/O abstraction, page size auto-detection, page iteration |
 //! | [`page`] | FIL header (38 bytes), FIL trailer (8 bytes), FSP header parsing |
+//! | [`page_dir`] | INDEX page directory slots, `n_owned` checks, and in-page key order |
 //! | [`page_types`] | Page type enum mapping `u16` codes to names and descriptions |
+//! | [`charset`] | Character set transcoding for CHAR/VARCHAR/TEXT columns |
 //! | [`checksum`] | CRC-32C and legacy InnoDB checksum validation |
 //! | [`corruption`] | Corruption pattern classification (bitrot, torn write, zero-fill) |
+//! | [`datafile`] | System tablespace data files (`innodb_data_file_path`) spanning several files |
+//! | [`doublewrite`] | Doublewrite buffer copies (`.dblwr` files and the legacy ibdata1 area) |
 //! | [`export`] | Record export logic — column layout extraction and record decoding |
+//! | [`geometry`] | GEOMETRY column decoding (SRID + WKB) to WKT and GeoJSON |
+//! | [`ibuf`] | Change buffer bitmap pages and the change buffer tree in `ibdata1` |
+//! | [`inode`] | File segment INODE pages and page-to-segment ownership |
 //! | [`index`] | INDEX page internals — B+Tree header, FSEG, system records |
+//! | [`index_rebuild`] | Offline secondary index rebuild from the clustered index |
+//! | [`tree_check`] | Cross-page B+Tree checks — sibling links, reachability, key order |
+//! | [`json_binary`] | MySQL binary JSON decoding for JSON columns |
 //! | [`record`] | Row-level record parsing — compact format, variable-length fields |
 //! | [`schema`] | Schema extraction and DDL reconstruction from SDI metadata |
 //! | [`sdi`] | SDI metadata extraction from MySQL 8.0+ tablespaces |
 //! | [`log`] | Redo log file header, checkpoints, and data block parsing |
+//! | [`mlog`] | Redo log record stream decoding with exact record LSNs |
+//! | [`mariadb_redo`] | MariaDB 10.8+ redo log (`ib_logfile0`) checkpoints and mini-transaction decoding |
+//! | [`redo_apply`] | Offline redo log application to roll pages forward to a target LSN |
+//! | [`redo_set`] | Redo log file sets (`#innodb_redo`, `ib_logfile*`) read as one LSN stream |
 //! | [`undo`] | UNDO log page header and segment header parsing |
 //! | [`lob`] | Large object page headers (old-style BLOB and MySQL 8.0+ LOB) |
 //! | [`compression`] | Compression algorithm detection and decompression (zlib, LZ4) |
 //! | [`encryption`] | Encryption detection from FSP flags, encryption info parsing |
 //! | [`keyring`] | MySQL `keyring_file` plugin format reader |
-//! | [`decryption`] | AES-256-CBC page decryption using tablespace keys |
+//! | [`decryption`] | AES-256-CBC page and redo log block decryption using tablespace keys |
+//! | [`xdes`] | Extent descriptors, FSP free lists, and the page allocation map |
 //! | [`vendor`] | Vendor detection (MySQL, Percona, MariaDB) and format variants |
 //! | [`constants`] | InnoDB page/file structure constants from MySQL source headers |
 
 pub mod backup;
 pub mod btree;
+pub mod charset;
 pub mod checksum;
 pub mod compat;
 pub mod compliance;
 pub mod compression;
 pub mod constants;
 pub mod corruption;
+pub mod datafile;
 pub mod decryption;
+pub mod doublewrite;
 pub mod encryption;
 pub mod export;
 pub mod field_decode;
 pub mod fts;
+pub mod geometry;
 pub mod health;
+pub mod ibuf;
 pub mod index;
+pub mod index_rebuild;
+pub mod inode;
+pub mod json_binary;
 pub mod keyring;
 pub mod lob;
 pub mod log;
+pub mod mariadb_redo;
+pub mod mlog;
 pub mod page;
+pub mod page_dir;
 pub mod page_types;
 pub mod record;
+pub mod redo_apply;
+pub mod redo_set;
 pub mod rtree;
 pub mod schema;
 pub mod sdi;
 pub mod simulate;
 pub mod tablespace;
 pub mod timeline;
+pub mod tree_check;
 pub mod undelete;
 pub mod undo;
 pub mod validate;
@@ -66,3 +95,4 @@ pub mod vendor;
 pub mod verify;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod write;
+pub mod xdes;
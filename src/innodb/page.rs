@@ -238,12 +238,46 @@ impl FspHeader {
             1u32 << (ssize + 9)
         }
     }
+
+    /// Extract the compressed (physical) page size for ROW_FORMAT=COMPRESSED
+    /// tablespaces.
+    ///
+    /// Returns `None` when the ZIP_SSIZE field (bits 1-4) is zero, i.e. the
+    /// tablespace is not compressed. MariaDB full_crc32 tablespaces never use
+    /// page_zip compression and always return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use idb::innodb::page::FspHeader;
+    /// use idb::innodb::vendor::VendorInfo;
+    ///
+    /// // KEY_BLOCK_SIZE=8: ZIP_SSIZE=4
+    /// let fsp = FspHeader { space_id: 5, size: 8, free_limit: 0, flags: 4 << 1, frag_n_used: 0 };
+    /// assert_eq!(fsp.zip_size_from_flags_with_vendor(&VendorInfo::mysql()), Some(8192));
+    /// ```
+    pub fn zip_size_from_flags_with_vendor(
+        &self,
+        vendor_info: &crate::innodb::vendor::VendorInfo,
+    ) -> Option<u32> {
+        if vendor_info.is_full_crc32() {
+            return None;
+        }
+        let zip_ssize = (self.flags & FSP_FLAGS_MASK_ZIP_SSIZE) >> FSP_FLAGS_POS_ZIP_SSIZE;
+        if zip_ssize == 0 {
+            None
+        } else {
+            // zip_ssize=1 => 1K, 2 => 2K, 3 => 4K, 4 => 8K, 5 => 16K
+            Some(512u32 << zip_ssize)
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[allow(clippy::too_many_arguments)]
     fn make_fil_header_bytes(
         checksum: u32,
         page_num: u32,
@@ -372,4 +406,42 @@ mod tests {
             SIZE_PAGE_DEFAULT
         );
     }
+
+    #[test]
+    fn test_fsp_header_zip_size() {
+        use crate::innodb::vendor::{MariaDbFormat, VendorInfo};
+
+        let mysql = VendorInfo::mysql();
+        let fsp = FspHeader {
+            space_id: 7,
+            size: 100,
+            free_limit: 64,
+            flags: 0,
+            frag_n_used: 0,
+        };
+        assert_eq!(fsp.zip_size_from_flags_with_vendor(&mysql), None);
+
+        // KEY_BLOCK_SIZE=8 (ZIP_SSIZE=4) with ATOMIC_BLOBS (bit 5)
+        let fsp_8k = FspHeader {
+            flags: (4 << FSP_FLAGS_POS_ZIP_SSIZE) | (1 << 5),
+            ..fsp
+        };
+        assert_eq!(fsp_8k.zip_size_from_flags_with_vendor(&mysql), Some(8192));
+        assert_eq!(fsp_8k.page_size_from_flags(), SIZE_PAGE_DEFAULT);
+
+        // KEY_BLOCK_SIZE=1 (ZIP_SSIZE=1)
+        let fsp_1k = FspHeader {
+            flags: 1 << FSP_FLAGS_POS_ZIP_SSIZE,
+            ..fsp
+        };
+        assert_eq!(fsp_1k.zip_size_from_flags_with_vendor(&mysql), Some(1024));
+
+        // full_crc32 never uses page_zip
+        let maria = VendorInfo::mariadb(MariaDbFormat::FullCrc32);
+        let fsp_fcrc = FspHeader {
+            flags: 0x10 | 5,
+            ..fsp
+        };
+        assert_eq!(fsp_fcrc.zip_size_from_flags_with_vendor(&maria), None);
+    }
 }
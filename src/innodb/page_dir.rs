@@ -0,0 +1,702 @@
+//! INDEX page directory parsing and in-page consistency checks.
+//!
+//! The page directory is an array of 2-byte slots stored backwards from the
+//! FIL trailer: slot 0 (closest to the trailer) points to the infimum record
+//! and the last slot to the supremum. Each slot points to the record that
+//! "owns" it; the owner's `n_owned` header field counts the records from the
+//! previous owner (exclusive) up to itself. Slots other than the infimum and
+//! supremum slots own 4 to 8 records, which keeps a binary search over the
+//! slots followed by a short linear scan fast.
+//!
+//! [`check_page_directory`] validates the slots against the record list that
+//! [`walk_records`] follows, [`check_record_order`] checks that the records
+//! are in ascending key order using an [`IndexLayout`], [`compare_records`]
+//! compares records across pages, and [`search_directory`] binary-searches
+//! the slots for a key.
+
+use std::cmp::Ordering;
+
+use byteorder::{BigEndian, ByteOrder};
+use serde::Serialize;
+
+use crate::innodb::constants::*;
+use crate::innodb::export::{read_record_fields, IndexLayout, RecordField};
+use crate::innodb::field_decode::ColumnStorageInfo;
+use crate::innodb::index::IndexHeader;
+use crate::innodb::record::{read_record_header, walk_records};
+use crate::innodb::schema::collation_name;
+
+/// A page directory slot and the record that owns it.
+#[derive(Debug, Clone, Serialize)]
+pub struct DirSlot {
+    /// Slot number (0 = infimum slot).
+    pub slot: usize,
+    /// Page offset of the owning record's origin.
+    pub offset: usize,
+    /// `n_owned` from the owning record's header.
+    pub n_owned: u8,
+}
+
+/// An inconsistency between the page directory and the record list.
+#[derive(Debug, Clone, Serialize)]
+pub struct DirectoryIssue {
+    /// Slot the issue was found at, if it concerns a single slot.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<usize>,
+    /// Human-readable description of the issue.
+    pub message: String,
+    /// Expected value (if applicable).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    /// Actual value found (if applicable).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<String>,
+}
+
+/// The parsed page directory of an INDEX page with any issues found.
+#[derive(Debug, Clone, Serialize)]
+pub struct PageDirectory {
+    /// Directory slots, slot 0 first.
+    pub slots: Vec<DirSlot>,
+    /// Inconsistencies between the directory and the record list.
+    pub issues: Vec<DirectoryIssue>,
+}
+
+/// Two records that are not in ascending key order.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordOrderIssue {
+    /// Page offset of the record that sorts too low.
+    pub offset: usize,
+    /// Page offset of the record before it in the record list.
+    pub prev_offset: usize,
+    /// Human-readable description of the issue.
+    pub message: String,
+}
+
+/// Infimum and supremum origins for the page's row format.
+fn system_records(compact: bool) -> (usize, usize) {
+    if compact {
+        (PAGE_NEW_INFIMUM, PAGE_NEW_SUPREMUM)
+    } else {
+        (PAGE_OLD_INFIMUM, PAGE_OLD_SUPREMUM)
+    }
+}
+
+/// Read the record offsets stored in the directory slots of an INDEX page,
+/// slot 0 first.
+///
+/// Returns `None` if the page has no INDEX header or the slot count does not
+/// fit between the record heap and the FIL trailer.
+///
+/// # Examples
+///
+/// ```
+/// use idb::innodb::constants::*;
+/// use idb::innodb::page_dir::read_dir_slots;
+/// use byteorder::{BigEndian, ByteOrder};
+///
+/// let mut page = vec![0u8; 16384];
+/// BigEndian::write_u16(&mut page[FIL_PAGE_DATA + PAGE_N_DIR_SLOTS..], 2);
+/// BigEndian::write_u16(&mut page[FIL_PAGE_DATA + PAGE_N_HEAP..], 0x8002);
+/// BigEndian::write_u16(&mut page[16384 - 10..], PAGE_NEW_INFIMUM as u16);
+/// BigEndian::write_u16(&mut page[16384 - 12..], PAGE_NEW_SUPREMUM as u16);
+///
+/// let slots = read_dir_slots(&page).unwrap();
+/// assert_eq!(slots, vec![PAGE_NEW_INFIMUM, PAGE_NEW_SUPREMUM]);
+/// ```
+pub fn read_dir_slots(page_data: &[u8]) -> Option<Vec<usize>> {
+    let hdr = IndexHeader::parse(page_data)?;
+    let dir_end = page_data.len().checked_sub(SIZE_FIL_TRAILER)?;
+    let n_slots = hdr.n_dir_slots as usize;
+    let dir_start = dir_end.checked_sub(n_slots * PAGE_DIR_SLOT_SIZE)?;
+    let (_, supremum) = system_records(hdr.is_compact());
+    if dir_start < supremum {
+        return None;
+    }
+
+    Some(
+        (0..n_slots)
+            .map(|i| {
+                let pos = dir_end - (i + 1) * PAGE_DIR_SLOT_SIZE;
+                BigEndian::read_u16(&page_data[pos..]) as usize
+            })
+            .collect(),
+    )
+}
+
+/// Validate the page directory of an INDEX page against its record list.
+///
+/// Checks that the directory fits below the record heap, that slot 0 points
+/// to the infimum and the last slot to the supremum, that every slot points
+/// to a record on the list in list order, that each owner's `n_owned`
+/// matches the records it actually owns and lies within InnoDB's bounds,
+/// and that records owning no slot have `n_owned` 0.
+///
+/// Returns `None` if the page has no INDEX header.
+pub fn check_page_directory(page_data: &[u8]) -> Option<PageDirectory> {
+    let hdr = IndexHeader::parse(page_data)?;
+    let compact = hdr.is_compact();
+    let (infimum, supremum) = system_records(compact);
+    let mut issues = Vec::new();
+    let issue = |slot: Option<usize>, message: String| DirectoryIssue {
+        slot,
+        message,
+        expected: None,
+        actual: None,
+    };
+
+    let n_slots = hdr.n_dir_slots as usize;
+    if n_slots < 2 {
+        issues.push(DirectoryIssue {
+            expected: Some(">= 2".to_string()),
+            actual: Some(n_slots.to_string()),
+            ..issue(None, format!("directory has {} slot(s)", n_slots))
+        });
+        return Some(PageDirectory {
+            slots: Vec::new(),
+            issues,
+        });
+    }
+    let slot_offsets = match read_dir_slots(page_data) {
+        Some(s) => s,
+        None => {
+            issues.push(issue(
+                None,
+                format!("{} directory slots do not fit in the page", n_slots),
+            ));
+            return Some(PageDirectory {
+                slots: Vec::new(),
+                issues,
+            });
+        }
+    };
+    let dir_start = page_data.len() - SIZE_FIL_TRAILER - n_slots * PAGE_DIR_SLOT_SIZE;
+    if (hdr.heap_top as usize) > dir_start {
+        issues.push(DirectoryIssue {
+            expected: Some(format!("<= {}", dir_start)),
+            actual: Some(hdr.heap_top.to_string()),
+            ..issue(None, "record heap overlaps the page directory".to_string())
+        });
+    }
+
+    // The record list in order, including the infimum and supremum
+    let mut list = vec![infimum];
+    list.extend(walk_records(page_data).iter().map(|r| r.offset));
+    list.push(supremum);
+    let n_owned_at =
+        |offset: usize| read_record_header(page_data, offset, compact).map_or(0, |h| h.n_owned());
+
+    let slots: Vec<DirSlot> = slot_offsets
+        .iter()
+        .enumerate()
+        .map(|(slot, &offset)| DirSlot {
+            slot,
+            offset,
+            n_owned: n_owned_at(offset),
+        })
+        .collect();
+
+    if slot_offsets[0] != infimum {
+        issues.push(DirectoryIssue {
+            expected: Some(infimum.to_string()),
+            actual: Some(slot_offsets[0].to_string()),
+            ..issue(Some(0), "slot 0 does not point to the infimum".to_string())
+        });
+    }
+    let last = n_slots - 1;
+    if slot_offsets[last] != supremum {
+        issues.push(DirectoryIssue {
+            expected: Some(supremum.to_string()),
+            actual: Some(slot_offsets[last].to_string()),
+            ..issue(
+                Some(last),
+                format!("last slot {} does not point to the supremum", last),
+            )
+        });
+    }
+
+    let mut owners = vec![false; list.len()];
+    let mut prev_pos: Option<usize> = None;
+    for s in &slots {
+        let pos = match list.iter().position(|&o| o == s.offset) {
+            Some(p) => p,
+            None => {
+                issues.push(issue(
+                    Some(s.slot),
+                    format!(
+                        "slot {} points to offset {}, which is not on the record list",
+                        s.slot, s.offset
+                    ),
+                ));
+                continue;
+            }
+        };
+        if prev_pos.is_some_and(|p| pos <= p) {
+            issues.push(issue(
+                Some(s.slot),
+                format!(
+                    "slot {} (offset {}) is out of order with the record list",
+                    s.slot, s.offset
+                ),
+            ));
+            continue;
+        }
+        owners[pos] = true;
+
+        let owned = pos - prev_pos.map_or(0, |p| p + 1) + 1;
+        prev_pos = Some(pos);
+        if usize::from(s.n_owned) != owned {
+            issues.push(DirectoryIssue {
+                expected: Some(owned.to_string()),
+                actual: Some(s.n_owned.to_string()),
+                ..issue(
+                    Some(s.slot),
+                    format!(
+                        "slot {} owner at offset {} has n_owned {} but owns {} record(s)",
+                        s.slot, s.offset, s.n_owned, owned
+                    ),
+                )
+            });
+        }
+        let (min, max) = match s.slot {
+            0 => (1, 1),
+            n if n == last => (1, PAGE_DIR_SLOT_MAX_N_OWNED as usize),
+            _ => (
+                PAGE_DIR_SLOT_MIN_N_OWNED as usize,
+                PAGE_DIR_SLOT_MAX_N_OWNED as usize,
+            ),
+        };
+        if owned < min || owned > max {
+            issues.push(DirectoryIssue {
+                expected: Some(format!("{}-{}", min, max)),
+                actual: Some(owned.to_string()),
+                ..issue(
+                    Some(s.slot),
+                    format!("slot {} owns {} record(s)", s.slot, owned),
+                )
+            });
+        }
+    }
+
+    for (&offset, _) in list.iter().zip(&owners).filter(|(_, &owner)| !owner) {
+        let n_owned = n_owned_at(offset);
+        if n_owned != 0 {
+            issues.push(DirectoryIssue {
+                expected: Some("0".to_string()),
+                actual: Some(n_owned.to_string()),
+                ..issue(
+                    None,
+                    format!(
+                        "record at offset {} has n_owned {} but owns no slot",
+                        offset, n_owned
+                    ),
+                )
+            });
+        }
+    }
+
+    Some(PageDirectory { slots, issues })
+}
+
+/// Binary-search the directory slots for a key.
+///
+/// `cmp` compares the record at a slot's owner offset against the search
+/// key. Returns the index of the last slot whose owner is less than or
+/// equal to the key, treating slot 0 (infimum) as smaller and the last
+/// slot (supremum) as greater than any key. Records from that owner up to
+/// the next slot's owner then hold the key's position.
+pub fn search_directory<F>(slots: &[usize], mut cmp: F) -> usize
+where
+    F: FnMut(usize) -> Ordering,
+{
+    let mut lo = 0;
+    let mut hi = slots.len().saturating_sub(1);
+    while hi > lo + 1 {
+        let mid = (lo + hi) / 2;
+        if cmp(slots[mid]) == Ordering::Greater {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    lo
+}
+
+/// How a key field's stored bytes compare.
+#[derive(Clone, Copy)]
+enum FieldOrder {
+    /// Plain byte comparison (integers, temporal types, DECIMAL, binary strings).
+    Bytes,
+    /// Byte comparison with trailing spaces ignored (`PAD SPACE` binary collations).
+    PadSpace,
+}
+
+/// The comparison for a key field, or `None` when its stored bytes do not
+/// sort in collation order (case-insensitive collations, multi-byte
+/// charsets, floating point).
+fn field_order(col: &ColumnStorageInfo) -> Option<FieldOrder> {
+    if col.is_system_column {
+        return Some(FieldOrder::Bytes);
+    }
+    match col.dd_type {
+        // Integers, YEAR, DATE, TIME/DATETIME/TIMESTAMP (old and new), BIT,
+        // DECIMAL, ENUM, SET: stored memcmp-comparable
+        2..=4 | 8..=10 | 12..=15 | 17..=23 => Some(FieldOrder::Bytes),
+        // CHAR, VARCHAR, and TEXT/BLOB prefixes
+        16 | 24..=29 => {
+            if col.collation_id == 63 {
+                return Some(FieldOrder::Bytes);
+            }
+            let name = collation_name(col.collation_id)?;
+            let byte_ordered = ["ascii_", "latin1_", "utf8_", "utf8mb3_", "utf8mb4_"]
+                .iter()
+                .any(|cs| name.starts_with(cs));
+            if !byte_ordered || !name.ends_with("_bin") {
+                None
+            } else if name.ends_with("_0900_bin") {
+                Some(FieldOrder::Bytes)
+            } else {
+                Some(FieldOrder::PadSpace)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Compare two stored field values, padding the shorter with spaces.
+pub(crate) fn cmp_pad_space(a: &[u8], b: &[u8]) -> Ordering {
+    let common = a.len().min(b.len());
+    match a[..common].cmp(&b[..common]) {
+        Ordering::Equal => {}
+        o => return o,
+    }
+    for &c in &a[common..] {
+        if c != b' ' {
+            return c.cmp(&b' ');
+        }
+    }
+    for &c in &b[common..] {
+        if c != b' ' {
+            return b' '.cmp(&c);
+        }
+    }
+    Ordering::Equal
+}
+
+/// Compare two stored values of a key field, or `None` when their order
+/// cannot be decided from the stored bytes (see [`check_record_order`]).
+pub(crate) fn compare_field_bytes(col: &ColumnStorageInfo, a: &[u8], b: &[u8]) -> Option<Ordering> {
+    Some(match field_order(col)? {
+        FieldOrder::Bytes => a.cmp(b),
+        FieldOrder::PadSpace => cmp_pad_space(a, b),
+    })
+}
+
+/// Whether the stored bytes of a key field sort in collation order.
+pub(crate) fn is_byte_ordered(col: &ColumnStorageInfo) -> bool {
+    field_order(col).is_some()
+}
+
+/// Stored key of a record: one entry per key field, `None` for NULL.
+type StoredKey<'a> = Vec<Option<&'a [u8]>>;
+
+/// Read the key fields of a record, or `None` if they cannot be located
+/// (or a key field is stored off-page).
+fn read_key<'a>(
+    page_data: &'a [u8],
+    origin: usize,
+    columns: &[ColumnStorageInfo],
+    n_key: usize,
+    compact: bool,
+) -> Option<StoredKey<'a>> {
+    let fields = read_record_fields(page_data, origin, columns, compact)?;
+    fields
+        .iter()
+        .take(n_key)
+        .map(|(start, field)| match field {
+            RecordField::Null => Some(None),
+            RecordField::Stored {
+                len,
+                external: false,
+            } => page_data.get(*start..*start + *len).map(Some),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Compare two stored keys field by field.
+///
+/// Returns `None` once a field is reached whose order cannot be decided
+/// from its stored bytes, unless an earlier field already decided it.
+fn compare_keys(a: &StoredKey, b: &StoredKey, layout: &IndexLayout) -> Option<Ordering> {
+    for (i, (fa, fb)) in a.iter().zip(b).enumerate() {
+        let col = &layout.columns[i];
+        let ord = match (fa, fb) {
+            // NULL sorts before any value
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(x), Some(y)) => compare_field_bytes(col, x, y)?,
+        };
+        let ord = if layout.descending.get(i).copied().unwrap_or(false) {
+            ord.reverse()
+        } else {
+            ord
+        };
+        if ord != Ordering::Equal {
+            return Some(ord);
+        }
+    }
+    Some(Ordering::Equal)
+}
+
+/// Compare the keys of two records of the same index, which may be on
+/// different pages and B+Tree levels.
+///
+/// Both records are compared on [`IndexLayout::node_pointer_columns`],
+/// reading each record with the fields stored at its page's level. Returns
+/// `None` if either key cannot be read or its order cannot be decided from
+/// the stored bytes (see [`check_record_order`]).
+pub fn compare_records(
+    page_a: &[u8],
+    origin_a: usize,
+    page_b: &[u8],
+    origin_b: usize,
+    layout: &IndexLayout,
+) -> Option<Ordering> {
+    let key_a = record_key(page_a, origin_a, layout)?;
+    let key_b = record_key(page_b, origin_b, layout)?;
+    compare_keys(&key_a, &key_b, layout)
+}
+
+/// Read the key of a record with the fields stored at its page's level.
+fn record_key<'a>(
+    page_data: &'a [u8],
+    origin: usize,
+    layout: &IndexLayout,
+) -> Option<StoredKey<'a>> {
+    let hdr = IndexHeader::parse(page_data)?;
+    let key_columns = layout.node_pointer_columns();
+    let columns = if hdr.level == 0 {
+        &layout.columns[..]
+    } else {
+        key_columns
+    };
+    read_key(
+        page_data,
+        origin,
+        columns,
+        key_columns.len(),
+        hdr.is_compact(),
+    )
+}
+
+/// Check that the records of an INDEX page are in strictly ascending key
+/// order.
+///
+/// Leaf records are compared on the index's key fields
+/// ([`IndexLayout::node_pointer_columns`], which for secondary indexes
+/// includes the primary key) and node pointers on their key prefix; the
+/// leftmost node pointer of a level (min_rec) is skipped. Fields whose
+/// stored bytes do not sort in collation order (e.g. case-insensitive
+/// strings) end the comparison of a record pair without a finding, so the
+/// check never reports records that may be correctly ordered.
+pub fn check_record_order(page_data: &[u8], layout: &IndexLayout) -> Vec<RecordOrderIssue> {
+    let hdr = match IndexHeader::parse(page_data) {
+        Some(h) => h,
+        None => return Vec::new(),
+    };
+    let compact = hdr.is_compact();
+    let key_columns = layout.node_pointer_columns();
+    let n_key = key_columns.len();
+    // Leaf records store every field; node pointers only the key prefix
+    let columns = if hdr.level == 0 {
+        &layout.columns[..]
+    } else {
+        key_columns
+    };
+
+    let mut issues = Vec::new();
+    let mut prev: Option<(usize, StoredKey)> = None;
+    for rec in walk_records(page_data) {
+        if hdr.level > 0 && rec.header.min_rec() {
+            continue;
+        }
+        let key = match read_key(page_data, rec.offset, columns, n_key, compact) {
+            Some(k) => k,
+            None => {
+                prev = None;
+                continue;
+            }
+        };
+        if let Some((prev_offset, ref prev_key)) = prev {
+            match compare_keys(prev_key, &key, layout) {
+                Some(Ordering::Greater) => issues.push(RecordOrderIssue {
+                    offset: rec.offset,
+                    prev_offset,
+                    message: format!(
+                        "record at offset {} sorts before the previous record at offset {} in index {}",
+                        rec.offset, prev_offset, layout.name
+                    ),
+                }),
+                Some(Ordering::Equal) => issues.push(RecordOrderIssue {
+                    offset: rec.offset,
+                    prev_offset,
+                    message: format!(
+                        "record at offset {} has the same key as the previous record at offset {} in index {}",
+                        rec.offset, prev_offset, layout.name
+                    ),
+                }),
+                _ => {}
+            }
+        }
+        prev = Some((rec.offset, key));
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PS: usize = 16384;
+
+    fn int_column(name: &str) -> ColumnStorageInfo {
+        ColumnStorageInfo {
+            name: name.to_string(),
+            dd_type: 4,
+            column_type: "int".to_string(),
+            is_nullable: false,
+            is_unsigned: false,
+            fixed_len: 4,
+            is_variable: false,
+            max_len: 4,
+            version_added: 0,
+            version_dropped: 0,
+            instant_default: None,
+            charset_max_bytes: 0,
+            collation_id: 0,
+            datetime_precision: 0,
+            is_system_column: false,
+            elements: Vec::new(),
+            numeric_precision: 10,
+            numeric_scale: 0,
+        }
+    }
+
+    fn layout() -> IndexLayout {
+        IndexLayout {
+            name: "PRIMARY".to_string(),
+            index_id: 7,
+            is_clustered: false,
+            columns: vec![int_column("id")],
+            descending: vec![false],
+            prefix_lengths: vec![None],
+        }
+    }
+
+    /// Compact leaf page holding `keys` as 4-byte INT records, with a
+    /// directory whose owners are the records at the `owners` positions
+    /// (1-based positions in the record list).
+    fn build_page(keys: &[i32], owners: &[usize]) -> Vec<u8> {
+        let mut page = vec![0u8; PS];
+        let base = FIL_PAGE_DATA;
+        BigEndian::write_u16(
+            &mut page[base + PAGE_N_HEAP..],
+            0x8000 | (keys.len() as u16 + 2),
+        );
+        BigEndian::write_u16(&mut page[base + PAGE_N_RECS..], keys.len() as u16);
+        BigEndian::write_u64(&mut page[base + PAGE_INDEX_ID..], 7);
+        BigEndian::write_u16(&mut page[PAGE_NEW_INFIMUM - 4..], 2);
+        BigEndian::write_u16(&mut page[PAGE_NEW_SUPREMUM - 4..], (1 << 3) | 3);
+
+        let mut list = vec![PAGE_NEW_INFIMUM];
+        let mut pos = PAGE_NEW_SUPREMUM_END;
+        for (i, key) in keys.iter().enumerate() {
+            let origin = pos + REC_N_NEW_EXTRA_BYTES;
+            BigEndian::write_u16(&mut page[origin - 4..], (i as u16 + 2) << 3);
+            BigEndian::write_u32(&mut page[origin..], (*key as u32) ^ 0x8000_0000);
+            list.push(origin);
+            pos = origin + 4;
+        }
+        list.push(PAGE_NEW_SUPREMUM);
+        BigEndian::write_u16(&mut page[base + PAGE_HEAP_TOP..], pos as u16);
+        for w in list.windows(2) {
+            let rel = (w[1] as i16).wrapping_sub(w[0] as i16);
+            BigEndian::write_i16(&mut page[w[0] - 2..], rel);
+        }
+
+        let mut slots = vec![0];
+        slots.extend_from_slice(owners);
+        slots.push(list.len() - 1);
+        BigEndian::write_u16(&mut page[base + PAGE_N_DIR_SLOTS..], slots.len() as u16);
+        for (i, w) in slots.iter().enumerate() {
+            let owned = if i == 0 {
+                1
+            } else {
+                w.saturating_sub(slots[i - 1])
+            };
+            page[list[*w] - REC_N_NEW_EXTRA_BYTES] = owned as u8;
+            let at = PS - SIZE_FIL_TRAILER - (i + 1) * PAGE_DIR_SLOT_SIZE;
+            BigEndian::write_u16(&mut page[at..], list[*w] as u16);
+        }
+        page
+    }
+
+    #[test]
+    fn test_valid_directory() {
+        let keys: Vec<i32> = (1..=10).collect();
+        let page = build_page(&keys, &[4]);
+        let dir = check_page_directory(&page).unwrap();
+        assert_eq!(dir.slots.len(), 3);
+        assert_eq!(dir.slots[1].n_owned, 4);
+        assert_eq!(dir.slots[2].n_owned, 7);
+        assert!(dir.issues.is_empty(), "{:?}", dir.issues);
+        assert!(check_record_order(&page, &layout()).is_empty());
+    }
+
+    #[test]
+    fn test_corrupt_directory() {
+        let keys: Vec<i32> = (1..=10).collect();
+
+        // n_owned of the middle owner off by one
+        let mut page = build_page(&keys, &[4]);
+        let owner = BigEndian::read_u16(&page[PS - 12..]) as usize;
+        page[owner - REC_N_NEW_EXTRA_BYTES] = 5;
+        let dir = check_page_directory(&page).unwrap();
+        assert_eq!(dir.issues.len(), 1);
+        assert_eq!(dir.issues[0].slot, Some(1));
+        assert_eq!(dir.issues[0].actual.as_deref(), Some("5"));
+
+        // Slot pointing into the middle of a record
+        let mut page = build_page(&keys, &[4]);
+        BigEndian::write_u16(&mut page[PS - 12..], owner as u16 + 1);
+        let dir = check_page_directory(&page).unwrap();
+        assert!(dir.issues[0].message.contains("not on the record list"));
+
+        // Slots swapped
+        let page = build_page(&keys, &[8, 4]);
+        let dir = check_page_directory(&page).unwrap();
+        assert!(dir
+            .issues
+            .iter()
+            .any(|i| i.message.contains("out of order")));
+    }
+
+    #[test]
+    fn test_record_order_and_search() {
+        let page = build_page(&[1, 2, 5, 4, 6], &[]);
+        let issues = check_record_order(&page, &layout());
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("sorts before"));
+
+        let keys: Vec<i32> = (0..40).map(|k| k * 10).collect();
+        let page = build_page(&keys, &[4, 8, 12, 16, 20, 24, 28, 32, 36]);
+        let slots = read_dir_slots(&page).unwrap();
+        let key_at = |o: usize| (BigEndian::read_u32(&page[o..]) ^ 0x8000_0000) as i32;
+        let slot = search_directory(&slots, |o| key_at(o).cmp(&215));
+        // Slot 5 is owned by the 20th record (key 190); slot 6 by key 230
+        assert_eq!(slot, 5);
+        assert_eq!(search_directory(&slots, |o| key_at(o).cmp(&-1)), 0);
+    }
+}
@@ -4,8 +4,9 @@
 //! record format. Each record has a header containing info bits, record type,
 //! heap number, and next-record pointer.
 //!
-//! This module provides [`RecordType`] classification, [`walk_compact_records`]
-//! and [`walk_redundant_records`] to traverse the singly-linked record chain
+//! This module provides [`RecordType`] classification, [`walk_compact_records`],
+//! [`walk_redundant_records`], and the format-selecting [`walk_records`] to
+//! traverse the singly-linked record chain
 //! within an INDEX page, starting from the infimum record.
 
 use byteorder::{BigEndian, ByteOrder};
@@ -80,6 +81,12 @@ pub struct CompactRecordHeader {
     pub delete_mark: bool,
     /// Min-rec flag (leftmost record on a non-leaf level).
     pub min_rec: bool,
+    /// Instant flag: the record stores its field count (instant ADD COLUMN,
+    /// MySQL 8.0.12-8.0.28).
+    pub instant: bool,
+    /// Version flag: the record stores a row version byte (instant ADD/DROP
+    /// COLUMN, MySQL 8.0.29+).
+    pub versioned: bool,
     /// Record's position in the heap.
     pub heap_no: u16,
     /// Record type.
@@ -122,12 +129,15 @@ impl CompactRecordHeader {
         }
 
         // Byte 0 layout: [info_bits(4) | n_owned(4)]
-        // Info bits (upper nibble): bit 5 = delete_mark, bit 4 = min_rec
+        // Info bits (upper nibble): bit 7 = instant, bit 6 = version,
+        // bit 5 = delete_mark, bit 4 = min_rec
         // n_owned (lower nibble): bits 0-3
         let byte0 = data[0];
         let n_owned = byte0 & 0x0F;
         let delete_mark = (byte0 & 0x20) != 0;
         let min_rec = (byte0 & 0x10) != 0;
+        let versioned = (byte0 & 0x40) != 0;
+        let instant = (byte0 & 0x80) != 0;
 
         let two_bytes = BigEndian::read_u16(&data[1..3]);
         let rec_type = RecordType::from_u8((two_bytes & 0x07) as u8);
@@ -139,6 +149,8 @@ impl CompactRecordHeader {
             n_owned,
             delete_mark,
             min_rec,
+            instant,
+            versioned,
             heap_no,
             rec_type,
             next_offset,
@@ -150,9 +162,12 @@ impl CompactRecordHeader {
 ///
 /// In redundant format, 6 bytes precede each record:
 /// - Byte 0: info bits (delete mark, min_rec flag) + n_owned
-/// - Bytes 1-2: heap_no (13 bits) + rec_type (3 bits)
+/// - Bytes 1-2: heap_no (13 bits) + high bits of n_fields (3 bits)
 /// - Bytes 2-3: n_fields (10 bits) + one_byte_offs flag (1 bit) (overlaps byte 2)
 /// - Bytes 4-5: next record offset (unsigned, absolute within page)
+///
+/// Redundant records carry no status bits; the record type is derived from
+/// the heap number (0 = infimum, 1 = supremum).
 #[derive(Debug, Clone)]
 pub struct RedundantRecordHeader {
     /// Number of records owned by this record in the page directory.
@@ -187,12 +202,11 @@ impl RedundantRecordHeader {
     /// let mut data = vec![0u8; 6];
     /// // byte 0: info_bits(4) | n_owned(4) — n_owned=1, no flags
     /// data[0] = 0x01;
-    /// // bytes 1-2: heap_no=5, rec_type=Ordinary(0) => (5 << 3) | 0 = 40
+    /// // bytes 1-2: heap_no=5 in the upper 13 bits => 5 << 3 = 40
     /// BigEndian::write_u16(&mut data[1..3], 5 << 3);
-    /// // bytes 2-3: n_fields=3, one_byte_offs=true => (3 << 6) | 0x20 = 0x00E0
-    /// // But byte 2 is shared — we set bytes 2-3 after bytes 1-2
-    /// // For this test, set byte 3 separately to encode n_fields in lower byte
-    /// data[3] = (3 << 6) as u8; // n_fields low bits + one_byte_offs=0
+    /// // bytes 2-3: n_fields=3 (bits 1-10), one_byte_offs=1 (bit 0) => 0x0007
+    /// // Byte 2 is shared with heap_no, so only byte 3 is written here
+    /// data[3] = (3 << 1) | 0x01;
     /// // bytes 4-5: next_offset = 200 (absolute)
     /// BigEndian::write_u16(&mut data[4..6], 200);
     ///
@@ -202,6 +216,8 @@ impl RedundantRecordHeader {
     /// assert_eq!(hdr.heap_no, 5);
     /// assert_eq!(hdr.rec_type, RecordType::Ordinary);
     /// assert_eq!(hdr.next_offset, 200);
+    /// assert_eq!(hdr.n_fields, 3);
+    /// assert!(hdr.one_byte_offs);
     /// ```
     pub fn parse(data: &[u8]) -> Option<Self> {
         if data.len() < REC_N_OLD_EXTRA_BYTES {
@@ -214,16 +230,19 @@ impl RedundantRecordHeader {
         let delete_mark = (byte0 & 0x20) != 0;
         let min_rec = (byte0 & 0x10) != 0;
 
-        // Bytes 1-2: heap_no (13 bits) + rec_type (3 bits) — same as compact
-        let heap_status = BigEndian::read_u16(&data[1..3]);
-        let rec_type = RecordType::from_u8((heap_status & 0x07) as u8);
-        let heap_no = (heap_status >> 3) & 0x1FFF;
+        // Bytes 1-2: heap_no (13 bits, REC_OLD_HEAP_NO mask 0xFFF8)
+        let heap_no = (BigEndian::read_u16(&data[1..3]) >> 3) & 0x1FFF;
+        let rec_type = match heap_no {
+            0 => RecordType::Infimum,
+            1 => RecordType::Supremum,
+            _ => RecordType::Ordinary,
+        };
 
-        // Bytes 2-3: n_fields (10 bits) + one_byte_offs_flag (1 bit) + unused (5 bits)
-        // Byte 2 is shared with the heap_no/rec_type word above.
+        // Bytes 2-3: n_fields (REC_OLD_N_FIELDS mask 0x07FE) + one_byte_offs
+        // (REC_OLD_SHORT mask 0x0001). Byte 2 is shared with heap_no above.
         let nf_word = BigEndian::read_u16(&data[2..4]);
-        let n_fields = (nf_word >> 6) & 0x03FF;
-        let one_byte_offs = (nf_word & 0x20) != 0;
+        let n_fields = (nf_word & 0x07FE) >> 1;
+        let one_byte_offs = (nf_word & 0x0001) != 0;
 
         // Bytes 4-5: next record offset (absolute, unsigned)
         let next_offset = BigEndian::read_u16(&data[4..6]);
@@ -470,6 +489,53 @@ pub fn walk_redundant_records(page_data: &[u8]) -> Vec<RecordInfo> {
     records
 }
 
+/// Walk all user records on an INDEX page in either row format.
+///
+/// Selects [`walk_compact_records`] or [`walk_redundant_records`] from the
+/// compact flag in the page's `PAGE_N_HEAP` field (see
+/// [`IndexHeader::is_compact`](crate::innodb::index::IndexHeader::is_compact)).
+/// Pages too short to carry an INDEX header are walked as compact.
+pub fn walk_records(page_data: &[u8]) -> Vec<RecordInfo> {
+    match crate::innodb::index::IndexHeader::parse(page_data) {
+        Some(hdr) if !hdr.is_compact() => walk_redundant_records(page_data),
+        _ => walk_compact_records(page_data),
+    }
+}
+
+/// Read the header of the record whose origin is at `origin`.
+///
+/// Works for any record on the page, including infimum and supremum.
+/// Returns `None` if the header does not fit in the page.
+pub fn read_record_header(page_data: &[u8], origin: usize, compact: bool) -> Option<RecordHeader> {
+    if compact {
+        let start = origin.checked_sub(REC_N_NEW_EXTRA_BYTES)?;
+        CompactRecordHeader::parse(page_data.get(start..origin)?).map(RecordHeader::Compact)
+    } else {
+        let start = origin.checked_sub(REC_N_OLD_EXTRA_BYTES)?;
+        RedundantRecordHeader::parse(page_data.get(start..origin)?).map(RecordHeader::Redundant)
+    }
+}
+
+/// Origin of the record that follows the record at `origin` in the record
+/// list, given that record's header.
+///
+/// Returns `None` at the end of the list (a zero next pointer) or when the
+/// pointer leaves the page.
+pub fn next_record_origin(page_data: &[u8], origin: usize, header: &RecordHeader) -> Option<usize> {
+    let next = match header {
+        // Relative offsets are added modulo 64K, as InnoDB does
+        RecordHeader::Compact(h) if h.next_offset != 0 => {
+            (origin as u16).wrapping_add(h.next_offset as u16) as usize
+        }
+        RecordHeader::Redundant(h) if h.next_offset != 0 => h.next_offset as usize,
+        _ => return None,
+    };
+    if next == 0 || next >= page_data.len() {
+        return None;
+    }
+    Some(next)
+}
+
 /// Parse the variable-length field lengths from a compact record's null bitmap
 /// and variable-length header. Returns the field data starting offset.
 ///
@@ -595,18 +661,33 @@ mod tests {
         assert_eq!(hdr.next_offset, -50);
     }
 
+    #[test]
+    fn test_compact_record_header_instant_flags() {
+        let mut data = vec![0u8; 5];
+        data[0] = 0x40; // version flag
+        let hdr = CompactRecordHeader::parse(&data).unwrap();
+        assert!(hdr.versioned);
+        assert!(!hdr.instant);
+        assert!(!hdr.delete_mark);
+
+        data[0] = 0xA0; // instant flag + delete_mark
+        let hdr = CompactRecordHeader::parse(&data).unwrap();
+        assert!(hdr.instant);
+        assert!(!hdr.versioned);
+        assert!(hdr.delete_mark);
+    }
+
     #[test]
     fn test_redundant_record_header_parse() {
         let mut data = vec![0u8; 6];
         // byte 0: n_owned=2, delete_mark=1 => 0x22
         data[0] = 0x22;
-        // bytes 1-2: heap_no=8, rec_type=Ordinary(0) => (8 << 3) = 64
+        // bytes 1-2: heap_no=8 => (8 << 3) = 64
         BigEndian::write_u16(&mut data[1..3], 8 << 3);
-        // bytes 2-3 overlap — byte 2 is shared; set byte 3 for n_fields encoding
-        // n_fields=5, one_byte_offs=false: (5 << 6) = 320 = 0x0140
-        // But byte 2 is already set from the heap_no write, so we only set byte 3
-        data[3] = 0x40; // lower byte: n_fields bits 1-0 shifted + one_byte_offs=0
-                        // bytes 4-5: next_offset = 300 (absolute)
+        // bytes 2-3 overlap — byte 2 is shared; set byte 3 for n_fields=5,
+        // one_byte_offs=false: 5 << 1 = 0x0A
+        data[3] = 0x0A;
+        // bytes 4-5: next_offset = 300 (absolute)
         BigEndian::write_u16(&mut data[4..6], 300);
 
         let hdr = RedundantRecordHeader::parse(&data).unwrap();
@@ -615,6 +696,20 @@ mod tests {
         assert_eq!(hdr.heap_no, 8);
         assert_eq!(hdr.rec_type, RecordType::Ordinary);
         assert_eq!(hdr.next_offset, 300);
+        assert_eq!(hdr.n_fields, 5);
+        assert!(!hdr.one_byte_offs);
+    }
+
+    #[test]
+    fn test_redundant_record_header_supremum() {
+        // Supremum: heap_no=1, n_fields=1, one_byte_offs=1
+        let mut data = vec![0u8; 6];
+        BigEndian::write_u16(&mut data[1..3], 1 << 3);
+        data[3] = (1 << 1) | 0x01;
+        let hdr = RedundantRecordHeader::parse(&data).unwrap();
+        assert_eq!(hdr.rec_type, RecordType::Supremum);
+        assert_eq!(hdr.n_fields, 1);
+        assert!(hdr.one_byte_offs);
     }
 
     #[test]
@@ -0,0 +1,1980 @@
+//! Offline redo log application.
+//!
+//! Rolls tablespace pages forward by replaying the page-level records
+//! decoded by [`mlog`](crate::innodb::mlog), the way InnoDB crash recovery
+//! does: records are grouped into mini-transactions, a mini-transaction is
+//! applied to a page only if it starts at or after the page's
+//! `FIL_PAGE_LSN`, and the page LSN then advances to the mini-transaction's
+//! end LSN. A mini-transaction still open at the end of the log is
+//! discarded, as are mini-transactions that end after the target LSN.
+//!
+//! Record-level operations (inserts, deletes, list deletes, reorganization)
+//! reproduce the heap, free list, and page directory handling of
+//! `page_cur_insert_rec_low()`, `page_cur_delete_rec()`, and
+//! `page_dir_split_slot()` / `page_dir_balance_slot()` exactly, so later
+//! records that address the page by byte offset land where the server put
+//! them. ROW_FORMAT=COMPRESSED records (`MLOG_ZIP_*`) and records of
+//! instant-versioned rows cannot be replayed; a page that needs one is
+//! reported and left unchanged.
+//!
+//! [`plan_redo_apply`] selects the records to replay, [`apply_to_tablespace`]
+//! replays them against one tablespace, and [`apply_page_redo`] replays them
+//! against a single page image. Checksums are left to the caller (see
+//! `write::fix_page_checksum`).
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use idb::innodb::log::LogFile;
+//! use idb::innodb::mlog::read_mlog_records;
+//! use idb::innodb::redo_apply::{apply_to_tablespace, plan_redo_apply};
+//! use idb::innodb::tablespace::Tablespace;
+//!
+//! let mut log = LogFile::open("ib_logfile0").unwrap();
+//! let checkpoint = log.read_checkpoint(0).unwrap().lsn;
+//! let decoded = read_mlog_records(&mut log).unwrap();
+//! let plan = plan_redo_apply(&decoded.records, checkpoint, None);
+//!
+//! let mut ts = Tablespace::open("table.ibd").unwrap();
+//! let space_id = ts.fsp_header().unwrap().space_id;
+//! let result = apply_to_tablespace(&mut ts, &plan, space_id).unwrap();
+//! println!("{} records applied", result.records_applied);
+//! ```
+
+use std::collections::BTreeMap;
+
+use byteorder::{BigEndian, ByteOrder};
+use serde::Serialize;
+
+use crate::innodb::constants::*;
+use crate::innodb::log::MlogRecordType;
+use crate::innodb::mlog::{MlogBody, MlogIndex, MlogInsert, MlogRecord, MlogSysFields};
+use crate::innodb::page_types::PageType;
+use crate::innodb::tablespace::Tablespace;
+use crate::IdbError;
+
+/// `BTR_KEEP_SYS_FLAG`: leave DB_TRX_ID and DB_ROLL_PTR untouched.
+const BTR_KEEP_SYS_FLAG: u8 = 4;
+
+/// End of the redundant supremum record (heap start of an empty page).
+const PAGE_OLD_SUPREMUM_END: usize = PAGE_OLD_SUPREMUM + 9;
+/// Bytes of the page header cleared by `page_create()` (up to PAGE_LEVEL).
+const PAGE_HEADER_PRIV_END: usize = 26;
+/// Page directory starts this many bytes before the end of the page.
+const PAGE_DIR: usize = SIZE_FIL_TRAILER;
+
+/// Infimum and supremum of an empty compact page (`page0page.cc`).
+const INFIMUM_SUPREMUM_COMPACT: [u8; 26] = [
+    0x01, 0x00, 0x02, 0x00, 0x0d, b'i', b'n', b'f', b'i', b'm', b'u', b'm', 0x00, //
+    0x01, 0x00, 0x0b, 0x00, 0x00, b's', b'u', b'p', b'r', b'e', b'm', b'u', b'm',
+];
+
+/// Infimum and supremum of an empty redundant page (`page0page.cc`).
+const INFIMUM_SUPREMUM_REDUNDANT: [u8; 31] = [
+    0x08, 0x01, 0x00, 0x00, 0x03, 0x00, 0x74, b'i', b'n', b'f', b'i', b'm', b'u', b'm',
+    0x00, //
+    0x09, 0x01, 0x00, 0x08, 0x03, 0x00, 0x00, b's', b'u', b'p', b'r', b'e', b'm', b'u', b'm', 0x00,
+];
+
+/// Result of a failed page operation; turned into an [`IdbError`] with the
+/// record's type and LSN by [`apply_page_redo`].
+type Apply<T> = Result<T, String>;
+
+// ── Planning ────────────────────────────────────────────────────────────
+
+/// A record to replay on one page, with the LSN range of its
+/// mini-transaction.
+#[derive(Debug, Clone, Serialize)]
+pub struct PageRedo {
+    /// Start LSN of the mini-transaction the record belongs to.
+    pub mtr_start_lsn: u64,
+    /// End LSN of the mini-transaction the record belongs to.
+    pub mtr_end_lsn: u64,
+    /// The record itself.
+    pub record: MlogRecord,
+}
+
+/// Page-level redo selected for replay, grouped by tablespace and page.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RedoApplyPlan {
+    /// Mini-transactions starting before this LSN were skipped.
+    pub from_lsn: u64,
+    /// End LSN of the last mini-transaction selected (`from_lsn` if none).
+    pub end_lsn: u64,
+    /// Mini-transactions selected for replay.
+    pub mtr_count: u64,
+    /// Mini-transactions skipped because they start before `from_lsn`.
+    pub mtrs_before: u64,
+    /// Mini-transactions skipped because they end after the target LSN.
+    pub mtrs_after: u64,
+    /// Mini-transactions discarded because their records were not contiguous.
+    pub mtrs_broken: u64,
+    /// Start LSN of a mini-transaction cut off by the end of the log.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub incomplete_at: Option<u64>,
+    /// Indexes built without redo logging (`MLOG_INDEX_LOAD`) in the
+    /// replayed range; their pages cannot be recovered from the log.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub index_loads: Vec<u64>,
+    /// Records to replay, keyed by space ID and page number, in LSN order.
+    #[serde(skip)]
+    pub pages: BTreeMap<u32, BTreeMap<u32, Vec<PageRedo>>>,
+}
+
+impl RedoApplyPlan {
+    /// Space IDs with at least one page to roll forward.
+    pub fn space_ids(&self) -> Vec<u32> {
+        self.pages.keys().copied().collect()
+    }
+
+    /// Total number of pages with redo to replay.
+    pub fn page_count(&self) -> usize {
+        self.pages.values().map(BTreeMap::len).sum()
+    }
+
+    /// Total number of page records to replay.
+    pub fn record_count(&self) -> usize {
+        self.pages
+            .values()
+            .flat_map(BTreeMap::values)
+            .map(Vec::len)
+            .sum()
+    }
+}
+
+/// Whether replaying a record changes the page it references.
+fn modifies_page(record_type: MlogRecordType) -> bool {
+    use MlogRecordType as T;
+    !matches!(
+        record_type,
+        T::MlogMultiRecEnd
+            | T::MlogDummyRecord
+            | T::MlogLsn
+            | T::MlogFileCreate
+            | T::MlogFileRename
+            | T::MlogFileDelete
+            | T::MlogFileExtend
+            | T::MlogIndexLoad
+            | T::MlogTableDynamicMeta
+            | T::MlogTest
+            | T::MariaOption
+            | T::MariaFileCreate
+            | T::MariaFileDelete
+            | T::MariaFileRename
+            | T::MariaFileModify
+            | T::MariaFileCheckpoint
+    )
+}
+
+/// Group decoded records into mini-transactions and select the page
+/// records to replay.
+///
+/// `records` must be in LSN order (as returned by
+/// [`read_mlog_records`](crate::innodb::mlog::read_mlog_records)). A
+/// mini-transaction is either one record with the single-record flag or a
+/// run of records closed by `MLOG_MULTI_REC_END`. Mini-transactions that
+/// start before `from_lsn` (normally the checkpoint LSN) or end after
+/// `to_lsn` are skipped.
+pub fn plan_redo_apply(
+    records: &[MlogRecord],
+    from_lsn: u64,
+    to_lsn: Option<u64>,
+) -> RedoApplyPlan {
+    let mut plan = RedoApplyPlan {
+        from_lsn,
+        end_lsn: from_lsn,
+        ..Default::default()
+    };
+    let mut pending: Vec<&MlogRecord> = Vec::new();
+
+    for rec in records {
+        let contiguous = pending
+            .last()
+            .is_none_or(|last| last.end_lsn == rec.start_lsn);
+        if !pending.is_empty() && (!contiguous || rec.single_rec) {
+            plan.mtrs_broken += 1;
+            pending.clear();
+        }
+        if pending.is_empty() && rec.single_rec {
+            select_mtr(&mut plan, &[rec], to_lsn);
+            continue;
+        }
+        if pending.is_empty() && rec.record_type == MlogRecordType::MlogMultiRecEnd {
+            continue;
+        }
+        pending.push(rec);
+        if rec.record_type == MlogRecordType::MlogMultiRecEnd {
+            select_mtr(&mut plan, &pending, to_lsn);
+            pending.clear();
+        }
+    }
+    plan.incomplete_at = pending.first().map(|rec| rec.start_lsn);
+
+    plan
+}
+
+/// Add the page records of one complete mini-transaction to the plan.
+fn select_mtr(plan: &mut RedoApplyPlan, mtr: &[&MlogRecord], to_lsn: Option<u64>) {
+    let start_lsn = mtr[0].start_lsn;
+    let end_lsn = mtr[mtr.len() - 1].end_lsn;
+    if start_lsn < plan.from_lsn {
+        plan.mtrs_before += 1;
+        return;
+    }
+    if to_lsn.is_some_and(|to| end_lsn > to) {
+        plan.mtrs_after += 1;
+        return;
+    }
+
+    plan.mtr_count += 1;
+    plan.end_lsn = plan.end_lsn.max(end_lsn);
+    for rec in mtr {
+        if let MlogBody::IndexLoad { index_id } = rec.body {
+            plan.index_loads.push(index_id);
+        }
+        let (Some(space_id), Some(page_no)) = (rec.space_id, rec.page_no) else {
+            continue;
+        };
+        if !modifies_page(rec.record_type) {
+            continue;
+        }
+        plan.pages
+            .entry(space_id)
+            .or_default()
+            .entry(page_no)
+            .or_default()
+            .push(PageRedo {
+                mtr_start_lsn: start_lsn,
+                mtr_end_lsn: end_lsn,
+                record: (*rec).clone(),
+            });
+    }
+}
+
+// ── Tablespace application ──────────────────────────────────────────────
+
+/// Outcome of rolling one page forward.
+#[derive(Debug, Clone, Serialize)]
+pub struct PageApplyResult {
+    /// Page number.
+    pub page_no: u32,
+    /// `FIL_PAGE_LSN` before replay.
+    pub old_lsn: u64,
+    /// `FIL_PAGE_LSN` after replay.
+    pub new_lsn: u64,
+    /// Records replayed.
+    pub applied: usize,
+    /// Records skipped because the page already contained them.
+    pub skipped: usize,
+    /// Whether the page lies beyond the end of the file and was created.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub extended: bool,
+    /// Why replay stopped; the page is left unchanged.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of rolling one tablespace forward.
+#[derive(Debug, Clone, Serialize)]
+pub struct TablespaceApplyResult {
+    /// Tablespace ID.
+    pub space_id: u32,
+    /// Pages that received at least one record.
+    pub pages_changed: u64,
+    /// Pages that already contained all their records.
+    pub pages_current: u64,
+    /// Pages left unchanged because a record could not be replayed.
+    pub pages_failed: u64,
+    /// Records replayed across all pages.
+    pub records_applied: u64,
+    /// Records skipped across all pages.
+    pub records_skipped: u64,
+    /// Per-page outcomes, in page order.
+    pub pages: Vec<PageApplyResult>,
+    /// Rolled-forward pages as `(page_number, contents)`, without checksums.
+    #[serde(skip)]
+    pub changed: Vec<(u32, Vec<u8>)>,
+}
+
+/// Replay the planned records of tablespace `space_id` against `ts`.
+///
+/// Pages are read through the tablespace (so page-compressed pages are
+/// replayed on their logical image); pages past the end of the file start
+/// out zero-filled, as InnoDB would find them after a crash during file
+/// extension. Only pages that changed are returned in
+/// [`TablespaceApplyResult::changed`]. A failed page is left out, but the
+/// other pages of its mini-transactions are not, so callers should not
+/// write `changed` when [`TablespaceApplyResult::pages_failed`] is nonzero.
+pub fn apply_to_tablespace(
+    ts: &mut Tablespace,
+    plan: &RedoApplyPlan,
+    space_id: u32,
+) -> Result<TablespaceApplyResult, IdbError> {
+    let page_size = ts.page_size() as usize;
+    let mut result = TablespaceApplyResult {
+        space_id,
+        pages_changed: 0,
+        pages_current: 0,
+        pages_failed: 0,
+        records_applied: 0,
+        records_skipped: 0,
+        pages: Vec::new(),
+        changed: Vec::new(),
+    };
+    let Some(pages) = plan.pages.get(&space_id) else {
+        return Ok(result);
+    };
+
+    for (&page_no, redo) in pages {
+        let extended = u64::from(page_no) >= ts.page_count();
+        let original = if extended {
+            vec![0u8; page_size]
+        } else {
+            ts.read_page(u64::from(page_no))?
+        };
+        let old_lsn = BigEndian::read_u64(&original[FIL_PAGE_LSN..]);
+        let mut page = original.clone();
+
+        let mut outcome = PageApplyResult {
+            page_no,
+            old_lsn,
+            new_lsn: old_lsn,
+            applied: 0,
+            skipped: 0,
+            extended,
+            error: None,
+        };
+        match apply_page_redo(&mut page, redo) {
+            Ok(applied) => {
+                outcome.applied = applied;
+                outcome.skipped = redo.len() - applied;
+                outcome.new_lsn = BigEndian::read_u64(&page[FIL_PAGE_LSN..]);
+                result.records_applied += applied as u64;
+                result.records_skipped += outcome.skipped as u64;
+                if applied > 0 {
+                    result.pages_changed += 1;
+                    result.changed.push((page_no, page));
+                } else {
+                    result.pages_current += 1;
+                }
+            }
+            Err(e) => {
+                outcome.error = Some(e.to_string());
+                result.pages_failed += 1;
+            }
+        }
+        result.pages.push(outcome);
+    }
+
+    Ok(result)
+}
+
+/// Report every planned page of tablespace `space_id` as failed with
+/// `reason`, for a tablespace whose pages cannot be replayed at all (such
+/// as an encrypted or ROW_FORMAT=COMPRESSED one).
+///
+/// The page LSNs are read from `ts`; nothing is replayed.
+pub fn reject_tablespace(
+    ts: &mut Tablespace,
+    plan: &RedoApplyPlan,
+    space_id: u32,
+    reason: &str,
+) -> Result<TablespaceApplyResult, IdbError> {
+    let mut result = TablespaceApplyResult {
+        space_id,
+        pages_changed: 0,
+        pages_current: 0,
+        pages_failed: 0,
+        records_applied: 0,
+        records_skipped: 0,
+        pages: Vec::new(),
+        changed: Vec::new(),
+    };
+    let Some(pages) = plan.pages.get(&space_id) else {
+        return Ok(result);
+    };
+
+    for &page_no in pages.keys() {
+        let extended = u64::from(page_no) >= ts.page_count();
+        let old_lsn = if extended {
+            0
+        } else {
+            BigEndian::read_u64(&ts.read_page(u64::from(page_no))?[FIL_PAGE_LSN..])
+        };
+        result.pages_failed += 1;
+        result.pages.push(PageApplyResult {
+            page_no,
+            old_lsn,
+            new_lsn: old_lsn,
+            applied: 0,
+            skipped: 0,
+            extended,
+            error: Some(reason.to_string()),
+        });
+    }
+
+    Ok(result)
+}
+
+/// Replay the records of one page in order.
+///
+/// Records whose mini-transaction starts before the page's original
+/// `FIL_PAGE_LSN` are skipped; after each replayed record the page LSN is
+/// set to its mini-transaction's end LSN. Returns the number of records
+/// replayed. On error the page may be partially modified.
+pub fn apply_page_redo(page: &mut [u8], redo: &[PageRedo]) -> Result<usize, IdbError> {
+    if page.len() < SIZE_PAGE_4K as usize || !page.len().is_power_of_two() {
+        return Err(IdbError::Argument(format!(
+            "Invalid page size {} for redo apply",
+            page.len()
+        )));
+    }
+    let page_lsn = BigEndian::read_u64(&page[FIL_PAGE_LSN..]);
+    let mut applied = 0;
+    for item in redo {
+        if item.mtr_start_lsn < page_lsn {
+            continue;
+        }
+        apply_record(page, &item.record).map_err(|e| {
+            IdbError::Parse(format!(
+                "{} at LSN {}: {}",
+                item.record.record_type, item.record.start_lsn, e
+            ))
+        })?;
+        BigEndian::write_u64(&mut page[FIL_PAGE_LSN..], item.mtr_end_lsn);
+        applied += 1;
+    }
+    Ok(applied)
+}
+
+/// Replay one record on a page image.
+fn apply_record(page: &mut [u8], rec: &MlogRecord) -> Apply<()> {
+    use MlogRecordType as T;
+
+    let page_type = match rec.record_type {
+        T::MlogPageCreate | T::MlogCompPageCreate => Some(PageType::Index),
+        T::MlogPageCreateRTree | T::MlogCompPageCreateRTree => Some(PageType::Rtree),
+        T::MlogPageCreateSdi | T::MlogCompPageCreateSdi => Some(PageType::Sdi),
+        _ => None,
+    };
+    if let Some(page_type) = page_type {
+        let comp = matches!(
+            rec.record_type,
+            T::MlogCompPageCreate | T::MlogCompPageCreateRTree | T::MlogCompPageCreateSdi
+        );
+        Frame::create(page, comp, page_type.as_u16());
+        return Ok(());
+    }
+
+    match &rec.body {
+        MlogBody::WriteBytes { offset, value } => {
+            let width = match rec.record_type {
+                T::Mlog1Byte => 1,
+                T::Mlog2Bytes => 2,
+                T::Mlog4Bytes => 4,
+                _ => 8,
+            };
+            let dst = slice_mut(page, *offset as usize, width)?;
+            BigEndian::write_uint(dst, *value, width);
+        }
+        MlogBody::WriteString { offset, data } => {
+            slice_mut(page, *offset as usize, data.len())?.copy_from_slice(data);
+        }
+        MlogBody::Empty => match rec.record_type {
+            T::MlogInitFilePage | T::MlogInitFilePage2 => {
+                page.fill(0);
+                BigEndian::write_u32(&mut page[FIL_PAGE_OFFSET..], rec.page_no.unwrap_or(0));
+                BigEndian::write_u32(&mut page[FIL_PAGE_SPACE_ID..], rec.space_id.unwrap_or(0));
+            }
+            T::MlogIbufBitmapInit => {
+                let len = page.len() * IBUF_BITS_PER_PAGE / 8;
+                BigEndian::write_u16(&mut page[FIL_PAGE_TYPE..], PageType::IbufBitmap.as_u16());
+                slice_mut(page, PAGE_DATA_OFFSET, len)?.fill(0);
+            }
+            T::MlogUndoEraseEnd => {
+                let free = read16(page, TRX_UNDO_PAGE_HDR + TRX_UNDO_PAGE_FREE);
+                let end = page.len() - SIZE_FIL_TRAILER;
+                if free > end {
+                    return Err(format!("undo page free offset {} out of range", free));
+                }
+                page[free..end].fill(0xFF);
+            }
+            _ => {}
+        },
+        MlogBody::UndoInit { undo_type } => {
+            let hdr = TRX_UNDO_PAGE_HDR;
+            let start = (TRX_UNDO_PAGE_HDR + TRX_UNDO_PAGE_HDR_SIZE) as u16;
+            BigEndian::write_u16(&mut page[hdr + TRX_UNDO_PAGE_TYPE..], *undo_type as u16);
+            BigEndian::write_u16(&mut page[hdr + TRX_UNDO_PAGE_START..], start);
+            BigEndian::write_u16(&mut page[hdr + TRX_UNDO_PAGE_FREE..], start);
+            BigEndian::write_u16(&mut page[FIL_PAGE_TYPE..], PageType::UndoLog.as_u16());
+        }
+        MlogBody::UndoInsert { data } => {
+            let free = read16(page, TRX_UNDO_PAGE_HDR + TRX_UNDO_PAGE_FREE);
+            let new_free = free + 4 + data.len();
+            if free < TRX_UNDO_SEG_HDR || new_free > page.len() - SIZE_FIL_TRAILER {
+                return Err(format!(
+                    "undo record of {} bytes does not fit at offset {}",
+                    data.len(),
+                    free
+                ));
+            }
+            BigEndian::write_u16(&mut page[free..], new_free as u16);
+            page[free + 2..free + 2 + data.len()].copy_from_slice(data);
+            BigEndian::write_u16(&mut page[free + 2 + data.len()..], free as u16);
+            BigEndian::write_u16(
+                &mut page[TRX_UNDO_PAGE_HDR + TRX_UNDO_PAGE_FREE..],
+                new_free as u16,
+            );
+        }
+        MlogBody::UndoHeader { trx_id } => {
+            if rec.record_type == T::MlogUndoHdrReuse {
+                undo_header_reuse(page, *trx_id);
+            } else {
+                undo_header_create(page, *trx_id)?;
+            }
+        }
+        MlogBody::RecMinMark { offset } => {
+            let mut f = Frame::new(page);
+            let rec = f.user_rec(*offset)?;
+            let bits = f.info_bits(rec);
+            f.set_info_bits(rec, bits | REC_INFO_MIN_REC_FLAG);
+        }
+        MlogBody::SecDeleteMark { value, offset, .. } => {
+            let mut f = Frame::new(page);
+            let rec = f.user_rec(*offset)?;
+            f.set_deleted(rec, *value);
+        }
+        MlogBody::ClustDeleteMark {
+            index,
+            flags,
+            value,
+            sys,
+            offset,
+        } => {
+            let mut f = Frame::new(page);
+            let rec = f.user_rec(*offset)?;
+            f.set_deleted(rec, *value);
+            if flags & BTR_KEEP_SYS_FLAG == 0 {
+                f.write_sys_fields(rec, index, sys)?;
+            }
+        }
+        MlogBody::UpdateInPlace {
+            index,
+            flags,
+            sys,
+            offset,
+            info_bits,
+            fields,
+        } => {
+            let mut f = Frame::new(page);
+            let rec = f.user_rec(*offset)?;
+            if flags & BTR_KEEP_SYS_FLAG == 0 {
+                f.write_sys_fields(rec, index, sys)?;
+            }
+            f.set_info_bits(rec, *info_bits);
+            let offsets = f.offsets(rec, index)?;
+            for field in fields {
+                f.update_field(
+                    rec,
+                    &offsets,
+                    field.field_no as usize,
+                    field.data.as_deref(),
+                )?;
+            }
+        }
+        MlogBody::RecInsert { index, insert } => {
+            let mut f = Frame::new(page);
+            let cursor = insert
+                .cursor_offset
+                .ok_or_else(|| "insert without a cursor record".to_string())?;
+            let cursor = f.rec_at(cursor)?;
+            f.parse_insert(cursor, index, insert)?;
+        }
+        MlogBody::ListCopyCreated { index, inserts } => {
+            let mut f = Frame::new(page);
+            for insert in inserts {
+                let cursor = f.prev(f.supremum())?;
+                f.parse_insert(cursor, index, insert)?;
+            }
+            f.set_header(PAGE_LAST_INSERT, 0);
+            if !f.spatial() {
+                f.set_header(PAGE_DIRECTION, PAGE_NO_DIRECTION as usize);
+                f.set_header(PAGE_N_DIRECTION, 0);
+            }
+        }
+        MlogBody::RecDelete { index, offset } => {
+            let mut f = Frame::new(page);
+            let rec = f.user_rec(*offset)?;
+            f.delete_rec(rec, index)?;
+        }
+        MlogBody::ListDelete { index, offset } => {
+            let mut f = Frame::new(page);
+            let at = f.rec_at(*offset)?;
+            match rec.record_type {
+                T::MlogListEndDelete8027 | T::MlogCompListEndDelete8027 | T::MlogListEndDelete => {
+                    f.delete_list_end(at, index)?
+                }
+                _ => f.delete_list_start(at, index)?,
+            }
+        }
+        MlogBody::PageReorganize {
+            index,
+            compression_level: None,
+        } => {
+            Frame::new(page).reorganize(index)?;
+        }
+        MlogBody::PageReorganize { .. }
+        | MlogBody::ZipWriteNodePtr { .. }
+        | MlogBody::ZipWriteBlobPtr { .. }
+        | MlogBody::ZipWriteHeader { .. }
+        | MlogBody::ZipPageCompress { .. } => {
+            return Err("compressed (ROW_FORMAT=COMPRESSED) page records are not supported".into());
+        }
+        MlogBody::Memset { .. } | MlogBody::Memmove { .. } | MlogBody::Extended { .. } => {
+            return Err("MariaDB redo records are not supported".into());
+        }
+        MlogBody::FileCreate { .. }
+        | MlogBody::FileRename { .. }
+        | MlogBody::FileDelete { .. }
+        | MlogBody::FileExtend { .. }
+        | MlogBody::IndexLoad { .. }
+        | MlogBody::TableDynamicMeta { .. }
+        | MlogBody::Test { .. }
+        | MlogBody::FileModify { .. }
+        | MlogBody::FileCheckpoint { .. } => {}
+    }
+    Ok(())
+}
+
+/// `trx_undo_header_create()`: start a new undo log on an undo page.
+fn undo_header_create(page: &mut [u8], trx_id: u64) -> Apply<()> {
+    let free = read16(page, TRX_UNDO_PAGE_HDR + TRX_UNDO_PAGE_FREE);
+    if free < TRX_UNDO_SEG_HDR || free + TRX_UNDO_LOG_XA_HDR_SIZE >= page.len() - 100 {
+        return Err(format!("undo page free offset {} out of range", free));
+    }
+    let new_free = (free + TRX_UNDO_LOG_OLD_HDR_SIZE) as u16;
+    let seg = TRX_UNDO_SEG_HDR;
+    BigEndian::write_u16(
+        &mut page[TRX_UNDO_PAGE_HDR + TRX_UNDO_PAGE_START..],
+        new_free,
+    );
+    BigEndian::write_u16(
+        &mut page[TRX_UNDO_PAGE_HDR + TRX_UNDO_PAGE_FREE..],
+        new_free,
+    );
+    BigEndian::write_u16(&mut page[seg + TRX_UNDO_STATE..], TRX_UNDO_ACTIVE);
+
+    let prev_log = read16(page, seg + TRX_UNDO_LAST_LOG);
+    if prev_log != 0 {
+        slice_mut(page, prev_log + TRX_UNDO_NEXT_LOG, 2)?
+            .copy_from_slice(&(free as u16).to_be_bytes());
+    }
+    BigEndian::write_u16(&mut page[seg + TRX_UNDO_LAST_LOG..], free as u16);
+
+    let log = free;
+    BigEndian::write_u16(&mut page[log + TRX_UNDO_DEL_MARKS..], 1);
+    BigEndian::write_u64(&mut page[log + TRX_UNDO_TRX_ID..], trx_id);
+    BigEndian::write_u16(&mut page[log + TRX_UNDO_LOG_START..], new_free);
+    page[log + TRX_UNDO_FLAGS] = 0;
+    page[log + TRX_UNDO_DICT_TRANS] = 0;
+    BigEndian::write_u16(&mut page[log + TRX_UNDO_NEXT_LOG..], 0);
+    BigEndian::write_u16(&mut page[log + TRX_UNDO_PREV_LOG..], prev_log as u16);
+    Ok(())
+}
+
+/// `trx_undo_insert_header_reuse()`: reuse a cached insert undo page.
+fn undo_header_reuse(page: &mut [u8], trx_id: u64) {
+    let log = TRX_UNDO_SEG_HDR + TRX_UNDO_SEG_HDR_SIZE;
+    let new_free = (log + TRX_UNDO_LOG_OLD_HDR_SIZE) as u16;
+    BigEndian::write_u16(
+        &mut page[TRX_UNDO_PAGE_HDR + TRX_UNDO_PAGE_START..],
+        new_free,
+    );
+    BigEndian::write_u16(
+        &mut page[TRX_UNDO_PAGE_HDR + TRX_UNDO_PAGE_FREE..],
+        new_free,
+    );
+    BigEndian::write_u16(
+        &mut page[TRX_UNDO_SEG_HDR + TRX_UNDO_STATE..],
+        TRX_UNDO_ACTIVE,
+    );
+    BigEndian::write_u64(&mut page[log + TRX_UNDO_TRX_ID..], trx_id);
+    BigEndian::write_u16(&mut page[log + TRX_UNDO_LOG_START..], new_free);
+    page[log + TRX_UNDO_FLAGS] = 0;
+    page[log + TRX_UNDO_DICT_TRANS] = 0;
+}
+
+fn read16(buf: &[u8], offset: usize) -> usize {
+    BigEndian::read_u16(&buf[offset..]) as usize
+}
+
+/// Bounds-checked mutable slice of a page.
+fn slice_mut(page: &mut [u8], offset: usize, len: usize) -> Apply<&mut [u8]> {
+    let size = page.len();
+    page.get_mut(offset..offset + len).ok_or_else(|| {
+        format!(
+            "write of {} bytes at offset {} overruns the {}-byte page",
+            len, offset, size
+        )
+    })
+}
+
+// ── Record offsets ──────────────────────────────────────────────────────
+
+/// Field layout of one record (`rec_get_offsets()`).
+struct RecOffsets {
+    /// Bytes before the record origin (header, lengths, null bitmap).
+    extra: usize,
+    /// End offset of each stored field, relative to the origin.
+    ends: Vec<usize>,
+    /// Whether each stored field is SQL NULL.
+    nulls: Vec<bool>,
+}
+
+impl RecOffsets {
+    fn data_size(&self) -> usize {
+        self.ends.last().copied().unwrap_or(0)
+    }
+
+    fn size(&self) -> usize {
+        self.extra + self.data_size()
+    }
+
+    /// Start offset and length of field `n`.
+    fn field(&self, n: usize) -> Apply<(usize, usize)> {
+        let end = *self
+            .ends
+            .get(n)
+            .ok_or_else(|| format!("record has no field {}", n))?;
+        let start = if n == 0 { 0 } else { self.ends[n - 1] };
+        Ok((start, end - start))
+    }
+}
+
+/// Compute the field layout of the record with origin `rec` in `buf`.
+///
+/// Redundant records describe themselves; compact records are decoded with
+/// the logged index descriptor.
+fn rec_offsets(buf: &[u8], rec: usize, index: &MlogIndex, comp: bool) -> Apply<RecOffsets> {
+    if rec < REC_N_OLD_EXTRA_BYTES || rec > buf.len() {
+        return Err(format!("record origin {} out of range", rec));
+    }
+    let offsets = if comp {
+        compact_offsets(buf, rec, index)?
+    } else {
+        redundant_offsets(buf, rec)?
+    };
+    if offsets.extra > rec || rec + offsets.data_size() > buf.len() {
+        return Err(format!("record at offset {} overruns the page", rec));
+    }
+    Ok(offsets)
+}
+
+fn redundant_offsets(buf: &[u8], rec: usize) -> Apply<RecOffsets> {
+    let n_fields = (read16(buf, rec - 4) >> 1) & 0x3FF;
+    let short = buf[rec - 3] & 0x01 != 0;
+    let extra = REC_N_OLD_EXTRA_BYTES + n_fields * if short { 1 } else { 2 };
+    if extra > rec {
+        return Err(format!("record at offset {} has a corrupt header", rec));
+    }
+    let mut ends = Vec::with_capacity(n_fields);
+    let mut nulls = Vec::with_capacity(n_fields);
+    for i in 0..n_fields {
+        let (end, null) = if short {
+            let b = buf[rec - REC_N_OLD_EXTRA_BYTES - 1 - i] as usize;
+            (b & 0x7F, b & 0x80 != 0)
+        } else {
+            let v = read16(buf, rec - REC_N_OLD_EXTRA_BYTES - 2 * (i + 1));
+            (v & 0x3FFF, v & 0x8000 != 0)
+        };
+        if ends.last().is_some_and(|&prev| end < prev) {
+            return Err(format!(
+                "record at offset {} has decreasing field ends",
+                rec
+            ));
+        }
+        ends.push(end);
+        nulls.push(null);
+    }
+    Ok(RecOffsets { extra, ends, nulls })
+}
+
+fn compact_offsets(buf: &[u8], rec: usize, index: &MlogIndex) -> Apply<RecOffsets> {
+    let status = buf[rec - 3] & 0x07;
+    if status > REC_STATUS_NODE_PTR {
+        // Infimum or supremum: one 8-byte field
+        return Ok(RecOffsets {
+            extra: REC_N_NEW_EXTRA_BYTES,
+            ends: vec![8],
+            nulls: vec![false],
+        });
+    }
+    if index.fields.is_empty() {
+        return Err("compact record without an index descriptor".to_string());
+    }
+
+    let all = index.fields.len();
+    let nullable_before = |n: usize| index.fields[..n].iter().filter(|f| !f.not_null).count();
+    // Header bytes are read downward from the origin
+    let byte_at = |at: isize| -> Apply<usize> {
+        usize::try_from(at)
+            .ok()
+            .and_then(|at| buf.get(at))
+            .map(|&b| b as usize)
+            .ok_or_else(|| format!("record at offset {} has a corrupt header", rec))
+    };
+    let info = buf[rec - REC_N_NEW_EXTRA_BYTES] & 0xF0;
+    let node_ptr = status == REC_STATUS_NODE_PTR;
+    let mut nulls_at = (rec - REC_N_NEW_EXTRA_BYTES) as isize - 1;
+
+    let (n_fields, n_nullable) = if node_ptr {
+        let original = index.instant_fields.map_or(all, |n| (n as usize).min(all));
+        (index.n_uniq as usize, nullable_before(original))
+    } else if index.versioned {
+        if info & REC_INFO_VERSION_FLAG != 0
+            || index.versioned_fields.iter().any(|f| f.version_added > 0)
+        {
+            return Err("records with instantly added columns are not supported".to_string());
+        }
+        (all, nullable_before(all))
+    } else if index.instant && info & REC_INFO_INSTANT_FLAG != 0 {
+        // The record stores its field count ahead of the null bitmap
+        let b = byte_at(nulls_at)?;
+        let n = if b & 0x80 != 0 {
+            let n = ((b & 0x7F) << 8) | byte_at(nulls_at - 1)?;
+            nulls_at -= 2;
+            n
+        } else {
+            nulls_at -= 1;
+            b
+        };
+        (n, nullable_before(n.min(all)))
+    } else if index.instant {
+        let n = index.instant_fields.map_or(all, |n| n as usize);
+        (n, nullable_before(n.min(all)))
+    } else {
+        (all, nullable_before(all))
+    };
+    if n_fields > all {
+        return Err(format!(
+            "record has {} fields but the index has {}",
+            n_fields, all
+        ));
+    }
+
+    let mut lens_at = nulls_at - n_nullable.div_ceil(8) as isize;
+    let mut ends = Vec::with_capacity(n_fields + 1);
+    let mut nulls = Vec::with_capacity(n_fields + 1);
+    let mut offs = 0usize;
+    let mut null_bit = 0usize;
+    for field in &index.fields[..n_fields] {
+        if !field.not_null {
+            let byte = byte_at(nulls_at - (null_bit / 8) as isize)?;
+            let is_null = byte & (1 << (null_bit % 8)) != 0;
+            null_bit += 1;
+            if is_null {
+                ends.push(offs);
+                nulls.push(true);
+                continue;
+            }
+        }
+        offs += match field.fixed_len {
+            Some(len) => len as usize,
+            None => {
+                let len = byte_at(lens_at)?;
+                lens_at -= 1;
+                if field.long && len & 0x80 != 0 {
+                    let low = byte_at(lens_at)?;
+                    lens_at -= 1;
+                    ((len << 8) | low) & 0x3FFF
+                } else {
+                    len
+                }
+            }
+        };
+        ends.push(offs);
+        nulls.push(false);
+    }
+    if node_ptr {
+        offs += REC_NODE_PTR_SIZE;
+        ends.push(offs);
+        nulls.push(false);
+    }
+
+    let extra = (rec as isize - (lens_at + 1)) as usize;
+    Ok(RecOffsets { extra, ends, nulls })
+}
+
+// ── Page frame ──────────────────────────────────────────────────────────
+
+/// A page image with the record list, heap, and directory operations of
+/// `page0cur.cc` / `page0page.cc`.
+struct Frame<'a> {
+    page: &'a mut [u8],
+    comp: bool,
+}
+
+impl<'a> Frame<'a> {
+    fn new(page: &'a mut [u8]) -> Self {
+        let comp = page[FIL_PAGE_DATA + PAGE_N_HEAP] & 0x80 != 0;
+        Frame { page, comp }
+    }
+
+    /// `page_create_low()`: format an empty index page.
+    fn create(page: &mut [u8], comp: bool, page_type: u16) {
+        let ps = page.len();
+        let ph = FIL_PAGE_DATA;
+        BigEndian::write_u16(&mut page[FIL_PAGE_TYPE..], page_type);
+        page[ph..ph + PAGE_HEADER_PRIV_END].fill(0);
+        page[ph + PAGE_N_DIR_SLOTS + 1] = 2;
+        page[ph + PAGE_DIRECTION + 1] = PAGE_NO_DIRECTION as u8;
+
+        let (records, heap_top, infimum, supremum): (&[u8], _, _, _) = if comp {
+            page[ph + PAGE_N_HEAP] = 0x80;
+            (
+                &INFIMUM_SUPREMUM_COMPACT,
+                PAGE_NEW_SUPREMUM_END,
+                PAGE_NEW_INFIMUM,
+                PAGE_NEW_SUPREMUM,
+            )
+        } else {
+            (
+                &INFIMUM_SUPREMUM_REDUNDANT,
+                PAGE_OLD_SUPREMUM_END,
+                PAGE_OLD_INFIMUM,
+                PAGE_OLD_SUPREMUM,
+            )
+        };
+        page[ph + PAGE_N_HEAP + 1] = PAGE_HEAP_NO_USER_LOW as u8;
+        BigEndian::write_u16(&mut page[ph + PAGE_HEAP_TOP..], heap_top as u16);
+        page[PAGE_DATA_OFFSET..PAGE_DATA_OFFSET + records.len()].copy_from_slice(records);
+        page[heap_top..ps - PAGE_DIR].fill(0);
+        BigEndian::write_u16(
+            &mut page[ps - PAGE_DIR - 2 * PAGE_DIR_SLOT_SIZE..],
+            supremum as u16,
+        );
+        BigEndian::write_u16(
+            &mut page[ps - PAGE_DIR - PAGE_DIR_SLOT_SIZE..],
+            infimum as u16,
+        );
+    }
+
+    fn size(&self) -> usize {
+        self.page.len()
+    }
+
+    fn header(&self, field: usize) -> usize {
+        read16(self.page, FIL_PAGE_DATA + field)
+    }
+
+    fn set_header(&mut self, field: usize, value: usize) {
+        BigEndian::write_u16(&mut self.page[FIL_PAGE_DATA + field..], value as u16);
+    }
+
+    fn n_heap(&self) -> usize {
+        self.header(PAGE_N_HEAP) & 0x7FFF
+    }
+
+    fn set_n_heap(&mut self, n: usize) {
+        let comp = self.header(PAGE_N_HEAP) & 0x8000;
+        self.set_header(PAGE_N_HEAP, comp | n);
+    }
+
+    /// Head of the free record list.
+    fn free(&self) -> Option<usize> {
+        Some(self.header(PAGE_FREE)).filter(|&f| f != 0)
+    }
+
+    fn infimum(&self) -> usize {
+        if self.comp {
+            PAGE_NEW_INFIMUM
+        } else {
+            PAGE_OLD_INFIMUM
+        }
+    }
+
+    fn supremum(&self) -> usize {
+        if self.comp {
+            PAGE_NEW_SUPREMUM
+        } else {
+            PAGE_OLD_SUPREMUM
+        }
+    }
+
+    fn spatial(&self) -> bool {
+        BigEndian::read_u16(&self.page[FIL_PAGE_TYPE..]) == PageType::Rtree.as_u16()
+    }
+
+    /// Upper bound on the number of records walked in one list traversal.
+    fn max_steps(&self) -> usize {
+        self.size() / REC_N_NEW_EXTRA_BYTES
+    }
+
+    /// Validate a logged record offset.
+    fn rec_at(&self, offset: u16) -> Apply<usize> {
+        let rec = offset as usize;
+        if rec < PAGE_DATA_OFFSET + REC_N_NEW_EXTRA_BYTES || rec >= self.size() - PAGE_DIR {
+            return Err(format!("record offset {} out of range", rec));
+        }
+        Ok(rec)
+    }
+
+    /// Validate a logged offset of a user record (not infimum/supremum).
+    fn user_rec(&self, offset: u16) -> Apply<usize> {
+        let rec = self.rec_at(offset)?;
+        if rec == self.infimum() || rec == self.supremum() {
+            return Err(format!("offset {} is not a user record", rec));
+        }
+        Ok(rec)
+    }
+
+    fn bits_at(&self, rec: usize) -> usize {
+        rec - if self.comp {
+            REC_N_NEW_EXTRA_BYTES
+        } else {
+            REC_N_OLD_EXTRA_BYTES
+        }
+    }
+
+    fn n_owned(&self, rec: usize) -> u8 {
+        self.page[self.bits_at(rec)] & 0x0F
+    }
+
+    fn set_n_owned(&mut self, rec: usize, n: u8) {
+        let at = self.bits_at(rec);
+        self.page[at] = (self.page[at] & 0xF0) | (n & 0x0F);
+    }
+
+    fn info_bits(&self, rec: usize) -> u8 {
+        self.page[self.bits_at(rec)] & 0xF0
+    }
+
+    fn set_info_bits(&mut self, rec: usize, bits: u8) {
+        let at = self.bits_at(rec);
+        self.page[at] = (self.page[at] & 0x0F) | (bits & 0xF0);
+    }
+
+    fn set_deleted(&mut self, rec: usize, deleted: bool) {
+        let bits = self.info_bits(rec);
+        let bits = if deleted {
+            bits | REC_INFO_DELETED_FLAG
+        } else {
+            bits & !REC_INFO_DELETED_FLAG
+        };
+        self.set_info_bits(rec, bits);
+    }
+
+    /// `rec_get_info_and_status_bits()`.
+    fn info_and_status(&self, rec: usize) -> u8 {
+        if self.comp {
+            self.info_bits(rec) | (self.page[rec - 3] & 0x07)
+        } else {
+            self.info_bits(rec)
+        }
+    }
+
+    fn heap_no_at(&self, rec: usize) -> usize {
+        rec - if self.comp { 4 } else { 5 }
+    }
+
+    fn heap_no(&self, rec: usize) -> usize {
+        read16(self.page, self.heap_no_at(rec)) >> 3
+    }
+
+    fn set_heap_no(&mut self, rec: usize, heap_no: usize) {
+        let at = self.heap_no_at(rec);
+        let v = (read16(self.page, at) & 0x07) | (heap_no << 3);
+        BigEndian::write_u16(&mut self.page[at..], v as u16);
+    }
+
+    /// Raw next-record pointer, `None` at the end of a list.
+    fn next_ptr(&self, rec: usize) -> Option<usize> {
+        let v = read16(self.page, rec - 2);
+        if v == 0 {
+            None
+        } else if self.comp {
+            Some((rec + v) % self.size())
+        } else {
+            Some(v)
+        }
+    }
+
+    /// Next record in the record list.
+    fn next(&self, rec: usize) -> Apply<usize> {
+        match self.next_ptr(rec) {
+            Some(next)
+                if next >= PAGE_DATA_OFFSET + REC_N_NEW_EXTRA_BYTES
+                    && next < self.size() - PAGE_DIR =>
+            {
+                Ok(next)
+            }
+            _ => Err(format!("record list broken after offset {}", rec)),
+        }
+    }
+
+    fn set_next(&mut self, rec: usize, next: Option<usize>) {
+        let v = match next {
+            None => 0,
+            Some(next) if self.comp => (next as isize - rec as isize) as u16,
+            Some(next) => next as u16,
+        };
+        BigEndian::write_u16(&mut self.page[rec - 2..], v);
+    }
+
+    fn n_slots(&self) -> usize {
+        self.header(PAGE_N_DIR_SLOTS)
+    }
+
+    fn slot_at(&self, slot: usize) -> usize {
+        self.size() - PAGE_DIR - PAGE_DIR_SLOT_SIZE * (slot + 1)
+    }
+
+    fn slot_rec(&self, slot: usize) -> usize {
+        read16(self.page, self.slot_at(slot))
+    }
+
+    fn set_slot_rec(&mut self, slot: usize, rec: usize) {
+        let at = self.slot_at(slot);
+        BigEndian::write_u16(&mut self.page[at..], rec as u16);
+    }
+
+    fn slot_n_owned(&self, slot: usize) -> u8 {
+        self.n_owned(self.slot_rec(slot))
+    }
+
+    fn set_slot_n_owned(&mut self, slot: usize, n: u8) {
+        let rec = self.slot_rec(slot);
+        self.set_n_owned(rec, n);
+    }
+
+    /// `page_rec_find_owner_rec()`.
+    fn owner_rec(&self, rec: usize) -> Apply<usize> {
+        let mut r = rec;
+        for _ in 0..self.max_steps() {
+            if self.n_owned(r) != 0 {
+                return Ok(r);
+            }
+            r = self.next(r)?;
+        }
+        Err(format!("no owner record for offset {}", rec))
+    }
+
+    /// `page_dir_find_owner_slot()`.
+    fn owner_slot(&self, rec: usize) -> Apply<usize> {
+        let owner = self.owner_rec(rec)?;
+        if self.n_slots() > self.max_steps() {
+            return Err(format!("corrupt page directory ({} slots)", self.n_slots()));
+        }
+        (0..self.n_slots())
+            .rev()
+            .find(|&slot| self.slot_rec(slot) == owner)
+            .ok_or_else(|| format!("record {} is not in the page directory", owner))
+    }
+
+    /// `page_rec_get_prev()`.
+    fn prev(&self, rec: usize) -> Apply<usize> {
+        let slot = self.owner_slot(rec)?;
+        if slot == 0 {
+            return Err(format!("record {} has no predecessor", rec));
+        }
+        let mut r = self.slot_rec(slot - 1);
+        for _ in 0..self.max_steps() {
+            let next = self.next(r)?;
+            if next == rec {
+                return Ok(r);
+            }
+            r = next;
+        }
+        Err(format!("record {} not found in its slot", rec))
+    }
+
+    fn offsets(&self, rec: usize, index: &MlogIndex) -> Apply<RecOffsets> {
+        rec_offsets(self.page, rec, index, self.comp)
+    }
+
+    /// `row_upd_rec_sys_fields_in_recovery()`: write DB_TRX_ID and
+    /// DB_ROLL_PTR.
+    fn write_sys_fields(
+        &mut self,
+        rec: usize,
+        index: &MlogIndex,
+        sys: &MlogSysFields,
+    ) -> Apply<()> {
+        let offsets = self.offsets(rec, index)?;
+        let (start, len) = offsets.field(sys.trx_id_pos as usize)?;
+        if len != DATA_TRX_ID_LEN {
+            return Err(format!(
+                "field {} is {} bytes, not a DB_TRX_ID",
+                sys.trx_id_pos, len
+            ));
+        }
+        let at = rec + start;
+        let dst = slice_mut(self.page, at, DATA_TRX_ID_LEN + DATA_ROLL_PTR_LEN)?;
+        BigEndian::write_uint(&mut dst[..DATA_TRX_ID_LEN], sys.trx_id, DATA_TRX_ID_LEN);
+        BigEndian::write_uint(&mut dst[DATA_TRX_ID_LEN..], sys.roll_ptr, DATA_ROLL_PTR_LEN);
+        Ok(())
+    }
+
+    /// `rec_set_nth_field()` for an update in place.
+    fn update_field(
+        &mut self,
+        rec: usize,
+        offsets: &RecOffsets,
+        n: usize,
+        data: Option<&[u8]>,
+    ) -> Apply<()> {
+        let (start, len) = offsets.field(n)?;
+        let was_null = offsets.nulls[n];
+        if self.comp && (was_null || data.is_none()) {
+            return Err(format!("field {} changes between NULL and non-NULL", n));
+        }
+        match data {
+            Some(data) => {
+                if data.len() != len {
+                    return Err(format!(
+                        "field {} is {} bytes, update has {}",
+                        n,
+                        len,
+                        data.len()
+                    ));
+                }
+                self.page[rec + start..rec + start + len].copy_from_slice(data);
+                if was_null {
+                    self.set_old_null_bit(rec, n, false);
+                }
+            }
+            None => {
+                self.page[rec + start..rec + start + len].fill(0);
+                self.set_old_null_bit(rec, n, true);
+            }
+        }
+        Ok(())
+    }
+
+    /// `rec_set_nth_field_null_bit()` for a redundant record.
+    fn set_old_null_bit(&mut self, rec: usize, n: usize, null: bool) {
+        if self.page[rec - 3] & 0x01 != 0 {
+            let at = rec - REC_N_OLD_EXTRA_BYTES - 1 - n;
+            self.page[at] = if null {
+                self.page[at] | 0x80
+            } else {
+                self.page[at] & 0x7F
+            };
+        } else {
+            let at = rec - REC_N_OLD_EXTRA_BYTES - 2 * (n + 1);
+            let v = read16(self.page, at);
+            let v = if null { v | 0x8000 } else { v & 0x7FFF };
+            BigEndian::write_u16(&mut self.page[at..], v as u16);
+        }
+    }
+
+    /// `page_cur_parse_insert_rec()`: rebuild the logged record from the
+    /// cursor record and insert it after the cursor.
+    fn parse_insert(
+        &mut self,
+        cursor: usize,
+        index: &MlogIndex,
+        insert: &MlogInsert,
+    ) -> Apply<usize> {
+        if cursor == self.supremum() {
+            return Err("insert after the supremum".to_string());
+        }
+        let cur = self.offsets(cursor, index)?;
+        let (bits, origin, mismatch) = match (
+            insert.info_and_status_bits,
+            insert.origin_offset,
+            insert.mismatch_index,
+        ) {
+            (Some(bits), Some(origin), Some(mismatch)) => {
+                (bits, origin as usize, mismatch as usize)
+            }
+            _ => {
+                let mismatch = cur
+                    .size()
+                    .checked_sub(insert.data.len())
+                    .ok_or_else(|| "logged record is longer than the cursor record".to_string())?;
+                (self.info_and_status(cursor), cur.extra, mismatch)
+            }
+        };
+
+        let start = cursor - cur.extra;
+        if mismatch >= self.size() || start + mismatch > self.size() {
+            return Err(format!("mismatch index {} out of range", mismatch));
+        }
+        let mut buf = self.page[start..start + mismatch].to_vec();
+        buf.extend_from_slice(&insert.data);
+        if origin < REC_N_OLD_EXTRA_BYTES || origin > buf.len() {
+            return Err(format!("origin offset {} out of range", origin));
+        }
+        if self.comp {
+            buf[origin - 3] = (buf[origin - 3] & !0x07) | (bits & 0x07);
+            buf[origin - REC_N_NEW_EXTRA_BYTES] =
+                (buf[origin - REC_N_NEW_EXTRA_BYTES] & 0x0F) | (bits & 0xF0);
+        } else {
+            buf[origin - REC_N_OLD_EXTRA_BYTES] =
+                (buf[origin - REC_N_OLD_EXTRA_BYTES] & 0x0F) | (bits & 0xF0);
+        }
+
+        let offsets = rec_offsets(&buf, origin, index, self.comp)?;
+        let image = &buf[origin - offsets.extra..origin + offsets.data_size()];
+        self.insert_rec(cursor, index, image, offsets.extra)
+    }
+
+    /// `page_get_max_insert_size()`.
+    fn max_insert_size(&self, n_recs: usize) -> usize {
+        let heap_start = if self.comp {
+            PAGE_NEW_SUPREMUM_END
+        } else {
+            PAGE_OLD_SUPREMUM_END
+        };
+        let dir_recs = (n_recs + self.n_heap()).saturating_sub(PAGE_HEAP_NO_USER_LOW);
+        let reserved = (PAGE_DIR_SLOT_SIZE * dir_recs).div_ceil(PAGE_DIR_SLOT_MIN_N_OWNED as usize);
+        let occupied = self.header(PAGE_HEAP_TOP).saturating_sub(heap_start) + reserved;
+        let free_space = self.size() - heap_start - PAGE_DIR - 2 * PAGE_DIR_SLOT_SIZE;
+        free_space.saturating_sub(occupied)
+    }
+
+    /// `page_cur_insert_rec_low()`: insert a record image (`extra` bytes of
+    /// header followed by the data) after `current`. Returns the new
+    /// record's origin.
+    fn insert_rec(
+        &mut self,
+        current: usize,
+        index: &MlogIndex,
+        image: &[u8],
+        extra: usize,
+    ) -> Apply<usize> {
+        let rec_size = image.len();
+
+        // Reuse the head of the free list if it is large enough
+        let mut slot = None;
+        if let Some(free_rec) = self.free() {
+            let free = self.offsets(free_rec, index)?;
+            if free.size() >= rec_size {
+                let heap_no = self.heap_no(free_rec);
+                let next = self.next_ptr(free_rec).unwrap_or(0);
+                let garbage = self.header(PAGE_GARBAGE).saturating_sub(rec_size);
+                self.set_header(PAGE_FREE, next);
+                self.set_header(PAGE_GARBAGE, garbage);
+                slot = Some((free_rec - free.extra, heap_no));
+            }
+        }
+        let (insert_buf, heap_no) = match slot {
+            Some(slot) => slot,
+            None => {
+                if self.max_insert_size(1) < rec_size {
+                    return Err(format!("no room for a {}-byte record", rec_size));
+                }
+                let top = self.header(PAGE_HEAP_TOP);
+                let heap_no = self.n_heap();
+                self.set_header(PAGE_HEAP_TOP, top + rec_size);
+                self.set_n_heap(heap_no + 1);
+                (top, heap_no)
+            }
+        };
+
+        slice_mut(self.page, insert_buf, rec_size)?.copy_from_slice(image);
+        let insert_rec = insert_buf + extra;
+
+        let next_rec = self.next(current)?;
+        self.set_next(insert_rec, Some(next_rec));
+        self.set_next(current, Some(insert_rec));
+        let n_recs = self.header(PAGE_N_RECS);
+        self.set_header(PAGE_N_RECS, n_recs + 1);
+
+        self.set_n_owned(insert_rec, 0);
+        self.set_heap_no(insert_rec, heap_no);
+
+        if !self.spatial() {
+            let last_insert = self.header(PAGE_LAST_INSERT);
+            let direction = self.header(PAGE_DIRECTION) as u16;
+            let n_direction = self.header(PAGE_N_DIRECTION);
+            if last_insert != 0 && last_insert == current && direction != PAGE_LEFT {
+                self.set_header(PAGE_DIRECTION, PAGE_RIGHT as usize);
+                self.set_header(PAGE_N_DIRECTION, n_direction + 1);
+            } else if last_insert != 0
+                && self.next_ptr(insert_rec) == Some(last_insert)
+                && direction != PAGE_RIGHT
+            {
+                self.set_header(PAGE_DIRECTION, PAGE_LEFT as usize);
+                self.set_header(PAGE_N_DIRECTION, n_direction + 1);
+            } else {
+                self.set_header(PAGE_DIRECTION, PAGE_NO_DIRECTION as usize);
+                self.set_header(PAGE_N_DIRECTION, 0);
+            }
+        }
+        self.set_header(PAGE_LAST_INSERT, insert_rec);
+
+        let owner = self.owner_rec(insert_rec)?;
+        let n_owned = self.n_owned(owner);
+        self.set_n_owned(owner, n_owned + 1);
+        if n_owned == PAGE_DIR_SLOT_MAX_N_OWNED {
+            let slot = self.owner_slot(owner)?;
+            self.split_slot(slot)?;
+        }
+
+        Ok(insert_rec)
+    }
+
+    /// `page_dir_split_slot()`.
+    fn split_slot(&mut self, slot: usize) -> Apply<()> {
+        if slot == 0 {
+            return Err("cannot split the infimum slot".to_string());
+        }
+        let n_owned = self.slot_n_owned(slot);
+        let mut rec = self.slot_rec(slot - 1);
+        for _ in 0..n_owned / 2 {
+            rec = self.next(rec)?;
+        }
+
+        // page_dir_add_slot(): open a slot above `slot - 1`
+        let n_slots = self.n_slots();
+        self.set_header(PAGE_N_DIR_SLOTS, n_slots + 1);
+        let lowest = self.slot_at(n_slots);
+        let len = (n_slots - slot) * PAGE_DIR_SLOT_SIZE;
+        self.page.copy_within(
+            lowest + PAGE_DIR_SLOT_SIZE..lowest + PAGE_DIR_SLOT_SIZE + len,
+            lowest,
+        );
+
+        self.set_slot_rec(slot, rec);
+        self.set_slot_n_owned(slot, n_owned / 2);
+        self.set_slot_n_owned(slot + 1, n_owned - n_owned / 2);
+        Ok(())
+    }
+
+    /// `page_cur_delete_rec()`.
+    fn delete_rec(&mut self, rec: usize, index: &MlogIndex) -> Apply<()> {
+        let offsets = self.offsets(rec, index)?;
+        let slot = self.owner_slot(rec)?;
+        if slot == 0 {
+            return Err(format!("cannot delete record {}", rec));
+        }
+        let n_owned = self.slot_n_owned(slot);
+
+        self.set_header(PAGE_LAST_INSERT, 0);
+        let prev_rec = self.prev(rec)?;
+        let next_rec = self.next(rec)?;
+        self.set_next(prev_rec, Some(next_rec));
+        if self.slot_rec(slot) == rec {
+            self.set_slot_rec(slot, prev_rec);
+        }
+        self.set_slot_n_owned(slot, n_owned - 1);
+
+        // page_mem_free()
+        let free = self.free();
+        self.set_next(rec, free);
+        self.set_header(PAGE_FREE, rec);
+        let garbage = self.header(PAGE_GARBAGE);
+        self.set_header(PAGE_GARBAGE, garbage + offsets.size());
+        let n_recs = self.header(PAGE_N_RECS);
+        self.set_header(PAGE_N_RECS, n_recs.saturating_sub(1));
+
+        if n_owned <= PAGE_DIR_SLOT_MIN_N_OWNED {
+            self.balance_slot(slot);
+        }
+        Ok(())
+    }
+
+    /// `page_dir_balance_slot()`.
+    fn balance_slot(&mut self, slot: usize) {
+        if slot + 1 >= self.n_slots() {
+            return;
+        }
+        let n_owned = self.slot_n_owned(slot);
+        let up_n_owned = self.slot_n_owned(slot + 1);
+        if up_n_owned > PAGE_DIR_SLOT_MIN_N_OWNED {
+            // Move one record from the upper slot to this one
+            let old_rec = self.slot_rec(slot);
+            let Some(new_rec) = self.next_ptr(old_rec) else {
+                return;
+            };
+            self.set_n_owned(old_rec, 0);
+            self.set_n_owned(new_rec, n_owned + 1);
+            self.set_slot_rec(slot, new_rec);
+            self.set_slot_n_owned(slot + 1, up_n_owned - 1);
+        } else {
+            self.delete_slot(slot);
+        }
+    }
+
+    /// `page_dir_delete_slot()`: merge `slot` into the slot above it.
+    fn delete_slot(&mut self, slot: usize) {
+        let n_slots = self.n_slots();
+        let n_owned = self.slot_n_owned(slot);
+        self.set_slot_n_owned(slot, 0);
+        let up_n_owned = self.slot_n_owned(slot + 1);
+        self.set_slot_n_owned(slot + 1, n_owned + up_n_owned);
+        for i in slot + 1..n_slots {
+            let rec = self.slot_rec(i);
+            self.set_slot_rec(i - 1, rec);
+        }
+        self.set_slot_rec(n_slots - 1, 0);
+        self.set_header(PAGE_N_DIR_SLOTS, n_slots - 1);
+    }
+
+    /// `page_delete_rec_list_end()`: delete `rec` and every record after it.
+    fn delete_list_end(&mut self, rec: usize, index: &MlogIndex) -> Apply<()> {
+        let supremum = self.supremum();
+        if rec == supremum {
+            return Ok(());
+        }
+        if rec == self.infimum() {
+            return Err("list end delete from the infimum".to_string());
+        }
+        self.set_header(PAGE_LAST_INSERT, 0);
+        let prev_rec = self.prev(rec)?;
+        let last_rec = self.prev(supremum)?;
+
+        let mut size = 0;
+        let mut n_recs = 0;
+        let mut r = rec;
+        while r != supremum {
+            size += self.offsets(r, index)?.size();
+            n_recs += 1;
+            if n_recs > self.max_steps() {
+                return Err("record list does not reach the supremum".to_string());
+            }
+            r = self.next(r)?;
+        }
+
+        // The owner of `rec` keeps only the records before `rec`, and the
+        // supremum takes over its slot
+        let mut owner = rec;
+        let mut count = 0u8;
+        while self.n_owned(owner) == 0 {
+            count += 1;
+            if count > PAGE_DIR_SLOT_MAX_N_OWNED {
+                return Err(format!("no owner record for offset {}", rec));
+            }
+            owner = self.next(owner)?;
+        }
+        let n_owned = self
+            .n_owned(owner)
+            .checked_sub(count)
+            .ok_or_else(|| format!("record {} owns too few records", owner))?;
+        let slot = self.owner_slot(owner)?;
+        self.set_slot_rec(slot, supremum);
+        self.set_n_owned(supremum, n_owned);
+        self.set_header(PAGE_N_DIR_SLOTS, slot + 1);
+
+        self.set_next(prev_rec, Some(supremum));
+        let free = self.free();
+        self.set_next(last_rec, free);
+        self.set_header(PAGE_FREE, rec);
+        let garbage = self.header(PAGE_GARBAGE);
+        self.set_header(PAGE_GARBAGE, garbage + size);
+        let total = self.header(PAGE_N_RECS);
+        self.set_header(PAGE_N_RECS, total.saturating_sub(n_recs));
+        Ok(())
+    }
+
+    /// `page_delete_rec_list_start()`: delete every record before `rec`.
+    fn delete_list_start(&mut self, rec: usize, index: &MlogIndex) -> Apply<()> {
+        if rec == self.infimum() {
+            return Ok(());
+        }
+        if rec == self.supremum() {
+            return Err("list start delete up to the supremum".to_string());
+        }
+        let mut cur = self.next(self.infimum())?;
+        for _ in 0..self.max_steps() {
+            if cur == rec {
+                return Ok(());
+            }
+            let next = self.next(cur)?;
+            self.delete_rec(cur, index)?;
+            cur = next;
+        }
+        Err(format!("record {} not found in the record list", rec))
+    }
+
+    /// `btr_page_reorganize_low()`: recreate the page and copy its records
+    /// back in order.
+    fn reorganize(&mut self, index: &MlogIndex) -> Apply<()> {
+        let mut old_page = self.page.to_vec();
+        let old = Frame {
+            page: &mut old_page,
+            comp: self.comp,
+        };
+        let page_type = BigEndian::read_u16(&old.page[FIL_PAGE_TYPE..]);
+        let leaf = old.header(PAGE_LEVEL) == 0;
+        let max_trx_id = BigEndian::read_u64(&old.page[FIL_PAGE_DATA + PAGE_MAX_TRX_ID..]);
+
+        Frame::create(self.page, self.comp, page_type);
+        let supremum = old.supremum();
+        let mut from = old.next(old.infimum())?;
+        let mut to = self.infimum();
+        let mut steps = 0;
+        while from != supremum {
+            steps += 1;
+            if steps > self.max_steps() {
+                return Err("record list does not reach the supremum".to_string());
+            }
+            let offsets = old.offsets(from, index)?;
+            let image = &old.page[from - offsets.extra..from + offsets.data_size()];
+            to = self.insert_rec(to, index, image, offsets.extra)?;
+            from = old.next(from)?;
+        }
+
+        // Secondary index leaves keep PAGE_MAX_TRX_ID (it is zero elsewhere)
+        if leaf {
+            BigEndian::write_u64(
+                &mut self.page[FIL_PAGE_DATA + PAGE_MAX_TRX_ID..],
+                max_trx_id,
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::innodb::mlog::MlogIndexField;
+    use crate::innodb::page_dir::check_page_directory;
+    use crate::innodb::record::walk_compact_records;
+
+    const PS: usize = 16384;
+
+    fn field(fixed_len: Option<u16>, not_null: bool) -> MlogIndexField {
+        MlogIndexField {
+            fixed_len,
+            not_null,
+            long: false,
+        }
+    }
+
+    /// Clustered index on `(id INT, DB_TRX_ID, DB_ROLL_PTR, name VARCHAR NULL)`.
+    fn index() -> MlogIndex {
+        MlogIndex {
+            compact: true,
+            n_uniq: 1,
+            fields: vec![
+                field(Some(4), true),
+                field(Some(6), true),
+                field(Some(7), true),
+                field(None, false),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn record(
+        lsn: u64,
+        record_type: MlogRecordType,
+        single_rec: bool,
+        page_no: u32,
+        body: MlogBody,
+    ) -> MlogRecord {
+        MlogRecord {
+            start_lsn: lsn,
+            end_lsn: lsn + 10,
+            record_type,
+            single_rec,
+            space_id: Some(7),
+            page_no: Some(page_no),
+            body,
+        }
+    }
+
+    fn redo(lsn: u64, record_type: MlogRecordType, body: MlogBody) -> PageRedo {
+        PageRedo {
+            mtr_start_lsn: lsn,
+            mtr_end_lsn: lsn + 10,
+            record: record(lsn, record_type, true, 3, body),
+        }
+    }
+
+    fn write4(lsn: u64, page_no: u32, single_rec: bool) -> MlogRecord {
+        let body = MlogBody::WriteBytes {
+            offset: 200,
+            value: lsn,
+        };
+        record(lsn, MlogRecordType::Mlog4Bytes, single_rec, page_no, body)
+    }
+
+    fn multi_rec_end(lsn: u64) -> MlogRecord {
+        MlogRecord {
+            end_lsn: lsn + 1,
+            space_id: None,
+            page_no: None,
+            ..record(
+                lsn,
+                MlogRecordType::MlogMultiRecEnd,
+                false,
+                0,
+                MlogBody::Empty,
+            )
+        }
+    }
+
+    /// Full compact image of row `(id, name)`, logged without sharing any
+    /// bytes with the cursor record.
+    fn insert(cursor: usize, id: u32, name: &[u8]) -> MlogBody {
+        let mut data = vec![name.len() as u8, 0, 0, 0, 0, 0, 0];
+        data.extend_from_slice(&(id | 0x8000_0000).to_be_bytes());
+        data.extend_from_slice(&[0, 0, 0, 0, 0, 9]);
+        data.extend_from_slice(&[0x80, 0, 0, 0, 0, 0, 1]);
+        data.extend_from_slice(name);
+        MlogBody::RecInsert {
+            index: index(),
+            insert: MlogInsert {
+                cursor_offset: Some(cursor as u16),
+                info_and_status_bits: Some(0),
+                origin_offset: Some(7),
+                mismatch_index: Some(0),
+                data,
+            },
+        }
+    }
+
+    fn apply(page: &mut [u8], lsn: u64, record_type: MlogRecordType, body: MlogBody) {
+        apply_page_redo(page, &[redo(lsn, record_type, body)]).unwrap();
+    }
+
+    /// Empty compact page with rows `1..=n` inserted in ascending order.
+    fn page_with_rows(n: u32) -> Vec<u8> {
+        let mut page = vec![0u8; PS];
+        apply(
+            &mut page,
+            0,
+            MlogRecordType::MlogCompPageCreate,
+            MlogBody::Empty,
+        );
+        for id in 1..=n {
+            let cursor = match read16(&page, FIL_PAGE_DATA + PAGE_LAST_INSERT) {
+                0 => PAGE_NEW_INFIMUM,
+                last => last,
+            };
+            let body = insert(cursor, id, format!("row{}", id).as_bytes());
+            apply(
+                &mut page,
+                u64::from(id) * 100,
+                MlogRecordType::MlogRecInsert,
+                body,
+            );
+        }
+        page
+    }
+
+    fn ids(page: &[u8]) -> Vec<u32> {
+        walk_compact_records(page)
+            .iter()
+            .map(|r| BigEndian::read_u32(&page[r.offset..]) & 0x7FFF_FFFF)
+            .collect()
+    }
+
+    fn assert_directory_valid(page: &[u8]) {
+        let dir = check_page_directory(page).unwrap();
+        assert!(dir.issues.is_empty(), "{:?}", dir.issues);
+    }
+
+    #[test]
+    fn test_plan_groups_mini_transactions() {
+        let records = vec![
+            write4(100, 1, true),
+            // Multi-record mtr [200, 221)
+            write4(200, 2, false),
+            write4(210, 3, false),
+            multi_rec_end(220),
+            write4(221, 4, true),
+            // Interrupted by a single-record mtr
+            write4(231, 5, false),
+            write4(241, 6, true),
+            write4(251, 7, true),
+            // Cut off by the end of the log
+            write4(261, 8, false),
+        ];
+
+        let plan = plan_redo_apply(&records, 200, None);
+        assert_eq!(plan.mtr_count, 4);
+        assert_eq!(plan.mtrs_before, 1);
+        assert_eq!(plan.mtrs_broken, 1);
+        assert_eq!(plan.incomplete_at, Some(261));
+        assert_eq!(plan.end_lsn, 261);
+        assert_eq!(plan.space_ids(), vec![7]);
+        assert_eq!(plan.page_count(), 5);
+        let pages: Vec<u32> = plan.pages[&7].keys().copied().collect();
+        assert_eq!(pages, vec![2, 3, 4, 6, 7]);
+        let page3 = &plan.pages[&7][&3][0];
+        assert_eq!((page3.mtr_start_lsn, page3.mtr_end_lsn), (200, 221));
+
+        let plan = plan_redo_apply(&records, 200, Some(231));
+        assert_eq!(plan.mtr_count, 2);
+        assert_eq!(plan.mtrs_after, 2);
+        assert_eq!(plan.end_lsn, 231);
+        assert_eq!(plan.record_count(), 3);
+    }
+
+    #[test]
+    fn test_page_lsn_gates_replay() {
+        let mut page = vec![0u8; PS];
+        BigEndian::write_u64(&mut page[FIL_PAGE_LSN..], 1000);
+        let write = |value| MlogBody::WriteBytes { offset: 300, value };
+        let redo = vec![
+            redo(900, MlogRecordType::Mlog2Bytes, write(0x1111)),
+            redo(1000, MlogRecordType::Mlog4Bytes, write(0x2222_3333)),
+            redo(1010, MlogRecordType::Mlog1Byte, write(0x44)),
+        ];
+
+        assert_eq!(apply_page_redo(&mut page, &redo).unwrap(), 2);
+        assert_eq!(&page[300..304], &[0x44, 0x22, 0x33, 0x33]);
+        assert_eq!(BigEndian::read_u64(&page[FIL_PAGE_LSN..]), 1020);
+
+        // Replaying again finds the page current
+        assert_eq!(apply_page_redo(&mut page, &redo).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_inserts_split_directory_slots() {
+        let page = page_with_rows(40);
+        assert_eq!(ids(&page), (1..=40).collect::<Vec<_>>());
+        assert_eq!(read16(&page, FIL_PAGE_DATA + PAGE_N_RECS), 40);
+        assert_eq!(read16(&page, FIL_PAGE_DATA + PAGE_N_HEAP), 0x8000 | 42);
+        assert!(read16(&page, FIL_PAGE_DATA + PAGE_N_DIR_SLOTS) > 5);
+        assert_eq!(
+            read16(&page, FIL_PAGE_DATA + PAGE_DIRECTION) as u16,
+            PAGE_RIGHT
+        );
+        assert_eq!(read16(&page, FIL_PAGE_DATA + PAGE_N_DIRECTION), 39);
+        assert_directory_valid(&page);
+    }
+
+    #[test]
+    fn test_delete_reuse_and_reorganize() {
+        let mut page = page_with_rows(40);
+        let heap_top = read16(&page, FIL_PAGE_DATA + PAGE_HEAP_TOP);
+        let offsets: Vec<usize> = walk_compact_records(&page)
+            .iter()
+            .map(|r| r.offset)
+            .collect();
+        for (i, &rec) in offsets.iter().enumerate().filter(|(i, _)| i % 4 != 0) {
+            let body = MlogBody::RecDelete {
+                index: index(),
+                offset: rec as u16,
+            };
+            apply(
+                &mut page,
+                10_000 + i as u64 * 10,
+                MlogRecordType::MlogRecDelete,
+                body,
+            );
+            assert_directory_valid(&page);
+        }
+        assert_eq!(ids(&page), (1..=40).step_by(4).collect::<Vec<_>>());
+        assert_eq!(read16(&page, FIL_PAGE_DATA + PAGE_N_RECS), 10);
+        assert!(read16(&page, FIL_PAGE_DATA + PAGE_GARBAGE) > 0);
+
+        // The last deleted record heads the free list and is reused
+        let free = read16(&page, FIL_PAGE_DATA + PAGE_FREE);
+        let body = insert(offsets[36], 38, b"row38");
+        apply(&mut page, 20_000, MlogRecordType::MlogRecInsert, body);
+        assert_eq!(read16(&page, FIL_PAGE_DATA + PAGE_LAST_INSERT), free);
+        assert_eq!(read16(&page, FIL_PAGE_DATA + PAGE_HEAP_TOP), heap_top);
+        assert_directory_valid(&page);
+
+        let body = MlogBody::PageReorganize {
+            index: index(),
+            compression_level: None,
+        };
+        apply(&mut page, 30_000, MlogRecordType::MlogPageReorganize, body);
+        assert_eq!(read16(&page, FIL_PAGE_DATA + PAGE_GARBAGE), 0);
+        assert_eq!(read16(&page, FIL_PAGE_DATA + PAGE_FREE), 0);
+        assert_eq!(read16(&page, FIL_PAGE_DATA + PAGE_N_HEAP), 0x8000 | 13);
+        assert_eq!(ids(&page), vec![1, 5, 9, 13, 17, 21, 25, 29, 33, 37, 38]);
+        assert_directory_valid(&page);
+    }
+
+    #[test]
+    fn test_list_end_and_start_delete() {
+        let mut page = page_with_rows(30);
+        let offsets: Vec<usize> = walk_compact_records(&page)
+            .iter()
+            .map(|r| r.offset)
+            .collect();
+
+        let body = MlogBody::ListDelete {
+            index: index(),
+            offset: offsets[20] as u16,
+        };
+        apply(&mut page, 10_000, MlogRecordType::MlogListEndDelete, body);
+        assert_eq!(ids(&page), (1..=20).collect::<Vec<_>>());
+        assert_eq!(read16(&page, FIL_PAGE_DATA + PAGE_N_RECS), 20);
+        assert_directory_valid(&page);
+
+        let body = MlogBody::ListDelete {
+            index: index(),
+            offset: offsets[5] as u16,
+        };
+        apply(&mut page, 11_000, MlogRecordType::MlogListStartDelete, body);
+        assert_eq!(ids(&page), (6..=20).collect::<Vec<_>>());
+        assert_directory_valid(&page);
+    }
+
+    #[test]
+    fn test_update_in_place_and_delete_mark() {
+        let mut page = page_with_rows(3);
+        let rec = walk_compact_records(&page)[1].offset;
+        let sys = MlogSysFields {
+            trx_id_pos: 1,
+            roll_ptr: 0x0001_0203_0405_0607,
+            trx_id: 0x77,
+        };
+        let body = MlogBody::UpdateInPlace {
+            index: index(),
+            flags: 0,
+            sys: sys.clone(),
+            offset: rec as u16,
+            info_bits: 0,
+            fields: vec![crate::innodb::mlog::MlogUpdateField {
+                field_no: 3,
+                data: Some(b"ROW2".to_vec()),
+            }],
+        };
+        apply(
+            &mut page,
+            10_000,
+            MlogRecordType::MlogRecUpdateInPlace,
+            body,
+        );
+        assert_eq!(&page[rec + 4..rec + 10], &[0, 0, 0, 0, 0, 0x77]);
+        assert_eq!(&page[rec + 10..rec + 17], &[1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(&page[rec + 17..rec + 21], b"ROW2");
+
+        let body = MlogBody::ClustDeleteMark {
+            index: index(),
+            flags: BTR_KEEP_SYS_FLAG,
+            value: true,
+            sys,
+            offset: rec as u16,
+        };
+        apply(
+            &mut page,
+            11_000,
+            MlogRecordType::MlogRecClustDeleteMark,
+            body,
+        );
+        assert_eq!(
+            page[rec - REC_N_NEW_EXTRA_BYTES] & REC_INFO_DELETED_FLAG,
+            REC_INFO_DELETED_FLAG
+        );
+    }
+
+    #[test]
+    fn test_unsupported_record_leaves_error() {
+        let mut page = page_with_rows(1);
+        let body = MlogBody::PageReorganize {
+            index: index(),
+            compression_level: Some(6),
+        };
+        let err = apply_page_redo(
+            &mut page,
+            &[redo(5000, MlogRecordType::MlogZipPageReorganize, body)],
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("at LSN 5000"), "{}", err);
+        assert!(err.to_string().contains("not supported"), "{}", err);
+    }
+}
@@ -0,0 +1,874 @@
+//! Redo log file sets as one logical LSN stream.
+//!
+//! InnoDB spreads its redo log over several files. MySQL 8.0.30+ keeps up to
+//! 32 `#ib_redoN` files in `#innodb_redo/`, each holding the LSN range that
+//! follows the one before it, plus spare `#ib_redoN_tmp` files that hold no
+//! live log yet. Older versions (and MariaDB before 10.8) write the
+//! `ib_logfile0..N` group as one circular buffer: after the end of the last
+//! file the log wraps to the start of the first, and the newest blocks
+//! overwrite the oldest.
+//!
+//! [`RedoLogSet`] opens all files of a set and places every data block with
+//! a valid checksum at its LSN. The block number stored in each block pins
+//! its exact LSN, so blocks left over from an earlier pass (or an earlier
+//! use of a recycled file) are told apart from live ones. The result is
+//! presented as one stream: [`RedoLogSet::gaps`] and
+//! [`RedoLogSet::overlaps`] report LSN ranges that no file (or two files)
+//! hold, [`RedoLogSet::read_lsn_range`] reads any LSN range regardless of
+//! file boundaries, and [`RedoLogSet::read_mlog_records`] decodes records
+//! that span files. MariaDB 10.8+ keeps its whole log in one `ib_logfile0`
+//! without blocks; such a set has no placed blocks, and its records are
+//! decoded by [`mariadb_redo`](crate::innodb::mariadb_redo).
+//!
+//! Encrypted logs (`innodb_redo_log_encrypt`) are opened with
+//! [`RedoLogSet::open_dir_with_keyring`], which installs the redo log key on
+//! every file before the blocks are placed.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use idb::innodb::redo_set::RedoLogSet;
+//!
+//! let mut set = RedoLogSet::open_dir("/var/lib/mysql").unwrap();
+//! println!("{} redo log, {} files", set.layout(), set.members().len());
+//! for gap in set.gaps() {
+//!     println!("missing LSN {}..{}", gap.start_lsn, gap.end_lsn);
+//! }
+//! let decoded = set.read_mlog_records().unwrap();
+//! println!("{} records", decoded.records.len());
+//! ```
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::innodb::keyring::Keyring;
+use crate::innodb::log::{
+    validate_log_block_checksum, LogBlockHeader, LogFile, LogFileHeader, LOG_BLOCK_CHECKSUM_OFFSET,
+    LOG_BLOCK_SIZE, LOG_FILE_HDR_BLOCKS,
+};
+use crate::innodb::mariadb_redo::{
+    is_mariadb_10_8, read_mariadb_checkpoint_lsn, read_mariadb_records,
+};
+use crate::innodb::mlog::{
+    block_start_lsn, decode_mlog_streams, LogStream, LogStreamBuilder, MlogStreamDecode,
+};
+use crate::IdbError;
+
+const BLOCK_SIZE: u64 = LOG_BLOCK_SIZE as u64;
+
+/// How the files of a redo log set map to LSNs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedoLogLayout {
+    /// `ib_logfile0..N` written as one circular buffer (before MySQL 8.0.30).
+    Circular,
+    /// `#ib_redoN` files, each continuing the LSN range of the previous one
+    /// (MySQL 8.0.30+).
+    Sequential,
+}
+
+impl fmt::Display for RedoLogLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RedoLogLayout::Circular => write!(f, "circular"),
+            RedoLogLayout::Sequential => write!(f, "sequential"),
+        }
+    }
+}
+
+/// One file of a redo log set.
+#[derive(Debug, Clone, Serialize)]
+pub struct RedoLogMember {
+    /// Path to the file.
+    pub path: String,
+    /// File number from the file name (`ib_logfileN`, `#ib_redoN`).
+    pub file_no: u32,
+    /// A spare `#ib_redoN_tmp` file; its blocks are not read.
+    pub spare: bool,
+    /// File size in bytes.
+    pub file_size: u64,
+    /// Log file header (block 0).
+    pub header: LogFileHeader,
+    /// The file carries redo log encryption info (`innodb_redo_log_encrypt`).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub encrypted: bool,
+    /// Higher of the two checkpoint LSNs stored in the file, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkpoint_lsn: Option<u64>,
+    /// Data blocks placed in the LSN stream.
+    pub blocks: u64,
+    /// Blocks with a valid checksum that do not belong at their position:
+    /// leftovers from an earlier use of the file.
+    pub stale_blocks: u64,
+}
+
+/// An LSN range that no file of the set holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LsnGap {
+    /// First missing LSN.
+    pub start_lsn: u64,
+    /// LSN where the log resumes.
+    pub end_lsn: u64,
+}
+
+/// An LSN range held by two files of the set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LsnOverlap {
+    /// LSN of the start of the first duplicated block.
+    pub start_lsn: u64,
+    /// LSN just past the last duplicated block.
+    pub end_lsn: u64,
+    /// File whose blocks are used.
+    pub file: String,
+    /// File whose copies of the blocks are ignored.
+    pub duplicate: String,
+}
+
+/// Where a block of the LSN stream is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RedoBlockLocation {
+    /// LSN of the start of the block (a multiple of 512).
+    pub lsn: u64,
+    /// Index of the file in [`RedoLogSet::members`].
+    pub member: usize,
+    /// Index of the block within the file.
+    pub block: u64,
+    /// Bytes of the block in use, including the header (at most 512).
+    pub data_len: u16,
+}
+
+impl RedoBlockLocation {
+    /// LSN just past the log data of the block.
+    ///
+    /// A full block covers all 512 LSNs, trailer included; a partially
+    /// written one ends at its data length.
+    pub fn end_lsn(&self) -> u64 {
+        if self.data_len as usize >= LOG_BLOCK_CHECKSUM_OFFSET {
+            self.lsn + BLOCK_SIZE
+        } else {
+            self.lsn + u64::from(self.data_len)
+        }
+    }
+}
+
+/// The files of a redo log, read as one LSN stream.
+pub struct RedoLogSet {
+    layout: RedoLogLayout,
+    members: Vec<RedoLogMember>,
+    logs: Vec<LogFile>,
+    blocks: BTreeMap<u64, RedoBlockLocation>,
+    gaps: Vec<LsnGap>,
+    overlaps: Vec<LsnOverlap>,
+}
+
+/// A block with a valid checksum, before it is placed in the stream.
+struct Candidate {
+    member: usize,
+    block: u64,
+    /// Offset of the block within the data area of the whole set.
+    pos: u64,
+    /// LSN the block would have if it were written in the current pass.
+    near: u64,
+    lsn: u64,
+    data_len: u16,
+}
+
+impl RedoLogSet {
+    /// Open the redo log of a data directory.
+    ///
+    /// `dir` may be the data directory or its `#innodb_redo` directory.
+    /// `#ib_redoN` files (MySQL 8.0.30+) are preferred; otherwise the
+    /// `ib_logfileN` group is used.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_dir(dir: &str) -> Result<Self, IdbError> {
+        Self::open_dir_with_keyring(dir, None)
+    }
+
+    /// Open the redo log of a data directory, decrypting its blocks with
+    /// the redo log key derived from `keyring` if the log is encrypted.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_dir_with_keyring(dir: &str, keyring: Option<&Keyring>) -> Result<Self, IdbError> {
+        let root = Path::new(dir);
+        if !root.is_dir() {
+            return Err(IdbError::Argument(format!("Not a directory: {}", dir)));
+        }
+
+        let mut paths = list_redo_files(&root.join("#innodb_redo"), RedoLogLayout::Sequential);
+        if paths.is_empty() {
+            paths = list_redo_files(root, RedoLogLayout::Sequential);
+        }
+        if paths.is_empty() {
+            paths = list_redo_files(root, RedoLogLayout::Circular);
+        }
+        if paths.is_empty() {
+            return Err(IdbError::Argument(format!(
+                "No redo log files found in {} (expected #innodb_redo/#ib_redo* or ib_logfile*)",
+                dir
+            )));
+        }
+        Self::open_files_with_keyring(&paths, keyring)
+    }
+
+    /// Open the given redo log files as one set.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_files(paths: &[String]) -> Result<Self, IdbError> {
+        Self::open_files_with_keyring(paths, None)
+    }
+
+    /// Open the given redo log files as one set, decrypting their blocks
+    /// with the redo log key derived from `keyring` if they are encrypted.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_files_with_keyring(
+        paths: &[String],
+        keyring: Option<&Keyring>,
+    ) -> Result<Self, IdbError> {
+        let mut logs = paths
+            .iter()
+            .map(|path| Ok((path.clone(), LogFile::open(path)?)))
+            .collect::<Result<Vec<_>, IdbError>>()?;
+        if let Some(keyring) = keyring {
+            enable_decryption(&mut logs, keyring)?;
+        }
+        Self::from_logs(logs)
+    }
+
+    /// Build a set from already opened files, each paired with its path.
+    ///
+    /// The layout and file numbers come from the file names; files with
+    /// other names are numbered in the given order, and their layout is
+    /// taken from the header format (6 and later is sequential). All files
+    /// must share one layout.
+    pub fn from_logs(logs: Vec<(String, LogFile)>) -> Result<Self, IdbError> {
+        if logs.is_empty() {
+            return Err(IdbError::Argument(
+                "A redo log set needs at least one file".to_string(),
+            ));
+        }
+
+        let mut layout = None;
+        let mut entries = Vec::with_capacity(logs.len());
+        for (i, (path, mut log)) in logs.into_iter().enumerate() {
+            let header = log.read_header()?;
+            let name = Path::new(&path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let (file_layout, file_no, spare) = parse_file_name(&name).unwrap_or_else(|| {
+                let file_layout = if header.format_version >= 6 {
+                    RedoLogLayout::Sequential
+                } else {
+                    RedoLogLayout::Circular
+                };
+                (file_layout, i as u32, false)
+            });
+            match layout {
+                None => layout = Some(file_layout),
+                Some(l) if l != file_layout => {
+                    return Err(IdbError::Argument(format!(
+                        "{} does not belong to a {} redo log",
+                        path, l
+                    )))
+                }
+                Some(_) => {}
+            }
+
+            let checkpoint_lsn = if is_mariadb_10_8(&header) {
+                read_mariadb_checkpoint_lsn(&mut log)?
+            } else {
+                (0..2)
+                    .filter_map(|slot| log.read_checkpoint(slot).ok())
+                    .map(|cp| cp.lsn)
+                    .max()
+                    .filter(|&lsn| lsn > 0)
+            };
+            let encrypted = log.read_encryption_info()?.is_some();
+            let member = RedoLogMember {
+                path,
+                file_no,
+                spare,
+                file_size: log.file_size(),
+                header,
+                encrypted,
+                checkpoint_lsn,
+                blocks: 0,
+                stale_blocks: 0,
+            };
+            entries.push((member, log));
+        }
+
+        // Oldest first; for a circular group that has wrapped this starts
+        // with the file after the one being written
+        entries.sort_by_key(|(m, _)| (m.spare, m.header.start_lsn, m.file_no));
+        let (members, logs) = entries.into_iter().unzip();
+
+        let mut set = RedoLogSet {
+            layout: layout.unwrap_or(RedoLogLayout::Sequential),
+            members,
+            logs,
+            blocks: BTreeMap::new(),
+            gaps: Vec::new(),
+            overlaps: Vec::new(),
+        };
+        set.place_blocks()?;
+        set.gaps = set.find_gaps();
+        Ok(set)
+    }
+
+    /// Read every data block and place those that belong to the live log
+    /// at their LSN.
+    fn place_blocks(&mut self) -> Result<(), IdbError> {
+        // Circular groups are laid out in file-number order; the first
+        // file's header anchors positions to LSNs
+        // A MariaDB 10.8+ log has no blocks
+        let mut order: Vec<usize> = (0..self.members.len())
+            .filter(|&i| !self.members[i].spare && !is_mariadb_10_8(&self.members[i].header))
+            .collect();
+        if self.layout == RedoLogLayout::Circular {
+            order.sort_by_key(|&i| self.members[i].file_no);
+        }
+        let anchor = order
+            .first()
+            .map(|&i| align(self.members[i].header.start_lsn))
+            .unwrap_or(0);
+
+        let mut candidates = Vec::new();
+        let mut pos_base = 0u64;
+        for &m in &order {
+            let file_start = match self.layout {
+                RedoLogLayout::Sequential => align(self.members[m].header.start_lsn),
+                RedoLogLayout::Circular => anchor + pos_base,
+            };
+            let log = &mut self.logs[m];
+            let data_blocks = log.data_block_count();
+            for j in 0..data_blocks {
+                let block_idx = LOG_FILE_HDR_BLOCKS + j;
+                let block = log.read_block(block_idx)?;
+                let hdr = match LogBlockHeader::parse(&block) {
+                    Some(h) if validate_log_block_checksum(&block) => h,
+                    _ => continue,
+                };
+                let near = file_start + j * BLOCK_SIZE;
+                candidates.push(Candidate {
+                    member: m,
+                    block: block_idx,
+                    pos: pos_base + j * BLOCK_SIZE,
+                    near,
+                    lsn: block_start_lsn(near, hdr.block_no),
+                    data_len: hdr.data_len.min(LOG_BLOCK_SIZE as u16),
+                });
+            }
+            pos_base += data_blocks * BLOCK_SIZE;
+        }
+
+        // A circular group holds each LSN at position `lsn mod capacity`,
+        // shifted by where the log started. Blocks of the current and the
+        // previous pass share that shift, so the shift most blocks agree on
+        // is the live one and anything else is stale. Sequential files hold
+        // exactly the LSNs their header says.
+        let capacity = pos_base.max(1);
+        let phase = |c: &Candidate| c.lsn.wrapping_sub(c.pos) % capacity;
+        let mut phases: HashMap<u64, (usize, u64)> = HashMap::new();
+        for c in &candidates {
+            let e = phases.entry(phase(c)).or_default();
+            e.0 += 1;
+            e.1 = e.1.max(c.lsn);
+        }
+        let head_phase = phases
+            .into_iter()
+            .max_by_key(|&(_, count_and_lsn)| count_and_lsn)
+            .map(|(p, _)| p);
+
+        let mut duplicates = Vec::new();
+        for c in &candidates {
+            let live = match self.layout {
+                RedoLogLayout::Sequential => c.lsn == c.near,
+                RedoLogLayout::Circular => Some(phase(c)) == head_phase,
+            };
+            if !live {
+                self.members[c.member].stale_blocks += 1;
+                continue;
+            }
+            match self.blocks.entry(c.lsn) {
+                Entry::Vacant(e) => {
+                    e.insert(RedoBlockLocation {
+                        lsn: c.lsn,
+                        member: c.member,
+                        block: c.block,
+                        data_len: c.data_len,
+                    });
+                    self.members[c.member].blocks += 1;
+                }
+                Entry::Occupied(e) => duplicates.push((e.get().member, c.member, c.lsn)),
+            }
+        }
+
+        duplicates.sort_unstable();
+        let mut overlaps: Vec<(usize, usize, u64, u64)> = Vec::new();
+        for (kept, dup, lsn) in duplicates {
+            match overlaps.last_mut() {
+                Some(o) if o.0 == kept && o.1 == dup && o.3 == lsn => o.3 = lsn + BLOCK_SIZE,
+                _ => overlaps.push((kept, dup, lsn, lsn + BLOCK_SIZE)),
+            }
+        }
+        overlaps.sort_by_key(|o| o.2);
+        self.overlaps = overlaps
+            .into_iter()
+            .map(|(kept, dup, start_lsn, end_lsn)| LsnOverlap {
+                start_lsn,
+                end_lsn,
+                file: self.members[kept].path.clone(),
+                duplicate: self.members[dup].path.clone(),
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// LSN ranges between the first and last block that no block covers.
+    fn find_gaps(&self) -> Vec<LsnGap> {
+        let mut gaps = Vec::new();
+        let mut prev: Option<&RedoBlockLocation> = None;
+        for loc in self.blocks.values() {
+            if let Some(p) = prev {
+                if p.end_lsn() < loc.lsn {
+                    gaps.push(LsnGap {
+                        start_lsn: p.end_lsn(),
+                        end_lsn: loc.lsn,
+                    });
+                }
+            }
+            prev = Some(loc);
+        }
+        gaps
+    }
+
+    /// How the files map to LSNs.
+    pub fn layout(&self) -> RedoLogLayout {
+        self.layout
+    }
+
+    /// The files of the set, ordered by start LSN (spare files last).
+    pub fn members(&self) -> &[RedoLogMember] {
+        &self.members
+    }
+
+    /// LSN of the start of the oldest block, or `None` if no file holds
+    /// any log data.
+    pub fn start_lsn(&self) -> Option<u64> {
+        self.blocks.keys().next().copied()
+    }
+
+    /// LSN just past the newest log data.
+    pub fn end_lsn(&self) -> Option<u64> {
+        self.blocks
+            .values()
+            .next_back()
+            .map(RedoBlockLocation::end_lsn)
+    }
+
+    /// Most recent checkpoint LSN across all files.
+    pub fn checkpoint_lsn(&self) -> Option<u64> {
+        self.members.iter().filter_map(|m| m.checkpoint_lsn).max()
+    }
+
+    /// LSN ranges inside the stream that no file holds.
+    pub fn gaps(&self) -> &[LsnGap] {
+        &self.gaps
+    }
+
+    /// LSN ranges held by more than one file.
+    pub fn overlaps(&self) -> &[LsnOverlap] {
+        &self.overlaps
+    }
+
+    /// Number of blocks in the stream.
+    pub fn block_count(&self) -> u64 {
+        self.blocks.len() as u64
+    }
+
+    /// Blocks of the stream in LSN order.
+    pub fn blocks(&self) -> impl Iterator<Item = &RedoBlockLocation> {
+        self.blocks.values()
+    }
+
+    /// The block holding `lsn`, if any file holds it.
+    pub fn locate(&self, lsn: u64) -> Option<RedoBlockLocation> {
+        self.blocks.get(&align(lsn)).copied()
+    }
+
+    /// Read the 512-byte block at `loc`.
+    pub fn read_block(&mut self, loc: &RedoBlockLocation) -> Result<Vec<u8>, IdbError> {
+        let log = self.logs.get_mut(loc.member).ok_or_else(|| {
+            IdbError::Argument(format!("No redo log file with index {}", loc.member))
+        })?;
+        log.read_block(loc.block)
+    }
+
+    /// Read the log bytes with LSNs `start..end`.
+    ///
+    /// LSNs address every byte of the log, so the result includes the
+    /// header and trailer of each block it crosses, exactly as InnoDB
+    /// wrote them. Fails if any LSN of the range is not in the set.
+    pub fn read_lsn_range(&mut self, start: u64, end: u64) -> Result<Vec<u8>, IdbError> {
+        if start > end {
+            return Err(IdbError::Argument(format!(
+                "Invalid LSN range {}..{}",
+                start, end
+            )));
+        }
+
+        let mut out = Vec::with_capacity((end - start) as usize);
+        let mut lsn = start;
+        while lsn < end {
+            let missing =
+                |lsn: u64| IdbError::Parse(format!("LSN {} is not in the redo log set", lsn));
+            let loc = self.locate(lsn).ok_or_else(|| missing(lsn))?;
+            let stop = end.min(loc.lsn + BLOCK_SIZE);
+            if stop > loc.end_lsn() {
+                return Err(missing(loc.end_lsn().max(lsn)));
+            }
+            let block = self.read_block(&loc)?;
+            out.extend_from_slice(&block[(lsn - loc.lsn) as usize..(stop - loc.lsn) as usize]);
+            lsn = stop;
+        }
+        Ok(out)
+    }
+
+    /// Split the stream into runs of consecutive blocks, as
+    /// [`read_log_streams`](crate::innodb::mlog::read_log_streams) does
+    /// for one file; runs continue across file boundaries.
+    ///
+    /// [`LogStream::first_block`] is the index within its own file of a
+    /// run's first block; use [`RedoLogSet::locate`] to find the file and
+    /// block of any LSN.
+    pub fn read_log_streams(&mut self) -> Result<Vec<LogStream>, IdbError> {
+        let locations: Vec<RedoBlockLocation> = self.blocks.values().copied().collect();
+        let mut builder = LogStreamBuilder::default();
+        for loc in &locations {
+            let block = self.read_block(loc)?;
+            builder.push(loc.block, loc.lsn, &block);
+        }
+        Ok(builder.finish())
+    }
+
+    /// Read and decode all records of the stream, in LSN order.
+    ///
+    /// A MariaDB 10.8+ log (a single `ib_logfile0` without blocks) is
+    /// decoded with [`read_mariadb_records`].
+    pub fn read_mlog_records(&mut self) -> Result<MlogStreamDecode, IdbError> {
+        if let Some(i) = self.mariadb_10_8_member() {
+            return read_mariadb_records(&mut self.logs[i]);
+        }
+        Ok(decode_mlog_streams(&self.read_log_streams()?))
+    }
+
+    /// Index of the set's MariaDB 10.8+ log file, if it is one.
+    pub fn mariadb_10_8_member(&self) -> Option<usize> {
+        self.members
+            .iter()
+            .position(|m| !m.spare && is_mariadb_10_8(&m.header))
+    }
+}
+
+/// Round `lsn` down to the start of its block.
+/// Install the redo log key on every file. Each file that carries
+/// encryption info gets the key derived from it; the others (`ib_logfile1`
+/// and later, before MySQL 8.0.30) share the key of the first one.
+#[cfg(not(target_arch = "wasm32"))]
+fn enable_decryption(logs: &mut [(String, LogFile)], keyring: &Keyring) -> Result<(), IdbError> {
+    let mut shared = None;
+    for (_, log) in logs.iter_mut() {
+        if log.enable_decryption(keyring)? && shared.is_none() {
+            shared = log.decryption_context().cloned();
+        }
+    }
+    if let Some(ctx) = shared {
+        for (_, log) in logs.iter_mut() {
+            if log.decryption_context().is_none() {
+                log.set_decryption_context(ctx.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn align(lsn: u64) -> u64 {
+    lsn - lsn % BLOCK_SIZE
+}
+
+/// Layout, file number, and spare flag from a redo log file name.
+fn parse_file_name(name: &str) -> Option<(RedoLogLayout, u32, bool)> {
+    let (layout, digits, spare) = if let Some(rest) = name.strip_prefix("#ib_redo") {
+        match rest.strip_suffix("_tmp") {
+            Some(digits) => (RedoLogLayout::Sequential, digits, true),
+            None => (RedoLogLayout::Sequential, rest, false),
+        }
+    } else {
+        (
+            RedoLogLayout::Circular,
+            name.strip_prefix("ib_logfile")?,
+            false,
+        )
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((layout, digits.parse().ok()?, spare))
+}
+
+/// Redo log files of `layout` in `dir`; empty if `dir` does not exist.
+#[cfg(not(target_arch = "wasm32"))]
+fn list_redo_files(dir: &Path, layout: RedoLogLayout) -> Vec<String> {
+    let mut files: Vec<(u32, String)> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| {
+                    let name = e.file_name().to_string_lossy().to_string();
+                    match parse_file_name(&name) {
+                        Some((l, n, _)) if l == layout && e.path().is_file() => {
+                            Some((n, e.path().to_string_lossy().to_string()))
+                        }
+                        _ => None,
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files.into_iter().map(|(_, path)| path).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::{BigEndian, ByteOrder};
+
+    const DATA_SIZE: usize = LOG_BLOCK_CHECKSUM_OFFSET - 12;
+
+    /// A data block at `lsn` carrying `data` from offset 12.
+    fn block(lsn: u64, data: &[u8], first_rec: u16) -> Vec<u8> {
+        let mut b = vec![0u8; LOG_BLOCK_SIZE];
+        BigEndian::write_u32(&mut b[0..], ((lsn / BLOCK_SIZE) & 0x3FFF_FFFF) as u32 + 1);
+        let data_len = if data.len() == DATA_SIZE {
+            LOG_BLOCK_SIZE
+        } else {
+            12 + data.len()
+        };
+        BigEndian::write_u16(&mut b[4..], data_len as u16);
+        BigEndian::write_u16(&mut b[6..], first_rec);
+        BigEndian::write_u32(&mut b[8..], 1);
+        b[12..12 + data.len()].copy_from_slice(data);
+        let crc = crc32c::crc32c(&b[..LOG_BLOCK_CHECKSUM_OFFSET]);
+        BigEndian::write_u32(&mut b[LOG_BLOCK_CHECKSUM_OFFSET..], crc);
+        b
+    }
+
+    /// A redo log file with header start LSN `start_lsn` and the given
+    /// data blocks.
+    fn file(format: u32, start_lsn: u64, checkpoint: u64, blocks: &[Vec<u8>]) -> LogFile {
+        let mut buf = vec![0u8; LOG_FILE_HDR_BLOCKS as usize * LOG_BLOCK_SIZE];
+        BigEndian::write_u32(&mut buf[0..], format);
+        if format >= 6 {
+            BigEndian::write_u64(&mut buf[8..], start_lsn);
+        } else {
+            BigEndian::write_u64(&mut buf[4..], start_lsn);
+        }
+        BigEndian::write_u64(&mut buf[LOG_BLOCK_SIZE + 8..], checkpoint);
+        for b in blocks {
+            buf.extend_from_slice(b);
+        }
+        LogFile::from_bytes(buf).unwrap()
+    }
+
+    /// 100 `MLOG_4BYTES` records (6 bytes each) cut into two blocks
+    /// starting at LSN 8192.
+    fn record_blocks() -> (Vec<u8>, Vec<u8>) {
+        let mut stream = Vec::new();
+        for i in 0..100u8 {
+            stream.extend_from_slice(&[4, 1, 3, 0, 38 + i, 7]);
+        }
+        let first = block(8192, &stream[..DATA_SIZE], 12);
+        // The first record starting in the second block
+        let next_rec = DATA_SIZE.div_ceil(6) * 6 - DATA_SIZE;
+        let second = block(8704, &stream[DATA_SIZE..], (12 + next_rec) as u16);
+        (first, second)
+    }
+
+    #[test]
+    fn test_parse_file_name() {
+        assert_eq!(
+            parse_file_name("#ib_redo12"),
+            Some((RedoLogLayout::Sequential, 12, false))
+        );
+        assert_eq!(
+            parse_file_name("#ib_redo13_tmp"),
+            Some((RedoLogLayout::Sequential, 13, true))
+        );
+        assert_eq!(
+            parse_file_name("ib_logfile1"),
+            Some((RedoLogLayout::Circular, 1, false))
+        );
+        assert_eq!(parse_file_name("ib_logfile"), None);
+        assert_eq!(parse_file_name("#ib_redo+1"), None);
+        assert_eq!(parse_file_name("ibdata1"), None);
+    }
+
+    #[test]
+    fn test_sequential_records_span_files() {
+        let (first, second) = record_blocks();
+        let mut set = RedoLogSet::from_logs(vec![
+            ("#ib_redo2".to_string(), file(6, 8704, 0, &[second])),
+            ("#ib_redo1".to_string(), file(6, 8192, 8200, &[first])),
+            ("#ib_redo3_tmp".to_string(), file(6, 0, 0, &[])),
+        ])
+        .unwrap();
+
+        assert_eq!(set.layout(), RedoLogLayout::Sequential);
+        let names: Vec<&str> = set.members().iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(names, ["#ib_redo1", "#ib_redo2", "#ib_redo3_tmp"]);
+        assert!(set.members()[2].spare);
+        assert_eq!(set.start_lsn(), Some(8192));
+        assert_eq!(set.end_lsn(), Some(8704 + 12 + 600 - DATA_SIZE as u64));
+        assert_eq!(set.checkpoint_lsn(), Some(8200));
+        assert!(set.gaps().is_empty());
+        assert!(set.overlaps().is_empty());
+
+        // The record cut by the file boundary decodes as one
+        let decoded = set.read_mlog_records().unwrap();
+        assert_eq!(decoded.records.len(), 100);
+        assert!(decoded.errors.is_empty());
+        let boundary = &decoded.records[82];
+        assert!(boundary.start_lsn < 8704 && boundary.end_lsn > 8704);
+
+        // Reading across the boundary returns the trailer and header bytes
+        let bytes = set.read_lsn_range(8700, 8720).unwrap();
+        assert_eq!(bytes.len(), 20);
+        assert_eq!(BigEndian::read_u32(&bytes[4..]), 8704 / 512 + 1);
+        assert!(set
+            .read_lsn_range(8704, set.end_lsn().unwrap() + 1)
+            .is_err());
+        assert!(set.read_lsn_range(8000, 8200).is_err());
+    }
+
+    #[test]
+    fn test_sequential_gap_overlap_and_stale_blocks() {
+        let first = block(8192, &[1; DATA_SIZE], 12);
+        let second = block(8704, &[1; DATA_SIZE], 12);
+        let third = block(9216, &[1; DATA_SIZE], 12);
+        let fifth = block(10240, &[1; 20], 12);
+        // A recycled file still holding a block from its previous use
+        let stale = block(4096, &[1; 20], 12);
+
+        let set = RedoLogSet::from_logs(vec![
+            ("#ib_redo1".to_string(), file(6, 8192, 0, &[first])),
+            (
+                "#ib_redo2".to_string(),
+                file(6, 8704, 0, std::slice::from_ref(&second)),
+            ),
+            ("#ib_redo3".to_string(), file(6, 9216, 0, &[third])),
+            ("#ib_redo5".to_string(), file(6, 10240, 0, &[fifth, stale])),
+            ("#ib_redo9".to_string(), file(6, 8704, 0, &[second])),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            set.gaps(),
+            [LsnGap {
+                start_lsn: 9728,
+                end_lsn: 10240
+            }]
+        );
+        assert_eq!(
+            set.overlaps(),
+            [LsnOverlap {
+                start_lsn: 8704,
+                end_lsn: 9216,
+                file: "#ib_redo2".to_string(),
+                duplicate: "#ib_redo9".to_string(),
+            }]
+        );
+        let redo5 = set.members().iter().find(|m| m.file_no == 5).unwrap();
+        assert_eq!((redo5.blocks, redo5.stale_blocks), (1, 1));
+        assert_eq!(set.block_count(), 4);
+    }
+
+    #[test]
+    fn test_circular_group_wraps() {
+        // Two files of two data blocks. The current pass started at LSN
+        // 10240 in ib_logfile0 and is partway through its second block;
+        // ib_logfile1 still holds the end of the previous pass.
+        let log0 = file(
+            4,
+            10240,
+            9800,
+            &[
+                block(10240, &[1; DATA_SIZE], 12),
+                block(10752, &[2; 100], 12),
+            ],
+        );
+        let log1 = file(
+            4,
+            9216,
+            0,
+            &[
+                block(9216, &[3; DATA_SIZE], 12),
+                block(9728, &[4; DATA_SIZE], 12),
+            ],
+        );
+        let mut set = RedoLogSet::from_logs(vec![
+            ("ib_logfile0".to_string(), log0),
+            ("ib_logfile1".to_string(), log1),
+        ])
+        .unwrap();
+
+        assert_eq!(set.layout(), RedoLogLayout::Circular);
+        assert_eq!(set.members()[0].path, "ib_logfile1");
+        assert_eq!(set.start_lsn(), Some(9216));
+        assert_eq!(set.end_lsn(), Some(10752 + 112));
+        assert_eq!(set.checkpoint_lsn(), Some(9800));
+        assert!(set.gaps().is_empty());
+        let loc = set.locate(10300).unwrap();
+        assert_eq!((set.members()[loc.member].file_no, loc.block), (0, 4));
+
+        // The range wraps from the end of ib_logfile1 to ib_logfile0
+        let bytes = set.read_lsn_range(10230, 10260).unwrap();
+        assert_eq!(bytes[0], 4);
+        assert_eq!(bytes[29], 1);
+    }
+
+    #[test]
+    fn test_circular_stale_block_is_ignored() {
+        // ib_logfile1 was never rewritten after the first pass; its second
+        // block carries a block number from an unrelated log
+        let log0 = file(4, 8192, 0, &[block(8192, &[1; DATA_SIZE], 12)]);
+        let log1 = file(
+            4,
+            8704,
+            0,
+            &[block(8704, &[2; 50], 12), block(1_049_600, &[3; 50], 12)],
+        );
+        let set = RedoLogSet::from_logs(vec![
+            ("ib_logfile0".to_string(), log0),
+            ("ib_logfile1".to_string(), log1),
+        ])
+        .unwrap();
+        assert_eq!(set.block_count(), 2);
+        assert_eq!(set.end_lsn(), Some(8704 + 62));
+        assert_eq!(set.members()[1].stale_blocks, 1);
+    }
+
+    #[test]
+    fn test_mixed_layouts_rejected() {
+        let err = RedoLogSet::from_logs(vec![
+            ("ib_logfile0".to_string(), file(4, 8192, 0, &[])),
+            ("#ib_redo1".to_string(), file(6, 8192, 0, &[])),
+        ])
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("does not belong"), "{}", err);
+    }
+}
@@ -202,6 +202,9 @@ pub struct DdIndex {
     /// Whether the index is visible.
     #[serde(default)]
     pub is_visible: bool,
+    /// Storage engine private data (e.g., "id=157;root=4;space_id=3;").
+    #[serde(default)]
+    pub se_private_data: Option<String>,
 }
 
 /// Data dictionary index element (column reference).
@@ -429,17 +432,39 @@ pub struct InferredIndex {
 /// ```
 pub fn collation_name(id: u64) -> Option<&'static str> {
     match id {
+        1 => Some("big5_chinese_ci"),
         2 => Some("latin2_czech_cs"),
+        7 => Some("koi8r_general_ci"),
         8 => Some("latin1_swedish_ci"),
+        9 => Some("latin2_general_ci"),
         11 => Some("ascii_general_ci"),
+        12 => Some("ujis_japanese_ci"),
+        13 => Some("sjis_japanese_ci"),
+        14 => Some("cp1251_bulgarian_ci"),
+        19 => Some("euckr_korean_ci"),
+        24 => Some("gb2312_chinese_ci"),
+        26 => Some("cp1250_general_ci"),
+        28 => Some("gbk_chinese_ci"),
         33 => Some("utf8mb3_general_ci"),
+        35 => Some("ucs2_general_ci"),
         45 => Some("utf8mb4_general_ci"),
         46 => Some("utf8mb4_bin"),
         47 => Some("latin1_bin"),
         48 => Some("latin1_general_ci"),
+        50 => Some("cp1251_bin"),
+        51 => Some("cp1251_general_ci"),
+        54 => Some("utf16_general_ci"),
+        55 => Some("utf16_bin"),
+        56 => Some("utf16le_general_ci"),
+        60 => Some("utf32_general_ci"),
+        61 => Some("utf32_bin"),
         63 => Some("binary"),
         83 => Some("utf8mb3_bin"),
+        87 => Some("gbk_bin"),
+        90 => Some("ucs2_bin"),
+        95 => Some("cp932_japanese_ci"),
         224 => Some("utf8mb4_unicode_ci"),
+        248 => Some("gb18030_chinese_ci"),
         255 => Some("utf8mb4_0900_ai_ci"),
         _ => None,
     }
@@ -447,6 +472,9 @@ pub fn collation_name(id: u64) -> Option<&'static str> {
 
 /// Map collation ID to character set name.
 ///
+/// Covers every collation ID defined by MySQL 8.0 (including the per-language
+/// UCA collations of each character set).
+///
 /// # Examples
 ///
 /// ```
@@ -459,12 +487,47 @@ pub fn collation_name(id: u64) -> Option<&'static str> {
 /// ```
 pub fn charset_from_collation(id: u64) -> Option<&'static str> {
     match id {
-        2 => Some("latin2"),
-        8 | 47 | 48 => Some("latin1"),
-        11 => Some("ascii"),
-        33 | 83 => Some("utf8mb3"),
-        45 | 46 | 224 | 255 => Some("utf8mb4"),
+        1 | 84 => Some("big5"),
+        2 | 9 | 21 | 27 | 77 => Some("latin2"),
+        3 | 69 => Some("dec8"),
+        4 | 80 => Some("cp850"),
+        5 | 8 | 15 | 31 | 47 | 48 | 49 | 94 => Some("latin1"),
+        6 | 72 => Some("hp8"),
+        7 | 74 => Some("koi8r"),
+        10 | 82 => Some("swe7"),
+        11 | 65 => Some("ascii"),
+        12 | 91 => Some("ujis"),
+        13 | 88 => Some("sjis"),
+        14 | 23 | 50 | 51 | 52 => Some("cp1251"),
+        16 | 71 => Some("hebrew"),
+        18 | 89 => Some("tis620"),
+        19 | 85 => Some("euckr"),
+        20 | 41 | 42 | 79 => Some("latin7"),
+        22 | 75 => Some("koi8u"),
+        24 | 86 => Some("gb2312"),
+        25 | 70 => Some("greek"),
+        26 | 34 | 44 | 66 | 99 => Some("cp1250"),
+        28 | 87 => Some("gbk"),
+        29 | 58 | 59 => Some("cp1257"),
+        30 | 78 => Some("latin5"),
+        32 | 64 => Some("armscii8"),
+        33 | 76 | 83 | 192..=215 | 223 => Some("utf8mb3"),
+        35 | 90 | 128..=151 | 159 => Some("ucs2"),
+        36 | 68 => Some("cp866"),
+        37 | 73 => Some("keybcs2"),
+        38 | 43 => Some("macce"),
+        39 | 53 => Some("macroman"),
+        40 | 81 => Some("cp852"),
+        45 | 46 | 224..=247 | 255..=323 => Some("utf8mb4"),
+        54 | 55 | 101..=124 => Some("utf16"),
+        56 | 62 => Some("utf16le"),
+        57 | 67 => Some("cp1256"),
+        60 | 61 | 160..=183 => Some("utf32"),
         63 => Some("binary"),
+        92 | 93 => Some("geostd8"),
+        95 | 96 => Some("cp932"),
+        97 | 98 => Some("eucjpms"),
+        248..=250 => Some("gb18030"),
         _ => None,
     }
 }
@@ -576,21 +639,19 @@ fn format_decimal(col: &DdColumn) -> String {
 fn format_varchar(col: &DdColumn) -> String {
     // char_length is in bytes; divide by max bytes per char for the charset
     let max_bytes_per_char = charset_max_bytes(col.collation_id);
-    let char_len = if max_bytes_per_char > 0 {
-        col.char_length / max_bytes_per_char
-    } else {
-        col.char_length
-    };
+    let char_len = col
+        .char_length
+        .checked_div(max_bytes_per_char)
+        .unwrap_or(col.char_length);
     format!("varchar({})", char_len)
 }
 
 fn format_char(col: &DdColumn) -> String {
     let max_bytes_per_char = charset_max_bytes(col.collation_id);
-    let char_len = if max_bytes_per_char > 0 {
-        col.char_length / max_bytes_per_char
-    } else {
-        col.char_length
-    };
+    let char_len = col
+        .char_length
+        .checked_div(max_bytes_per_char)
+        .unwrap_or(col.char_length);
     format!("char({})", char_len.max(1))
 }
 
@@ -603,15 +664,11 @@ fn format_text(col: &DdColumn) -> String {
     }
 }
 
-/// Returns max bytes per character for a collation ID.
+/// Returns max bytes per character for a collation ID (4 if unknown).
 fn charset_max_bytes(collation_id: u64) -> u64 {
-    match collation_id {
-        2 | 8 | 11 | 47 | 48 => 1, // latin1, latin2, ascii
-        33 | 83 => 3,              // utf8mb3
-        45 | 46 | 224 | 255 => 4,  // utf8mb4
-        63 => 1,                   // binary
-        _ => 4,                    // default to utf8mb4
-    }
+    charset_from_collation(collation_id)
+        .map(|cs| crate::innodb::charset::charset_width(cs).1 as u64)
+        .unwrap_or(4)
 }
 
 /// Format MySQL version from version_id (e.g., 90001 -> "9.0.1").
@@ -880,18 +937,10 @@ fn build_index_def(idx: &DdIndex, columns: &HashMap<u64, &DdColumn>) -> IndexDef
                 let col = columns.get(&e.column_opx);
                 let full_len = col.map(|c| c.char_length).unwrap_or(0);
                 let max_bytes = col.map(|c| charset_max_bytes(c.collation_id)).unwrap_or(4);
-                let full_char_len = if max_bytes > 0 {
-                    full_len / max_bytes
-                } else {
-                    full_len
-                };
+                let full_char_len = full_len.checked_div(max_bytes).unwrap_or(full_len);
                 if e.length < full_char_len {
                     // Convert byte-based e.length to characters for DDL
-                    let prefix_chars = if max_bytes > 0 {
-                        e.length / max_bytes
-                    } else {
-                        e.length
-                    };
+                    let prefix_chars = e.length.checked_div(max_bytes).unwrap_or(e.length);
                     if prefix_chars > 0 {
                         Some(prefix_chars)
                     } else {
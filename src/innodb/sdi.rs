@@ -143,7 +143,7 @@ pub fn extract_sdi_from_pages(
     let mut all_records = Vec::new();
 
     for &page_num in sdi_pages {
-        let page_data = ts.read_page(page_num)?;
+        let page_data = ts.read_page_uncompressed(page_num)?;
 
         let header = match FilHeader::parse(&page_data) {
             Some(h) => h,
@@ -497,11 +497,7 @@ pub fn simulate_recovery(
             .iter()
             .filter(|s| s.checksum_valid && s.btree_level == Some(0))
             .count() as u64;
-        let avg_records_per_leaf = if intact_leaf_count > 0 {
-            total_records / intact_leaf_count
-        } else {
-            0
-        };
+        let avg_records_per_leaf = total_records.checked_div(intact_leaf_count).unwrap_or(0);
         let corrupt_leaf_no_header = pages_for_index
             .iter()
             .filter(|s| {
@@ -7,13 +7,25 @@
 //!
 //! The FSP header from page 0 is also parsed and cached, giving access to
 //! the space ID, tablespace size, and feature flags (compression, encryption).
+//!
+//! For ROW_FORMAT=COMPRESSED tablespaces the physical page size is the
+//! compressed (KEY_BLOCK_SIZE) size; [`Tablespace::read_page_uncompressed`]
+//! returns INDEX pages inflated to the logical page size.
+//!
+//! A system tablespace split across `ibdata1`, `ibdata2`, ... is opened with
+//! [`Tablespace::open_data_files`], which numbers pages continuously across
+//! the files.
 
 use std::io::{Cursor, Read, Seek, SeekFrom};
 
+use crate::innodb::compression::{decompress_page, decompress_zip_page};
 use crate::innodb::constants::*;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::innodb::datafile::DataFile;
 use crate::innodb::decryption::DecryptionContext;
 use crate::innodb::encryption::{self, EncryptionInfo};
 use crate::innodb::page::{FilHeader, FilTrailer, FspHeader};
+use crate::innodb::page_types::PageType;
 use crate::innodb::vendor::{detect_vendor_from_flags, VendorInfo};
 use crate::IdbError;
 
@@ -76,16 +88,71 @@ impl Seek for MmapReader {
     }
 }
 
+/// A reader presenting an ordered set of files as one contiguous stream.
+///
+/// Each segment contributes its first `len` bytes; reads that reach the end
+/// of a segment continue at the start of the next one.
+#[cfg(not(target_arch = "wasm32"))]
+struct MultiFileReader {
+    /// (file, start offset in the stream, length)
+    segments: Vec<(std::fs::File, u64, u64)>,
+    total: u64,
+    position: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Read for MultiFileReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let pos = self.position;
+        let seg = match self
+            .segments
+            .iter_mut()
+            .find(|(_, start, len)| pos >= *start && pos < start + len)
+        {
+            Some(seg) => seg,
+            None => return Ok(0),
+        };
+        let (file, start, len) = (&mut seg.0, seg.1, seg.2);
+        let to_read = buf.len().min((start + len - pos) as usize);
+        file.seek(SeekFrom::Start(pos - start))?;
+        let n = file.read(&mut buf[..to_read])?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Seek for MultiFileReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::End(offset) => self.total as i64 + offset,
+            SeekFrom::Current(offset) => self.position as i64 + offset,
+        };
+        if new_pos < 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "seek to a negative position",
+            ));
+        }
+        self.position = new_pos as u64;
+        Ok(self.position)
+    }
+}
+
 /// Represents an open InnoDB tablespace file (.ibd) or in-memory tablespace.
 pub struct Tablespace {
     reader: Box<dyn ReadSeek>,
     file_size: u64,
     page_size: u32,
+    logical_page_size: u32,
+    zip_size: Option<u32>,
     page_count: u64,
     fsp_header: Option<FspHeader>,
     vendor_info: VendorInfo,
     encryption_info: Option<EncryptionInfo>,
     decryption_ctx: Option<DecryptionContext>,
+    decompress_pages: bool,
 }
 
 impl Tablespace {
@@ -180,6 +247,71 @@ impl Tablespace {
         Self::init(Box::new(file), file_size, Some(page_size))
     }
 
+    /// Open a tablespace stored across several files, such as a system
+    /// tablespace configured as `ibdata1:1G;ibdata2:1G:autoextend`.
+    ///
+    /// The files are concatenated in order into one page space: page 0 is
+    /// read from the first file and numbering continues across file
+    /// boundaries. A file with a configured [`DataFile::size`] contributes
+    /// exactly that many bytes (an error is returned if it is shorter); a
+    /// file without one contributes its whole length. The page size is
+    /// auto-detected from page 0 unless `page_size` is given.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    /// use idb::innodb::datafile::{system_data_files, SystemTablespaceConfig};
+    /// use idb::innodb::tablespace::Tablespace;
+    ///
+    /// let files = system_data_files(
+    ///     Path::new("/var/lib/mysql"),
+    ///     &SystemTablespaceConfig::default(),
+    /// )
+    /// .unwrap();
+    /// let ts = Tablespace::open_data_files(&files, None).unwrap();
+    /// println!("{} pages in {} files", ts.page_count(), files.len());
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_data_files(files: &[DataFile], page_size: Option<u32>) -> Result<Self, IdbError> {
+        if files.is_empty() {
+            return Err(IdbError::Argument("No data files given".to_string()));
+        }
+
+        let mut segments = Vec::with_capacity(files.len());
+        let mut total = 0u64;
+        for df in files {
+            let path = &df.path;
+            let file = std::fs::File::open(path)
+                .map_err(|e| IdbError::Io(format!("Cannot open {}: {}", path.display(), e)))?;
+            let actual = file
+                .metadata()
+                .map_err(|e| IdbError::Io(format!("Cannot stat {}: {}", path.display(), e)))?
+                .len();
+            let len = match df.size {
+                Some(size) if actual < size => {
+                    return Err(IdbError::Io(format!(
+                        "{} is {} bytes, expected at least {}",
+                        path.display(),
+                        actual,
+                        size
+                    )));
+                }
+                Some(size) => size,
+                None => actual,
+            };
+            segments.push((file, total, len));
+            total += len;
+        }
+
+        let reader = MultiFileReader {
+            segments,
+            total,
+            position: 0,
+        };
+        Self::init(Box::new(reader), total, page_size)
+    }
+
     /// Create a tablespace from an in-memory byte buffer with auto-detected page size.
     ///
     /// The byte buffer must contain at least one valid page starting with
@@ -238,7 +370,7 @@ impl Tablespace {
             Some(fsp) => detect_vendor_from_flags(fsp.flags),
             None => VendorInfo::mysql(),
         };
-        let page_size = forced_page_size.unwrap_or_else(|| match &fsp_header {
+        let logical_page_size = match &fsp_header {
             Some(fsp) => {
                 let detected = fsp.page_size_from_flags_with_vendor(&vendor_info);
                 if matches!(detected, 4096 | 8192 | 16384 | 32768 | 65536) {
@@ -248,7 +380,15 @@ impl Tablespace {
                 }
             }
             None => SIZE_PAGE_DEFAULT,
-        });
+        };
+        // ROW_FORMAT=COMPRESSED: pages are stored at the compressed size
+        let detected_zip_size = fsp_header
+            .as_ref()
+            .and_then(|fsp| fsp.zip_size_from_flags_with_vendor(&vendor_info))
+            .filter(|&zs| zs < logical_page_size);
+        let page_size =
+            forced_page_size.unwrap_or_else(|| detected_zip_size.unwrap_or(logical_page_size));
+        let zip_size = detected_zip_size.filter(|&zs| zs == page_size);
 
         let page_count = file_size / page_size as u64;
         let encryption_info = encryption::parse_encryption_info(&buf, page_size);
@@ -262,11 +402,14 @@ impl Tablespace {
             reader,
             file_size,
             page_size,
+            logical_page_size,
+            zip_size,
             page_count,
             fsp_header,
             vendor_info,
             encryption_info,
             decryption_ctx: None,
+            decompress_pages: false,
         })
     }
 
@@ -275,6 +418,25 @@ impl Tablespace {
         self.page_size
     }
 
+    /// Returns the logical (uncompressed) page size.
+    ///
+    /// Equal to [`page_size`](Self::page_size) except for ROW_FORMAT=COMPRESSED
+    /// tablespaces, where it is the size of the pages returned by
+    /// [`read_page_uncompressed`](Self::read_page_uncompressed).
+    pub fn logical_page_size(&self) -> u32 {
+        if self.zip_size.is_some() {
+            self.logical_page_size
+        } else {
+            self.page_size
+        }
+    }
+
+    /// Returns the compressed page size for ROW_FORMAT=COMPRESSED tablespaces,
+    /// or `None` if pages are stored uncompressed.
+    pub fn zip_size(&self) -> Option<u32> {
+        self.zip_size
+    }
+
     /// Returns the total number of pages in the file.
     pub fn page_count(&self) -> u64 {
         self.page_count
@@ -322,10 +484,39 @@ impl Tablespace {
         self.decryption_ctx.is_some()
     }
 
+    /// Enable or disable transparent page decompression.
+    ///
+    /// When enabled, [`read_page`](Self::read_page),
+    /// [`read_all_pages`](Self::read_all_pages), and
+    /// [`for_each_page`](Self::for_each_page) inflate MySQL punch-hole
+    /// (type 14) and MariaDB page_compressed (type 34354) pages with
+    /// [`decompress_page`] after any decryption, returning the logical page
+    /// image. Pages that fail to decompress are returned as stored.
+    pub fn set_page_decompression(&mut self, enabled: bool) {
+        self.decompress_pages = enabled;
+    }
+
+    /// Returns true if transparent page decompression is enabled.
+    pub fn page_decompression(&self) -> bool {
+        self.decompress_pages
+    }
+
+    /// Inflate a transparently compressed page in place, if enabled.
+    fn decompress_in_place(&self, buf: &mut [u8]) {
+        if !self.decompress_pages {
+            return;
+        }
+        if let Ok(Some(page)) = decompress_page(buf, self.page_size as usize) {
+            buf.copy_from_slice(&page);
+        }
+    }
+
     /// Read a single page by page number into a newly allocated buffer.
     ///
     /// If a decryption context has been set and the page has an encrypted
-    /// page type, the page is decrypted before being returned.
+    /// page type, the page is decrypted before being returned. With
+    /// [page decompression](Self::set_page_decompression) enabled,
+    /// transparently compressed pages are then inflated.
     ///
     /// # Examples
     ///
@@ -364,10 +555,56 @@ impl Tablespace {
         if let Some(ref ctx) = self.decryption_ctx {
             let _ = ctx.decrypt_page(&mut buf, self.page_size as usize)?;
         }
+        self.decompress_in_place(&mut buf);
 
         Ok(buf)
     }
 
+    /// Read a single page and return its logical (uncompressed) image.
+    ///
+    /// For ROW_FORMAT=COMPRESSED tablespaces, INDEX, RTREE, and SDI pages are
+    /// inflated with [`decompress_zip_page`] into a
+    /// [`logical_page_size`](Self::logical_page_size) buffer so the regular
+    /// record walkers can be used on them. All other pages, and every page of
+    /// an uncompressed tablespace, are returned exactly as
+    /// [`read_page`](Self::read_page) would return them.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use idb::innodb::tablespace::Tablespace;
+    /// use idb::innodb::record::walk_compact_records;
+    ///
+    /// let mut ts = Tablespace::open("compressed_table.ibd").unwrap();
+    /// let page = ts.read_page_uncompressed(4).unwrap();
+    /// for rec in walk_compact_records(&page) {
+    ///     println!("record at offset {}", rec.offset);
+    /// }
+    /// ```
+    pub fn read_page_uncompressed(&mut self, page_num: u64) -> Result<Vec<u8>, IdbError> {
+        let buf = self.read_page(page_num)?;
+        match self.uncompress_page(&buf)? {
+            Some(page) => Ok(page),
+            None => Ok(buf),
+        }
+    }
+
+    /// Inflate a compressed INDEX page, or return `None` if `page_data` is
+    /// not stored in page_zip format.
+    fn uncompress_page(&self, page_data: &[u8]) -> Result<Option<Vec<u8>>, IdbError> {
+        if self.zip_size.is_none() {
+            return Ok(None);
+        }
+        let page_type = FilHeader::parse(page_data).map(|h| h.page_type);
+        if !matches!(
+            page_type,
+            Some(PageType::Index) | Some(PageType::Rtree) | Some(PageType::Sdi)
+        ) {
+            return Ok(None);
+        }
+        decompress_zip_page(page_data, self.logical_page_size as usize).map(Some)
+    }
+
     /// Parse the FIL header from a page buffer.
     pub fn parse_fil_header(page_data: &[u8]) -> Option<FilHeader> {
         FilHeader::parse(page_data)
@@ -437,13 +674,24 @@ impl Tablespace {
             }
         }
 
+        // Inflate transparently compressed pages
+        if self.decompress_pages {
+            let ps = self.page_size as usize;
+            for page_num in 0..self.page_count as usize {
+                let offset = page_num * ps;
+                self.decompress_in_place(&mut data[offset..offset + ps]);
+            }
+        }
+
         Ok(data)
     }
 
     /// Iterate over all pages, calling the callback with (page_number, page_data).
     ///
     /// If a decryption context has been set, encrypted pages are decrypted
-    /// before being passed to the callback.
+    /// before being passed to the callback, and transparently compressed
+    /// pages are inflated when [page decompression](Self::set_page_decompression)
+    /// is enabled.
     ///
     /// # Examples
     ///
@@ -478,11 +726,39 @@ impl Tablespace {
             if let Some(ref ctx) = self.decryption_ctx {
                 let _ = ctx.decrypt_page(&mut buf, ps)?;
             }
+            self.decompress_in_place(&mut buf);
 
             callback(page_num, &buf)?;
         }
         Ok(())
     }
+
+    /// Iterate over all pages like [`for_each_page`](Self::for_each_page),
+    /// passing logical (uncompressed) page images to the callback.
+    ///
+    /// See [`read_page_uncompressed`](Self::read_page_uncompressed) for which
+    /// pages are inflated. Pages that fail to decompress (corrupt zlib stream
+    /// or modification log) are passed to the callback as `Err` so a single
+    /// damaged page does not abort the scan; the callback decides whether to
+    /// skip it or fail. For uncompressed tablespaces every page is passed as
+    /// `Ok`, as with [`for_each_page`](Self::for_each_page).
+    pub fn for_each_page_uncompressed<F>(&mut self, mut callback: F) -> Result<(), IdbError>
+    where
+        F: FnMut(u64, Result<&[u8], IdbError>) -> Result<(), IdbError>,
+    {
+        if self.zip_size.is_none() {
+            return self.for_each_page(|page_num, data| callback(page_num, Ok(data)));
+        }
+        for page_num in 0..self.page_count {
+            let buf = self.read_page(page_num)?;
+            match self.uncompress_page(&buf) {
+                Ok(Some(page)) => callback(page_num, Ok(&page))?,
+                Ok(None) => callback(page_num, Ok(&buf))?,
+                Err(e) => callback(page_num, Err(e))?,
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -508,7 +784,7 @@ mod tests {
         BigEndian::write_u32(&mut page[fsp + FSP_FREE_LIMIT..], total_pages);
         BigEndian::write_u32(&mut page[fsp + FSP_SPACE_FLAGS..], 0);
         let trailer = PS - SIZE_FIL_TRAILER;
-        BigEndian::write_u32(&mut page[trailer + 4..], 1000 & 0xFFFFFFFF);
+        BigEndian::write_u32(&mut page[trailer + 4..], 1000);
         let end = PS - SIZE_FIL_TRAILER;
         let crc1 = crc32c::crc32c(&page[FIL_PAGE_OFFSET..FIL_PAGE_FILE_FLUSH_LSN]);
         let crc2 = crc32c::crc32c(&page[FIL_PAGE_DATA..end]);
@@ -567,6 +843,43 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_open_data_files_numbers_pages_across_files() {
+        // ibdata1 is declared as 2 pages but holds a stray third one
+        let first = write_pages(&[
+            build_fsp_page(0, 4),
+            build_index_page(1, 0, 2000),
+            build_index_page(9, 0, 9000),
+        ]);
+        let second = write_pages(&[build_index_page(2, 0, 3000), build_index_page(3, 0, 4000)]);
+        let files = [
+            DataFile {
+                path: first.path().to_path_buf(),
+                size: Some(2 * SIZE_PAGE_DEFAULT as u64),
+            },
+            DataFile {
+                path: second.path().to_path_buf(),
+                size: None,
+            },
+        ];
+        let mut ts = Tablespace::open_data_files(&files, None).unwrap();
+        assert_eq!(ts.page_count(), 4);
+        for n in 1..4 {
+            let header = FilHeader::parse(&ts.read_page(n).unwrap()).unwrap();
+            assert_eq!(header.page_number, n as u32);
+        }
+        assert_eq!(
+            ts.read_all_pages().unwrap().len(),
+            4 * SIZE_PAGE_DEFAULT as usize
+        );
+
+        let too_short = [DataFile {
+            path: second.path().to_path_buf(),
+            size: Some(3 * SIZE_PAGE_DEFAULT as u64),
+        }];
+        assert!(Tablespace::open_data_files(&too_short, None).is_err());
+    }
+
     #[test]
     fn test_read_page_returns_correct_data() {
         let tmp = write_pages(&[build_fsp_page(5, 2), build_index_page(1, 5, 9999)]);
@@ -619,6 +932,45 @@ mod tests {
         assert_eq!(visited, vec![0, 1, 2]);
     }
 
+    #[test]
+    fn test_page_decompression_returns_logical_pages() {
+        let page1 = build_index_page(1, 1, 2000);
+        // MySQL COMPRESSION='lz4' image of page 1
+        let payload = lz4_flex::compress(&page1[FIL_PAGE_DATA..]);
+        let mut stored = vec![0u8; PS];
+        stored[..FIL_PAGE_DATA].copy_from_slice(&page1[..FIL_PAGE_DATA]);
+        BigEndian::write_u16(&mut stored[FIL_PAGE_TYPE..], 14);
+        stored[FIL_PAGE_COMPRESSION_VERSION] = 1;
+        stored[FIL_PAGE_COMPRESSION_ALGORITHM] = 2;
+        BigEndian::write_u16(&mut stored[FIL_PAGE_COMPRESSION_ORIGINAL_TYPE..], 17855);
+        BigEndian::write_u16(
+            &mut stored[FIL_PAGE_COMPRESSION_ORIGINAL_SIZE..],
+            (PS - FIL_PAGE_DATA) as u16,
+        );
+        BigEndian::write_u16(
+            &mut stored[FIL_PAGE_COMPRESSION_COMPRESSED_SIZE..],
+            payload.len() as u16,
+        );
+        stored[FIL_PAGE_DATA..FIL_PAGE_DATA + payload.len()].copy_from_slice(&payload);
+
+        let mut data = build_fsp_page(1, 2);
+        data.extend_from_slice(&stored);
+        let mut ts = Tablespace::from_bytes(data).unwrap();
+        assert!(!ts.page_decompression());
+        assert_eq!(ts.read_page(1).unwrap(), stored);
+
+        ts.set_page_decompression(true);
+        assert_eq!(ts.read_page(1).unwrap(), page1);
+        assert_eq!(&ts.read_all_pages().unwrap()[PS..], &page1[..]);
+        ts.for_each_page(|num, data| {
+            if num == 1 {
+                assert_eq!(data, &page1[..]);
+            }
+            Ok(())
+        })
+        .unwrap();
+    }
+
     #[test]
     fn test_from_bytes_detects_page_size() {
         let mut data = build_fsp_page(1, 2);
@@ -830,4 +1182,64 @@ mod tests {
         let mut ts = Tablespace::open_mmap(tmp.path()).unwrap();
         assert!(ts.read_page(99).is_err());
     }
+
+    #[test]
+    fn test_compressed_tablespace_uses_zip_page_size() {
+        // KEY_BLOCK_SIZE=8: physical pages are 8K, logical pages 16K
+        let zip = 8192usize;
+        let mut data = vec![0u8; zip * 4];
+        let fsp = FIL_PAGE_DATA;
+        BigEndian::write_u16(&mut data[FIL_PAGE_TYPE..], 8); // FSP_HDR
+        BigEndian::write_u32(&mut data[fsp + FSP_SIZE..], 4);
+        BigEndian::write_u32(
+            &mut data[fsp + FSP_SPACE_FLAGS..],
+            (4 << FSP_FLAGS_POS_ZIP_SSIZE) | (1 << 5),
+        );
+        BigEndian::write_u32(&mut data[zip + FIL_PAGE_OFFSET..], 1);
+        BigEndian::write_u16(&mut data[zip + FIL_PAGE_TYPE..], 5); // INODE
+        BigEndian::write_u32(&mut data[2 * zip + FIL_PAGE_OFFSET..], 2);
+        BigEndian::write_u16(&mut data[2 * zip + FIL_PAGE_TYPE..], 17855); // INDEX, no zlib stream
+
+        let mut ts = Tablespace::from_bytes(data).unwrap();
+        assert_eq!(ts.page_size(), 8192);
+        assert_eq!(ts.logical_page_size(), 16384);
+        assert_eq!(ts.zip_size(), Some(8192));
+        assert_eq!(ts.page_count(), 4);
+
+        // Non-INDEX pages are returned as stored
+        let page = ts.read_page_uncompressed(1).unwrap();
+        assert_eq!(page, ts.read_page(1).unwrap());
+        assert_eq!(page.len(), 8192);
+
+        // A damaged compressed page is reported instead of passed through raw
+        assert!(ts.read_page_uncompressed(2).is_err());
+        let mut failed = Vec::new();
+        ts.for_each_page_uncompressed(|pn, page| {
+            if page.is_err() {
+                failed.push(pn);
+            }
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(failed, vec![2]);
+    }
+
+    #[test]
+    fn test_uncompressed_tablespace_logical_page_size() {
+        let tmp = write_pages(&[build_fsp_page(1, 2), build_index_page(1, 1, 2000)]);
+        let mut ts = Tablespace::open(tmp.path()).unwrap();
+        assert_eq!(ts.zip_size(), None);
+        assert_eq!(ts.logical_page_size(), ts.page_size());
+        assert_eq!(
+            ts.read_page_uncompressed(1).unwrap(),
+            ts.read_page(1).unwrap()
+        );
+        let mut count = 0;
+        ts.for_each_page_uncompressed(|_, _| {
+            count += 1;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(count, 2);
+    }
 }
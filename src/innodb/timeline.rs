@@ -8,7 +8,8 @@
 //! * **Undo log** — transaction history with `trx_id`, `table_id`, and operation type
 //! * **Binary log** — row events with `(database, table)` and Unix timestamps
 //!
-//! Use [`extract_redo_timeline`] / [`extract_undo_timeline`] /
+//! Use [`extract_redo_timeline`] (or [`extract_redo_set_timeline`] for a
+//! whole redo log directory) / [`extract_undo_timeline`] /
 //! [`extract_binlog_timeline`] to produce entries from each source, then
 //! [`merge_timeline`] to combine, sort, and summarize them.
 
@@ -16,11 +17,11 @@ use serde::Serialize;
 use std::collections::HashMap;
 use std::io::{Read, Seek};
 
-use crate::innodb::log::{
-    compute_record_lsn, parse_mlog_records, LogBlockHeader, LogFile, LOG_FILE_HDR_BLOCKS,
-};
+use crate::innodb::log::LogFile;
+use crate::innodb::mlog::{read_mlog_records, MlogStreamDecode};
 use crate::innodb::page::FilHeader;
 use crate::innodb::page_types::PageType;
+use crate::innodb::redo_set::RedoLogSet;
 use crate::innodb::undo::{parse_undo_records, UndoRecordType};
 use crate::IdbError;
 
@@ -129,40 +130,37 @@ pub struct TimelineReport {
 
 /// Extract timeline entries from a redo log file.
 ///
-/// Iterates all data blocks, parses MLOG records with
-/// [`parse_mlog_records`], and computes an approximate LSN for each record.
+/// Decodes the redo log record stream with [`read_mlog_records`]; each
+/// entry carries the exact start LSN of its record.
 pub fn extract_redo_timeline(log: &mut LogFile) -> Result<Vec<TimelineEntry>, IdbError> {
-    let header = log.read_header()?;
-    let data_blocks = log.data_block_count();
-    let mut entries = Vec::new();
-
-    for i in 0..data_blocks {
-        let block_idx = LOG_FILE_HDR_BLOCKS + i;
-        let block_data = log.read_block(block_idx)?;
-        let hdr = match LogBlockHeader::parse(&block_data) {
-            Some(h) if h.has_data() => h,
-            _ => continue,
-        };
+    Ok(redo_entries(read_mlog_records(log)?))
+}
 
-        let records = parse_mlog_records(&block_data, &hdr);
-        for rec in records {
-            let lsn = compute_record_lsn(header.start_lsn, i, rec.block_offset);
-            entries.push(TimelineEntry {
-                seq: 0, // assigned later by merge_timeline
-                source: TimelineSource::RedoLog,
-                lsn: Some(lsn),
-                timestamp: None,
-                space_id: rec.space_id,
-                page_no: rec.page_no,
-                action: TimelineAction::Redo {
-                    mlog_type: rec.record_type.to_string(),
-                    single_rec: rec.single_rec,
-                },
-            });
-        }
-    }
+/// Extract timeline entries from all files of a redo log set.
+///
+/// Unlike [`extract_redo_timeline`] on each file, records that span a file
+/// boundary are decoded too.
+pub fn extract_redo_set_timeline(set: &mut RedoLogSet) -> Result<Vec<TimelineEntry>, IdbError> {
+    Ok(redo_entries(set.read_mlog_records()?))
+}
 
-    Ok(entries)
+fn redo_entries(decoded: MlogStreamDecode) -> Vec<TimelineEntry> {
+    decoded
+        .records
+        .into_iter()
+        .map(|rec| TimelineEntry {
+            seq: 0, // assigned later by merge_timeline
+            source: TimelineSource::RedoLog,
+            lsn: Some(rec.start_lsn),
+            timestamp: None,
+            space_id: rec.space_id,
+            page_no: rec.page_no,
+            action: TimelineAction::Redo {
+                mlog_type: rec.record_type.to_string(),
+                single_rec: rec.single_rec,
+            },
+        })
+        .collect()
 }
 
 // ── Undo log extraction ─────────────────────────────────────────────────
@@ -242,13 +240,11 @@ pub fn extract_binlog_timeline<R: Read + Seek>(reader: R) -> Result<Vec<Timeline
     for ev in &analysis.events {
         match ev.type_code {
             // TABLE_MAP_EVENT — advance to next parsed TABLE_MAP
-            19 => {
-                if table_map_idx < analysis.table_maps.len() {
-                    let tme = &analysis.table_maps[table_map_idx];
-                    current_db = Some(tme.database_name.clone());
-                    current_table = Some(tme.table_name.clone());
-                    table_map_idx += 1;
-                }
+            19 if table_map_idx < analysis.table_maps.len() => {
+                let tme = &analysis.table_maps[table_map_idx];
+                current_db = Some(tme.database_name.clone());
+                current_table = Some(tme.table_name.clone());
+                table_map_idx += 1;
             }
             // WRITE_ROWS_EVENT_V2 (30), UPDATE_ROWS_EVENT_V2 (31), DELETE_ROWS_EVENT_V2 (32)
             30..=32 => {
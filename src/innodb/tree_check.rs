@@ -0,0 +1,470 @@
+//! Cross-page B+Tree consistency checks.
+//!
+//! [`page_dir`](crate::innodb::page_dir) validates each INDEX page on its
+//! own; [`check_index_trees`] checks how the pages of each index fit
+//! together, the offline counterpart of `CHECK TABLE ... EXTENDED`:
+//!
+//! - prev/next sibling links are symmetric and stay on one level of one index;
+//! - `PAGE_N_RECS` matches the records on the page's record list;
+//! - every page of an index is reached from its root exactly once, with each
+//!   child one level below its parent;
+//! - each node pointer's key is the minimum of its child page, and the
+//!   child's keys sort below the next node pointer;
+//! - the last key of each page sorts before the first key of its right
+//!   sibling.
+//!
+//! The first two checks need only the page headers and record lists. The
+//! others follow node pointers and compare keys, so they run only for
+//! indexes with an [`IndexLayout`].
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+use crate::innodb::btree::{decode_node_pointers, NodePointer};
+use crate::innodb::constants::FIL_NULL;
+use crate::innodb::export::IndexLayout;
+use crate::innodb::index::IndexHeader;
+use crate::innodb::page::FilHeader;
+use crate::innodb::page_dir::compare_records;
+use crate::innodb::page_types::PageType;
+use crate::innodb::record::walk_records;
+use crate::innodb::xdes::{AllocationMap, PageAllocState};
+
+/// Which cross-page check produced a [`TreeIssue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TreeCheckKind {
+    /// prev/next links are not symmetric or leave the index level.
+    SiblingLinks,
+    /// `PAGE_N_RECS` differs from the records on the record list.
+    RecordCount,
+    /// A page is unreachable from the root, reached twice, or on the wrong level.
+    Reachability,
+    /// A node pointer's key does not bound its child page.
+    NodePointerKeys,
+    /// The last key of a page does not sort before its right sibling's first key.
+    SiblingKeyOrder,
+}
+
+/// A cross-page inconsistency in an index tree.
+#[derive(Debug, Clone, Serialize)]
+pub struct TreeIssue {
+    /// Which check found the issue.
+    pub kind: TreeCheckKind,
+    /// Page the issue is reported on.
+    pub page_number: u32,
+    /// Human-readable description.
+    pub message: String,
+    /// Expected value (if applicable).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    /// Actual value found (if applicable).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<String>,
+}
+
+/// Result of [`check_index_trees`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TreeCheckReport {
+    /// INDEX pages whose sibling links were checked.
+    pub links_checked: u64,
+    /// INDEX pages whose `PAGE_N_RECS` was checked.
+    pub counts_checked: u64,
+    /// INDEX pages of indexes walked from their root.
+    pub reachability_checked: u64,
+    /// Non-leaf pages whose node pointers were checked against their children.
+    pub node_pointers_checked: u64,
+    /// Pages whose last key was compared with their right sibling's first key.
+    pub sibling_keys_checked: u64,
+    /// Issues found.
+    pub issues: Vec<TreeIssue>,
+}
+
+impl TreeCheckReport {
+    fn push(&mut self, kind: TreeCheckKind, page_number: u32, message: String) {
+        self.issues.push(TreeIssue {
+            kind,
+            page_number,
+            message,
+            expected: None,
+            actual: None,
+        });
+    }
+}
+
+/// Header fields of an INDEX page used by the checks.
+struct TreePage {
+    index_id: u64,
+    level: u16,
+    prev: u32,
+    next: u32,
+}
+
+/// Format a page pointer, showing FIL_NULL by name.
+fn page_ref(page_no: u32) -> String {
+    if page_no == FIL_NULL {
+        "FIL_NULL".to_string()
+    } else {
+        page_no.to_string()
+    }
+}
+
+/// Check the B+Tree structure of every index in a tablespace.
+///
+/// `read_page` returns the page image for a page number. With an
+/// `allocation` map, pages marked free in their extent descriptor are
+/// ignored, since freed pages keep their stale INDEX contents. The root of
+/// each index is its page on the highest level.
+pub fn check_index_trees<'a, F>(
+    total_pages: u64,
+    read_page: F,
+    allocation: Option<&AllocationMap>,
+    layouts: &[IndexLayout],
+) -> TreeCheckReport
+where
+    F: Fn(u32) -> Option<&'a [u8]>,
+{
+    let mut report = TreeCheckReport::default();
+    let mut pages: BTreeMap<u32, TreePage> = BTreeMap::new();
+
+    for page_no in 0..total_pages.min(u64::from(FIL_NULL)) as u32 {
+        if allocation.is_some_and(|a| a.page_state(page_no) == PageAllocState::Free) {
+            continue;
+        }
+        let data = match read_page(page_no) {
+            Some(d) => d,
+            None => continue,
+        };
+        let fil = match FilHeader::parse(data) {
+            Some(h) if h.page_type == PageType::Index => h,
+            _ => continue,
+        };
+        let hdr = match IndexHeader::parse(data) {
+            Some(h) => h,
+            None => continue,
+        };
+
+        report.counts_checked += 1;
+        let walked = walk_records(data).len();
+        if walked != hdr.n_recs as usize {
+            report.issues.push(TreeIssue {
+                kind: TreeCheckKind::RecordCount,
+                page_number: page_no,
+                message: format!(
+                    "Page {} PAGE_N_RECS is {} but the record list holds {} record(s)",
+                    page_no, hdr.n_recs, walked
+                ),
+                expected: Some(walked.to_string()),
+                actual: Some(hdr.n_recs.to_string()),
+            });
+        }
+
+        pages.insert(
+            page_no,
+            TreePage {
+                index_id: hdr.index_id,
+                level: hdr.level,
+                prev: fil.prev_page,
+                next: fil.next_page,
+            },
+        );
+    }
+
+    check_sibling_links(&pages, total_pages, &mut report);
+
+    let mut by_index: BTreeMap<u64, Vec<u32>> = BTreeMap::new();
+    for (&page_no, page) in &pages {
+        by_index.entry(page.index_id).or_default().push(page_no);
+    }
+    for layout in layouts {
+        if let Some(index_pages) = by_index.get(&layout.index_id) {
+            walk_index(&pages, index_pages, &read_page, layout, &mut report);
+            check_sibling_keys(&pages, index_pages, &read_page, layout, &mut report);
+        }
+    }
+
+    report
+}
+
+/// Check that each page's neighbours link back to it on the same level.
+///
+/// Pointers past the end of the file are left to the chain bounds check.
+fn check_sibling_links(
+    pages: &BTreeMap<u32, TreePage>,
+    total_pages: u64,
+    report: &mut TreeCheckReport,
+) {
+    for (&page_no, page) in pages {
+        report.links_checked += 1;
+        for (dir, target) in [("next", page.next), ("prev", page.prev)] {
+            if target == FIL_NULL || u64::from(target) >= total_pages {
+                continue;
+            }
+            let back = match pages.get(&target) {
+                Some(t) if t.index_id == page.index_id && t.level == page.level => {
+                    if dir == "next" {
+                        t.prev
+                    } else {
+                        t.next
+                    }
+                }
+                _ => {
+                    report.push(
+                        TreeCheckKind::SiblingLinks,
+                        page_no,
+                        format!(
+                            "Page {} {} page {} is not an INDEX page of index {} at level {}",
+                            page_no, dir, target, page.index_id, page.level
+                        ),
+                    );
+                    continue;
+                }
+            };
+            if back != page_no {
+                let back_dir = if dir == "next" { "prev" } else { "next" };
+                report.issues.push(TreeIssue {
+                    kind: TreeCheckKind::SiblingLinks,
+                    page_number: page_no,
+                    message: format!(
+                        "Page {} {} page {} has {} pointer {}",
+                        page_no,
+                        dir,
+                        target,
+                        back_dir,
+                        page_ref(back)
+                    ),
+                    expected: Some(page_no.to_string()),
+                    actual: Some(page_ref(back)),
+                });
+            }
+        }
+    }
+}
+
+/// Walk one index from its root, checking reachability, child levels, and
+/// node pointer keys.
+fn walk_index<'a, F>(
+    pages: &BTreeMap<u32, TreePage>,
+    index_pages: &[u32],
+    read_page: &F,
+    layout: &IndexLayout,
+    report: &mut TreeCheckReport,
+) where
+    F: Fn(u32) -> Option<&'a [u8]>,
+{
+    let top = match index_pages.iter().map(|p| pages[p].level).max() {
+        Some(l) => l,
+        None => return,
+    };
+    let roots: Vec<u32> = index_pages
+        .iter()
+        .copied()
+        .filter(|p| pages[p].level == top)
+        .collect();
+    if roots.len() > 1 {
+        report.issues.push(TreeIssue {
+            kind: TreeCheckKind::Reachability,
+            page_number: roots[0],
+            message: format!(
+                "Index {} has {} pages at its top level {}",
+                layout.name,
+                roots.len(),
+                top
+            ),
+            expected: Some("1 root page".to_string()),
+            actual: Some(format!("{} pages", roots.len())),
+        });
+    }
+
+    report.reachability_checked += index_pages.len() as u64;
+    let key_columns = layout.node_pointer_columns();
+    let mut reached: HashMap<u32, u32> = roots.iter().map(|&r| (r, r)).collect();
+    let mut stack = roots.clone();
+    while let Some(page_no) = stack.pop() {
+        let level = pages[&page_no].level;
+        if level == 0 {
+            continue;
+        }
+        let data = match read_page(page_no) {
+            Some(d) => d,
+            None => continue,
+        };
+        report.node_pointers_checked += 1;
+        let pointers = decode_node_pointers(data, key_columns);
+        for (i, ptr) in pointers.iter().enumerate() {
+            let child = ptr.child_page_no;
+            let child_level = match pages.get(&child) {
+                Some(c) if c.index_id == layout.index_id => c.level,
+                _ => {
+                    report.push(
+                        TreeCheckKind::Reachability,
+                        page_no,
+                        format!(
+                            "Page {} node pointer at offset {} points to page {}, which is not an INDEX page of index {}",
+                            page_no, ptr.offset, page_ref(child), layout.name
+                        ),
+                    );
+                    continue;
+                }
+            };
+            if let Some(&parent) = reached.get(&child) {
+                report.push(
+                    TreeCheckKind::Reachability,
+                    child,
+                    format!(
+                        "Page {} is reached from page {} and again from page {}",
+                        child, parent, page_no
+                    ),
+                );
+                continue;
+            }
+            reached.insert(child, page_no);
+            if child_level + 1 != level {
+                report.issues.push(TreeIssue {
+                    kind: TreeCheckKind::Reachability,
+                    page_number: child,
+                    message: format!(
+                        "Page {} is at level {} but its parent page {} is at level {}",
+                        child, child_level, page_no, level
+                    ),
+                    expected: Some((level - 1).to_string()),
+                    actual: Some(child_level.to_string()),
+                });
+            } else {
+                stack.push(child);
+            }
+            if let Some(child_data) = read_page(child) {
+                check_node_pointer(
+                    (page_no, data),
+                    ptr,
+                    pointers.get(i + 1),
+                    (child, child_data),
+                    layout,
+                    report,
+                );
+            }
+        }
+    }
+
+    for &page_no in index_pages {
+        if !reached.contains_key(&page_no) {
+            report.push(
+                TreeCheckKind::Reachability,
+                page_no,
+                format!(
+                    "Page {} (level {}) is not reachable from the root of index {}",
+                    page_no, pages[&page_no].level, layout.name
+                ),
+            );
+        }
+    }
+}
+
+/// Check that a node pointer's key is the minimum of its child page and
+/// that the child's keys sort below the next node pointer on the parent.
+///
+/// On non-leaf children the key must equal the child's first key; leaf
+/// children may have lost their first record since the pointer was made.
+fn check_node_pointer(
+    (parent_no, parent): (u32, &[u8]),
+    ptr: &NodePointer,
+    next_ptr: Option<&NodePointer>,
+    (child_no, child): (u32, &[u8]),
+    layout: &IndexLayout,
+    report: &mut TreeCheckReport,
+) {
+    let records = walk_records(child);
+    let (first, last) = match (records.first(), records.last()) {
+        (Some(f), Some(l)) => (f, l),
+        _ => return,
+    };
+    let child_leaf = IndexHeader::parse(child).is_none_or(|h| h.level == 0);
+
+    if !ptr.min_rec {
+        match compare_records(parent, ptr.offset, child, first.offset, layout) {
+            Some(Ordering::Greater) => report.push(
+                TreeCheckKind::NodePointerKeys,
+                parent_no,
+                format!(
+                    "Page {} node pointer at offset {} has a key greater than the first record of child page {}",
+                    parent_no, ptr.offset, child_no
+                ),
+            ),
+            Some(Ordering::Less) if !child_leaf => report.push(
+                TreeCheckKind::NodePointerKeys,
+                parent_no,
+                format!(
+                    "Page {} node pointer at offset {} does not match the first record of child page {}",
+                    parent_no, ptr.offset, child_no
+                ),
+            ),
+            _ => {}
+        }
+    }
+
+    if let Some(next) = next_ptr {
+        if matches!(
+            compare_records(child, last.offset, parent, next.offset, layout),
+            Some(Ordering::Greater | Ordering::Equal)
+        ) {
+            report.push(
+                TreeCheckKind::NodePointerKeys,
+                parent_no,
+                format!(
+                    "Page {} child page {} holds keys at or above the next node pointer at offset {}",
+                    parent_no, child_no, next.offset
+                ),
+            );
+        }
+    }
+}
+
+/// Check that the last key of each page sorts before the first key of its
+/// right sibling.
+fn check_sibling_keys<'a, F>(
+    pages: &BTreeMap<u32, TreePage>,
+    index_pages: &[u32],
+    read_page: &F,
+    layout: &IndexLayout,
+    report: &mut TreeCheckReport,
+) where
+    F: Fn(u32) -> Option<&'a [u8]>,
+{
+    for &page_no in index_pages {
+        let page = &pages[&page_no];
+        // Only follow links the sibling check found consistent
+        let linked = pages.get(&page.next).is_some_and(|n| {
+            n.index_id == page.index_id && n.level == page.level && n.prev == page_no
+        });
+        if !linked {
+            continue;
+        }
+        let (data, next_data) = match (read_page(page_no), read_page(page.next)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => continue,
+        };
+        let (last, first) = match (
+            walk_records(data).last().map(|r| r.offset),
+            walk_records(next_data).first().map(|r| r.offset),
+        ) {
+            (Some(l), Some(f)) => (l, f),
+            _ => continue,
+        };
+
+        report.sibling_keys_checked += 1;
+        if matches!(
+            compare_records(data, last, next_data, first, layout),
+            Some(Ordering::Greater | Ordering::Equal)
+        ) {
+            report.push(
+                TreeCheckKind::SiblingKeyOrder,
+                page_no,
+                format!(
+                    "Page {} last record at offset {} does not sort before the first record at offset {} of next page {} in index {}",
+                    page_no, last, first, page.next, layout.name
+                ),
+            );
+        }
+    }
+}
@@ -15,7 +15,10 @@
 
 use serde::Serialize;
 
-use crate::innodb::export::{decode_page_records, extract_column_layout, extract_table_name};
+use crate::innodb::export::{
+    decode_page_records, decode_page_records_with_lobs, extract_column_layout, extract_table_name,
+    DEFAULT_MAX_LOB_SIZE,
+};
 use crate::innodb::field_decode::{self, ColumnStorageInfo, FieldValue};
 use crate::innodb::index::IndexHeader;
 use crate::innodb::page::FilHeader;
@@ -103,10 +106,33 @@ pub fn scan_delete_marked_records(
     page_data: &[u8],
     page_number: u64,
     columns: &[ColumnStorageInfo],
-    page_size: u32,
 ) -> Vec<UndeletedRecord> {
-    let rows = decode_page_records(page_data, columns, true, true, page_size);
+    let rows = decode_page_records(page_data, columns, true, true);
+    delete_marked_rows_to_records(rows, page_number)
+}
 
+/// Scan a single page for delete-marked records, reassembling off-page
+/// columns from the tablespace (confidence 1.0).
+///
+/// Like [`scan_delete_marked_records`], but BLOB/TEXT values stored on
+/// overflow pages are followed and returned in full (up to `max_lob_size`
+/// bytes).
+pub fn scan_delete_marked_records_with_lobs(
+    ts: &mut Tablespace,
+    page_data: &[u8],
+    page_number: u64,
+    columns: &[ColumnStorageInfo],
+    max_lob_size: usize,
+) -> Vec<UndeletedRecord> {
+    let rows = decode_page_records_with_lobs(ts, page_data, columns, true, true, max_lob_size);
+    delete_marked_rows_to_records(rows, page_number)
+}
+
+/// Convert decoded delete-marked rows into [`UndeletedRecord`]s.
+fn delete_marked_rows_to_records(
+    rows: Vec<Vec<(String, FieldValue)>>,
+    page_number: u64,
+) -> Vec<UndeletedRecord> {
     rows.into_iter()
         .map(|row| {
             // Extract trx_id from system columns
@@ -452,13 +478,15 @@ pub fn extract_table_id(ts: &mut Tablespace) -> Option<u64> {
 /// Scans all leaf INDEX pages for delete-marked and free-list records.
 /// If `undo_ts` is provided, also scans undo log pages for DEL_MARK_REC entries.
 /// Results are filtered by `min_confidence` and `min_trx_id`, then sorted by
-/// confidence descending.
+/// confidence descending. Off-page columns of delete-marked records are
+/// reassembled up to `max_lob_size` bytes.
 pub fn scan_undeleted(
     ts: &mut Tablespace,
     undo_ts: Option<&mut Tablespace>,
     min_confidence: f64,
     min_trx_id: Option<u64>,
     target_page: Option<u64>,
+    max_lob_size: usize,
 ) -> Result<UndeleteScanResult, IdbError> {
     let table_name = extract_table_name(ts);
 
@@ -469,7 +497,7 @@ pub fn scan_undeleted(
         )
     })?;
 
-    let page_size = ts.page_size();
+    let page_size = ts.logical_page_size();
     let col_names: Vec<String> = columns
         .iter()
         .filter(|c| !c.is_system_column)
@@ -480,7 +508,11 @@ pub fn scan_undeleted(
 
     // Collect leaf INDEX pages matching the clustered index
     let mut leaf_pages: Vec<(u64, Vec<u8>)> = Vec::new();
-    ts.for_each_page(|pn, pdata| {
+    ts.for_each_page_uncompressed(|pn, pdata| {
+        let pdata = match pdata {
+            Ok(d) => d,
+            Err(_) => return Ok(()),
+        };
         if let Some(target) = target_page {
             if pn != target {
                 return Ok(());
@@ -506,7 +538,7 @@ pub fn scan_undeleted(
 
     // Scan each leaf page for delete-marked and free-list records
     for (pn, pdata) in &leaf_pages {
-        let mut dm = scan_delete_marked_records(pdata, *pn, &columns, page_size);
+        let mut dm = scan_delete_marked_records_with_lobs(ts, pdata, *pn, &columns, max_lob_size);
         all_records.append(&mut dm);
 
         let mut fl = scan_free_list_records(pdata, *pn, &columns, page_size);
@@ -589,7 +621,7 @@ pub fn scan_deleted_from_bytes(
         None => return Ok(None),
     };
 
-    let page_size = ts.page_size();
+    let page_size = ts.logical_page_size();
     let col_names: Vec<String> = columns
         .iter()
         .filter(|c| !c.is_system_column)
@@ -598,7 +630,12 @@ pub fn scan_deleted_from_bytes(
 
     let mut all_records = Vec::new();
 
-    ts.for_each_page(|pn, pdata| {
+    let mut leaf_pages: Vec<(u64, Vec<u8>)> = Vec::new();
+    ts.for_each_page_uncompressed(|pn, pdata| {
+        let pdata = match pdata {
+            Ok(d) => d,
+            Err(_) => return Ok(()),
+        };
         if let Some(target) = target_page {
             if pn != target {
                 return Ok(());
@@ -618,15 +655,23 @@ pub fn scan_deleted_from_bytes(
         if idx_hdr.index_id != clustered_index_id || !idx_hdr.is_leaf() {
             return Ok(());
         }
+        leaf_pages.push((pn, pdata.to_vec()));
+        Ok(())
+    })?;
 
-        let mut dm = scan_delete_marked_records(pdata, pn, &columns, page_size);
+    for (pn, pdata) in &leaf_pages {
+        let mut dm = scan_delete_marked_records_with_lobs(
+            &mut ts,
+            pdata,
+            *pn,
+            &columns,
+            DEFAULT_MAX_LOB_SIZE,
+        );
         all_records.append(&mut dm);
 
-        let mut fl = scan_free_list_records(pdata, pn, &columns, page_size);
+        let mut fl = scan_free_list_records(pdata, *pn, &columns, page_size);
         all_records.append(&mut fl);
-
-        Ok(())
-    })?;
+    }
 
     all_records.sort_by(|a, b| {
         b.confidence
@@ -670,6 +715,15 @@ pub fn field_value_to_sql(val: &FieldValue) -> String {
         FieldValue::Double(d) => d.to_string(),
         FieldValue::Str(s) => format!("'{}'", s.replace('\'', "''")),
         FieldValue::Hex(h) => format!("X'{}'", h),
+        FieldValue::Json(v) => format!("'{}'", v.to_string().replace('\'', "''")),
+        // Coordinates are in storage (x = longitude) order, so geographic
+        // SRIDs need an explicit axis order
+        FieldValue::Geometry(g) if g.srid != 0 => format!(
+            "ST_GeomFromText('{}', {}, 'axis-order=long-lat')",
+            g.geometry.to_wkt(),
+            g.srid
+        ),
+        FieldValue::Geometry(g) => format!("ST_GeomFromText('{}')", g.geometry.to_wkt()),
     }
 }
 
@@ -683,6 +737,8 @@ pub fn field_value_to_json(val: &FieldValue) -> serde_json::Value {
         FieldValue::Double(d) => serde_json::json!(*d),
         FieldValue::Str(s) => serde_json::json!(s),
         FieldValue::Hex(h) => serde_json::json!(h),
+        FieldValue::Json(v) => v.clone(),
+        FieldValue::Geometry(g) => g.geometry.to_geojson(),
     }
 }
 
@@ -745,6 +801,26 @@ mod tests {
             field_value_to_sql(&FieldValue::Hex("DEADBEEF".into())),
             "X'DEADBEEF'"
         );
+        assert_eq!(
+            field_value_to_sql(&FieldValue::Json(serde_json::json!({"a": "it's"}))),
+            "'{\"a\":\"it''s\"}'"
+        );
+        let point = |srid| {
+            FieldValue::Geometry(crate::innodb::geometry::SpatialValue {
+                srid,
+                geometry: crate::innodb::geometry::Geometry::Point(
+                    crate::innodb::geometry::Point { x: 1.0, y: 2.5 },
+                ),
+            })
+        };
+        assert_eq!(
+            field_value_to_sql(&point(0)),
+            "ST_GeomFromText('POINT(1 2.5)')"
+        );
+        assert_eq!(
+            field_value_to_sql(&point(4326)),
+            "ST_GeomFromText('POINT(1 2.5)', 4326, 'axis-order=long-lat')"
+        );
     }
 
     #[test]
@@ -761,6 +837,10 @@ mod tests {
             field_value_to_json(&FieldValue::Str("test".into())),
             serde_json::json!("test")
         );
+        assert_eq!(
+            field_value_to_json(&FieldValue::Json(serde_json::json!([1, "x"]))),
+            serde_json::json!([1, "x"])
+        );
     }
 
     #[test]
@@ -776,7 +856,7 @@ mod tests {
         // An all-zeros page has no valid INDEX header so should return nothing
         let page = vec![0u8; 16384];
         let cols = vec![];
-        let result = scan_delete_marked_records(&page, 0, &cols, 16384);
+        let result = scan_delete_marked_records(&page, 0, &cols);
         assert!(result.is_empty());
     }
 
@@ -9,42 +9,12 @@
 use byteorder::{BigEndian, ByteOrder};
 use serde::Serialize;
 
-use crate::innodb::constants::{FIL_NULL, FIL_PAGE_DATA};
+use crate::innodb::constants::*;
 use crate::innodb::page::FilHeader;
 use crate::innodb::page_types::PageType;
 use crate::innodb::tablespace::Tablespace;
 use crate::IdbError;
 
-/// Undo log page header offsets (relative to FIL_PAGE_DATA).
-///
-/// From trx0undo.h in MySQL source.
-const TRX_UNDO_PAGE_TYPE: usize = 0; // 2 bytes
-const TRX_UNDO_PAGE_START: usize = 2; // 2 bytes
-const TRX_UNDO_PAGE_FREE: usize = 4; // 2 bytes
-#[allow(dead_code)]
-const TRX_UNDO_PAGE_NODE: usize = 6; // 12 bytes (FLST_NODE)
-const TRX_UNDO_PAGE_HDR_SIZE: usize = 18;
-
-/// Undo segment header offsets (relative to FIL_PAGE_DATA + TRX_UNDO_PAGE_HDR_SIZE).
-const TRX_UNDO_STATE: usize = 0; // 2 bytes
-const TRX_UNDO_LAST_LOG: usize = 2; // 2 bytes
-#[allow(dead_code)]
-const TRX_UNDO_FSEG_HEADER: usize = 4; // 10 bytes (FSEG_HEADER)
-#[allow(dead_code)]
-const TRX_UNDO_PAGE_LIST: usize = 14; // 16 bytes (FLST_BASE_NODE)
-const TRX_UNDO_SEG_HDR_SIZE: usize = 30;
-
-/// Undo log header offsets (at the start of the undo log within the page).
-const TRX_UNDO_TRX_ID: usize = 0; // 8 bytes
-const TRX_UNDO_TRX_NO: usize = 8; // 8 bytes
-const TRX_UNDO_DEL_MARKS: usize = 16; // 2 bytes
-const TRX_UNDO_LOG_START: usize = 18; // 2 bytes
-const TRX_UNDO_XID_EXISTS: usize = 20; // 1 byte
-const TRX_UNDO_DICT_TRANS: usize = 21; // 1 byte
-const TRX_UNDO_TABLE_ID: usize = 22; // 8 bytes
-const TRX_UNDO_NEXT_LOG: usize = 30; // 2 bytes
-const TRX_UNDO_PREV_LOG: usize = 32; // 2 bytes
-
 /// Undo page types.
 ///
 /// # Examples
@@ -336,7 +306,7 @@ impl UndoLogHeader {
             trx_no: BigEndian::read_u64(&d[TRX_UNDO_TRX_NO..]),
             del_marks: BigEndian::read_u16(&d[TRX_UNDO_DEL_MARKS..]) != 0,
             log_start: BigEndian::read_u16(&d[TRX_UNDO_LOG_START..]),
-            xid_exists: d[TRX_UNDO_XID_EXISTS] != 0,
+            xid_exists: d[TRX_UNDO_FLAGS] != 0,
             dict_trans: d[TRX_UNDO_DICT_TRANS] != 0,
             table_id: BigEndian::read_u64(&d[TRX_UNDO_TABLE_ID..]),
             next_log: BigEndian::read_u16(&d[TRX_UNDO_NEXT_LOG..]),
@@ -4,18 +4,38 @@
 //! requiring checksums to be valid — useful for catching
 //! logical corruption and metadata inconsistencies.
 
+use std::collections::HashMap;
+
 use byteorder::{BigEndian, ByteOrder};
 use serde::Serialize;
 
+use crate::innodb::btree::{extract_clustered_index_info, search_btree, PkValue};
 use crate::innodb::constants::*;
+use crate::innodb::export::{
+    csv_escape, decode_index_records, decode_page_records_with_lobs, extract_column_layout,
+    extract_index_layouts, read_compact_field_lengths, IndexLayout, RecordField,
+    DEFAULT_MAX_LOB_SIZE,
+};
+use crate::innodb::field_decode::{self, ColumnStorageInfo, FieldValue};
+use crate::innodb::ibuf;
 use crate::innodb::index::IndexHeader;
-use crate::innodb::page::FilHeader;
+use crate::innodb::inode;
+use crate::innodb::page::{FilHeader, FspHeader};
+use crate::innodb::page_dir;
 use crate::innodb::page_types::PageType;
+use crate::innodb::record::walk_compact_records;
+use crate::innodb::rtree::MinimumBoundingRectangle;
+use crate::innodb::schema::{parse_se_private_data, DdTable, SdiEnvelope};
+use crate::innodb::sdi;
+use crate::innodb::tablespace::Tablespace;
+use crate::innodb::tree_check::{self, TreeCheckKind, TreeCheckReport};
+use crate::innodb::vendor::detect_vendor_from_flags;
+use crate::innodb::xdes::{self, PageAllocState};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::IdbError;
 
 /// Kind of structural check performed.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum VerifyCheckKind {
     /// Page number at offset 4 matches expected position.
     PageNumberSequence,
@@ -29,6 +49,24 @@ pub enum VerifyCheckKind {
     PageChainBounds,
     /// Trailer LSN low-32 matches header LSN low-32.
     TrailerLsnMatch,
+    /// Pages marked used in the extent descriptors are owned by a file segment.
+    LeakedPages,
+    /// No page is owned by more than one file segment.
+    DoubleOwnedPages,
+    /// INDEX pages: directory slots agree with the record list and `n_owned`.
+    PageDirectory,
+    /// INDEX pages: records are in ascending key order.
+    RecordOrder,
+    /// INDEX pages: prev/next links are symmetric within one index level.
+    SiblingLinks,
+    /// INDEX pages: `PAGE_N_RECS` matches the records on the record list.
+    RecordCount,
+    /// Every INDEX page is reachable from its index root exactly once.
+    PageReachability,
+    /// Node pointer keys are the minimum of their child pages.
+    NodePointerKeys,
+    /// The last key of each page sorts before its right sibling's first key.
+    SiblingKeyOrder,
 }
 
 impl std::fmt::Display for VerifyCheckKind {
@@ -40,6 +78,15 @@ impl std::fmt::Display for VerifyCheckKind {
             VerifyCheckKind::BTreeLevelConsistency => write!(f, "btree_level_consistency"),
             VerifyCheckKind::PageChainBounds => write!(f, "page_chain_bounds"),
             VerifyCheckKind::TrailerLsnMatch => write!(f, "trailer_lsn_match"),
+            VerifyCheckKind::LeakedPages => write!(f, "leaked_pages"),
+            VerifyCheckKind::DoubleOwnedPages => write!(f, "double_owned_pages"),
+            VerifyCheckKind::PageDirectory => write!(f, "page_directory"),
+            VerifyCheckKind::RecordOrder => write!(f, "record_order"),
+            VerifyCheckKind::SiblingLinks => write!(f, "sibling_links"),
+            VerifyCheckKind::RecordCount => write!(f, "record_count"),
+            VerifyCheckKind::PageReachability => write!(f, "page_reachability"),
+            VerifyCheckKind::NodePointerKeys => write!(f, "node_pointer_keys"),
+            VerifyCheckKind::SiblingKeyOrder => write!(f, "sibling_key_order"),
         }
     }
 }
@@ -88,6 +135,21 @@ pub struct VerifyConfig {
     pub check_chain_bounds: bool,
     /// Check that trailer LSN low-32 matches header LSN low-32.
     pub check_trailer_lsn: bool,
+    /// Check page ownership against the file segment inodes (leaked and
+    /// double-owned pages).
+    pub check_page_ownership: bool,
+    /// Check the page directory of INDEX pages against their record lists.
+    pub check_page_directory: bool,
+    /// Check that records on INDEX pages are in ascending key order (only
+    /// for indexes listed in `index_layouts`).
+    pub check_record_order: bool,
+    /// Check the B+Tree structure across pages: sibling links and
+    /// `PAGE_N_RECS` on every INDEX page, plus reachability from the root
+    /// and key order across pages for indexes listed in `index_layouts`.
+    pub check_btree_structure: bool,
+    /// Column layouts of the table's indexes, from
+    /// [`export::extract_index_layouts`](crate::innodb::export::extract_index_layouts).
+    pub index_layouts: Vec<IndexLayout>,
 }
 
 impl Default for VerifyConfig {
@@ -99,6 +161,11 @@ impl Default for VerifyConfig {
             check_btree_levels: true,
             check_chain_bounds: true,
             check_trailer_lsn: true,
+            check_page_ownership: true,
+            check_page_directory: true,
+            check_record_order: true,
+            check_btree_structure: true,
+            index_layouts: Vec::new(),
         }
     }
 }
@@ -119,6 +186,10 @@ pub struct VerifyReport {
     pub findings: Vec<VerifyFinding>,
     /// Per-check summaries.
     pub summary: Vec<CheckSummary>,
+    /// Conditions that do not fail verification but make the file alone
+    /// an incomplete copy of the table (e.g. unmerged change buffer entries).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
 }
 
 /// Verify a tablespace by running all structural checks.
@@ -149,9 +220,23 @@ pub fn verify_tablespace(
     let mut chain_issues = 0u64;
     let mut trailer_checked = 0u64;
     let mut trailer_issues = 0u64;
+    let mut dir_checked = 0u64;
+    let mut dir_issues = 0u64;
+    let mut order_checked = 0u64;
+    let mut order_issues = 0u64;
 
     let mut prev_lsn: u64 = 0;
 
+    // ROW_FORMAT=COMPRESSED pages keep no uncompressed page directory
+    let compressed = all_pages
+        .get(..ps)
+        .and_then(FspHeader::parse)
+        .is_some_and(|fsp| {
+            fsp.zip_size_from_flags_with_vendor(&detect_vendor_from_flags(fsp.flags))
+                .is_some()
+        });
+    let check_order = config.check_record_order && !config.index_layouts.is_empty();
+
     for page_idx in 0..total_pages {
         let offset = page_idx as usize * ps;
         let page_data = &all_pages[offset..offset + ps];
@@ -302,6 +387,127 @@ pub fn verify_tablespace(
                 }
             }
         }
+
+        // Checks 7 and 8: Page directory and in-page record order
+        if header.page_type == PageType::Index && !compressed {
+            if config.check_page_directory {
+                if let Some(dir) = page_dir::check_page_directory(page_data) {
+                    dir_checked += 1;
+                    for issue in dir.issues {
+                        dir_issues += 1;
+                        findings.push(VerifyFinding {
+                            kind: VerifyCheckKind::PageDirectory,
+                            page_number: page_idx,
+                            message: format!("Page {} {}", page_idx, issue.message),
+                            expected: issue.expected,
+                            actual: issue.actual,
+                        });
+                    }
+                }
+            }
+            let layout = IndexHeader::parse(page_data).and_then(|idx| {
+                config
+                    .index_layouts
+                    .iter()
+                    .find(|l| l.index_id == idx.index_id)
+            });
+            if let Some(layout) = layout.filter(|_| check_order) {
+                order_checked += 1;
+                for issue in page_dir::check_record_order(page_data, layout) {
+                    order_issues += 1;
+                    findings.push(VerifyFinding {
+                        kind: VerifyCheckKind::RecordOrder,
+                        page_number: page_idx,
+                        message: format!("Page {} {}", page_idx, issue.message),
+                        expected: None,
+                        actual: None,
+                    });
+                }
+            }
+        }
+    }
+
+    // Checks 9 and 10: Page ownership by file segments
+    let mut owned_checked = 0u64;
+    let mut leaked_issues = 0u64;
+    let mut double_issues = 0u64;
+    let read_page = |p: u32| {
+        let start = p as usize * ps;
+        all_pages.get(start..start + ps).map(|d| d.to_vec())
+    };
+    let allocation =
+        if (config.check_page_ownership || config.check_btree_structure) && total_pages > 0 {
+            let page0 = &all_pages[..ps];
+            FspHeader::parse(page0).and_then(|fsp| {
+                let extent = xdes::extent_size(fsp.page_size_from_flags());
+                xdes::build_allocation_map(page0, page_size, extent, total_pages, read_page).ok()
+            })
+        } else {
+            None
+        };
+    let ownership = allocation
+        .as_ref()
+        .filter(|_| config.check_page_ownership)
+        .map(|alloc| (alloc, inode::build_ownership_map(alloc, read_page)));
+    if let Some((alloc, owners)) = &ownership {
+        owned_checked = (0..total_pages.min(u64::from(u32::MAX)) as u32)
+            .filter(|&p| alloc.page_state(p) == PageAllocState::Used)
+            .count() as u64;
+        for p in owners.leaked_pages(alloc, total_pages) {
+            leaked_issues += 1;
+            findings.push(VerifyFinding {
+                kind: VerifyCheckKind::LeakedPages,
+                page_number: p as u64,
+                message: format!("Page {} is marked used but owned by no file segment", p),
+                expected: None,
+                actual: None,
+            });
+        }
+        for (p, segs) in owners.double_owned_pages() {
+            double_issues += 1;
+            let ids: Vec<String> = segs.iter().map(|s| s.to_string()).collect();
+            findings.push(VerifyFinding {
+                kind: VerifyCheckKind::DoubleOwnedPages,
+                page_number: p as u64,
+                message: format!("Page {} is owned by {} file segments", p, segs.len()),
+                expected: Some("1 segment".to_string()),
+                actual: Some(format!("segments {}", ids.join(", "))),
+            });
+        }
+    }
+
+    // Checks 11-15: B+Tree structure across pages
+    let check_trees = config.check_btree_structure && !compressed;
+    let tree = if check_trees {
+        tree_check::check_index_trees(
+            total_pages,
+            |p| {
+                let start = p as usize * ps;
+                all_pages.get(start..start + ps)
+            },
+            allocation.as_ref(),
+            &config.index_layouts,
+        )
+    } else {
+        TreeCheckReport::default()
+    };
+    let mut tree_issues: HashMap<VerifyCheckKind, u64> = HashMap::new();
+    for issue in &tree.issues {
+        let kind = match issue.kind {
+            TreeCheckKind::SiblingLinks => VerifyCheckKind::SiblingLinks,
+            TreeCheckKind::RecordCount => VerifyCheckKind::RecordCount,
+            TreeCheckKind::Reachability => VerifyCheckKind::PageReachability,
+            TreeCheckKind::NodePointerKeys => VerifyCheckKind::NodePointerKeys,
+            TreeCheckKind::SiblingKeyOrder => VerifyCheckKind::SiblingKeyOrder,
+        };
+        *tree_issues.entry(kind).or_default() += 1;
+        findings.push(VerifyFinding {
+            kind,
+            page_number: u64::from(issue.page_number),
+            message: issue.message.clone(),
+            expected: issue.expected.clone(),
+            actual: issue.actual.clone(),
+        });
     }
 
     // Build summaries
@@ -354,9 +560,72 @@ pub fn verify_tablespace(
             passed: trailer_issues == 0,
         });
     }
+    if config.check_page_ownership {
+        summary.push(CheckSummary {
+            kind: VerifyCheckKind::LeakedPages,
+            pages_checked: owned_checked,
+            issues_found: leaked_issues,
+            passed: leaked_issues == 0,
+        });
+        summary.push(CheckSummary {
+            kind: VerifyCheckKind::DoubleOwnedPages,
+            pages_checked: owned_checked,
+            issues_found: double_issues,
+            passed: double_issues == 0,
+        });
+    }
+    if config.check_page_directory {
+        summary.push(CheckSummary {
+            kind: VerifyCheckKind::PageDirectory,
+            pages_checked: dir_checked,
+            issues_found: dir_issues,
+            passed: dir_issues == 0,
+        });
+    }
+    if check_order {
+        summary.push(CheckSummary {
+            kind: VerifyCheckKind::RecordOrder,
+            pages_checked: order_checked,
+            issues_found: order_issues,
+            passed: order_issues == 0,
+        });
+    }
+    let mut tree_checks = Vec::new();
+    if config.check_btree_structure {
+        tree_checks.push((VerifyCheckKind::SiblingLinks, tree.links_checked));
+        tree_checks.push((VerifyCheckKind::RecordCount, tree.counts_checked));
+        if !config.index_layouts.is_empty() {
+            tree_checks.push((VerifyCheckKind::PageReachability, tree.reachability_checked));
+            tree_checks.push((VerifyCheckKind::NodePointerKeys, tree.node_pointers_checked));
+            tree_checks.push((VerifyCheckKind::SiblingKeyOrder, tree.sibling_keys_checked));
+        }
+    }
+    for (kind, pages_checked) in tree_checks {
+        let issues_found = tree_issues.get(&kind).copied().unwrap_or(0);
+        summary.push(CheckSummary {
+            kind,
+            pages_checked,
+            issues_found,
+            passed: issues_found == 0,
+        });
+    }
 
     let passed = summary.iter().all(|s| s.passed);
 
+    let mut warnings = Vec::new();
+    let buffered = ibuf::scan_buffered_pages(page_size, total_pages, |p| {
+        let offset = p as usize * ps;
+        all_pages.get(offset..offset + ps).map(|d| d.to_vec())
+    });
+    if !buffered.is_empty() {
+        warnings.push(format!(
+            "{} page(s) have unmerged changes in the change buffer (first: page {}); \
+             an offline copy of this file without ibdata1 would lose them",
+            buffered.len(),
+            buffered[0]
+        ));
+    }
+
     VerifyReport {
         file: file.to_string(),
         total_pages,
@@ -364,6 +633,7 @@ pub fn verify_tablespace(
         passed,
         findings,
         summary,
+        warnings,
     }
 }
 
@@ -384,25 +654,78 @@ pub struct RedoVerifyResult {
     pub covers_tablespace: bool,
     /// LSN gap (tablespace_max_lsn - checkpoint_lsn) if not covered; 0 otherwise.
     pub lsn_gap: u64,
+    /// Files of the redo log set, when a directory was given.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub redo_files: Vec<String>,
+    /// Ranges missing from the redo log set past the checkpoint; crash
+    /// recovery would stop at the first one.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub gaps_after_checkpoint: Vec<crate::innodb::redo_set::LsnGap>,
+    /// LSN ranges held by more than one file of the redo log set.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub overlaps: Vec<crate::innodb::redo_set::LsnOverlap>,
 }
 
 /// Verify redo log continuity against a tablespace.
 ///
 /// Opens the redo log, reads the most recent checkpoint LSN (higher of the
-/// two checkpoint slots), and compares it against the maximum LSN found
-/// across all pages in the tablespace.
+/// two checkpoint slots; for MariaDB 10.8+, of those with a valid
+/// checksum), and compares it against the maximum LSN found
+/// across all pages in the tablespace. If `redo_path` is a directory, all
+/// of its redo files are opened as a [`RedoLogSet`](crate::innodb::redo_set::RedoLogSet);
+/// the newest checkpoint of any file is used, and LSN gaps past it and
+/// overlaps between files are reported.
 #[cfg(not(target_arch = "wasm32"))]
 pub fn verify_redo_continuity(
     redo_path: &str,
     all_pages: &[u8],
     page_size: u32,
+) -> Result<RedoVerifyResult, IdbError> {
+    verify_redo_continuity_with_keyring(redo_path, None, all_pages, page_size)
+}
+
+/// Verify redo log continuity against a tablespace, decrypting an
+/// encrypted redo log (`innodb_redo_log_encrypt`) with the key derived from
+/// `keyring`.
+///
+/// Without the key, the blocks of an encrypted log fail their checksums,
+/// so a redo log directory would appear to have gaps past the checkpoint.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn verify_redo_continuity_with_keyring(
+    redo_path: &str,
+    keyring: Option<&crate::innodb::keyring::Keyring>,
+    all_pages: &[u8],
+    page_size: u32,
 ) -> Result<RedoVerifyResult, IdbError> {
     use crate::innodb::log::LogFile;
+    use crate::innodb::mariadb_redo::{is_mariadb_10_8, read_mariadb_checkpoint_lsn};
+    use crate::innodb::redo_set::RedoLogSet;
 
-    let mut log = LogFile::open(redo_path)?;
-    let cp0 = log.read_checkpoint(0)?;
-    let cp1 = log.read_checkpoint(1)?;
-    let checkpoint_lsn = cp0.lsn.max(cp1.lsn);
+    let mut redo_files = Vec::new();
+    let mut gaps_after_checkpoint = Vec::new();
+    let mut overlaps = Vec::new();
+    let checkpoint_lsn = if std::path::Path::new(redo_path).is_dir() {
+        let set = RedoLogSet::open_dir_with_keyring(redo_path, keyring)?;
+        let checkpoint_lsn = set.checkpoint_lsn().unwrap_or(0);
+        redo_files = set.members().iter().map(|m| m.path.clone()).collect();
+        gaps_after_checkpoint = set
+            .gaps()
+            .iter()
+            .filter(|g| g.end_lsn > checkpoint_lsn)
+            .copied()
+            .collect();
+        overlaps = set.overlaps().to_vec();
+        checkpoint_lsn
+    } else {
+        let mut log = LogFile::open(redo_path)?;
+        if is_mariadb_10_8(&log.read_header()?) {
+            read_mariadb_checkpoint_lsn(&mut log)?.unwrap_or(0)
+        } else {
+            let cp0 = log.read_checkpoint(0)?;
+            let cp1 = log.read_checkpoint(1)?;
+            cp0.lsn.max(cp1.lsn)
+        }
+    };
 
     let ps = page_size as usize;
     let total_pages = all_pages.len() / ps;
@@ -433,6 +756,9 @@ pub fn verify_redo_continuity(
         tablespace_max_lsn: max_lsn,
         covers_tablespace,
         lsn_gap,
+        redo_files,
+        gaps_after_checkpoint,
+        overlaps,
     })
 }
 
@@ -683,3 +1009,639 @@ pub fn verify_backup_meta(
         passed,
     })
 }
+
+// ---------------------------------------------------------------------------
+// Spatial index MBR verification
+// ---------------------------------------------------------------------------
+
+/// A spatial (R-tree) index and the GEOMETRY column it covers.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpatialIndexInfo {
+    /// Index name from the data dictionary.
+    pub name: String,
+    /// InnoDB index ID (from `se_private_data`).
+    pub index_id: u64,
+    /// Name of the indexed GEOMETRY column.
+    pub column: String,
+}
+
+/// A clustered-index row whose geometry is not covered by its spatial index entry.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpatialMbrIssue {
+    /// Spatial index name.
+    pub index_name: String,
+    /// Clustered index leaf page holding the row.
+    pub page_number: u64,
+    /// Primary key values of the row, comma-separated.
+    pub primary_key: String,
+    /// Human-readable description of the issue.
+    pub message: String,
+    /// MBR computed from the row's geometry.
+    pub geometry_mbr: MinimumBoundingRectangle,
+    /// MBR recorded in the spatial index leaf (if an entry was found).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_mbr: Option<MinimumBoundingRectangle>,
+}
+
+/// Result of cross-checking clustered-index geometries against spatial index MBRs.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpatialVerifyResult {
+    /// Spatial indexes that were checked.
+    pub indexes: Vec<SpatialIndexInfo>,
+    /// Number of (row, spatial index) pairs with a non-NULL geometry checked.
+    pub rows_checked: u64,
+    /// Rows whose geometry is missing from or outside the spatial index.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<SpatialMbrIssue>,
+    /// Whether every checked geometry lies within its index MBR.
+    pub passed: bool,
+}
+
+/// Find the spatial indexes of a table definition.
+///
+/// Returns one entry per SPATIAL index that has an InnoDB index ID in its
+/// `se_private_data`.
+pub fn extract_spatial_indexes(dd_table: &DdTable) -> Vec<SpatialIndexInfo> {
+    dd_table
+        .indexes
+        .iter()
+        .filter(|idx| idx.index_type == 5)
+        .filter_map(|idx| {
+            let index_id = parse_se_private_data(idx.se_private_data.as_deref()?)
+                .get("id")?
+                .parse::<u64>()
+                .ok()?;
+            let elem = idx.elements.first()?;
+            let column = dd_table.columns.get(elem.column_opx as usize)?;
+            Some(SpatialIndexInfo {
+                name: idx.name.clone(),
+                index_id,
+                column: column.name.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Verify that each row's geometry lies within its spatial index MBR.
+///
+/// Reads the table definition from SDI, then delegates to
+/// [`check_spatial_mbrs`]. Tables without spatial indexes pass trivially.
+pub fn verify_spatial_mbrs(ts: &mut Tablespace) -> Result<SpatialVerifyResult, crate::IdbError> {
+    let sdi_pages = sdi::find_sdi_pages(ts)?;
+    let records = sdi::extract_sdi_from_pages(ts, &sdi_pages)?;
+    let dd_table = records
+        .iter()
+        .filter(|rec| rec.sdi_type == 1)
+        .find_map(|rec| serde_json::from_str::<SdiEnvelope>(&rec.data).ok())
+        .map(|env| env.dd_object)
+        .ok_or_else(|| {
+            crate::IdbError::Parse("Cannot read table definition from SDI metadata".to_string())
+        })?;
+
+    let spatial = extract_spatial_indexes(&dd_table);
+    if spatial.is_empty() {
+        return Ok(SpatialVerifyResult {
+            indexes: spatial,
+            rows_checked: 0,
+            issues: Vec::new(),
+            passed: true,
+        });
+    }
+
+    let (columns, clustered_index_id) = extract_column_layout(ts).ok_or_else(|| {
+        crate::IdbError::Parse("Cannot extract column layout from SDI".to_string())
+    })?;
+    check_spatial_mbrs(ts, &columns, clustered_index_id, spatial)
+}
+
+/// Cross-check clustered-index geometries against spatial index leaf MBRs.
+///
+/// Decodes every live row on the clustered index leaf pages and computes the
+/// MBR of each indexed GEOMETRY column. Spatial index leaf records (an MBR
+/// followed by the primary key) are matched to rows by primary key. A row is
+/// reported if no live index entry exists for it, or if its geometry MBR is
+/// not contained in the recorded MBR. NULL geometries are skipped.
+pub fn check_spatial_mbrs(
+    ts: &mut Tablespace,
+    columns: &[ColumnStorageInfo],
+    clustered_index_id: u64,
+    indexes: Vec<SpatialIndexInfo>,
+) -> Result<SpatialVerifyResult, crate::IdbError> {
+    let pk_cols: Vec<ColumnStorageInfo> = columns
+        .iter()
+        .take_while(|c| !c.is_system_column)
+        .cloned()
+        .collect();
+    if pk_cols.is_empty() {
+        return Err(crate::IdbError::Parse(
+            "Spatial MBR check requires an explicit PRIMARY KEY".to_string(),
+        ));
+    }
+
+    // Clustered leaf pages are re-read one at a time below: decoding rows
+    // may follow LOB pointers, which needs the tablespace.
+    let mut clustered_pages: Vec<u64> = Vec::new();
+    // (spatial index position, primary key) -> recorded MBR
+    let mut index_mbrs: HashMap<(usize, String), MinimumBoundingRectangle> = HashMap::new();
+
+    ts.for_each_page_uncompressed(|pn, pdata| {
+        let pdata = match pdata {
+            Ok(d) => d,
+            Err(_) => return Ok(()),
+        };
+        let hdr = match FilHeader::parse(pdata) {
+            Some(h) => h,
+            None => return Ok(()),
+        };
+        let idx_hdr = match IndexHeader::parse(pdata) {
+            Some(h) if h.is_leaf() => h,
+            _ => return Ok(()),
+        };
+        if hdr.page_type == PageType::Index && idx_hdr.index_id == clustered_index_id {
+            clustered_pages.push(pn);
+        } else if hdr.page_type == PageType::Rtree {
+            if let Some(pos) = indexes.iter().position(|i| i.index_id == idx_hdr.index_id) {
+                for (pk, mbr) in read_spatial_leaf_entries(pdata, &pk_cols) {
+                    index_mbrs.insert((pos, pk), mbr);
+                }
+            }
+        }
+        Ok(())
+    })?;
+
+    let mut rows_checked = 0u64;
+    let mut issues = Vec::new();
+
+    for pn in clustered_pages {
+        let pdata = match ts.read_page_uncompressed(pn) {
+            Ok(d) => d,
+            Err(_) => continue,
+        };
+        let rows =
+            decode_page_records_with_lobs(ts, &pdata, columns, false, false, DEFAULT_MAX_LOB_SIZE);
+        for row in rows {
+            let pk = row_primary_key(&row[..pk_cols.len().min(row.len())]);
+            for (pos, index) in indexes.iter().enumerate() {
+                let geometry_mbr = match row.iter().find(|(name, _)| *name == index.column) {
+                    Some((_, FieldValue::Geometry(g))) => match g.geometry.mbr() {
+                        Some(m) => m,
+                        None => continue,
+                    },
+                    _ => continue,
+                };
+                rows_checked += 1;
+
+                let index_mbr = index_mbrs.get(&(pos, pk.clone())).cloned();
+                let message = match &index_mbr {
+                    None => "no spatial index entry for row".to_string(),
+                    Some(m) if !mbr_contains(m, &geometry_mbr) => {
+                        "geometry lies outside spatial index MBR".to_string()
+                    }
+                    Some(_) => continue,
+                };
+                issues.push(SpatialMbrIssue {
+                    index_name: index.name.clone(),
+                    page_number: pn,
+                    primary_key: pk.clone(),
+                    message,
+                    geometry_mbr,
+                    index_mbr,
+                });
+            }
+        }
+    }
+
+    let passed = issues.is_empty();
+    Ok(SpatialVerifyResult {
+        indexes,
+        rows_checked,
+        issues,
+        passed,
+    })
+}
+
+/// Read `(primary_key, mbr)` pairs from the live records of a spatial index
+/// leaf page.
+fn read_spatial_leaf_entries(
+    page_data: &[u8],
+    pk_cols: &[ColumnStorageInfo],
+) -> Vec<(String, MinimumBoundingRectangle)> {
+    let mut entries = Vec::new();
+    for rec in walk_compact_records(page_data) {
+        if rec.header.delete_mark() {
+            continue;
+        }
+        // The fixed 32-byte MBR has no null bit or length byte, so the
+        // record header only describes the primary key columns
+        let fields = match read_compact_field_lengths(page_data, rec.offset, pk_cols) {
+            Some(f) => f,
+            None => continue,
+        };
+        let mbr = match page_data
+            .get(rec.offset..)
+            .and_then(MinimumBoundingRectangle::parse)
+        {
+            Some(m) => m,
+            None => continue,
+        };
+
+        let mut pos = rec.offset + 32;
+        let mut values = Vec::with_capacity(pk_cols.len());
+        for (col, field) in pk_cols.iter().zip(fields) {
+            let len = match field {
+                RecordField::Stored { len, .. } => len,
+                RecordField::Null | RecordField::Absent => 0,
+            };
+            let data = match page_data.get(pos..pos + len) {
+                Some(d) => d,
+                None => break,
+            };
+            values.push((col.name.clone(), field_decode::decode_field(data, col)));
+            pos += len;
+        }
+        if values.len() == pk_cols.len() {
+            entries.push((row_primary_key(&values), mbr));
+        }
+    }
+    entries
+}
+
+/// Join primary key values into a lookup key.
+fn row_primary_key(values: &[(String, FieldValue)]) -> String {
+    values
+        .iter()
+        .map(|(_, v)| csv_escape(v))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Whether `outer` fully contains `inner`.
+fn mbr_contains(outer: &MinimumBoundingRectangle, inner: &MinimumBoundingRectangle) -> bool {
+    outer.min_x <= inner.min_x
+        && outer.min_y <= inner.min_y
+        && inner.max_x <= outer.max_x
+        && inner.max_y <= outer.max_y
+}
+
+// ---------------------------------------------------------------------------
+// Secondary index consistency
+// ---------------------------------------------------------------------------
+
+/// A decoded record as `(column_name, value)` pairs.
+type DecodedRow = Vec<(String, FieldValue)>;
+
+/// Kind of mismatch between a secondary index and the clustered index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum IndexEntryIssueKind {
+    /// A secondary index entry has no clustered row with its primary key
+    /// and key column values.
+    Orphan,
+    /// A clustered row has no entry in the secondary index.
+    Missing,
+    /// A clustered row has more than one entry in the secondary index.
+    Duplicate,
+}
+
+impl std::fmt::Display for IndexEntryIssueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IndexEntryIssueKind::Orphan => write!(f, "orphan"),
+            IndexEntryIssueKind::Missing => write!(f, "missing"),
+            IndexEntryIssueKind::Duplicate => write!(f, "duplicate"),
+        }
+    }
+}
+
+/// A secondary index entry or clustered row that the two indexes disagree on.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexEntryIssue {
+    /// Secondary index name.
+    pub index_name: String,
+    /// Kind of mismatch.
+    pub kind: IndexEntryIssueKind,
+    /// Secondary index leaf page for orphan entries, clustered index leaf
+    /// page for missing and duplicate entries.
+    pub page_number: u64,
+    /// Primary key values, comma-separated.
+    pub primary_key: String,
+    /// Human-readable description of the issue.
+    pub message: String,
+}
+
+/// Per-index totals of a secondary index consistency check.
+#[derive(Debug, Clone, Serialize)]
+pub struct SecondaryIndexCheck {
+    /// Index name from the data dictionary.
+    pub name: String,
+    /// InnoDB index ID (from `se_private_data`).
+    pub index_id: u64,
+    /// Live leaf entries decoded from the index.
+    pub entries_checked: u64,
+    /// Entries without a matching clustered row.
+    pub orphan_entries: u64,
+    /// Clustered rows without an entry.
+    pub missing_entries: u64,
+    /// Clustered rows with more than one entry.
+    pub duplicate_entries: u64,
+}
+
+/// Result of cross-checking secondary indexes against the clustered index.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexConsistencyResult {
+    /// Secondary indexes that were checked.
+    pub indexes: Vec<SecondaryIndexCheck>,
+    /// Live clustered index rows checked.
+    pub rows_checked: u64,
+    /// Orphan, missing, and duplicate entries.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<IndexEntryIssue>,
+    /// Whether every secondary index matches the clustered index.
+    pub passed: bool,
+}
+
+/// Verify that every secondary index matches the clustered index.
+///
+/// Reads the index layouts and the clustered index root page from SDI,
+/// then delegates to [`check_secondary_indexes`]. Tables without secondary
+/// indexes pass trivially.
+pub fn verify_secondary_indexes(
+    ts: &mut Tablespace,
+) -> Result<IndexConsistencyResult, crate::IdbError> {
+    let layouts = extract_index_layouts(ts).ok_or_else(|| {
+        crate::IdbError::Parse("Cannot extract index layouts from SDI".to_string())
+    })?;
+    if layouts.iter().all(|l| l.is_clustered) {
+        return Ok(IndexConsistencyResult {
+            indexes: Vec::new(),
+            rows_checked: 0,
+            issues: Vec::new(),
+            passed: true,
+        });
+    }
+    let (root_page_no, _, _) = extract_clustered_index_info(ts).ok_or_else(|| {
+        crate::IdbError::Parse(
+            "Index consistency check requires an explicit PRIMARY KEY and its root page in SDI"
+                .to_string(),
+        )
+    })?;
+    check_secondary_indexes(ts, &layouts, root_page_no)
+}
+
+/// Cross-check secondary index leaf entries against the clustered index.
+///
+/// Each live secondary entry is decoded with its index layout, and the
+/// clustered row with its primary key is located by searching the
+/// clustered B+Tree from `root_page_no` (see [`search_btree`]). The search
+/// compares strings byte-wise, so when it misses (e.g. a VARCHAR primary
+/// key in a case-insensitive collation) the row is looked up among all
+/// decoded clustered rows instead. The entry is an orphan if no live row
+/// is found or if any key column differs from the row; prefix key parts
+/// match when they are a prefix of the row's value. Every live clustered
+/// row must then be matched by exactly one entry per secondary index.
+/// Delete-marked rows and entries are ignored.
+pub fn check_secondary_indexes(
+    ts: &mut Tablespace,
+    layouts: &[IndexLayout],
+    root_page_no: u32,
+) -> Result<IndexConsistencyResult, crate::IdbError> {
+    let clustered = layouts
+        .iter()
+        .find(|l| l.is_clustered)
+        .ok_or_else(|| crate::IdbError::Parse("No clustered index layout".to_string()))?;
+    let pk_cols: Vec<ColumnStorageInfo> = clustered
+        .columns
+        .iter()
+        .take_while(|c| !c.is_system_column)
+        .cloned()
+        .collect();
+    if pk_cols.is_empty() {
+        return Err(crate::IdbError::Parse(
+            "Index consistency check requires an explicit PRIMARY KEY".to_string(),
+        ));
+    }
+    // search_btree compares keys in ascending order only
+    let searchable = !clustered.descending.iter().take(pk_cols.len()).any(|&d| d);
+    let secondary: Vec<&IndexLayout> = layouts.iter().filter(|l| !l.is_clustered).collect();
+
+    let mut clustered_pages: Vec<(u64, Vec<u8>)> = Vec::new();
+    // (secondary index position, leaf page, decoded entry)
+    let mut entries: Vec<(usize, u64, DecodedRow)> = Vec::new();
+
+    ts.for_each_page_uncompressed(|pn, pdata| {
+        let pdata = match pdata {
+            Ok(d) => d,
+            Err(_) => return Ok(()),
+        };
+        match FilHeader::parse(pdata) {
+            Some(h) if h.page_type == PageType::Index => {}
+            _ => return Ok(()),
+        }
+        let idx_hdr = match IndexHeader::parse(pdata) {
+            Some(h) if h.is_leaf() => h,
+            _ => return Ok(()),
+        };
+        if idx_hdr.index_id == clustered.index_id {
+            clustered_pages.push((pn, pdata.to_vec()));
+        } else if let Some(pos) = secondary
+            .iter()
+            .position(|l| l.index_id == idx_hdr.index_id)
+        {
+            for entry in decode_index_records(pdata, idx_hdr.index_id, layouts, false, false) {
+                entries.push((pos, pn, entry));
+            }
+        }
+        Ok(())
+    })?;
+
+    // Clustered leaf page -> live rows with their primary keys
+    let mut rows_by_page: HashMap<u64, Vec<(String, DecodedRow)>> = HashMap::new();
+    for (pn, pdata) in &clustered_pages {
+        let rows = decode_page_records_with_lobs(
+            ts,
+            pdata,
+            &clustered.columns,
+            false,
+            false,
+            DEFAULT_MAX_LOB_SIZE,
+        );
+        let keyed = rows
+            .into_iter()
+            .map(|row| (row_primary_key(&row[..pk_cols.len().min(row.len())]), row))
+            .collect();
+        rows_by_page.insert(*pn, keyed);
+    }
+    let rows_by_pk: HashMap<&str, &DecodedRow> = rows_by_page
+        .values()
+        .flatten()
+        .map(|(pk, row)| (pk.as_str(), row))
+        .collect();
+
+    let mut checks: Vec<SecondaryIndexCheck> = secondary
+        .iter()
+        .map(|l| SecondaryIndexCheck {
+            name: l.name.clone(),
+            index_id: l.index_id,
+            entries_checked: 0,
+            orphan_entries: 0,
+            missing_entries: 0,
+            duplicate_entries: 0,
+        })
+        .collect();
+    let mut issues = Vec::new();
+    // (secondary index position, primary key) -> matching entries
+    let mut matched: HashMap<(usize, String), u64> = HashMap::new();
+    let page_size = ts.page_size();
+
+    for (pos, pn, entry) in &entries {
+        checks[*pos].entries_checked += 1;
+        let pk_values: Vec<(String, FieldValue)> = pk_cols
+            .iter()
+            .filter_map(|c| entry.iter().find(|(name, _)| *name == c.name).cloned())
+            .collect();
+        if pk_values.len() != pk_cols.len() {
+            continue;
+        }
+        let pk = row_primary_key(&pk_values);
+
+        let search_key: Option<Vec<PkValue>> = if searchable {
+            pk_values.iter().map(|(_, v)| pk_search_value(v)).collect()
+        } else {
+            None
+        };
+        let found = search_key.and_then(|key| {
+            search_btree(
+                ts,
+                root_page_no,
+                clustered.index_id,
+                &pk_cols,
+                &key,
+                page_size,
+            )
+            .ok()
+            .and_then(|r| rows_by_page.get(&u64::from(r.leaf_page_no)))
+            .and_then(|rows| rows.iter().find(|(k, _)| *k == pk))
+            .map(|(_, row)| row)
+        });
+        let row = found.or_else(|| rows_by_pk.get(pk.as_str()).copied());
+
+        let message = match row {
+            None => "no clustered index row with this primary key".to_string(),
+            Some(row) => match entry_mismatch(entry, row, secondary[*pos]) {
+                Some(m) => m,
+                None => {
+                    *matched.entry((*pos, pk)).or_default() += 1;
+                    continue;
+                }
+            },
+        };
+        checks[*pos].orphan_entries += 1;
+        issues.push(IndexEntryIssue {
+            index_name: secondary[*pos].name.clone(),
+            kind: IndexEntryIssueKind::Orphan,
+            page_number: *pn,
+            primary_key: pk,
+            message,
+        });
+    }
+
+    let mut rows_checked = 0u64;
+    let mut pages: Vec<u64> = rows_by_page.keys().copied().collect();
+    pages.sort_unstable();
+    for pn in pages {
+        for (pk, _) in &rows_by_page[&pn] {
+            rows_checked += 1;
+            for (pos, index) in secondary.iter().enumerate() {
+                let count = matched.get(&(pos, pk.clone())).copied().unwrap_or(0);
+                let (kind, message) = match count {
+                    1 => continue,
+                    0 => {
+                        checks[pos].missing_entries += 1;
+                        (
+                            IndexEntryIssueKind::Missing,
+                            "no secondary index entry for row".to_string(),
+                        )
+                    }
+                    n => {
+                        checks[pos].duplicate_entries += 1;
+                        (
+                            IndexEntryIssueKind::Duplicate,
+                            format!("{} secondary index entries for row", n),
+                        )
+                    }
+                };
+                issues.push(IndexEntryIssue {
+                    index_name: index.name.clone(),
+                    kind,
+                    page_number: pn,
+                    primary_key: pk.clone(),
+                    message,
+                });
+            }
+        }
+    }
+
+    let passed = issues.is_empty();
+    Ok(IndexConsistencyResult {
+        indexes: checks,
+        rows_checked,
+        issues,
+        passed,
+    })
+}
+
+/// Describe the first key column of a secondary entry that differs from
+/// the clustered row, or `None` if they match.
+///
+/// Column prefix key parts of `index` match when they are a prefix of the
+/// row's value; every other column must be equal. Columns missing from the
+/// row (e.g. the hidden columns of functional indexes) are not compared.
+fn entry_mismatch(
+    entry: &[(String, FieldValue)],
+    row: &[(String, FieldValue)],
+    index: &IndexLayout,
+) -> Option<String> {
+    for (name, value) in entry {
+        let row_value = match row.iter().find(|(n, _)| n == name) {
+            Some((_, v)) => v,
+            None => continue,
+        };
+        let is_prefix = index
+            .columns
+            .iter()
+            .position(|c| c.name == *name)
+            .and_then(|i| index.prefix_lengths.get(i).copied().flatten())
+            .is_some();
+        let equal = match (value, row_value) {
+            (FieldValue::Str(a), FieldValue::Str(b)) | (FieldValue::Hex(a), FieldValue::Hex(b))
+                if is_prefix =>
+            {
+                b.starts_with(a.as_str())
+            }
+            _ => csv_escape(value) == csv_escape(row_value),
+        };
+        if !equal {
+            return Some(format!(
+                "column {} is {} in the index but {} in the clustered row",
+                name,
+                csv_escape(value),
+                csv_escape(row_value)
+            ));
+        }
+    }
+    None
+}
+
+/// Convert a decoded primary key value into a B+Tree search key.
+fn pk_search_value(value: &FieldValue) -> Option<PkValue> {
+    match value {
+        FieldValue::Int(v) => Some(PkValue::Int(*v)),
+        FieldValue::Uint(v) => Some(PkValue::Uint(*v)),
+        FieldValue::Str(s) => Some(PkValue::Str(s.clone())),
+        FieldValue::Hex(h) => (0..h.len())
+            .step_by(2)
+            .map(|i| h.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .map(PkValue::Bytes),
+        _ => None,
+    }
+}
@@ -0,0 +1,859 @@
+//! Extent descriptor (XDES) and FSP free-list parsing.
+//!
+//! InnoDB allocates space in extents of [`extent_size`] pages (1 MiB for
+//! page sizes up to 16K). Page 0 (FSP_HDR) and every `page_size`-th page
+//! after it (XDES pages, e.g. 16384, 32768, ... for 16K pages) carry an
+//! array of extent descriptors, one per extent in the following
+//! `page_size` pages. Each descriptor records:
+//!
+//! - the ID of the segment that owns the extent (if any),
+//! - a file-list node linking it into one of the FSP or segment lists,
+//! - the extent state (`XDES_FREE`, `XDES_FREE_FRAG`, `XDES_FULL_FRAG`,
+//!   `XDES_FSEG`, `XDES_FSEG_FRAG`), and
+//! - a bitmap with two bits per page, of which the first marks the page free.
+//!
+//! The FSP header on page 0 anchors the space-level lists: FSP_FREE (extents
+//! with no used pages), FSP_FREE_FRAG and FSP_FULL_FRAG (extents used for
+//! single-page fragment allocations), and FSP_SEG_INODES_FULL /
+//! FSP_SEG_INODES_FREE (file segment INODE pages).
+//!
+//! [`read_allocation_map`] decodes every descriptor below the tablespace size,
+//! walks the lists, and builds an [`AllocationMap`] that classifies each page
+//! as free, used, or not yet initialized. Combined with page contents this
+//! distinguishes pages that are allocated but still empty from pages that are
+//! truly free (see [`AllocationMap::summary`]).
+
+use std::collections::{HashMap, HashSet};
+
+use byteorder::{BigEndian, ByteOrder};
+use serde::Serialize;
+
+use crate::innodb::constants::*;
+use crate::innodb::page::FspHeader;
+use crate::innodb::tablespace::Tablespace;
+use crate::IdbError;
+
+/// Maximum number of nodes followed when walking a file list.
+const MAX_LIST_NODES: usize = 1 << 24;
+
+/// Number of pages in an extent for a (logical) page size.
+///
+/// # Examples
+///
+/// ```
+/// use idb::innodb::xdes::extent_size;
+///
+/// assert_eq!(extent_size(4096), 256);
+/// assert_eq!(extent_size(16384), 64);
+/// assert_eq!(extent_size(65536), 64);
+/// ```
+pub fn extent_size(page_size: u32) -> u32 {
+    if page_size <= SIZE_PAGE_16K {
+        1048576 / page_size
+    } else {
+        64
+    }
+}
+
+/// Size in bytes of one extent descriptor for an extent of `extent_size` pages.
+///
+/// This is 40 bytes for 16K and larger pages, and grows with the bitmap for
+/// smaller pages (56 bytes for 8K, 88 bytes for 4K).
+///
+/// # Examples
+///
+/// ```
+/// use idb::innodb::xdes::xdes_entry_size;
+///
+/// assert_eq!(xdes_entry_size(64), 40);
+/// assert_eq!(xdes_entry_size(256), 88);
+/// ```
+pub fn xdes_entry_size(extent_size: u32) -> usize {
+    XDES_BITMAP + (extent_size as usize * XDES_BITS_PER_PAGE).div_ceil(8)
+}
+
+/// A file address: page number and byte offset within that page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FilAddr {
+    /// Page number (FIL_NULL for a null address).
+    pub page_no: u32,
+    /// Byte offset within the page.
+    pub boffset: u16,
+}
+
+impl FilAddr {
+    /// Parse a 6-byte file address.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < FIL_ADDR_SIZE {
+            return None;
+        }
+        Some(FilAddr {
+            page_no: BigEndian::read_u32(&data[0..]),
+            boffset: BigEndian::read_u16(&data[4..]),
+        })
+    }
+
+    /// Returns true if this is the null address (end of list).
+    pub fn is_null(&self) -> bool {
+        self.page_no == FIL_NULL
+    }
+}
+
+/// A file-list base node: list length and first/last node addresses.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FlstBaseNode {
+    /// Number of nodes in the list.
+    pub len: u32,
+    /// Address of the first node.
+    pub first: FilAddr,
+    /// Address of the last node.
+    pub last: FilAddr,
+}
+
+impl FlstBaseNode {
+    /// Parse a 16-byte list base node.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < FLST_BASE_NODE_SIZE {
+            return None;
+        }
+        Some(FlstBaseNode {
+            len: BigEndian::read_u32(&data[0..]),
+            first: FilAddr::parse(&data[4..])?,
+            last: FilAddr::parse(&data[4 + FIL_ADDR_SIZE..])?,
+        })
+    }
+}
+
+/// The list base nodes stored in the FSP header on page 0.
+#[derive(Debug, Clone, Serialize)]
+pub struct FspLists {
+    /// Extents with no used pages.
+    pub free: FlstBaseNode,
+    /// Fragment extents that still have free pages.
+    pub free_frag: FlstBaseNode,
+    /// Fragment extents with every page used.
+    pub full_frag: FlstBaseNode,
+    /// Next segment ID to be assigned.
+    pub next_seg_id: u64,
+    /// INODE pages with no free inode slots.
+    pub seg_inodes_full: FlstBaseNode,
+    /// INODE pages with free inode slots.
+    pub seg_inodes_free: FlstBaseNode,
+}
+
+impl FspLists {
+    /// Parse the FSP header lists from page 0.
+    ///
+    /// `page0` should be the full page buffer; the FSP header starts at
+    /// FIL_PAGE_DATA (byte 38).
+    pub fn parse(page0: &[u8]) -> Option<Self> {
+        let base = FIL_PAGE_DATA;
+        if page0.len() < base + FSP_HEADER_SIZE {
+            return None;
+        }
+        let d = &page0[base..];
+        Some(FspLists {
+            free: FlstBaseNode::parse(&d[FSP_FREE..])?,
+            free_frag: FlstBaseNode::parse(&d[FSP_FREE_FRAG..])?,
+            full_frag: FlstBaseNode::parse(&d[FSP_FULL_FRAG..])?,
+            next_seg_id: BigEndian::read_u64(&d[FSP_SEG_ID..]),
+            seg_inodes_full: FlstBaseNode::parse(&d[FSP_SEG_INODES_FULL..])?,
+            seg_inodes_free: FlstBaseNode::parse(&d[FSP_SEG_INODES_FREE..])?,
+        })
+    }
+}
+
+/// Extent state stored in an extent descriptor.
+///
+/// # Examples
+///
+/// ```
+/// use idb::innodb::xdes::XdesState;
+///
+/// assert_eq!(XdesState::from_u32(1), XdesState::Free);
+/// assert_eq!(XdesState::from_u32(4), XdesState::Fseg);
+/// assert_eq!(XdesState::from_u32(4).name(), "FSEG");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum XdesState {
+    /// Descriptor not yet initialized (extent at or beyond the free limit).
+    NotInited,
+    /// Extent is in the FSP_FREE list.
+    Free,
+    /// Fragment extent in the FSP_FREE_FRAG list.
+    FreeFrag,
+    /// Fragment extent in the FSP_FULL_FRAG list.
+    FullFrag,
+    /// Extent belongs to a file segment.
+    Fseg,
+    /// Fragment extent partially allocated to a file segment (MySQL 8.0+).
+    FsegFrag,
+    /// Unknown state value.
+    Unknown(u32),
+}
+
+impl XdesState {
+    /// Convert a raw `XDES_STATE` value.
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            0 => XdesState::NotInited,
+            1 => XdesState::Free,
+            2 => XdesState::FreeFrag,
+            3 => XdesState::FullFrag,
+            4 => XdesState::Fseg,
+            5 => XdesState::FsegFrag,
+            v => XdesState::Unknown(v),
+        }
+    }
+
+    /// Returns the MySQL source-style name (without the `XDES_` prefix).
+    pub fn name(&self) -> &'static str {
+        match self {
+            XdesState::NotInited => "NOT_INITED",
+            XdesState::Free => "FREE",
+            XdesState::FreeFrag => "FREE_FRAG",
+            XdesState::FullFrag => "FULL_FRAG",
+            XdesState::Fseg => "FSEG",
+            XdesState::FsegFrag => "FSEG_FRAG",
+            XdesState::Unknown(_) => "UNKNOWN",
+        }
+    }
+}
+
+/// A decoded extent descriptor.
+#[derive(Debug, Clone, Serialize)]
+pub struct XdesEntry {
+    /// Extent number (first page / extent size).
+    pub extent_no: u32,
+    /// First page number of the extent.
+    pub first_page: u32,
+    /// ID of the owning segment (0 if none).
+    pub segment_id: u64,
+    /// Extent state.
+    pub state: XdesState,
+    /// Previous node in the list this extent is on.
+    pub prev: FilAddr,
+    /// Next node in the list this extent is on.
+    pub next: FilAddr,
+    /// Number of pages marked used in the bitmap.
+    pub n_used_pages: u32,
+    /// Per-page free bit, indexed by page within the extent.
+    #[serde(skip)]
+    pub free_pages: Vec<bool>,
+}
+
+impl XdesEntry {
+    /// Parse one extent descriptor.
+    ///
+    /// `data` starts at the descriptor and must hold [`xdes_entry_size`]
+    /// bytes; `first_page` is the first page of the extent it describes.
+    pub fn parse(data: &[u8], first_page: u32, extent_size: u32) -> Option<Self> {
+        if data.len() < xdes_entry_size(extent_size) {
+            return None;
+        }
+        let bitmap = &data[XDES_BITMAP..];
+        let free_pages: Vec<bool> = (0..extent_size as usize)
+            .map(|i| {
+                let bit = i * XDES_BITS_PER_PAGE + XDES_FREE_BIT;
+                bitmap[bit / 8] & (1 << (bit % 8)) != 0
+            })
+            .collect();
+        Some(XdesEntry {
+            extent_no: first_page / extent_size,
+            first_page,
+            segment_id: BigEndian::read_u64(&data[XDES_ID..]),
+            state: XdesState::from_u32(BigEndian::read_u32(&data[XDES_STATE..])),
+            prev: FilAddr::parse(&data[XDES_FLST_NODE..])?,
+            next: FilAddr::parse(&data[XDES_FLST_NODE + FIL_ADDR_SIZE..])?,
+            n_used_pages: free_pages.iter().filter(|&&f| !f).count() as u32,
+            free_pages,
+        })
+    }
+
+    /// Returns true if the page at `index` within the extent is marked free.
+    pub fn is_page_free(&self, index: usize) -> bool {
+        self.free_pages.get(index).copied().unwrap_or(true)
+    }
+
+    /// One character per page: `#` for used and `.` for free.
+    pub fn page_map(&self) -> String {
+        self.free_pages
+            .iter()
+            .map(|&f| if f { '.' } else { '#' })
+            .collect()
+    }
+}
+
+/// Allocation state of a single page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PageAllocState {
+    /// Marked used in its extent descriptor.
+    Used,
+    /// Marked free (or in an extent on the FSP_FREE list).
+    Free,
+    /// At or beyond the free limit, or in an extent whose descriptor is not
+    /// initialized: the page has never been handed out.
+    Uninitialized,
+}
+
+/// Result of walking one file list.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ListWalk {
+    /// List name (e.g. `FSP_FREE`).
+    pub name: String,
+    /// Length recorded in the base node.
+    pub base_len: u32,
+    /// Extent numbers (XDES lists) or page numbers (INODE lists) in list order.
+    pub members: Vec<u32>,
+    /// Problems found while walking (broken links, cycles, length mismatch).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Decoded extent descriptors and FSP lists for a whole tablespace.
+#[derive(Debug, Clone, Serialize)]
+pub struct AllocationMap {
+    /// Physical page size (also the spacing of XDES pages, in pages).
+    pub page_size: u32,
+    /// Pages per extent.
+    pub extent_size: u32,
+    /// Tablespace size in pages (FSP_SIZE).
+    pub size: u32,
+    /// First page not yet initialized (FSP_FREE_LIMIT).
+    pub free_limit: u32,
+    /// FSP header list base nodes.
+    pub lists: FspLists,
+    /// Extent descriptors, indexed by extent number.
+    pub extents: Vec<XdesEntry>,
+    /// FSP_FREE list walk.
+    pub free: ListWalk,
+    /// FSP_FREE_FRAG list walk.
+    pub free_frag: ListWalk,
+    /// FSP_FULL_FRAG list walk.
+    pub full_frag: ListWalk,
+    /// FSP_SEG_INODES_FULL list walk (INODE page numbers).
+    pub seg_inodes_full: ListWalk,
+    /// FSP_SEG_INODES_FREE list walk (INODE page numbers).
+    pub seg_inodes_free: ListWalk,
+    /// Raw page 0 and XDES pages, for resolving extent list nodes.
+    #[serde(skip)]
+    xdes_pages: HashMap<u32, Vec<u8>>,
+}
+
+/// Page and extent counts derived from an [`AllocationMap`].
+#[derive(Debug, Clone, Serialize)]
+pub struct AllocationSummary {
+    /// Pages per extent.
+    pub extent_size: u32,
+    /// Tablespace size in pages (FSP_SIZE).
+    pub size: u32,
+    /// First page not yet initialized (FSP_FREE_LIMIT).
+    pub free_limit: u32,
+    /// Extents with an initialized descriptor.
+    pub initialized_extents: u64,
+    /// Extents on the FSP_FREE list.
+    pub free_extents: u64,
+    /// Extents on the FSP_FREE_FRAG list.
+    pub free_frag_extents: u64,
+    /// Extents on the FSP_FULL_FRAG list.
+    pub full_frag_extents: u64,
+    /// Extents owned by file segments.
+    pub segment_extents: u64,
+    /// INODE pages on the FSP_SEG_INODES_FULL and FSP_SEG_INODES_FREE lists.
+    pub inode_pages: u64,
+    /// Pages marked used.
+    pub used_pages: u64,
+    /// Pages marked free below the free limit.
+    pub free_pages: u64,
+    /// Pages never initialized (at or beyond the free limit).
+    pub uninitialized_pages: u64,
+    /// Pages marked used whose contents are still empty.
+    pub allocated_empty_pages: u64,
+    /// Pages marked free that still contain data.
+    pub free_pages_with_data: u64,
+    /// Problems found while walking the FSP lists.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub list_errors: Vec<String>,
+}
+
+impl AllocationMap {
+    /// Allocation state of a page.
+    pub fn page_state(&self, page_no: u32) -> PageAllocState {
+        if page_no >= self.free_limit {
+            return PageAllocState::Uninitialized;
+        }
+        let entry = match self.extents.get((page_no / self.extent_size) as usize) {
+            Some(e) => e,
+            None => return PageAllocState::Uninitialized,
+        };
+        match entry.state {
+            XdesState::NotInited => PageAllocState::Uninitialized,
+            XdesState::Free => PageAllocState::Free,
+            _ if entry.is_page_free((page_no % self.extent_size) as usize) => PageAllocState::Free,
+            _ => PageAllocState::Used,
+        }
+    }
+
+    /// Extent number of the descriptor holding the list node at `addr`.
+    ///
+    /// Returns `None` if the address is not the list node of a descriptor
+    /// on page 0 or an XDES page.
+    pub fn extent_of_node(&self, addr: FilAddr) -> Option<u32> {
+        let entry_size = xdes_entry_size(self.extent_size);
+        if !addr.page_no.is_multiple_of(self.page_size) {
+            return None;
+        }
+        let rel = (addr.boffset as usize).checked_sub(XDES_ARR_OFFSET + XDES_FLST_NODE)?;
+        if !rel.is_multiple_of(entry_size)
+            || rel / entry_size >= (self.page_size / self.extent_size) as usize
+        {
+            return None;
+        }
+        Some(addr.page_no / self.extent_size + (rel / entry_size) as u32)
+    }
+
+    /// Walk a list of extents (an FSP list or a segment's FREE, NOT_FULL,
+    /// or FULL list), returning extent numbers in list order.
+    pub fn walk_extent_list(&self, name: &str, base: &FlstBaseNode) -> ListWalk {
+        walk_list(name, base, |addr| {
+            let extent_no = self.extent_of_node(addr)?;
+            let page = self.xdes_pages.get(&addr.page_no)?;
+            let next = FilAddr::parse(page.get(addr.boffset as usize + FIL_ADDR_SIZE..)?)?;
+            Some((extent_no, next))
+        })
+    }
+
+    /// Iterate over the list walks in FSP header order.
+    pub fn list_walks(&self) -> [&ListWalk; 5] {
+        [
+            &self.free,
+            &self.free_frag,
+            &self.full_frag,
+            &self.seg_inodes_full,
+            &self.seg_inodes_free,
+        ]
+    }
+
+    /// Summarize the map over `page_count` pages.
+    ///
+    /// `empty_pages` holds the numbers of pages whose contents are all
+    /// zero; pages marked used that are empty are counted as allocated but
+    /// unused, and pages marked free that are not empty as free with data.
+    pub fn summary(&self, page_count: u64, empty_pages: &HashSet<u64>) -> AllocationSummary {
+        let mut summary = AllocationSummary {
+            extent_size: self.extent_size,
+            size: self.size,
+            free_limit: self.free_limit,
+            initialized_extents: 0,
+            free_extents: self.free.members.len() as u64,
+            free_frag_extents: self.free_frag.members.len() as u64,
+            full_frag_extents: self.full_frag.members.len() as u64,
+            segment_extents: 0,
+            inode_pages: (self.seg_inodes_full.members.len() + self.seg_inodes_free.members.len())
+                as u64,
+            used_pages: 0,
+            free_pages: 0,
+            uninitialized_pages: 0,
+            allocated_empty_pages: 0,
+            free_pages_with_data: 0,
+            list_errors: self
+                .list_walks()
+                .iter()
+                .flat_map(|w| w.errors.iter().map(move |e| format!("{}: {}", w.name, e)))
+                .collect(),
+        };
+        for e in &self.extents {
+            match e.state {
+                XdesState::NotInited => continue,
+                XdesState::Fseg | XdesState::FsegFrag => summary.segment_extents += 1,
+                _ => {}
+            }
+            summary.initialized_extents += 1;
+        }
+        for page_no in 0..page_count {
+            let empty = empty_pages.contains(&page_no);
+            match self.page_state(page_no as u32) {
+                PageAllocState::Used => {
+                    summary.used_pages += 1;
+                    if empty {
+                        summary.allocated_empty_pages += 1;
+                    }
+                }
+                PageAllocState::Free => {
+                    summary.free_pages += 1;
+                    if !empty {
+                        summary.free_pages_with_data += 1;
+                    }
+                }
+                PageAllocState::Uninitialized => summary.uninitialized_pages += 1,
+            }
+        }
+        summary
+    }
+}
+
+/// Decode every extent descriptor on page 0 or an XDES page.
+///
+/// `page_no` is the page's own number (a multiple of `page_size`) and
+/// `page_size` the physical page size, which is also the number of pages
+/// the array describes. Descriptors are returned in extent order; callers
+/// typically skip those in the [`XdesState::NotInited`] state.
+///
+/// # Examples
+///
+/// ```
+/// use idb::innodb::xdes::{parse_xdes_page, XdesState};
+///
+/// let page = vec![0u8; 16384];
+/// let entries = parse_xdes_page(&page, 0, 16384, 64);
+/// assert_eq!(entries.len(), 256);
+/// assert_eq!(entries[1].first_page, 64);
+/// assert_eq!(entries[0].state, XdesState::NotInited);
+/// ```
+pub fn parse_xdes_page(
+    page_data: &[u8],
+    page_no: u32,
+    page_size: u32,
+    extent_size: u32,
+) -> Vec<XdesEntry> {
+    let entry_size = xdes_entry_size(extent_size);
+    (0..page_size / extent_size)
+        .map_while(|i| {
+            let off = XDES_ARR_OFFSET + i as usize * entry_size;
+            XdesEntry::parse(
+                page_data.get(off..)?,
+                page_no + i * extent_size,
+                extent_size,
+            )
+        })
+        .collect()
+}
+
+/// Decode the extent descriptors and FSP lists of a tablespace.
+///
+/// Reads page 0 and every XDES page below the tablespace size, decodes each
+/// initialized descriptor, and walks the FSP_FREE, FSP_FREE_FRAG,
+/// FSP_FULL_FRAG, FSP_SEG_INODES_FULL and FSP_SEG_INODES_FREE lists.
+///
+/// # Errors
+///
+/// Returns `IdbError::Parse` if page 0 has no valid FSP header, or an I/O
+/// error if page 0 cannot be read. Unreadable XDES or INODE pages are
+/// reported as list errors instead.
+pub fn read_allocation_map(ts: &mut Tablespace) -> Result<AllocationMap, IdbError> {
+    let page_size = ts.page_size();
+    let extent = extent_size(ts.logical_page_size());
+    let page_count = ts.page_count();
+    let page0 = ts.read_page(0)?;
+    build_allocation_map(&page0, page_size, extent, page_count, |p| {
+        ts.read_page(p as u64).ok()
+    })
+}
+
+/// Build an [`AllocationMap`] from page 0 and a page reader.
+///
+/// `page_size` is the physical page size, `extent_size` the number of pages
+/// per extent (see [`extent_size`]), and `read_page` returns the contents
+/// of a page by number (or `None` if it cannot be read). This is the
+/// storage-independent form of [`read_allocation_map`].
+pub fn build_allocation_map(
+    page0: &[u8],
+    page_size: u32,
+    extent_size: u32,
+    page_count: u64,
+    mut read_page: impl FnMut(u32) -> Option<Vec<u8>>,
+) -> Result<AllocationMap, IdbError> {
+    let fsp = FspHeader::parse(page0)
+        .ok_or_else(|| IdbError::Parse("cannot parse FSP header on page 0".to_string()))?;
+    let lists = FspLists::parse(page0)
+        .ok_or_else(|| IdbError::Parse("cannot parse FSP lists on page 0".to_string()))?;
+
+    let extents_per_xdes = (page_size / extent_size) as usize;
+    let n_pages = u64::from(fsp.size).min(page_count) as u32;
+
+    // Descriptor pages, kept for list walking
+    let mut xdes_pages: HashMap<u32, Vec<u8>> = HashMap::new();
+    let mut extents = Vec::new();
+    let mut xdes_page_no = 0u32;
+    while xdes_page_no < n_pages.max(1) {
+        let data = if xdes_page_no == 0 {
+            Some(page0.to_vec())
+        } else {
+            read_page(xdes_page_no)
+        };
+        let parsed = data
+            .as_deref()
+            .map(|d| parse_xdes_page(d, xdes_page_no, page_size, extent_size))
+            .unwrap_or_default();
+        for i in 0..extents_per_xdes {
+            let first_page = xdes_page_no + (i as u32) * extent_size;
+            if first_page >= n_pages.max(1) {
+                break;
+            }
+            let entry = parsed.get(i).cloned().unwrap_or_else(|| XdesEntry {
+                extent_no: first_page / extent_size,
+                first_page,
+                segment_id: 0,
+                state: XdesState::NotInited,
+                prev: FilAddr {
+                    page_no: FIL_NULL,
+                    boffset: 0,
+                },
+                next: FilAddr {
+                    page_no: FIL_NULL,
+                    boffset: 0,
+                },
+                n_used_pages: 0,
+                free_pages: vec![true; extent_size as usize],
+            });
+            extents.push(entry);
+        }
+        if let Some(d) = data {
+            xdes_pages.insert(xdes_page_no, d);
+        }
+        xdes_page_no = match xdes_page_no.checked_add(page_size) {
+            Some(p) => p,
+            None => break,
+        };
+    }
+
+    let mut map = AllocationMap {
+        page_size,
+        extent_size,
+        size: fsp.size,
+        free_limit: fsp.free_limit,
+        lists,
+        extents,
+        free: ListWalk::default(),
+        free_frag: ListWalk::default(),
+        full_frag: ListWalk::default(),
+        seg_inodes_full: ListWalk::default(),
+        seg_inodes_free: ListWalk::default(),
+        xdes_pages,
+    };
+    map.free = map
+        .walk_extent_list("FSP_FREE", &map.lists.free)
+        .check_states(&map.extents, XdesState::Free);
+    map.free_frag = map
+        .walk_extent_list("FSP_FREE_FRAG", &map.lists.free_frag)
+        .check_states(&map.extents, XdesState::FreeFrag);
+    map.full_frag = map
+        .walk_extent_list("FSP_FULL_FRAG", &map.lists.full_frag)
+        .check_states(&map.extents, XdesState::FullFrag);
+
+    // INODE pages are linked through the list node at FIL_PAGE_DATA
+    let mut walk_inodes = |name: &str, base: &FlstBaseNode| {
+        walk_list(name, base, |addr| {
+            if addr.boffset as usize != FIL_PAGE_DATA {
+                return None;
+            }
+            let page = read_page(addr.page_no)?;
+            let next = FilAddr::parse(page.get(FIL_PAGE_DATA + FIL_ADDR_SIZE..)?)?;
+            Some((addr.page_no, next))
+        })
+    };
+    map.seg_inodes_full = walk_inodes("FSP_SEG_INODES_FULL", &map.lists.seg_inodes_full);
+    map.seg_inodes_free = walk_inodes("FSP_SEG_INODES_FREE", &map.lists.seg_inodes_free);
+
+    Ok(map)
+}
+
+/// Walk a file list from its base node.
+///
+/// `node` resolves a node address to the member it identifies and the
+/// address of the next node, or `None` if the address is invalid.
+fn walk_list(
+    name: &str,
+    base: &FlstBaseNode,
+    mut node: impl FnMut(FilAddr) -> Option<(u32, FilAddr)>,
+) -> ListWalk {
+    let mut walk = ListWalk {
+        name: name.to_string(),
+        base_len: base.len,
+        members: Vec::new(),
+        errors: Vec::new(),
+    };
+    // Empty lists are not followed: InnoDB sets the first address to
+    // FIL_NULL, but zero-filled headers leave it at page 0
+    if base.len == 0 {
+        return walk;
+    }
+    let mut seen = HashSet::new();
+    let mut addr = base.first;
+    while !addr.is_null() {
+        if !seen.insert((addr.page_no, addr.boffset)) || walk.members.len() >= MAX_LIST_NODES {
+            walk.errors.push(format!(
+                "cycle at page {} offset {}",
+                addr.page_no, addr.boffset
+            ));
+            break;
+        }
+        match node(addr) {
+            Some((member, next)) => {
+                walk.members.push(member);
+                addr = next;
+            }
+            None => {
+                walk.errors.push(format!(
+                    "invalid node address page {} offset {}",
+                    addr.page_no, addr.boffset
+                ));
+                break;
+            }
+        }
+    }
+    if walk.errors.is_empty() && walk.members.len() != base.len as usize {
+        walk.errors.push(format!(
+            "base node length {} but {} nodes linked",
+            base.len,
+            walk.members.len()
+        ));
+    }
+    walk
+}
+
+impl ListWalk {
+    /// Record list members whose descriptor state does not match the list.
+    pub(crate) fn check_states(mut self, extents: &[XdesEntry], expected: XdesState) -> Self {
+        for &extent_no in &self.members {
+            if let Some(e) = extents.get(extent_no as usize) {
+                if e.state != expected {
+                    self.errors.push(format!(
+                        "extent {} has state {}, expected {}",
+                        extent_no,
+                        e.state.name(),
+                        expected.name()
+                    ));
+                }
+            }
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PS: u32 = SIZE_PAGE_16K;
+
+    fn write_addr(buf: &mut [u8], page_no: u32, boffset: u16) {
+        BigEndian::write_u32(&mut buf[0..], page_no);
+        BigEndian::write_u16(&mut buf[4..], boffset);
+    }
+
+    fn node_offset(index: usize) -> u16 {
+        (XDES_ARR_OFFSET + index * XDES_SIZE + XDES_FLST_NODE) as u16
+    }
+
+    /// Page 0 with three extents: 0 is FREE_FRAG with pages 0-3 used,
+    /// 1 is FSEG with every page used, 2 is FREE.
+    fn build_page0() -> Vec<u8> {
+        let mut page = vec![0u8; PS as usize];
+        let fsp = FIL_PAGE_DATA;
+        BigEndian::write_u32(&mut page[fsp + FSP_SIZE..], 192);
+        BigEndian::write_u32(&mut page[fsp + FSP_FREE_LIMIT..], 192);
+        BigEndian::write_u32(&mut page[fsp + FSP_FRAG_N_USED..], 4);
+        for list in [
+            FSP_FREE,
+            FSP_FREE_FRAG,
+            FSP_FULL_FRAG,
+            FSP_SEG_INODES_FULL,
+            FSP_SEG_INODES_FREE,
+        ] {
+            write_addr(&mut page[fsp + list + 4..], FIL_NULL, 0);
+            write_addr(&mut page[fsp + list + 10..], FIL_NULL, 0);
+        }
+        // FSP_FREE_FRAG -> extent 0, FSP_FREE -> extent 2
+        BigEndian::write_u32(&mut page[fsp + FSP_FREE_FRAG..], 1);
+        write_addr(&mut page[fsp + FSP_FREE_FRAG + 4..], 0, node_offset(0));
+        write_addr(&mut page[fsp + FSP_FREE_FRAG + 10..], 0, node_offset(0));
+        BigEndian::write_u32(&mut page[fsp + FSP_FREE..], 1);
+        write_addr(&mut page[fsp + FSP_FREE + 4..], 0, node_offset(2));
+        write_addr(&mut page[fsp + FSP_FREE + 10..], 0, node_offset(2));
+
+        for (i, state, used) in [(0usize, 2u32, 4usize), (1, 4, 64), (2, 1, 0)] {
+            let e = XDES_ARR_OFFSET + i * XDES_SIZE;
+            if state == 4 {
+                BigEndian::write_u64(&mut page[e + XDES_ID..], 7);
+            }
+            write_addr(&mut page[e + XDES_FLST_NODE..], FIL_NULL, 0);
+            write_addr(&mut page[e + XDES_FLST_NODE + 6..], FIL_NULL, 0);
+            BigEndian::write_u32(&mut page[e + XDES_STATE..], state);
+            // Free and clean bits set for every page, then clear free for used pages
+            for b in &mut page[e + XDES_BITMAP..e + XDES_SIZE] {
+                *b = 0xFF;
+            }
+            for p in 0..used {
+                let bit = p * XDES_BITS_PER_PAGE + XDES_FREE_BIT;
+                page[e + XDES_BITMAP + bit / 8] &= !(1 << (bit % 8));
+            }
+        }
+        page
+    }
+
+    #[test]
+    fn test_xdes_entry_parse() {
+        let page = build_page0();
+        let e = XdesEntry::parse(&page[XDES_ARR_OFFSET..], 0, 64).unwrap();
+        assert_eq!(e.state, XdesState::FreeFrag);
+        assert_eq!(e.n_used_pages, 4);
+        assert!(!e.is_page_free(3));
+        assert!(e.is_page_free(4));
+        assert!(e.page_map().starts_with("####."));
+        assert!(e.prev.is_null());
+
+        let e = XdesEntry::parse(&page[XDES_ARR_OFFSET + XDES_SIZE..], 64, 64).unwrap();
+        assert_eq!(e.extent_no, 1);
+        assert_eq!(e.segment_id, 7);
+        assert_eq!(e.n_used_pages, 64);
+    }
+
+    #[test]
+    fn test_allocation_map() {
+        let page0 = build_page0();
+        let map = build_allocation_map(&page0, PS, 64, 200, |_| None).unwrap();
+
+        assert_eq!(map.extents.len(), 3);
+        assert_eq!(map.free.members, vec![2]);
+        assert_eq!(map.free_frag.members, vec![0]);
+        assert!(map.full_frag.members.is_empty());
+        assert!(map.list_walks().iter().all(|w| w.errors.is_empty()));
+
+        assert_eq!(map.page_state(2), PageAllocState::Used);
+        assert_eq!(map.page_state(10), PageAllocState::Free);
+        assert_eq!(map.page_state(100), PageAllocState::Used);
+        assert_eq!(map.page_state(130), PageAllocState::Free);
+        assert_eq!(map.page_state(195), PageAllocState::Uninitialized);
+
+        // Page 3 is marked used but empty; page 5 is free but holds data
+        let empty: HashSet<u64> = (0..200u64).filter(|&p| p != 5 && p != 2).collect();
+        let s = map.summary(200, &empty);
+        assert_eq!(s.used_pages, 68);
+        assert_eq!(s.free_pages, 124);
+        assert_eq!(s.uninitialized_pages, 8);
+        assert_eq!(s.allocated_empty_pages, 67);
+        assert_eq!(s.free_pages_with_data, 1);
+        assert_eq!(s.segment_extents, 1);
+        assert_eq!(s.initialized_extents, 3);
+    }
+
+    #[test]
+    fn test_list_walk_errors() {
+        let mut page0 = build_page0();
+        // Claim two FREE extents and point the list at the FSEG extent
+        let fsp = FIL_PAGE_DATA;
+        BigEndian::write_u32(&mut page0[fsp + FSP_FREE..], 2);
+        write_addr(&mut page0[fsp + FSP_FREE + 4..], 0, node_offset(1));
+        let map = build_allocation_map(&page0, PS, 64, 200, |_| None).unwrap();
+        assert_eq!(map.free.members, vec![1]);
+        assert_eq!(map.free.errors.len(), 2);
+
+        // A node address that is not inside a descriptor
+        write_addr(&mut page0[fsp + FSP_FREE + 4..], 0, 151);
+        let map = build_allocation_map(&page0, PS, 64, 200, |_| None).unwrap();
+        assert!(map.free.errors[0].contains("invalid node address"));
+        let s = map.summary(200, &HashSet::new());
+        assert!(s.list_errors[0].starts_with("FSP_FREE: "));
+    }
+}
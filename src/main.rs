@@ -260,6 +260,7 @@ fn main() {
             no_empty,
             verbose,
             json,
+            keyring,
         } => cli::log::execute(
             &cli::log::LogOptions {
                 file,
@@ -267,6 +268,7 @@ fn main() {
                 no_empty,
                 verbose,
                 json,
+                keyring,
             },
             &mut writer,
         ),
@@ -460,6 +462,31 @@ fn main() {
             &mut writer,
         ),
 
+        Commands::Doublewrite {
+            file,
+            dblwr,
+            ibdata,
+            restore,
+            no_backup,
+            verbose,
+            json,
+            page_size,
+        } => cli::doublewrite::execute(
+            &cli::doublewrite::DoublewriteOptions {
+                file,
+                dblwr,
+                ibdata,
+                restore,
+                no_backup,
+                verbose,
+                json,
+                page_size,
+                mmap: cli.mmap,
+                audit_logger: audit_logger.clone(),
+            },
+            &mut writer,
+        ),
+
         Commands::Health {
             file,
             verbose,
@@ -496,6 +523,8 @@ fn main() {
             verbose,
             page_size,
             keyring,
+            max_lob_size,
+            index,
         } => cli::export::execute(
             &cli::export::ExportOptions {
                 file,
@@ -507,6 +536,8 @@ fn main() {
                 page_size,
                 keyring,
                 mmap: cli.mmap,
+                max_lob_size,
+                index,
             },
             &mut writer,
         ),
@@ -595,6 +626,54 @@ fn main() {
             &mut writer,
         ),
 
+        Commands::RebuildIndex {
+            file,
+            index,
+            output,
+            verbose,
+            json,
+            page_size,
+        } => cli::rebuild_index::execute(
+            &cli::rebuild_index::RebuildIndexOptions {
+                file,
+                index,
+                output,
+                verbose,
+                json,
+                page_size,
+                mmap: cli.mmap,
+                audit_logger: audit_logger.clone(),
+            },
+            &mut writer,
+        ),
+
+        Commands::RedoApply {
+            datadir,
+            file,
+            redo,
+            output_dir,
+            to_lsn,
+            verbose,
+            json,
+            page_size,
+            depth,
+        } => cli::redo_apply::execute(
+            &cli::redo_apply::RedoApplyOptions {
+                datadir,
+                files: file,
+                redo,
+                output_dir,
+                to_lsn,
+                verbose,
+                json: json || global_format == OutputFormat::Json,
+                page_size,
+                depth,
+                mmap: cli.mmap,
+                audit_logger: audit_logger.clone(),
+            },
+            &mut writer,
+        ),
+
         Commands::Validate {
             datadir,
             database,
@@ -636,6 +715,8 @@ fn main() {
             redo,
             chain,
             backup_meta,
+            spatial,
+            indexes,
         } => cli::verify::execute(
             &cli::verify::VerifyOptions {
                 file,
@@ -647,6 +728,8 @@ fn main() {
                 redo,
                 chain,
                 backup_meta,
+                spatial,
+                indexes,
             },
             &mut writer,
         ),
@@ -704,6 +787,7 @@ fn main() {
             json,
             page_size,
             keyring,
+            max_lob_size,
         } => cli::undelete::execute(
             &cli::undelete::UndeleteOptions {
                 file,
@@ -718,6 +802,7 @@ fn main() {
                 page_size,
                 keyring,
                 mmap: cli.mmap,
+                max_lob_size,
             },
             &mut writer,
         ),
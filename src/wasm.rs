@@ -13,8 +13,9 @@ use crate::innodb::index::IndexHeader;
 use crate::innodb::lob::{BlobPageHeader, LobFirstPageHeader};
 use crate::innodb::log::{
     validate_log_block_checksum, LogBlockHeader, LogCheckpoint, LogFile, LogFileHeader,
-    MlogRecordType, LOG_BLOCK_HDR_SIZE, LOG_BLOCK_SIZE, LOG_FILE_HDR_BLOCKS,
+    LOG_FILE_HDR_BLOCKS,
 };
+use crate::innodb::mlog::{decode_mlog_stream, read_log_streams};
 use crate::innodb::page::{FilHeader, FspHeader};
 use crate::innodb::page_types::PageType;
 use crate::innodb::record::{walk_compact_records, walk_redundant_records};
@@ -891,6 +892,8 @@ struct IndexRecordReport {
     n_recs: u16,
     is_compact: bool,
     records: Vec<RecordDetail>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    node_pointers: Option<Vec<crate::innodb::btree::NodePointer>>,
 }
 
 /// Inspects records on an INDEX page, returning record headers and raw hex snippets.
@@ -905,12 +908,17 @@ struct IndexRecordReport {
 /// `next_offset` (i16, relative), and `raw_hex` (string, hex encoding of the
 /// first 20 bytes at the record origin).
 ///
+/// For non-leaf pages whose index is defined in SDI, a `node_pointers` array
+/// is included with each node pointer's `offset`, `min_rec`, `delete_mark`,
+/// decoded `key` (array of `[column_name, value]` pairs), and
+/// `child_page_no`.
+///
 /// Returns an error string if the page is not an INDEX page or the input is
 /// not a valid InnoDB tablespace.
 #[wasm_bindgen]
 pub fn inspect_index_records(data: &[u8], page_num: u64) -> Result<String, JsValue> {
     let mut ts = Tablespace::from_bytes(data.to_vec()).map_err(to_js_err)?;
-    let page_data = ts.read_page(page_num).map_err(to_js_err)?;
+    let page_data = ts.read_page_uncompressed(page_num).map_err(to_js_err)?;
 
     let hdr =
         FilHeader::parse(&page_data).ok_or_else(|| JsValue::from_str("Cannot parse FIL header"))?;
@@ -955,6 +963,18 @@ pub fn inspect_index_records(data: &[u8], page_num: u64) -> Result<String, JsVal
         })
         .collect();
 
+    let node_pointers = if idx_hdr.is_leaf() {
+        None
+    } else {
+        crate::innodb::export::extract_index_layouts(&mut ts).and_then(|layouts| {
+            let layout = layouts.iter().find(|l| l.index_id == idx_hdr.index_id)?;
+            Some(crate::innodb::btree::decode_node_pointers(
+                &page_data,
+                layout.node_pointer_columns(),
+            ))
+        })
+    };
+
     let report = IndexRecordReport {
         page_number: page_num,
         index_id: idx_hdr.index_id,
@@ -962,6 +982,7 @@ pub fn inspect_index_records(data: &[u8], page_num: u64) -> Result<String, JsVal
         n_recs: idx_hdr.n_recs,
         is_compact: idx_hdr.is_compact(),
         records,
+        node_pointers,
     };
     to_json(&report)
 }
@@ -1038,8 +1059,8 @@ struct RedoBlock {
 ///
 /// Takes raw redo log file bytes (typically `ib_logfile0` or `#ib_redo*`
 /// files) and parses the file header, both checkpoint slots, and every
-/// 512-byte log block. For each block that contains data, the first
-/// mini-transaction record type is identified and included.
+/// 512-byte log block. The redo record stream is decoded across block
+/// boundaries and each block lists the types of the records that start in it.
 ///
 /// Returns a JSON string containing an object with fields: `file_size`
 /// (u64), `total_blocks` (u64, including header blocks), `data_blocks`
@@ -1061,6 +1082,17 @@ pub fn parse_redo_log(data: &[u8]) -> Result<String, JsValue> {
     let cp1 = log.read_checkpoint(0).ok();
     let cp2 = log.read_checkpoint(1).ok();
 
+    let mut types_by_block: std::collections::BTreeMap<u64, Vec<String>> =
+        std::collections::BTreeMap::new();
+    for stream in read_log_streams(&mut log).unwrap_or_default() {
+        for rec in decode_mlog_stream(&stream).records {
+            types_by_block
+                .entry(stream.block_index_of_lsn(rec.start_lsn))
+                .or_default()
+                .push(rec.record_type.to_string());
+        }
+    }
+
     let mut blocks = Vec::new();
     for i in 0..log.data_block_count() {
         let block_index = LOG_FILE_HDR_BLOCKS + i;
@@ -1071,14 +1103,7 @@ pub fn parse_redo_log(data: &[u8]) -> Result<String, JsValue> {
         };
         let cksum_ok = validate_log_block_checksum(&block_data);
 
-        let mut record_types = Vec::new();
-        if bhdr.has_data() {
-            let data_end = std::cmp::min(bhdr.data_len as usize, LOG_BLOCK_SIZE - 4);
-            if LOG_BLOCK_HDR_SIZE < data_end {
-                let rec_type = MlogRecordType::from_u8(block_data[LOG_BLOCK_HDR_SIZE]);
-                record_types.push(rec_type.to_string());
-            }
-        }
+        let record_types = types_by_block.remove(&block_index).unwrap_or_default();
 
         blocks.push(RedoBlock {
             block_index,
@@ -1248,7 +1273,6 @@ pub fn analyze_health_extended(
                         leaves,
                         &columns,
                         &col_name,
-                        page_size,
                         sample_size as usize,
                     );
                 }
@@ -1346,10 +1370,12 @@ pub fn export_records(
     where_delete_mark: bool,
     system_columns: bool,
 ) -> Result<String, JsValue> {
-    use crate::innodb::export::{decode_page_records, extract_column_layout, extract_table_name};
+    use crate::innodb::export::{
+        decode_page_records_with_lobs, extract_column_layout, extract_table_name,
+        DEFAULT_MAX_LOB_SIZE,
+    };
 
     let mut ts = Tablespace::from_bytes(data.to_vec()).map_err(to_js_err)?;
-    let page_size = ts.page_size();
 
     // Extract table name
     let table_name = extract_table_name(&mut ts).unwrap_or_else(|| "unknown".to_string());
@@ -1375,7 +1401,11 @@ pub fn export_records(
         Some(page_num as u64)
     };
 
-    ts.for_each_page(|pn, pdata| {
+    ts.for_each_page_uncompressed(|pn, pdata| {
+        let pdata = match pdata {
+            Ok(d) => d,
+            Err(_) => return Ok(()),
+        };
         if let Some(specific) = target_page {
             if pn != specific {
                 return Ok(());
@@ -1407,12 +1437,13 @@ pub fn export_records(
     let mut all_rows: Vec<Vec<serde_json::Value>> = Vec::new();
 
     for (_, page_data) in &pages_data {
-        let rows = decode_page_records(
+        let rows = decode_page_records_with_lobs(
+            &mut ts,
             page_data,
             &columns,
             where_delete_mark,
             system_columns,
-            page_size,
+            DEFAULT_MAX_LOB_SIZE,
         );
         for row in rows {
             let json_row: Vec<serde_json::Value> = row
@@ -1437,6 +1468,8 @@ pub fn export_records(
                     }
                     crate::innodb::field_decode::FieldValue::Str(s) => serde_json::Value::String(s),
                     crate::innodb::field_decode::FieldValue::Hex(h) => serde_json::Value::String(h),
+                    crate::innodb::field_decode::FieldValue::Json(v) => v,
+                    crate::innodb::field_decode::FieldValue::Geometry(g) => g.geometry.to_geojson(),
                 })
                 .collect();
             all_rows.push(json_row);
@@ -24,6 +24,7 @@ fn build_fsp_hdr_page(space_id: u32, total_pages: u32) -> Vec<u8> {
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_index_page(
     page_num: u32,
     space_id: u32,
@@ -146,6 +146,7 @@ fn test_compat_mariadb_vendor_error() {
         mysql_version_id: None,
         has_compressed_pages: false,
         has_instant_columns: false,
+        ibuf_buffered_pages: 0,
     };
     let target = MysqlVersion::parse("8.4.0").unwrap();
     let checks = check_compatibility(&info, &target);
@@ -172,6 +173,7 @@ fn test_compat_no_sdi_error_for_80_target() {
         mysql_version_id: None,
         has_compressed_pages: false,
         has_instant_columns: false,
+        ibuf_buffered_pages: 0,
     };
     let target = MysqlVersion::parse("8.0.0").unwrap();
     let checks = check_compatibility(&info, &target);
@@ -197,6 +199,7 @@ fn test_compat_no_sdi_ok_for_57_target() {
         mysql_version_id: None,
         has_compressed_pages: false,
         has_instant_columns: false,
+        ibuf_buffered_pages: 0,
     };
     let target = MysqlVersion::parse("5.7.44").unwrap();
     let checks = check_compatibility(&info, &target);
@@ -219,6 +222,7 @@ fn test_compat_compressed_row_format_warning() {
         mysql_version_id: Some(80032),
         has_compressed_pages: false,
         has_instant_columns: false,
+        ibuf_buffered_pages: 0,
     };
     let target = MysqlVersion::parse("8.4.0").unwrap();
     let checks = check_compatibility(&info, &target);
@@ -244,6 +248,7 @@ fn test_compat_redundant_row_format_warning_90() {
         mysql_version_id: Some(80032),
         has_compressed_pages: false,
         has_instant_columns: false,
+        ibuf_buffered_pages: 0,
     };
     let target = MysqlVersion::parse("9.0.0").unwrap();
     let checks = check_compatibility(&info, &target);
@@ -273,6 +278,7 @@ fn test_build_report_compatible() {
         mysql_version_id: Some(80032),
         has_compressed_pages: false,
         has_instant_columns: false,
+        ibuf_buffered_pages: 0,
     };
     let target = MysqlVersion::parse("8.4.0").unwrap();
     let report = build_compat_report(&info, &target, "test.ibd");
@@ -297,6 +303,7 @@ fn test_build_report_incompatible() {
         mysql_version_id: None,
         has_compressed_pages: false,
         has_instant_columns: false,
+        ibuf_buffered_pages: 0,
     };
     let target = MysqlVersion::parse("8.4.0").unwrap();
     let report = build_compat_report(&info, &target, "bad.ibd");
@@ -322,6 +329,7 @@ fn test_json_output_parsing() {
         mysql_version_id: Some(80400),
         has_compressed_pages: false,
         has_instant_columns: false,
+        ibuf_buffered_pages: 0,
     };
     let target = MysqlVersion::parse("9.0.0").unwrap();
     let report = build_compat_report(&info, &target, "table.ibd");
@@ -28,7 +28,7 @@ fn build_fsp_hdr_page(space_id: u32, total_pages: u32) -> Vec<u8> {
     BigEndian::write_u32(&mut page[fsp + FSP_FREE_LIMIT..], total_pages);
     BigEndian::write_u32(&mut page[fsp + FSP_SPACE_FLAGS..], 0);
     let trailer = PS - SIZE_FIL_TRAILER;
-    BigEndian::write_u32(&mut page[trailer + 4..], 1000 & 0xFFFFFFFF);
+    BigEndian::write_u32(&mut page[trailer + 4..], 1000);
     write_crc32c_checksum(&mut page);
     page
 }
@@ -217,13 +217,9 @@ fn test_diff_byte_ranges() {
     let mut page2 = page1.clone();
 
     // Modify bytes 100-109 in page2
-    for i in 100..110 {
-        page2[i] = 0xFF;
-    }
+    page2[100..110].fill(0xFF);
     // Modify bytes 200-204
-    for i in 200..205 {
-        page2[i] = 0xAA;
-    }
+    page2[200..205].fill(0xAA);
     // Recalculate checksum for page2
     write_crc32c_checksum(&mut page2);
     // Also recalculate page1 (already valid, but be safe)
@@ -0,0 +1,204 @@
+#![cfg(feature = "cli")]
+//! Integration tests for `inno doublewrite`.
+
+use byteorder::{BigEndian, ByteOrder};
+use tempfile::TempDir;
+
+use idb::innodb::checksum::{recalculate_checksum, validate_checksum, ChecksumAlgorithm};
+use idb::innodb::constants::*;
+use idb::innodb::write;
+
+const PAGE_SIZE: u32 = 16384;
+const PS: usize = PAGE_SIZE as usize;
+
+fn build_index_page(page_num: u32, space_id: u32, lsn: u64) -> Vec<u8> {
+    let mut page = vec![0u8; PS];
+    BigEndian::write_u32(&mut page[FIL_PAGE_OFFSET..], page_num);
+    BigEndian::write_u32(&mut page[FIL_PAGE_PREV..], FIL_NULL);
+    BigEndian::write_u32(&mut page[FIL_PAGE_NEXT..], FIL_NULL);
+    BigEndian::write_u64(&mut page[FIL_PAGE_LSN..], lsn);
+    BigEndian::write_u16(&mut page[FIL_PAGE_TYPE..], 17855);
+    BigEndian::write_u32(&mut page[FIL_PAGE_SPACE_ID..], space_id);
+    page[FIL_PAGE_DATA + 200] = (lsn % 251) as u8;
+    let trailer = PS - SIZE_FIL_TRAILER;
+    BigEndian::write_u32(&mut page[trailer + 4..], (lsn & 0xFFFFFFFF) as u32);
+    recalculate_checksum(&mut page, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+    page
+}
+
+/// Tablespace (space 42) whose page 2 is torn: new header, old trailer.
+/// The doublewrite file holds an intact copy of page 2 at LSN 5000, a stale
+/// copy of page 1, and a copy from another tablespace.
+fn setup(dir: &TempDir) -> (String, String) {
+    let page0 = write::build_fsp_page(42, 3, 0, 1000, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+    let page1 = build_index_page(1, 42, 3000);
+    let mut page2 = build_index_page(2, 42, 5000);
+    BigEndian::write_u32(&mut page2[PS - 4..], 4000);
+
+    let ibd = dir.path().join("t.ibd");
+    std::fs::write(&ibd, [page0, page1, page2].concat()).unwrap();
+
+    let dblwr = dir.path().join("#ib_16384_0.dblwr");
+    let copies = [
+        build_index_page(1, 42, 2000),
+        vec![0u8; PS],
+        build_index_page(2, 42, 5000),
+        build_index_page(2, 7, 9000),
+    ];
+    std::fs::write(&dblwr, copies.concat()).unwrap();
+
+    (
+        ibd.to_str().unwrap().to_string(),
+        dblwr.to_str().unwrap().to_string(),
+    )
+}
+
+fn options(
+    file: &str,
+    dblwr: &str,
+    restore: bool,
+    json: bool,
+) -> idb::cli::doublewrite::DoublewriteOptions {
+    idb::cli::doublewrite::DoublewriteOptions {
+        file: file.to_string(),
+        dblwr: vec![dblwr.to_string()],
+        ibdata: None,
+        restore,
+        no_backup: false,
+        verbose: false,
+        json,
+        page_size: None,
+        mmap: false,
+        audit_logger: None,
+    }
+}
+
+#[test]
+fn test_doublewrite_lists_copies_without_modifying() {
+    let dir = TempDir::new().unwrap();
+    let (ibd, dblwr) = setup(&dir);
+    let before = std::fs::read(&ibd).unwrap();
+
+    let mut output = Vec::new();
+    idb::cli::doublewrite::execute(&options(&ibd, &dblwr, false, true), &mut output).unwrap();
+
+    assert_eq!(std::fs::read(&ibd).unwrap(), before);
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let copies = json["sources"][0]["copies"].as_array().unwrap();
+    assert_eq!(copies.len(), 3);
+    assert_eq!(copies[1]["slot"], 2);
+    assert_eq!(copies[1]["checksum_valid"], true);
+
+    let pages = json["pages"].as_array().unwrap();
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[0]["page_number"], 1);
+    assert_eq!(pages[0]["action"], "intact");
+    assert_eq!(pages[1]["page_number"], 2);
+    assert_eq!(pages[1]["damage"], "torn");
+    assert_eq!(pages[1]["action"], "would_restore");
+    assert_eq!(json["restored"], 1);
+}
+
+#[test]
+fn test_doublewrite_restores_torn_page() {
+    let dir = TempDir::new().unwrap();
+    let (ibd, dblwr) = setup(&dir);
+
+    let mut output = Vec::new();
+    idb::cli::doublewrite::execute(&options(&ibd, &dblwr, true, false), &mut output).unwrap();
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.contains("Backup created"));
+    assert!(text.contains("restored"));
+
+    let p2 = write::read_page_raw(&ibd, 2, PAGE_SIZE).unwrap();
+    assert!(validate_checksum(&p2, PAGE_SIZE, None).valid);
+    assert_eq!(p2, build_index_page(2, 42, 5000));
+    // The intact page 1 is left alone even though an older copy exists
+    let p1 = write::read_page_raw(&ibd, 1, PAGE_SIZE).unwrap();
+    assert_eq!(p1, build_index_page(1, 42, 3000));
+    assert!(std::path::Path::new(&format!("{}.bak", ibd)).exists());
+}
+
+#[test]
+fn test_doublewrite_requires_a_source() {
+    let dir = TempDir::new().unwrap();
+    let (ibd, _) = setup(&dir);
+    let mut opts = options(&ibd, "", false, false);
+    opts.dblwr.clear();
+    let mut output = Vec::new();
+    assert!(idb::cli::doublewrite::execute(&opts, &mut output).is_err());
+}
+
+/// System tablespace (pre-8.0.20) whose legacy doublewrite area, pages
+/// 64-191, holds an intact copy of page 2 of space 42 and a copy of a page
+/// of another tablespace with a newer LSN.
+fn build_ibdata(dir: &TempDir) -> String {
+    let n_pages = 192;
+    let mut pages = vec![vec![0u8; PS]; n_pages];
+    pages[0] = write::build_fsp_page(
+        0,
+        n_pages as u32,
+        0,
+        1000,
+        PAGE_SIZE,
+        ChecksumAlgorithm::Crc32c,
+    );
+
+    let trx_sys = &mut pages[TRX_SYS_PAGE_NO as usize];
+    BigEndian::write_u32(&mut trx_sys[FIL_PAGE_OFFSET..], TRX_SYS_PAGE_NO as u32);
+    BigEndian::write_u16(&mut trx_sys[FIL_PAGE_TYPE..], 7);
+    let base = PS - TRX_SYS_DOUBLEWRITE_FROM_END;
+    BigEndian::write_u32(
+        &mut trx_sys[base + TRX_SYS_DOUBLEWRITE_MAGIC..],
+        TRX_SYS_DOUBLEWRITE_MAGIC_N,
+    );
+    BigEndian::write_u32(&mut trx_sys[base + TRX_SYS_DOUBLEWRITE_BLOCK1..], 64);
+    BigEndian::write_u32(&mut trx_sys[base + TRX_SYS_DOUBLEWRITE_BLOCK2..], 128);
+
+    pages[64] = build_index_page(2, 7, 9000);
+    pages[65] = build_index_page(2, 42, 5000);
+
+    let path = dir.path().join("ibdata1");
+    std::fs::write(&path, pages.concat()).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_doublewrite_restores_ibd_page_from_ibdata() {
+    let dir = TempDir::new().unwrap();
+    let (ibd, _) = setup(&dir);
+    let ibdata = build_ibdata(&dir);
+
+    let mut opts = options(&ibd, "", true, true);
+    opts.dblwr.clear();
+    opts.ibdata = Some(ibdata.clone());
+    let mut output = Vec::new();
+    idb::cli::doublewrite::execute(&opts, &mut output).unwrap();
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        json["sources"][0]["source"],
+        format!("{} (legacy doublewrite area)", ibdata)
+    );
+    let pages = json["pages"].as_array().unwrap();
+    assert_eq!(pages.len(), 1, "only space 42 copies are matched");
+    assert_eq!(pages[0]["page_number"], 2);
+    assert_eq!(pages[0]["action"], "restored");
+    assert_eq!(json["restored"], 1);
+
+    let p2 = write::read_page_raw(&ibd, 2, PAGE_SIZE).unwrap();
+    assert_eq!(p2, build_index_page(2, 42, 5000));
+}
+
+#[test]
+fn test_doublewrite_ibdata_without_legacy_area() {
+    let dir = TempDir::new().unwrap();
+    let (ibd, _) = setup(&dir);
+    let mut opts = options(&ibd, "", false, false);
+    opts.dblwr.clear();
+    // A file-per-table tablespace has no TRX_SYS page
+    opts.ibdata = Some(ibd.clone());
+    let mut output = Vec::new();
+    let err = idb::cli::doublewrite::execute(&opts, &mut output).unwrap_err();
+    assert!(err.to_string().contains("no legacy doublewrite area"));
+}
@@ -92,7 +92,7 @@ fn build_index_page_with_records(
         // Infimum header: points to first user record
         let infimum_hdr_start = PAGE_NEW_INFIMUM - REC_N_NEW_EXTRA_BYTES;
         page[infimum_hdr_start] = 0x01;
-        BigEndian::write_u16(&mut page[infimum_hdr_start + 1..], 0 << 3 | 2); // heap_no=0, infimum
+        BigEndian::write_u16(&mut page[infimum_hdr_start + 1..], 2); // heap_no=0, infimum
         let offset_to_first = first_rec_origin as i16 - PAGE_NEW_INFIMUM as i16;
         BigEndian::write_i16(&mut page[infimum_hdr_start + 3..], offset_to_first);
         page[PAGE_NEW_INFIMUM..PAGE_NEW_INFIMUM + 8].copy_from_slice(b"infimum\0");
@@ -105,7 +105,7 @@ fn build_index_page_with_records(
             // Record extra header (5 bytes before origin)
             let hdr_start = rec_start;
             page[hdr_start] = 0x00; // n_owned=0, no delete_mark
-            let heap_no = (i + 2) as u16; // 0=infimum, 1=supremum, 2+=user
+            let heap_no = i + 2; // 0=infimum, 1=supremum, 2+=user
             BigEndian::write_u16(&mut page[hdr_start + 1..], heap_no << 3); // Ordinary type
 
             // Next offset
@@ -171,6 +171,8 @@ fn test_export_hex_output() {
             page_size: None,
             keyring: None,
             mmap: false,
+            max_lob_size: 16 * 1024 * 1024,
+            index: None,
         },
         &mut output,
     )
@@ -210,6 +212,8 @@ fn test_export_hex_specific_page() {
             page_size: None,
             keyring: None,
             mmap: false,
+            max_lob_size: 16 * 1024 * 1024,
+            index: None,
         },
         &mut output,
     )
@@ -240,6 +244,8 @@ fn test_export_empty_page() {
             page_size: None,
             keyring: None,
             mmap: false,
+            max_lob_size: 16 * 1024 * 1024,
+            index: None,
         },
         &mut output,
     )
@@ -271,6 +277,8 @@ fn test_export_invalid_format() {
             page_size: None,
             keyring: None,
             mmap: false,
+            max_lob_size: 16 * 1024 * 1024,
+            index: None,
         },
         &mut output,
     );
@@ -301,6 +309,8 @@ fn test_export_csv_falls_back_to_hex_without_sdi() {
             page_size: None,
             keyring: None,
             mmap: false,
+            max_lob_size: 16 * 1024 * 1024,
+            index: None,
         },
         &mut output,
     )
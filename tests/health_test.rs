@@ -23,6 +23,7 @@ fn build_fsp_hdr_page(space_id: u32, total_pages: u32) -> Vec<u8> {
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_index_page(
     page_num: u32,
     space_id: u32,
@@ -308,3 +309,54 @@ fn test_health_multiple_indexes_json() {
     assert_eq!(indexes.len(), 3);
     assert_eq!(json["summary"]["index_count"], 3);
 }
+
+#[test]
+fn test_health_allocation_summary() {
+    // Extent 0 is a fragment extent with pages 0-3 marked used
+    let mut page0 = build_fsp_hdr_page(42, 5);
+    let fsp = FIL_PAGE_DATA;
+    BigEndian::write_u32(&mut page0[fsp + FSP_FREE_LIMIT..], 64);
+    let xdes = XDES_ARR_OFFSET;
+    BigEndian::write_u32(&mut page0[xdes + XDES_STATE..], 2); // XDES_FREE_FRAG
+    for b in &mut page0[xdes + XDES_BITMAP..xdes + XDES_SIZE] {
+        *b = 0xFF;
+    }
+    page0[xdes + XDES_BITMAP] = 0xAA; // free bit cleared for pages 0-3
+    idb::innodb::checksum::recalculate_checksum(&mut page0, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+
+    let page1 = build_index_page(1, 42, 2000, 100, 0, 50, 8000, 0);
+    let page2 = build_index_page(2, 42, 3000, 100, 0, 50, 8000, 0);
+    let page3 = vec![0u8; PS]; // allocated but never written
+    let page4 = build_index_page(4, 42, 4000, 100, 0, 50, 8000, 0); // stale data on a free page
+
+    let tmp = write_tablespace(&[page0, page1, page2, page3, page4]);
+    let path = tmp.path().to_str().unwrap();
+
+    let mut output = Vec::new();
+    idb::cli::health::execute(
+        &idb::cli::health::HealthOptions {
+            file: path.to_string(),
+            verbose: false,
+            json: true,
+            csv: false,
+            prometheus: false,
+            bloat: false,
+            cardinality: false,
+            sample_size: 30,
+            page_size: None,
+            keyring: None,
+            mmap: false,
+        },
+        &mut output,
+    )
+    .unwrap();
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let alloc = &json["allocation"];
+    assert_eq!(alloc["extent_size"], 64);
+    assert_eq!(alloc["initialized_extents"], 1);
+    assert_eq!(alloc["used_pages"], 4);
+    assert_eq!(alloc["free_pages"], 1);
+    assert_eq!(alloc["allocated_empty_pages"], 1);
+    assert_eq!(alloc["free_pages_with_data"], 1);
+}
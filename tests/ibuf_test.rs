@@ -0,0 +1,178 @@
+#![cfg(feature = "cli")]
+//! Integration tests for change buffer reporting in `inno info --ibdata`,
+//! `inno verify`, and `inno compat`.
+
+use byteorder::{BigEndian, ByteOrder};
+use tempfile::TempDir;
+
+use idb::innodb::checksum::{recalculate_checksum, ChecksumAlgorithm};
+use idb::innodb::constants::*;
+use idb::innodb::write;
+
+const PAGE_SIZE: u32 = 16384;
+const PS: usize = PAGE_SIZE as usize;
+
+fn finish_page(page: &mut [u8], page_num: u32, space_id: u32, page_type: u16) {
+    BigEndian::write_u32(&mut page[FIL_PAGE_OFFSET..], page_num);
+    BigEndian::write_u32(&mut page[FIL_PAGE_PREV..], FIL_NULL);
+    BigEndian::write_u64(&mut page[FIL_PAGE_LSN..], 1000);
+    BigEndian::write_u16(&mut page[FIL_PAGE_TYPE..], page_type);
+    BigEndian::write_u32(&mut page[FIL_PAGE_SPACE_ID..], space_id);
+    BigEndian::write_u32(&mut page[PS - 4..], 1000);
+    recalculate_checksum(page, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+}
+
+/// Change buffer root page (a leaf) holding one buffered insert and one
+/// delete-mark for page 3 of space 12.
+fn build_ibuf_root() -> Vec<u8> {
+    let mut page = vec![0u8; PS];
+    BigEndian::write_u32(&mut page[FIL_PAGE_NEXT..], FIL_NULL);
+    BigEndian::write_u64(&mut page[FIL_PAGE_DATA + PAGE_INDEX_ID..], DICT_IBUF_ID_MIN);
+    BigEndian::write_u16(&mut page[FIL_PAGE_DATA + PAGE_N_HEAP..], 4);
+
+    let mut prev = PAGE_OLD_INFIMUM;
+    let mut heap = PAGE_OLD_SUPREMUM + 9;
+    for (i, op) in [0u8, 1].into_iter().enumerate() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&12u32.to_be_bytes());
+        data.push(0);
+        data.extend_from_slice(&3u32.to_be_bytes());
+        data.extend_from_slice(&(i as u16).to_be_bytes());
+        data.extend_from_slice(&[op, IBUF_REC_COMPACT]);
+        data.extend_from_slice(&[0u8; 6]);
+        data.extend_from_slice(&[0x80, 0, 0, 1]);
+        let ends = [4u8, 5, 9, 19, 23];
+
+        let origin = heap + ends.len() + REC_N_OLD_EXTRA_BYTES;
+        for (j, end) in ends.iter().enumerate() {
+            page[origin - REC_N_OLD_EXTRA_BYTES - (j + 1)] = *end;
+        }
+        BigEndian::write_u16(&mut page[origin - 5..], ((i as u16) + 2) << 3);
+        page[origin - 3] = ((ends.len() as u8) << 1) | 1;
+        page[origin..origin + data.len()].copy_from_slice(&data);
+        BigEndian::write_u16(&mut page[prev - 2..], origin as u16);
+        prev = origin;
+        heap = origin + data.len();
+    }
+    BigEndian::write_u16(&mut page[prev - 2..], PAGE_OLD_SUPREMUM as u16);
+    finish_page(&mut page, 4, 0, 17855);
+    page
+}
+
+/// A data directory whose ibdata1 buffers changes for page 3 of `t.ibd`
+/// (space 12), and whose `t.ibd` bitmap marks that page as buffered.
+fn setup() -> TempDir {
+    let dir = TempDir::new().unwrap();
+
+    let mut ibdata = write::build_fsp_page(0, 6, 0, 1000, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+    for p in 1..4 {
+        let mut page = vec![0u8; PS];
+        finish_page(&mut page, p, 0, 0);
+        ibdata.extend_from_slice(&page);
+    }
+    ibdata.extend_from_slice(&build_ibuf_root());
+    ibdata.extend_from_slice(&vec![0u8; PS]);
+    std::fs::write(dir.path().join("ibdata1"), ibdata).unwrap();
+
+    let mut ibd = write::build_fsp_page(12, 4, 0, 1000, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+    let mut bitmap = vec![0u8; PS];
+    // Page 3: buffered bit (bit 2 of its nibble, byte 1 high nibble)
+    bitmap[IBUF_BITMAP + 1] = 1 << 6;
+    finish_page(&mut bitmap, 1, 12, 5);
+    ibd.extend_from_slice(&bitmap);
+    for p in 2..4 {
+        let mut page = vec![0u8; PS];
+        finish_page(&mut page, p, 12, 0);
+        ibd.extend_from_slice(&page);
+    }
+    std::fs::write(dir.path().join("t.ibd"), ibd).unwrap();
+
+    dir
+}
+
+#[test]
+fn test_info_ibdata_reports_pending_changes() {
+    let dir = setup();
+    let opts = idb::cli::info::InfoOptions {
+        ibdata: true,
+        lsn_check: false,
+        datadir: Some(dir.path().to_string_lossy().to_string()),
+        database: None,
+        table: None,
+        host: None,
+        port: None,
+        user: None,
+        password: None,
+        defaults_file: None,
+        tablespace_map: false,
+        json: true,
+        page_size: None,
+        mmap: false,
+    };
+
+    let mut out = Vec::new();
+    idb::cli::info::execute(&opts, &mut out).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let cb = &json["change_buffer"];
+    assert_eq!(cb["levels"], 1);
+    assert_eq!(cb["pending_changes"], 2);
+    let tables = cb["tables"].as_array().unwrap();
+    assert_eq!(tables.len(), 1);
+    assert_eq!(tables[0]["space_id"], 12);
+    assert_eq!(tables[0]["file"], "t.ibd");
+    assert_eq!(tables[0]["pages"], 1);
+    assert_eq!(tables[0]["inserts"], 1);
+    assert_eq!(tables[0]["delete_marks"], 1);
+}
+
+#[test]
+fn test_verify_warns_about_buffered_pages() {
+    let dir = setup();
+    let opts = idb::cli::verify::VerifyOptions {
+        file: dir.path().join("t.ibd").to_string_lossy().to_string(),
+        verbose: false,
+        json: true,
+        page_size: None,
+        keyring: None,
+        mmap: false,
+        redo: None,
+        chain: Vec::new(),
+        backup_meta: None,
+        spatial: false,
+        indexes: false,
+    };
+
+    let mut out = Vec::new();
+    let _ = idb::cli::verify::execute(&opts, &mut out);
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let warnings = json["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].as_str().unwrap().contains("change buffer"));
+}
+
+#[test]
+fn test_compat_warns_about_buffered_pages() {
+    let dir = setup();
+    let opts = idb::cli::compat::CompatOptions {
+        file: Some(dir.path().join("t.ibd").to_string_lossy().to_string()),
+        scan: None,
+        target: "8.4.0".to_string(),
+        verbose: false,
+        json: true,
+        page_size: None,
+        keyring: None,
+        mmap: false,
+        depth: None,
+    };
+
+    let mut out = Vec::new();
+    idb::cli::compat::execute(&opts, &mut out).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let check = json["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["check"] == "change_buffer")
+        .expect("change_buffer check");
+    assert_eq!(check["severity"], "Warning");
+}
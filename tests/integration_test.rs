@@ -40,7 +40,7 @@ fn build_fsp_hdr_page(space_id: u32, total_pages: u32) -> Vec<u8> {
 
     // FIL trailer: low 32 bits of LSN at trailer+4
     let trailer = PS - SIZE_FIL_TRAILER;
-    BigEndian::write_u32(&mut page[trailer + 4..], 1000 & 0xFFFFFFFF);
+    BigEndian::write_u32(&mut page[trailer + 4..], 1000);
 
     // Calculate and write CRC-32C checksum
     write_crc32c_checksum(&mut page);
@@ -689,7 +689,7 @@ fn test_sdi_execute_no_sdi_pages() {
 #[test]
 fn test_dump_raw_mode() {
     let page0 = build_fsp_hdr_page(1, 1);
-    let tmp = write_tablespace(&[page0.clone()]);
+    let tmp = write_tablespace(std::slice::from_ref(&page0));
 
     let opts = idb::cli::dump::DumpOptions {
         file: tmp.path().to_string_lossy().to_string(),
@@ -1236,8 +1236,14 @@ fn build_log_data_block(block_no: u32, data_len: u16, epoch_no: u32) -> Vec<u8>
     BigEndian::write_u32(&mut block[8..], epoch_no);
     // Fill some data bytes
     if data_len as usize > LOG_BLOCK_HDR_SIZE {
-        for i in LOG_BLOCK_HDR_SIZE..(data_len as usize).min(LOG_BLOCK_SIZE - 4) {
-            block[i] = (i % 256) as u8;
+        let end = (data_len as usize).min(LOG_BLOCK_SIZE - 4);
+        for (i, b) in block
+            .iter_mut()
+            .enumerate()
+            .take(end)
+            .skip(LOG_BLOCK_HDR_SIZE)
+        {
+            *b = (i % 256) as u8;
         }
     }
     write_log_block_checksum(&mut block);
@@ -1285,6 +1291,7 @@ fn test_log_basic_parse() {
         no_empty: false,
         verbose: false,
         json: false,
+        keyring: None,
     };
 
     let mut out = Vec::new();
@@ -1313,6 +1320,7 @@ fn test_log_json_output() {
         no_empty: false,
         verbose: false,
         json: true,
+        keyring: None,
     };
 
     let mut out = Vec::new();
@@ -1341,6 +1349,7 @@ fn test_log_blocks_limit() {
         no_empty: false,
         verbose: false,
         json: true,
+        keyring: None,
     };
 
     let mut out = Vec::new();
@@ -1364,6 +1373,7 @@ fn test_log_verbose_mode() {
         no_empty: false,
         verbose: true,
         json: false,
+        keyring: None,
     };
 
     let mut out = Vec::new();
@@ -1384,6 +1394,7 @@ fn test_log_nonexistent_file() {
         no_empty: false,
         verbose: false,
         json: false,
+        keyring: None,
     };
 
     let mut out = Vec::new();
@@ -2049,7 +2060,7 @@ fn test_dump_offset_mode() {
 #[test]
 fn test_dump_offset_raw_mode() {
     let page0 = build_fsp_hdr_page(1, 1);
-    let tmp = write_tablespace(&[page0.clone()]);
+    let tmp = write_tablespace(std::slice::from_ref(&page0));
 
     let opts = idb::cli::dump::DumpOptions {
         file: tmp.path().to_string_lossy().to_string(),
@@ -2544,6 +2555,7 @@ fn test_log_no_empty_filter() {
         no_empty: true,
         verbose: false,
         json: true,
+        keyring: None,
     };
 
     let mut out = Vec::new();
@@ -2849,13 +2861,17 @@ fn test_mysql91_standard_page_types() {
 fn test_mysql90_compressed_opens() {
     let path = format!("{}/mysql90_compressed.ibd", MYSQL9_FIXTURE_DIR);
     let ts = Tablespace::open(&path).expect("should open MySQL 9.0 compressed tablespace");
-    // The file is 56K. The parser reads at the detected page size.
-    // Compressed tablespace with KEY_BLOCK_SIZE=8 has 8K physical pages,
-    // but page 0 (FSP_HDR) is always stored at the logical page size.
+    // Compressed tablespace with KEY_BLOCK_SIZE=8 has 8K physical pages
+    // that decompress to the 16K logical page size.
     assert_eq!(
         ts.page_size(),
+        8192,
+        "physical page size should be the zip size"
+    );
+    assert_eq!(
+        ts.logical_page_size(),
         16384,
-        "logical page size should be 16K even for compressed tablespaces"
+        "logical page size should be 16K for compressed tablespaces"
     );
     assert!(
         ts.page_count() > 1,
@@ -2884,8 +2900,13 @@ fn test_mysql91_compressed_opens() {
     let ts = Tablespace::open(&path).expect("should open MySQL 9.1 compressed tablespace");
     assert_eq!(
         ts.page_size(),
+        8192,
+        "physical page size should be the zip size"
+    );
+    assert_eq!(
+        ts.logical_page_size(),
         16384,
-        "logical page size should be 16K even for compressed tablespaces"
+        "logical page size should be 16K for compressed tablespaces"
     );
     assert!(
         ts.page_count() > 1,
@@ -3466,6 +3487,7 @@ fn test_mysql9_redo_log_json_output() {
             no_empty: false,
             verbose: true,
             json: true,
+            keyring: None,
         };
         let mut out = Vec::new();
         idb::cli::log::execute(&opts, &mut out)
@@ -3830,7 +3852,7 @@ fn assert_table_dd_object(dd: &serde_json::Value, expected_name: &str) {
         "should have at least 3 columns (id, name, data or similar)"
     );
     assert!(
-        dd["indexes"].as_array().unwrap().len() >= 1,
+        !dd["indexes"].as_array().unwrap().is_empty(),
         "should have at least 1 index"
     );
     assert!(dd["foreign_keys"].is_array(), "foreign_keys must be array");
@@ -4490,8 +4512,13 @@ fn test_percona80_compressed_opens() {
     let ts = Tablespace::open(&path).expect("should open Percona 8.0 compressed tablespace");
     assert_eq!(
         ts.page_size(),
+        8192,
+        "physical page size should be the zip size"
+    );
+    assert_eq!(
+        ts.logical_page_size(),
         16384,
-        "logical page size should be 16K even for compressed tablespaces"
+        "logical page size should be 16K for compressed tablespaces"
     );
     assert!(
         ts.page_count() > 1,
@@ -4520,8 +4547,13 @@ fn test_percona84_compressed_opens() {
     let ts = Tablespace::open(&path).expect("should open Percona 8.4 compressed tablespace");
     assert_eq!(
         ts.page_size(),
+        8192,
+        "physical page size should be the zip size"
+    );
+    assert_eq!(
+        ts.logical_page_size(),
         16384,
-        "logical page size should be 16K even for compressed tablespaces"
+        "logical page size should be 16K for compressed tablespaces"
     );
     assert!(
         ts.page_count() > 1,
@@ -5401,17 +5433,18 @@ fn test_schema_percona84_standard_extracts_ddl() {
 }
 
 #[test]
-fn test_schema_compressed_falls_back_to_inference() {
+fn test_schema_compressed_sdi_and_inference() {
     use idb::innodb::schema::infer_schema_from_pages;
     use idb::innodb::sdi::find_sdi_pages;
 
     let path = format!("{}/mysql90_compressed.ibd", MYSQL9_FIXTURE_DIR);
     let mut ts = Tablespace::open(&path).expect("open");
 
+    // Read at the zip size, the SDI page is found like in any other tablespace
     let sdi_pages = find_sdi_pages(&mut ts).expect("find SDI pages");
     assert!(
-        sdi_pages.is_empty(),
-        "compressed fixture should have no SDI pages"
+        !sdi_pages.is_empty(),
+        "compressed fixture should have SDI pages"
     );
 
     let inferred = infer_schema_from_pages(&mut ts).expect("infer schema");
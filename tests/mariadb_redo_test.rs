@@ -0,0 +1,163 @@
+#![cfg(feature = "cli")]
+//! Integration tests for MariaDB 10.8+ redo logs: `inno log` on a single
+//! `ib_logfile0` and on its data directory, and `inno timeline`.
+
+use byteorder::{BigEndian, ByteOrder};
+use tempfile::TempDir;
+
+use idb::cli::log::{execute, LogOptions};
+use idb::innodb::mariadb_redo::{
+    MARIADB_CHECKPOINT_1, MARIADB_FORMAT_10_8, MARIADB_LOG_START_OFFSET,
+};
+
+const FIRST_LSN: u64 = 12_288;
+const CAPACITY: usize = 64 * 1024;
+
+/// A mini-transaction: records, the sequence bit of the first pass, and
+/// the big-endian CRC-32C of the records.
+fn mtr(records: &[&[u8]]) -> Vec<u8> {
+    let recs = records.concat();
+    let mut out = recs.clone();
+    out.push(1);
+    out.extend_from_slice(&crc32c::crc32c(&recs).to_be_bytes());
+    out
+}
+
+/// Two mini-transactions: one initialising and writing page 5:3, one
+/// with a FILE_MODIFY for space 5 and a FILE_CHECKPOINT.
+fn log_data() -> Vec<u8> {
+    let mut data = mtr(&[
+        // INIT_PAGE, space 5, page 3
+        &[0x12, 5, 3],
+        // same-page WRITE of 2 bytes at FIL_PAGE_TYPE + 14
+        &[0xB3, 14, 0, 2],
+    ]);
+    let mut modify = vec![0xB0 | 13, 5, 0];
+    modify.extend_from_slice(b"test/t1.ibd");
+    let mut checkpoint = vec![0xF0 | 10, 0, 0];
+    checkpoint.extend_from_slice(&FIRST_LSN.to_be_bytes());
+    data.extend(mtr(&[&modify, &checkpoint]));
+    data
+}
+
+fn write_log(dir: &TempDir) -> String {
+    let mut buf = vec![0u8; MARIADB_LOG_START_OFFSET as usize + CAPACITY];
+    BigEndian::write_u32(&mut buf[0..], MARIADB_FORMAT_10_8);
+    BigEndian::write_u64(&mut buf[8..], FIRST_LSN);
+    buf[16..31].copy_from_slice(b"MariaDB 10.11.6");
+    let crc = crc32c::crc32c(&buf[..508]);
+    BigEndian::write_u32(&mut buf[508..], crc);
+
+    let cp = MARIADB_CHECKPOINT_1 as usize;
+    BigEndian::write_u64(&mut buf[cp..], FIRST_LSN);
+    BigEndian::write_u64(&mut buf[cp + 8..], FIRST_LSN);
+    let crc = crc32c::crc32c(&buf[cp..cp + 60]);
+    BigEndian::write_u32(&mut buf[cp + 60..], crc);
+
+    let data = log_data();
+    let start = MARIADB_LOG_START_OFFSET as usize;
+    buf[start..start + data.len()].copy_from_slice(&data);
+
+    let path = dir.path().join("ib_logfile0");
+    std::fs::write(&path, buf).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+fn run(file: String, verbose: bool, json: bool) -> Vec<u8> {
+    let opts = LogOptions {
+        file,
+        blocks: None,
+        no_empty: false,
+        verbose,
+        json,
+        keyring: None,
+    };
+    let mut out = Vec::new();
+    execute(&opts, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn test_log_mariadb_text() {
+    let dir = TempDir::new().unwrap();
+    let path = write_log(&dir);
+    let text = String::from_utf8(run(path, true, false)).unwrap();
+    assert!(text.contains("MariaDB 10.8+"), "{}", text);
+    assert!(text.contains("Created by: MariaDB 10.11.6"), "{}", text);
+    assert!(text.contains("Checkpoint 1 (offset 4096)"), "{}", text);
+    assert!(
+        text.contains(&format!("LSN:          {}", FIRST_LSN)),
+        "{}",
+        text
+    );
+    assert!(text.contains("INIT_PAGE"), "{}", text);
+    assert!(text.contains("FILE_CHECKPOINT"), "{}", text);
+    assert!(text.contains("Displayed 2 mini-transactions"), "{}", text);
+    assert!(!text.contains("not decoded"), "{}", text);
+}
+
+#[test]
+fn test_log_mariadb_json() {
+    let dir = TempDir::new().unwrap();
+    let path = write_log(&dir);
+    let json: serde_json::Value = serde_json::from_slice(&run(path, true, true)).unwrap();
+    assert_eq!(json["capacity"], CAPACITY);
+    assert_eq!(json["checkpoint_lsn"], FIRST_LSN);
+    assert_eq!(json["start_lsn"], FIRST_LSN);
+    assert_eq!(json["end_lsn"], FIRST_LSN + log_data().len() as u64);
+    assert_eq!(json["total_mtrs"], 2);
+
+    let mtrs = json["mini_transactions"].as_array().unwrap();
+    assert_eq!(
+        mtrs[0]["record_types"],
+        serde_json::json!(["INIT_PAGE", "WRITE"])
+    );
+    assert_eq!(
+        mtrs[1]["record_types"],
+        serde_json::json!(["FILE_MODIFY", "FILE_CHECKPOINT"])
+    );
+    let write = &mtrs[0]["records"][1];
+    assert_eq!(write["space_id"], 5);
+    assert_eq!(write["page_no"], 3);
+    let modify = &mtrs[1]["records"][0]["body"];
+    assert_eq!(modify["kind"], "file_modify");
+    assert_eq!(modify["path"], "test/t1.ibd");
+}
+
+#[test]
+fn test_log_mariadb_directory() {
+    let dir = TempDir::new().unwrap();
+    write_log(&dir);
+    let path = dir.path().to_string_lossy().to_string();
+    let json: serde_json::Value = serde_json::from_slice(&run(path, false, true)).unwrap();
+    assert!(json["file"].as_str().unwrap().ends_with("ib_logfile0"));
+    assert_eq!(json["total_mtrs"], 2);
+    // Records are only listed with --verbose
+    assert!(json["mini_transactions"][0].get("records").is_none());
+}
+
+#[test]
+fn test_timeline_mariadb_redo() {
+    use idb::cli::timeline::{execute, TimelineOptions};
+
+    let dir = TempDir::new().unwrap();
+    let opts = TimelineOptions {
+        redo_log: Some(write_log(&dir)),
+        undo_file: None,
+        binlog: None,
+        file: None,
+        datadir: None,
+        space_id: None,
+        page: None,
+        table: None,
+        limit: None,
+        verbose: false,
+        json: true,
+        page_size: None,
+        keyring: None,
+    };
+    let mut out = Vec::new();
+    execute(&opts, &mut out).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json["redo_count"], 4);
+}
@@ -90,7 +90,7 @@ fn build_mysql_fsp_page(space_id: u32, total_pages: u32) -> Vec<u8> {
     BigEndian::write_u32(&mut page[fsp + FSP_SPACE_FLAGS..], 0);
 
     let trailer = PS - SIZE_FIL_TRAILER;
-    BigEndian::write_u32(&mut page[trailer + 4..], 1000 & 0xFFFFFFFF);
+    BigEndian::write_u32(&mut page[trailer + 4..], 1000);
 
     // MySQL CRC-32C
     let end = PS - SIZE_FIL_TRAILER;
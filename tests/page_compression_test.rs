@@ -0,0 +1,112 @@
+#![cfg(feature = "cli")]
+//! Integration tests for transparently compressed (punch-hole) tablespaces.
+
+use byteorder::{BigEndian, ByteOrder};
+use flate2::write::ZlibEncoder;
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+use idb::innodb::checksum::{recalculate_checksum, ChecksumAlgorithm};
+use idb::innodb::constants::*;
+use idb::innodb::write;
+
+const PAGE_SIZE: u32 = 16384;
+const PS: usize = PAGE_SIZE as usize;
+
+fn build_index_page(page_num: u32) -> Vec<u8> {
+    let mut page = vec![0u8; PS];
+    BigEndian::write_u32(&mut page[FIL_PAGE_OFFSET..], page_num);
+    BigEndian::write_u32(&mut page[FIL_PAGE_PREV..], FIL_NULL);
+    BigEndian::write_u32(&mut page[FIL_PAGE_NEXT..], FIL_NULL);
+    BigEndian::write_u64(&mut page[FIL_PAGE_LSN..], 2000 + u64::from(page_num));
+    BigEndian::write_u16(&mut page[FIL_PAGE_TYPE..], 17855); // INDEX
+    BigEndian::write_u32(&mut page[FIL_PAGE_SPACE_ID..], 7);
+    BigEndian::write_u32(&mut page[PS - 4..], 2000 + page_num);
+    recalculate_checksum(&mut page, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+    page
+}
+
+/// Compress a page as MySQL does for COMPRESSION='zlib' and punch the hole.
+fn compress_page(page: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&page[FIL_PAGE_DATA..]).unwrap();
+    let payload = encoder.finish().unwrap();
+
+    let mut out = vec![0u8; PS];
+    out[..FIL_PAGE_DATA].copy_from_slice(&page[..FIL_PAGE_DATA]);
+    BigEndian::write_u16(&mut out[FIL_PAGE_TYPE..], 14);
+    out[FIL_PAGE_COMPRESSION_VERSION] = 2;
+    out[FIL_PAGE_COMPRESSION_ALGORITHM] = 1;
+    BigEndian::write_u16(&mut out[FIL_PAGE_COMPRESSION_ORIGINAL_TYPE..], 17855);
+    BigEndian::write_u16(
+        &mut out[FIL_PAGE_COMPRESSION_ORIGINAL_SIZE..],
+        (PS - FIL_PAGE_DATA) as u16,
+    );
+    BigEndian::write_u16(
+        &mut out[FIL_PAGE_COMPRESSION_COMPRESSED_SIZE..],
+        payload.len() as u16,
+    );
+    out[FIL_PAGE_DATA..FIL_PAGE_DATA + payload.len()].copy_from_slice(&payload);
+    out
+}
+
+fn write_tablespace() -> NamedTempFile {
+    let page0 = write::build_fsp_page(7, 3, 0, 1000, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+    let mut tmp = NamedTempFile::new().unwrap();
+    tmp.write_all(&page0).unwrap();
+    for page_num in 1..3 {
+        tmp.write_all(&compress_page(&build_index_page(page_num)))
+            .unwrap();
+    }
+    tmp.flush().unwrap();
+    tmp
+}
+
+#[test]
+fn test_checksum_validates_decompressed_pages() {
+    let tmp = write_tablespace();
+    let opts = idb::cli::checksum::ChecksumOptions {
+        file: tmp.path().to_string_lossy().to_string(),
+        verbose: false,
+        json: true,
+        csv: false,
+        page_size: None,
+        keyring: None,
+        threads: 0,
+        mmap: false,
+        streaming: false,
+    };
+
+    let mut out = Vec::new();
+    idb::cli::checksum::execute(&opts, &mut out).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json["valid_pages"], 3);
+    assert_eq!(json["invalid_pages"], 0);
+}
+
+#[test]
+fn test_parse_reports_original_page_types() {
+    let tmp = write_tablespace();
+    let opts = idb::cli::parse::ParseOptions {
+        file: tmp.path().to_string_lossy().to_string(),
+        page: None,
+        verbose: false,
+        no_empty: false,
+        page_size: None,
+        json: true,
+        csv: false,
+        keyring: None,
+        threads: 0,
+        mmap: true,
+        streaming: false,
+    };
+
+    let mut out = Vec::new();
+    idb::cli::parse::execute(&opts, &mut out).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let pages = json.as_array().unwrap();
+    assert_eq!(pages.len(), 3);
+    for page in &pages[1..] {
+        assert_eq!(page["page_type_name"], "INDEX");
+    }
+}
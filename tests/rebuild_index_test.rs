@@ -0,0 +1,390 @@
+#![cfg(feature = "cli")]
+//! Integration tests for offline secondary index rebuild.
+
+use byteorder::{BigEndian, ByteOrder};
+
+use idb::innodb::checksum::ChecksumAlgorithm;
+use idb::innodb::constants::*;
+use idb::innodb::export::IndexLayout;
+use idb::innodb::field_decode::ColumnStorageInfo;
+use idb::innodb::index::IndexHeader;
+use idb::innodb::index_rebuild::{rebuild_secondary_index, IndexRebuildSpec, SegmentPages};
+use idb::innodb::page_dir::{check_page_directory, check_record_order};
+use idb::innodb::record::walk_records;
+use idb::innodb::tablespace::Tablespace;
+use idb::innodb::verify::check_secondary_indexes;
+use idb::innodb::write;
+
+const PAGE_SIZE: u32 = 16384;
+const PS: usize = PAGE_SIZE as usize;
+
+const CLUSTERED_ID: u64 = 100;
+const SECONDARY_ID: u64 = 200;
+const ROWS_PER_LEAF: u32 = 500;
+
+fn storage_col(name: &str, dd_type: u64, fixed_len: usize, nullable: bool) -> ColumnStorageInfo {
+    ColumnStorageInfo {
+        name: name.to_string(),
+        dd_type,
+        column_type: String::new(),
+        is_nullable: nullable,
+        is_unsigned: false,
+        fixed_len,
+        is_variable: false,
+        max_len: fixed_len,
+        version_added: 0,
+        version_dropped: 0,
+        instant_default: None,
+        charset_max_bytes: 1,
+        collation_id: 0,
+        datetime_precision: 0,
+        is_system_column: dd_type == 0,
+        elements: Vec::new(),
+        numeric_precision: 0,
+        numeric_scale: 0,
+    }
+}
+
+/// PRIMARY (id) and `b_idx` (b) on a table `(id INT, b INT NULL)`.
+fn layouts() -> Vec<IndexLayout> {
+    vec![
+        IndexLayout {
+            name: "PRIMARY".to_string(),
+            index_id: CLUSTERED_ID,
+            is_clustered: true,
+            columns: vec![
+                storage_col("id", 4, 4, false),
+                storage_col("DB_TRX_ID", 0, 6, false),
+                storage_col("DB_ROLL_PTR", 0, 7, false),
+                storage_col("b", 4, 4, true),
+            ],
+            descending: vec![false; 4],
+            prefix_lengths: vec![None; 4],
+        },
+        IndexLayout {
+            name: "b_idx".to_string(),
+            index_id: SECONDARY_ID,
+            is_clustered: false,
+            columns: vec![storage_col("b", 4, 4, true), storage_col("id", 4, 4, false)],
+            descending: vec![false; 2],
+            prefix_lengths: vec![None; 2],
+        },
+    ]
+}
+
+fn int(v: u32) -> [u8; 4] {
+    (v | 0x8000_0000).to_be_bytes()
+}
+
+/// Value of `b` for row `id`: scattered relative to `id`, NULL for every
+/// 50th row.
+fn b_value(id: u32) -> Option<u32> {
+    (!id.is_multiple_of(50)).then_some((id * 7919) % 1000)
+}
+
+/// Compact INDEX page of `(extra bytes, record data)` records.
+fn build_page(
+    page_num: u32,
+    index_id: u64,
+    level: u16,
+    prev: u32,
+    next: u32,
+    records: &[(Vec<u8>, Vec<u8>)],
+) -> Vec<u8> {
+    let mut page = vec![0u8; PS];
+    BigEndian::write_u32(&mut page[FIL_PAGE_OFFSET..], page_num);
+    BigEndian::write_u32(&mut page[FIL_PAGE_PREV..], prev);
+    BigEndian::write_u32(&mut page[FIL_PAGE_NEXT..], next);
+    BigEndian::write_u64(&mut page[FIL_PAGE_LSN..], 5000);
+    BigEndian::write_u16(&mut page[FIL_PAGE_TYPE..], 17855);
+    BigEndian::write_u32(&mut page[FIL_PAGE_SPACE_ID..], 42);
+    let ph = FIL_PAGE_DATA;
+    BigEndian::write_u16(
+        &mut page[ph + PAGE_N_HEAP..],
+        0x8000 | (records.len() as u16 + 2),
+    );
+    BigEndian::write_u16(&mut page[ph + PAGE_N_RECS..], records.len() as u16);
+    BigEndian::write_u16(&mut page[ph + PAGE_LEVEL..], level);
+    BigEndian::write_u64(&mut page[ph + PAGE_INDEX_ID..], index_id);
+
+    page[PAGE_NEW_INFIMUM - REC_N_NEW_EXTRA_BYTES + 2] = 0x02;
+    page[PAGE_NEW_SUPREMUM - REC_N_NEW_EXTRA_BYTES + 2] = 0x0B;
+    let status = if level == 0 { 0 } else { 1 };
+    let mut prev_rec = PAGE_NEW_INFIMUM;
+    let mut heap = PAGE_NEW_SUPREMUM + 8;
+    for (i, (extra, data)) in records.iter().enumerate() {
+        let origin = heap + extra.len() + REC_N_NEW_EXTRA_BYTES;
+        page[heap..heap + extra.len()].copy_from_slice(extra);
+        if level > 0 && i == 0 {
+            page[origin - REC_N_NEW_EXTRA_BYTES] = 0x10; // min_rec
+        }
+        BigEndian::write_u16(&mut page[origin - 4..], (((i as u16) + 2) << 3) | status);
+        page[origin..origin + data.len()].copy_from_slice(data);
+        BigEndian::write_i16(&mut page[prev_rec - 2..], origin as i16 - prev_rec as i16);
+        prev_rec = origin;
+        heap = origin + data.len();
+    }
+    BigEndian::write_i16(
+        &mut page[prev_rec - 2..],
+        PAGE_NEW_SUPREMUM as i16 - prev_rec as i16,
+    );
+    page
+}
+
+/// Tablespace with a two-level clustered index (root page 3, leaves 4..)
+/// holding rows `1..=n_leaves * ROWS_PER_LEAF`, a corrupt one-page
+/// secondary index at page 10, and spare pages up to page 15.
+fn build_tablespace(n_leaves: u32) -> Vec<u8> {
+    let mut pages = vec![vec![0u8; PS]; 16];
+    pages[0] = write::build_fsp_page(42, 16, 0, 1000, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+
+    let mut pointers = Vec::new();
+    for leaf in 0..n_leaves {
+        let page_num = 4 + leaf;
+        let first = leaf * ROWS_PER_LEAF + 1;
+        let rows: Vec<(Vec<u8>, Vec<u8>)> = (first..first + ROWS_PER_LEAF)
+            .map(|id| {
+                let mut data = int(id).to_vec();
+                data.extend_from_slice(&[0, 0, 0, 0, 0, 9]); // DB_TRX_ID
+                data.extend_from_slice(&[0u8; 7]);
+                let extra = match b_value(id) {
+                    Some(b) => {
+                        data.extend_from_slice(&int(b));
+                        vec![0]
+                    }
+                    None => vec![1],
+                };
+                (extra, data)
+            })
+            .collect();
+        let prev = if leaf == 0 { FIL_NULL } else { page_num - 1 };
+        let next = if leaf + 1 == n_leaves {
+            FIL_NULL
+        } else {
+            page_num + 1
+        };
+        pages[page_num as usize] = build_page(page_num, CLUSTERED_ID, 0, prev, next, &rows);
+
+        let mut ptr = int(first).to_vec();
+        ptr.extend_from_slice(&page_num.to_be_bytes());
+        pointers.push((Vec::new(), ptr));
+    }
+    pages[3] = build_page(3, CLUSTERED_ID, 1, FIL_NULL, FIL_NULL, &pointers);
+
+    // Secondary index root with one stale entry
+    let mut stale = int(5).to_vec();
+    stale.extend_from_slice(&int(999_999));
+    pages[10] = build_page(10, SECONDARY_ID, 0, FIL_NULL, FIL_NULL, &[(vec![0], stale)]);
+    // Recognizable FSEG headers on the old root
+    for (i, b) in pages[10][FIL_PAGE_DATA + PAGE_BTR_SEG_LEAF..][..20]
+        .iter_mut()
+        .enumerate()
+    {
+        *b = i as u8 + 1;
+    }
+    pages.concat()
+}
+
+fn spec() -> IndexRebuildSpec {
+    let layouts = layouts();
+    IndexRebuildSpec {
+        clustered: layouts[0].clone(),
+        clustered_root: 3,
+        index: layouts[1].clone(),
+        index_root: 10,
+        prefix_lengths: vec![None, None],
+        instant_defaults: vec![None, None],
+    }
+}
+
+fn segments() -> SegmentPages {
+    SegmentPages {
+        root: 10,
+        leaf: vec![11, 12, 13, 14],
+        non_leaf: vec![15],
+    }
+}
+
+/// Apply rebuilt pages (with checksums) to a tablespace image.
+fn apply(data: &mut [u8], pages: &[(u32, Vec<u8>)]) {
+    for (page_no, page) in pages {
+        let mut page = page.clone();
+        write::fix_page_checksum(&mut page, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+        let at = *page_no as usize * PS;
+        data[at..at + PS].copy_from_slice(&page);
+    }
+}
+
+#[test]
+fn test_rebuild_multi_level_index() {
+    let mut data = build_tablespace(6);
+    let mut ts = Tablespace::from_bytes(data.clone()).unwrap();
+    let result = rebuild_secondary_index(&mut ts, &spec(), &segments()).unwrap();
+
+    assert_eq!(result.entries, 3000);
+    assert_eq!(result.levels, 2);
+    assert_eq!(result.leaf_pages, 3);
+    assert_eq!(result.non_leaf_pages, 1);
+    assert_eq!(result.freed_pages, 2);
+    let page_nos: Vec<u32> = result.pages.iter().map(|(p, _)| *p).collect();
+    assert_eq!(page_nos, vec![10, 11, 12, 13, 14, 15]);
+
+    // Root keeps its FSEG headers and points at the leaves in order
+    let root = &result.pages[0].1;
+    let hdr = IndexHeader::parse(root).unwrap();
+    assert_eq!(hdr.level, 1);
+    assert_eq!(hdr.n_recs, 3);
+    assert_eq!(root[FIL_PAGE_DATA + PAGE_BTR_SEG_LEAF], 1);
+    assert_eq!(root[FIL_PAGE_DATA + PAGE_BTR_SEG_TOP + 9], 20);
+
+    let layouts = layouts();
+    for (page_no, page) in &result.pages[..4] {
+        let dir = check_page_directory(page).unwrap();
+        assert!(dir.issues.is_empty(), "page {}: {:?}", page_no, dir.issues);
+        assert!(check_record_order(page, &layouts[1]).is_empty());
+    }
+    let leaf = IndexHeader::parse(&result.pages[1].1).unwrap();
+    assert_eq!(leaf.max_trx_id, 9);
+    assert_eq!(BigEndian::read_u32(&result.pages[2].1[FIL_PAGE_PREV..]), 11);
+    assert_eq!(BigEndian::read_u32(&result.pages[2].1[FIL_PAGE_NEXT..]), 13);
+    assert_eq!(BigEndian::read_u16(&result.pages[4].1[FIL_PAGE_TYPE..]), 0);
+
+    apply(&mut data, &result.pages);
+    let mut rebuilt = Tablespace::from_bytes(data).unwrap();
+    let check = check_secondary_indexes(&mut rebuilt, &layouts, 3).unwrap();
+    assert!(check.passed, "{:?}", check.issues);
+    assert_eq!(check.indexes[0].entries_checked, 3000);
+}
+
+#[test]
+fn test_rebuild_single_page_index() {
+    let mut data = build_tablespace(1);
+    let mut ts = Tablespace::from_bytes(data.clone()).unwrap();
+    let result = rebuild_secondary_index(&mut ts, &spec(), &segments()).unwrap();
+
+    assert_eq!(result.entries, 500);
+    assert_eq!(result.levels, 1);
+    assert_eq!(result.leaf_pages, 1);
+    assert_eq!(result.freed_pages, 5);
+
+    apply(&mut data, &result.pages);
+    let mut rebuilt = Tablespace::from_bytes(data).unwrap();
+    let check = check_secondary_indexes(&mut rebuilt, &layouts(), 3).unwrap();
+    assert!(check.passed, "{:?}", check.issues);
+}
+
+#[test]
+fn test_rebuild_fails_without_enough_segment_pages() {
+    let data = build_tablespace(6);
+    let mut ts = Tablespace::from_bytes(data).unwrap();
+    let pages = SegmentPages {
+        root: 10,
+        leaf: vec![11, 12],
+        non_leaf: Vec::new(),
+    };
+    let err = rebuild_secondary_index(&mut ts, &spec(), &pages).unwrap_err();
+    assert!(err.to_string().contains("leaf segment owns (2)"));
+}
+
+/// A `VARCHAR(20)` column in `utf8mb4_0900_ai_ci`.
+fn varchar_col(name: &str) -> ColumnStorageInfo {
+    ColumnStorageInfo {
+        is_variable: true,
+        max_len: 80,
+        charset_max_bytes: 4,
+        collation_id: 255,
+        ..storage_col(name, 16, 0, false)
+    }
+}
+
+/// Tablespace of `(id INT, s VARCHAR(20))` rows on a one-page clustered
+/// index (page 3) with an empty `s_idx (s)` root at page 10, and the
+/// layouts and rebuild spec of `s_idx`.
+fn build_string_tablespace(values: &[&str]) -> (Vec<u8>, Vec<IndexLayout>, IndexRebuildSpec) {
+    let mut pages = vec![vec![0u8; PS]; 16];
+    pages[0] = write::build_fsp_page(42, 16, 0, 1000, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+    let rows: Vec<(Vec<u8>, Vec<u8>)> = values
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let mut data = int(i as u32 + 1).to_vec();
+            data.extend_from_slice(&[0, 0, 0, 0, 0, 9]); // DB_TRX_ID
+            data.extend_from_slice(&[0u8; 7]);
+            data.extend_from_slice(s.as_bytes());
+            (vec![s.len() as u8], data)
+        })
+        .collect();
+    pages[3] = build_page(3, CLUSTERED_ID, 0, FIL_NULL, FIL_NULL, &rows);
+    pages[10] = build_page(10, SECONDARY_ID, 0, FIL_NULL, FIL_NULL, &[]);
+
+    let layouts = vec![
+        IndexLayout {
+            name: "PRIMARY".to_string(),
+            index_id: CLUSTERED_ID,
+            is_clustered: true,
+            columns: vec![
+                storage_col("id", 4, 4, false),
+                storage_col("DB_TRX_ID", 0, 6, false),
+                storage_col("DB_ROLL_PTR", 0, 7, false),
+                varchar_col("s"),
+            ],
+            descending: vec![false; 4],
+            prefix_lengths: vec![None; 4],
+        },
+        IndexLayout {
+            name: "s_idx".to_string(),
+            index_id: SECONDARY_ID,
+            is_clustered: false,
+            columns: vec![varchar_col("s"), storage_col("id", 4, 4, false)],
+            descending: vec![false; 2],
+            prefix_lengths: vec![None; 2],
+        },
+    ];
+    let spec = IndexRebuildSpec {
+        clustered: layouts[0].clone(),
+        clustered_root: 3,
+        index: layouts[1].clone(),
+        index_root: 10,
+        prefix_lengths: vec![None, None],
+        instant_defaults: vec![None, None],
+    };
+    (pages.concat(), layouts, spec)
+}
+
+#[test]
+fn test_rebuild_string_key_in_case_insensitive_collation() {
+    let (mut data, layouts, spec) = build_string_tablespace(&["b", "A", "c", "a1", "B0"]);
+    let mut ts = Tablespace::from_bytes(data.clone()).unwrap();
+    let result = rebuild_secondary_index(&mut ts, &spec, &segments()).unwrap();
+    assert_eq!(result.entries, 5);
+    assert_eq!(result.levels, 1);
+
+    // Ordered case-insensitively, not by byte value
+    let root = &result.pages[0].1;
+    let keys: Vec<String> = walk_records(root)
+        .iter()
+        .map(|r| {
+            let len = root[r.offset - REC_N_NEW_EXTRA_BYTES - 1] as usize;
+            String::from_utf8(root[r.offset..r.offset + len].to_vec()).unwrap()
+        })
+        .collect();
+    assert_eq!(keys, vec!["A", "a1", "b", "B0", "c"]);
+
+    apply(&mut data, &result.pages);
+    let mut rebuilt = Tablespace::from_bytes(data).unwrap();
+    let check = check_secondary_indexes(&mut rebuilt, &layouts, 3).unwrap();
+    assert!(check.passed, "{:?}", check.issues);
+}
+
+#[test]
+fn test_rebuild_rejects_non_ascii_string_key() {
+    let (data, _, spec) = build_string_tablespace(&["b", "é", "a"]);
+    let mut ts = Tablespace::from_bytes(data).unwrap();
+    let err = rebuild_secondary_index(&mut ts, &spec, &segments()).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("utf8mb4_0900_ai_ci order of column s cannot be reproduced"),
+        "{}",
+        err
+    );
+}
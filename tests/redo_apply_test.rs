@@ -0,0 +1,421 @@
+#![cfg(feature = "cli")]
+//! Integration tests for `inno redo-apply`.
+
+use byteorder::{BigEndian, ByteOrder};
+use tempfile::TempDir;
+
+use idb::cli::redo_apply::{execute, RedoApplyOptions};
+use idb::innodb::checksum::{recalculate_checksum, validate_checksum, ChecksumAlgorithm};
+use idb::innodb::constants::*;
+use idb::innodb::encryption::encryption_info_offset;
+use idb::innodb::log::{LOG_BLOCK_CHECKSUM_OFFSET, LOG_BLOCK_HDR_SIZE, LOG_BLOCK_SIZE};
+use idb::innodb::write;
+
+const PAGE_SIZE: u32 = 16384;
+const PS: usize = PAGE_SIZE as usize;
+const SPACE_ID: u32 = 42;
+const CHECKPOINT_LSN: u64 = 2048;
+/// LSN of the first record in the log's only data block.
+const FIRST_LSN: u64 = CHECKPOINT_LSN + LOG_BLOCK_HDR_SIZE as u64;
+
+fn compressed(val: u32) -> Vec<u8> {
+    match val {
+        0..=0x7F => vec![val as u8],
+        0x80..=0x3FFF => vec![0x80 | (val >> 8) as u8, val as u8],
+        _ => {
+            let mut v = vec![0xF0];
+            v.extend_from_slice(&val.to_be_bytes());
+            v
+        }
+    }
+}
+
+fn record(type_byte: u8, page_no: u32) -> Vec<u8> {
+    let mut v = vec![type_byte];
+    v.extend(compressed(SPACE_ID));
+    v.extend(compressed(page_no));
+    v
+}
+
+fn write4(type_byte: u8, page_no: u32, offset: u16, value: u32) -> Vec<u8> {
+    let mut v = record(type_byte, page_no);
+    v.extend_from_slice(&offset.to_be_bytes());
+    v.extend(compressed(value));
+    v
+}
+
+/// Redo records after the checkpoint:
+///
+/// 1. a mini-transaction that formats page 3 as an empty compact INDEX
+///    page, creates page 6 past the end of the file, and grows FSP_SIZE;
+/// 2. a single-record write to page 1, whose LSN is already past it;
+/// 3. a write to page 2 in a mini-transaction the log cuts off.
+///
+/// Returns the payload and the end LSN of the first mini-transaction.
+fn redo_payload() -> (Vec<u8>, u64) {
+    let mut p = Vec::new();
+    p.extend(record(59, 3)); // MLOG_INIT_FILE_PAGE2
+    p.extend(record(37, 3)); // MLOG_COMP_PAGE_CREATE
+    p.extend(record(8, 3)); // MLOG_8BYTES: PAGE_INDEX_ID = 100
+    p.extend_from_slice(&((FIL_PAGE_DATA + PAGE_INDEX_ID) as u16).to_be_bytes());
+    p.push(0);
+    p.extend_from_slice(&100u32.to_be_bytes());
+    p.extend(record(59, 6));
+    p.extend(write4(4, 0, (FIL_PAGE_DATA + FSP_SIZE) as u16, 7));
+    p.push(31); // MLOG_MULTI_REC_END
+    let mtr_end = FIRST_LSN + p.len() as u64;
+
+    p.extend(write4(0x80 | 4, 1, 200, 0x1234));
+    p.extend(write4(4, 2, 200, 5));
+    (p, mtr_end)
+}
+
+/// A redo log file (format 6) with checkpoint LSN 2048 and one data block.
+fn build_redo_log() -> (Vec<u8>, u64) {
+    let (payload, mtr_end) = redo_payload();
+    (build_redo_log_with(&payload), mtr_end)
+}
+
+/// A redo log file (format 6) holding `payload` after checkpoint LSN 2048.
+fn build_redo_log_with(payload: &[u8]) -> Vec<u8> {
+    let mut log = vec![0u8; 5 * LOG_BLOCK_SIZE];
+    BigEndian::write_u32(&mut log[0..], 6);
+    BigEndian::write_u64(&mut log[8..], CHECKPOINT_LSN);
+    log[48..60].copy_from_slice(b"MySQL 8.0.35");
+    BigEndian::write_u64(&mut log[LOG_BLOCK_SIZE + 8..], CHECKPOINT_LSN);
+    BigEndian::write_u64(&mut log[3 * LOG_BLOCK_SIZE + 8..], CHECKPOINT_LSN);
+
+    let block = &mut log[4 * LOG_BLOCK_SIZE..];
+    BigEndian::write_u32(
+        &mut block[0..],
+        (CHECKPOINT_LSN / LOG_BLOCK_SIZE as u64) as u32 + 1,
+    );
+    BigEndian::write_u16(&mut block[4..], (LOG_BLOCK_HDR_SIZE + payload.len()) as u16);
+    BigEndian::write_u16(&mut block[6..], LOG_BLOCK_HDR_SIZE as u16);
+    BigEndian::write_u32(&mut block[8..], 1);
+    block[LOG_BLOCK_HDR_SIZE..LOG_BLOCK_HDR_SIZE + payload.len()].copy_from_slice(payload);
+    let crc = crc32c::crc32c(&block[..LOG_BLOCK_CHECKSUM_OFFSET]);
+    BigEndian::write_u32(&mut block[LOG_BLOCK_CHECKSUM_OFFSET..], crc);
+    log
+}
+
+fn build_page(page_no: u32, lsn: u64) -> Vec<u8> {
+    let mut page = vec![0u8; PS];
+    BigEndian::write_u32(&mut page[FIL_PAGE_OFFSET..], page_no);
+    BigEndian::write_u64(&mut page[FIL_PAGE_LSN..], lsn);
+    BigEndian::write_u16(&mut page[FIL_PAGE_TYPE..], 2); // UNDO_LOG
+    BigEndian::write_u32(&mut page[FIL_PAGE_SPACE_ID..], SPACE_ID);
+    recalculate_checksum(&mut page, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+    page
+}
+
+/// A 6-page tablespace; page 1 is newer than the redo log.
+fn build_tablespace() -> Vec<u8> {
+    let mut pages = [
+        write::build_fsp_page(SPACE_ID, 6, 0, 1000, PAGE_SIZE, ChecksumAlgorithm::Crc32c),
+        build_page(1, 900_000),
+        build_page(2, 1000),
+        vec![0u8; PS],
+        build_page(4, 1000),
+        build_page(5, 1000),
+    ];
+    BigEndian::write_u64(&mut pages[3][FIL_PAGE_LSN..], 1000);
+    pages.concat()
+}
+
+fn options(dir: &TempDir, output_dir: &str) -> RedoApplyOptions {
+    RedoApplyOptions {
+        datadir: Some(dir.path().to_str().unwrap().to_string()),
+        files: Vec::new(),
+        redo: Vec::new(),
+        output_dir: output_dir.to_string(),
+        to_lsn: None,
+        verbose: true,
+        json: false,
+        page_size: None,
+        depth: None,
+        mmap: false,
+        audit_logger: None,
+    }
+}
+
+/// A data directory with `db1/t1.ibd` and `ib_logfile0`.
+fn setup() -> (TempDir, Vec<u8>, u64) {
+    let dir = TempDir::new().unwrap();
+    std::fs::create_dir(dir.path().join("db1")).unwrap();
+    let ts = build_tablespace();
+    std::fs::write(dir.path().join("db1/t1.ibd"), &ts).unwrap();
+    let (log, mtr_end) = build_redo_log();
+    std::fs::write(dir.path().join("ib_logfile0"), log).unwrap();
+    (dir, ts, mtr_end)
+}
+
+fn page(data: &[u8], page_no: usize) -> &[u8] {
+    &data[page_no * PS..(page_no + 1) * PS]
+}
+
+#[test]
+fn test_redo_apply_datadir() {
+    let (dir, source, mtr_end) = setup();
+    let out = TempDir::new().unwrap();
+    let out_dir = out.path().to_str().unwrap();
+
+    let mut output = Vec::new();
+    execute(&options(&dir, out_dir), &mut output).unwrap();
+    let text = String::from_utf8(output).unwrap();
+    assert!(
+        text.contains("3 page(s) rolled forward, 1 current"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("log ends inside a mini-transaction"),
+        "{}",
+        text
+    );
+
+    let data = std::fs::read(out.path().join("db1/t1.ibd")).unwrap();
+    assert_eq!(data.len(), 7 * PS);
+    for page_no in 0..7 {
+        assert!(
+            validate_checksum(page(&data, page_no), PAGE_SIZE, None).valid,
+            "page {}",
+            page_no
+        );
+    }
+
+    // Page 0: FSP_SIZE grown by the log
+    let p0 = page(&data, 0);
+    assert_eq!(BigEndian::read_u32(&p0[FIL_PAGE_DATA + FSP_SIZE..]), 7);
+    assert_eq!(BigEndian::read_u64(&p0[FIL_PAGE_LSN..]), mtr_end);
+
+    // Page 3: a fresh, empty compact INDEX page
+    let p3 = page(&data, 3);
+    assert_eq!(BigEndian::read_u16(&p3[FIL_PAGE_TYPE..]), 17855);
+    assert_eq!(BigEndian::read_u32(&p3[FIL_PAGE_OFFSET..]), 3);
+    assert_eq!(BigEndian::read_u32(&p3[FIL_PAGE_SPACE_ID..]), SPACE_ID);
+    assert_eq!(BigEndian::read_u64(&p3[FIL_PAGE_LSN..]), mtr_end);
+    assert_eq!(
+        BigEndian::read_u64(&p3[FIL_PAGE_DATA + PAGE_INDEX_ID..]),
+        100
+    );
+    assert_eq!(&p3[PAGE_NEW_SUPREMUM..PAGE_NEW_SUPREMUM + 8], b"supremum");
+
+    // Page 6: created past the old end of the file
+    assert_eq!(BigEndian::read_u32(&page(&data, 6)[FIL_PAGE_OFFSET..]), 6);
+
+    // Pages 1 (already current) and 2 (incomplete mtr) are untouched
+    assert_eq!(page(&data, 1), page(&source, 1));
+    assert_eq!(page(&data, 2), page(&source, 2));
+
+    // The source is never modified
+    assert_eq!(
+        std::fs::read(dir.path().join("db1/t1.ibd")).unwrap(),
+        source
+    );
+}
+
+#[test]
+fn test_redo_apply_to_lsn_json() {
+    let (dir, _, mtr_end) = setup();
+    let out = TempDir::new().unwrap();
+    let mut opts = options(&dir, out.path().to_str().unwrap());
+    opts.json = true;
+    opts.to_lsn = Some(mtr_end - 1);
+
+    let mut output = Vec::new();
+    execute(&opts, &mut output).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["checkpoint_lsn"], CHECKPOINT_LSN);
+    assert_eq!(json["plan"]["mtr_count"], 0);
+    assert_eq!(json["plan"]["mtrs_after"], 2);
+    assert_eq!(json["plan"]["incomplete_at"], mtr_end + 7);
+    assert!(json["tablespaces"].as_array().unwrap().is_empty());
+    assert!(!out.path().join("db1/t1.ibd").exists());
+}
+
+#[test]
+fn test_redo_apply_files_and_redo() {
+    let (dir, _, _) = setup();
+    let out = TempDir::new().unwrap();
+    let mut opts = options(&dir, out.path().to_str().unwrap());
+    opts.datadir = None;
+    opts.json = true;
+    opts.files = vec![dir.path().join("db1/t1.ibd").to_string_lossy().to_string()];
+    opts.redo = vec![dir.path().join("ib_logfile0").to_string_lossy().to_string()];
+
+    let mut output = Vec::new();
+    execute(&opts, &mut output).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let ts = &json["tablespaces"][0];
+    assert_eq!(ts["space_id"], SPACE_ID);
+    assert_eq!(ts["pages_changed"], 3);
+    assert_eq!(ts["pages_current"], 1);
+    assert_eq!(ts["records_applied"], 5);
+    assert_eq!(ts["post_validation"]["valid"], 3);
+    assert!(out.path().join("t1.ibd").exists());
+}
+
+#[test]
+fn test_redo_apply_refuses_to_overwrite_source() {
+    let (dir, source, _) = setup();
+    let opts = options(&dir, dir.path().to_str().unwrap());
+    let err = execute(&opts, &mut Vec::new()).unwrap_err();
+    assert!(err.to_string().contains("overwrite its source"), "{}", err);
+    assert_eq!(
+        std::fs::read(dir.path().join("db1/t1.ibd")).unwrap(),
+        source
+    );
+}
+
+#[test]
+fn test_redo_apply_refuses_partial_mini_transaction() {
+    let (dir, _, _) = setup();
+    // Page 3 replays, but the undo insert on page 4 finds no undo header
+    let mut p = Vec::new();
+    p.extend(record(59, 3)); // MLOG_INIT_FILE_PAGE2
+    p.extend(record(20, 4)); // MLOG_UNDO_INSERT
+    p.extend_from_slice(&[0, 1, 0xAA]);
+    p.push(31); // MLOG_MULTI_REC_END
+    std::fs::write(dir.path().join("ib_logfile0"), build_redo_log_with(&p)).unwrap();
+
+    let out = TempDir::new().unwrap();
+    let mut output = Vec::new();
+    let err = execute(&options(&dir, out.path().to_str().unwrap()), &mut output).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("1 page(s) could not be rolled forward"),
+        "{}",
+        err
+    );
+    let text = String::from_utf8(output).unwrap();
+    assert!(
+        text.contains("1 page(s) rolled forward, 0 current, 1 failed"),
+        "{}",
+        text
+    );
+    assert!(!out.path().join("db1/t1.ibd").exists());
+}
+
+#[test]
+fn test_redo_apply_system_tablespace_across_data_files() {
+    let (dir, source, mtr_end) = setup();
+    // Split the tablespace into ibdata1 (pages 0-2) and ibdata2 (pages 3-5)
+    std::fs::remove_dir_all(dir.path().join("db1")).unwrap();
+    std::fs::write(dir.path().join("ibdata1"), &source[..3 * PS]).unwrap();
+    std::fs::write(dir.path().join("ibdata2"), &source[3 * PS..]).unwrap();
+
+    let out = TempDir::new().unwrap();
+    let mut opts = options(&dir, out.path().to_str().unwrap());
+    opts.json = true;
+    let mut output = Vec::new();
+    execute(&opts, &mut output).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let ts = &json["tablespaces"][0];
+    assert_eq!(ts["pages_changed"], 3);
+    assert_eq!(ts["post_validation"]["valid"], 3);
+
+    // Page 0 stays in ibdata1; pages 3 and 6 land in ibdata2
+    let ibdata1 = std::fs::read(out.path().join("ibdata1")).unwrap();
+    assert_eq!(ibdata1.len(), 3 * PS);
+    let p0 = page(&ibdata1, 0);
+    assert_eq!(BigEndian::read_u32(&p0[FIL_PAGE_DATA + FSP_SIZE..]), 7);
+    assert_eq!(page(&ibdata1, 1), page(&source, 1));
+
+    let ibdata2 = std::fs::read(out.path().join("ibdata2")).unwrap();
+    assert_eq!(ibdata2.len(), 4 * PS);
+    let p3 = page(&ibdata2, 0);
+    assert_eq!(BigEndian::read_u32(&p3[FIL_PAGE_OFFSET..]), 3);
+    assert_eq!(BigEndian::read_u64(&p3[FIL_PAGE_LSN..]), mtr_end);
+    assert_eq!(page(&ibdata2, 1), page(&source, 4));
+    assert_eq!(
+        BigEndian::read_u32(&page(&ibdata2, 3)[FIL_PAGE_OFFSET..]),
+        6
+    );
+}
+
+#[test]
+fn test_redo_apply_fails_mini_transaction_on_unsupported_tablespace() {
+    let (dir, _, _) = setup();
+    // db1/t2.ibd: an encrypted tablespace with space ID 43
+    let mut t2 = build_tablespace();
+    BigEndian::write_u32(&mut t2[FIL_PAGE_DATA + FSP_SPACE_ID..], 43);
+    let at = encryption_info_offset(PAGE_SIZE);
+    t2[at..at + 3].copy_from_slice(ENCRYPTION_MAGIC_V3);
+    std::fs::write(dir.path().join("db1/t2.ibd"), &t2).unwrap();
+
+    // One mini-transaction formats page 3 of t1 and writes page 4 of t2
+    let mut p = record(59, 3); // MLOG_INIT_FILE_PAGE2
+    p.extend_from_slice(&[4, 43, 4]); // MLOG_4BYTES, space 43, page 4
+    p.extend_from_slice(&200u16.to_be_bytes());
+    p.push(5);
+    p.push(31); // MLOG_MULTI_REC_END
+    std::fs::write(dir.path().join("ib_logfile0"), build_redo_log_with(&p)).unwrap();
+
+    let out = TempDir::new().unwrap();
+    let mut opts = options(&dir, out.path().to_str().unwrap());
+    opts.json = true;
+    let mut output = Vec::new();
+    let err = execute(&opts, &mut output).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("1 page(s) could not be rolled forward"),
+        "{}",
+        err
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let t2 = json["tablespaces"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["space_id"] == 43)
+        .unwrap();
+    assert_eq!(t2["unsupported"], "encrypted tablespace");
+    assert_eq!(t2["pages_failed"], 1);
+    assert!(!out.path().join("db1/t1.ibd").exists());
+}
+
+#[test]
+fn test_redo_apply_records_unreadable_tablespace() {
+    let (dir, _, _) = setup();
+    std::fs::write(dir.path().join("db1/t0.ibd"), [0u8; 100]).unwrap();
+
+    let out = TempDir::new().unwrap();
+    let mut output = Vec::new();
+    let err = execute(&options(&dir, out.path().to_str().unwrap()), &mut output).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("1 tablespace(s) could not be read; no files were written"),
+        "{}",
+        err
+    );
+    // The readable tablespace after it was still replayed
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.contains("db1/t0.ibd: "), "{}", text);
+    assert!(
+        text.contains("3 page(s) rolled forward, 1 current"),
+        "{}",
+        text
+    );
+    assert!(!out.path().join("db1/t1.ibd").exists());
+}
+
+#[test]
+fn test_redo_apply_rejects_files_with_the_same_name() {
+    let (dir, source, _) = setup();
+    std::fs::create_dir(dir.path().join("db2")).unwrap();
+    std::fs::write(dir.path().join("db2/t1.ibd"), &source).unwrap();
+
+    let out = TempDir::new().unwrap();
+    let mut opts = options(&dir, out.path().to_str().unwrap());
+    opts.files = vec![
+        dir.path().join("db1/t1.ibd").to_string_lossy().to_string(),
+        dir.path().join("db2/t1.ibd").to_string_lossy().to_string(),
+    ];
+    let err = execute(&opts, &mut Vec::new()).unwrap_err();
+    assert!(
+        err.to_string().contains("would both be written to t1.ibd"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,243 @@
+#![cfg(feature = "cli")]
+//! Integration tests for redo log sets: `RedoLogSet::open_dir` and the
+//! directory mode of `inno log`, `inno verify --redo`, and `inno timeline`.
+
+use byteorder::{BigEndian, ByteOrder};
+use tempfile::TempDir;
+
+use idb::innodb::constants::FIL_PAGE_LSN;
+use idb::innodb::log::{LOG_BLOCK_CHECKSUM_OFFSET, LOG_BLOCK_SIZE};
+use idb::innodb::redo_set::{RedoLogLayout, RedoLogSet};
+
+/// Log data bytes in a full block.
+const DATA_SIZE: usize = LOG_BLOCK_CHECKSUM_OFFSET - 12;
+const CHECKPOINT_LSN: u64 = 8300;
+
+/// A data block at `lsn` holding `data`, with its first record group at
+/// block offset `first_rec`.
+fn data_block(lsn: u64, data: &[u8], first_rec: usize) -> Vec<u8> {
+    let mut b = vec![0u8; LOG_BLOCK_SIZE];
+    BigEndian::write_u32(&mut b[0..], (lsn / LOG_BLOCK_SIZE as u64) as u32 + 1);
+    let data_len = if data.len() == DATA_SIZE {
+        LOG_BLOCK_SIZE
+    } else {
+        12 + data.len()
+    };
+    BigEndian::write_u16(&mut b[4..], data_len as u16);
+    BigEndian::write_u16(&mut b[6..], first_rec as u16);
+    BigEndian::write_u32(&mut b[8..], 1);
+    b[12..12 + data.len()].copy_from_slice(data);
+    let crc = crc32c::crc32c(&b[..LOG_BLOCK_CHECKSUM_OFFSET]);
+    BigEndian::write_u32(&mut b[LOG_BLOCK_CHECKSUM_OFFSET..], crc);
+    b
+}
+
+/// A format 6 redo file starting at `start_lsn`.
+fn redo_file(start_lsn: u64, checkpoint: u64, blocks: &[Vec<u8>]) -> Vec<u8> {
+    let mut buf = vec![0u8; 4 * LOG_BLOCK_SIZE];
+    BigEndian::write_u32(&mut buf[0..], 6);
+    BigEndian::write_u64(&mut buf[8..], start_lsn);
+    buf[16..28].copy_from_slice(b"MySQL 8.0.35");
+    BigEndian::write_u64(&mut buf[LOG_BLOCK_SIZE + 8..], checkpoint);
+    for b in blocks {
+        buf.extend_from_slice(b);
+    }
+    buf
+}
+
+/// Three blocks of 200 `MLOG_4BYTES` records (6 bytes each) from LSN 8192,
+/// so records cross both block boundaries.
+fn record_blocks() -> Vec<Vec<u8>> {
+    let mut stream = Vec::new();
+    for i in 0..200u32 {
+        stream.extend_from_slice(&[4, 1, 3, 0, 38 + (i % 100) as u8, 7]);
+    }
+    stream
+        .chunks(DATA_SIZE)
+        .enumerate()
+        .map(|(k, chunk)| {
+            let start = k * DATA_SIZE;
+            let first_rec = start.div_ceil(6) * 6 - start;
+            data_block(8192 + (k * LOG_BLOCK_SIZE) as u64, chunk, 12 + first_rec)
+        })
+        .collect()
+}
+
+/// A data directory whose `#innodb_redo` holds `#ib_redo1..3` (one block
+/// each, `skip` left out) and a spare `#ib_redo4_tmp`.
+fn setup(skip: Option<usize>) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let redo_dir = dir.path().join("#innodb_redo");
+    std::fs::create_dir(&redo_dir).unwrap();
+    for (k, block) in record_blocks().into_iter().enumerate() {
+        if Some(k + 1) == skip {
+            continue;
+        }
+        let start = 8192 + (k * LOG_BLOCK_SIZE) as u64;
+        let checkpoint = if k == 0 { CHECKPOINT_LSN } else { 0 };
+        std::fs::write(
+            redo_dir.join(format!("#ib_redo{}", k + 1)),
+            redo_file(start, checkpoint, &[block]),
+        )
+        .unwrap();
+    }
+    std::fs::write(
+        redo_dir.join("#ib_redo4_tmp"),
+        redo_file(0, 0, &[vec![0u8; LOG_BLOCK_SIZE]]),
+    )
+    .unwrap();
+    dir
+}
+
+fn path(dir: &TempDir) -> String {
+    dir.path().to_string_lossy().to_string()
+}
+
+#[test]
+fn test_open_dir_discovers_redo_files() {
+    let dir = setup(None);
+    let mut set = RedoLogSet::open_dir(&path(&dir)).unwrap();
+    assert_eq!(set.layout(), RedoLogLayout::Sequential);
+    let numbers: Vec<(u32, bool)> = set.members().iter().map(|m| (m.file_no, m.spare)).collect();
+    assert_eq!(numbers, [(1, false), (2, false), (3, false), (4, true)]);
+    assert_eq!(set.checkpoint_lsn(), Some(CHECKPOINT_LSN));
+    assert!(set.gaps().is_empty());
+
+    let decoded = set.read_mlog_records().unwrap();
+    assert_eq!(decoded.records.len(), 200);
+    assert!(decoded.errors.is_empty());
+
+    // The #innodb_redo directory itself works too
+    let inner = dir.path().join("#innodb_redo");
+    let set = RedoLogSet::open_dir(&inner.to_string_lossy()).unwrap();
+    assert_eq!(set.block_count(), 3);
+}
+
+#[test]
+fn test_open_dir_falls_back_to_ib_logfiles() {
+    let dir = TempDir::new().unwrap();
+    let blocks = record_blocks();
+    let mut log0 = redo_file(8192, CHECKPOINT_LSN, &blocks[..2]);
+    // Pre-8.0.30 header layout: format 4, start LSN at offset 4
+    BigEndian::write_u32(&mut log0[0..], 4);
+    BigEndian::write_u64(&mut log0[4..], 8192);
+    let mut log1 = redo_file(0, 0, &blocks[2..]);
+    BigEndian::write_u32(&mut log1[0..], 4);
+    BigEndian::write_u64(&mut log1[4..], 9216);
+    log1.extend_from_slice(&[0u8; LOG_BLOCK_SIZE]);
+    std::fs::write(dir.path().join("ib_logfile0"), log0).unwrap();
+    std::fs::write(dir.path().join("ib_logfile1"), log1).unwrap();
+
+    let mut set = RedoLogSet::open_dir(&path(&dir)).unwrap();
+    assert_eq!(set.layout(), RedoLogLayout::Circular);
+    assert_eq!(set.members().len(), 2);
+    assert_eq!(set.start_lsn(), Some(8192));
+    assert_eq!(set.read_mlog_records().unwrap().records.len(), 200);
+}
+
+#[test]
+fn test_open_dir_without_redo_files() {
+    let dir = TempDir::new().unwrap();
+    let err = RedoLogSet::open_dir(&path(&dir)).err().unwrap();
+    assert!(err.to_string().contains("No redo log files"), "{}", err);
+}
+
+#[test]
+fn test_log_directory_text() {
+    let dir = setup(Some(2));
+    let opts = idb::cli::log::LogOptions {
+        file: path(&dir),
+        blocks: None,
+        no_empty: false,
+        verbose: true,
+        json: false,
+        keyring: None,
+    };
+    let mut out = Vec::new();
+    idb::cli::log::execute(&opts, &mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert!(text.contains("InnoDB Redo Log Set"), "{}", text);
+    assert!(text.contains("Layout:     sequential"), "{}", text);
+    assert!(text.contains("Files:      2 (+1 spare)"), "{}", text);
+    assert!(
+        text.contains("LSN 8704..9216 (512 bytes) is missing"),
+        "{}",
+        text
+    );
+    assert!(text.contains("#ib_redo3:4"), "{}", text);
+    assert!(text.contains("MLOG_4BYTES"), "{}", text);
+    assert!(text.contains("Displayed 2 data blocks"), "{}", text);
+}
+
+#[test]
+fn test_log_directory_json() {
+    let dir = setup(None);
+    let opts = idb::cli::log::LogOptions {
+        file: path(&dir),
+        blocks: Some(2),
+        no_empty: false,
+        verbose: true,
+        json: true,
+        keyring: None,
+    };
+    let mut out = Vec::new();
+    idb::cli::log::execute(&opts, &mut out).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json["layout"], "sequential");
+    assert_eq!(json["files"].as_array().unwrap().len(), 4);
+    assert_eq!(json["checkpoint_lsn"], CHECKPOINT_LSN);
+    assert_eq!(json["total_blocks"], 3);
+    assert!(json["gaps"].as_array().unwrap().is_empty());
+
+    let blocks = json["blocks"].as_array().unwrap();
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[1]["lsn"], 8704);
+    assert!(blocks[1]["file"].as_str().unwrap().ends_with("#ib_redo2"));
+    // 83 records start in each of the first two blocks
+    assert_eq!(blocks[0]["records"].as_array().unwrap().len(), 83);
+    assert_eq!(blocks[1]["records"].as_array().unwrap().len(), 83);
+}
+
+#[test]
+fn test_verify_redo_directory() {
+    let mut page = vec![0u8; 16384];
+    BigEndian::write_u64(&mut page[FIL_PAGE_LSN..], 8000);
+
+    let dir = setup(None);
+    let result = idb::innodb::verify::verify_redo_continuity(&path(&dir), &page, 16384).unwrap();
+    assert_eq!(result.checkpoint_lsn, CHECKPOINT_LSN);
+    assert!(result.covers_tablespace);
+    assert_eq!(result.redo_files.len(), 4);
+    assert!(result.gaps_after_checkpoint.is_empty());
+
+    let dir = setup(Some(2));
+    let result = idb::innodb::verify::verify_redo_continuity(&path(&dir), &page, 16384).unwrap();
+    assert_eq!(result.gaps_after_checkpoint.len(), 1);
+    assert_eq!(result.gaps_after_checkpoint[0].start_lsn, 8704);
+}
+
+#[test]
+fn test_timeline_redo_directory() {
+    use idb::cli::timeline::{execute, TimelineOptions};
+
+    let dir = setup(None);
+    let opts = TimelineOptions {
+        redo_log: Some(path(&dir)),
+        undo_file: None,
+        binlog: None,
+        file: None,
+        datadir: None,
+        space_id: None,
+        page: None,
+        table: None,
+        limit: None,
+        verbose: false,
+        json: true,
+        page_size: None,
+        keyring: None,
+    };
+    let mut out = Vec::new();
+    execute(&opts, &mut out).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json["redo_count"], 200);
+}
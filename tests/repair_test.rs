@@ -594,7 +594,7 @@ fn test_batch_no_backup_skips_bak_files() {
         e.unwrap()
             .path()
             .extension()
-            .map_or(false, |ext| ext == "bak")
+            .is_some_and(|ext| ext == "bak")
     });
     assert!(!bak_exists);
 }
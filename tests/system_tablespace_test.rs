@@ -0,0 +1,127 @@
+#![cfg(feature = "cli")]
+//! Integration tests for system tablespaces split across several data files.
+
+use byteorder::{BigEndian, ByteOrder};
+use tempfile::TempDir;
+
+use idb::innodb::checksum::{recalculate_checksum, ChecksumAlgorithm};
+use idb::innodb::constants::*;
+use idb::innodb::write;
+
+const PAGE_SIZE: u32 = 16384;
+const PS: usize = PAGE_SIZE as usize;
+
+fn build_page(page_num: u32) -> Vec<u8> {
+    let mut page = vec![0u8; PS];
+    BigEndian::write_u32(&mut page[FIL_PAGE_OFFSET..], page_num);
+    BigEndian::write_u32(&mut page[FIL_PAGE_PREV..], FIL_NULL);
+    BigEndian::write_u32(&mut page[FIL_PAGE_NEXT..], FIL_NULL);
+    BigEndian::write_u64(&mut page[FIL_PAGE_LSN..], 1000 + u64::from(page_num));
+    BigEndian::write_u16(&mut page[FIL_PAGE_TYPE..], 2); // UNDO_LOG
+    BigEndian::write_u32(&mut page[PS - 4..], 1000 + page_num);
+    recalculate_checksum(&mut page, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+    page
+}
+
+/// `ibdata1` holds pages 0-1 plus a page past its configured 32K size;
+/// `ibdata2` (autoextend) holds pages 2-3.
+fn setup() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let page0 = write::build_fsp_page(0, 4, 0, 1000, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+    let ibdata1 = [page0, build_page(1), build_page(99)].concat();
+    std::fs::write(dir.path().join("ibdata1"), ibdata1).unwrap();
+    std::fs::write(
+        dir.path().join("ibdata2"),
+        [build_page(2), build_page(3)].concat(),
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("my.cnf"),
+        "[mysqld]\ninnodb_data_file_path=ibdata1:32K;ibdata2:32K:autoextend\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn info_options(dir: &TempDir, defaults_file: Option<String>) -> idb::cli::info::InfoOptions {
+    idb::cli::info::InfoOptions {
+        ibdata: true,
+        lsn_check: false,
+        datadir: Some(dir.path().to_string_lossy().to_string()),
+        database: None,
+        table: None,
+        host: None,
+        port: None,
+        user: None,
+        password: None,
+        defaults_file,
+        tablespace_map: false,
+        json: true,
+        page_size: None,
+        mmap: false,
+    }
+}
+
+#[test]
+fn test_info_ibdata_uses_data_file_path() {
+    let dir = setup();
+    let cnf = dir.path().join("my.cnf").to_string_lossy().to_string();
+
+    let mut out = Vec::new();
+    idb::cli::info::execute(&info_options(&dir, Some(cnf)), &mut out).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let files = json["data_files"].as_array().unwrap();
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0]["size"], 2 * PS);
+    assert_eq!(files[0]["page_count"], 2);
+    assert_eq!(files[1]["first_page"], 2);
+    assert_eq!(files[1]["page_count"], 2);
+}
+
+#[test]
+fn test_info_ibdata_infers_data_files() {
+    let dir = setup();
+
+    let mut out = Vec::new();
+    idb::cli::info::execute(&info_options(&dir, None), &mut out).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let files = json["data_files"].as_array().unwrap();
+    assert_eq!(files.len(), 2);
+    // Without the configured size, all of ibdata1 is used
+    assert_eq!(files[0]["page_count"], 3);
+    assert_eq!(files[1]["first_page"], 3);
+}
+
+#[test]
+fn test_parse_reads_across_data_files() {
+    let dir = setup();
+    let ibdata2 = dir.path().join("ibdata2");
+    // Trim ibdata1 to its two real pages so numbering continues in ibdata2
+    let ibdata1 = dir.path().join("ibdata1");
+    let data = std::fs::read(&ibdata1).unwrap();
+    std::fs::write(&ibdata1, &data[..2 * PS]).unwrap();
+
+    let opts = idb::cli::parse::ParseOptions {
+        file: format!("{};{}", ibdata1.display(), ibdata2.display()),
+        page: None,
+        verbose: false,
+        no_empty: false,
+        page_size: None,
+        json: true,
+        csv: false,
+        keyring: None,
+        threads: 0,
+        mmap: false,
+        streaming: false,
+    };
+
+    let mut out = Vec::new();
+    idb::cli::parse::execute(&opts, &mut out).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let pages = json.as_array().unwrap();
+    assert_eq!(pages.len(), 4);
+    for (n, page) in pages.iter().enumerate() {
+        assert_eq!(page["page_number"], n);
+        assert_eq!(page["header"]["page_number"], n);
+    }
+}
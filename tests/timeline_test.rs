@@ -3,9 +3,10 @@
 use byteorder::{BigEndian, ByteOrder};
 
 use idb::innodb::log::{
-    compute_record_lsn, parse_mlog_records, LogBlockHeader, LogFile, MlogRecordType,
-    LOG_BLOCK_CHECKSUM_OFFSET, LOG_BLOCK_HDR_SIZE, LOG_BLOCK_SIZE, LOG_FILE_HDR_BLOCKS,
+    compute_record_lsn, LogFile, MlogRecordType, LOG_BLOCK_CHECKSUM_OFFSET, LOG_BLOCK_HDR_SIZE,
+    LOG_BLOCK_SIZE, LOG_FILE_HDR_BLOCKS,
 };
+use idb::innodb::mlog::{read_log_streams, read_mlog_records, MlogBody, MlogStreamDecode};
 use idb::innodb::timeline::{merge_timeline, TimelineAction, TimelineEntry, TimelineSource};
 
 // ── Helpers ─────────────────────────────────────────────────────────────
@@ -92,40 +93,66 @@ fn compress_u32(val: u32) -> Vec<u8> {
     }
 }
 
-// ── MLOG record parsing tests ───────────────────────────────────────────
+// ── MLOG record decoding tests ──────────────────────────────────────────
+
+/// Block number of data block `i` in a log from [`make_redo_log`]
+/// (start LSN 2048).
+fn block_no(i: u32) -> u32 {
+    2048 / LOG_BLOCK_SIZE as u32 + 1 + i
+}
+
+/// Decode a redo log built from the given data blocks.
+fn decode_blocks(blocks: &[Vec<u8>]) -> MlogStreamDecode {
+    let mut log = LogFile::from_bytes(make_redo_log(blocks)).unwrap();
+    read_mlog_records(&mut log).unwrap()
+}
+
+/// Page-referencing record header: type byte, space_id, page_no.
+fn record_header(type_byte: u8, space_id: u32, page_no: u32) -> Vec<u8> {
+    let mut v = vec![type_byte];
+    v.extend_from_slice(&compress_u32(space_id));
+    v.extend_from_slice(&compress_u32(page_no));
+    v
+}
 
 #[test]
-fn test_parse_mlog_single_byte_space_and_page() {
-    // Type 1 (MLOG_1BYTE), space_id=5, page_no=3
-    let mut payload = vec![1u8]; // type byte
-    payload.extend_from_slice(&compress_u32(5)); // space_id
-    payload.extend_from_slice(&compress_u32(3)); // page_no
-
-    let block = make_data_block(4, &payload);
-    let hdr = LogBlockHeader::parse(&block).unwrap();
-    let records = parse_mlog_records(&block, &hdr);
-
-    assert!(!records.is_empty());
-    let rec = &records[0];
+fn test_decode_mlog_single_byte_space_and_page() {
+    // MLOG_1BYTE, space_id=5, page_no=3, write 0x7F at offset 38
+    let mut payload = record_header(1, 5, 3);
+    payload.extend_from_slice(&38u16.to_be_bytes());
+    payload.extend_from_slice(&compress_u32(0x7F));
+
+    let decoded = decode_blocks(&[make_data_block(block_no(0), &payload)]);
+
+    assert_eq!(decoded.records.len(), 1);
+    let rec = &decoded.records[0];
     assert_eq!(rec.record_type, MlogRecordType::Mlog1Byte);
     assert!(!rec.single_rec);
     assert_eq!(rec.space_id, Some(5));
     assert_eq!(rec.page_no, Some(3));
+    assert!(matches!(
+        rec.body,
+        MlogBody::WriteBytes {
+            offset: 38,
+            value: 0x7F
+        }
+    ));
+    assert_eq!(rec.start_lsn, 2048 + LOG_BLOCK_HDR_SIZE as u64);
+    assert_eq!(rec.end_lsn, rec.start_lsn + payload.len() as u64);
 }
 
 #[test]
-fn test_parse_mlog_single_rec_flag() {
+fn test_decode_mlog_single_rec_flag() {
     // Type byte with single-rec flag: 0x80 | 30 (MLOG_WRITE_STRING)
-    let mut payload = vec![0x80 | 30];
-    payload.extend_from_slice(&compress_u32(10)); // space_id
-    payload.extend_from_slice(&compress_u32(42)); // page_no
+    let mut payload = record_header(0x80 | 30, 10, 42);
+    payload.extend_from_slice(&120u16.to_be_bytes()); // offset
+    payload.extend_from_slice(&4u16.to_be_bytes()); // length
+    payload.extend_from_slice(b"abcd");
 
-    let block = make_data_block(4, &payload);
-    let hdr = LogBlockHeader::parse(&block).unwrap();
-    let records = parse_mlog_records(&block, &hdr);
+    let decoded = decode_blocks(&[make_data_block(block_no(0), &payload)]);
 
-    assert!(!records.is_empty());
-    let rec = &records[0];
+    assert_eq!(decoded.records.len(), 1);
+    let rec = &decoded.records[0];
     assert_eq!(rec.record_type, MlogRecordType::MlogWriteString);
     assert!(rec.single_rec);
     assert_eq!(rec.space_id, Some(10));
@@ -133,74 +160,116 @@ fn test_parse_mlog_single_rec_flag() {
 }
 
 #[test]
-fn test_parse_mlog_non_page_type() {
+fn test_decode_mlog_non_page_type() {
     // Type 31 = MLOG_MULTI_REC_END — no space_id/page_no
-    let payload = vec![31u8];
-    let block = make_data_block(4, &payload);
-    let hdr = LogBlockHeader::parse(&block).unwrap();
-    let records = parse_mlog_records(&block, &hdr);
+    let decoded = decode_blocks(&[make_data_block(block_no(0), &[31u8])]);
 
-    assert!(!records.is_empty());
-    let rec = &records[0];
+    assert_eq!(decoded.records.len(), 1);
+    let rec = &decoded.records[0];
     assert_eq!(rec.record_type, MlogRecordType::MlogMultiRecEnd);
     assert_eq!(rec.space_id, None);
     assert_eq!(rec.page_no, None);
 }
 
 #[test]
-fn test_parse_mlog_multi_byte_compressed() {
-    // Type 1, space_id=300 (2-byte compressed), page_no=65000 (3-byte compressed)
-    let mut payload = vec![1u8];
-    payload.extend_from_slice(&compress_u32(300));
-    payload.extend_from_slice(&compress_u32(65000));
-
-    let block = make_data_block(4, &payload);
-    let hdr = LogBlockHeader::parse(&block).unwrap();
-    let records = parse_mlog_records(&block, &hdr);
-
-    assert!(!records.is_empty());
-    assert_eq!(records[0].space_id, Some(300));
-    assert_eq!(records[0].page_no, Some(65000));
+fn test_decode_mlog_multi_byte_compressed() {
+    // MLOG_INIT_FILE_PAGE2, space_id=300 (2 bytes), page_no=65000 (3 bytes)
+    let payload = record_header(59, 300, 65000);
+    let decoded = decode_blocks(&[make_data_block(block_no(0), &payload)]);
+
+    assert_eq!(decoded.records.len(), 1);
+    assert_eq!(decoded.records[0].space_id, Some(300));
+    assert_eq!(decoded.records[0].page_no, Some(65000));
+    assert_eq!(decoded.records[0].end_lsn - decoded.records[0].start_lsn, 6);
 }
 
 #[test]
-fn test_parse_mlog_empty_block() {
-    // Block with data_len = LOG_BLOCK_HDR_SIZE (no payload)
-    let block = make_data_block(4, &[]);
-    let mut modified = block.clone();
-    BigEndian::write_u16(&mut modified[4..], LOG_BLOCK_HDR_SIZE as u16);
-    let hdr = LogBlockHeader::parse(&modified).unwrap();
-    let records = parse_mlog_records(&modified, &hdr);
-    assert!(records.is_empty());
+fn test_decode_mlog_empty_block() {
+    // Block with data_len = 12 (header only) yields no stream at all
+    let mut block = make_data_block(block_no(0), &[]);
+    BigEndian::write_u16(&mut block[4..], 12);
+    let mut log = LogFile::from_bytes(make_redo_log(&[block])).unwrap();
+    assert!(read_log_streams(&mut log).unwrap().is_empty());
+    assert!(read_mlog_records(&mut log).unwrap().records.is_empty());
 }
 
 #[test]
-fn test_parse_mlog_multiple_records() {
-    // Two records: MLOG_1BYTE + MLOG_UNDO_INSERT
-    let mut payload = Vec::new();
-    // Record 1: type=1, space=1, page=0
-    payload.push(1u8);
+fn test_decode_mlog_multiple_records() {
+    // MLOG_1BYTE followed by MLOG_UNDO_INSERT
+    let mut payload = record_header(1, 1, 0);
+    payload.extend_from_slice(&50u16.to_be_bytes());
     payload.extend_from_slice(&compress_u32(1));
-    payload.extend_from_slice(&compress_u32(0));
-    // Record 2: type=20, space=2, page=100
-    payload.push(20u8);
-    payload.extend_from_slice(&compress_u32(2));
-    payload.extend_from_slice(&compress_u32(100));
-
-    let block = make_data_block(4, &payload);
-    let hdr = LogBlockHeader::parse(&block).unwrap();
-    let records = parse_mlog_records(&block, &hdr);
-
-    assert!(records.len() >= 2);
-    assert_eq!(records[0].record_type, MlogRecordType::Mlog1Byte);
-    assert_eq!(records[0].space_id, Some(1));
-    // Find the MLOG_UNDO_INSERT record
-    let undo_rec = records
+    payload.extend_from_slice(&record_header(20, 2, 100));
+    payload.extend_from_slice(&3u16.to_be_bytes());
+    payload.extend_from_slice(&[0x0B, 0x01, 0x02]);
+
+    let decoded = decode_blocks(&[make_data_block(block_no(0), &payload)]);
+
+    assert_eq!(decoded.records.len(), 2);
+    assert_eq!(decoded.records[0].record_type, MlogRecordType::Mlog1Byte);
+    assert_eq!(decoded.records[0].space_id, Some(1));
+    let undo_rec = &decoded.records[1];
+    assert_eq!(undo_rec.record_type, MlogRecordType::MlogUndoInsert);
+    assert_eq!(undo_rec.space_id, Some(2));
+    assert_eq!(undo_rec.page_no, Some(100));
+    assert_eq!(undo_rec.start_lsn, decoded.records[0].end_lsn);
+    match &undo_rec.body {
+        MlogBody::UndoInsert { data } => assert_eq!(data, &[0x0B, 0x01, 0x02]),
+        other => panic!("unexpected body {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_mlog_record_spanning_blocks() {
+    // An MLOG_UNDO_INSERT whose 600 data bytes run through three blocks,
+    // followed by MLOG_MULTI_REC_END in the third block.
+    let mut stream = record_header(20, 7, 9);
+    stream.extend_from_slice(&600u16.to_be_bytes());
+    stream.extend((0..600).map(|i| (i % 251) as u8));
+    stream.push(31);
+
+    // De-block the stream: full blocks carry 496 data bytes from offset 12
+    let chunks: Vec<&[u8]> = stream.chunks(496).collect();
+    let blocks: Vec<Vec<u8>> = chunks
         .iter()
-        .find(|r| r.record_type == MlogRecordType::MlogUndoInsert);
-    assert!(undo_rec.is_some());
-    assert_eq!(undo_rec.unwrap().space_id, Some(2));
-    assert_eq!(undo_rec.unwrap().page_no, Some(100));
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut block = vec![0u8; LOG_BLOCK_SIZE];
+            BigEndian::write_u32(&mut block[0..], block_no(i as u32));
+            let data_len = if i + 1 < chunks.len() {
+                LOG_BLOCK_SIZE
+            } else {
+                12 + chunk.len()
+            };
+            BigEndian::write_u16(&mut block[4..], data_len as u16);
+            // Only the first block starts a record group
+            let first_rec = if i == 0 { 12 } else { 0 };
+            BigEndian::write_u16(&mut block[6..], first_rec);
+            block[12..12 + chunk.len()].copy_from_slice(chunk);
+            block
+        })
+        .collect();
+    assert_eq!(blocks.len(), 2);
+
+    let decoded = decode_blocks(&blocks);
+    assert!(decoded.errors.is_empty());
+    assert_eq!(decoded.records.len(), 2);
+    let undo = &decoded.records[0];
+    assert_eq!(undo.start_lsn, 2048 + 12);
+    // 5 header bytes + 600 data bytes: 496 in block 0, 109 in block 1
+    assert_eq!(undo.end_lsn, 2048 + 512 + 12 + 109);
+    match &undo.body {
+        MlogBody::UndoInsert { data } => {
+            assert_eq!(data.len(), 600);
+            assert_eq!(data[599], (599 % 251) as u8);
+        }
+        other => panic!("unexpected body {:?}", other),
+    }
+    assert_eq!(
+        decoded.records[1].record_type,
+        MlogRecordType::MlogMultiRecEnd
+    );
+    assert_eq!(decoded.records[1].start_lsn, undo.end_lsn);
 }
 
 // ── compute_record_lsn tests ────────────────────────────────────────────
@@ -303,23 +372,24 @@ fn test_extract_redo_timeline_from_synthetic_log() {
     use idb::innodb::timeline::extract_redo_timeline;
 
     // Build a redo log with one data block containing two MLOG records
-    let mut payload = Vec::new();
-    payload.push(1u8); // MLOG_1BYTE
-    payload.extend_from_slice(&compress_u32(5)); // space_id
-    payload.extend_from_slice(&compress_u32(3)); // page_no
-    payload.push(20u8); // MLOG_UNDO_INSERT
-    payload.extend_from_slice(&compress_u32(5)); // space_id
-    payload.extend_from_slice(&compress_u32(7)); // page_no
-
-    let data_block = make_data_block(4, &payload);
+    let mut payload = record_header(1, 5, 3); // MLOG_1BYTE
+    payload.extend_from_slice(&38u16.to_be_bytes()); // offset
+    payload.extend_from_slice(&compress_u32(1)); // value
+    payload.extend_from_slice(&record_header(20, 5, 7)); // MLOG_UNDO_INSERT
+    payload.extend_from_slice(&2u16.to_be_bytes()); // length
+    payload.extend_from_slice(&[0x0B, 0x00]);
+
+    let data_block = make_data_block(block_no(0), &payload);
     let log_data = make_redo_log(&[data_block]);
 
     let mut log = LogFile::from_bytes(log_data).unwrap();
     let entries = extract_redo_timeline(&mut log).unwrap();
 
-    assert!(entries.len() >= 2);
+    assert_eq!(entries.len(), 2);
     assert!(entries.iter().all(|e| e.source == TimelineSource::RedoLog));
-    assert!(entries.iter().all(|e| e.lsn.is_some()));
+    assert_eq!(entries[0].lsn, Some(2048 + LOG_BLOCK_HDR_SIZE as u64));
+    assert_eq!(entries[1].lsn, Some(2048 + LOG_BLOCK_HDR_SIZE as u64 + 6));
+    assert_eq!(entries[1].page_no, Some(7));
 }
 
 // ── CLI validation test ─────────────────────────────────────────────────
@@ -7,6 +7,12 @@ use tempfile::NamedTempFile;
 
 use idb::innodb::checksum::{recalculate_checksum, ChecksumAlgorithm};
 use idb::innodb::constants::*;
+use idb::innodb::field_decode::ColumnStorageInfo;
+use idb::innodb::tablespace::Tablespace;
+use idb::innodb::verify::{
+    check_secondary_indexes, check_spatial_mbrs, verify_tablespace, IndexEntryIssueKind,
+    SpatialIndexInfo, VerifyCheckKind, VerifyConfig,
+};
 use idb::innodb::write;
 
 const PAGE_SIZE: u32 = 16384;
@@ -39,10 +45,23 @@ fn build_index_page(page_num: u32, space_id: u32, lsn: u64, prev: u32, next: u32
     BigEndian::write_u16(&mut page[ph + PAGE_N_DIR_SLOTS..], 2);
     BigEndian::write_u16(&mut page[ph + PAGE_HEAP_TOP..], 200);
     BigEndian::write_u16(&mut page[ph + PAGE_N_HEAP..], 0x8002); // compact
-    BigEndian::write_u16(&mut page[ph + PAGE_N_RECS..], 1);
+    BigEndian::write_u16(&mut page[ph + PAGE_N_RECS..], 0);
     BigEndian::write_u16(&mut page[ph + PAGE_LEVEL..], 0); // leaf
     BigEndian::write_u64(&mut page[ph + PAGE_INDEX_ID..], 100);
 
+    // Empty record list: infimum -> supremum, each owning its own slot
+    page[PAGE_NEW_INFIMUM - 5] = 1;
+    BigEndian::write_u16(&mut page[PAGE_NEW_INFIMUM - 4..], 2);
+    BigEndian::write_i16(
+        &mut page[PAGE_NEW_INFIMUM - 2..],
+        (PAGE_NEW_SUPREMUM - PAGE_NEW_INFIMUM) as i16,
+    );
+    page[PAGE_NEW_SUPREMUM - 5] = 1;
+    BigEndian::write_u16(&mut page[PAGE_NEW_SUPREMUM - 4..], (1 << 3) | 3);
+    let dir = PS - SIZE_FIL_TRAILER;
+    BigEndian::write_u16(&mut page[dir - 2..], PAGE_NEW_INFIMUM as u16);
+    BigEndian::write_u16(&mut page[dir - 4..], PAGE_NEW_SUPREMUM as u16);
+
     // FIL trailer
     let trailer = PS - SIZE_FIL_TRAILER;
     BigEndian::write_u32(&mut page[trailer + 4..], (lsn & 0xFFFFFFFF) as u32);
@@ -84,6 +103,8 @@ fn test_verify_passes_valid_tablespace() {
             redo: None,
             chain: vec![],
             backup_meta: None,
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -125,6 +146,8 @@ fn test_verify_fails_wrong_page_number() {
             redo: None,
             chain: vec![],
             backup_meta: None,
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -164,6 +187,8 @@ fn test_verify_fails_mixed_space_ids() {
             redo: None,
             chain: vec![],
             backup_meta: None,
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -202,6 +227,8 @@ fn test_verify_fails_chain_out_of_bounds() {
             redo: None,
             chain: vec![],
             backup_meta: None,
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -253,6 +280,8 @@ fn test_verify_fails_trailer_lsn_mismatch() {
             redo: None,
             chain: vec![],
             backup_meta: None,
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -288,6 +317,8 @@ fn test_verify_json_output_valid_tablespace() {
             redo: None,
             chain: vec![],
             backup_meta: None,
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -336,6 +367,8 @@ fn test_verify_json_output_with_failures() {
             redo: None,
             chain: vec![],
             backup_meta: None,
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -385,6 +418,8 @@ fn test_verify_passes_with_empty_pages() {
             redo: None,
             chain: vec![],
             backup_meta: None,
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -419,6 +454,8 @@ fn test_verify_fails_prev_pointer_out_of_bounds() {
             redo: None,
             chain: vec![],
             backup_meta: None,
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -459,6 +496,8 @@ fn test_chain_contiguous_passes() {
                 file_b.path().to_str().unwrap().to_string(),
             ],
             backup_meta: None,
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -493,6 +532,8 @@ fn test_chain_json_output() {
                 file_b.path().to_str().unwrap().to_string(),
             ],
             backup_meta: None,
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -531,6 +572,8 @@ fn test_chain_mixed_space_ids_fails() {
                 file_b.path().to_str().unwrap().to_string(),
             ],
             backup_meta: None,
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -561,6 +604,8 @@ fn test_chain_requires_two_files() {
             redo: None,
             chain: vec![file_a.path().to_str().unwrap().to_string()],
             backup_meta: None,
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -600,6 +645,8 @@ fn test_verify_backup_meta_within_window() {
             redo: None,
             chain: vec![],
             backup_meta: Some(ckpt.path().to_str().unwrap().to_string()),
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -649,6 +696,8 @@ fn test_verify_backup_meta_pages_outside_window() {
             redo: None,
             chain: vec![],
             backup_meta: Some(ckpt.path().to_str().unwrap().to_string()),
+            spatial: false,
+            indexes: false,
         },
         &mut output,
     );
@@ -673,3 +722,588 @@ fn test_verify_backup_meta_pages_outside_window() {
     );
     assert_eq!(page2_issue.unwrap()["lsn"], 9000);
 }
+
+// ── Test: used pages with no owning segment fail LeakedPages ─────────
+
+#[test]
+fn test_verify_reports_leaked_pages() {
+    let mut page0 = build_fsp_hdr_page(42, 4);
+    // Extent 0 is FREE_FRAG with pages 0-3 in use, but no INODE pages exist
+    let x = XDES_ARR_OFFSET;
+    BigEndian::write_u32(&mut page0[x + XDES_STATE..], 2);
+    for b in &mut page0[x + XDES_BITMAP..x + XDES_SIZE] {
+        *b = 0xFF;
+    }
+    page0[x + XDES_BITMAP] = 0xAA;
+    recalculate_checksum(&mut page0, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+    let page1 = build_index_page(1, 42, 2000, FIL_NULL, FIL_NULL);
+    let page2 = build_index_page(2, 42, 3000, FIL_NULL, FIL_NULL);
+    let page3 = build_index_page(3, 42, 4000, FIL_NULL, FIL_NULL);
+    let all: Vec<u8> = [page0, page1, page2, page3].concat();
+
+    let report = verify_tablespace(&all, PAGE_SIZE, 42, "t.ibd", &VerifyConfig::default());
+    assert!(!report.passed);
+    let leaked: Vec<u64> = report
+        .findings
+        .iter()
+        .filter(|f| f.kind == VerifyCheckKind::LeakedPages)
+        .map(|f| f.page_number)
+        .collect();
+    // Page 0 (FSP_HDR) and page 1 (change buffer bitmap) belong to the space
+    assert_eq!(leaked, vec![2, 3]);
+    let double = report
+        .summary
+        .iter()
+        .find(|s| s.kind == VerifyCheckKind::DoubleOwnedPages)
+        .unwrap();
+    assert!(double.passed);
+    assert_eq!(double.pages_checked, 4);
+}
+
+// ── Test: page directory and in-page record order ────────────────────
+
+/// Link 4-byte INT records holding `keys` into the empty record list of a
+/// page from `build_index_page`; the supremum slot owns all of them.
+fn add_int_records(page: &mut [u8], keys: &[i32]) {
+    let mut prev = PAGE_NEW_INFIMUM;
+    let mut pos = PAGE_NEW_SUPREMUM + 8;
+    for (i, key) in keys.iter().enumerate() {
+        let origin = pos + REC_N_NEW_EXTRA_BYTES;
+        BigEndian::write_u16(&mut page[origin - 4..], (i as u16 + 2) << 3);
+        BigEndian::write_u32(&mut page[origin..], (*key as u32) ^ 0x8000_0000);
+        BigEndian::write_i16(&mut page[prev - 2..], (origin - prev) as i16);
+        prev = origin;
+        pos = origin + 4;
+    }
+    BigEndian::write_i16(
+        &mut page[prev - 2..],
+        PAGE_NEW_SUPREMUM as i16 - prev as i16,
+    );
+    page[PAGE_NEW_SUPREMUM - 5] = keys.len() as u8 + 1;
+    BigEndian::write_u16(&mut page[FIL_PAGE_DATA + PAGE_HEAP_TOP..], pos as u16);
+    BigEndian::write_u16(&mut page[FIL_PAGE_DATA + PAGE_N_RECS..], keys.len() as u16);
+    recalculate_checksum(page, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+}
+
+#[test]
+fn test_verify_reports_page_directory_and_record_order() {
+    let page0 = build_fsp_hdr_page(42, 3);
+    let mut page1 = build_index_page(1, 42, 2000, FIL_NULL, FIL_NULL);
+    add_int_records(&mut page1, &[1, 2, 3]);
+    // Supremum claims to own 3 records instead of 4
+    page1[PAGE_NEW_SUPREMUM - 5] = 3;
+    let mut page2 = build_index_page(2, 42, 3000, FIL_NULL, FIL_NULL);
+    add_int_records(&mut page2, &[10, 30, 20]);
+    let all: Vec<u8> = [page0, page1, page2].concat();
+
+    let config = VerifyConfig {
+        index_layouts: vec![idb::innodb::export::IndexLayout {
+            name: "PRIMARY".to_string(),
+            index_id: 100,
+            is_clustered: true,
+            columns: vec![storage_col("id", 4, 4)],
+            descending: vec![false],
+            prefix_lengths: vec![None],
+        }],
+        ..VerifyConfig::default()
+    };
+    let report = verify_tablespace(&all, PAGE_SIZE, 42, "t.ibd", &config);
+    assert!(!report.passed);
+
+    let dir: Vec<_> = report
+        .findings
+        .iter()
+        .filter(|f| f.kind == VerifyCheckKind::PageDirectory)
+        .collect();
+    assert_eq!(dir.len(), 1);
+    assert_eq!(dir[0].page_number, 1);
+    assert_eq!(dir[0].expected.as_deref(), Some("4"));
+    assert_eq!(dir[0].actual.as_deref(), Some("3"));
+
+    let order: Vec<_> = report
+        .findings
+        .iter()
+        .filter(|f| f.kind == VerifyCheckKind::RecordOrder)
+        .collect();
+    assert_eq!(order.len(), 1);
+    assert_eq!(order[0].page_number, 2);
+    let summary = report
+        .summary
+        .iter()
+        .find(|s| s.kind == VerifyCheckKind::RecordOrder)
+        .unwrap();
+    assert_eq!(summary.pages_checked, 2);
+}
+
+// ── Test: cross-page B+Tree structure ────────────────────────────────
+
+/// Fill a level-1 page with node pointers `(key, child page)`; the first
+/// one carries the min_rec flag.
+fn add_node_pointers(page: &mut [u8], pointers: &[(i32, u32)]) {
+    let mut prev = PAGE_NEW_INFIMUM;
+    let mut pos = PAGE_NEW_SUPREMUM + 8;
+    for (i, (key, child)) in pointers.iter().enumerate() {
+        let origin = pos + REC_N_NEW_EXTRA_BYTES;
+        if i == 0 {
+            page[origin - 5] = 0x10;
+        }
+        BigEndian::write_u16(&mut page[origin - 4..], ((i as u16 + 2) << 3) | 1);
+        BigEndian::write_u32(&mut page[origin..], (*key as u32) ^ 0x8000_0000);
+        BigEndian::write_u32(&mut page[origin + 4..], *child);
+        BigEndian::write_i16(&mut page[prev - 2..], (origin - prev) as i16);
+        prev = origin;
+        pos = origin + 8;
+    }
+    BigEndian::write_i16(
+        &mut page[prev - 2..],
+        PAGE_NEW_SUPREMUM as i16 - prev as i16,
+    );
+    page[PAGE_NEW_SUPREMUM - 5] = pointers.len() as u8 + 1;
+    BigEndian::write_u16(&mut page[FIL_PAGE_DATA + PAGE_HEAP_TOP..], pos as u16);
+    BigEndian::write_u16(
+        &mut page[FIL_PAGE_DATA + PAGE_N_RECS..],
+        pointers.len() as u16,
+    );
+    BigEndian::write_u16(&mut page[FIL_PAGE_DATA + PAGE_LEVEL..], 1);
+    recalculate_checksum(page, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+}
+
+/// Root page 3 over leaves 1 and 2, plus any extra pages.
+fn build_two_level_tree(right_leaf_keys: &[i32], extra: &[Vec<u8>]) -> Vec<u8> {
+    let total = 4 + extra.len() as u32;
+    let page0 = build_fsp_hdr_page(42, total);
+    let mut page1 = build_index_page(1, 42, 2000, FIL_NULL, 2);
+    add_int_records(&mut page1, &[1, 2, 3]);
+    let mut page2 = build_index_page(2, 42, 2000, 1, FIL_NULL);
+    add_int_records(&mut page2, right_leaf_keys);
+    let mut page3 = build_index_page(3, 42, 2000, FIL_NULL, FIL_NULL);
+    add_node_pointers(&mut page3, &[(1, 1), (10, 2)]);
+    let mut all: Vec<u8> = [page0, page1, page2, page3].concat();
+    for page in extra {
+        all.extend_from_slice(page);
+    }
+    all
+}
+
+fn int_primary_config() -> VerifyConfig {
+    VerifyConfig {
+        index_layouts: vec![idb::innodb::export::IndexLayout {
+            name: "PRIMARY".to_string(),
+            index_id: 100,
+            is_clustered: true,
+            columns: vec![storage_col("id", 4, 4)],
+            descending: vec![false],
+            prefix_lengths: vec![None],
+        }],
+        ..VerifyConfig::default()
+    }
+}
+
+const TREE_CHECKS: [VerifyCheckKind; 5] = [
+    VerifyCheckKind::SiblingLinks,
+    VerifyCheckKind::RecordCount,
+    VerifyCheckKind::PageReachability,
+    VerifyCheckKind::NodePointerKeys,
+    VerifyCheckKind::SiblingKeyOrder,
+];
+
+#[test]
+fn test_verify_btree_structure_consistent_tree() {
+    let all = build_two_level_tree(&[10, 20], &[]);
+    let report = verify_tablespace(&all, PAGE_SIZE, 42, "t.ibd", &int_primary_config());
+
+    for kind in TREE_CHECKS {
+        let summary = report.summary.iter().find(|s| s.kind == kind).unwrap();
+        assert!(summary.passed, "{:?} failed: {:?}", kind, report.findings);
+    }
+    let pages = |kind| {
+        report
+            .summary
+            .iter()
+            .find(|s| s.kind == kind)
+            .unwrap()
+            .pages_checked
+    };
+    assert_eq!(pages(VerifyCheckKind::SiblingLinks), 3);
+    assert_eq!(pages(VerifyCheckKind::PageReachability), 3);
+    assert_eq!(pages(VerifyCheckKind::NodePointerKeys), 1);
+    assert_eq!(pages(VerifyCheckKind::SiblingKeyOrder), 1);
+}
+
+#[test]
+fn test_verify_btree_structure_reports_cross_page_issues() {
+    // Page 4: orphan leaf claiming page 2 as its left sibling, with a wrong
+    // PAGE_N_RECS
+    let mut page4 = build_index_page(4, 42, 2000, 2, FIL_NULL);
+    add_int_records(&mut page4, &[50]);
+    BigEndian::write_u16(&mut page4[FIL_PAGE_DATA + PAGE_N_RECS..], 2);
+    recalculate_checksum(&mut page4, PAGE_SIZE, ChecksumAlgorithm::Crc32c);
+    // Right leaf starts at 3: overlaps the left leaf and sorts below its
+    // node pointer key 10
+    let all = build_two_level_tree(&[3, 20], &[page4]);
+    let report = verify_tablespace(&all, PAGE_SIZE, 42, "t.ibd", &int_primary_config());
+    assert!(!report.passed);
+
+    let pages_with = |kind| -> Vec<u64> {
+        report
+            .findings
+            .iter()
+            .filter(|f| f.kind == kind)
+            .map(|f| f.page_number)
+            .collect()
+    };
+    assert_eq!(pages_with(VerifyCheckKind::SiblingKeyOrder), vec![1]);
+    assert_eq!(pages_with(VerifyCheckKind::NodePointerKeys), vec![3]);
+    assert_eq!(pages_with(VerifyCheckKind::SiblingLinks), vec![4]);
+    assert_eq!(pages_with(VerifyCheckKind::PageReachability), vec![4]);
+    assert_eq!(pages_with(VerifyCheckKind::RecordCount), vec![4]);
+
+    let count = report
+        .findings
+        .iter()
+        .find(|f| f.kind == VerifyCheckKind::RecordCount)
+        .unwrap();
+    assert_eq!(count.expected.as_deref(), Some("1"));
+    assert_eq!(count.actual.as_deref(), Some("2"));
+
+    let disabled = VerifyConfig {
+        check_btree_structure: false,
+        ..int_primary_config()
+    };
+    let report = verify_tablespace(&all, PAGE_SIZE, 42, "t.ibd", &disabled);
+    assert!(report
+        .summary
+        .iter()
+        .all(|s| !TREE_CHECKS.contains(&s.kind)));
+}
+
+// ── Test: spatial index MBR cross-check ──────────────────────────────
+
+fn storage_col(name: &str, dd_type: u64, fixed_len: usize) -> ColumnStorageInfo {
+    ColumnStorageInfo {
+        name: name.to_string(),
+        dd_type,
+        column_type: String::new(),
+        is_nullable: false,
+        is_unsigned: false,
+        fixed_len,
+        is_variable: fixed_len == 0,
+        max_len: if fixed_len == 0 { 65535 } else { fixed_len },
+        version_added: 0,
+        version_dropped: 0,
+        instant_default: None,
+        charset_max_bytes: 1,
+        collation_id: 0,
+        datetime_precision: 0,
+        is_system_column: dd_type == 0,
+        elements: Vec::new(),
+        numeric_precision: 0,
+        numeric_scale: 0,
+    }
+}
+
+/// Build a leaf page of compact records: `(extra bytes, record data)`.
+fn build_leaf_page(
+    page_num: u32,
+    page_type: u16,
+    index_id: u64,
+    records: &[(Vec<u8>, Vec<u8>)],
+) -> Vec<u8> {
+    let mut page = vec![0u8; PS];
+    BigEndian::write_u32(&mut page[FIL_PAGE_OFFSET..], page_num);
+    BigEndian::write_u32(&mut page[FIL_PAGE_PREV..], FIL_NULL);
+    BigEndian::write_u32(&mut page[FIL_PAGE_NEXT..], FIL_NULL);
+    BigEndian::write_u16(&mut page[FIL_PAGE_TYPE..], page_type);
+    BigEndian::write_u32(&mut page[FIL_PAGE_SPACE_ID..], 42);
+    let ph = FIL_PAGE_DATA;
+    BigEndian::write_u16(
+        &mut page[ph + PAGE_N_HEAP..],
+        0x8000 | (records.len() as u16 + 2),
+    );
+    BigEndian::write_u16(&mut page[ph + PAGE_N_RECS..], records.len() as u16);
+    BigEndian::write_u64(&mut page[ph + PAGE_INDEX_ID..], index_id);
+
+    page[PAGE_NEW_INFIMUM - REC_N_NEW_EXTRA_BYTES + 2] = 0x02;
+    page[PAGE_NEW_SUPREMUM - REC_N_NEW_EXTRA_BYTES + 2] = 0x0B;
+    let mut prev = PAGE_NEW_INFIMUM;
+    let mut heap = PAGE_NEW_SUPREMUM + 8;
+    for (i, (extra, data)) in records.iter().enumerate() {
+        let origin = heap + extra.len() + REC_N_NEW_EXTRA_BYTES;
+        page[heap..heap + extra.len()].copy_from_slice(extra);
+        BigEndian::write_u16(&mut page[origin - 4..], ((i as u16) + 2) << 3);
+        page[origin..origin + data.len()].copy_from_slice(data);
+        BigEndian::write_i16(&mut page[prev - 2..], origin as i16 - prev as i16);
+        prev = origin;
+        heap = origin + data.len();
+    }
+    BigEndian::write_i16(
+        &mut page[prev - 2..],
+        PAGE_NEW_SUPREMUM as i16 - prev as i16,
+    );
+    page
+}
+
+/// Clustered record: id INT, DB_TRX_ID, DB_ROLL_PTR, g POINT.
+fn clustered_point_row(id: u32, x: f64, y: f64) -> (Vec<u8>, Vec<u8>) {
+    let mut data = (id | 0x8000_0000).to_be_bytes().to_vec();
+    data.extend_from_slice(&[0u8; 13]);
+    data.extend_from_slice(&[0, 0, 0, 0, 1]); // SRID 0, little-endian WKB
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(&x.to_le_bytes());
+    data.extend_from_slice(&y.to_le_bytes());
+    (vec![25], data)
+}
+
+/// Spatial index record: MBR followed by the primary key.
+fn spatial_entry(id: u32, mbr: [f64; 4]) -> (Vec<u8>, Vec<u8>) {
+    let mut data = vec![0u8; 32];
+    for (i, v) in mbr.iter().enumerate() {
+        BigEndian::write_f64(&mut data[i * 8..], *v);
+    }
+    data.extend_from_slice(&(id | 0x8000_0000).to_be_bytes());
+    (Vec::new(), data)
+}
+
+#[test]
+fn test_check_spatial_mbrs() {
+    let page0 = build_fsp_hdr_page(42, 3);
+    let page1 = build_leaf_page(
+        1,
+        17855,
+        100,
+        &[
+            clustered_point_row(1, 1.0, 1.0),
+            clustered_point_row(2, 5.0, 5.0),
+            clustered_point_row(3, 0.5, 0.5),
+        ],
+    );
+    let page2 = build_leaf_page(
+        2,
+        17854,
+        200,
+        &[
+            spatial_entry(1, [0.0, 0.0, 2.0, 2.0]),
+            spatial_entry(2, [0.0, 0.0, 2.0, 2.0]),
+        ],
+    );
+    let mut data = page0;
+    data.extend_from_slice(&page1);
+    data.extend_from_slice(&page2);
+    let mut ts = Tablespace::from_bytes(data).unwrap();
+
+    let columns = vec![
+        storage_col("id", 4, 4),
+        storage_col("DB_TRX_ID", 0, 6),
+        storage_col("DB_ROLL_PTR", 0, 7),
+        storage_col("g", 30, 0),
+    ];
+    let indexes = vec![SpatialIndexInfo {
+        name: "g_idx".to_string(),
+        index_id: 200,
+        column: "g".to_string(),
+    }];
+
+    let result = check_spatial_mbrs(&mut ts, &columns, 100, indexes).unwrap();
+    assert_eq!(result.rows_checked, 3);
+    assert!(!result.passed);
+    assert_eq!(result.issues.len(), 2);
+    assert_eq!(result.issues[0].primary_key, "2");
+    assert_eq!(
+        result.issues[0].message,
+        "geometry lies outside spatial index MBR"
+    );
+    assert_eq!(result.issues[1].primary_key, "3");
+    assert!(result.issues[1].index_mbr.is_none());
+}
+
+// ── Test: secondary index consistency ────────────────────────────────
+
+/// Clustered record: id INT, DB_TRX_ID, DB_ROLL_PTR, b INT.
+fn clustered_int_row(id: u32, b: u32) -> (Vec<u8>, Vec<u8>) {
+    let mut data = (id | 0x8000_0000).to_be_bytes().to_vec();
+    data.extend_from_slice(&[0u8; 13]);
+    data.extend_from_slice(&(b | 0x8000_0000).to_be_bytes());
+    (Vec::new(), data)
+}
+
+/// Secondary index record on (b): b INT, id INT.
+fn secondary_int_entry(b: u32, id: u32) -> (Vec<u8>, Vec<u8>) {
+    let mut data = (b | 0x8000_0000).to_be_bytes().to_vec();
+    data.extend_from_slice(&(id | 0x8000_0000).to_be_bytes());
+    (Vec::new(), data)
+}
+
+#[test]
+fn test_check_secondary_indexes() {
+    let page0 = build_fsp_hdr_page(42, 3);
+    let page1 = build_leaf_page(
+        1,
+        17855,
+        100,
+        &[
+            clustered_int_row(1, 10),
+            clustered_int_row(2, 20),
+            clustered_int_row(3, 30),
+        ],
+    );
+    let page2 = build_leaf_page(
+        2,
+        17855,
+        200,
+        &[
+            secondary_int_entry(10, 1),
+            secondary_int_entry(10, 1),
+            secondary_int_entry(21, 2),
+            secondary_int_entry(40, 4),
+        ],
+    );
+    let mut data = page0;
+    data.extend_from_slice(&page1);
+    data.extend_from_slice(&page2);
+    let mut ts = Tablespace::from_bytes(data).unwrap();
+
+    let layouts = vec![
+        idb::innodb::export::IndexLayout {
+            name: "PRIMARY".to_string(),
+            index_id: 100,
+            is_clustered: true,
+            columns: vec![
+                storage_col("id", 4, 4),
+                storage_col("DB_TRX_ID", 0, 6),
+                storage_col("DB_ROLL_PTR", 0, 7),
+                storage_col("b", 4, 4),
+            ],
+            descending: vec![false; 4],
+            prefix_lengths: vec![None; 4],
+        },
+        idb::innodb::export::IndexLayout {
+            name: "b_idx".to_string(),
+            index_id: 200,
+            is_clustered: false,
+            columns: vec![storage_col("b", 4, 4), storage_col("id", 4, 4)],
+            descending: vec![false; 2],
+            prefix_lengths: vec![None; 2],
+        },
+    ];
+
+    let result = check_secondary_indexes(&mut ts, &layouts, 1).unwrap();
+    assert!(!result.passed);
+    assert_eq!(result.rows_checked, 3);
+    assert_eq!(result.indexes.len(), 1);
+    let idx = &result.indexes[0];
+    assert_eq!(idx.entries_checked, 4);
+    assert_eq!(idx.orphan_entries, 2);
+    assert_eq!(idx.missing_entries, 2);
+    assert_eq!(idx.duplicate_entries, 1);
+
+    let issues: Vec<_> = result
+        .issues
+        .iter()
+        .map(|i| (i.kind, i.page_number, i.primary_key.as_str()))
+        .collect();
+    assert_eq!(
+        issues,
+        vec![
+            (IndexEntryIssueKind::Orphan, 2, "2"),
+            (IndexEntryIssueKind::Orphan, 2, "4"),
+            (IndexEntryIssueKind::Duplicate, 1, "1"),
+            (IndexEntryIssueKind::Missing, 1, "2"),
+            (IndexEntryIssueKind::Missing, 1, "3"),
+        ]
+    );
+    assert_eq!(
+        result.issues[0].message,
+        "column b is 21 in the index but 20 in the clustered row"
+    );
+}
+
+/// Clustered record: id INT, DB_TRX_ID, DB_ROLL_PTR, s VARCHAR.
+fn clustered_str_row(id: u32, s: &str) -> (Vec<u8>, Vec<u8>) {
+    let mut data = (id | 0x8000_0000).to_be_bytes().to_vec();
+    data.extend_from_slice(&[0u8; 13]);
+    data.extend_from_slice(s.as_bytes());
+    (vec![s.len() as u8], data)
+}
+
+/// Secondary index record on (s): s VARCHAR, id INT.
+fn secondary_str_entry(s: &str, id: u32) -> (Vec<u8>, Vec<u8>) {
+    let mut data = s.as_bytes().to_vec();
+    data.extend_from_slice(&(id | 0x8000_0000).to_be_bytes());
+    (vec![s.len() as u8], data)
+}
+
+fn str_index_layouts(prefix_len: Option<usize>) -> Vec<idb::innodb::export::IndexLayout> {
+    vec![
+        idb::innodb::export::IndexLayout {
+            name: "PRIMARY".to_string(),
+            index_id: 100,
+            is_clustered: true,
+            columns: vec![
+                storage_col("id", 4, 4),
+                storage_col("DB_TRX_ID", 0, 6),
+                storage_col("DB_ROLL_PTR", 0, 7),
+                storage_col("s", 16, 0),
+            ],
+            descending: vec![false; 4],
+            prefix_lengths: vec![None; 4],
+        },
+        idb::innodb::export::IndexLayout {
+            name: "s_idx".to_string(),
+            index_id: 200,
+            is_clustered: false,
+            columns: vec![storage_col("s", 16, 0), storage_col("id", 4, 4)],
+            descending: vec![false; 2],
+            prefix_lengths: vec![prefix_len, None],
+        },
+    ]
+}
+
+#[test]
+fn test_check_secondary_indexes_prefix_key_parts() {
+    let page0 = build_fsp_hdr_page(42, 3);
+    let page1 = build_leaf_page(1, 17855, 100, &[clustered_str_row(1, "abc")]);
+    let page2 = build_leaf_page(2, 17855, 200, &[secondary_str_entry("ab", 1)]);
+    let mut data = page0;
+    data.extend_from_slice(&page1);
+    data.extend_from_slice(&page2);
+
+    // A truncated value only matches a column prefix key part
+    let mut ts = Tablespace::from_bytes(data.clone()).unwrap();
+    let result = check_secondary_indexes(&mut ts, &str_index_layouts(None), 1).unwrap();
+    assert!(!result.passed);
+    assert_eq!(result.indexes[0].orphan_entries, 1);
+    assert_eq!(result.indexes[0].missing_entries, 1);
+    assert_eq!(
+        result.issues[0].message,
+        "column s is ab in the index but abc in the clustered row"
+    );
+
+    let mut ts = Tablespace::from_bytes(data).unwrap();
+    let result = check_secondary_indexes(&mut ts, &str_index_layouts(Some(2)), 1).unwrap();
+    assert!(result.passed);
+    assert!(result.issues.is_empty());
+}
+
+#[test]
+fn test_check_secondary_indexes_falls_back_when_search_misses() {
+    // The search from page 1 never reaches row 2 on page 3
+    let page0 = build_fsp_hdr_page(42, 4);
+    let page1 = build_leaf_page(1, 17855, 100, &[clustered_str_row(1, "a")]);
+    let page2 = build_leaf_page(
+        2,
+        17855,
+        200,
+        &[secondary_str_entry("a", 1), secondary_str_entry("b", 2)],
+    );
+    let page3 = build_leaf_page(3, 17855, 100, &[clustered_str_row(2, "b")]);
+    let mut data = page0;
+    data.extend_from_slice(&page1);
+    data.extend_from_slice(&page2);
+    data.extend_from_slice(&page3);
+    let mut ts = Tablespace::from_bytes(data).unwrap();
+
+    let result = check_secondary_indexes(&mut ts, &str_index_layouts(None), 1).unwrap();
+    assert_eq!(result.rows_checked, 2);
+    assert_eq!(result.indexes[0].entries_checked, 2);
+    assert!(result.passed, "issues: {:?}", result.issues);
+}
@@ -35,7 +35,7 @@ fn build_fsp_hdr_page(space_id: u32, total_pages: u32) -> Vec<u8> {
     BigEndian::write_u32(&mut page[fsp + FSP_FREE_LIMIT..], total_pages);
     BigEndian::write_u32(&mut page[fsp + FSP_SPACE_FLAGS..], 0);
     let trailer = PS - SIZE_FIL_TRAILER;
-    BigEndian::write_u32(&mut page[trailer + 4..], 1000 & 0xFFFFFFFF);
+    BigEndian::write_u32(&mut page[trailer + 4..], 1000);
     write_crc32c_checksum(&mut page);
     page
 }